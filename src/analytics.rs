@@ -0,0 +1,266 @@
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, TopContractsParams},
+};
+#[cfg(feature = "beacon")]
+use crate::{database::AggregateStats, rewards::RewardsService};
+use anyhow::Result;
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::Arc,
+};
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Aggregated bucket lengths: (period name, seconds)
+pub const AGGREGATE_PERIODS: [(&str, i64); 2] = [("day", 86_400), ("hour", 3_600)];
+
+/// Daily leaderboards maintained with the day buckets
+pub const LEADERBOARD_METRICS: [&str; 3] = ["fee-payers", "gas-guzzlers", "transfers"];
+
+/// Entries kept per leaderboard
+pub const LEADERBOARD_SIZE: i64 = 100;
+
+/// Missing buckets backfilled per period and stale hours refreshed per run, so a fresh
+/// database catches up gradually
+const MAX_BACKFILL_PER_RUN: usize = 200;
+
+/// Contract interaction rollup hours kept behind the newest indexed block
+const CONTRACT_INTERACTION_RETENTION_SECONDS: i64 = TopContractsParams::MAX_WINDOW_SECONDS;
+
+/// Consensus issuance per unit of proposer reward: since Altair proposers earn 8 of the 64
+/// reward weight units, attesters and sync committees the other 56
+#[cfg(feature = "beacon")]
+const ISSUANCE_PER_PROPOSER_REWARD: f64 = 8.0;
+
+/// Job maintaining the aggregate_stats table behind the chart endpoints and the daily
+/// leaderboards, and trimming the contract interaction rollup.
+///
+/// Each run fills buckets that were never aggregated, recomputes the two newest ones per
+/// period, which may still receive blocks, and those holding blocks written since the last
+/// run by gap repair, reindexing, backfills or reorgs. With block rewards available, day buckets also
+/// get an issuance estimate from the proposer rewards of a sample of their blocks.
+pub struct AnalyticsService {
+    db: Arc<DatabaseService>,
+    config: AppConfig,
+    #[cfg(feature = "beacon")]
+    rewards: Option<Arc<RewardsService>>,
+}
+
+impl AnalyticsService {
+    /// Create a new analytics service
+    pub fn new(db: Arc<DatabaseService>, config: AppConfig) -> Self {
+        Self {
+            db,
+            config,
+            #[cfg(feature = "beacon")]
+            rewards: None,
+        }
+    }
+
+    /// Also estimate daily issuance from block rewards
+    #[cfg(feature = "beacon")]
+    pub fn with_rewards(mut self, rewards: Arc<RewardsService>) -> Self {
+        if self.config.issuance_sample_blocks > 0 {
+            self.rewards = Some(rewards);
+        }
+        self
+    }
+
+    /// Start updating aggregates in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Analytics job starting");
+            let mut interval = time::interval(Duration::from_secs(
+                service.config.analytics_interval_seconds,
+            ));
+
+            loop {
+                interval.tick().await;
+                let stale = match service
+                    .db
+                    .get_stale_aggregate_hours(MAX_BACKFILL_PER_RUN as i64)
+                    .await
+                {
+                    Ok(stale) => stale,
+                    Err(e) => {
+                        warn!("Failed to read stale aggregate hours: {:#}", e);
+                        Vec::new()
+                    }
+                };
+
+                let mut refreshed = true;
+                for (period, seconds) in AGGREGATE_PERIODS {
+                    if let Err(e) = service.update(period, seconds, &stale).await {
+                        warn!("Failed to update {} aggregates: {:#}", period, e);
+                        refreshed = false;
+                    }
+                }
+                // Stale hours are kept until every period recomputed them
+                if refreshed
+                    && let Some((last_id, _)) = stale.last()
+                    && let Err(e) = service.db.clear_stale_aggregate_hours(*last_id).await
+                {
+                    warn!("Failed to clear stale aggregate hours: {:#}", e);
+                }
+                if let Err(e) = service.prune_contract_interactions().await {
+                    warn!("Failed to prune contract interactions: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Aggregate the buckets of one period that are missing, may still change or contain
+    /// one of the `stale` (id, hour start) hours
+    async fn update(&self, period: &str, seconds: i64, stale: &[(i64, i64)]) -> Result<()> {
+        let Some((oldest, newest)) = self.db.get_block_timestamp_range().await? else {
+            return Ok(());
+        };
+
+        let first = oldest.div_euclid(seconds) * seconds;
+        let last = newest.div_euclid(seconds) * seconds;
+        let mut existing: HashSet<i64> = self
+            .db
+            .get_aggregate_bucket_starts(period)
+            .await?
+            .into_iter()
+            .collect();
+        if period == "day" {
+            // Days aggregated before leaderboards existed are ranked on the next passes
+            let ranked: HashSet<i64> = self
+                .db
+                .get_leaderboard_days(LEADERBOARD_METRICS.len())
+                .await?
+                .into_iter()
+                .collect();
+            existing.retain(|start| ranked.contains(start));
+
+            // Likewise for days aggregated before issuance was estimated
+            #[cfg(feature = "beacon")]
+            if self.rewards.is_some() {
+                let estimated: HashSet<i64> = self
+                    .db
+                    .get_issuance_bucket_starts(period)
+                    .await?
+                    .into_iter()
+                    .collect();
+                existing.retain(|start| estimated.contains(start));
+            }
+        }
+
+        let mut buckets: BTreeSet<i64> = (first..last - seconds)
+            .step_by(seconds as usize)
+            .filter(|start| !existing.contains(start))
+            .take(MAX_BACKFILL_PER_RUN)
+            .collect();
+        buckets.extend(
+            [last - seconds, last]
+                .into_iter()
+                .filter(|start| *start >= first),
+        );
+        buckets.extend(
+            stale
+                .iter()
+                .map(|(_, hour)| hour.div_euclid(seconds) * seconds)
+                .filter(|start| (first..=last).contains(start)),
+        );
+
+        for start in &buckets {
+            let stats = self
+                .db
+                .compute_aggregate_stats(period, *start, start + seconds)
+                .await?;
+            #[cfg(feature = "beacon")]
+            let stats = match period {
+                "day" => self.estimate_issuance(stats, start + seconds).await?,
+                _ => stats,
+            };
+            self.db.upsert_aggregate_stats(&stats).await?;
+
+            if period == "day" {
+                self.update_leaderboards(*start, start + seconds).await?;
+            }
+        }
+
+        debug!("Updated {} {} aggregate buckets", buckets.len(), period);
+        Ok(())
+    }
+
+    /// Estimate the consensus issuance of a day bucket ending at `end` from the proposer
+    /// rewards of a sample of its blocks. The bucket is left unsampled when the beacon node
+    /// fails, so a later run retries it.
+    #[cfg(feature = "beacon")]
+    async fn estimate_issuance(
+        &self,
+        mut stats: AggregateStats,
+        end: i64,
+    ) -> Result<AggregateStats> {
+        let Some(rewards) = &self.rewards else {
+            return Ok(stats);
+        };
+
+        let blocks = self
+            .db
+            .get_block_sample(
+                stats.bucket_start,
+                end,
+                self.config.issuance_sample_blocks as i64,
+            )
+            .await?;
+        let mut proposer_rewards_gwei = Vec::new();
+        for block in &blocks {
+            match rewards.block_rewards(block).await {
+                Ok(Some(reward)) => proposer_rewards_gwei.push(
+                    reward.attestations.parse::<f64>().unwrap_or(0.0)
+                        + reward.sync_aggregate.parse::<f64>().unwrap_or(0.0),
+                ),
+                // Pre-merge, or no longer kept by the beacon node
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(
+                        "Failed to sample rewards of block {} for issuance: {:#}",
+                        block.number, e
+                    );
+                    return Ok(stats);
+                }
+            }
+        }
+
+        stats.issuance_samples = Some(proposer_rewards_gwei.len() as i64);
+        if !proposer_rewards_gwei.is_empty() {
+            let average_gwei =
+                proposer_rewards_gwei.iter().sum::<f64>() / proposer_rewards_gwei.len() as f64;
+            stats.issuance_eth =
+                Some(average_gwei * ISSUANCE_PER_PROPOSER_REWARD * stats.block_count as f64 / 1e9);
+        }
+
+        Ok(stats)
+    }
+
+    /// Drop contract interaction hours no ranking window reaches anymore
+    async fn prune_contract_interactions(&self) -> Result<()> {
+        let Some((_, newest)) = self.db.get_latest_block_timestamp().await? else {
+            return Ok(());
+        };
+        let deleted = self
+            .db
+            .prune_contract_interactions(newest - CONTRACT_INTERACTION_RETENTION_SECONDS - 3_600)
+            .await?;
+        debug!("Pruned {} contract interaction rows", deleted);
+        Ok(())
+    }
+
+    /// Rank the transactions of the day starting at `start` for every leaderboard metric
+    async fn update_leaderboards(&self, start: i64, end: i64) -> Result<()> {
+        for metric in LEADERBOARD_METRICS {
+            let entries = self
+                .db
+                .compute_leaderboard(metric, start, end, LEADERBOARD_SIZE)
+                .await?;
+            self.db.upsert_leaderboard(metric, start, &entries).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,497 @@
+use crate::{
+    api::ApiError,
+    config::AppConfig,
+    database::{ApiKey, DatabaseService},
+};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, Request, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDate, Utc};
+use ethers::{core::rand, utils::keccak256};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// Header carrying the API key, `?api_key=` is accepted as well for SSE and browser use
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// How long key lookups are cached, bounding how late a disabled key keeps working
+const KEY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Length of a rate limit window
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Tracked anonymous clients before idle ones are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Valid keys cached, the least recently used is dropped past this
+const MAX_CACHED_KEYS: usize = 10_000;
+
+/// Unknown or disabled keys a client IP may try per rate limit window before it is refused
+/// without looking its keys up
+const MAX_INVALID_KEYS_PER_WINDOW: usize = 10;
+
+/// Client a request is accounted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Client {
+    Key(i64),
+    Anonymous(IpAddr),
+}
+
+/// Limits applying to a client
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    requests_per_minute: u32, // 0 = unlimited
+    daily_quota: Option<u32>,
+}
+
+/// Requests made by a client in the current minute window and UTC day
+#[derive(Debug)]
+struct Usage {
+    window_started: Instant,
+    window_requests: u32,
+    day: NaiveDate,
+    day_requests: u32,
+}
+
+/// Valid key cached by key hash
+#[derive(Debug)]
+struct CachedKey {
+    api_key: ApiKey,
+    fetched_at: Instant,
+    used_at: Instant, // Orders evictions
+}
+
+/// Unknown or disabled keys a client IP tried in the current window, answered without a
+/// database lookup when tried again
+#[derive(Debug)]
+struct InvalidKeys {
+    window_started: Instant,
+    key_hashes: Vec<String>,
+}
+
+/// Reason a request was refused by the rate limiter
+enum Refusal {
+    RateLimited { limit: u32, retry_after: Duration },
+    QuotaExhausted { quota: u32 },
+}
+
+/// API key authentication and per-client rate limiting.
+///
+/// Requests with a key are limited by the key's `requests_per_minute` and `daily_quota`,
+/// requests without one by client IP according to API_ANONYMOUS_REQUESTS_PER_MINUTE,
+/// unless API_AUTH_REQUIRED rejects them outright. Counters live in memory, so limits
+/// apply per API server instance. Client IPs trying more than
+/// `MAX_INVALID_KEYS_PER_WINDOW` invalid keys a minute are refused before any lookup.
+pub struct ApiAuth {
+    db: Arc<DatabaseService>,
+    config: AppConfig,
+    keys: Mutex<HashMap<String, CachedKey>>, // Valid keys by key hash
+    invalid_keys: Mutex<HashMap<IpAddr, InvalidKeys>>,
+    usage: Mutex<HashMap<Client, Usage>>,
+}
+
+/// Outcome of an API key lookup
+enum KeyLookup {
+    Valid(ApiKey),
+    Invalid,
+    Throttled { retry_after: Duration }, // Too many invalid keys from the client IP
+}
+
+impl ApiAuth {
+    /// Create the authenticator
+    pub fn new(db: Arc<DatabaseService>, config: AppConfig) -> Self {
+        Self {
+            db,
+            config,
+            keys: Mutex::new(HashMap::new()),
+            invalid_keys: Mutex::new(HashMap::new()),
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Look up an API key sent from `ip`. Valid keys are served from the cache while fresh,
+    /// invalid ones tried by `ip` in the current window are refused without a lookup.
+    async fn lookup(&self, key: &str, ip: IpAddr) -> Result<KeyLookup> {
+        let key_hash = hash_api_key(key);
+        if let Some(cached) = self.keys.lock().unwrap().get_mut(&key_hash)
+            && cached.fetched_at.elapsed() < KEY_CACHE_TTL
+        {
+            cached.used_at = Instant::now();
+            return Ok(KeyLookup::Valid(cached.api_key.clone()));
+        }
+        if let Some(invalid) = self.invalid_keys.lock().unwrap().get(&ip) {
+            let elapsed = invalid.window_started.elapsed();
+            if elapsed < RATE_LIMIT_WINDOW {
+                if invalid.key_hashes.contains(&key_hash) {
+                    return Ok(KeyLookup::Invalid);
+                }
+                if invalid.key_hashes.len() >= MAX_INVALID_KEYS_PER_WINDOW {
+                    return Ok(KeyLookup::Throttled {
+                        retry_after: RATE_LIMIT_WINDOW - elapsed,
+                    });
+                }
+            }
+        }
+
+        match self.db.get_api_key_by_hash(&key_hash).await? {
+            Some(api_key) if api_key.enabled => {
+                if let Some(id) = api_key.id {
+                    // Usage is recorded at most once per cache period
+                    if let Err(e) = self.db.touch_api_key(id).await {
+                        warn!("Failed to record API key usage: {:#}", e);
+                    }
+                }
+                self.cache_key(key_hash, &api_key);
+                Ok(KeyLookup::Valid(api_key))
+            }
+            _ => {
+                self.record_invalid_key(ip, key_hash);
+                Ok(KeyLookup::Invalid)
+            }
+        }
+    }
+
+    /// Cache a valid key, dropping the least recently used one when the cache is full
+    fn cache_key(&self, key_hash: String, api_key: &ApiKey) {
+        let mut keys = self.keys.lock().unwrap();
+        if keys.len() >= MAX_CACHED_KEYS && !keys.contains_key(&key_hash) {
+            keys.retain(|_, cached| cached.fetched_at.elapsed() < KEY_CACHE_TTL);
+            if keys.len() >= MAX_CACHED_KEYS
+                && let Some(oldest) = keys
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.used_at)
+                    .map(|(key_hash, _)| key_hash.clone())
+            {
+                keys.remove(&oldest);
+            }
+        }
+        let now = Instant::now();
+        keys.insert(
+            key_hash,
+            CachedKey {
+                api_key: api_key.clone(),
+                fetched_at: now,
+                used_at: now,
+            },
+        );
+    }
+
+    /// Remember an invalid key tried by a client IP in the current window
+    fn record_invalid_key(&self, ip: IpAddr, key_hash: String) {
+        let now = Instant::now();
+        let mut invalid_keys = self.invalid_keys.lock().unwrap();
+        if invalid_keys.len() >= MAX_TRACKED_CLIENTS && !invalid_keys.contains_key(&ip) {
+            invalid_keys.retain(|_, invalid| {
+                now.duration_since(invalid.window_started) < RATE_LIMIT_WINDOW
+            });
+            if invalid_keys.len() >= MAX_TRACKED_CLIENTS
+                && let Some(oldest) = invalid_keys
+                    .iter()
+                    .min_by_key(|(_, invalid)| invalid.window_started)
+                    .map(|(ip, _)| *ip)
+            {
+                invalid_keys.remove(&oldest);
+            }
+        }
+
+        let invalid = invalid_keys.entry(ip).or_insert(InvalidKeys {
+            window_started: now,
+            key_hashes: Vec::new(),
+        });
+        if now.duration_since(invalid.window_started) >= RATE_LIMIT_WINDOW {
+            invalid.window_started = now;
+            invalid.key_hashes.clear();
+        }
+        if invalid.key_hashes.len() < MAX_INVALID_KEYS_PER_WINDOW {
+            invalid.key_hashes.push(key_hash);
+        }
+    }
+
+    /// Count a request of a client, returning the requests left in the current window
+    fn record(&self, client: Client, limits: Limits) -> Result<Option<u32>, Refusal> {
+        let now = Instant::now();
+        let today = Utc::now().date_naive();
+        let mut usage = self.usage.lock().unwrap();
+
+        if usage.len() >= MAX_TRACKED_CLIENTS && !usage.contains_key(&client) {
+            usage.retain(|client, entry| {
+                matches!(client, Client::Key(_))
+                    || now.duration_since(entry.window_started) < RATE_LIMIT_WINDOW
+            });
+            // Keys keep their daily quota, the anonymous client seen longest ago goes
+            if usage.len() >= MAX_TRACKED_CLIENTS
+                && let Some(oldest) = usage
+                    .iter()
+                    .filter(|(client, _)| matches!(client, Client::Anonymous(_)))
+                    .min_by_key(|(_, entry)| entry.window_started)
+                    .map(|(client, _)| *client)
+            {
+                usage.remove(&oldest);
+            }
+        }
+
+        let entry = usage.entry(client).or_insert(Usage {
+            window_started: now,
+            window_requests: 0,
+            day: today,
+            day_requests: 0,
+        });
+        if now.duration_since(entry.window_started) >= RATE_LIMIT_WINDOW {
+            entry.window_started = now;
+            entry.window_requests = 0;
+        }
+        if entry.day != today {
+            entry.day = today;
+            entry.day_requests = 0;
+        }
+
+        if let Some(quota) = limits.daily_quota
+            && entry.day_requests >= quota
+        {
+            return Err(Refusal::QuotaExhausted { quota });
+        }
+        if limits.requests_per_minute > 0 && entry.window_requests >= limits.requests_per_minute {
+            return Err(Refusal::RateLimited {
+                limit: limits.requests_per_minute,
+                retry_after: RATE_LIMIT_WINDOW
+                    .saturating_sub(now.duration_since(entry.window_started)),
+            });
+        }
+
+        entry.window_requests += 1;
+        entry.day_requests += 1;
+
+        Ok((limits.requests_per_minute > 0)
+            .then(|| limits.requests_per_minute - entry.window_requests))
+    }
+}
+
+/// Middleware authenticating API requests and enforcing rate limits and quotas
+pub async fn enforce_api_access<B>(
+    State(auth): State<Arc<ApiAuth>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    // Health probes stay reachable without a key
+    if matches!(
+        request.uri().path(),
+        "/health" | "/health/live" | "/health/ready"
+    ) {
+        return next.run(request).await;
+    }
+
+    // Without connection info (e.g. in tests) all anonymous requests share a bucket
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::from([0, 0, 0, 0]));
+
+    let (client, limits) = match provided_api_key(&request) {
+        Some(key) => match auth.lookup(&key, ip).await {
+            Ok(KeyLookup::Valid(api_key)) => (
+                Client::Key(api_key.id.unwrap_or_default()),
+                Limits {
+                    requests_per_minute: api_key.requests_per_minute.max(0) as u32,
+                    daily_quota: api_key.daily_quota.map(|quota| quota.max(0) as u32),
+                },
+            ),
+            Ok(KeyLookup::Invalid) => {
+                return ApiError::Unauthorized("Invalid API key".to_string()).into_response();
+            }
+            Ok(KeyLookup::Throttled { retry_after }) => {
+                return ApiError::TooManyRequests {
+                    message: "Too many invalid API keys, retry later".to_string(),
+                    retry_after: Some(retry_after),
+                }
+                .into_response();
+            }
+            Err(e) => {
+                warn!("API key lookup failed: {:#}", e);
+                return ApiError::Unavailable("API key could not be verified".to_string())
+                    .into_response();
+            }
+        },
+        None if auth.config.api_auth_required => {
+            return ApiError::Unauthorized(
+                "API key required, pass it in the x-api-key header".to_string(),
+            )
+            .into_response();
+        }
+        None => {
+            let limits = Limits {
+                requests_per_minute: auth.config.api_anonymous_requests_per_minute,
+                daily_quota: None,
+            };
+            if limits.requests_per_minute == 0 {
+                return next.run(request).await;
+            }
+            (Client::Anonymous(ip), limits)
+        }
+    };
+
+    match auth.record(client, limits) {
+        Ok(remaining) => {
+            let mut response = next.run(request).await;
+            if let Some(remaining) = remaining {
+                let headers = response.headers_mut();
+                headers.insert("x-ratelimit-limit", limits.requests_per_minute.into());
+                headers.insert("x-ratelimit-remaining", remaining.into());
+            }
+            response
+        }
+        Err(Refusal::RateLimited { limit, retry_after }) => ApiError::TooManyRequests {
+            message: format!("Rate limit of {} requests per minute exceeded", limit),
+            retry_after: Some(retry_after),
+        }
+        .into_response(),
+        Err(Refusal::QuotaExhausted { quota }) => ApiError::TooManyRequests {
+            message: format!("Daily quota of {} requests exhausted", quota),
+            retry_after: None,
+        }
+        .into_response(),
+    }
+}
+
+/// Middleware restricting the admin endpoints to holders of ADMIN_API_KEY,
+/// passed as `Authorization: Bearer <key>`
+pub async fn require_admin_key<B>(
+    State(admin_key): State<Option<Arc<str>>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(admin_key) = admin_key else {
+        return ApiError::Forbidden(
+            "Admin API is disabled, set ADMIN_API_KEY to enable it".to_string(),
+        )
+        .into_response();
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    // Hashes are compared so the comparison time does not depend on the key
+    match provided {
+        Some(key) if hash_api_key(key) == hash_api_key(&admin_key) => next.run(request).await,
+        _ => ApiError::Unauthorized("Invalid admin API key".to_string()).into_response(),
+    }
+}
+
+/// Settings of a new API key
+#[derive(Debug, Clone)]
+pub struct NewApiKeyOptions {
+    pub name: String,
+    pub requests_per_minute: u32, // 0 = unlimited
+    pub daily_quota: Option<u32>, // None = unlimited
+}
+
+impl NewApiKeyOptions {
+    /// Build options from command line arguments (`<name>`, `--requests-per-minute`,
+    /// `--daily-quota`), defaulting the limit to API_KEY_DEFAULT_REQUESTS_PER_MINUTE
+    pub fn from_args(args: &[String], config: &AppConfig) -> Result<Self> {
+        let mut name = None;
+        let mut options = Self {
+            name: String::new(),
+            requests_per_minute: config.api_key_default_requests_per_minute,
+            daily_quota: None,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().context(format!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--requests-per-minute" => {
+                    options.requests_per_minute =
+                        value()?.parse().context("Invalid --requests-per-minute")?
+                }
+                "--daily-quota" => {
+                    options.daily_quota = Some(value()?.parse().context("Invalid --daily-quota")?)
+                }
+                other if other.starts_with("--") => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown create-api-key argument: {}",
+                        other
+                    ));
+                }
+                other => name = Some(other.to_string()),
+            }
+        }
+
+        options.name = name
+            .context("Usage: create-api-key <name> [--requests-per-minute N] [--daily-quota N]")?;
+        Ok(options)
+    }
+}
+
+/// Generate a new API key and store it, returning the key (only its hash is persisted)
+pub async fn create_api_key(db: &DatabaseService, options: &NewApiKeyOptions) -> Result<String> {
+    let key = format!("eik_{}", hex::encode(rand::random::<[u8; 24]>()));
+    db.insert_api_key(&ApiKey {
+        id: None,
+        key_hash: hash_api_key(&key),
+        name: options.name.clone(),
+        requests_per_minute: options.requests_per_minute as i64,
+        daily_quota: options.daily_quota.map(|quota| quota as i64),
+        enabled: true,
+        created_at: None,
+        last_used_at: None,
+    })
+    .await?;
+
+    Ok(key)
+}
+
+/// Hash under which an API key is stored
+fn hash_api_key(key: &str) -> String {
+    format!("0x{}", hex::encode(keccak256(key.as_bytes())))
+}
+
+/// API key from the x-api-key header or the api_key query parameter
+fn provided_api_key<B>(request: &Request<B>) -> Option<String> {
+    header_api_key(request.headers()).or_else(|| {
+        request.uri().query()?.split('&').find_map(|pair| {
+            pair.strip_prefix("api_key=")
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+        })
+    })
+}
+
+/// Path and query of a request URI with the value of `api_key` replaced, for logging
+pub fn redact_api_key(uri: &Uri) -> String {
+    let Some(query) = uri.query() else {
+        return uri.path().to_string();
+    };
+    let query: Vec<&str> = query
+        .split('&')
+        .map(|pair| {
+            if pair.starts_with("api_key=") {
+                "api_key=REDACTED"
+            } else {
+                pair
+            }
+        })
+        .collect();
+    format!("{}?{}", uri.path(), query.join("&"))
+}
+
+fn header_api_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(API_KEY_HEADER)?
+        .to_str()
+        .ok()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
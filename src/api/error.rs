@@ -0,0 +1,134 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::{fmt::Display, time::Duration};
+use tracing::error;
+
+/// Seconds clients are told to wait before retrying a request refused by a full RPC queue
+const RPC_OVERLOADED_RETRY_SECONDS: u64 = 1;
+
+/// Error answered by an API handler or middleware, as
+/// `{"error": message, "code": ..., "status": ...}` with the matching HTTP status
+#[derive(Debug)]
+pub enum ApiError {
+    /// Malformed request: missing or unparsable parameters (400)
+    BadRequest(String),
+    /// Missing or invalid API key (401)
+    Unauthorized(String),
+    /// The endpoint is disabled on this instance (403)
+    Forbidden(String),
+    /// The requested block, transaction, account or token doesn't exist (404)
+    NotFound(String),
+    /// Well-formed request that can't be served, e.g. an invalid address (422)
+    Unprocessable(String),
+    /// Rate limit or quota exceeded, with the time until the client may retry (429)
+    TooManyRequests {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Database or node failure, logged where it happened (500)
+    Internal(String),
+    /// An upstream server, e.g. an image host, failed (502)
+    BadGateway(String),
+    /// The feature is disabled or has no capacity left, retry later (503)
+    Unavailable(String),
+    /// The RPC fallback was refused because the RPC queue is full (503)
+    RpcOverloaded,
+}
+
+impl ApiError {
+    /// Internal error answered as `message`, logging the underlying error
+    pub fn internal(message: &str, e: impl Display) -> Self {
+        error!("{}: {:#}", message, e);
+        Self::Internal(message.to_string())
+    }
+
+    /// Seconds the client is told to wait before retrying, rounded up
+    pub fn retry_after_seconds(&self) -> Option<u64> {
+        match self {
+            Self::TooManyRequests { retry_after, .. } => retry_after.map(|retry_after| {
+                (retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)).max(1)
+            }),
+            Self::RpcOverloaded => Some(RPC_OVERLOADED_RETRY_SECONDS),
+            _ => None,
+        }
+    }
+
+    /// HTTP status answered
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            Self::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::RpcOverloaded => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Machine-readable error code of the envelope
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BadRequest(_) => "bad_request",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::NotFound(_) => "not_found",
+            Self::Unprocessable(_) => "unprocessable",
+            Self::TooManyRequests { .. } => "too_many_requests",
+            Self::Internal(_) => "internal",
+            Self::BadGateway(_) => "bad_gateway",
+            Self::Unavailable(_) => "unavailable",
+            Self::RpcOverloaded => "rpc_overloaded",
+        }
+    }
+
+    /// Human-readable message of the envelope
+    pub fn message(&self) -> &str {
+        match self {
+            Self::BadRequest(message)
+            | Self::Unauthorized(message)
+            | Self::Forbidden(message)
+            | Self::NotFound(message)
+            | Self::Unprocessable(message)
+            | Self::TooManyRequests { message, .. }
+            | Self::Internal(message)
+            | Self::BadGateway(message)
+            | Self::Unavailable(message) => message,
+            Self::RpcOverloaded => "RPC node is overloaded, retry later",
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message(), self.status())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let mut body = json!({
+            "error": self.message(),
+            "code": self.code(),
+            "status": status.as_u16()
+        });
+        if let Some(retry_after) = self.retry_after_seconds() {
+            body["retry_after_seconds"] = json!(retry_after);
+            return (
+                status,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(body),
+            )
+                .into_response();
+        }
+        (status, Json(body)).into_response()
+    }
+}
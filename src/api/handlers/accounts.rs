@@ -3,10 +3,18 @@ use axum::{
     Extension, Json,
 };
 use serde::Deserialize;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::sync::Arc;
 
-use crate::{database::Account, App};
+use crate::{
+    api::ApiError,
+    database::{Account, ApprovalParams, BalanceHistoryParams, PaginationParams},
+    ens::EnsService,
+    executor::RpcOverloaded,
+    App,
+};
+
+use super::{gwei_to_eth_string, is_address};
 
 #[derive(Deserialize)]
 pub struct AccountsQuery {
@@ -20,24 +28,62 @@ pub struct AccountsQuery {
 pub async fn get_account(
     Path(address): Path<String>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
+    // Accept ENS names in place of addresses
+    let (address, ens_name) = if EnsService::is_ens_name(&address) {
+        match app.ens.resolve_name(&address).await {
+            Ok(Some(resolved)) => (resolved, Some(address.to_lowercase())),
+            Ok(None) => {
+                return Err(ApiError::NotFound(format!(
+                    "ENS name {} does not resolve to an address",
+                    address
+                )));
+            }
+            Err(e) => {
+                return Err(ApiError::internal(
+                    &format!("Failed to resolve ENS name {}", address),
+                    e,
+                ));
+            }
+        }
+    } else if !is_address(&address) {
+        return Err(ApiError::Unprocessable(format!(
+            "Invalid address: {}",
+            address
+        )));
+    } else {
+        let ens_name = app.ens.primary_name(&address).await;
+        (address, ens_name)
+    };
+    let label = db.get_address_label(&address).await.ok().flatten();
+
     // Get account from DB
     if let Ok(Some(account)) = db.get_account_by_address(&address).await {
         // Determine account type based on transaction count and blockchain state
         let account_type = determine_account_type(&account, &app).await;
+        let code_hash = db
+            .get_contract_deployment(&account.address)
+            .await
+            .ok()
+            .flatten()
+            .map(|deployment| deployment.code_hash);
 
-        return Json(json!({
+        return Ok(Json(json!({
             "account": {
                 "address": account.address,
+                "ens_name": ens_name,
+                "label": label,
                 "balance": account.balance,
+                "balance_at_block": account.balance_at_block,
                 "transaction_count": account.transaction_count,
                 "account_type": account_type,
+                "code_hash": code_hash,
                 "first_seen_block": account.first_seen_block,
                 "last_seen_block": account.last_seen_block
             }
-        }));
+        })));
     }
 
     // Account not found in our DB, try getting from RPC
@@ -49,35 +95,143 @@ pub async fn get_account(
                 transaction_count: 0,
                 first_seen_block: 0,
                 last_seen_block: 0,
+                balance_at_block: None,
+                account_type: None,
             };
 
             let account_type = determine_account_type(&account, &app).await;
 
-            return Json(json!({
+            Ok(Json(json!({
                 "account": {
                     "address": account.address,
+                    "ens_name": ens_name,
+                    "label": label,
                     "balance": account.balance,
+                    "balance_at_block": account.balance_at_block,
                     "transaction_count": account.transaction_count,
                     "account_type": account_type,
                     "first_seen_block": account.first_seen_block,
                     "last_seen_block": account.last_seen_block
                 },
                 "note": "Account not yet indexed, basic info retrieved from blockchain"
-            }));
+            })))
         }
-        Err(_) => {
-            return Json(json!({
-                "error": "Account not found or invalid address"
-            }));
+        Err(e) if RpcOverloaded::is(&e) => Err(ApiError::RpcOverloaded),
+        Err(e) => Err(ApiError::internal("Failed to get account balance", e)),
+    }
+}
+
+/// Get validator withdrawals received by an address, with lifetime totals
+pub async fn get_account_withdrawals(
+    Path(address): Path<String>,
+    Query(params): Query<PaginationParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let db = &app.db;
+    let address = address.to_lowercase();
+
+    let withdrawals = db
+        .get_withdrawals_by_address(&address, params.limit(), params.offset())
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch withdrawals", e))?;
+
+    let (total, total_gwei) = db
+        .get_withdrawal_totals_by_address(&address)
+        .await
+        .unwrap_or((0, 0));
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.per_page.unwrap_or(10);
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    let has_next = current_page < total_pages;
+
+    Ok(Json(json!({
+        "address": address,
+        "withdrawals": withdrawals,
+        "totals": {
+            "count": total,
+            "amount_gwei": total_gwei.to_string(),
+            "amount_eth": gwei_to_eth_string(total_gwei)
+        },
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": has_next
         }
+    })))
+}
+
+/// Get balance snapshots of an address for balance charts, oldest first
+pub async fn get_account_balance_history(
+    Path(address): Path<String>,
+    Query(params): Query<BalanceHistoryParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let address = address.to_lowercase();
+    let interval = app.config.balance_history_interval_blocks;
+    if interval <= 0 {
+        return Err(ApiError::NotFound(
+            "Balance history is disabled".to_string(),
+        ));
     }
+
+    let snapshots = app
+        .db
+        .get_balance_history(&address, &params)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch balance history", e))?;
+
+    Ok(Json(json!({
+        "address": address,
+        "interval_blocks": interval,
+        "snapshots": snapshots
+    })))
+}
+
+/// Get the outstanding token approvals granted by an address: ERC-20 allowances and
+/// ERC-721/1155 operators, newest first
+pub async fn get_account_approvals(
+    Path(address): Path<String>,
+    Query(params): Query<ApprovalParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let address = address.to_lowercase();
+    let token_address = params.token.as_deref().map(str::to_lowercase);
+
+    let (approvals, total) = app
+        .db
+        .get_account_approvals(
+            &address,
+            token_address.as_deref(),
+            params.include_revoked.unwrap_or(false),
+            params.limit(),
+            params.offset(),
+        )
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch approvals", e))?;
+
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    Ok(Json(json!({
+        "address": address,
+        "approvals": approvals,
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": current_page < total_pages
+        }
+    })))
 }
 
 /// Get accounts with pagination and sorting
 pub async fn get_accounts(
     Query(query): Query<AccountsQuery>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
     let page = query.page.unwrap_or(1).max(1);
@@ -87,13 +241,13 @@ pub async fn get_accounts(
 
     let offset = (page - 1) * per_page;
 
-    // Build the SQL query based on sort and order
+    // Build the SQL query based on sort and order, balances by their numeric order
     let order_clause = match sort.as_str() {
-        "balance" => "balance",
+        "balance" => "balance_sort",
         "transaction_count" => "transaction_count",
         "first_seen" => "first_seen_block",
         "last_activity" => "last_seen_block",
-        _ => "balance", // default
+        _ => "balance_sort", // default
     };
 
     let order_direction = match order.as_str() {
@@ -102,7 +256,7 @@ pub async fn get_accounts(
     };
 
     let query_str = format!(
-        "SELECT address, balance, transaction_count, first_seen_block, last_seen_block 
+        "SELECT address, balance, transaction_count, first_seen_block, last_seen_block, balance_at_block, account_type
          FROM accounts 
          ORDER BY {} {} 
          LIMIT {} OFFSET {}",
@@ -112,70 +266,59 @@ pub async fn get_accounts(
         offset
     );
 
-    match sqlx::query_as::<_, Account>(&query_str)
+    let mut accounts = sqlx::query_as::<_, Account>(&query_str)
         .fetch_all(&db.pool)
         .await
-    {
-        Ok(mut accounts) => {
-            let has_next = accounts.len() > per_page as usize;
-            if has_next {
-                accounts.pop(); // Remove the extra item
-            }
-
-            // Add account_type field based on some heuristics
-            let accounts_with_type: Vec<serde_json::Value> = accounts
-                .into_iter()
-                .map(|account| {
-                    let account_type = if account.transaction_count > 0 {
-                        "eoa" // Externally Owned Account
-                    } else {
-                        "unknown"
-                    };
-
-                    json!({
-                        "address": account.address,
-                        "balance": account.balance,
-                        "transaction_count": account.transaction_count,
-                        "account_type": account_type,
-                        "first_seen": account.first_seen_block,
-                        "last_activity": account.last_seen_block
-                    })
-                })
-                .collect();
-
-            Json(json!({
-                "accounts": accounts_with_type,
-                "has_next": has_next,
-                "page": page,
-                "per_page": per_page
-            }))
-        }
-        Err(e) => Json(json!({
-            "error": format!("Failed to fetch accounts: {}", e),
-            "accounts": [],
-            "has_next": false,
-            "page": page,
-            "per_page": per_page
-        })),
+        .map_err(|e| ApiError::internal("Failed to fetch accounts", e))?;
+    let has_next = accounts.len() > per_page as usize;
+    if has_next {
+        accounts.pop(); // Remove the extra item
     }
+
+    // Add account_type field, detected at index time
+    let accounts_with_type: Vec<Value> = accounts
+        .into_iter()
+        .map(|account| {
+            let account_type = account.account_type.as_deref().unwrap_or("unknown");
+
+            json!({
+                "address": account.address,
+                "balance": account.balance,
+                "balance_at_block": account.balance_at_block,
+                "transaction_count": account.transaction_count,
+                "account_type": account_type,
+                "first_seen": account.first_seen_block,
+                "last_activity": account.last_seen_block
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "accounts": accounts_with_type,
+        "has_next": has_next,
+        "page": page,
+        "per_page": per_page
+    })))
 }
 
 /// Get accounts with filtering
 pub async fn get_filtered_accounts(
     Query(filters): Query<crate::database::AccountFilterParams>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
-    let accounts = db.get_filtered_accounts(&filters).await.unwrap_or_default();
+    let (accounts, total) = db
+        .get_filtered_accounts(&filters)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch accounts", e))?;
 
-    let total = db.get_account_count().await.unwrap_or(0);
     let current_page = filters.page.unwrap_or(1);
     let per_page = filters.per_page.unwrap_or(10);
     let total_pages = (total as f64 / per_page as f64).ceil() as u64;
     let has_next = current_page < total_pages;
 
-    Json(json!({
+    Ok(Json(json!({
         "accounts": accounts,
         "pagination": {
             "current_page": current_page,
@@ -193,11 +336,17 @@ pub async fn get_filtered_accounts(
             "sort": filters.sort,
             "order": filters.order
         }
-    }))
+    })))
 }
 
-/// Determine account type based on transaction count and blockchain state
+/// Determine account type, from the type detected at index time when the account has one
 async fn determine_account_type(account: &Account, app: &App) -> &'static str {
+    match account.account_type.as_deref() {
+        Some("contract") => return "contract",
+        Some("eoa") => return "eoa",
+        _ => {}
+    }
+
     // If account has made transactions, it's likely an EOA (Externally Owned Account)
     if account.transaction_count > 0 {
         return "eoa";
@@ -0,0 +1,396 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::{
+    api::ApiError,
+    database::{
+        DebugBlockParams, IndexerEventParams, IndexerSettingsRequest, LogFilterRequest,
+        ReindexRequest, StorageParams,
+    },
+    executor::RateLimits,
+    log_filter::{self, LogFilter},
+    storage_stats::storage_report,
+    App,
+};
+
+/// Get recorded operational events, newest first
+pub async fn get_indexer_events(
+    Query(params): Query<IndexerEventParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let (events, total) = app
+        .db
+        .get_indexer_events(&params)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch indexer events", e))?;
+
+    let events: Vec<Value> = events
+        .into_iter()
+        .map(|event| {
+            json!({
+                "id": event.id,
+                "kind": event.kind,
+                "severity": event.severity,
+                "message": event.message,
+                "block_number": event.block_number,
+                "details": event
+                    .details
+                    .and_then(|details| serde_json::from_str::<Value>(&details).ok()),
+                "created_at": event.created_at
+            })
+        })
+        .collect();
+
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    let has_next = current_page < total_pages;
+
+    Ok(Json(json!({
+        "events": events,
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": has_next
+        },
+        "filters": {
+            "kind": params.kind,
+            "severity": params.severity
+        }
+    })))
+}
+
+/// Get the previous versions of a block rewritten by reorg handling or a repair
+pub async fn get_block_history(
+    Path(number): Path<i64>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.get_block_versions(number).await {
+        Ok(versions) => {
+            let versions: Vec<Value> = versions
+                .into_iter()
+                .map(|version| {
+                    json!({
+                        "id": version.id,
+                        "hash": version.hash,
+                        "reason": version.reason,
+                        "replaced_at": version.replaced_at,
+                        "data": serde_json::from_str::<Value>(&version.data).ok()
+                    })
+                })
+                .collect();
+            Ok(Json(json!({
+                "block_number": number,
+                "versions": versions
+            })))
+        }
+        Err(e) => Err(ApiError::internal("Failed to fetch block history", e)),
+    }
+}
+
+/// Get the previous versions of a transaction rewritten by reorg handling or a repair
+pub async fn get_transaction_history(
+    Path(hash): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.get_transaction_versions(&hash).await {
+        Ok(versions) => {
+            let versions: Vec<Value> = versions
+                .into_iter()
+                .map(|version| {
+                    json!({
+                        "id": version.id,
+                        "block_number": version.block_number,
+                        "reason": version.reason,
+                        "replaced_at": version.replaced_at,
+                        "data": serde_json::from_str::<Value>(&version.data).ok()
+                    })
+                })
+                .collect();
+            Ok(Json(json!({
+                "hash": hash.to_lowercase(),
+                "versions": versions
+            })))
+        }
+        Err(e) => Err(ApiError::internal("Failed to fetch transaction history", e)),
+    }
+}
+
+/// Current indexer status with per-worker statistics and RPC rate limits
+pub async fn get_indexer_status(Extension(app): Extension<Arc<App>>) -> Json<Value> {
+    Json(indexer_status(&app))
+}
+
+/// Pause block fetching and processing
+pub async fn pause_indexer(Extension(app): Extension<Arc<App>>) -> Json<Value> {
+    app.indexer.pause();
+    Json(indexer_status(&app))
+}
+
+/// Resume block fetching and processing
+pub async fn resume_indexer(Extension(app): Extension<Arc<App>>) -> Json<Value> {
+    app.indexer.resume();
+    Json(indexer_status(&app))
+}
+
+/// Change the worker pool size and RPC rate limits of the running indexer
+pub async fn update_indexer_settings(
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<IndexerSettingsRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if let Some(size) = request.worker_pool_size
+        && let Err(e) = app.indexer.set_worker_pool_size(size)
+    {
+        return Err(ApiError::Unprocessable(e.to_string()));
+    }
+
+    if request.eth_rpc_max_concurrent.is_some() || request.eth_rpc_min_interval_ms.is_some() {
+        let limits = app.rpc.rate_limits();
+        app.rpc.set_rate_limits(RateLimits {
+            max_concurrent: request
+                .eth_rpc_max_concurrent
+                .unwrap_or(limits.max_concurrent),
+            min_interval_ms: request
+                .eth_rpc_min_interval_ms
+                .unwrap_or(limits.min_interval_ms),
+        });
+    }
+
+    #[cfg(feature = "beacon")]
+    if request.beacon_rpc_max_concurrent.is_some() || request.beacon_rpc_min_interval_ms.is_some() {
+        let limits = app.beacon.rate_limits();
+        app.beacon.set_rate_limits(RateLimits {
+            max_concurrent: request
+                .beacon_rpc_max_concurrent
+                .unwrap_or(limits.max_concurrent),
+            min_interval_ms: request
+                .beacon_rpc_min_interval_ms
+                .unwrap_or(limits.min_interval_ms),
+        });
+    }
+
+    Ok(Json(indexer_status(&app)))
+}
+
+/// Queue a block to be fetched and processed again
+pub async fn requeue_block(
+    Path(number): Path<i64>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    if number < 0 {
+        return Err(ApiError::Unprocessable(
+            "Block number must not be negative".to_string(),
+        ));
+    }
+
+    match app.indexer.requeue_block(number) {
+        Ok(()) => Ok(Json(json!({
+            "block_number": number,
+            "requeued": true
+        }))),
+        // The indexer is stopped or its queue is full
+        Err(e) => Err(ApiError::Unavailable(format!(
+            "Failed to re-queue block #{}: {}",
+            number, e
+        ))),
+    }
+}
+
+/// Delete and re-fetch a block range in the background, the outcome is recorded as a
+/// `range_reindexed` event
+pub async fn reindex_blocks(
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<ReindexRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if request.from_block < 0 || request.to_block < request.from_block {
+        return Err(ApiError::Unprocessable(format!(
+            "Invalid block range {}..={}",
+            request.from_block, request.to_block
+        )));
+    }
+
+    let indexer = app.indexer.clone();
+    tokio::spawn(async move {
+        if let Err(e) = indexer
+            .reindex_range(request.from_block, request.to_block)
+            .await
+        {
+            error!("Reindex failed: {:#}", e);
+        }
+    });
+
+    Ok(Json(json!({
+        "from_block": request.from_block,
+        "to_block": request.to_block,
+        "started": true
+    })))
+}
+
+/// Replay a block against the stored data, reporting decoded rows, RPC calls, timings and
+/// differences. The block is only reindexed with `apply=true`.
+pub async fn debug_block(
+    Path(number): Path<i64>,
+    Query(params): Query<DebugBlockParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app
+        .indexer
+        .debug_block(number, params.apply.unwrap_or(false))
+        .await
+    {
+        Ok(report) => Ok(Json(json!(report))),
+        Err(e) => Err(ApiError::internal(
+            &format!("Failed to replay block #{}", number),
+            e,
+        )),
+    }
+}
+
+/// Progress of the background token balance refresh
+pub async fn get_token_refresh_status(Extension(app): Extension<Arc<App>>) -> Json<Value> {
+    #[cfg(feature = "token-service")]
+    let status = json!(app.token_service.refresh_status());
+    #[cfg(not(feature = "token-service"))]
+    let status = {
+        let _ = app;
+        json!({ "enabled": false })
+    };
+
+    Json(status)
+}
+
+/// Row counts, table and index sizes of every table, with their daily growth over the
+/// `days` before the latest storage sample
+pub async fn get_storage_stats(
+    Query(params): Query<StorageParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let db = &app.db;
+    let latest = match db.get_latest_storage_sample_date().await {
+        Ok(Some(latest)) => latest,
+        // Never sampled, e.g. with the storage statistics job disabled
+        Ok(None) => {
+            let today = chrono::Utc::now().date_naive().to_string();
+            db.record_storage_snapshot(&today)
+                .await
+                .map_err(|e| ApiError::internal("Failed to sample storage", e))?;
+            today
+        }
+        Err(e) => return Err(ApiError::internal("Failed to fetch storage statistics", e)),
+    };
+
+    let since = chrono::NaiveDate::parse_from_str(&latest, "%Y-%m-%d")
+        .map(|latest| (latest - chrono::Duration::days(params.days())).to_string())
+        .unwrap_or_else(|_| latest.clone());
+    let tables = match db.get_storage_snapshots(&since).await {
+        Ok(snapshots) => storage_report(&snapshots),
+        Err(e) => return Err(ApiError::internal("Failed to fetch storage statistics", e)),
+    };
+
+    let rows: i64 = tables.iter().map(|table| table.row_count).sum();
+    let table_bytes: i64 = tables.iter().map(|table| table.table_bytes).sum();
+    let index_bytes: i64 = tables.iter().map(|table| table.index_bytes).sum();
+    let bytes_per_day: f64 = tables
+        .iter()
+        .filter_map(|table| table.growth.as_ref())
+        .map(|growth| growth.bytes_per_day)
+        .sum();
+
+    Ok(Json(json!({
+        "sampled_on": latest,
+        "window_days": params.days(),
+        "database": {
+            "size_bytes": db.get_database_size().await.ok(),
+            "free_bytes": db.get_database_free_size().await.ok()
+        },
+        "totals": {
+            "rows": rows,
+            "table_bytes": table_bytes,
+            "index_bytes": index_bytes,
+            "bytes_per_day": bytes_per_day
+        },
+        "tables": tables
+    })))
+}
+
+fn indexer_status(app: &App) -> Value {
+    #[cfg(feature = "beacon")]
+    let (beacon_rate_limits, beacon_queue) = (
+        Some(app.beacon.rate_limits()),
+        Some(app.beacon.queue_stats()),
+    );
+    #[cfg(not(feature = "beacon"))]
+    let (beacon_rate_limits, beacon_queue): (
+        Option<RateLimits>,
+        Option<crate::executor::QueueStats>,
+    ) = (None, None);
+
+    json!({
+        "status": app.indexer.get_status(),
+        "rate_limits": {
+            "eth_rpc": app.rpc.rate_limits(),
+            "beacon_rpc": beacon_rate_limits
+        },
+        "rpc_queues": {
+            "eth_rpc": app.rpc.queue_stats(),
+            "beacon_rpc": beacon_queue
+        }
+    })
+}
+
+/// Log directives of the process, shared by every network it serves
+pub async fn get_log_filter() -> Result<Json<Value>, ApiError> {
+    match log_filter::runtime() {
+        Some(filter) => Ok(Json(log_filter_status(filter))),
+        None => Err(log_filter_unavailable()),
+    }
+}
+
+/// Replace the log directives of the running process, e.g. `info,indexer=debug,rpc=warn`
+pub async fn update_log_filter(
+    Json(request): Json<LogFilterRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let Some(filter) = log_filter::runtime() else {
+        return Err(log_filter_unavailable());
+    };
+    if let Err(e) = filter.set(&request.directives) {
+        return Err(ApiError::Unprocessable(e.to_string()));
+    }
+
+    info!("Log directives changed to {}", filter.directives());
+    Ok(Json(log_filter_status(filter)))
+}
+
+/// Go back to the log directives of `LOG_LEVEL`
+pub async fn reset_log_filter() -> Result<Json<Value>, ApiError> {
+    let Some(filter) = log_filter::runtime() else {
+        return Err(log_filter_unavailable());
+    };
+    if let Err(e) = filter.reset() {
+        return Err(ApiError::internal("Failed to reset log directives", e));
+    }
+
+    info!("Log directives reset to {}", filter.directives());
+    Ok(Json(log_filter_status(filter)))
+}
+
+fn log_filter_status(filter: &LogFilter) -> Value {
+    json!({
+        "directives": filter.directives(),
+        "default_directives": filter.default_directives()
+    })
+}
+
+fn log_filter_unavailable() -> ApiError {
+    ApiError::Unavailable(
+        "Log directives can't be changed, the log filter wasn't installed by this process"
+            .to_string(),
+    )
+}
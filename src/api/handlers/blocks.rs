@@ -2,11 +2,17 @@ use axum::{
     extract::{Path, Query},
     Extension, Json,
 };
-use serde_json::json;
-use std::sync::Arc;
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc};
+use tracing::warn;
 
 use crate::{
-    database::{BlockResponse, PaginationParams},
+    api::ApiError,
+    database::{
+        AddressLabel, Block, BlockResponse, DatabaseService, FeeRecipientParams, Finality,
+        PaginationParams, Transaction, TransactionResponse,
+    },
+    executor::RpcOverloaded,
     App,
 };
 
@@ -14,24 +20,46 @@ use crate::{
 pub async fn get_blocks(
     Query(params): Query<PaginationParams>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
     let limit = params.limit();
     let offset = params.offset();
 
-    let blocks = db
-        .get_recent_blocks(limit, offset)
-        .await
-        .unwrap_or_default();
+    let blocks = match params.cursor.as_deref() {
+        Some(cursor) => match cursor.parse::<i64>() {
+            Ok(before) => db.get_blocks_before(before, limit).await,
+            Err(_) => {
+                return Err(ApiError::BadRequest(
+                    "Invalid cursor, expected a block number".to_string(),
+                ));
+            }
+        },
+        None => match app.recent.recent_blocks(limit, offset) {
+            Some(blocks) => Ok(blocks),
+            None => db.get_recent_blocks(limit, offset).await,
+        },
+    }
+    .unwrap_or_default();
+    let next_cursor = blocks
+        .last()
+        .filter(|_| blocks.len() as i64 == limit)
+        .map(|block| block.number.to_string());
 
     // Convert to BlockResponse with calculated fields
+    let (latest_block, finality) = block_status_context(db).await;
     let mut block_responses = Vec::new();
     for block in blocks {
         let mut block_response = BlockResponse::from(&block);
+        block_response.calculate_status(latest_block, &finality);
 
         // Get transactions for this block to calculate block reward
-        if let Ok(transactions) = db.get_transactions_by_block(block.number).await {
-            block_response.calculate_block_reward_with_transactions(&transactions);
+        let transactions = match app.recent.transactions_by_block(block.number) {
+            Some(transactions) => Ok(transactions),
+            None => db.get_transactions_by_block(block.number).await,
+        };
+        if let Ok(transactions) = transactions {
+            let labels = recipient_labels(db, &transactions).await;
+            block_response.calculate_block_reward_with_transactions(&transactions, &labels);
         }
 
         block_responses.push(block_response);
@@ -41,74 +69,119 @@ pub async fn get_blocks(
     let current_page = params.page.unwrap_or(1);
     let per_page = params.per_page.unwrap_or(10);
     let total_pages = (total as f64 / per_page as f64).ceil() as u64;
-    let has_next = current_page < total_pages;
+    let has_next = match params.cursor {
+        Some(_) => next_cursor.is_some(),
+        None => current_page < total_pages,
+    };
 
-    Json(json!({
+    Ok(Json(json!({
         "blocks": block_responses,
         "total": total,
         "page": current_page,
         "per_page": per_page,
         "pages": total_pages,
-        "has_next": has_next
-    }))
+        "has_next": has_next,
+        "next_cursor": next_cursor
+    })))
 }
 
 /// Get block by number
 pub async fn get_block_by_number(
     Path(number): Path<i64>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
     // Try to get block from DB
     if let Ok(Some(block)) = db.get_block_by_number(number).await {
         // Convert to BlockResponse with calculated fields
         let mut block_response = BlockResponse::from(&block);
+        let (latest_block, finality) = block_status_context(db).await;
+        block_response.calculate_status(latest_block, &finality);
 
         // Get transactions for this block to calculate block reward
         if let Ok(transactions) = db.get_transactions_by_block(number).await {
-            block_response.calculate_block_reward_with_transactions(&transactions);
+            let labels = recipient_labels(db, &transactions).await;
+            block_response.calculate_block_reward_with_transactions(&transactions, &labels);
+            #[cfg(feature = "beacon")]
+            // Stored by the rewards job, the page never waits on the beacon or archive node
+            match db.get_block_rewards(number).await {
+                Ok(Some(rewards)) => block_response.apply_rewards(&rewards, &transactions),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to get rewards of block {}: {:#}", number, e),
+            }
+            let transactions: Vec<TransactionResponse> = transactions
+                .into_iter()
+                .map(TransactionResponse::from)
+                .collect();
 
-            return Json(json!({
-                "block": block_response,
-                "transactions": transactions
-            }));
+            return Ok(Json(
+                with_block_extras(
+                    &app,
+                    &block,
+                    json!({
+                        "block": block_response,
+                        "transactions": transactions
+                    }),
+                )
+                .await,
+            ));
         }
 
-        return Json(json!({
-            "block": block_response,
-            "transactions": []
-        }));
+        return Ok(Json(
+            with_block_extras(
+                &app,
+                &block,
+                json!({
+                    "block": block_response,
+                    "transactions": []
+                }),
+            )
+            .await,
+        ));
     }
 
     // Block not found in our DB, try getting from RPC
-    if let Ok(Some(eth_block)) = app.rpc.get_block_by_number(number as u64).await {
-        return Json(json!({
-            "block": {
-                "number": eth_block.number.map(|n| n.as_u64()).unwrap_or_default(),
-                "hash": eth_block.hash.map(|h| format!("{:?}", h)).unwrap_or_default(),
-                "parent_hash": format!("{:?}", eth_block.parent_hash),
-                "timestamp": eth_block.timestamp.as_u64(),
-                "gas_used": eth_block.gas_used.as_u64(),
-                "gas_limit": eth_block.gas_limit.as_u64(),
-                "transaction_count": eth_block.transactions.len(),
-            },
-            "transactions": [],
-            "note": "Block not yet indexed, basic info retrieved from blockchain"
-        }));
+    match app.rpc.get_block_by_number(number as u64).await {
+        Ok(Some(eth_block)) => {
+            return Ok(Json(json!({
+                "block": {
+                    "number": eth_block.number.map(|n| n.as_u64()).unwrap_or_default(),
+                    "hash": eth_block.hash.map(|h| format!("{:?}", h)).unwrap_or_default(),
+                    "parent_hash": format!("{:?}", eth_block.parent_hash),
+                    "timestamp": eth_block.timestamp.as_u64(),
+                    "gas_used": eth_block.gas_used.as_u64(),
+                    "gas_limit": eth_block.gas_limit.as_u64(),
+                    "transaction_count": eth_block.transactions.len(),
+                },
+                "transactions": [],
+                "note": "Block not yet indexed, basic info retrieved from blockchain"
+            })));
+        }
+        Err(e) if RpcOverloaded::is(&e) => return Err(ApiError::RpcOverloaded),
+        _ => {}
     }
 
     // Neither in DB nor on chain
-    Json(json!({
-        "error": "Block not found"
-    }))
+    Err(ApiError::NotFound("Block not found".to_string()))
+}
+
+/// Latest indexed block and finality checkpoints, from which block statuses are computed
+async fn block_status_context(db: &DatabaseService) -> (i64, Finality) {
+    let latest_block = db
+        .get_latest_block_number()
+        .await
+        .unwrap_or(None)
+        .unwrap_or(0);
+    let finality = db.get_finality().await.unwrap_or_default();
+    (latest_block, finality)
 }
 
 /// Get recent blocks since a specific block number (delta updates)
 pub async fn get_blocks_since(
     Query(params): Query<std::collections::HashMap<String, String>>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
     let since_block = params
@@ -117,44 +190,54 @@ pub async fn get_blocks_since(
         .unwrap_or(0);
 
     // Get blocks with number > since_block, ordered by block_number DESC, limit 10
-    let blocks = match sqlx::query_as::<_, crate::database::Block>(
-        r#"
-        SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
-               miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
-               nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
-               slot, proposer_index, epoch, slot_root, parent_root, beacon_deposit_count,
-               graffiti, randao_reveal, randao_mix
-        FROM blocks 
-        WHERE number > ? 
-        ORDER BY number DESC 
-        LIMIT 10
-        "#,
-    )
-    .bind(since_block)
-    .fetch_all(&db.pool)
-    .await
-    {
-        Ok(blocks) => blocks,
-        Err(_) => vec![],
+    let blocks = if let Some(blocks) = app.recent.blocks_since(since_block, 10) {
+        blocks
+    } else {
+        sqlx::query_as::<_, crate::database::Block>(
+            r#"
+            SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+                   miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+                   nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+                   slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+                   eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+                   attestation_count, slashing_count
+            FROM blocks 
+            WHERE number > ? 
+            ORDER BY number DESC 
+            LIMIT 10
+            "#,
+        )
+        .bind(since_block)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap_or_default()
     };
 
-    Json(json!({
+    Ok(Json(json!({
         "blocks": blocks,
         "since": since_block,
         "timestamp": chrono::Utc::now().timestamp()
-    }))
+    })))
 }
 
 /// Get recent transactions since a specific transaction hash (delta updates)
 pub async fn get_transactions_since(
     Query(params): Query<std::collections::HashMap<String, String>>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
     let since_hash = params.get("since").cloned().unwrap_or_default();
 
-    let transactions = if since_hash.is_empty() {
+    let cached = if since_hash.is_empty() {
+        app.recent.recent_transactions(10, 0)
+    } else {
+        app.recent.transactions_since(&since_hash, 10)
+    };
+
+    let transactions = if let Some(transactions) = cached {
+        transactions
+    } else if since_hash.is_empty() {
         // First load - get latest 5 transactions
         db.get_recent_transactions(10, 0).await.unwrap_or_default()
     } else {
@@ -173,7 +256,7 @@ pub async fn get_transactions_since(
                 // Found reference transaction, get newer ones
                 match sqlx::query_as::<_, crate::database::Transaction>(
                     r#"
-                    SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index
+                    SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
                     FROM transactions
                     WHERE (block_number > ?)
                        OR (block_number = ? AND transaction_index > ?)
@@ -197,9 +280,115 @@ pub async fn get_transactions_since(
         }
     };
 
-    Json(json!({
+    let transactions: Vec<TransactionResponse> = transactions
+        .into_iter()
+        .map(TransactionResponse::from)
+        .collect();
+
+    Ok(Json(json!({
         "transactions": transactions,
         "since": since_hash,
         "timestamp": chrono::Utc::now().timestamp()
-    }))
+    })))
+}
+
+/// Add the L1 origin and batch references of a block to its response on rollups, the
+/// fee recipient of its beacon execution payload when it was fetched, and the proposer
+/// scheduled for its slot with the slots missed since the previous block
+async fn with_block_extras(app: &App, block: &Block, mut response: Value) -> Value {
+    let number = block.number;
+    if app.config.network_profile.is_l2()
+        && let Ok(Some(l2)) = app.db.get_l2_block(number).await
+    {
+        response["l2"] = json!(l2);
+    }
+    if let Ok(Some(fee_recipient)) = app.db.get_block_fee_recipient(number).await {
+        response["fee_recipient"] = json!(fee_recipient);
+    }
+    if let Some(slot) = block.slot {
+        let previous_slot = match app.db.get_block_by_number(number - 1).await {
+            Ok(Some(previous)) => previous.slot,
+            _ => None,
+        };
+        let from_slot = previous_slot.map_or(slot, |previous_slot| previous_slot + 1);
+        if let Ok(duties) = app.db.get_proposer_duties(from_slot, slot).await
+            && !duties.is_empty()
+        {
+            let (scheduled, missed): (Vec<_>, Vec<_>) =
+                duties.into_iter().partition(|duty| duty.slot == slot);
+            response["proposer_duties"] = json!({
+                "scheduled_proposer": scheduled.first(),
+                "missed_slots": missed
+            });
+        }
+    }
+    response
+}
+
+/// List blocks with the fee recipient of their beacon execution payload, the miner and
+/// the proposer payment, newest first. `mismatch=true` keeps blocks whose fee recipient
+/// isn't the miner.
+pub async fn get_block_fee_recipients(
+    Query(params): Query<FeeRecipientParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let (fee_recipients, total) = app
+        .db
+        .get_block_fee_recipients(&params)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch block fee recipients", e))?;
+
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    Ok(Json(json!({
+        "fee_recipients": fee_recipients,
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": current_page < total_pages
+        }
+    })))
+}
+
+/// Which fee recipients (builders under MEV-Boost) paid which proposer payees, with block
+/// counts, mismatches and total payments per pair. Takes the filters of
+/// `get_block_fee_recipients`.
+pub async fn get_fee_recipient_relationships(
+    Query(params): Query<FeeRecipientParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let relationships = app
+        .db
+        .get_fee_recipient_relationships(&params)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch fee recipient relationships", e))?;
+
+    Ok(Json(json!({
+        "relationships": relationships,
+        "page": params.page.unwrap_or(1),
+        "per_page": params.limit()
+    })))
+}
+
+/// Labels of the recipients of `transactions`, empty when they could not be loaded
+async fn recipient_labels(
+    db: &DatabaseService,
+    transactions: &[Transaction],
+) -> HashMap<String, AddressLabel> {
+    let mut recipients: Vec<String> = transactions
+        .iter()
+        .filter_map(|tx| tx.to_address.as_ref().map(|to| to.to_lowercase()))
+        .collect();
+    recipients.sort();
+    recipients.dedup();
+
+    db.get_address_labels_for(&recipients)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to get address labels: {:#}", e);
+            HashMap::new()
+        })
 }
@@ -0,0 +1,111 @@
+use axum::{extract::Query, Extension, Json};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{
+    api::ApiError,
+    database::{AggregateStats, ChartParams},
+    App,
+};
+
+/// Daily or hourly transaction and block counts
+pub async fn get_transactions_chart(
+    Query(params): Query<ChartParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    chart(&app, &params, |stats| {
+        json!({
+            "transaction_count": stats.transaction_count,
+            "block_count": stats.block_count
+        })
+    })
+    .await
+}
+
+/// Daily or hourly gas usage, average gas price and burnt fees
+pub async fn get_gas_chart(
+    Query(params): Query<ChartParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    chart(&app, &params, |stats| {
+        json!({
+            "gas_used": stats.gas_used,
+            "avg_gas_price_gwei": stats.avg_gas_price_gwei,
+            "burnt_fees_eth": stats.burnt_fees_eth
+        })
+    })
+    .await
+}
+
+/// Daily or hourly active addresses and contract deployments
+pub async fn get_accounts_chart(
+    Query(params): Query<ChartParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    chart(&app, &params, |stats| {
+        json!({
+            "active_addresses": stats.active_addresses,
+            "new_contracts": stats.new_contracts
+        })
+    })
+    .await
+}
+
+/// Daily burnt fees against estimated consensus issuance. Issuance is extrapolated from the
+/// proposer rewards of `issuance_samples` blocks per day, and null for days without any.
+pub async fn get_issuance_chart(
+    Query(params): Query<ChartParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    if params.period() == Some("hour") {
+        return Err(ApiError::BadRequest(
+            "Issuance is only estimated per day".to_string(),
+        ));
+    }
+
+    chart(&app, &params, |stats| {
+        json!({
+            "burnt_fees_eth": stats.burnt_fees_eth,
+            "issuance_eth": stats.issuance_eth,
+            "net_issuance_eth": stats.issuance_eth.map(|issuance| issuance - stats.burnt_fees_eth),
+            "issuance_samples": stats.issuance_samples,
+            "block_count": stats.block_count
+        })
+    })
+    .await
+}
+
+/// Series of aggregate buckets, each point built by `point` plus its timestamp
+async fn chart(
+    app: &App,
+    params: &ChartParams,
+    point: impl Fn(&AggregateStats) -> Value,
+) -> Result<Json<Value>, ApiError> {
+    let Some(period) = params.period() else {
+        return Err(ApiError::BadRequest(
+            "interval must be \"day\" or \"hour\"".to_string(),
+        ));
+    };
+
+    let since = chrono::Utc::now().timestamp() - params.days() * 86400;
+    let stats = app
+        .db
+        .get_aggregate_stats(period, since)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch chart data", e))?;
+
+    let points: Vec<Value> = stats
+        .iter()
+        .map(|stats| {
+            let mut value = point(stats);
+            value["timestamp"] = json!(stats.bucket_start);
+            value
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "interval": period,
+        "days": params.days(),
+        "points": points
+    })))
+}
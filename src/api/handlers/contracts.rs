@@ -0,0 +1,277 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{
+    api::ApiError,
+    database::{
+        ContractChildrenParams, ContractFailureParams, PaginationParams, TopContractsParams,
+        VerifyContractRequest,
+    },
+    App,
+};
+
+/// Verify submitted source code against the bytecode deployed at an address
+pub async fn verify_contract(
+    Path(address): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<VerifyContractRequest>,
+) -> Result<Json<Value>, ApiError> {
+    match app.contracts.verify(&address, &request).await {
+        Ok(result) => Ok(Json(json!(result))),
+        Err(e) => Err(ApiError::Unprocessable(format!(
+            "Verification failed: {:#}",
+            e
+        ))),
+    }
+}
+
+/// Get the verified source and ABI of a contract
+pub async fn get_verified_contract(
+    Path(address): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.get_verified_contract(&address.to_lowercase()).await {
+        Ok(Some(contract)) => Ok(Json(json!({
+            "contract": {
+                "address": contract.address,
+                "contract_name": contract.contract_name,
+                "compiler_version": contract.compiler_version,
+                "optimizer_enabled": contract.optimizer_enabled,
+                "optimizer_runs": contract.optimizer_runs,
+                "evm_version": contract.evm_version,
+                "match_type": contract.match_type,
+                "verified_at": contract.verified_at,
+                "abi": serde_json::from_str::<Value>(&contract.abi).unwrap_or(Value::Null),
+                "source_code": contract.source_code
+            }
+        }))),
+        Ok(None) => Err(ApiError::NotFound("Contract not verified".to_string())),
+        Err(e) => Err(ApiError::internal("Failed to fetch contract", e)),
+    }
+}
+
+/// Get all indexed deployments of identical runtime bytecode
+pub async fn get_contracts_by_codehash(
+    Path(code_hash): Path<String>,
+    Query(params): Query<PaginationParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let code_hash = code_hash.to_lowercase();
+
+    let code = match app.db.get_contract_code(&code_hash).await {
+        Ok(Some(code)) => code,
+        Ok(None) => {
+            return Err(ApiError::NotFound(
+                "No deployments found for code hash".to_string(),
+            ));
+        }
+        Err(e) => return Err(ApiError::internal("Failed to fetch contract code", e)),
+    };
+
+    let limit = params.limit();
+    match app
+        .db
+        .get_deployments_by_code_hash(&code_hash, limit, params.offset())
+        .await
+    {
+        Ok((deployments, total)) => {
+            let page = params.page.unwrap_or(1);
+            let total_pages = (total as f64 / limit as f64).ceil() as i64;
+
+            Ok(Json(json!({
+                "code_hash": code.code_hash,
+                "code_size": code.code_size,
+                "first_seen_block": code.first_seen_block,
+                "bytecode": code.bytecode,
+                "deployments": deployments,
+                "pagination": {
+                    "current_page": page,
+                    "per_page": limit,
+                    "total": total,
+                    "total_pages": total_pages,
+                    "has_next": (page as i64) < total_pages
+                }
+            })))
+        }
+        Err(e) => Err(ApiError::internal("Failed to fetch deployments", e)),
+    }
+}
+
+/// Contracts deployed by an address (a factory contract or an EOA), down to `depth`
+/// creation levels, nearest first. Contracts deployed by contracts are only known with
+/// `TRACE_CONTRACT_CREATIONS`.
+pub async fn get_contract_children(
+    Path(address): Path<String>,
+    Query(params): Query<ContractChildrenParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let address = address.to_lowercase();
+    let limit = params.limit();
+    match app
+        .db
+        .get_contract_descendants(&address, params.depth(), limit, params.offset())
+        .await
+    {
+        Ok((children, total)) => {
+            let page = params.page.unwrap_or(1);
+            let total_pages = (total as f64 / limit as f64).ceil() as i64;
+
+            Ok(Json(json!({
+                "address": address,
+                "depth": params.depth(),
+                "children": children,
+                "pagination": {
+                    "current_page": page,
+                    "per_page": limit,
+                    "total": total,
+                    "total_pages": total_pages,
+                    "has_next": (page as i64) < total_pages
+                }
+            })))
+        }
+        Err(e) => Err(ApiError::internal("Failed to fetch contract children", e)),
+    }
+}
+
+/// Deployment of a contract and of the factories above it, up to the account whose
+/// transaction deployed the outermost factory
+pub async fn get_contract_ancestry(
+    Path(address): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let address = address.to_lowercase();
+    match app.db.get_contract_ancestry(&address).await {
+        Ok(ancestry) if ancestry.is_empty() => Err(ApiError::NotFound(
+            "No deployment indexed for this contract".to_string(),
+        )),
+        Ok(ancestry) => Ok(Json(json!({
+            "address": address,
+            "root_creator": ancestry.last().map(|root| root.creator_address.clone()),
+            "factories": ancestry.len() - 1,
+            "ancestry": ancestry
+        }))),
+        Err(e) => Err(ApiError::internal("Failed to fetch contract ancestry", e)),
+    }
+}
+
+/// Daily failed transaction rollup of a contract with revert reasons
+pub async fn get_contract_failures(
+    Path(address): Path<String>,
+    Query(params): Query<ContractFailureParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let address = address.to_lowercase();
+
+    // Decode the reasons of recent failures first, so they show up in this response
+    if let Err(e) = app.contracts.decode_revert_reasons(&address).await {
+        tracing::debug!("Failed to decode revert reasons for {}: {}", address, e);
+    }
+
+    let since = chrono::Utc::now().timestamp() - params.days() * 86400;
+    let days = app
+        .db
+        .get_contract_failure_days(&address, since)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch contract failures", e))?;
+    let reasons = app
+        .db
+        .get_contract_failure_reasons(&address, since)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch revert reasons", e))?;
+
+    let total: i64 = days.iter().map(|day| day.total_transactions).sum();
+    let failed: i64 = days.iter().map(|day| day.failed_transactions).sum();
+
+    let days: Vec<Value> = days
+        .into_iter()
+        .map(|day| {
+            let day_reasons: Vec<Value> = reasons
+                .iter()
+                .filter(|(date, _, _)| *date == day.day)
+                .map(|(_, reason, count)| json!({ "reason": reason, "count": count }))
+                .collect();
+
+            json!({
+                "date": day.day,
+                "total_transactions": day.total_transactions,
+                "failed_transactions": day.failed_transactions,
+                "error_rate": day.failed_transactions as f64 / day.total_transactions as f64,
+                "reasons": day_reasons
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "address": address,
+        "days": params.days(),
+        "total_transactions": total,
+        "failed_transactions": failed,
+        "error_rate": if total > 0 { failed as f64 / total as f64 } else { 0.0 },
+        "daily": days
+    })))
+}
+
+/// Most called contracts over a recent window (`?window=24h`), for trending contracts.
+/// The window ends at the newest indexed block.
+pub async fn get_top_contracts(
+    Query(params): Query<TopContractsParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let Some(window) = params.window_seconds() else {
+        return Err(ApiError::BadRequest(
+            "window must be formatted as <n>h or <n>d, at most 30d".to_string(),
+        ));
+    };
+
+    let until = match app.db.get_latest_block_timestamp().await {
+        Ok(Some((_, timestamp))) => timestamp,
+        Ok(None) => {
+            return Ok(Json(json!({
+                "window": window,
+                "contracts": []
+            })));
+        }
+        Err(e) => return Err(ApiError::internal("Failed to fetch latest block", e)),
+    };
+
+    let contracts = app
+        .db
+        .get_top_contracts(until - window, params.limit())
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch top contracts", e))?;
+    let addresses: Vec<String> = contracts
+        .iter()
+        .map(|contract| contract.contract_address.clone())
+        .collect();
+    let labels = app
+        .db
+        .get_address_labels_for(&addresses)
+        .await
+        .unwrap_or_default();
+
+    let contracts: Vec<Value> = contracts
+        .into_iter()
+        .enumerate()
+        .map(|(index, contract)| {
+            json!({
+                "rank": index + 1,
+                "address": contract.contract_address,
+                "label": labels.get(&contract.contract_address),
+                "calls": contract.calls,
+                "calls_per_day": contract.calls as f64 * 86_400.0 / window as f64,
+                "unique_callers": contract.unique_callers
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "window": window,
+        "since": until - window,
+        "until": until,
+        "contracts": contracts
+    })))
+}
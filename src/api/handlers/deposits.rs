@@ -0,0 +1,55 @@
+use axum::{extract::Query, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::{api::ApiError, database::DepositParams, App};
+
+use super::gwei_to_eth_string;
+
+/// Get beacon deposits, optionally made by a depositor and/or for a validator pubkey,
+/// newest first, with the totals of every matching deposit
+pub async fn get_deposits(
+    Query(params): Query<DepositParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let db = &app.db;
+    let depositor = params.depositor.as_deref().map(|d| d.trim().to_lowercase());
+    let pubkey = params.pubkey.as_deref().map(|p| p.trim().to_lowercase());
+
+    let deposits = db
+        .get_deposits(
+            depositor.as_deref(),
+            pubkey.as_deref(),
+            params.limit(),
+            params.offset(),
+        )
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch deposits", e))?;
+
+    let (total, total_gwei) = db
+        .get_deposit_totals(depositor.as_deref(), pubkey.as_deref())
+        .await
+        .unwrap_or((0, 0));
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    let has_next = current_page < total_pages;
+
+    Ok(Json(json!({
+        "depositor": depositor,
+        "pubkey": pubkey,
+        "deposits": deposits,
+        "totals": {
+            "count": total,
+            "amount_gwei": total_gwei.to_string(),
+            "amount_eth": gwei_to_eth_string(total_gwei)
+        },
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": has_next
+        }
+    })))
+}
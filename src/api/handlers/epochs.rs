@@ -0,0 +1,44 @@
+use axum::{extract::Path, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::{api::ApiError, App};
+
+/// Slots per beacon epoch
+const SLOTS_PER_EPOCH: i64 = 32;
+
+/// Get the summary of a beacon epoch with its finality status: finalized or justified
+/// when the epoch precedes the matching checkpoint, pending otherwise
+pub async fn get_epoch(
+    Path(epoch): Path<i64>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let db = &app.db;
+
+    let summary = match db.get_epoch(epoch).await {
+        Ok(Some(summary)) => summary,
+        Ok(None) => return Err(ApiError::NotFound("Epoch not found".to_string())),
+        Err(e) => return Err(ApiError::internal("Failed to fetch epoch", e)),
+    };
+
+    let checkpoints = db.get_finality_checkpoints().await.unwrap_or_default();
+    let covered_by = |kind: &str| {
+        checkpoints
+            .iter()
+            .any(|checkpoint| checkpoint.kind == kind && epoch < checkpoint.epoch)
+    };
+    let status = if covered_by("finalized") {
+        "finalized"
+    } else if covered_by("justified") {
+        "justified"
+    } else {
+        "pending"
+    };
+
+    Ok(Json(json!({
+        "epoch": summary,
+        "first_slot": epoch * SLOTS_PER_EPOCH,
+        "last_slot": (epoch + 1) * SLOTS_PER_EPOCH - 1,
+        "status": status
+    })))
+}
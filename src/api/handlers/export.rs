@@ -0,0 +1,439 @@
+use crate::{
+    api::ApiError,
+    database::{DatabaseService, ExportParams, HolderExportParams, HolderExportRange},
+    export::{csv_header, csv_line, ExportRow},
+    App,
+};
+use axum::{
+    body::StreamBody,
+    extract::Query,
+    http::{header, HeaderName, HeaderValue},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use ethers::types::{I256, U256};
+use futures::{channel::mpsc, stream::BoxStream, SinkExt, StreamExt};
+use std::{collections::HashMap, sync::Arc};
+use tracing::{debug, error};
+
+/// Number of encoded rows buffered between the database cursor and the HTTP body
+const EXPORT_BUFFER_ROWS: usize = 256;
+
+/// Token holder snapshots kept for paging, oldest dropped first
+const MAX_HOLDER_SNAPSHOTS: i64 = 8;
+
+/// Output format of export endpoints
+#[derive(Debug, Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn from_param(format: Option<&str>) -> Self {
+        match format {
+            Some("ndjson") | Some("jsonl") => ExportFormat::Ndjson,
+            _ => ExportFormat::Csv,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// Encode a single row as one output line
+    fn encode<T: ExportRow>(self, row: &T) -> String {
+        match self {
+            ExportFormat::Csv => format!("{}\n", csv_line(row)),
+            ExportFormat::Ndjson => match serde_json::to_string(row) {
+                Ok(json) => format!("{}\n", json),
+                Err(e) => {
+                    error!("Failed to serialize export row: {}", e);
+                    String::new()
+                }
+            },
+        }
+    }
+}
+
+/// Stream rows from a database cursor straight into the response body.
+///
+/// Rows are encoded one at a time and pushed through a bounded channel, so memory
+/// stays constant regardless of how many rows the export covers.
+fn stream_export<T: ExportRow>(
+    app: Arc<App>,
+    params: ExportParams,
+    name: &'static str,
+    fetch: for<'a> fn(&'a DatabaseService, i64, i64) -> BoxStream<'a, Result<T, sqlx::Error>>,
+) -> impl IntoResponse {
+    let format = ExportFormat::from_param(params.format.as_deref());
+    let from_block = params.from_block();
+    let to_block = params.to_block();
+    let (mut sender, receiver) = mpsc::channel::<Result<String, sqlx::Error>>(EXPORT_BUFFER_ROWS);
+
+    tokio::spawn(async move {
+        send_rows(
+            &mut sender,
+            format,
+            name,
+            fetch(&app.db, from_block, to_block),
+        )
+        .await;
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}_{}_{}.{}\"",
+                    name,
+                    from_block,
+                    params
+                        .to_block
+                        .map(|b| b.to_string())
+                        .unwrap_or_else(|| "latest".to_string()),
+                    format.extension()
+                ),
+            ),
+        ],
+        StreamBody::new(receiver),
+    )
+}
+
+/// Encode the CSV header and then rows into the channel until they run out, one fails or
+/// the client disconnects
+async fn send_rows<T: ExportRow>(
+    sender: &mut mpsc::Sender<Result<String, sqlx::Error>>,
+    format: ExportFormat,
+    name: &str,
+    mut rows: BoxStream<'_, Result<T, sqlx::Error>>,
+) {
+    if let ExportFormat::Csv = format
+        && sender
+            .send(Ok(format!("{}\n", csv_header::<T>())))
+            .await
+            .is_err()
+    {
+        return;
+    }
+
+    while let Some(row) = rows.next().await {
+        let line = row.map(|row| format.encode(&row));
+        let failed = line.is_err();
+        if let Err(e) = &line {
+            error!("Export of {} aborted: {}", name, e);
+        }
+
+        if sender.send(line).await.is_err() {
+            debug!("Export of {} cancelled, client disconnected", name);
+            return;
+        }
+        if failed {
+            return;
+        }
+    }
+}
+
+/// Export blocks in a block range as CSV or NDJSON
+pub async fn export_blocks(
+    Query(params): Query<ExportParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> impl IntoResponse {
+    stream_export(app, params, "blocks", DatabaseService::stream_blocks)
+}
+
+/// Export transactions in a block range as CSV or NDJSON
+pub async fn export_transactions(
+    Query(params): Query<ExportParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> impl IntoResponse {
+    stream_export(
+        app,
+        params,
+        "transactions",
+        DatabaseService::stream_transactions,
+    )
+}
+
+/// Export logs in a block range as CSV or NDJSON
+pub async fn export_logs(
+    Query(params): Query<ExportParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> impl IntoResponse {
+    stream_export(app, params, "logs", DatabaseService::stream_logs)
+}
+
+/// Export the holders of a token, or ETH balances, from `min_balance` as CSV or NDJSON,
+/// largest balance first, then by address. Balances are decimal base units ordered by
+/// their digits, so exports are byte-identical whatever the host locale.
+///
+/// `snapshot=block` (default) reconstructs the balances at `block` from indexed data:
+/// summed token transfers up to the block, or the latest ETH balance history snapshot at
+/// or before it. Such snapshots don't move while indexing goes on, so exports resumed with
+/// `after` line up. `snapshot=latest` exports the stored balances as last read. The
+/// `X-Snapshot-*` headers report the semantics, the block and whether the indexed blocks
+/// cover every balance at it.
+pub async fn export_holders(
+    Query(params): Query<HolderExportParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Response {
+    let token = match params.token.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(token) if token.eq_ignore_ascii_case("eth") => None,
+        Some(token) => match parse_address(token) {
+            Some(address) => Some(address),
+            None => {
+                return ApiError::Unprocessable(
+                    "token must be eth or a 0x-prefixed 20-byte address".to_string(),
+                )
+                .into_response();
+            }
+        },
+    };
+    let at_block = match params.snapshot.as_deref() {
+        None | Some("block") => true,
+        Some("latest") => false,
+        Some(other) => {
+            return ApiError::BadRequest(format!(
+                "Unknown snapshot {}, expected block or latest",
+                other
+            ))
+            .into_response();
+        }
+    };
+    let range = match holder_range(&params) {
+        Ok(range) => range,
+        Err(e) => return ApiError::BadRequest(e).into_response(),
+    };
+
+    let mut snapshot_headers = vec![(
+        "x-snapshot-semantics",
+        if at_block { "block" } else { "latest" }.to_string(),
+    )];
+    let snapshot_block = if at_block {
+        if token.is_none() && app.config.balance_history_interval_blocks <= 0 {
+            return ApiError::Unprocessable(
+                "ETH snapshots at a block need BALANCE_HISTORY_INTERVAL_BLOCKS".to_string(),
+            )
+            .into_response();
+        }
+        let latest_block = match app.db.get_latest_block_number().await {
+            Ok(Some(number)) => number,
+            Ok(None) => {
+                return ApiError::NotFound("No blocks indexed yet".to_string()).into_response();
+            }
+            Err(e) => {
+                return ApiError::internal("Failed to get the latest indexed block", e)
+                    .into_response();
+            }
+        };
+        let block = params.block.unwrap_or(latest_block);
+        if !(0..=latest_block).contains(&block) {
+            return ApiError::Unprocessable(format!(
+                "block must be between 0 and the latest indexed block {}",
+                latest_block
+            ))
+            .into_response();
+        }
+        let complete = match snapshot_complete(&app, token.as_deref(), block).await {
+            Ok(complete) => complete,
+            Err(e) => {
+                return ApiError::internal("Failed to check snapshot coverage", e).into_response();
+            }
+        };
+        snapshot_headers.push(("x-snapshot-block", block.to_string()));
+        snapshot_headers.push(("x-snapshot-complete", complete.to_string()));
+        Some(block)
+    } else {
+        None
+    };
+    let holder_snapshot = match (&token, snapshot_block) {
+        (Some(token), Some(block)) => match token_holder_snapshot(&app.db, token, block).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                return ApiError::internal("Failed to build the holder snapshot", e)
+                    .into_response();
+            }
+        },
+        _ => None,
+    };
+
+    let format = ExportFormat::from_param(params.format.as_deref());
+    let filename = format!(
+        "holders_{}_{}.{}",
+        token.as_deref().unwrap_or("eth"),
+        snapshot_block
+            .map(|block| block.to_string())
+            .unwrap_or_else(|| "latest".to_string()),
+        format.extension()
+    );
+    let (mut sender, receiver) = mpsc::channel::<Result<String, sqlx::Error>>(EXPORT_BUFFER_ROWS);
+
+    tokio::spawn(async move {
+        let rows = match (&token, snapshot_block, holder_snapshot) {
+            (_, _, Some(snapshot)) => app.db.stream_token_holder_snapshot(snapshot, &range),
+            (None, Some(block), _) => app.db.stream_eth_holders_at_block(block, &range),
+            (Some(token), _, _) => app.db.stream_token_holders(token, &range),
+            (None, None, _) => app.db.stream_eth_holders(&range),
+        };
+        send_rows(&mut sender, format, "holders", rows).await;
+    });
+
+    let mut response = StreamBody::new(receiver).into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    for (name, value) in [(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}\"", filename),
+    )]
+    .into_iter()
+    .chain(
+        snapshot_headers
+            .into_iter()
+            .map(|(name, value)| (HeaderName::from_static(name), value)),
+    ) {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+    response
+}
+
+/// Id of the holder snapshot of a token at `block_number`, summing its indexed transfers
+/// into one the first time. Addresses whose sum isn't positive, like the zero address
+/// tokens are minted from, are left out.
+async fn token_holder_snapshot(
+    db: &DatabaseService,
+    token: &str,
+    block_number: i64,
+) -> anyhow::Result<i64> {
+    if let Some(id) = db.get_token_holder_snapshot(token, block_number).await? {
+        return Ok(id);
+    }
+
+    let mut balances: HashMap<String, (I256, i64)> = HashMap::new();
+    let mut transfers = db.stream_token_transfer_amounts(token, block_number);
+    while let Some(transfer) = transfers.next().await {
+        let (from, to, amount, block) = transfer?;
+        let amount = I256::from_raw(U256::from_dec_str(&amount).unwrap_or_default());
+        for (address, delta) in [(from, amount.saturating_neg()), (to, amount)] {
+            let entry = balances.entry(address).or_insert((I256::zero(), block));
+            entry.0 = entry.0.saturating_add(delta);
+            entry.1 = entry.1.max(block);
+        }
+    }
+
+    let holders: Vec<(String, String, i64)> = balances
+        .into_iter()
+        .filter(|(_, (balance, _))| balance.is_positive())
+        .map(|(address, (balance, block))| (address, balance.into_raw().to_string(), block))
+        .collect();
+    db.create_token_holder_snapshot(token, block_number, &holders, MAX_HOLDER_SNAPSHOTS)
+        .await
+}
+
+/// Whether the indexed data holds every balance at `block_number`: blocks are indexed
+/// without gaps from genesis up to it, or for a token from before its deployment, and ETH
+/// balance history snapshots close the period the block ends
+async fn snapshot_complete(
+    app: &App,
+    token: Option<&str>,
+    block_number: i64,
+) -> anyhow::Result<bool> {
+    let (first_block, indexed) = app.db.get_block_coverage(block_number).await?;
+    let Some(first_block) = first_block else {
+        return Ok(false);
+    };
+    if indexed != block_number - first_block + 1 {
+        return Ok(false);
+    }
+
+    Ok(match token {
+        Some(token) => {
+            first_block == 0
+                || app
+                    .db
+                    .get_contract_deployment(token)
+                    .await?
+                    .is_some_and(|deployment| deployment.block_number >= first_block)
+        }
+        None => {
+            let interval = app.config.balance_history_interval_blocks;
+            first_block == 0 && (block_number + 1) % interval == 0
+        }
+    })
+}
+
+/// Bounds of a holder export from its parameters, amounts without leading zeros. Zero
+/// balances are never exported.
+fn holder_range(params: &HolderExportParams) -> Result<HolderExportRange, String> {
+    let min_balance = match params.min_balance.as_deref() {
+        None => "1".to_string(),
+        Some(value) => match decimal_amount(value) {
+            Some(amount) if amount == "0" => "1".to_string(),
+            Some(amount) => amount,
+            None => return Err(format!("Invalid min_balance: {}", value)),
+        },
+    };
+    let after = match params.after.as_deref() {
+        None => None,
+        Some(after) => Some(
+            after
+                .split_once(':')
+                .and_then(|(balance, address)| {
+                    Some((decimal_amount(balance)?, parse_address(address)?))
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid after cursor {}, expected <balance>:<address>",
+                        after
+                    )
+                })?,
+        ),
+    };
+    if params.limit.is_some_and(|limit| limit < 0) {
+        return Err("limit must not be negative".to_string());
+    }
+
+    Ok(HolderExportRange {
+        min_balance,
+        after,
+        limit: params.limit,
+    })
+}
+
+/// Decimal amount without leading zeros, None unless it is up to 78 digits
+fn decimal_amount(amount: &str) -> Option<String> {
+    let amount = amount.trim();
+    if amount.is_empty() || amount.len() > 78 || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(match amount.trim_start_matches('0') {
+        "" => "0".to_string(),
+        digits => digits.to_string(),
+    })
+}
+
+/// Lowercase address, None unless it is 0x followed by 40 hex digits
+fn parse_address(address: &str) -> Option<String> {
+    let address = address.trim().to_lowercase();
+    (address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit()))
+    .then_some(address)
+}
@@ -1,4 +1,4 @@
-use axum::{Extension, Json};
+use axum::{http::StatusCode, Extension, Json};
 use serde_json::{json, Value};
 use std::sync::Arc;
 
@@ -8,13 +8,70 @@ use crate::App;
 pub async fn health_check(Extension(app): Extension<Arc<App>>) -> Json<Value> {
     // Get cached health status (updated every 60 seconds in background)
     let health_status = app.health_cache.get_health_status().await;
-    let is_indexer_running = app.indexer.is_running();
+    let indexer_status = app.indexer.get_status();
+
+    #[cfg(feature = "beacon")]
+    let (beacon_nodes, beacon_queue) = (app.beacon.node_stats(), Some(app.beacon.queue_stats()));
+    #[cfg(not(feature = "beacon"))]
+    let (beacon_nodes, beacon_queue): (
+        Vec<crate::rpc::ProviderStats>,
+        Option<crate::executor::QueueStats>,
+    ) = (Vec::new(), None);
+
+    // Network head minus the last indexed block, unknown until both are seen
+    let indexing_lag = match health_status.last_indexed_block {
+        Some(indexed) if indexer_status.latest_network_block > 0 => {
+            Some((indexer_status.latest_network_block - indexed).max(0))
+        }
+        _ => None,
+    };
 
     Json(json!({
-        "status": "ok",
-        "indexer_running": is_indexer_running,
+        "status": if app.health_cache.is_ready().await { "ok" } else { "degraded" },
+        "indexer_running": indexer_status.is_running,
         "version": env!("CARGO_PKG_VERSION"),
         "rpc_connected": health_status.rpc_connected,
+        "rpc_archive_node": app.rpc.is_archive_node(),
+        "rpc_providers": app.rpc.provider_stats(),
+        "rpc_method_usage": app.rpc.method_usage(),
+        "rpc_queue": app.rpc.queue_stats(),
+        "database_connected": health_status.database_connected,
+        "database_size_bytes": health_status.database_size_bytes,
+        "beacon_healthy": health_status.beacon_healthy,
+        "beacon_nodes": beacon_nodes,
+        "beacon_queue": beacon_queue,
+        "live_subscribers": app.live.stats(),
+        "latest_network_block": indexer_status.latest_network_block,
+        "last_indexed_block": health_status.last_indexed_block,
+        "last_block_timestamp": health_status.last_block_timestamp,
+        "last_block_indexed_at": indexer_status.last_block_indexed_at,
+        "indexing_lag": indexing_lag,
+        "queue_depth": indexer_status.queue_depth,
         "last_rpc_check": health_status.last_checked.elapsed().as_secs(),
     }))
 }
+
+/// Liveness probe, succeeds while the process is serving requests
+pub async fn health_live() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness probe, fails while the database, RPC or beacon node is unreachable
+pub async fn health_ready(Extension(app): Extension<Arc<App>>) -> (StatusCode, Json<Value>) {
+    let health_status = app.health_cache.get_health_status().await;
+    let code = if app.health_cache.is_ready().await {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        code,
+        Json(json!({
+            "status": if code == StatusCode::OK { "ready" } else { "not_ready" },
+            "database_connected": health_status.database_connected,
+            "rpc_connected": health_status.rpc_connected,
+            "beacon_healthy": health_status.beacon_healthy,
+        })),
+    )
+}
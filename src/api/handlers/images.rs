@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, Query},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use ethers::types::U256;
+use std::sync::Arc;
+
+use crate::{api::ApiError, database::ImageParams, App};
+
+/// Resized token logo, from the logo URI of its token list entry
+pub async fn get_token_logo(
+    Path(address): Path<String>,
+    Query(params): Query<ImageParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Response {
+    let image = app
+        .images
+        .token_logo(&address.to_lowercase(), params.size())
+        .await;
+    image_response(image, "Token has no logo")
+}
+
+/// Resized NFT image, from the metadata its tokenURI points to
+pub async fn get_nft_image(
+    Path((address, token_id)): Path<(String, String)>,
+    Query(params): Query<ImageParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Response {
+    let token_id = match token_id.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(&token_id).ok(),
+    };
+    let Some(token_id) = token_id else {
+        return ApiError::BadRequest("Invalid token id".to_string()).into_response();
+    };
+
+    let image = app
+        .images
+        .nft_image(&address.to_lowercase(), token_id, params.size())
+        .await;
+    image_response(image, "Token has no image")
+}
+
+/// PNG response of a proxied image, cacheable by browsers
+fn image_response(image: anyhow::Result<Option<Vec<u8>>>, missing: &str) -> Response {
+    match image {
+        Ok(Some(png)) => (
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "public, max-age=86400"),
+            ],
+            png,
+        )
+            .into_response(),
+        Ok(None) => ApiError::NotFound(missing.to_string()).into_response(),
+        Err(e) => ApiError::BadGateway(format!("Failed to fetch image: {:#}", e)).into_response(),
+    }
+}
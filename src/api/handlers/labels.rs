@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{
+    api::ApiError,
+    database::{AddressLabelParams, AddressLabelRequest},
+    App,
+};
+
+/// Get address labels, optionally of a single category
+pub async fn get_address_labels(
+    Query(params): Query<AddressLabelParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.get_address_labels(params.category.as_deref()).await {
+        Ok(labels) => Ok(Json(json!({
+            "labels": labels,
+            "total": labels.len()
+        }))),
+        Err(e) => Err(ApiError::internal("Failed to get address labels", e)),
+    }
+}
+
+/// Set the label of an address, replacing a seeded one
+pub async fn set_address_label(
+    Path(address): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<AddressLabelRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let address = address.to_lowercase();
+    if address.len() != 42
+        || !address.starts_with("0x")
+        || !address[2..].chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(ApiError::Unprocessable(format!(
+            "Invalid address: {}",
+            address
+        )));
+    }
+
+    let label = request.label.trim();
+    let category = request.category.trim().to_lowercase();
+    if label.is_empty() || category.is_empty() {
+        return Err(ApiError::Unprocessable(
+            "label and category must not be empty".to_string(),
+        ));
+    }
+
+    app.db
+        .set_address_label(&address, label, &category)
+        .await
+        .map_err(|e| ApiError::internal("Failed to set address label", e))?;
+
+    match app.db.get_address_label(&address).await {
+        Ok(label) => Ok(Json(json!({ "label": label }))),
+        Err(e) => Err(ApiError::internal("Failed to get address label", e)),
+    }
+}
+
+/// Remove the label of an address
+pub async fn delete_address_label(
+    Path(address): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.delete_address_label(&address).await {
+        Ok(deleted) => Ok(Json(json!({
+            "address": address.to_lowercase(),
+            "deleted": deleted
+        }))),
+        Err(e) => Err(ApiError::internal("Failed to delete address label", e)),
+    }
+}
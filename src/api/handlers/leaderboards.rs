@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{
+    analytics::LEADERBOARD_METRICS,
+    api::ApiError,
+    database::{LeaderboardEntry, LeaderboardParams},
+    App,
+};
+
+/// Daily leaderboard of a metric: fee-payers, gas-guzzlers or transfers
+pub async fn get_leaderboard(
+    Path(metric): Path<String>,
+    Query(params): Query<LeaderboardParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    if !LEADERBOARD_METRICS.contains(&metric.as_str()) {
+        return Err(ApiError::NotFound(format!(
+            "Unknown leaderboard {}, expected one of: {}",
+            metric,
+            LEADERBOARD_METRICS.join(", ")
+        )));
+    }
+    let Some(day_start) = params.day_start() else {
+        return Err(ApiError::BadRequest(
+            "date must be formatted as YYYY-MM-DD".to_string(),
+        ));
+    };
+    let date = chrono::DateTime::from_timestamp(day_start, 0)
+        .map(|date| date.format("%Y-%m-%d").to_string());
+
+    let leaderboard = match app.db.get_leaderboard(&metric, day_start).await {
+        Ok(Some(leaderboard)) => leaderboard,
+        Ok(None) => {
+            return Err(ApiError::NotFound(format!(
+                "No {} leaderboard computed for {}",
+                metric,
+                date.unwrap_or_default()
+            )));
+        }
+        Err(e) => return Err(ApiError::internal("Failed to fetch leaderboard", e)),
+    };
+
+    let mut entries: Vec<LeaderboardEntry> =
+        serde_json::from_str(&leaderboard.entries).unwrap_or_default();
+    entries.truncate(params.limit());
+
+    Ok(Json(json!({
+        "metric": metric,
+        "date": date,
+        "entries": entries,
+        "updated_at": leaderboard.updated_at
+    })))
+}
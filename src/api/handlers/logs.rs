@@ -0,0 +1,217 @@
+use axum::{
+    extract::Query,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Extension, Json,
+};
+use ethers::abi::Abi;
+use futures::StreamExt;
+use serde_json::json;
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
+
+use crate::{
+    api::ApiError,
+    contracts::decode_log,
+    database::{LogCursor, LogFilterParams, LogStreamParams},
+    live::{LiveLog, LogFilter},
+    App,
+};
+
+/// Query stored logs by address, topics and block range, like eth_getLogs. Without an
+/// address or topic0 the block range is required and capped, as nothing else narrows the
+/// scan.
+pub async fn get_logs(
+    Query(params): Query<LogFilterParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let is_hex = |value: &str, len: usize| {
+        value.len() == len
+            && value.starts_with("0x")
+            && value[2..].chars().all(|c| c.is_ascii_hexdigit())
+    };
+
+    if let Some(address) = params
+        .addresses()
+        .iter()
+        .find(|address| !is_hex(address, 42))
+    {
+        return Err(ApiError::Unprocessable(format!(
+            "Invalid address: {}",
+            address
+        )));
+    }
+    if let Some(topic) = params
+        .topics()
+        .iter()
+        .flatten()
+        .find(|topic| !is_hex(topic, 66))
+    {
+        return Err(ApiError::Unprocessable(format!("Invalid topic: {}", topic)));
+    }
+    if let (Some(from_block), Some(to_block)) = (params.from_block, params.to_block)
+        && from_block > to_block
+    {
+        return Err(ApiError::Unprocessable(
+            "from_block must not be greater than to_block".to_string(),
+        ));
+    }
+    if !params.is_indexed() {
+        let within_limit = match (params.from_block, params.to_block) {
+            (Some(from_block), Some(to_block)) => {
+                to_block - from_block < LogFilterParams::MAX_UNFILTERED_BLOCKS
+            }
+            _ => false,
+        };
+        if !within_limit {
+            return Err(ApiError::Unprocessable(format!(
+                "Filter by address or topic0, or set from_block and to_block spanning at most {} blocks",
+                LogFilterParams::MAX_UNFILTERED_BLOCKS
+            )));
+        }
+    }
+    let cursor = match params.cursor.as_deref() {
+        Some(cursor) => Some(LogCursor::parse(cursor).ok_or_else(|| {
+            ApiError::Unprocessable("Invalid cursor, expected block_number:log_index".to_string())
+        })?),
+        None => None,
+    };
+
+    let logs = app
+        .db
+        .get_filtered_logs(&params, cursor)
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch logs", e))?;
+    let next_cursor = logs
+        .last()
+        .filter(|_| logs.len() as i64 == params.limit())
+        .map(|log| LogCursor::after(log).to_string());
+
+    let logs: Vec<serde_json::Value> = logs
+        .into_iter()
+        .map(|log| {
+            let topics: Vec<&String> = [&log.topic0, &log.topic1, &log.topic2, &log.topic3]
+                .into_iter()
+                .flatten()
+                .collect();
+
+            json!({
+                "transaction_hash": log.transaction_hash,
+                "block_number": log.block_number,
+                "log_index": log.log_index,
+                "address": log.address,
+                "topics": topics,
+                "data": log.data
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "logs": logs,
+        "pagination": {
+            "per_page": params.limit(),
+            "has_next": next_cursor.is_some(),
+            "next_cursor": next_cursor
+        },
+        "filters": {
+            "address": params.address,
+            "topic0": params.topic0,
+            "topic1": params.topic1,
+            "topic2": params.topic2,
+            "topic3": params.topic3,
+            "from_block": params.from_block,
+            "to_block": params.to_block
+        }
+    })))
+}
+
+/// Stream logs of the given contracts as server-sent events while blocks are indexed
+pub async fn stream_logs(
+    Query(params): Query<LogStreamParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Response {
+    let addresses: Vec<String> = params
+        .address
+        .split(',')
+        .map(|address| address.trim().to_lowercase())
+        .filter(|address| !address.is_empty())
+        .collect();
+
+    if addresses.is_empty()
+        || addresses
+            .iter()
+            .any(|address| address.len() != 42 || !address.starts_with("0x"))
+    {
+        return ApiError::Unprocessable(
+            "address must be a comma-separated list of contract addresses".to_string(),
+        )
+        .into_response();
+    }
+
+    // ABIs are resolved once per connection
+    let mut abis: HashMap<String, Abi> = HashMap::new();
+    for address in &addresses {
+        match app.contracts.event_abi(address).await {
+            Ok(abi) => {
+                abis.insert(address.clone(), abi);
+            }
+            Err(e) => {
+                return ApiError::internal(&format!("Failed to load ABI of {}", address), e)
+                    .into_response();
+            }
+        }
+    }
+
+    let filter = LogFilter {
+        addresses: addresses.into_iter().collect(),
+        topic0: params.topic0.map(|topic| topic.to_lowercase()),
+    };
+
+    let Some(subscription) = app.live.subscribe_logs(filter) else {
+        return ApiError::Unavailable(format!(
+            "Too many live subscribers (at most {}), retry later",
+            app.live.stats().max_subscribers
+        ))
+        .into_response();
+    };
+
+    let events = subscription.map(move |item| {
+        let event = match item {
+            LiveLog::Log(log) => {
+                let decoded = abis.get(&log.address).and_then(|abi| decode_log(abi, &log));
+                let (event, params) = decoded.unzip();
+                let topics: Vec<&String> = [&log.topic0, &log.topic1, &log.topic2, &log.topic3]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+
+                Event::default().event("log").data(
+                    json!({
+                        "transaction_hash": log.transaction_hash,
+                        "block_number": log.block_number,
+                        "log_index": log.log_index,
+                        "address": log.address,
+                        "topics": topics,
+                        "data": log.data,
+                        "event": event,
+                        "params": params
+                    })
+                    .to_string(),
+                )
+            }
+            LiveLog::Lagged(skipped) => Event::default()
+                .event("lagged")
+                .data(json!({ "skipped": skipped }).to_string()),
+            LiveLog::Evicted => Event::default()
+                .event("evicted")
+                .data(json!({ "reason": "slow consumer" }).to_string()),
+        };
+
+        Ok::<_, Infallible>(event)
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
@@ -0,0 +1,58 @@
+use axum::{extract::Query, Extension, Json};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{api::ApiError, database::PaginationParams, mempool::GasPriceDistribution, App};
+
+/// Get pending transactions with the gas price distribution of the mempool
+pub async fn get_mempool(
+    Query(params): Query<PaginationParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    if !app.config.mempool_enabled {
+        return Err(ApiError::Unavailable(
+            "Mempool watcher is disabled (set MEMPOOL_ENABLED=true)".to_string(),
+        ));
+    }
+
+    let limit = params.limit();
+    let (transactions, total) = app
+        .db
+        .get_pending_transactions(limit, params.offset())
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch pending transactions", e))?;
+
+    let prices = app
+        .db
+        .get_pending_gas_prices()
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch pending gas prices", e))?;
+
+    let gas_price = GasPriceDistribution::from_wei(
+        prices
+            .iter()
+            .filter_map(|(gas_price, _)| gas_price.parse::<u128>().ok()),
+    );
+    let priority_fee = GasPriceDistribution::from_wei(
+        prices
+            .iter()
+            .filter_map(|(_, fee)| fee.as_ref()?.parse::<u128>().ok()),
+    );
+
+    let page = params.page.unwrap_or(1);
+    let total_pages = (total as f64 / limit as f64).ceil() as i64;
+
+    Ok(Json(json!({
+        "pending_count": total,
+        "gas_price_gwei": gas_price,
+        "priority_fee_gwei": priority_fee,
+        "transactions": transactions,
+        "pagination": {
+            "current_page": page,
+            "per_page": limit,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": (page as i64) < total_pages
+        }
+    })))
+}
@@ -1,17 +1,76 @@
 mod accounts;
+mod admin;
 mod blocks;
+mod charts;
+mod contracts;
+mod deposits;
+mod epochs;
+mod export;
 mod health;
+mod images;
+mod labels;
+mod leaderboards;
+mod logs;
+mod mempool;
 mod network;
 mod search;
 mod stats;
 mod tokens;
 mod transactions;
+mod transfers;
+mod validators;
+mod watchlists;
+mod webhooks;
+mod withdrawals;
 
 pub use accounts::*;
+pub use admin::*;
 pub use blocks::*;
+pub use charts::*;
+pub use contracts::*;
+pub use deposits::*;
+pub use epochs::*;
+pub use export::*;
 pub use health::*;
+pub use images::*;
+pub use labels::*;
+pub use leaderboards::*;
+pub use logs::*;
+pub use mempool::*;
 pub use network::*;
 pub use search::*;
 pub use stats::*;
 pub use tokens::*;
 pub use transactions::*;
+pub use transfers::*;
+pub use validators::*;
+pub use watchlists::*;
+pub use webhooks::*;
+pub use withdrawals::*;
+
+use serde_json::{json, Value};
+
+use crate::{database::TokenTransfer, App};
+
+/// Add the approximate `value_usd` of each of `transfers` to its JSON, with the
+/// `price_source` it was valued with
+async fn add_usd_values(app: &App, transfers: &[TokenTransfer], transfers_json: &mut [Value]) {
+    let values = app.market_data.transfer_values(transfers).await;
+    for (transfer, value) in transfers_json.iter_mut().zip(values) {
+        transfer["value_usd"] = json!(value.value_usd);
+        transfer["price_source"] = json!(value.price_source);
+    }
+}
+
+/// Whether `address` is 0x followed by 40 hex digits
+fn is_address(address: &str) -> bool {
+    address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Format a Gwei amount as ETH with full precision
+fn gwei_to_eth_string(gwei: i64) -> String {
+    const GWEI_PER_ETH: i64 = 1_000_000_000;
+    format!("{}.{:09}", gwei / GWEI_PER_ETH, gwei % GWEI_PER_ETH)
+}
@@ -1,8 +1,38 @@
-use axum::{Extension, Json};
+use axum::{extract::Query, Extension, Json};
+use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
 
-use crate::App;
+use crate::{api::ApiError, networks::Networks, App};
+
+#[derive(Deserialize)]
+pub struct LivenessQuery {
+    pub blocks: Option<i64>, // Newest indexed blocks to inspect
+}
+
+#[derive(Deserialize)]
+pub struct ReorgQuery {
+    pub limit: Option<i64>, // Recent reorgs to return
+}
+
+/// List the networks served by this process with their indexing progress
+pub async fn get_networks(
+    Extension(networks): Extension<Arc<Networks>>,
+) -> Json<serde_json::Value> {
+    let mut list = Vec::new();
+    for (name, app) in networks.iter() {
+        let latest_indexed_block = app.db.get_latest_block_number().await.unwrap_or(None);
+        list.push(json!({
+            "name": name,
+            "default": name == networks.default_name(),
+            "api_prefix": format!("/api/{}", name),
+            "indexer_running": app.indexer.is_running(),
+            "latest_indexed_block": latest_indexed_block
+        }));
+    }
+
+    Json(json!({ "networks": list }))
+}
 
 /// Get network-wide statistics
 pub async fn get_network_stats(Extension(app): Extension<Arc<App>>) -> Json<serde_json::Value> {
@@ -23,3 +53,106 @@ pub async fn get_network_stats(Extension(app): Extension<Arc<App>>) -> Json<serd
         "timestamp": chrono::Utc::now().timestamp()
     }))
 }
+
+/// Get block production liveness: slot delays, missed-slot streaks and node head progress
+pub async fn get_network_liveness(
+    Query(query): Query<LivenessQuery>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let window = query.blocks.unwrap_or(100).clamp(2, 10_000);
+
+    match app.liveness.report(window).await {
+        Ok(report) => Ok(Json(json!({
+            "liveness": report,
+            "timestamp": chrono::Utc::now().timestamp()
+        }))),
+        Err(e) => Err(ApiError::internal("Failed to compute liveness", e)),
+    }
+}
+
+/// Get reorg statistics: a histogram of reorg depths and the most recent reorgs
+pub async fn get_network_reorgs(
+    Query(query): Query<ReorgQuery>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 500);
+
+    let stats = async {
+        let histogram = app.db.get_reorg_depth_histogram().await?;
+        let recent = app.db.get_recent_reorgs(limit).await?;
+        anyhow::Ok((histogram, recent))
+    }
+    .await;
+
+    match stats {
+        Ok((histogram, recent)) => {
+            let total: i64 = histogram.iter().map(|(_, count)| count).sum();
+            let blocks_replaced: i64 = histogram.iter().map(|(depth, count)| depth * count).sum();
+            let recent: Vec<_> = recent
+                .into_iter()
+                .map(|reorg| {
+                    json!({
+                        "id": reorg.id,
+                        "first_block": reorg.first_block,
+                        "depth": reorg.depth,
+                        "replaced_blocks": serde_json::from_str::<serde_json::Value>(&reorg.replaced_blocks).ok(),
+                        "created_at": reorg.created_at
+                    })
+                })
+                .collect();
+
+            Ok(Json(json!({
+                "total_reorgs": total,
+                "blocks_replaced": blocks_replaced,
+                "max_depth": histogram.last().map(|(depth, _)| depth),
+                "average_depth": if total > 0 {
+                    blocks_replaced as f64 / total as f64
+                } else {
+                    0.0
+                },
+                "depth_histogram": histogram
+                    .iter()
+                    .map(|(depth, count)| json!({ "depth": depth, "count": count }))
+                    .collect::<Vec<_>>(),
+                "recent": recent,
+                "timestamp": chrono::Utc::now().timestamp()
+            })))
+        }
+        Err(e) => Err(ApiError::internal("Failed to get reorg statistics", e)),
+    }
+}
+
+/// Get the justified and finalized checkpoints and how far indexing is ahead of finality
+pub async fn get_network_finality(
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let finality = async {
+        let checkpoints = app.db.get_finality_checkpoints().await?;
+        let latest_block = app.db.get_latest_block_number().await?;
+        anyhow::Ok((checkpoints, latest_block))
+    }
+    .await;
+
+    match finality {
+        Ok((checkpoints, latest_block)) => {
+            let checkpoint = |kind: &str| {
+                checkpoints
+                    .iter()
+                    .find(|checkpoint| checkpoint.kind == kind)
+            };
+            let finalized_block =
+                checkpoint("finalized").and_then(|checkpoint| checkpoint.block_number);
+
+            Ok(Json(json!({
+                "justified": checkpoint("justified"),
+                "finalized": checkpoint("finalized"),
+                "latest_indexed_block": latest_block,
+                "unfinalized_blocks": latest_block
+                    .zip(finalized_block)
+                    .map(|(latest, finalized)| (latest - finalized).max(0)),
+                "timestamp": chrono::Utc::now().timestamp()
+            })))
+        }
+        Err(e) => Err(ApiError::internal("Failed to fetch finality", e)),
+    }
+}
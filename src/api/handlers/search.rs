@@ -1,60 +1,183 @@
-use axum::{extract::Path, Extension, Json};
-use serde_json::json;
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use serde_json::{json, Value};
 use std::sync::Arc;
 
-use crate::App;
+use crate::{api::ApiError, database::SearchParams, ens::EnsService, App};
 
-/// Search for blocks, transactions, or accounts
+/// Hex digits a partial hash or address needs before prefix search kicks in
+const MIN_PREFIX_DIGITS: usize = 4;
+
+/// Relevance of exact block number, hash and address matches
+const EXACT_SCORE: i64 = 100;
+/// Relevance of addresses resolved from ENS names
+const ENS_SCORE: i64 = 90;
+/// Relevance of partial hash and address matches
+const PREFIX_SCORE: i64 = 40;
+
+/// Search blocks, transactions, accounts and tokens by block number, full or partial
+/// hash or address, ENS name, token name and symbol or address label. Results are typed
+/// and ordered by relevance; `type` and `result` repeat the best match.
 pub async fn search(
     Path(query): Path<String>,
+    Query(params): Query<SearchParams>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
+    Ok(Json(run_search(&app, &query, params.limit()).await))
+}
+
+/// Same as `search`, with the query in `q`
+pub async fn search_by_query(
+    Query(params): Query<SearchParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match params.q.as_deref().map(str::trim) {
+        Some(query) if !query.is_empty() => Ok(Json(run_search(&app, query, params.limit()).await)),
+        _ => Err(ApiError::BadRequest("Missing search query q".to_string())),
+    }
+}
+
+async fn run_search(app: &App, query: &str, limit: usize) -> Value {
     let db = &app.db;
     let query = query.trim();
+    let mut results = Vec::new();
 
     // Try to parse as number for block search
-    if let Ok(block_num) = query.parse::<i64>() {
-        if let Ok(Some(block)) = db.get_block_by_number(block_num).await {
-            return Json(json!({
-                "type": "block",
-                "result": block
-            }));
-        }
+    if let Ok(block_num) = query.parse::<i64>()
+        && let Ok(Some(block)) = db.get_block_by_number(block_num).await
+    {
+        results.push(search_result("block", EXACT_SCORE, json!(block)));
     }
 
-    // Check if it looks like a block hash (0x followed by 64 hex chars)
-    if query.starts_with("0x") && query.len() == 66 {
-        // Try as block hash
-        if let Ok(Some(block)) = db.get_block_by_hash(query).await {
-            return Json(json!({
-                "type": "block",
-                "result": block
-            }));
+    if let Some(hex) = hex_query(query) {
+        search_hex(app, &hex, limit as i64, &mut results).await;
+    } else {
+        // Check if it looks like an ENS name (e.g. vitalik.eth)
+        if EnsService::is_ens_name(query)
+            && let Ok(Some(address)) = app.ens.resolve_name(query).await
+        {
+            let account = db.get_account_by_address(&address).await.ok().flatten();
+            let mut result = search_result("account", ENS_SCORE, json!(account));
+            result["address"] = json!(address);
+            result["ens_name"] = json!(query.to_lowercase());
+            results.push(result);
+        }
+
+        if let Ok(tokens) = db.search_tokens_by_name(query, limit as i64).await {
+            for token in tokens {
+                results.push(search_result("token", token.score, json!(token.token)));
+            }
         }
 
-        // Try as transaction hash
-        if let Ok(Some(tx)) = db.get_transaction_by_hash(query).await {
-            return Json(json!({
-                "type": "transaction",
-                "result": tx
-            }));
+        if let Ok(labels) = db.search_address_labels(query, limit as i64).await {
+            for label in labels {
+                let account = db
+                    .get_account_by_address(&label.label.address)
+                    .await
+                    .ok()
+                    .flatten();
+                let mut result = search_result("account", label.score, json!(account));
+                result["address"] = json!(label.label.address);
+                result["label"] = json!(label.label);
+                results.push(result);
+            }
         }
     }
 
-    // Check if it looks like an address (0x followed by 40 hex chars)
-    if query.starts_with("0x") && query.len() == 42 {
-        if let Ok(Some(account)) = db.get_account_by_address(query).await {
-            return Json(json!({
-                "type": "account",
-                "result": account
-            }));
+    // Ties keep the order of the sources: blocks, transactions, accounts, then tokens
+    results.sort_by_key(|result| std::cmp::Reverse(result["score"].as_i64().unwrap_or(0)));
+    results.truncate(limit);
+
+    let mut response = match results.first() {
+        Some(best) => best.clone(),
+        None => json!({
+            "type": "unknown",
+            "result": null,
+            "message": "No matching block, transaction, account, token, label or ENS name found"
+        }),
+    };
+    response["results"] = json!(results);
+    response
+}
+
+/// Look a hex query up: full block and transaction hashes and addresses exactly, shorter
+/// ones as prefixes of every kind
+async fn search_hex(app: &App, hex: &str, limit: i64, results: &mut Vec<Value>) {
+    let db = &app.db;
+    match hex.len() {
+        66 => {
+            if let Ok(Some(block)) = db.get_block_by_hash(hex).await {
+                results.push(search_result("block", EXACT_SCORE, json!(block)));
+            }
+            if let Ok(Some(tx)) = db.get_transaction_by_hash(hex).await {
+                results.push(search_result("transaction", EXACT_SCORE, json!(tx)));
+            }
+        }
+        42 => {
+            if let Ok(Some(account)) = db.get_account_by_address(hex).await {
+                let mut result = search_result("account", EXACT_SCORE, json!(account));
+                result["ens_name"] = json!(app.ens.primary_name(hex).await);
+                results.push(result);
+            }
+            if let Ok(Some(token)) = db.get_token_by_address(hex).await {
+                results.push(search_result("token", EXACT_SCORE, json!(token)));
+            }
+        }
+        _ => {}
+    }
+
+    if hex.len() < 2 + MIN_PREFIX_DIGITS {
+        return;
+    }
+    if hex.len() < 66 {
+        if let Ok(blocks) = db.search_blocks_by_hash_prefix(hex, limit).await {
+            results.extend(
+                blocks
+                    .into_iter()
+                    .map(|block| search_result("block", PREFIX_SCORE, json!(block))),
+            );
+        }
+        if let Ok(transactions) = db.search_transactions_by_hash_prefix(hex, limit).await {
+            results.extend(
+                transactions
+                    .into_iter()
+                    .map(|tx| search_result("transaction", PREFIX_SCORE, json!(tx))),
+            );
         }
     }
+    if hex.len() < 42 {
+        if let Ok(accounts) = db.search_accounts_by_address_prefix(hex, limit).await {
+            results.extend(
+                accounts
+                    .into_iter()
+                    .map(|account| search_result("account", PREFIX_SCORE, json!(account))),
+            );
+        }
+        if let Ok(tokens) = db.search_tokens_by_address_prefix(hex, limit).await {
+            results.extend(
+                tokens
+                    .into_iter()
+                    .map(|token| search_result("token", PREFIX_SCORE, json!(token))),
+            );
+        }
+    }
+}
+
+/// Lowercase query when it is 0x followed by hex digits only
+fn hex_query(query: &str) -> Option<String> {
+    let query = query.to_lowercase();
+    let digits = query.strip_prefix("0x")?;
+    (!digits.is_empty() && digits.len() <= 64 && digits.chars().all(|c| c.is_ascii_hexdigit()))
+        .then_some(query)
+}
 
-    // Nothing found
-    Json(json!({
-        "type": "unknown",
-        "result": null,
-        "message": "No matching block, transaction, or account found"
-    }))
+/// Typed search result
+fn search_result(kind: &str, score: i64, result: Value) -> Value {
+    json!({
+        "type": kind,
+        "score": score,
+        "result": result
+    })
 }
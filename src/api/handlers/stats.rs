@@ -1,4 +1,4 @@
-use crate::{database::IndexerStats, App};
+use crate::{api::ApiError, database::IndexerStats, App};
 use axum::{Extension, Json};
 use serde_json::json;
 use std::sync::Arc;
@@ -57,23 +57,34 @@ pub async fn get_stats(Extension(app): Extension<Arc<App>>) -> Json<IndexerStats
         "stopped"
     };
 
-    // Calculate sync percentage (assume we start from genesis block)
+    // Sync progress covers the blocks from the configured start block to the chain tip
     let latest_chain_block = app
         .rpc
         .get_latest_block_number()
         .await
-        .unwrap_or(latest_block as u64);
+        .map(|block| block as i64)
+        .unwrap_or(latest_block);
+    let first_block = start_block.max(0);
+    let (range_blocks, range_transactions_indexed, range_transactions_declared) =
+        if latest_block >= first_block {
+            db.get_range_indexing_counts(first_block, latest_block)
+                .await
+                .unwrap_or((0, 0, 0))
+        } else {
+            (0, 0, 0)
+        };
 
-    let sync_percentage = if latest_chain_block > 0 {
-        (latest_block as f64 / latest_chain_block as f64) * 100.0
+    let expected_blocks = latest_chain_block - first_block + 1;
+    let sync_percentage = if expected_blocks > 0 {
+        (range_blocks as f64 / expected_blocks as f64 * 100.0).min(100.0)
     } else {
         0.0
     };
 
-    // Calculate transaction indexing percentage (only for blocks we're tracking)
-    // Use total_transactions_declared as the target (includes skipped transactions as expected)
-    let transaction_indexing_percentage = if total_transactions_declared > 0 {
-        (total_transactions_indexed as f64 / total_transactions_declared as f64) * 100.0
+    // Transaction indexing compares stored transactions with the counts declared by the
+    // indexed blocks of the same range
+    let transaction_indexing_percentage = if range_transactions_declared > 0 {
+        (range_transactions_indexed as f64 / range_transactions_declared as f64) * 100.0
     } else {
         100.0
     };
@@ -99,6 +110,7 @@ pub async fn get_stats(Extension(app): Extension<Arc<App>>) -> Json<IndexerStats
         start_block: start_block as i64,
         current_block_tx_indexed,
         current_block_tx_declared,
+        block_consistency: app.indexer.consistency_report(),
     })
 }
 
@@ -110,3 +122,39 @@ pub async fn get_network_latest(Extension(app): Extension<Arc<App>>) -> Json<ser
         "latest_network_block": latest_network_block
     }))
 }
+
+/// Get transactions per second over the last 1, 5 and 15 minutes of indexed blocks
+pub async fn get_tps(
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match app.network_stats.get_tps().await {
+        Ok(stats) => Ok(Json(json!(stats))),
+        Err(e) => Err(ApiError::internal("Failed to compute TPS", e)),
+    }
+}
+
+/// Figures of the explorer header: ETH market data when a provider is configured, the
+/// newest indexed and network blocks and the transactions per second of the last minute
+pub async fn get_header_stats(Extension(app): Extension<Arc<App>>) -> Json<serde_json::Value> {
+    let latest_block = app.db.get_latest_block_number().await.unwrap_or(None);
+    let latest_network_block = app.network_stats.get_latest_network_block().await;
+    let tps = app
+        .network_stats
+        .get_tps()
+        .await
+        .ok()
+        .and_then(|stats| stats.windows.first().and_then(|window| window.tps));
+    let market = app.market_data.latest();
+
+    Json(json!({
+        "eth_price_usd": market.as_ref().map(|market| market.price_usd),
+        "eth_price_btc": market.as_ref().and_then(|market| market.price_btc),
+        "eth_price_change_24h": market.as_ref().and_then(|market| market.change_24h_percentage),
+        "market_cap_usd": market.as_ref().and_then(|market| market.market_cap_usd),
+        "market_data_source": market.as_ref().map(|market| market.source),
+        "market_data_updated_at": market.as_ref().map(|market| market.updated_at),
+        "latest_block": latest_block,
+        "latest_network_block": latest_network_block,
+        "tps": tps
+    }))
+}
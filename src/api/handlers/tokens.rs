@@ -1,9 +1,18 @@
-use crate::App;
-use axum::{extract::Query, response::Json, Extension};
+use crate::{
+    api::ApiError,
+    database::{DelegateParams, TokenListImportRequest, TokenMetadataOverride, TokenSort},
+    token_metadata, App,
+};
+use axum::{
+    extract::{Path, Query},
+    response::Json,
+    Extension,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
-use tracing::error;
+
+use super::is_address;
 
 #[derive(Debug, Deserialize)]
 pub struct TokenBalanceQuery {
@@ -25,148 +34,222 @@ pub struct TokenBalanceResponse {
 pub async fn get_token_balances(
     Query(params): Query<TokenBalanceQuery>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<Value> {
+) -> Result<Json<Value>, ApiError> {
     let account_address = params.account;
 
     // If specific token requested
     if let Some(token_address) = params.token {
-        match app.db.get_token_balance(&account_address, &token_address).await {
-            Ok(Some(balance)) => {
-                // Get token info
-                match app.db.get_token_by_address(&token_address).await {
-                    Ok(Some(token)) => {
-                        let response = TokenBalanceResponse {
-                            token_address: token.address,
-                            token_name: token.name,
-                            token_symbol: token.symbol,
-                            token_decimals: token.decimals,
-                            balance: balance.balance,
-                            last_updated_block: balance.last_updated_block,
-                        };
-                        return Json(json!({ "balance": response }));
-                    }
-                    Ok(None) => {
-                        return Json(json!({ "error": "Token not found" }));
-                    }
-                    Err(e) => {
-                        error!("Failed to get token info: {}", e);
-                        return Json(json!({ "error": "Failed to get token info" }));
-                    }
-                }
-            }
-            Ok(None) => {
-                return Json(json!({ "error": "Token balance not found" }));
-            }
-            Err(e) => {
-                error!("Failed to get token balance: {}", e);
-                return Json(json!({ "error": "Failed to get token balance" }));
-            }
-        }
+        let balance = app
+            .db
+            .get_token_balance(&account_address, &token_address)
+            .await
+            .map_err(|e| ApiError::internal("Failed to get token balance", e))?
+            .ok_or_else(|| ApiError::NotFound("Token balance not found".to_string()))?;
+        let token = app
+            .db
+            .get_token_by_address(&token_address)
+            .await
+            .map_err(|e| ApiError::internal("Failed to get token info", e))?
+            .ok_or_else(|| ApiError::NotFound("Token not found".to_string()))?;
+
+        let response = TokenBalanceResponse {
+            token_address: token.address,
+            token_name: token.name,
+            token_symbol: token.symbol,
+            token_decimals: token.decimals,
+            balance: balance.balance,
+            last_updated_block: balance.last_updated_block,
+        };
+        return Ok(Json(json!({ "balance": response })));
     }
 
     // Get all token balances for the account
-    match app.token_service.get_account_token_info(&account_address).await {
-        Ok(token_balances) => {
-            let balances: Vec<TokenBalanceResponse> = token_balances
-                .into_iter()
-                .map(|(token, balance)| TokenBalanceResponse {
-                    token_address: token.address,
-                    token_name: token.name,
-                    token_symbol: token.symbol,
-                    token_decimals: token.decimals,
-                    balance: balance.balance,
-                    last_updated_block: balance.last_updated_block,
-                })
-                .collect();
-
-            Json(json!({
-                "account": account_address,
-                "balances": balances,
-                "total_tokens": balances.len()
-            }))
-        }
-        Err(e) => {
-            error!("Failed to get account token balances: {}", e);
-            Json(json!({ "error": "Failed to get token balances" }))
-        }
-    }
+    let token_balances = app
+        .db
+        .get_account_token_info(&account_address)
+        .await
+        .map_err(|e| ApiError::internal("Failed to get token balances", e))?;
+    let balances: Vec<TokenBalanceResponse> = token_balances
+        .into_iter()
+        .map(|(token, balance)| TokenBalanceResponse {
+            token_address: token.address,
+            token_name: token.name,
+            token_symbol: token.symbol,
+            token_decimals: token.decimals,
+            balance: balance.balance,
+            last_updated_block: balance.last_updated_block,
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "account": account_address,
+        "balances": balances,
+        "total_tokens": balances.len()
+    })))
 }
 
 /// Get token holders for a specific token
 pub async fn get_token_holders(
     Query(params): Query<serde_json::Value>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<Value> {
-    let token_address = match params.get("token").and_then(|v| v.as_str()) {
-        Some(addr) => addr,
-        None => return Json(json!({ "error": "Token address is required" })),
-    };
+) -> Result<Json<Value>, ApiError> {
+    let token_address = params
+        .get("token")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ApiError::BadRequest("Token address is required".to_string()))?;
 
-    let offset = params
-        .get("offset")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(0);
+    let offset = params.get("offset").and_then(|v| v.as_i64()).unwrap_or(0);
     let limit = params
         .get("limit")
         .and_then(|v| v.as_i64())
         .unwrap_or(50)
         .min(100); // Cap at 100
 
-    match app.db.get_token_holders(token_address, offset, limit).await {
-        Ok(holders) => {
-            // Get token info
-            match app.db.get_token_by_address(token_address).await {
-                Ok(Some(token)) => {
-                    Json(json!({
-                        "token": {
-                            "address": token.address,
-                            "name": token.name,
-                            "symbol": token.symbol,
-                            "decimals": token.decimals
-                        },
-                        "holders": holders,
-                        "total_holders": holders.len()
-                    }))
-                }
-                Ok(None) => Json(json!({ "error": "Token not found" })),
-                Err(e) => {
-                    error!("Failed to get token info: {}", e);
-                    Json(json!({ "error": "Failed to get token info" }))
-                }
-            }
-        }
-        Err(e) => {
-            error!("Failed to get token holders: {}", e);
-            Json(json!({ "error": "Failed to get token holders" }))
-        }
-    }
+    let holders = app
+        .db
+        .get_token_holders(token_address, offset, limit)
+        .await
+        .map_err(|e| ApiError::internal("Failed to get token holders", e))?;
+    let token = app
+        .db
+        .get_token_by_address(token_address)
+        .await
+        .map_err(|e| ApiError::internal("Failed to get token info", e))?
+        .ok_or_else(|| ApiError::NotFound("Token not found".to_string()))?;
+
+    Ok(Json(json!({
+        "token": {
+            "address": token.address,
+            "name": token.name,
+            "symbol": token.symbol,
+            "decimals": token.decimals
+        },
+        "holders": holders,
+        "total_holders": holders.len()
+    })))
 }
 
-/// Get list of known tokens
+/// Get list of known tokens, by total transfers, holder count or recent activity
 pub async fn get_tokens(
     Query(params): Query<serde_json::Value>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<Value> {
-    let offset = params
-        .get("offset")
-        .and_then(|v| v.as_i64())
-        .unwrap_or(0);
+) -> Result<Json<Value>, ApiError> {
+    let offset = params.get("offset").and_then(|v| v.as_i64()).unwrap_or(0);
     let limit = params
         .get("limit")
         .and_then(|v| v.as_i64())
         .unwrap_or(50)
         .min(100); // Cap at 100
+    let sort = match params.get("sort").and_then(|v| v.as_str()) {
+        Some(sort) => match TokenSort::parse(sort) {
+            Some(sort) => sort,
+            None => {
+                return Err(ApiError::BadRequest(
+                    "Invalid sort, expected transfers, holders or activity".to_string(),
+                ));
+            }
+        },
+        None => TokenSort::default(),
+    };
 
-    match app.db.get_tokens(offset, limit).await {
-        Ok(tokens) => {
-            Json(json!({
-                "tokens": tokens,
-                "total": tokens.len()
-            }))
-        }
-        Err(e) => {
-            error!("Failed to get tokens: {}", e);
-            Json(json!({ "error": "Failed to get tokens" }))
+    let tokens = app
+        .db
+        .get_tokens(offset, limit, sort)
+        .await
+        .map_err(|e| ApiError::internal("Failed to get tokens", e))?;
+
+    Ok(Json(json!({
+        "tokens": tokens,
+        "total": tokens.len()
+    })))
+}
+
+/// Set the metadata of a token by hand, overriding on-chain and token list values
+pub async fn set_token_metadata_override(
+    Path(address): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+    Json(metadata): Json<TokenMetadataOverride>,
+) -> Result<Json<Value>, ApiError> {
+    let address = address.to_lowercase();
+    if !is_address(&address) {
+        return Err(ApiError::Unprocessable(format!(
+            "Invalid token address: {}",
+            address
+        )));
+    }
+
+    app.db
+        .set_token_metadata_override(&address, &metadata)
+        .await
+        .map_err(|e| ApiError::internal("Failed to set token metadata override", e))?;
+    let token = app
+        .db
+        .get_token_by_address(&address)
+        .await
+        .map_err(|e| ApiError::internal("Failed to get token", e))?;
+
+    Ok(Json(json!({
+        "address": address,
+        "override": metadata,
+        "token": token
+    })))
+}
+
+/// Delegates of an ERC-20 Votes token by voting power, decoded from its DelegateChanged
+/// and DelegateVotesChanged events
+pub async fn get_token_delegates(
+    Path(address): Path<String>,
+    Query(params): Query<DelegateParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let token_address = address.to_lowercase();
+    let (delegates, total) = app
+        .db
+        .get_token_delegates(&token_address, params.limit(), params.offset())
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch token delegates", e))?;
+
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    Ok(Json(json!({
+        "token_address": token_address,
+        "delegates": delegates,
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": current_page < total_pages
         }
+    })))
+}
+
+/// Import a token list as curated tokens, preferred over discovered metadata. Only the
+/// lists of TOKEN_LIST_URLS are fetched, other lists are imported from the command line.
+pub async fn import_token_list(
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<TokenListImportRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let url = request.url.trim();
+    if !app.config.token_list_urls.iter().any(|list| list == url) {
+        return Err(ApiError::Unprocessable(format!(
+            "{} is not one of TOKEN_LIST_URLS",
+            url
+        )));
     }
+
+    let chain_id = match request.chain_id {
+        Some(chain_id) => chain_id,
+        None => app
+            .rpc
+            .get_chain_id()
+            .await
+            .map_err(|e| ApiError::internal("Failed to get chain id", e))?,
+    };
+
+    let import = token_metadata::import_token_list(&app.db, url, chain_id)
+        .await
+        .map_err(|e| ApiError::internal("Failed to import token list", e))?;
+    Ok(Json(json!(import)))
 }
@@ -1,54 +1,106 @@
-use crate::{database::PaginationParams, App};
+use crate::{
+    api::ApiError,
+    database::{
+        PaginationParams, TransactionCursor, TransactionMethodParams, TransactionResponse,
+        TransferValueParams,
+    },
+    executor::RpcOverloaded,
+    App,
+};
 use axum::{
     extract::{Path, Query},
     Extension, Json,
 };
-use serde_json::json;
+use serde_json::{json, Value};
 use std::sync::Arc;
+use tracing::debug;
+
+use super::add_usd_values;
 
 /// Get recent transactions with pagination
 pub async fn get_transactions(
     Query(params): Query<PaginationParams>,
+    Query(method): Query<TransactionMethodParams>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
     let limit = params.limit();
     let offset = params.offset();
 
-    let txs = db
-        .get_recent_transactions(limit, offset)
-        .await
-        .unwrap_or_default();
+    let method_id = method.method_id().map_err(ApiError::BadRequest)?;
+    let cursor = match params.cursor.as_deref() {
+        Some(cursor) => match TransactionCursor::parse(cursor) {
+            Some(cursor) => Some(cursor),
+            None => {
+                return Err(ApiError::BadRequest(
+                    "Invalid cursor, expected block_number:transaction_index".to_string(),
+                ));
+            }
+        },
+        None => None,
+    };
 
-    let total = db.get_transaction_count().await.unwrap_or(0);
+    let txs = match (&method_id, cursor) {
+        (Some(method_id), cursor) => {
+            // The cursor replaces the offset
+            let offset = if cursor.is_some() { 0 } else { offset };
+            db.get_transactions_by_method(method_id, cursor, limit, offset)
+                .await
+        }
+        (None, Some(cursor)) => db.get_transactions_before(cursor, limit).await,
+        (None, None) => match app.recent.recent_transactions(limit, offset) {
+            Some(txs) => Ok(txs),
+            None => db.get_recent_transactions(limit, offset).await,
+        },
+    }
+    .unwrap_or_default();
+    let next_cursor = txs
+        .last()
+        .filter(|_| txs.len() as i64 == limit)
+        .map(|tx| TransactionCursor::after(tx).to_string());
+    let txs: Vec<TransactionResponse> = txs.into_iter().map(TransactionResponse::from).collect();
+
+    let total = match &method_id {
+        Some(method_id) => db.get_transaction_count_by_method(method_id).await,
+        None => db.get_transaction_count().await,
+    }
+    .unwrap_or(0);
     let current_page = params.page.unwrap_or(1);
     let per_page = params.per_page.unwrap_or(10);
     let total_pages = (total as f64 / per_page as f64).ceil() as u64;
-    let has_next = current_page < total_pages;
+    let has_next = match params.cursor {
+        Some(_) => next_cursor.is_some(),
+        None => current_page < total_pages,
+    };
 
-    Json(json!({
+    Ok(Json(json!({
         "transactions": txs,
         "pagination": {
             "current_page": current_page,
             "per_page": per_page,
             "total": total,
             "total_pages": total_pages,
-            "has_next": has_next
-        }
-    }))
+            "has_next": has_next,
+            "next_cursor": next_cursor
+        },
+        "method": method_id
+    })))
 }
 
 /// Get transactions with filtering
 pub async fn get_filtered_transactions(
     Query(filters): Query<crate::database::TransactionFilterParams>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
-    let txs = db
+    let txs: Vec<TransactionResponse> = db
         .get_filtered_transactions(&filters)
         .await
-        .unwrap_or_default();
+        .unwrap_or_default()
+        .into_iter()
+        .map(TransactionResponse::from)
+        .collect();
 
     let total = db.get_transaction_count().await.unwrap_or(0);
     let current_page = filters.page.unwrap_or(1);
@@ -56,7 +108,7 @@ pub async fn get_filtered_transactions(
     let total_pages = (total as f64 / per_page as f64).ceil() as u64;
     let has_next = current_page < total_pages;
 
-    Json(json!({
+    Ok(Json(json!({
         "transactions": txs,
         "pagination": {
             "current_page": current_page,
@@ -72,127 +124,159 @@ pub async fn get_filtered_transactions(
             "from_block": filters.from_block,
             "to_block": filters.to_block
         }
-    }))
+    })))
 }
 
 /// Get transaction by hash
 pub async fn get_transaction_by_hash(
     Path(hash): Path<String>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
     // Get transaction from DB
     if let Ok(Some(tx)) = db.get_transaction_by_hash(&hash).await {
+        // Calls are decoded on demand, so newly verified ABIs apply to past transactions
+        let decoded_input = match (&tx.to_address, &tx.input) {
+            (Some(to), Some(input)) => {
+                app.contracts
+                    .decode_input(to, input)
+                    .await
+                    .unwrap_or_else(|e| {
+                        debug!("Failed to decode input of {}: {:#}", hash, e);
+                        None
+                    })
+            }
+            _ => None,
+        };
+        let tx = TransactionResponse::from(tx);
+
         // Get logs for this transaction
-        if let Ok(logs) = db.get_logs_by_transaction(&hash).await {
-            return Json(json!({
-                "transaction": tx,
-                "logs": logs
-            }));
-        }
-        return Json(json!({
+        let logs = db.get_logs_by_transaction(&hash).await.unwrap_or_default();
+        // Missing for transactions indexed before receipts were stored
+        let receipt = db
+            .get_receipt(&tx.transaction.hash, tx.transaction.block_number)
+            .await
+            .unwrap_or_else(|e| {
+                debug!("Failed to get receipt of {}: {:#}", hash, e);
+                None
+            });
+        let created_contracts: Vec<&String> = receipt
+            .iter()
+            .filter(|_| tx.transaction.status == 1)
+            .filter_map(|receipt| receipt.contract_address.as_ref())
+            .collect();
+        let mut response = json!({
             "transaction": tx,
-            "logs": []
-        }));
+            "decoded_input": decoded_input,
+            "logs": logs,
+            "receipt": receipt,
+            "created_contracts": created_contracts
+        });
+
+        // L1 fee components and system transaction kind on rollups
+        if app.config.network_profile.is_l2()
+            && let Ok(Some(l2)) = db.get_l2_transaction(&tx.transaction.hash).await
+        {
+            response["l2"] = json!(l2);
+        }
+        return Ok(Json(response));
     }
 
     // Transaction not found in our DB, try getting from RPC
-    if let Ok(Some(receipt)) = app.rpc.get_transaction_receipt(&hash).await {
-        return Json(json!({
-            "transaction": {
-                "hash": format!("{:?}", receipt.transaction_hash),
-                "block_number": receipt.block_number.map(|n| n.as_u64()).unwrap_or_default(),
-                "status": receipt.status.map(|s| s.as_u64()).unwrap_or_default(),
-                "gas_used": receipt.gas_used.map(|g| g.as_u64()).unwrap_or_default(),
-            },
-            "logs": [],
-            "note": "Transaction not yet indexed, basic info retrieved from blockchain"
-        }));
+    match app.rpc.get_transaction_receipt(&hash).await {
+        Ok(Some(receipt)) => {
+            return Ok(Json(json!({
+                "transaction": {
+                    "hash": format!("{:?}", receipt.transaction_hash),
+                    "block_number": receipt.block_number.map(|n| n.as_u64()).unwrap_or_default(),
+                    "status": receipt.status.map(|s| s.as_u64()).unwrap_or_default(),
+                    "gas_used": receipt.gas_used.map(|g| g.as_u64()).unwrap_or_default(),
+                    "contract_address": receipt.contract_address.map(|a| format!("{:?}", a)),
+                },
+                "logs": [],
+                "note": "Transaction not yet indexed, basic info retrieved from blockchain"
+            })));
+        }
+        Err(e) if RpcOverloaded::is(&e) => return Err(ApiError::RpcOverloaded),
+        _ => {}
     }
 
     // Neither in DB nor on chain
-    Json(json!({
-        "error": "Transaction not found"
-    }))
+    Err(ApiError::NotFound("Transaction not found".to_string()))
 }
 
 /// Get the most recent transactions (live feed)
-pub async fn get_live_transactions(Extension(app): Extension<Arc<App>>) -> Json<serde_json::Value> {
+pub async fn get_live_transactions(
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
     // Get only the 10 most recent transactions, ordered by block and transaction index
-    let txs = db.get_recent_transactions(10, 0).await.unwrap_or_default();
+    let txs = match app.recent.recent_transactions(10, 0) {
+        Some(txs) => txs,
+        None => db.get_recent_transactions(10, 0).await.unwrap_or_default(),
+    };
+    let txs: Vec<TransactionResponse> = txs.into_iter().map(TransactionResponse::from).collect();
 
-    Json(json!({
+    Ok(Json(json!({
         "transactions": txs,
         "timestamp": chrono::Utc::now().timestamp(),
         "count": txs.len()
-    }))
+    })))
 }
 
-/// Get token transfers for a specific transaction
+/// Get token transfers for a specific transaction, `usd=true` adding the approximate USD
+/// value of each
 pub async fn get_transaction_token_transfers(
     Path(hash): Path<String>,
+    Query(value): Query<TransferValueParams>,
     Extension(app): Extension<Arc<App>>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Value>, ApiError> {
     let db = &app.db;
 
-    match db.get_token_transfers_by_transaction_hash(&hash).await {
-        Ok(transfers) => {
-            if transfers.is_empty() {
-                Json(json!({
-                    "transaction_hash": hash,
-                    "token_transfers": [],
-                    "count": 0
-                }))
-            } else {
-                // Get token info for each transfer
-                let mut enhanced_transfers = Vec::new();
-
-                for transfer in transfers {
-                    let token_info = db
-                        .get_token_by_address(&transfer.token_address)
-                        .await
-                        .unwrap_or(None);
-
-                    let enhanced_transfer = json!({
-                        "id": transfer.id,
-                        "transaction_hash": transfer.transaction_hash,
-                        "token_address": transfer.token_address,
-                        "from_address": transfer.from_address,
-                        "to_address": transfer.to_address,
-                        "amount": transfer.amount,
-                        "block_number": transfer.block_number,
-                        "token_type": transfer.token_type,
-                        "token_id": transfer.token_id,
-                        "token": token_info.map(|token| json!({
-                            "name": token.name,
-                            "symbol": token.symbol,
-                            "decimals": token.decimals
-                        }))
-                    });
-
-                    enhanced_transfers.push(enhanced_transfer);
-                }
-
-                Json(json!({
-                    "transaction_hash": hash,
-                    "token_transfers": enhanced_transfers,
-                    "count": enhanced_transfers.len()
-                }))
-            }
-        }
-        Err(e) => {
-            tracing::error!(
-                "Failed to get token transfers for transaction {}: {}",
-                hash,
-                e
-            );
-            Json(json!({
-                "error": "Failed to get token transfers",
-                "transaction_hash": hash
+    let transfers = match app.recent.token_transfers_by_transaction(&hash) {
+        Some(transfers) => transfers,
+        None => db
+            .get_token_transfers_by_transaction_hash(&hash)
+            .await
+            .map_err(|e| ApiError::internal("Failed to get token transfers", e))?,
+    };
+
+    // Get token info for each transfer
+    let mut enhanced_transfers = Vec::new();
+    for transfer in &transfers {
+        let token_info = db
+            .get_token_by_address(&transfer.token_address)
+            .await
+            .unwrap_or(None);
+
+        let enhanced_transfer = json!({
+            "id": transfer.id,
+            "transaction_hash": transfer.transaction_hash,
+            "token_address": transfer.token_address,
+            "from_address": transfer.from_address,
+            "to_address": transfer.to_address,
+            "amount": transfer.amount,
+            "block_number": transfer.block_number,
+            "token_type": transfer.token_type,
+            "token_id": transfer.token_id,
+            "token": token_info.map(|token| json!({
+                "name": token.name,
+                "symbol": token.symbol,
+                "decimals": token.decimals
             }))
-        }
+        });
+
+        enhanced_transfers.push(enhanced_transfer);
+    }
+    if value.usd.unwrap_or(false) {
+        add_usd_values(&app, &transfers, &mut enhanced_transfers).await;
     }
+
+    Ok(Json(json!({
+        "transaction_hash": hash,
+        "token_transfers": enhanced_transfers,
+        "count": enhanced_transfers.len()
+    })))
 }
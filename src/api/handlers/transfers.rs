@@ -0,0 +1,263 @@
+use axum::{extract::Query, Extension, Json};
+use ethers::types::{I256, U256};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    api::ApiError,
+    database::{
+        TokenTransfer, TokenTransferFilter, TokenTransferFilterParams, Transaction,
+        TransferValueParams, TransfersBetweenParams,
+    },
+    token_metadata::to_base_units,
+    App,
+};
+
+use super::add_usd_values;
+
+/// Totals of one asset moved between the two addresses
+#[derive(Default)]
+struct AssetTotals {
+    token_type: Option<String>,
+    transfers: u64,
+    sent: U256,     // Moved from `from` to `to`
+    received: U256, // Moved from `to` back to `from`
+}
+
+/// Get the ETH transactions and token transfers between two addresses in both directions,
+/// with totals per asset. Failed transactions are listed but move no value.
+pub async fn get_transfers_between(
+    Query(params): Query<TransfersBetweenParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let (from, to) = match (
+        parse_address(params.from.as_deref()),
+        parse_address(params.to.as_deref()),
+    ) {
+        (Some(from), Some(to)) if from != to => (from, to),
+        (Some(_), Some(_)) => {
+            return Err(ApiError::Unprocessable(
+                "from and to must be different addresses".to_string(),
+            ));
+        }
+        _ => {
+            return Err(ApiError::Unprocessable(
+                "from and to must both be 0x-prefixed 20-byte addresses".to_string(),
+            ));
+        }
+    };
+
+    let activity = async {
+        let transactions = app.db.get_transactions_between(&from, &to).await?;
+        let transfers = app.db.get_token_transfers_between(&from, &to).await?;
+        anyhow::Ok((transactions, transfers))
+    }
+    .await;
+    let (transactions, transfers) =
+        activity.map_err(|e| ApiError::internal("Failed to get transfers between addresses", e))?;
+
+    let mut assets = Vec::new();
+    let eth = eth_totals(&from, &transactions);
+    if eth.transfers > 0 {
+        assets.push(asset_json("ETH", None, Some(18), &eth));
+    }
+    for (token_address, totals) in token_totals(&from, &transfers) {
+        let token = app
+            .db
+            .get_token_by_address(&token_address)
+            .await
+            .unwrap_or(None);
+        let symbol = token
+            .as_ref()
+            .and_then(|token| token.symbol.clone())
+            .unwrap_or_else(|| token_address.clone());
+        assets.push(asset_json(
+            &symbol,
+            Some(&token_address),
+            token.as_ref().and_then(|token| token.decimals),
+            &totals,
+        ));
+    }
+
+    let limit = params.limit();
+    Ok(Json(json!({
+        "from": from,
+        "to": to,
+        "assets": assets,
+        "transaction_count": transactions.len(),
+        "token_transfer_count": transfers.len(),
+        "transactions": transactions.iter().take(limit).collect::<Vec<_>>(),
+        "token_transfers": transfers.iter().take(limit).collect::<Vec<_>>()
+    })))
+}
+
+/// List token transfers by token, sender, recipient, block range and amount. `token` is an
+/// address or a symbol; `min_amount` and `max_amount` are in token units, converted to
+/// base units with the stored decimals of the token. `usd=true` adds the approximate
+/// USD value of each transfer.
+pub async fn get_token_transfers(
+    Query(params): Query<TokenTransferFilterParams>,
+    Query(value): Query<TransferValueParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let mut filter = TokenTransferFilter {
+        from_block: params.from_block,
+        to_block: params.to_block,
+        ..Default::default()
+    };
+    for (name, value, target) in [
+        ("from", &params.from, &mut filter.from_address),
+        ("to", &params.to, &mut filter.to_address),
+    ] {
+        if let Some(value) = value {
+            match parse_address(Some(value)) {
+                Some(address) => *target = Some(address),
+                None => {
+                    return Err(ApiError::Unprocessable(format!(
+                        "{} must be a 0x-prefixed 20-byte address",
+                        name
+                    )));
+                }
+            }
+        }
+    }
+
+    // Transfers of tokens without metadata can still be listed by address
+    let token = match params.token.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(token) => {
+            let address = parse_address(Some(token));
+            let lookup = match &address {
+                Some(address) => app.db.get_token_by_address(address).await,
+                None => app.db.get_token_by_symbol(token).await,
+            };
+            match lookup {
+                Ok(found) if found.is_some() || address.is_some() => {
+                    filter.token_address =
+                        address.or_else(|| found.as_ref().map(|token| token.address.clone()));
+                    found
+                }
+                Ok(_) => return Err(ApiError::NotFound(format!("Unknown token: {}", token))),
+                Err(e) => return Err(ApiError::internal("Failed to look up token", e)),
+            }
+        }
+    };
+
+    if params.min_amount.is_some() || params.max_amount.is_some() {
+        let Some(decimals) = token.as_ref().and_then(|token| token.decimals) else {
+            return Err(ApiError::Unprocessable(
+                "min_amount and max_amount need a token with known decimals".to_string(),
+            ));
+        };
+        for (value, target) in [
+            (&params.min_amount, &mut filter.min_amount),
+            (&params.max_amount, &mut filter.max_amount),
+        ] {
+            if let Some(value) = value {
+                *target = Some(to_base_units(value, decimals).map_err(ApiError::Unprocessable)?);
+            }
+        }
+    }
+
+    let (transfers, total) = app
+        .db
+        .get_filtered_token_transfers(&filter, params.limit(), params.offset())
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch token transfers", e))?;
+
+    let mut transfers_json: Vec<Value> = transfers.iter().map(|transfer| json!(transfer)).collect();
+    if value.usd.unwrap_or(false) {
+        add_usd_values(&app, &transfers, &mut transfers_json).await;
+    }
+
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    Ok(Json(json!({
+        "token_transfers": transfers_json,
+        "token": token,
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": current_page < total_pages
+        },
+        "filters": {
+            "token_address": filter.token_address,
+            "from": filter.from_address,
+            "to": filter.to_address,
+            "min_amount": params.min_amount,
+            "max_amount": params.max_amount,
+            "min_amount_raw": filter.min_amount,
+            "max_amount_raw": filter.max_amount,
+            "from_block": filter.from_block,
+            "to_block": filter.to_block
+        }
+    })))
+}
+
+/// Lowercase address, None unless it is 0x followed by 40 hex digits
+fn parse_address(address: Option<&str>) -> Option<String> {
+    let address = address?.trim().to_lowercase();
+    (address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit()))
+    .then_some(address)
+}
+
+/// ETH moved by successful transactions between the two addresses
+fn eth_totals(from: &str, transactions: &[Transaction]) -> AssetTotals {
+    let mut totals = AssetTotals::default();
+    for tx in transactions {
+        totals.transfers += 1;
+        if tx.status != 1 {
+            continue;
+        }
+        let value = U256::from_dec_str(&tx.value).unwrap_or_default();
+        if tx.from_address == from {
+            totals.sent = totals.sent.saturating_add(value);
+        } else {
+            totals.received = totals.received.saturating_add(value);
+        }
+    }
+    totals
+}
+
+/// Token amounts moved between the two addresses per token contract. NFT amounts count
+/// items.
+fn token_totals(from: &str, transfers: &[TokenTransfer]) -> BTreeMap<String, AssetTotals> {
+    let mut totals: BTreeMap<String, AssetTotals> = BTreeMap::new();
+    for transfer in transfers {
+        let entry = totals.entry(transfer.token_address.clone()).or_default();
+        entry.token_type = transfer.token_type.clone();
+        entry.transfers += 1;
+        let amount = U256::from_dec_str(&transfer.amount).unwrap_or_default();
+        if transfer.from_address == from {
+            entry.sent = entry.sent.saturating_add(amount);
+        } else {
+            entry.received = entry.received.saturating_add(amount);
+        }
+    }
+    totals
+}
+
+/// Totals of an asset as returned by the API, amounts in base units
+fn asset_json(
+    symbol: &str,
+    token_address: Option<&str>,
+    decimals: Option<u8>,
+    totals: &AssetTotals,
+) -> Value {
+    let net = I256::from_raw(totals.sent).saturating_sub(I256::from_raw(totals.received));
+    json!({
+        "asset": symbol,
+        "token_address": token_address,
+        "token_type": token_address.map(|_| totals.token_type.as_deref().unwrap_or("ERC20")),
+        "decimals": decimals,
+        "transfers": totals.transfers,
+        "sent": totals.sent.to_string(),
+        "received": totals.received.to_string(),
+        "net": net.to_string()
+    })
+}
@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::{api::ApiError, database::ValidatorParams, App};
+
+/// Get synced validators with pagination, optionally filtered by status
+pub async fn get_validators(
+    Query(params): Query<ValidatorParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let db = &app.db;
+    let status = params.status.as_deref().map(str::trim);
+
+    let validators = db
+        .get_validators(status, params.limit(), params.offset())
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch validators", e))?;
+
+    let total = db.get_validator_count(status).await.unwrap_or(0);
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    let has_next = current_page < total_pages;
+
+    Ok(Json(json!({
+        "validators": validators,
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": has_next
+        },
+        "status": status
+    })))
+}
+
+/// Get a validator by index or BLS public key
+pub async fn get_validator(
+    Path(id): Path<String>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let db = &app.db;
+
+    let validator = if id.starts_with("0x") {
+        db.get_validator_by_pubkey(&id.to_lowercase()).await
+    } else {
+        match id.parse::<i64>() {
+            Ok(index) => db.get_validator_by_index(index).await,
+            Err(_) => {
+                return Err(ApiError::BadRequest(
+                    "Expected a validator index or 0x-prefixed public key".to_string(),
+                ));
+            }
+        }
+    };
+
+    match validator {
+        Ok(Some(validator)) => Ok(Json(json!({
+            "validator": validator,
+            "balance_eth": validator.balance_gwei as f64 / 1e9,
+            "effective_balance_eth": validator.effective_balance_gwei as f64 / 1e9
+        }))),
+        Ok(None) => Err(ApiError::NotFound("Validator not found".to_string())),
+        Err(e) => Err(ApiError::internal("Failed to fetch validator", e)),
+    }
+}
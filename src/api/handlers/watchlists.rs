@@ -0,0 +1,253 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use ethers::types::I256;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    api::ApiError,
+    database::{
+        TokenTransfer, Transaction, WatchlistActivityParams, WatchlistAddress, WatchlistRequest,
+    },
+    App,
+};
+
+/// Addresses a single watchlist may hold
+const MAX_WATCHLIST_ADDRESSES: usize = 500;
+
+/// Get all watchlists
+pub async fn get_watchlists(Extension(app): Extension<Arc<App>>) -> Result<Json<Value>, ApiError> {
+    match app.db.get_watchlists().await {
+        Ok(watchlists) => Ok(Json(json!({ "watchlists": watchlists }))),
+        Err(e) => Err(ApiError::internal("Failed to get watchlists", e)),
+    }
+}
+
+/// Create a watchlist
+pub async fn create_watchlist(
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<WatchlistRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let (name, addresses) = validate_request(request)?;
+
+    match app.db.create_watchlist(&name, &addresses).await {
+        Ok(id) => watchlist_response(&app, id).await,
+        Err(e) => Err(ApiError::internal("Failed to create watchlist", e)),
+    }
+}
+
+/// Get a watchlist with its addresses
+pub async fn get_watchlist(
+    Path(id): Path<i64>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    watchlist_response(&app, id).await
+}
+
+/// Replace the name and addresses of a watchlist
+pub async fn replace_watchlist(
+    Path(id): Path<i64>,
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<WatchlistRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let (name, addresses) = validate_request(request)?;
+
+    match app.db.replace_watchlist(id, &name, &addresses).await {
+        Ok(true) => watchlist_response(&app, id).await,
+        Ok(false) => Err(ApiError::NotFound("Watchlist not found".to_string())),
+        Err(e) => Err(ApiError::internal("Failed to replace watchlist", e)),
+    }
+}
+
+/// Delete a watchlist
+pub async fn delete_watchlist(
+    Path(id): Path<i64>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.delete_watchlist(id).await {
+        Ok(deleted) => Ok(Json(json!({ "id": id, "deleted": deleted }))),
+        Err(e) => Err(ApiError::internal("Failed to delete watchlist", e)),
+    }
+}
+
+/// Get the recent activity of all addresses of a watchlist: balances, transactions, token
+/// transfers and the per-block balance changes they caused
+pub async fn get_watchlist_activity(
+    Path(id): Path<i64>,
+    Query(params): Query<WatchlistActivityParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    let db = &app.db;
+    let watchlist = match db.get_watchlist(id).await {
+        Ok(Some(watchlist)) => watchlist,
+        Ok(None) => return Err(ApiError::NotFound("Watchlist not found".to_string())),
+        Err(e) => return Err(ApiError::internal("Failed to get watchlist", e)),
+    };
+
+    let activity = async {
+        let entries = db.get_watchlist_addresses(id).await?;
+        let addresses: Vec<String> = entries.iter().map(|entry| entry.address.clone()).collect();
+        let transactions = db
+            .get_transactions_involving(&addresses, params.limit())
+            .await?;
+        let transfers = db
+            .get_token_transfers_involving(&addresses, params.limit())
+            .await?;
+
+        let mut balances = Vec::new();
+        for entry in &entries {
+            let account = db.get_account_by_address(&entry.address).await?;
+            balances.push(json!({
+                "address": entry.address,
+                "label": entry.label,
+                "balance": account.as_ref().map(|account| &account.balance),
+                "balance_at_block": account.as_ref().and_then(|account| account.balance_at_block),
+                "transaction_count": account.as_ref().map_or(0, |account| account.transaction_count)
+            }));
+        }
+
+        anyhow::Ok((addresses, balances, transactions, transfers))
+    }
+    .await;
+
+    match activity {
+        Ok((addresses, balances, transactions, transfers)) => {
+            let balance_changes = balance_changes(&addresses, &transactions, &transfers);
+            Ok(Json(json!({
+                "watchlist": watchlist,
+                "addresses": balances,
+                "transactions": transactions,
+                "token_transfers": transfers,
+                "balance_changes": balance_changes
+            })))
+        }
+        Err(e) => Err(ApiError::internal("Failed to get watchlist activity", e)),
+    }
+}
+
+/// Watchlist with its addresses
+async fn watchlist_response(app: &App, id: i64) -> Result<Json<Value>, ApiError> {
+    let watchlist = match app.db.get_watchlist(id).await {
+        Ok(Some(watchlist)) => watchlist,
+        Ok(None) => return Err(ApiError::NotFound("Watchlist not found".to_string())),
+        Err(e) => return Err(ApiError::internal("Failed to get watchlist", e)),
+    };
+
+    match app.db.get_watchlist_addresses(id).await {
+        Ok(addresses) => Ok(Json(json!({
+            "watchlist": watchlist,
+            "addresses": addresses
+        }))),
+        Err(e) => Err(ApiError::internal("Failed to get watchlist addresses", e)),
+    }
+}
+
+/// Trimmed name and lowercase, deduplicated addresses of a request
+fn validate_request(
+    request: WatchlistRequest,
+) -> Result<(String, Vec<WatchlistAddress>), ApiError> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::Unprocessable(
+            "name must not be empty".to_string(),
+        ));
+    }
+    if request.addresses.len() > MAX_WATCHLIST_ADDRESSES {
+        return Err(ApiError::Unprocessable(format!(
+            "A watchlist holds at most {} addresses",
+            MAX_WATCHLIST_ADDRESSES
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut addresses = Vec::new();
+    for entry in request.addresses {
+        let address = entry.address.trim().to_lowercase();
+        if address.len() != 42
+            || !address.starts_with("0x")
+            || !address[2..].chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Err(ApiError::Unprocessable(format!(
+                "Invalid address: {}",
+                entry.address
+            )));
+        }
+        if seen.insert(address.clone()) {
+            addresses.push(WatchlistAddress {
+                address,
+                label: entry
+                    .label
+                    .map(|label| label.trim().to_string())
+                    .filter(|label| !label.is_empty()),
+            });
+        }
+    }
+
+    Ok((name, addresses))
+}
+
+/// Net change per address, block and asset caused by the given activity, newest first.
+/// ETH changes include the fees of transactions sent; failed transactions move no value.
+fn balance_changes(
+    addresses: &[String],
+    transactions: &[Transaction],
+    transfers: &[TokenTransfer],
+) -> Vec<Value> {
+    let watched: HashSet<&str> = addresses.iter().map(String::as_str).collect();
+    let mut changes: BTreeMap<(i64, String, String), I256> = BTreeMap::new();
+    let mut apply = |block: i64, address: &str, asset: &str, delta: I256| {
+        if watched.contains(address) {
+            *changes
+                .entry((block, address.to_string(), asset.to_string()))
+                .or_insert(I256::zero()) += delta;
+        }
+    };
+
+    for tx in transactions {
+        let value = if tx.status == 1 {
+            I256::from_dec_str(&tx.value).unwrap_or_default()
+        } else {
+            I256::zero()
+        };
+        let fee = I256::from_dec_str(&tx.gas_price).unwrap_or_default() * I256::from(tx.gas_used);
+
+        apply(tx.block_number, &tx.from_address, "ETH", -(value + fee));
+        if let Some(to) = &tx.to_address {
+            apply(tx.block_number, to, "ETH", value);
+        }
+    }
+    for transfer in transfers {
+        let amount = I256::from_dec_str(&transfer.amount).unwrap_or_default();
+        let asset = match &transfer.token_id {
+            Some(token_id) => format!("{}:{}", transfer.token_address, token_id),
+            None => transfer.token_address.clone(),
+        };
+
+        apply(
+            transfer.block_number,
+            &transfer.from_address,
+            &asset,
+            -amount,
+        );
+        apply(transfer.block_number, &transfer.to_address, &asset, amount);
+    }
+
+    changes
+        .into_iter()
+        .rev()
+        .filter(|(_, delta)| !delta.is_zero())
+        .map(|((block_number, address, asset), delta)| {
+            json!({
+                "block_number": block_number,
+                "address": address,
+                "asset": asset,
+                "delta": delta.to_string()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,70 @@
+use axum::{extract::Path, Extension, Json};
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{api::ApiError, database::BlockWebhookRequest, App};
+
+/// Get all block webhooks with their pending notification counts
+pub async fn get_block_webhooks(
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.get_block_webhooks().await {
+        Ok(webhooks) => Ok(Json(json!({ "webhooks": webhooks }))),
+        Err(e) => Err(ApiError::internal("Failed to get block webhooks", e)),
+    }
+}
+
+/// Subscribe a URL to new block notifications
+pub async fn create_block_webhook(
+    Extension(app): Extension<Arc<App>>,
+    Json(request): Json<BlockWebhookRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let url = request.url.trim();
+    if !Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+        return Err(ApiError::Unprocessable(
+            "url must be an http or https URL".to_string(),
+        ));
+    }
+    let secret = request
+        .secret
+        .as_deref()
+        .map(str::trim)
+        .filter(|secret| !secret.is_empty());
+
+    let from_block = match request.from_block {
+        Some(from_block) if from_block < 0 => {
+            return Err(ApiError::Unprocessable(
+                "from_block must not be negative".to_string(),
+            ));
+        }
+        Some(from_block) => from_block,
+        // Blocks still being backfilled below the head are not notified
+        None => match app.db.get_latest_block_number().await {
+            Ok(latest) => latest.map_or(0, |latest| latest + 1),
+            Err(e) => return Err(ApiError::internal("Failed to create block webhook", e)),
+        },
+    };
+
+    match app.db.create_block_webhook(url, secret, from_block).await {
+        Ok(id) => Ok(Json(json!({
+            "id": id,
+            "url": url,
+            "from_block": from_block,
+            "signed": secret.is_some()
+        }))),
+        Err(e) => Err(ApiError::internal("Failed to create block webhook", e)),
+    }
+}
+
+/// Unsubscribe a block webhook, dropping its pending notifications
+pub async fn delete_block_webhook(
+    Path(id): Path<i64>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<Value>, ApiError> {
+    match app.db.delete_block_webhook(id).await {
+        Ok(true) => Ok(Json(json!({ "id": id, "deleted": true }))),
+        Ok(false) => Err(ApiError::NotFound("Block webhook not found".to_string())),
+        Err(e) => Err(ApiError::internal("Failed to delete block webhook", e)),
+    }
+}
@@ -0,0 +1,59 @@
+use axum::{extract::Query, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::{api::ApiError, database::WithdrawalParams, App};
+
+use super::gwei_to_eth_string;
+
+/// Get validator withdrawals received by an address and/or made by a validator, newest
+/// first, with the totals of every matching withdrawal
+pub async fn get_withdrawals(
+    Query(params): Query<WithdrawalParams>,
+    Extension(app): Extension<Arc<App>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let db = &app.db;
+    let address = params.address.as_deref().map(|a| a.trim().to_lowercase());
+    if address.is_none() && params.validator.is_none() {
+        return Err(ApiError::BadRequest(
+            "Either address or validator is required".to_string(),
+        ));
+    }
+
+    let withdrawals = db
+        .get_withdrawals(
+            address.as_deref(),
+            params.validator,
+            params.limit(),
+            params.offset(),
+        )
+        .await
+        .map_err(|e| ApiError::internal("Failed to fetch withdrawals", e))?;
+
+    let (total, total_gwei) = db
+        .get_withdrawal_totals(address.as_deref(), params.validator)
+        .await
+        .unwrap_or((0, 0));
+    let current_page = params.page.unwrap_or(1);
+    let per_page = params.limit();
+    let total_pages = (total as f64 / per_page as f64).ceil() as u64;
+    let has_next = current_page < total_pages;
+
+    Ok(Json(json!({
+        "address": address,
+        "validator": params.validator,
+        "withdrawals": withdrawals,
+        "totals": {
+            "count": total,
+            "amount_gwei": total_gwei.to_string(),
+            "amount_eth": gwei_to_eth_string(total_gwei)
+        },
+        "pagination": {
+            "current_page": current_page,
+            "per_page": per_page,
+            "total": total,
+            "total_pages": total_pages,
+            "has_next": has_next
+        }
+    })))
+}
@@ -1,4 +1,9 @@
+pub mod auth;
+mod error;
 mod handlers;
+mod request_id;
 mod routes;
+mod timestamps;
 
+pub use error::ApiError;
 pub use routes::*;
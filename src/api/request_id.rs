@@ -0,0 +1,63 @@
+use axum::{
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use tracing::{info_span, Instrument};
+
+use crate::rpc::request_id::with_request_id;
+
+/// Header carrying the request ID, read from the client and echoed in the response
+static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request ID kept, longer ones are replaced
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// Requests served by this process, makes generated IDs unique
+static REQUEST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Middleware giving every API request an ID, the client's `X-Request-Id` when it sends a
+/// reasonable one. The handler runs in a span carrying the ID, and RPC calls it makes are
+/// attributed to it in logs and the `rpc_method_usage` health counters. The ID is returned
+/// in the `X-Request-Id` response header.
+pub async fn assign_request_id<B>(request: Request<B>, next: Next<B>) -> Response {
+    let request_id: Arc<str> = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(Arc::from)
+        .unwrap_or_else(|| {
+            format!(
+                "{:x}-{:x}",
+                chrono::Utc::now().timestamp_millis(),
+                REQUEST_COUNTER.fetch_add(1, Ordering::Relaxed)
+            )
+            .into()
+        });
+
+    let span = info_span!("api_request", request_id = %request_id);
+    let mut response = with_request_id(Some(request_id.clone()), next.run(request))
+        .instrument(span)
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+/// Whether a client-supplied ID is short and made of URL-safe characters only, so it can
+/// be logged as is
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
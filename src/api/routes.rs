@@ -1,30 +1,170 @@
-use crate::App;
+use crate::{networks::Networks, App};
 use axum::{
-    routing::{get, Router},
+    body::Body,
+    http::Request,
+    middleware,
+    routing::{delete, get, post, put, Router},
     Extension,
 };
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
+#[cfg(feature = "web-ui")]
+use tower_http::services::ServeDir;
 use tower_http::{
+    classify::{ServerErrorsAsFailures, SharedClassifier},
     cors::{Any, CorsLayer},
-    services::ServeDir,
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{debug_span, info, Span};
 
+use super::auth::{enforce_api_access, redact_api_key, require_admin_key, ApiAuth};
 use super::handlers::*;
+use super::request_id::assign_request_id;
+use super::timestamps::add_iso_timestamps;
 
+/// First path segments of the API routes, network names may not reuse them
+pub const API_ROOT_SEGMENTS: &[&str] = &[
+    "accounts",
+    "admin",
+    "blocks",
+    "charts",
+    "contracts",
+    "deposits",
+    "epochs",
+    "export",
+    "health",
+    "images",
+    "labels",
+    "leaderboards",
+    "logs",
+    "mempool",
+    "network",
+    "networks",
+    "search",
+    "stats",
+    "tokens",
+    "transactions",
+    "transfers",
+    "validators",
+    "watchlists",
+    "withdrawals",
+];
+
+/// Router serving a single network under /api
 pub async fn create_router(app: Arc<App>) -> Router {
+    create_networks_router(Arc::new(Networks::single(app))).await
+}
+
+/// Router serving every network under /api/<network>, and the default one under /api
+pub async fn create_networks_router(networks: Arc<Networks>) -> Router {
     let cors = CorsLayer::new()
         .allow_methods(Any)
         .allow_headers(Any)
         .allow_origin(Any);
+    let default_app = networks.default_app().clone();
+    // API keys and rate limits live in the default network's database and cover all networks
+    let auth = Arc::new(ApiAuth::new(
+        default_app.db.clone(),
+        default_app.config.clone(),
+    ));
+    let admin_key: Option<Arc<str>> = default_app.config.admin_api_key.as_deref().map(Arc::from);
+
+    let mut router = Router::new().nest(
+        "/api",
+        api_router(
+            default_app.clone(),
+            auth.clone(),
+            admin_key.clone(),
+            cors.clone(),
+        ),
+    );
+    for (name, app) in networks.iter() {
+        router = router.nest(
+            &format!("/api/{}", name),
+            api_router(app.clone(), auth.clone(), admin_key.clone(), cors.clone()),
+        );
+    }
+
+    #[cfg(feature = "web-ui")]
+    let router = router.merge(Router::new().nest_service("/", ServeDir::new("src/web/static")));
+
+    router
+        .layer(Extension(networks))
+        .layer(Extension(default_app))
+        .layer(trace_layer())
+}
+
+/// API routes of a single network
+fn api_router(
+    app: Arc<App>,
+    auth: Arc<ApiAuth>,
+    admin_key: Option<Arc<str>>,
+    cors: CorsLayer,
+) -> Router {
+    // Admin endpoints additionally require ADMIN_API_KEY
+    let admin_routes = Router::new()
+        .route("/admin/indexer", get(get_indexer_status))
+        .route("/admin/indexer/pause", post(pause_indexer))
+        .route("/admin/indexer/resume", post(resume_indexer))
+        .route("/admin/indexer/settings", put(update_indexer_settings))
+        .route("/admin/indexer/requeue/:number", post(requeue_block))
+        .route("/admin/indexer/reindex", post(reindex_blocks))
+        .route("/admin/indexer/debug-block/:number", post(debug_block))
+        .route("/admin/token-refresh", get(get_token_refresh_status))
+        .route("/admin/tokens/import-list", post(import_token_list))
+        .route(
+            "/admin/tokens/:address/metadata",
+            put(set_token_metadata_override),
+        )
+        .route(
+            "/admin/labels/:address",
+            put(set_address_label).delete(delete_address_label),
+        )
+        .route("/admin/export/holders", get(export_holders))
+        .route("/admin/storage", get(get_storage_stats))
+        .route("/admin/events", get(get_indexer_events))
+        .route("/admin/history/blocks/:number", get(get_block_history))
+        .route(
+            "/admin/history/transactions/:hash",
+            get(get_transaction_history),
+        )
+        .route(
+            "/admin/log-filter",
+            get(get_log_filter)
+                .put(update_log_filter)
+                .delete(reset_log_filter),
+        )
+        .route(
+            "/admin/webhooks",
+            get(get_block_webhooks).post(create_block_webhook),
+        )
+        .route("/admin/webhooks/:id", delete(delete_block_webhook))
+        .route_layer(middleware::from_fn_with_state(admin_key, require_admin_key));
+
     let api_routes = Router::new()
+        .route("/networks", get(get_networks))
         .route("/health", get(health_check))
+        .route("/health/live", get(health_live))
+        .route("/health/ready", get(health_ready))
         .route("/stats", get(get_stats))
+        .route("/stats/tps", get(get_tps))
+        .route("/stats/header", get(get_header_stats))
+        .route("/charts/transactions", get(get_transactions_chart))
+        .route("/charts/gas", get(get_gas_chart))
+        .route("/charts/accounts", get(get_accounts_chart))
+        .route("/charts/issuance", get(get_issuance_chart))
+        .route("/leaderboards/:metric", get(get_leaderboard))
         .route("/network/latest", get(get_network_latest))
         .route("/network/stats", get(get_network_stats))
+        .route("/network/liveness", get(get_network_liveness))
+        .route("/network/reorgs", get(get_network_reorgs))
+        .route("/network/finality", get(get_network_finality))
         .route("/blocks", get(get_blocks))
         .route("/blocks/since", get(get_blocks_since))
+        .route("/blocks/fee-recipients", get(get_block_fee_recipients))
+        .route(
+            "/blocks/fee-recipients/relationships",
+            get(get_fee_recipient_relationships),
+        )
         .route("/blocks/:number", get(get_block_by_number))
         .route("/transactions", get(get_transactions))
         .route("/transactions/filtered", get(get_filtered_transactions))
@@ -35,34 +175,98 @@ pub async fn create_router(app: Arc<App>) -> Router {
             "/transactions/:hash/token-transfers",
             get(get_transaction_token_transfers),
         )
+        .route("/transfers", get(get_token_transfers))
+        .route("/transfers/between", get(get_transfers_between))
+        .route("/logs", get(get_logs))
+        .route("/logs/stream", get(stream_logs))
+        .route("/mempool", get(get_mempool))
         .route("/accounts", get(get_accounts))
         .route("/accounts/filtered", get(get_filtered_accounts))
         .route("/accounts/:address", get(get_account))
+        .route(
+            "/accounts/:address/withdrawals",
+            get(get_account_withdrawals),
+        )
+        .route(
+            "/accounts/:address/balance-history",
+            get(get_account_balance_history),
+        )
+        .route("/accounts/:address/approvals", get(get_account_approvals))
+        .route(
+            "/contracts/by-codehash/:hash",
+            get(get_contracts_by_codehash),
+        )
+        .route("/contracts/top", get(get_top_contracts))
+        .route("/contracts/:address", get(get_verified_contract))
+        .route("/contracts/:address/verify", post(verify_contract))
+        .route("/contracts/:address/failures", get(get_contract_failures))
+        .route("/contracts/:address/children", get(get_contract_children))
+        .route("/contracts/:address/ancestry", get(get_contract_ancestry))
         .route("/tokens", get(get_tokens))
         .route("/tokens/balances", get(get_token_balances))
         .route("/tokens/holders", get(get_token_holders))
+        .route("/tokens/:address/delegates", get(get_token_delegates))
+        .route("/images/tokens/:address", get(get_token_logo))
+        .route("/images/nfts/:address/:token_id", get(get_nft_image))
+        .route("/labels", get(get_address_labels))
+        .route("/validators", get(get_validators))
+        .route("/validators/:id", get(get_validator))
+        .route("/withdrawals", get(get_withdrawals))
+        .route("/deposits", get(get_deposits))
+        .route("/epochs/:epoch", get(get_epoch))
+        .route("/watchlists", get(get_watchlists).post(create_watchlist))
+        .route(
+            "/watchlists/:id",
+            get(get_watchlist)
+                .put(replace_watchlist)
+                .delete(delete_watchlist),
+        )
+        .route("/watchlists/:id/activity", get(get_watchlist_activity))
+        .route("/search", get(search_by_query))
         .route("/search/:query", get(search))
-        .layer(Extension(app.clone()))
-        .layer(cors.clone())
-        .layer(TraceLayer::new_for_http());
-
-    let static_files = Router::new().nest_service("/", ServeDir::new("src/web/static"));
+        .route("/export/blocks", get(export_blocks))
+        .route("/export/transactions", get(export_transactions))
+        .route("/export/logs", get(export_logs))
+        .merge(admin_routes)
+        .route_layer(middleware::from_fn_with_state(auth, enforce_api_access));
 
-    Router::new()
-        .nest("/api", api_routes)
-        .merge(static_files)
+    // Validated when the configuration is loaded
+    let api_routes = match app.config.api_timezone_offset() {
+        Some(offset) if app.config.api_iso_timestamps => {
+            api_routes.layer(middleware::from_fn_with_state(offset, add_iso_timestamps))
+        }
+        _ => api_routes,
+    };
+    api_routes
         .layer(Extension(app))
-        .layer(TraceLayer::new_for_http())
+        .layer(cors)
+        .layer(middleware::from_fn(assign_request_id))
+        .layer(trace_layer())
+}
+
+/// Span of a traced request
+type MakeRequestSpan = fn(&Request<Body>) -> Span;
+
+/// Request tracing, with API keys passed in the query redacted from the logged URI
+fn trace_layer() -> TraceLayer<SharedClassifier<ServerErrorsAsFailures>, MakeRequestSpan> {
+    TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
+        debug_span!(
+            "request",
+            method = %request.method(),
+            uri = %redact_api_key(request.uri()),
+            version = ?request.version()
+        )
+    })
 }
 
-pub async fn start_server(app: Arc<App>) -> anyhow::Result<()> {
-    let addr = format!("0.0.0.0:{}", app.config.api_port);
-    let router = create_router(app).await;
+pub async fn start_server(networks: Arc<Networks>) -> anyhow::Result<()> {
+    let addr = format!("0.0.0.0:{}", networks.default_app().config.api_port);
+    let router = create_networks_router(networks).await;
 
     info!("Starting API server on {}", addr);
 
     axum::Server::bind(&addr.parse()?)
-        .serve(router.into_make_service())
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
         .await?;
 
     Ok(())
@@ -0,0 +1,122 @@
+use axum::{
+    body::{boxed, Full, HttpBody},
+    extract::State,
+    http::{header, Request},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, SecondsFormat};
+use serde_json::{Map, Value};
+use tracing::warn;
+
+/// Largest unix timestamp rendered, the end of year 9999
+const MAX_TIMESTAMP: i64 = 253_402_300_799;
+
+/// Middleware adding ISO-8601 fields to JSON responses, enabled by API_ISO_TIMESTAMPS.
+///
+/// Every integer `timestamp`, `*_timestamp`, `*_at` or `bucket_start` field gets a
+/// `<field>_iso` sibling, and `*_at` columns stored by SQLite as `YYYY-MM-DD HH:MM:SS` (UTC)
+/// are rewritten as ISO-8601. Both are rendered in the API_TIMEZONE offset. Streams, CSV
+/// and images pass through untouched.
+pub async fn add_iso_timestamps<B>(
+    State(offset): State<FixedOffset>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, mut body) = response.into_parts();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        match chunk {
+            Ok(chunk) => bytes.extend_from_slice(&chunk),
+            Err(e) => {
+                warn!("Failed to read response body: {}", e);
+                return Response::from_parts(parts, boxed(Full::from(bytes)));
+            }
+        }
+    }
+
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, boxed(Full::from(bytes)));
+    };
+    add_iso_fields(&mut value, offset);
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let body = serde_json::to_vec(&value).unwrap_or(bytes);
+    Response::from_parts(parts, boxed(Full::from(body)))
+}
+
+/// Add ISO-8601 fields to every object of a JSON document
+fn add_iso_fields(value: &mut Value, offset: FixedOffset) {
+    match value {
+        Value::Object(object) => {
+            for child in object.values_mut() {
+                add_iso_fields(child, offset);
+            }
+            add_object_iso_fields(object, offset);
+        }
+        Value::Array(items) => {
+            for item in items {
+                add_iso_fields(item, offset);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn add_object_iso_fields(object: &mut Map<String, Value>, offset: FixedOffset) {
+    let mut added = Vec::new();
+    for (key, field) in object.iter_mut() {
+        let is_time_key = key == "timestamp"
+            || key == "bucket_start"
+            || key.ends_with("_timestamp")
+            || key.ends_with("_at");
+        if !is_time_key {
+            continue;
+        }
+
+        match field {
+            Value::Number(number) => {
+                if let Some(iso) = number
+                    .as_i64()
+                    .and_then(|seconds| iso_from_unix(seconds, offset))
+                {
+                    added.push((format!("{}_iso", key), Value::String(iso)));
+                }
+            }
+            Value::String(text) if key.ends_with("_at") => {
+                if let Ok(naive) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
+                    *text = naive
+                        .and_utc()
+                        .with_timezone(&offset)
+                        .to_rfc3339_opts(SecondsFormat::Secs, true);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (key, iso) in added {
+        object.entry(key).or_insert(iso);
+    }
+}
+
+/// ISO-8601 rendering of unix seconds, None outside the representable range
+fn iso_from_unix(seconds: i64, offset: FixedOffset) -> Option<String> {
+    if !(0..=MAX_TIMESTAMP).contains(&seconds) {
+        return None;
+    }
+    DateTime::from_timestamp(seconds, 0).map(|utc| {
+        utc.with_timezone(&offset)
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    })
+}
@@ -1,17 +1,40 @@
 use anyhow::{Context, Result};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::OnceCell;
 use tracing::{debug, error, info, warn};
 
 use crate::{
+    beacon::fallback::BeaconEndpoints,
     config::AppConfig,
-    executor::{BeaconRpcOperation, BeaconRpcResponse, RpcExecutor},
+    database::DatabaseService,
+    events::EventLog,
+    executor::{
+        BeaconRpcOperation, BeaconRpcResponse, QueueStats, RateLimitFeedback, RateLimits,
+        RpcExecutor,
+    },
+    rpc::ProviderStats,
 };
 
+/// Genesis time and slot length of a beacon chain, which place every execution block
+/// since the merge: its payload timestamp is `genesis_time + slot * seconds_per_slot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotClock {
+    pub genesis_time: u64,
+    pub seconds_per_slot: u64,
+}
+
+impl SlotClock {
+    /// Slot starting at `timestamp`, None before genesis
+    pub fn slot_at(&self, timestamp: u64) -> Option<u64> {
+        let elapsed = timestamp.checked_sub(self.genesis_time)?;
+        elapsed.checked_div(self.seconds_per_slot)
+    }
+}
+
 /// Beacon Chain client for fetching consensus layer data
 pub struct BeaconClient {
-    client: Client,
-    base_url: String,
+    endpoints: Arc<BeaconEndpoints>,
     executor: RpcExecutor<BeaconRpcOperation, BeaconRpcResponse>,
 }
 
@@ -70,6 +93,62 @@ pub struct ExecutionPayload {
     pub excess_blob_gas: Option<String>,
 }
 
+/// Validator entry of the beacon state
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BeaconValidator {
+    pub index: String,
+    pub balance: String,
+    pub status: String,
+    pub validator: BeaconValidatorInfo,
+}
+
+/// Registry record of a validator
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BeaconValidatorInfo {
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub effective_balance: String,
+    pub slashed: bool,
+    pub activation_eligibility_epoch: String,
+    pub activation_epoch: String,
+    pub exit_epoch: String,
+    pub withdrawable_epoch: String,
+}
+
+/// Justified and finalized checkpoints of the beacon head state
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FinalityCheckpoints {
+    pub previous_justified: Checkpoint,
+    pub current_justified: Checkpoint,
+    pub finalized: Checkpoint,
+}
+
+/// Epoch boundary block voted on by the validators
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Checkpoint {
+    pub epoch: String,
+    pub root: String, // Zero before the first checkpoint
+}
+
+/// Validator scheduled to propose the block of a slot
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BeaconProposerDuty {
+    pub pubkey: String,
+    pub validator_index: String,
+    pub slot: String,
+}
+
+/// Proposer reward breakdown of a block, amounts in Gwei
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BeaconBlockRewards {
+    pub proposer_index: String,
+    pub total: String,
+    pub attestations: String,
+    pub sync_aggregate: String,
+    pub proposer_slashings: String,
+    pub attester_slashings: String,
+}
+
 /// API response wrapper for beacon blocks (v2 endpoint)
 #[derive(Debug, Deserialize)]
 struct ApiResponse<T> {
@@ -89,46 +168,94 @@ struct ApiHeaderResponse<T> {
 }
 
 impl BeaconClient {
-    /// Create new Beacon client with rate limiting
-    pub fn new(beacon_url: &str, config: &AppConfig) -> Self {
-        let client = Client::new();
-        let base_url = beacon_url.trim_end_matches('/').to_string();
+    /// Create new Beacon client with rate limiting over the comma-separated `beacon_urls`,
+    /// persisting resolved slots in `db`
+    pub fn new(
+        beacon_urls: &str,
+        config: &AppConfig,
+        db: Arc<DatabaseService>,
+        events: EventLog,
+    ) -> Result<Self> {
+        let rate_limits = Arc::new(RateLimitFeedback::new(Duration::from_secs(
+            config.rpc_rate_limit_cooldown_seconds,
+        )));
+        let endpoints = Arc::new(
+            BeaconEndpoints::new(beacon_urls, events, rate_limits.clone())
+                .context("Failed to configure beacon nodes")?,
+        );
 
         // Clone for the closure
-        let client_clone = client.clone();
-        let base_url_clone = base_url.clone();
+        let endpoints_clone = endpoints.clone();
+        // Read from the node on first use, failures are retried on the next block
+        let clock = Arc::new(OnceCell::new());
 
-        let executor = RpcExecutor::new(
+        let executor = RpcExecutor::with_rate_limit_feedback(
             "Beacon".to_string(),
             config.beacon_rpc_max_concurrent,
             config.beacon_rpc_min_interval_ms,
+            config.beacon_rpc_queue_capacity,
+            rate_limits,
             move |operation| {
-                let client = client_clone.clone();
-                let base_url = base_url_clone.clone();
-                async move { Self::execute_beacon_operation(client, base_url, operation).await }
+                let endpoints = endpoints_clone.clone();
+                let db = db.clone();
+                let clock = clock.clone();
+                async move { Self::execute_beacon_operation(endpoints, db, clock, operation).await }
             },
         );
 
-        Self {
-            client,
-            base_url,
+        Ok(Self {
+            endpoints,
             executor,
-        }
+        })
+    }
+
+    /// Labels of the configured beacon nodes
+    pub fn node_labels(&self) -> Vec<&str> {
+        self.endpoints.labels()
+    }
+
+    /// Concurrency and rate limits applied to beacon requests
+    pub fn rate_limits(&self) -> RateLimits {
+        self.executor.limits()
+    }
+
+    /// Change the concurrency and rate limits applied to beacon requests
+    pub fn set_rate_limits(&self, limits: RateLimits) {
+        self.executor.set_limits(limits);
+    }
+
+    /// Depth and wait times of the queue of beacon requests waiting for a slot
+    pub fn queue_stats(&self) -> QueueStats {
+        self.executor.queue_stats()
+    }
+
+    /// Request and health statistics of each configured beacon node
+    pub fn node_stats(&self) -> Vec<ProviderStats> {
+        self.endpoints.stats()
     }
 
     /// Execute a beacon operation (internal implementation)
     async fn execute_beacon_operation(
-        client: Client,
-        base_url: String,
+        endpoints: Arc<BeaconEndpoints>,
+        db: Arc<DatabaseService>,
+        clock: Arc<OnceCell<SlotClock>>,
         operation: BeaconRpcOperation,
     ) -> Result<BeaconRpcResponse> {
         match operation {
-            BeaconRpcOperation::GetBeaconDataForBlock(block_number) => {
+            BeaconRpcOperation::GetBeaconDataForBlock(block_number, timestamp) => {
                 debug!("Fetching beacon data for block {}", block_number);
 
                 // First, get the slot for this execution block
-                let slot = match Self::get_slot_for_execution_block(block_number).await {
-                    Ok(Some(slot)) => slot,
+                let (slot, resolved_block) = match Self::resolve_slot(
+                    &endpoints,
+                    &db,
+                    &clock,
+                    block_number,
+                    timestamp,
+                )
+                .await
+                {
+                    Ok(Some(resolved)) => resolved,
                     Ok(None) => {
                         debug!("No slot found for execution block {}", block_number);
                         return Ok(BeaconRpcResponse::BeaconDataForBlock(serde_json::json!({
@@ -137,10 +264,15 @@ impl BeaconClient {
                             "epoch": null,
                             "slot_root": null,
                             "parent_root": null,
-                            "beacon_deposit_count": null,
+                            "block_deposit_count": null,
+                            "eth1_deposit_count": null,
                             "graffiti": null,
                             "randao_reveal": null,
-                            "randao_mix": null
+                            "randao_mix": null,
+                            "sync_participation": null,
+                            "attestation_count": null,
+                            "slashing_count": null,
+                            "fee_recipient": null
                         })));
                     }
                     Err(e) => {
@@ -151,90 +283,291 @@ impl BeaconClient {
                             "epoch": null,
                             "slot_root": null,
                             "parent_root": null,
-                            "beacon_deposit_count": null,
+                            "block_deposit_count": null,
+                            "eth1_deposit_count": null,
                             "graffiti": null,
                             "randao_reveal": null,
-                            "randao_mix": null
+                            "randao_mix": null,
+                            "sync_participation": null,
+                            "attestation_count": null,
+                            "slashing_count": null,
+                            "fee_recipient": null
                         })));
                     }
                 };
 
-                // Get beacon block data for this slot
-                let beacon_data =
-                    match Self::get_beacon_block_for_slot(client.clone(), base_url.clone(), slot)
-                        .await
-                    {
-                        Ok(Some(block_data)) => {
-                            let epoch = slot / 32; // 32 slots per epoch
-
-                            serde_json::json!({
-                                "slot": slot,
-                                "proposer_index": block_data.get("proposer_index"),
-                                "epoch": epoch,
-                                "slot_root": block_data.get("state_root"),
-                                "parent_root": block_data.get("parent_root"),
-                                "beacon_deposit_count": block_data
-                                    .get("body")
-                                    .and_then(|body| body.get("deposits"))
-                                    .and_then(|deposits| deposits.as_array())
-                                    .map(|arr| arr.len() as i64),
-                                "graffiti": block_data
-                                    .get("body")
-                                    .and_then(|body| body.get("graffiti")),
-                                "randao_reveal": block_data
-                                    .get("body")
-                                    .and_then(|body| body.get("randao_reveal")),
-                                "randao_mix": null
-                            })
-                        }
-                        Ok(None) => {
-                            debug!("No beacon block found for slot {}", slot);
-                            serde_json::json!({
-                                "slot": slot,
-                                "proposer_index": null,
-                                "epoch": slot / 32,
-                                "slot_root": null,
-                                "parent_root": null,
-                                "beacon_deposit_count": null,
-                                "graffiti": null,
-                                "randao_reveal": null,
-                                "randao_mix": null
-                            })
-                        }
-                        Err(e) => {
-                            debug!("Error getting beacon block for slot {}: {}", slot, e);
-                            serde_json::json!({
-                                "slot": slot,
-                                "proposer_index": null,
-                                "epoch": slot / 32,
-                                "slot_root": null,
-                                "parent_root": null,
-                                "beacon_deposit_count": null,
-                                "graffiti": null,
-                                "randao_reveal": null,
-                                "randao_mix": null
-                            })
-                        }
-                    };
+                // Get beacon block data for this slot, unless resolving it fetched the block
+                let block_data = match resolved_block {
+                    Some(block_data) => Ok(Some(block_data)),
+                    None => Self::get_beacon_block_for_slot(&endpoints, slot).await,
+                };
+                let beacon_data = match block_data {
+                    Ok(Some(block_data)) => {
+                        let epoch = slot / 32; // 32 slots per epoch
+
+                        serde_json::json!({
+                            "slot": slot,
+                            // Quoted by the beacon API, stored as a number
+                            "proposer_index": block_data
+                                .get("proposer_index")
+                                .and_then(|index| index.as_str())
+                                .and_then(|index| index.parse::<i64>().ok()),
+                            "epoch": epoch,
+                            "slot_root": block_data.get("state_root"),
+                            "parent_root": block_data.get("parent_root"),
+                            "block_deposit_count": Self::block_deposit_count(&block_data),
+                            "eth1_deposit_count": block_data
+                                .get("body")
+                                .and_then(|body| body.get("eth1_data"))
+                                .and_then(|eth1_data| eth1_data.get("deposit_count"))
+                                .and_then(|count| count.as_str())
+                                .and_then(|count| count.parse::<i64>().ok()),
+                            "graffiti": block_data
+                                .get("body")
+                                .and_then(|body| body.get("graffiti")),
+                            "randao_reveal": block_data
+                                .get("body")
+                                .and_then(|body| body.get("randao_reveal")),
+                            "randao_mix": null, // Read from the state by get_beacon_data_for_block
+                            "sync_participation": Self::sync_participation(&block_data),
+                            "attestation_count": block_data
+                                .pointer("/body/attestations")
+                                .and_then(|attestations| attestations.as_array())
+                                .map(|attestations| attestations.len()),
+                            "slashing_count": Self::slashing_count(&block_data),
+                            // Cross-checked with the miner of the execution block
+                            "fee_recipient": block_data
+                                .get("body")
+                                .and_then(|body| body.get("execution_payload"))
+                                .and_then(|payload| payload.get("fee_recipient"))
+                        })
+                    }
+                    Ok(None) => {
+                        debug!("No beacon block found for slot {}", slot);
+                        serde_json::json!({
+                            "slot": slot,
+                            "proposer_index": null,
+                            "epoch": slot / 32,
+                            "slot_root": null,
+                            "parent_root": null,
+                            "block_deposit_count": null,
+                            "eth1_deposit_count": null,
+                            "graffiti": null,
+                            "randao_reveal": null,
+                            "randao_mix": null,
+                            "sync_participation": null,
+                            "attestation_count": null,
+                            "slashing_count": null,
+                            "fee_recipient": null
+                        })
+                    }
+                    Err(e) => {
+                        debug!("Error getting beacon block for slot {}: {}", slot, e);
+                        serde_json::json!({
+                            "slot": slot,
+                            "proposer_index": null,
+                            "epoch": slot / 32,
+                            "slot_root": null,
+                            "parent_root": null,
+                            "block_deposit_count": null,
+                            "eth1_deposit_count": null,
+                            "graffiti": null,
+                            "randao_reveal": null,
+                            "randao_mix": null,
+                            "sync_participation": null,
+                            "attestation_count": null,
+                            "slashing_count": null,
+                            "fee_recipient": null
+                        })
+                    }
+                };
                 info!("Fetched beacon data for block {}", block_number);
                 Ok(BeaconRpcResponse::BeaconDataForBlock(beacon_data))
             }
+            BeaconRpcOperation::GetSlotByExecutionBlock(block_number, timestamp) => {
+                let slot =
+                    Self::resolve_slot(&endpoints, &db, &clock, block_number, timestamp).await?;
+                Ok(BeaconRpcResponse::SlotByExecutionBlock(
+                    slot.map(|(slot, _)| slot),
+                ))
+            }
+            BeaconRpcOperation::GetValidators(indices) => {
+                let path = "/eth/v1/beacon/states/head/validators";
+                let ids: Vec<String> = indices.iter().map(u64::to_string).collect();
+
+                let mut response = endpoints
+                    .send(|client, base_url| {
+                        client
+                            .post(format!("{}{}", base_url, path))
+                            .json(&serde_json::json!({ "ids": ids }))
+                    })
+                    .await
+                    .context(format!("Failed to make request to {}", path))?;
+                if response.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+                    // Nodes predating the POST variant only take ids in the query string
+                    response = endpoints
+                        .send(|client, base_url| {
+                            client
+                                .get(format!("{}{}", base_url, path))
+                                .query(&[("id", ids.join(","))])
+                        })
+                        .await
+                        .context(format!("Failed to make request to {}", path))?;
+                }
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error".to_string());
+                    return Err(anyhow::anyhow!("HTTP {} error: {}", status, error_text));
+                }
+
+                let body: ApiHeaderResponse<Vec<serde_json::Value>> = response
+                    .json()
+                    .await
+                    .context("Failed to parse validators response")?;
+                Ok(BeaconRpcResponse::Validators(body.data))
+            }
+            BeaconRpcOperation::GetFinalityCheckpoints => {
+                let path = "/eth/v1/beacon/states/head/finality_checkpoints";
+                let response = endpoints
+                    .send(|client, base_url| client.get(format!("{}{}", base_url, path)))
+                    .await
+                    .context(format!("Failed to make request to {}", path))?;
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error".to_string());
+                    return Err(anyhow::anyhow!("HTTP {} error: {}", status, error_text));
+                }
+
+                let body: ApiHeaderResponse<serde_json::Value> = response
+                    .json()
+                    .await
+                    .context("Failed to parse finality checkpoints response")?;
+                Ok(BeaconRpcResponse::FinalityCheckpoints(body.data))
+            }
+            BeaconRpcOperation::GetExecutionBlockNumber(block_id) => {
+                let path = format!("/eth/v2/beacon/blocks/{}", block_id);
+                let response = endpoints
+                    .send(|client, base_url| client.get(format!("{}{}", base_url, path)))
+                    .await
+                    .context(format!("Failed to make request to {}", path))?;
+
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(BeaconRpcResponse::ExecutionBlockNumber(None));
+                }
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error".to_string());
+                    return Err(anyhow::anyhow!("HTTP {} error: {}", status, error_text));
+                }
+
+                let body: ApiResponse<serde_json::Value> = response
+                    .json()
+                    .await
+                    .context("Failed to parse beacon block response")?;
+                // Quoted by the beacon API, absent before the merge
+                let block_number = body
+                    .data
+                    .message
+                    .pointer("/body/execution_payload/block_number")
+                    .and_then(|number| number.as_str())
+                    .and_then(|number| number.parse().ok())
+                    .filter(|number| *number > 0);
+                Ok(BeaconRpcResponse::ExecutionBlockNumber(block_number))
+            }
+            BeaconRpcOperation::GetProposerDuties(epoch) => {
+                let path = format!("/eth/v1/validator/duties/proposer/{}", epoch);
+                let response = endpoints
+                    .send(|client, base_url| client.get(format!("{}{}", base_url, path)))
+                    .await
+                    .context(format!("Failed to make request to {}", path))?;
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error".to_string());
+                    return Err(anyhow::anyhow!("HTTP {} error: {}", status, error_text));
+                }
+
+                let body: ApiHeaderResponse<Vec<serde_json::Value>> = response
+                    .json()
+                    .await
+                    .context("Failed to parse proposer duties response")?;
+                Ok(BeaconRpcResponse::ProposerDuties(body.data))
+            }
+            BeaconRpcOperation::GetRandaoMix(slot) => {
+                let path = format!("/eth/v1/beacon/states/{}/randao", slot);
+                let response = endpoints
+                    .send(|client, base_url| client.get(format!("{}{}", base_url, path)))
+                    .await
+                    .context(format!("Failed to make request to {}", path))?;
+
+                // Nodes without historical states don't know the mix of older slots
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(BeaconRpcResponse::RandaoMix(None));
+                }
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error".to_string());
+                    return Err(anyhow::anyhow!("HTTP {} error: {}", status, error_text));
+                }
+
+                let body: ApiHeaderResponse<serde_json::Value> = response
+                    .json()
+                    .await
+                    .context("Failed to parse randao response")?;
+                Ok(BeaconRpcResponse::RandaoMix(
+                    body.data["randao"].as_str().map(|mix| mix.to_string()),
+                ))
+            }
+            BeaconRpcOperation::GetBlockRewards(slot) => {
+                let path = format!("/eth/v1/beacon/rewards/blocks/{}", slot);
+                let response = endpoints
+                    .send(|client, base_url| client.get(format!("{}{}", base_url, path)))
+                    .await
+                    .context(format!("Failed to make request to {}", path))?;
+
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    debug!("Beacon block rewards not found for slot {}", slot);
+                    return Ok(BeaconRpcResponse::BlockRewards(None));
+                }
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unable to read error".to_string());
+                    return Err(anyhow::anyhow!("HTTP {} error: {}", status, error_text));
+                }
+
+                let body: ApiHeaderResponse<serde_json::Value> = response
+                    .json()
+                    .await
+                    .context("Failed to parse beacon block rewards response")?;
+                Ok(BeaconRpcResponse::BlockRewards(Some(body.data)))
+            }
             BeaconRpcOperation::TestConnection => {
-                let url = format!("{}/eth/v1/node/health", base_url);
-                match client.get(&url).send().await {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            info!("Successfully connected to Beacon node");
-                            Ok(BeaconRpcResponse::TestConnection(()))
-                        } else {
-                            error!("Beacon connection failed: {}", response.status());
-                            Err(anyhow::anyhow!("Beacon connection failed"))
-                        }
-                    }
-                    Err(e) => {
-                        error!("Beacon connection error: {}", e);
-                        Err(anyhow::anyhow!("Beacon connection error: {}", e))
-                    }
+                if endpoints.check_health().await {
+                    info!("Successfully connected to Beacon node");
+                    Ok(BeaconRpcResponse::TestConnection(()))
+                } else {
+                    error!("No configured Beacon node is healthy");
+                    Err(anyhow::anyhow!("Beacon connection failed"))
                 }
             }
             _ => {
@@ -256,27 +589,54 @@ impl BeaconClient {
         }
     }
 
-    /// Get beacon data for a specific execution block
-    pub async fn get_beacon_data_for_block(&self, block_number: u64) -> Result<serde_json::Value> {
+    /// Get beacon data for the execution block `block_number` with timestamp `timestamp`
+    pub async fn get_beacon_data_for_block(
+        &self,
+        block_number: u64,
+        timestamp: u64,
+    ) -> Result<serde_json::Value> {
+        let mut data = match self
+            .executor
+            .execute(BeaconRpcOperation::GetBeaconDataForBlock(
+                block_number,
+                timestamp,
+            ))
+            .await?
+        {
+            BeaconRpcResponse::BeaconDataForBlock(data) => data,
+            _ => return Err(anyhow::anyhow!("Unexpected response type")),
+        };
+
+        // The mix is read from the state after the block, only when the block was found
+        if let (Some(slot), false) = (data["slot"].as_u64(), data["proposer_index"].is_null()) {
+            match self.get_randao_mix(slot).await {
+                Ok(mix) => data["randao_mix"] = serde_json::json!(mix),
+                Err(e) => debug!("Failed to fetch randao mix of slot {}: {}", slot, e),
+            }
+        }
+        Ok(data)
+    }
+
+    /// Get the randao mix of the state at a slot, None when the node no longer keeps it
+    pub async fn get_randao_mix(&self, slot: u64) -> Result<Option<String>> {
         match self
             .executor
-            .execute(BeaconRpcOperation::GetBeaconDataForBlock(block_number))
+            .execute(BeaconRpcOperation::GetRandaoMix(slot))
             .await?
         {
-            BeaconRpcResponse::BeaconDataForBlock(data) => Ok(data),
+            BeaconRpcResponse::RandaoMix(mix) => Ok(mix),
             _ => Err(anyhow::anyhow!("Unexpected response type")),
         }
     }
 
     /// Get beacon block header by slot
     pub async fn get_block_header(&self, slot: u64) -> Result<Option<BeaconBlockHeader>> {
-        let url = format!("{}/eth/v1/beacon/headers/{}", self.base_url, slot);
+        let url = format!("/eth/v1/beacon/headers/{}", slot);
         info!("Fetching beacon block header from URL: {}", url);
 
         let response = self
-            .client
-            .get(&url)
-            .send()
+            .endpoints
+            .send(|client, base_url| client.get(format!("{}{}", base_url, url)))
             .await
             .context(format!("Failed to make request to {}", url))?;
 
@@ -323,15 +683,30 @@ impl BeaconClient {
         Ok(Some(api_response.data))
     }
 
+    /// Get the proposer rewards of the block at a slot, None for missed or pruned slots
+    pub async fn get_block_rewards(&self, slot: u64) -> Result<Option<BeaconBlockRewards>> {
+        match self
+            .executor
+            .execute(BeaconRpcOperation::GetBlockRewards(slot))
+            .await?
+        {
+            BeaconRpcResponse::BlockRewards(rewards) => rewards
+                .map(|rewards| {
+                    serde_json::from_value(rewards).context("Failed to parse block rewards")
+                })
+                .transpose(),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
+    }
+
     /// Get beacon block by slot  
     pub async fn get_block(&self, slot: u64) -> Result<Option<BeaconBlock>> {
-        let url = format!("{}/eth/v2/beacon/blocks/{}", self.base_url, slot);
+        let url = format!("/eth/v2/beacon/blocks/{}", slot);
         info!("Fetching beacon block from URL: {}", url);
 
         let response = self
-            .client
-            .get(&url)
-            .send()
+            .endpoints
+            .send(|client, base_url| client.get(format!("{}{}", base_url, url)))
             .await
             .context(format!("Failed to make request to {}", url))?;
 
@@ -389,22 +764,87 @@ impl BeaconClient {
         Ok(Some(api_response.data.message))
     }
 
-    /// Get slot for execution block number
-    /// This requires mapping between execution and consensus layers
-    pub async fn get_slot_by_execution_block(&self, block_number: u64) -> Result<Option<u64>> {
-        // For post-merge blocks, we can estimate slot based on block number
-        // The merge happened at block 15537394 and slot 4700013
-        const MERGE_BLOCK: u64 = 15537394;
-        const MERGE_SLOT: u64 = 4700013;
+    /// Get the slot of the execution block `block_number` with timestamp `timestamp`, None
+    /// for pre-merge blocks
+    pub async fn get_slot_by_execution_block(
+        &self,
+        block_number: u64,
+        timestamp: u64,
+    ) -> Result<Option<u64>> {
+        match self
+            .executor
+            .execute(BeaconRpcOperation::GetSlotByExecutionBlock(
+                block_number,
+                timestamp,
+            ))
+            .await?
+        {
+            BeaconRpcResponse::SlotByExecutionBlock(slot) => Ok(slot),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Get the head state entries of the validators with the given indices. Indices past
+    /// the end of the registry are left out of the result.
+    pub async fn get_validators(&self, indices: &[u64]) -> Result<Vec<BeaconValidator>> {
+        match self
+            .executor
+            .execute(BeaconRpcOperation::GetValidators(indices.to_vec()))
+            .await?
+        {
+            BeaconRpcResponse::Validators(validators) => validators
+                .into_iter()
+                .map(|validator| {
+                    serde_json::from_value(validator).context("Failed to parse validator")
+                })
+                .collect(),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Get the justified and finalized checkpoints of the head state
+    pub async fn get_finality_checkpoints(&self) -> Result<FinalityCheckpoints> {
+        match self
+            .executor
+            .execute(BeaconRpcOperation::GetFinalityCheckpoints)
+            .await?
+        {
+            BeaconRpcResponse::FinalityCheckpoints(checkpoints) => {
+                serde_json::from_value(checkpoints).context("Failed to parse finality checkpoints")
+            }
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
+    }
 
-        if block_number < MERGE_BLOCK {
-            return Ok(None); // Pre-merge blocks don't have slots
+    /// Get the execution block number of a beacon block given by slot or root, None when
+    /// the block is unknown or predates the merge
+    pub async fn get_execution_block_number(&self, block_id: &str) -> Result<Option<u64>> {
+        match self
+            .executor
+            .execute(BeaconRpcOperation::GetExecutionBlockNumber(
+                block_id.to_string(),
+            ))
+            .await?
+        {
+            BeaconRpcResponse::ExecutionBlockNumber(block_number) => Ok(block_number),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
         }
+    }
 
-        // Estimate slot based on block progression
-        // This is approximate and should be refined with actual beacon state
-        let estimated_slot = MERGE_SLOT + (block_number - MERGE_BLOCK);
-        Ok(Some(estimated_slot))
+    /// Get the validators scheduled to propose the blocks of an epoch, one per slot. Nodes
+    /// compute duties of past epochs from historical states, which they may not keep.
+    pub async fn get_proposer_duties(&self, epoch: u64) -> Result<Vec<BeaconProposerDuty>> {
+        match self
+            .executor
+            .execute(BeaconRpcOperation::GetProposerDuties(epoch))
+            .await?
+        {
+            BeaconRpcResponse::ProposerDuties(duties) => duties
+                .into_iter()
+                .map(|duty| serde_json::from_value(duty).context("Failed to parse proposer duty"))
+                .collect(),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
     }
 
     /// Calculate epoch from slot
@@ -414,9 +854,12 @@ impl BeaconClient {
 
     /// Get beacon chain deposit count
     pub async fn get_deposit_count(&self) -> Result<u64> {
-        let url = format!("{}/eth/v1/beacon/deposit_snapshot", self.base_url);
+        let url = "/eth/v1/beacon/deposit_snapshot";
 
-        let response = self.client.get(&url).send().await?;
+        let response = self
+            .endpoints
+            .send(|client, base_url| client.get(format!("{}{}", base_url, url)))
+            .await?;
         let data: serde_json::Value = response.json().await?;
 
         if let Some(count) = data["data"]["deposit_count"].as_str() {
@@ -426,30 +869,147 @@ impl BeaconClient {
         }
     }
 
-    /// Get slot for execution block using slot estimation
-    async fn get_slot_for_execution_block(block_number: u64) -> Result<Option<u64>> {
-        // For post-merge blocks, estimate slot based on block number
-        const MERGE_BLOCK: u64 = 15537394;
-        const MERGE_SLOT: u64 = 4700013;
+    /// Resolve the slot whose execution payload is `block_number`, along with the beacon
+    /// block when it had to be fetched. None for blocks before the merge or whose beacon
+    /// block the node doesn't have.
+    ///
+    /// The slot follows from the block's timestamp and the chain's genesis time and slot
+    /// length, then is checked against the payload of the beacon block at it. Resolved slots
+    /// are persisted as mappings.
+    async fn resolve_slot(
+        endpoints: &BeaconEndpoints,
+        db: &DatabaseService,
+        clock: &OnceCell<SlotClock>,
+        block_number: u64,
+        timestamp: u64,
+    ) -> Result<Option<(u64, Option<serde_json::Value>)>> {
+        if let Some((block, slot)) = db.get_nearest_slot_mapping(block_number as i64).await?
+            && block as u64 == block_number
+        {
+            return Ok(Some((slot as u64, None)));
+        }
 
-        if block_number < MERGE_BLOCK {
-            return Ok(None); // Pre-merge blocks don't have slots
+        let clock = clock
+            .get_or_try_init(|| Self::fetch_slot_clock(endpoints))
+            .await?;
+        let Some(slot) = clock.slot_at(timestamp) else {
+            return Ok(None); // Before the beacon chain
+        };
+        let Some(block_data) = Self::get_beacon_block_for_slot(endpoints, slot).await? else {
+            debug!("No beacon block at slot {} of block {}", slot, block_number);
+            return Ok(None);
+        };
+
+        // Blocks before the merge carry no payload, or an empty one
+        let payload_block = block_data
+            .pointer("/body/execution_payload/block_number")
+            .and_then(|number| number.as_str())
+            .and_then(|number| number.parse::<u64>().ok());
+        match payload_block {
+            Some(payload_block) if payload_block == block_number => {
+                if let Err(e) = db
+                    .insert_slot_mapping(block_number as i64, slot as i64)
+                    .await
+                {
+                    warn!("Failed to store slot of block {}: {:#}", block_number, e);
+                }
+                Ok(Some((slot, Some(block_data))))
+            }
+            None | Some(0) => Ok(None),
+            Some(payload_block) => Err(anyhow::anyhow!(
+                "Slot {} holds block {}, not block {}",
+                slot,
+                payload_block,
+                block_number
+            )),
+        }
+    }
+
+    /// Read the genesis time and slot length of the chain from the beacon node
+    async fn fetch_slot_clock(endpoints: &BeaconEndpoints) -> Result<SlotClock> {
+        let get = |path: &'static str| async move {
+            let response = endpoints
+                .send(|client, base_url| client.get(format!("{}{}", base_url, path)))
+                .await
+                .context(format!("Failed to make request to {}", path))?;
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!(
+                    "HTTP {} error from {}",
+                    response.status(),
+                    path
+                ));
+            }
+            let body: ApiHeaderResponse<serde_json::Value> = response
+                .json()
+                .await
+                .context(format!("Failed to parse {} response", path))?;
+            Ok(body.data)
+        };
+        // Quoted by the beacon API
+        let number = |value: &serde_json::Value, field: &str| {
+            value
+                .get(field)
+                .and_then(|value| value.as_str())
+                .and_then(|value| value.parse::<u64>().ok())
+                .context(format!("Beacon node reported no {}", field))
+        };
+
+        let genesis = get("/eth/v1/beacon/genesis").await?;
+        let spec = get("/eth/v1/config/spec").await?;
+        Ok(SlotClock {
+            genesis_time: number(&genesis, "genesis_time")?,
+            seconds_per_slot: number(&spec, "SECONDS_PER_SLOT")?,
+        })
+    }
+
+    /// Count the deposits processed by a beacon block: bridge deposits in the body plus
+    /// EIP-6110 deposit requests carried in the execution requests (Electra onwards)
+    fn block_deposit_count(block_data: &serde_json::Value) -> Option<i64> {
+        let body = block_data.get("body")?;
+        let deposits = body.get("deposits")?.as_array()?.len();
+        let deposit_requests = body
+            .get("execution_requests")
+            .and_then(|requests| requests.get("deposits"))
+            .and_then(|deposits| deposits.as_array())
+            .map_or(0, |deposits| deposits.len());
+
+        Some((deposits + deposit_requests) as i64)
+    }
+
+    /// Share of the sync committee that signed the parent block, as a percentage of the
+    /// bits of the block's sync aggregate. None before Altair.
+    fn sync_participation(block_data: &serde_json::Value) -> Option<f64> {
+        let bits = block_data
+            .pointer("/body/sync_aggregate/sync_committee_bits")?
+            .as_str()?;
+        let bits = hex::decode(bits.trim_start_matches("0x")).ok()?;
+        if bits.is_empty() {
+            return None;
         }
 
-        // Estimate slot based on block progression
-        let estimated_slot = MERGE_SLOT + (block_number - MERGE_BLOCK);
-        Ok(Some(estimated_slot))
+        let participants: u32 = bits.iter().map(|byte| byte.count_ones()).sum();
+        Some(participants as f64 / (bits.len() * 8) as f64 * 100.0)
+    }
+
+    /// Count the proposer and attester slashings included in a beacon block
+    fn slashing_count(block_data: &serde_json::Value) -> Option<i64> {
+        let body = block_data.get("body")?;
+        let count = |field: &str| body.get(field)?.as_array().map(|items| items.len());
+
+        Some((count("proposer_slashings")? + count("attester_slashings")?) as i64)
     }
 
     /// Get beacon block data for a specific slot
     async fn get_beacon_block_for_slot(
-        client: Client,
-        base_url: String,
+        endpoints: &BeaconEndpoints,
         slot: u64,
     ) -> Result<Option<serde_json::Value>> {
-        let url = format!("{}/eth/v2/beacon/blocks/{}", base_url, slot);
+        let url = format!("/eth/v2/beacon/blocks/{}", slot);
 
-        let response = match client.get(&url).send().await {
+        let response = match endpoints
+            .send(|client, base_url| client.get(format!("{}{}", base_url, url)))
+            .await
+        {
             Ok(response) => response,
             Err(e) => {
                 debug!("Failed to fetch beacon block for slot {}: {}", slot, e);
@@ -497,7 +1057,8 @@ pub struct BeaconBlockData {
     pub epoch: Option<i64>,
     pub slot_root: Option<String>,
     pub parent_root: Option<String>,
-    pub beacon_deposit_count: Option<i64>,
+    pub block_deposit_count: Option<i64>,
+    pub eth1_deposit_count: Option<i64>,
     pub graffiti: Option<String>,
     pub randao_reveal: Option<String>,
     pub randao_mix: Option<String>,
@@ -0,0 +1,297 @@
+use crate::{
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+    executor::RateLimitFeedback,
+    rpc::ProviderStats,
+};
+use anyhow::Result;
+use reqwest::{header::RETRY_AFTER, Client, RequestBuilder, Response, StatusCode};
+use serde_json::json;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Consecutive failures after which a beacon node is benched
+const MAX_CONSECUTIVE_FAILURES: u64 = 3;
+
+/// How long a benched beacon node is skipped before it is tried again
+const BENCH_DURATION: Duration = Duration::from_secs(30);
+
+/// Health counters of a single beacon node
+#[derive(Debug)]
+struct Endpoint {
+    base_url: String,
+    label: String, // Scheme and host only, URLs often embed API keys
+    requests: AtomicU64,
+    failures: AtomicU64,
+    consecutive_failures: AtomicU64,
+    latency_ms: AtomicU64, // Moving average of successful request latency
+    benched_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_benched(&self) -> bool {
+        self.benched_until
+            .lock()
+            .unwrap()
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    fn record_success(&self, latency: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        *self.benched_until.lock().unwrap() = None;
+
+        let sample = latency.as_millis() as u64;
+        let previous = self.latency_ms.load(Ordering::Relaxed);
+        let average = if previous == 0 {
+            sample
+        } else {
+            (previous * 4 + sample) / 5
+        };
+        self.latency_ms.store(average, Ordering::Relaxed);
+    }
+
+    /// Record a failed request, returns whether the node just got benched
+    fn record_failure(&self) -> bool {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        let consecutive = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if consecutive < MAX_CONSECUTIVE_FAILURES {
+            return false;
+        }
+
+        let mut benched_until = self.benched_until.lock().unwrap();
+        let newly_benched = !benched_until.is_some_and(|until| Instant::now() < until);
+        *benched_until = Some(Instant::now() + BENCH_DURATION);
+        newly_benched
+    }
+
+    /// Success rate in percent, 100 for nodes that were never used
+    fn health_score(&self) -> f64 {
+        let requests = self.requests.load(Ordering::Relaxed);
+        if requests == 0 {
+            return 100.0;
+        }
+        let failures = self.failures.load(Ordering::Relaxed);
+        (requests - failures) as f64 * 100.0 / requests as f64
+    }
+}
+
+/// Beacon API nodes requests are spread over.
+///
+/// Requests go to the healthiest available node; connection errors, rate limits and
+/// server errors fail over to the next one. Nodes failing repeatedly are benched for a
+/// while, the same way `FallbackTransport` handles ETH RPC providers.
+#[derive(Debug)]
+pub struct BeaconEndpoints {
+    client: Client,
+    endpoints: Vec<Endpoint>,
+    events: EventLog,
+    rate_limits: Arc<RateLimitFeedback>, // Told when every node tried answered 429
+}
+
+impl BeaconEndpoints {
+    /// Create the endpoint set from a comma-separated list of beacon API URLs, reporting
+    /// requests no node would serve because of rate limits to `rate_limits`
+    pub fn new(
+        beacon_urls: &str,
+        events: EventLog,
+        rate_limits: Arc<RateLimitFeedback>,
+    ) -> Result<Self> {
+        let endpoints: Vec<Endpoint> = beacon_urls
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .enumerate()
+            .map(|(index, url)| {
+                let base_url = url.trim_end_matches('/').to_string();
+                let label = match reqwest::Url::parse(&base_url) {
+                    Ok(parsed) => format!(
+                        "#{} {}://{}",
+                        index + 1,
+                        parsed.scheme(),
+                        parsed.host_str().unwrap_or_default()
+                    ),
+                    Err(_) => format!("#{}", index + 1),
+                };
+
+                Endpoint {
+                    base_url,
+                    label,
+                    requests: AtomicU64::new(0),
+                    failures: AtomicU64::new(0),
+                    consecutive_failures: AtomicU64::new(0),
+                    latency_ms: AtomicU64::new(0),
+                    benched_until: Mutex::new(None),
+                }
+            })
+            .collect();
+
+        if endpoints.is_empty() {
+            return Err(anyhow::anyhow!("No beacon API URL configured"));
+        }
+
+        Ok(Self {
+            client: Client::new(),
+            endpoints,
+            events,
+            rate_limits,
+        })
+    }
+
+    /// Labels of the configured nodes, in configuration order
+    pub fn labels(&self) -> Vec<&str> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.label.as_str())
+            .collect()
+    }
+
+    /// Node statistics in configuration order
+    pub fn stats(&self) -> Vec<ProviderStats> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| ProviderStats {
+                url: endpoint.label.clone(),
+                requests: endpoint.requests.load(Ordering::Relaxed),
+                failures: endpoint.failures.load(Ordering::Relaxed),
+                consecutive_failures: endpoint.consecutive_failures.load(Ordering::Relaxed),
+                avg_latency_ms: endpoint.latency_ms.load(Ordering::Relaxed),
+                health_score: endpoint.health_score(),
+                available: !endpoint.is_benched(),
+            })
+            .collect()
+    }
+
+    /// Send a request built from the base URL of each node in turn until one answers.
+    ///
+    /// Any response but a rate limit or server error is returned as is, so 404s for missed
+    /// slots reach the caller. When every node fails, the last failing response is
+    /// returned, or an error if none could be reached.
+    pub async fn send<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn(&Client, &str) -> RequestBuilder,
+    {
+        let mut last_failure = None;
+        let mut rate_limited = None; // Longest Retry-After of the nodes answering 429
+        for index in self.ranked_endpoints() {
+            let endpoint = &self.endpoints[index];
+            let started = Instant::now();
+
+            let error = match build(&self.client, &endpoint.base_url).send().await {
+                Ok(response) if !is_node_fault(response.status()) => {
+                    endpoint.record_success(started.elapsed());
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        rate_limited = Some(retry_after(&response).max(rate_limited.flatten()));
+                    }
+                    let error = format!("HTTP {}", response.status());
+                    last_failure = Some(Ok(response));
+                    error
+                }
+                Err(e) => {
+                    let error = e.to_string();
+                    last_failure = Some(Err(anyhow::Error::new(e)));
+                    error
+                }
+            };
+
+            self.record_failure(endpoint, &error);
+        }
+
+        if let Some(retry_after) = rate_limited {
+            self.rate_limits.report(retry_after);
+        }
+        last_failure.expect("at least one beacon node is configured")
+    }
+
+    /// Probe the health endpoint of every node, returns whether any of them is healthy
+    pub async fn check_health(&self) -> bool {
+        let mut any_healthy = false;
+        for endpoint in &self.endpoints {
+            let url = format!("{}/eth/v1/node/health", endpoint.base_url);
+            let started = Instant::now();
+
+            match self.client.get(&url).send().await {
+                Ok(response) if response.status().is_success() => {
+                    endpoint.record_success(started.elapsed());
+                    any_healthy = true;
+                    info!("Beacon node {} is healthy", endpoint.label);
+                }
+                Ok(response) => {
+                    self.record_failure(endpoint, &format!("HTTP {}", response.status()))
+                }
+                Err(e) => self.record_failure(endpoint, &e.to_string()),
+            }
+        }
+        any_healthy
+    }
+
+    fn record_failure(&self, endpoint: &Endpoint, error: &str) {
+        if endpoint.record_failure() {
+            self.events.record(
+                NewIndexerEvent::warning(
+                    IndexerEventKind::RpcFailover,
+                    format!(
+                        "Beacon node {} benched for {}s after {} consecutive failures",
+                        endpoint.label,
+                        BENCH_DURATION.as_secs(),
+                        MAX_CONSECUTIVE_FAILURES
+                    ),
+                )
+                .with_details(json!({
+                    "provider": endpoint.label,
+                    "error": error,
+                })),
+            );
+        }
+        if self.endpoints.len() > 1 {
+            warn!(
+                "Beacon node {} failed: {}, trying next node",
+                endpoint.label, error
+            );
+        }
+    }
+
+    /// Endpoint indexes in the order they should be tried: available nodes by consecutive
+    /// failures then latency, benched nodes last as a final resort
+    fn ranked_endpoints(&self) -> Vec<usize> {
+        let mut ranked: Vec<usize> = (0..self.endpoints.len()).collect();
+        ranked.sort_by_key(|&index| {
+            let endpoint = &self.endpoints[index];
+            (
+                endpoint.is_benched(),
+                endpoint.consecutive_failures.load(Ordering::Relaxed),
+                endpoint.latency_ms.load(Ordering::Relaxed),
+                index,
+            )
+        });
+        ranked
+    }
+}
+
+/// Whether a status points at the node (rate limited, overloaded, syncing) rather than at
+/// the request, so another node may succeed
+fn is_node_fault(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Delay a response asks to wait through its Retry-After header, in seconds (the
+/// HTTP date form is ignored)
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
@@ -1,4 +1,6 @@
 // Beacon Chain integration module
 pub mod client;
+mod fallback;
 
 pub use client::BeaconClient;
+pub use fallback::BeaconEndpoints;
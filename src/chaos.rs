@@ -0,0 +1,147 @@
+//! Failure injection for resilience tests.
+//!
+//! A [`FaultInjector`] attached to the RPC client and the database makes a share of RPC
+//! requests fail or answer late, hands out receipts of the wrong transaction and makes
+//! block writes fail before they commit. Draws come from a seeded generator, so a failing
+//! run can be replayed with the same seed. Only compiled with the `chaos` feature.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+use std::time::Duration;
+
+/// JSON-RPC methods whose results are receipts, as an object or an array of objects
+const RECEIPT_METHODS: [&str; 2] = ["eth_getTransactionReceipt", "eth_getBlockReceipts"];
+
+/// Share of operations failed or slowed down, each between 0 and 1
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FaultRates {
+    pub rpc_error: f64, // RPC requests failing like an unreachable provider
+    pub rpc_delay: f64, // RPC requests answered after `rpc_delay_duration`
+    pub rpc_delay_duration: Duration,
+    pub malformed_receipt: f64, // Receipt responses pointing at another transaction
+    pub db_write_failure: f64,  // Block writes failing before their commit
+}
+
+/// Faults injected so far, by kind
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InjectedFaults {
+    pub rpc_errors: u64,
+    pub rpc_delays: u64,
+    pub malformed_receipts: u64,
+    pub db_write_failures: u64,
+}
+
+/// Decides which operations fail, from rates that can change while the indexer runs
+#[derive(Debug)]
+pub struct FaultInjector {
+    rates: Mutex<FaultRates>,
+    seed: u64,
+    draws: AtomicU64,
+    rpc_errors: AtomicU64,
+    rpc_delays: AtomicU64,
+    malformed_receipts: AtomicU64,
+    db_write_failures: AtomicU64,
+}
+
+impl FaultInjector {
+    pub fn new(rates: FaultRates, seed: u64) -> Self {
+        Self {
+            rates: Mutex::new(rates),
+            seed,
+            draws: AtomicU64::new(0),
+            rpc_errors: AtomicU64::new(0),
+            rpc_delays: AtomicU64::new(0),
+            malformed_receipts: AtomicU64::new(0),
+            db_write_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the rates, e.g. to let the indexer start before injecting faults
+    pub fn set_rates(&self, rates: FaultRates) {
+        *self.rates.lock().unwrap() = rates;
+    }
+
+    /// Faults injected since creation
+    pub fn injected(&self) -> InjectedFaults {
+        InjectedFaults {
+            rpc_errors: self.rpc_errors.load(Ordering::Relaxed),
+            rpc_delays: self.rpc_delays.load(Ordering::Relaxed),
+            malformed_receipts: self.malformed_receipts.load(Ordering::Relaxed),
+            db_write_failures: self.db_write_failures.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Whether an RPC request should fail
+    pub fn rpc_error(&self) -> bool {
+        let rate = self.rates.lock().unwrap().rpc_error;
+        self.inject(rate, &self.rpc_errors)
+    }
+
+    /// How long an RPC request should be held before it is sent, if at all
+    pub fn rpc_delay(&self) -> Option<Duration> {
+        let rates = *self.rates.lock().unwrap();
+        self.inject(rates.rpc_delay, &self.rpc_delays)
+            .then_some(rates.rpc_delay_duration)
+    }
+
+    /// Point one receipt of a `method` result at another transaction, returns whether the
+    /// result was changed. Results of other methods are left alone.
+    pub fn malform_receipts(&self, method: &str, result: &mut Value) -> bool {
+        if !RECEIPT_METHODS.contains(&method) {
+            return false;
+        }
+        let receipt = match result {
+            Value::Array(receipts) if !receipts.is_empty() => {
+                let index = (self.draw() * receipts.len() as f64) as usize;
+                &mut receipts[index]
+            }
+            Value::Object(_) => result,
+            _ => return false,
+        };
+        let Some(hash) = receipt.get_mut("transactionHash") else {
+            return false;
+        };
+
+        let rate = self.rates.lock().unwrap().malformed_receipt;
+        if !self.inject(rate, &self.malformed_receipts) {
+            return false;
+        }
+        *hash = Value::String(format!("0x{:064x}", self.next()));
+        true
+    }
+
+    /// Whether a block write should fail before its commit
+    pub fn db_write_failure(&self) -> bool {
+        let rate = self.rates.lock().unwrap().db_write_failure;
+        self.inject(rate, &self.db_write_failures)
+    }
+
+    /// Draw against `rate`, counting the fault in `injected` when it hits
+    fn inject(&self, rate: f64, injected: &AtomicU64) -> bool {
+        if rate <= 0.0 || self.draw() >= rate {
+            return false;
+        }
+        injected.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    /// Uniform draw in [0, 1)
+    fn draw(&self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Next value of a SplitMix64 sequence started at the seed
+    fn next(&self) -> u64 {
+        let draw = self.draws.fetch_add(1, Ordering::Relaxed);
+        let mut z = self
+            .seed
+            .wrapping_add(draw.wrapping_add(1).wrapping_mul(0x9e3779b97f4a7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
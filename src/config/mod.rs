@@ -2,11 +2,16 @@ use serde::{Deserialize, Serialize};
 use std::{env, fmt, fs};
 use thiserror::Error;
 
+use crate::export::ColdStorageFormat;
+
+/// Beacon deposit contract of Ethereum mainnet
+pub const MAINNET_DEPOSIT_CONTRACT: &str = "0x00000000219ab540356cbb839cbe05303d7705fa";
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AppConfig {
     pub database_url: String,
-    pub eth_rpc_url: String,
-    pub beacon_rpc_url: String, // Beacon Chain API URL (now mandatory)
+    pub eth_rpc_url: String, // Comma-separated list of ETH RPC URLs, tried in order on failure
+    pub beacon_rpc_url: String, // Comma-separated list of Beacon Chain API URLs, failed over on errors
     pub api_port: u16,
     pub start_block: Option<i64>, // Changed from u64 to i64 to support -1
 
@@ -21,24 +26,229 @@ pub struct AppConfig {
     pub beacon_rpc_min_interval_ms: u64, // Min interval between Beacon RPC requests (ms)
     pub eth_rpc_max_concurrent: usize, // Max concurrent ETH RPC requests
     pub beacon_rpc_max_concurrent: usize, // Max concurrent Beacon RPC requests
+    pub eth_rpc_queue_capacity: usize, // ETH RPC requests waiting for a slot before new ones are refused
+    pub beacon_rpc_queue_capacity: usize, // Beacon RPC requests waiting for a slot before new ones are refused
+    pub rpc_rate_limit_cooldown_seconds: u64, // Lowered RPC limits after a rate limit (0 = disabled)
 
     // Batch Processing Configuration
     pub account_batch_size: usize, // Batch size for account balance fetching
     pub rpc_batch_size: usize,     // Batch size for RPC calls
     pub max_concurrent_balance_fetches: usize, // Max concurrent balance fetch operations
+    pub balance_history_interval_blocks: i64, // Blocks per balance history snapshot of an account (0 = disabled)
+
+    // Multicall Configuration
+    pub multicall_address: Option<String>, // Multicall3 contract used to batch eth_calls (None = disabled)
+    pub multicall_batch_size: usize,       // Max calls aggregated into a single multicall request
+
+    // Calldata Configuration
+    pub transaction_input_max_bytes: usize, // Calldata stored per transaction, longer inputs are truncated (0 = unlimited)
+
+    // Contract Lineage Configuration
+    pub trace_contract_creations: bool, // Trace blocks (debug_traceBlockByNumber) to find contracts deployed by contracts
+
+    // Contract Verification Configuration
+    pub solc_path: String, // solc binary, "{version}" is replaced by the requested compiler version
+    pub solc_timeout_seconds: u64, // Max time a single compilation may take
+
+    // ENS Configuration
+    pub ens_cache_ttl_seconds: u64, // How long ENS resolutions are cached before re-resolving
+
+    // Watchlist Configuration
+    pub tracked_addresses: Vec<String>, // Only index activity of these addresses (empty = full chain)
+
+    // Header Sync Configuration
+    pub headers_only_ranges: Vec<BlockRange>, // Blocks indexed without transactions and receipts (empty = none)
+
+    // Mempool Configuration
+    pub mempool_enabled: bool, // Watch pending transactions (requires txpool or filter support)
+    pub mempool_poll_interval_ms: u64, // Interval between mempool polls (ms)
+    pub mempool_max_age_seconds: u64, // Pending transactions older than this are dropped
+    pub mempool_fetch_limit: usize, // Max transactions fetched per poll in filter mode
+
+    // Validator Registry Configuration
+    pub validator_sync_interval_seconds: u64, // Interval between validator registry syncs (0 = disabled)
+    pub validator_sync_batch_size: usize,     // Validators requested per beacon API call
+    pub finality_poll_interval_seconds: u64, // Interval between finality checkpoint polls (0 = disabled)
+
+    // Liveness Configuration
+    pub liveness_check_interval_seconds: u64, // Interval between liveness alert checks (0 = disabled)
+    pub liveness_max_missed_slots: i64,       // Consecutive missed slots before alerting
+    pub liveness_stall_seconds: u64,          // Time without a new node head before alerting
+
+    // Pruning Configuration
+    pub prune_interval_seconds: u64, // Interval between pruning runs (0 = disabled)
+    pub prune_blocks_older_than: i64, // Blocks kept behind the newest indexed block (0 = keep all)
+    pub prune_logs_older_than: i64, // Blocks of logs kept behind the newest indexed block (0 = keep all)
+    pub prune_token_transfers_older_than: i64, // Blocks of token transfers kept (0 = keep all)
+    pub prune_batch_blocks: i64,    // Blocks cleared per delete transaction
+    pub prune_archive_database: Option<String>, // SQLite file pruned rows are copied to before deletion
+    pub prune_vacuum: bool,                     // VACUUM the database after a run deleted rows
+
+    // Database Sharding Configuration
+    pub database_shard_blocks: i64, // Blocks per shard database file (0 = sharding disabled)
+    pub database_shard_interval_seconds: u64, // Interval between shard rotations
+    pub database_shard_confirmations: i64, // Blocks the head must be into a shard before the previous one moves
+
+    // Dataset Publisher Configuration
+    pub publish_interval_seconds: u64, // Interval between publisher runs (0 = disabled)
+    pub publish_target: Option<String>, // s3://bucket/prefix or ipfs (unset = disabled)
+    pub publish_format: ColdStorageFormat, // File format of published ranges
+    pub publish_range_blocks: i64,     // Blocks per published range, aligned to multiples
+    pub publish_confirmations: i64,    // Blocks a range must trail the newest indexed block
+    pub publish_work_dir: String,      // Directory ranges are exported to before uploading
+    pub publish_s3_endpoint: String,   // S3-compatible endpoint, addressed path-style
+    pub publish_s3_region: String,
+    pub publish_s3_access_key_id: Option<String>,
+    pub publish_s3_secret_access_key: Option<String>,
+    pub publish_ipfs_api_url: String, // Kubo RPC API files are added through
+    pub publish_ipns_key: Option<String>, // IPNS key the manifest CID is published under
+
+    // Multi-Network Configuration
+    pub network_name: String, // Network configured by the unprefixed variables, served under /api
+    pub networks: Vec<String>, // Additional networks, each configured by <NAME>_* variables
+    pub network_profile: NetworkProfile, // Chain family, enables L2-specific indexing
+    pub deposit_contract_address: Option<String>, // Beacon deposit contract whose DepositEvent logs are indexed (None = disabled)
+
+    // Event Streaming Configuration
+    pub stream_broker_url: Option<String>, // nats://host:4222 or kafka://broker1:9092,broker2:9092 (unset = disabled)
+    pub stream_topic_prefix: String, // Topics are <prefix>.blocks, .transactions and .token_transfers
+    pub live_max_subscribers: usize, // Concurrent live stream subscribers, further ones are refused (0 = unlimited)
+    pub live_buffer_size: usize,     // Items buffered per live subscriber before they are dropped
+    pub live_slow_consumer_seconds: u64, // Time a live subscriber may keep its buffer full before it is disconnected
+
+    // Block Webhook Configuration
+    pub webhook_interval_seconds: u64, // Interval between outbox delivery runs (0 = disabled)
+    pub webhook_timeout_seconds: u64,  // Time a webhook endpoint has to answer
+    pub webhook_max_attempts: i64, // Failed deliveries before a notification is dropped (0 = retry forever)
+
+    // Image Proxy Configuration
+    pub image_proxy_allowed_hosts: Vec<String>, // Hosts images may be fetched from
+    pub image_proxy_max_bytes: usize,           // Largest image or metadata document fetched
+    pub image_cache_dir: String,                // Directory holding resized images
+    pub image_cache_max_bytes: u64,             // Cache size past which unused images are deleted
+    pub ipfs_gateway_url: String,               // Gateway ipfs:// URIs are fetched through
+
+    // Market Data Configuration
+    pub market_data_providers: Vec<MarketDataProvider>, // Price sources tried in order (empty = disabled)
+    pub market_data_interval_seconds: u64,              // Interval between market data updates
+    pub coingecko_api_key: Option<String>, // CoinGecko demo API key, for its higher rate limit
+
+    // API Access Configuration
+    pub api_auth_required: bool, // Reject API requests without a valid API key
+    pub api_anonymous_requests_per_minute: u32, // Per-IP limit for requests without a key (0 = unlimited)
+    pub api_key_default_requests_per_minute: u32, // Limit assigned to newly created API keys
+    pub admin_api_key: Option<String>, // Bearer token of the indexer control endpoints (unset = disabled)
+
+    // API Response Configuration
+    pub api_iso_timestamps: bool, // Add ISO-8601 fields next to unix timestamps, normalize *_at columns
+    pub api_timezone: String, // "UTC" or a fixed offset such as "+02:00" ISO fields are rendered in
+    pub recent_cache_blocks: usize, // Newest blocks kept in memory for the latest-data endpoints (0 = disabled)
+
+    // Cache Warm-up Configuration
+    pub cache_warm_accounts: usize, // Most recently seen accounts preloaded at startup (0 = disabled)
+    pub cache_warm_tokens: usize,   // Most recently seen tokens preloaded at startup (0 = disabled)
 
     // Token Service Configuration
     pub token_balance_update_interval_ms: u64, // Interval between token balance updates (ms)
     pub token_refresh_interval_ms: u64,        // Interval between token refresh operations (ms)
+    pub token_balance_refresh_interval_seconds: u64, // Interval between stale balance refreshes (0 = disabled)
+    pub token_balance_refresh_max_age_blocks: u64, // Blocks after which a stored balance is refreshed
+    pub token_stats_interval_seconds: u64, // Interval between token supply and holder count updates (0 = disabled)
+    pub token_stats_batch_size: usize,     // Tokens whose totalSupply() is read per update
+    pub token_activity_window_blocks: u64, // Blocks counted as recent token activity
+    pub token_list_urls: Vec<String>, // Token lists consulted for token metadata (empty = disabled)
 
     // Timing Configuration
     pub sync_delay_seconds: Option<u32>, // Delay between sync attempts when already in sync
     pub block_fetch_interval_seconds: Option<u32>, // Polling interval for new blocks
     pub worker_timeout_seconds: u64,     // Timeout for workers waiting for blocks (seconds)
+    pub consistency_check_interval_seconds: u64, // Interval between block consistency checks (0 = disabled)
+    pub consistency_check_range: i64,            // Blocks compared per consistency check
+    pub analytics_interval_seconds: u64, // Interval between aggregate statistics updates (0 = disabled)
+    pub issuance_sample_blocks: usize,   // Blocks per day whose rewards estimate issuance (0 = disabled)
+    pub rewards_interval_seconds: u64,   // Interval between block rewards fetches (0 = disabled)
+    pub storage_stats_interval_seconds: u64, // Interval between checks for the daily storage sample (0 = disabled)
     pub bigquery_service_account_path: Option<String>,
 
     // Logging Configuration
-    pub log_level: String, // Log level for tracing (e.g., "info", "debug", "error")
+    pub log_level: String, // Tracing directives (e.g., "info" or "info,indexer=debug,rpc=warn")
+}
+
+/// Chain family of a network, deciding which chain-specific data is indexed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkProfile {
+    Ethereum, // L1, with beacon chain data
+    OpStack,  // Optimism, Base and other OP-stack rollups
+    Arbitrum, // Arbitrum One and Nova
+}
+
+impl NetworkProfile {
+    /// Parse a NETWORK_PROFILE value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "ethereum" | "l1" => Some(Self::Ethereum),
+            "op-stack" | "op_stack" | "optimism" | "base" => Some(Self::OpStack),
+            "arbitrum" => Some(Self::Arbitrum),
+            _ => None,
+        }
+    }
+
+    /// Whether blocks carry L2 data (L1 fees, system transactions, batch references)
+    pub fn is_l2(self) -> bool {
+        self != Self::Ethereum
+    }
+}
+
+/// Source of ETH price and market data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketDataProvider {
+    Coingecko, // Price, 24h change and market cap
+    Coinbase,  // Price and 24h change of the ETH-USD exchange pair
+}
+
+impl MarketDataProvider {
+    /// Parse a MARKET_DATA_PROVIDERS entry
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "coingecko" => Some(Self::Coingecko),
+            "coinbase" => Some(Self::Coinbase),
+            _ => None,
+        }
+    }
+
+    /// Name stored with the prices fetched from this provider
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Coingecko => "coingecko",
+            Self::Coinbase => "coinbase",
+        }
+    }
+}
+
+/// Inclusive range of block numbers, open ended when `to` is None
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BlockRange {
+    pub from: i64,
+    pub to: Option<i64>,
+}
+
+impl BlockRange {
+    /// Parse a `from-to` range, `from-` for every block from `from` on
+    pub fn parse(value: &str) -> Option<Self> {
+        let (from, to) = value.trim().split_once('-')?;
+        let from: i64 = from.trim().parse().ok().filter(|from| *from >= 0)?;
+        let to = match to.trim() {
+            "" => None,
+            to => Some(to.parse().ok().filter(|to| *to >= from)?),
+        };
+        Some(Self { from, to })
+    }
+
+    pub fn contains(&self, block_number: i64) -> bool {
+        block_number >= self.from && self.to.is_none_or(|to| block_number <= to)
+    }
 }
 
 #[derive(Error, Debug)]
@@ -59,6 +269,7 @@ impl AppConfig {
         // Load .env file if present (ignore error if not found)
         let _ = dotenvy::dotenv();
 
+        let network_profile = parse_network_profile("NETWORK_PROFILE")?;
         // Initialize with defaults
         let config = Self {
             database_url: env::var("DATABASE_URL")
@@ -108,6 +319,18 @@ impl AppConfig {
                 .ok()
                 .and_then(|n| n.parse().ok())
                 .unwrap_or(10),
+            eth_rpc_queue_capacity: env::var("ETH_RPC_QUEUE_CAPACITY")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(10000),
+            beacon_rpc_queue_capacity: env::var("BEACON_RPC_QUEUE_CAPACITY")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1000),
+            rpc_rate_limit_cooldown_seconds: env::var("RPC_RATE_LIMIT_COOLDOWN_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(60),
 
             // Batch Processing Configuration
             account_batch_size: env::var("ACCOUNT_BATCH_SIZE")
@@ -122,6 +345,306 @@ impl AppConfig {
                 .ok()
                 .and_then(|n| n.parse().ok())
                 .unwrap_or(10),
+            balance_history_interval_blocks: env::var("BALANCE_HISTORY_INTERVAL_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(100),
+
+            // Multicall Configuration
+            multicall_address: match env::var("MULTICALL_ADDRESS") {
+                Ok(address) if address.is_empty() || address == "none" => None,
+                Ok(address) => Some(address),
+                Err(_) => Some(crate::rpc::multicall::MULTICALL3_ADDRESS.to_string()),
+            },
+            multicall_batch_size: env::var("MULTICALL_BATCH_SIZE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(500),
+
+            // Calldata Configuration
+            transaction_input_max_bytes: env::var("TRANSACTION_INPUT_MAX_BYTES")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(16_384),
+
+            // Contract Lineage Configuration
+            trace_contract_creations: env::var("TRACE_CONTRACT_CREATIONS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+
+            // Contract Verification Configuration
+            solc_path: env::var("SOLC_PATH").unwrap_or_else(|_| "solc".to_string()),
+            solc_timeout_seconds: env::var("SOLC_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(60),
+
+            // ENS Configuration
+            ens_cache_ttl_seconds: env::var("ENS_CACHE_TTL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(86400),
+
+            // Watchlist Configuration
+            tracked_addresses: env::var("TRACKED_ADDRESSES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|address| address.trim().to_lowercase())
+                .filter(|address| !address.is_empty())
+                .collect(),
+
+            // Header Sync Configuration
+            headers_only_ranges: parse_block_ranges("HEADERS_ONLY_BLOCKS")?,
+
+            // Mempool Configuration
+            mempool_enabled: env::var("MEMPOOL_ENABLED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            mempool_poll_interval_ms: env::var("MEMPOOL_POLL_INTERVAL_MS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(2000),
+            mempool_max_age_seconds: env::var("MEMPOOL_MAX_AGE_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(3600),
+            mempool_fetch_limit: env::var("MEMPOOL_FETCH_LIMIT")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(200),
+
+            // Validator Registry Configuration
+            validator_sync_interval_seconds: env::var("VALIDATOR_SYNC_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            validator_sync_batch_size: env::var("VALIDATOR_SYNC_BATCH_SIZE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1000),
+            finality_poll_interval_seconds: env::var("FINALITY_POLL_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(60),
+
+            // Liveness Configuration
+            liveness_check_interval_seconds: env::var("LIVENESS_CHECK_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(60),
+            liveness_max_missed_slots: env::var("LIVENESS_MAX_MISSED_SLOTS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(4),
+            liveness_stall_seconds: env::var("LIVENESS_STALL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(120),
+
+            // Pruning Configuration
+            prune_interval_seconds: env::var("PRUNE_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(3600),
+            prune_blocks_older_than: env::var("PRUNE_BLOCKS_OLDER_THAN")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            prune_logs_older_than: env::var("PRUNE_LOGS_OLDER_THAN")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            prune_token_transfers_older_than: env::var("PRUNE_TOKEN_TRANSFERS_OLDER_THAN")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            prune_batch_blocks: env::var("PRUNE_BATCH_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(100),
+            prune_archive_database: env::var("PRUNE_ARCHIVE_DATABASE")
+                .ok()
+                .filter(|path| !path.trim().is_empty()),
+            prune_vacuum: env::var("PRUNE_VACUUM")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+
+            // Database Sharding Configuration
+            database_shard_blocks: env::var("DATABASE_SHARD_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            database_shard_interval_seconds: env::var("DATABASE_SHARD_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(3600),
+            database_shard_confirmations: env::var("DATABASE_SHARD_CONFIRMATIONS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(128),
+
+            // Dataset Publisher Configuration
+            publish_interval_seconds: env::var("PUBLISH_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(3600),
+            publish_target: env::var("PUBLISH_TARGET")
+                .ok()
+                .map(|target| target.trim().trim_end_matches('/').to_string())
+                .filter(|target| !target.is_empty()),
+            publish_format: match env::var("PUBLISH_FORMAT") {
+                Ok(value) => ColdStorageFormat::parse(&value).ok_or_else(|| {
+                    ConfigError::InvalidValue(format!(
+                        "PUBLISH_FORMAT must be parquet or csv, got {}",
+                        value
+                    ))
+                })?,
+                Err(_) => ColdStorageFormat::Parquet,
+            },
+            publish_range_blocks: env::var("PUBLISH_RANGE_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(100_000),
+            publish_confirmations: env::var("PUBLISH_CONFIRMATIONS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(128),
+            publish_work_dir: env::var("PUBLISH_WORK_DIR")
+                .unwrap_or_else(|_| "data/publish".to_string()),
+            publish_s3_endpoint: env::var("PUBLISH_S3_ENDPOINT")
+                .unwrap_or_else(|_| "https://s3.amazonaws.com".to_string()),
+            publish_s3_region: env::var("PUBLISH_S3_REGION")
+                .unwrap_or_else(|_| "us-east-1".to_string()),
+            publish_s3_access_key_id: env::var("PUBLISH_S3_ACCESS_KEY_ID")
+                .or_else(|_| env::var("AWS_ACCESS_KEY_ID"))
+                .ok(),
+            publish_s3_secret_access_key: env::var("PUBLISH_S3_SECRET_ACCESS_KEY")
+                .or_else(|_| env::var("AWS_SECRET_ACCESS_KEY"))
+                .ok(),
+            publish_ipfs_api_url: env::var("PUBLISH_IPFS_API_URL")
+                .unwrap_or_else(|_| "http://127.0.0.1:5001".to_string()),
+            publish_ipns_key: env::var("PUBLISH_IPNS_KEY")
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+
+            // Multi-Network Configuration
+            network_name: env::var("NETWORK_NAME")
+                .map(|name| name.trim().to_lowercase())
+                .unwrap_or_else(|_| "mainnet".to_string()),
+            networks: env::var("NETWORKS")
+                .map(|names| {
+                    names
+                        .split(',')
+                        .map(|name| name.trim().to_lowercase())
+                        .filter(|name| !name.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            network_profile,
+            deposit_contract_address: parse_deposit_contract(
+                "DEPOSIT_CONTRACT_ADDRESS",
+                network_profile,
+            )?,
+
+            // Event Streaming Configuration
+            stream_broker_url: env::var("STREAM_BROKER_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+            stream_topic_prefix: env::var("STREAM_TOPIC_PREFIX")
+                .unwrap_or_else(|_| "eth-indexer".to_string()),
+            live_max_subscribers: env::var("LIVE_MAX_SUBSCRIBERS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(100),
+            live_buffer_size: env::var("LIVE_BUFFER_SIZE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1024),
+            live_slow_consumer_seconds: env::var("LIVE_SLOW_CONSUMER_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(30),
+
+            // Block Webhook Configuration
+            webhook_interval_seconds: env::var("WEBHOOK_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(2),
+            webhook_timeout_seconds: env::var("WEBHOOK_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(10),
+            webhook_max_attempts: env::var("WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+
+            // Image Proxy Configuration
+            image_proxy_allowed_hosts: env::var("IMAGE_PROXY_ALLOWED_HOSTS")
+                .unwrap_or_else(|_| {
+                    "raw.githubusercontent.com,assets.coingecko.com,coin-images.coingecko.com,ipfs.io,arweave.net".to_string()
+                })
+                .split(',')
+                .map(|host| host.trim().to_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            image_proxy_max_bytes: env::var("IMAGE_PROXY_MAX_BYTES")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(5 * 1024 * 1024),
+            image_cache_dir: env::var("IMAGE_CACHE_DIR").unwrap_or_else(|_| "data/images".to_string()),
+            image_cache_max_bytes: env::var("IMAGE_CACHE_MAX_BYTES")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(256 * 1024 * 1024),
+            ipfs_gateway_url: env::var("IPFS_GATEWAY_URL")
+                .unwrap_or_else(|_| "https://ipfs.io/ipfs/".to_string()),
+
+            // Market Data Configuration
+            market_data_providers: parse_market_data_providers("MARKET_DATA_PROVIDERS")?,
+            market_data_interval_seconds: env::var("MARKET_DATA_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(60),
+            coingecko_api_key: env::var("COINGECKO_API_KEY")
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+
+            // API Access Configuration
+            api_auth_required: env::var("API_AUTH_REQUIRED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            api_anonymous_requests_per_minute: env::var("API_ANONYMOUS_REQUESTS_PER_MINUTE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            api_key_default_requests_per_minute: env::var("API_KEY_DEFAULT_REQUESTS_PER_MINUTE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(600),
+            admin_api_key: env::var("ADMIN_API_KEY")
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+
+            // API Response Configuration
+            api_iso_timestamps: env::var("API_ISO_TIMESTAMPS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            api_timezone: env::var("API_TIMEZONE").unwrap_or_else(|_| "UTC".to_string()),
+            recent_cache_blocks: env::var("RECENT_CACHE_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(64),
+
+            // Cache Warm-up Configuration
+            cache_warm_accounts: env::var("CACHE_WARM_ACCOUNTS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(10000),
+            cache_warm_tokens: env::var("CACHE_WARM_TOKENS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(10000),
 
             // Token Service Configuration
             token_balance_update_interval_ms: env::var("TOKEN_BALANCE_UPDATE_INTERVAL_MS")
@@ -132,6 +655,34 @@ impl AppConfig {
                 .ok()
                 .and_then(|n| n.parse().ok())
                 .unwrap_or(50),
+            token_balance_refresh_interval_seconds: env::var(
+                "TOKEN_BALANCE_REFRESH_INTERVAL_SECONDS",
+            )
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(300),
+            token_balance_refresh_max_age_blocks: env::var("TOKEN_BALANCE_REFRESH_MAX_AGE_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(7200),
+            token_stats_interval_seconds: env::var("TOKEN_STATS_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(600),
+            token_stats_batch_size: env::var("TOKEN_STATS_BATCH_SIZE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(500),
+            token_activity_window_blocks: env::var("TOKEN_ACTIVITY_WINDOW_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(7200),
+            token_list_urls: env::var("TOKEN_LIST_URLS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect(),
 
             // Timing Configuration
             sync_delay_seconds: env::var("SYNC_DELAY_SECONDS")
@@ -144,12 +695,174 @@ impl AppConfig {
                 .ok()
                 .and_then(|n| n.parse().ok())
                 .unwrap_or(30),
+            consistency_check_interval_seconds: env::var("CONSISTENCY_CHECK_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(60),
+            consistency_check_range: env::var("CONSISTENCY_CHECK_RANGE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1000),
+            analytics_interval_seconds: env::var("ANALYTICS_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(300),
+            issuance_sample_blocks: env::var("ISSUANCE_SAMPLE_BLOCKS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(24),
+            rewards_interval_seconds: env::var("REWARDS_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(60),
+            storage_stats_interval_seconds: env::var("STORAGE_STATS_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(3600),
             bigquery_service_account_path: env::var("BIGQUERY_SERVICE_ACCOUNT_PATH").ok(),
             log_level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
         };
 
+        config.check_network("")?;
+
+        let mut network_names = vec![config.network_name.as_str()];
+        for name in config.networks.iter().map(String::as_str) {
+            if network_names.contains(&name) {
+                return Err(ConfigError::InvalidValue(format!(
+                    "Network {} is configured more than once",
+                    name
+                )));
+            }
+            network_names.push(name);
+        }
+        if let Some(name) = network_names.iter().find(|name| {
+            name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        }) {
+            return Err(ConfigError::InvalidValue(format!(
+                "Network names may only contain a-z, 0-9, - and _, got {:?}",
+                name
+            )));
+        }
+
+        if let Some(address) = config
+            .tracked_addresses
+            .iter()
+            .find(|address| address.len() != 42 || !address.starts_with("0x"))
+        {
+            return Err(ConfigError::InvalidValue(format!(
+                "TRACKED_ADDRESSES contains an invalid address: {}",
+                address
+            )));
+        }
+
+        if let Some(target) = &config.publish_target
+            && target != "ipfs"
+            && !target.starts_with("s3://")
+        {
+            return Err(ConfigError::InvalidValue(format!(
+                "PUBLISH_TARGET must be s3://bucket/prefix or ipfs, got {}",
+                target
+            )));
+        }
+
+        if config.api_timezone_offset().is_none() {
+            return Err(ConfigError::InvalidValue(format!(
+                "API_TIMEZONE must be UTC or an offset such as +02:00, got {}",
+                config.api_timezone
+            )));
+        }
+
+        Ok(config)
+    }
+
+    /// Configuration of every network: this one first, then one per NETWORKS entry.
+    ///
+    /// Additional networks take their RPC, beacon and database settings from
+    /// <NAME>_ETH_RPC_URL, <NAME>_BEACON_RPC_URL, <NAME>_DATABASE_URL (default
+    /// sqlite:./data/<name>.db), <NAME>_START_BLOCK, <NAME>_NETWORK_PROFILE,
+    /// <NAME>_DEPOSIT_CONTRACT_ADDRESS and <NAME>_PRUNE_ARCHIVE_DATABASE (default: the
+    /// shared archive file suffixed with the name); broker topics, the publish target and
+    /// its work directory get the name appended, and the IPNS key is taken from
+    /// <NAME>_PUBLISH_IPNS_KEY. Everything else is shared.
+    pub fn network_configs(&self) -> Result<Vec<AppConfig>, ConfigError> {
+        let mut configs = vec![self.clone()];
+
+        for name in &self.networks {
+            let prefix = format!("{}_", name.to_uppercase().replace('-', "_"));
+            let var = |key: &str| env::var(format!("{}{}", prefix, key)).ok();
+
+            let network_profile = parse_network_profile(&format!("{}NETWORK_PROFILE", prefix))?;
+            let config = Self {
+                network_name: name.clone(),
+                networks: Vec::new(),
+                eth_rpc_url: var("ETH_RPC_URL")
+                    .ok_or_else(|| ConfigError::MissingEnv(format!("{}ETH_RPC_URL", prefix)))?,
+                beacon_rpc_url: var("BEACON_RPC_URL")
+                    .ok_or_else(|| ConfigError::MissingEnv(format!("{}BEACON_RPC_URL", prefix)))?,
+                database_url: var("DATABASE_URL")
+                    .unwrap_or_else(|| format!("sqlite:./data/{}.db", name)),
+                start_block: var("START_BLOCK").and_then(|b| b.parse().ok()),
+                network_profile,
+                deposit_contract_address: parse_deposit_contract(
+                    &format!("{}DEPOSIT_CONTRACT_ADDRESS", prefix),
+                    network_profile,
+                )?,
+                // Archives and broker topics must not mix the rows of several networks
+                prune_archive_database: var("PRUNE_ARCHIVE_DATABASE").or_else(|| {
+                    self.prune_archive_database.as_ref().map(|path| {
+                        let path = std::path::Path::new(path);
+                        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                        let file_name = match path.extension() {
+                            Some(extension) => {
+                                format!("{}-{}.{}", stem, name, extension.to_string_lossy())
+                            }
+                            None => format!("{}-{}", stem, name),
+                        };
+                        path.with_file_name(file_name)
+                            .to_string_lossy()
+                            .into_owned()
+                    })
+                }),
+                stream_topic_prefix: format!("{}.{}", self.stream_topic_prefix, name),
+                // Published ranges of each network go under their own prefix
+                publish_target: self.publish_target.as_ref().map(|target| {
+                    if target == "ipfs" {
+                        target.clone()
+                    } else {
+                        format!("{}/{}", target, name)
+                    }
+                }),
+                publish_work_dir: format!("{}/{}", self.publish_work_dir, name),
+                publish_ipns_key: var("PUBLISH_IPNS_KEY"),
+                ..self.clone()
+            };
+
+            // Networks sharing a database would mix their blocks
+            if let Some(other) = configs
+                .iter()
+                .find(|other| other.database_url == config.database_url)
+            {
+                return Err(ConfigError::InvalidValue(format!(
+                    "Networks {} and {} use the same database {}",
+                    other.network_name, name, config.database_url
+                )));
+            }
+
+            config.check_network(&prefix)?;
+            configs.push(config);
+        }
+
+        Ok(configs)
+    }
+
+    /// Create the database directory and validate the RPC URLs of a network, whose
+    /// variables carry the given prefix
+    fn check_network(&self, prefix: &str) -> Result<(), ConfigError> {
         // Ensure data directory exists
-        if let Some(path) = config.database_url.strip_prefix("sqlite:") {
+        if let Some(path) = self.database_url.strip_prefix("sqlite:") {
             let path = path.trim_start_matches("/");
             if let Some(dir) = std::path::Path::new(path).parent() {
                 fs::create_dir_all(dir).map_err(|e| {
@@ -161,20 +874,58 @@ impl AppConfig {
             }
         }
 
-        // Validate RPC URLs
-        if !config.eth_rpc_url.starts_with("http") && !config.eth_rpc_url.starts_with("ws") {
-            return Err(ConfigError::InvalidValue(
-                "ETH_RPC_URL must start with http:// or ws://".to_string(),
-            ));
+        // Validate RPC URLs. Requests go over HTTP only, so a ws:// or wss:// endpoint
+        // would pass here and then fail on every call
+        let eth_rpc_urls = self.eth_rpc_urls();
+        if eth_rpc_urls.is_empty() || !eth_rpc_urls.iter().all(|url| is_http_url(url)) {
+            return Err(ConfigError::InvalidValue(format!(
+                "{}ETH_RPC_URL must start with http:// or https://, WebSocket endpoints are not supported",
+                prefix
+            )));
         }
 
-        if !config.beacon_rpc_url.starts_with("http") && !config.beacon_rpc_url.starts_with("ws") {
-            return Err(ConfigError::InvalidValue(
-                "BEACON_RPC_URL must start with http:// or ws://".to_string(),
-            ));
+        let beacon_rpc_urls = self.beacon_rpc_urls();
+        if beacon_rpc_urls.is_empty() || !beacon_rpc_urls.iter().all(|url| is_http_url(url)) {
+            return Err(ConfigError::InvalidValue(format!(
+                "{}BEACON_RPC_URL must start with http:// or https://, WebSocket endpoints are not supported",
+                prefix
+            )));
         }
 
-        Ok(config)
+        Ok(())
+    }
+
+    /// Whether a block is indexed headers only, without transactions and receipts
+    pub fn is_headers_only(&self, block_number: i64) -> bool {
+        self.headers_only_ranges
+            .iter()
+            .any(|range| range.contains(block_number))
+    }
+
+    /// Offset ISO-8601 timestamps are rendered in, None when API_TIMEZONE is invalid
+    pub fn api_timezone_offset(&self) -> Option<chrono::FixedOffset> {
+        match self.api_timezone.trim() {
+            "" | "UTC" | "utc" | "Z" => chrono::FixedOffset::east_opt(0),
+            offset => offset.parse().ok(),
+        }
+    }
+
+    /// ETH RPC endpoints listed in ETH_RPC_URL
+    pub fn eth_rpc_urls(&self) -> Vec<&str> {
+        self.eth_rpc_url
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect()
+    }
+
+    /// Beacon API endpoints listed in BEACON_RPC_URL
+    pub fn beacon_rpc_urls(&self) -> Vec<&str> {
+        self.beacon_rpc_url
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect()
     }
 
     /// Resolve the start_block using database cache and environment variables
@@ -277,6 +1028,83 @@ impl AppConfig {
     }
 }
 
+/// Whether an endpoint URL uses a scheme the HTTP transports can reach
+fn is_http_url(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Comma-separated block ranges of a variable, none when unset
+fn parse_block_ranges(var: &str) -> Result<Vec<BlockRange>, ConfigError> {
+    env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|range| !range.trim().is_empty())
+        .map(|range| {
+            BlockRange::parse(range).ok_or_else(|| {
+                ConfigError::InvalidValue(format!(
+                    "{} must list block ranges such as 0-999 or 1000-, got {}",
+                    var, range
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Network profile of a variable, Ethereum when unset
+fn parse_network_profile(var: &str) -> Result<NetworkProfile, ConfigError> {
+    let value = env::var(var).unwrap_or_default();
+    NetworkProfile::parse(&value).ok_or_else(|| {
+        ConfigError::InvalidValue(format!(
+            "{} must be ethereum, op-stack or arbitrum, got {}",
+            var, value
+        ))
+    })
+}
+
+/// Comma-separated market data providers of a variable, none when unset
+fn parse_market_data_providers(var: &str) -> Result<Vec<MarketDataProvider>, ConfigError> {
+    env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|provider| !provider.is_empty() && *provider != "none")
+        .map(|provider| {
+            MarketDataProvider::parse(provider).ok_or_else(|| {
+                ConfigError::InvalidValue(format!(
+                    "{} must list coingecko and/or coinbase, got {}",
+                    var, provider
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Deposit contract of a variable, "none" disabling deposit indexing. When unset, the
+/// mainnet deposit contract on Ethereum networks and none on rollups.
+fn parse_deposit_contract(
+    var: &str,
+    profile: NetworkProfile,
+) -> Result<Option<String>, ConfigError> {
+    let Ok(value) = env::var(var) else {
+        return Ok((!profile.is_l2()).then(|| MAINNET_DEPOSIT_CONTRACT.to_string()));
+    };
+    match value.trim().to_lowercase().as_str() {
+        "" | "none" => Ok(None),
+        address
+            if address.len() == 42
+                && address.starts_with("0x")
+                && address[2..].bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            Ok(Some(address.to_string()))
+        }
+        _ => Err(ConfigError::InvalidValue(format!(
+            "{} must be a contract address or none, got {}",
+            var, value
+        ))),
+    }
+}
+
 impl fmt::Display for AppConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
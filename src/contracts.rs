@@ -0,0 +1,521 @@
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, FunctionSignature, Log, VerifiedContract, VerifyContractRequest},
+    rpc::RpcClient,
+};
+use anyhow::{Context, Result};
+use ethers::abi::{Abi, Function, RawLog, Token};
+use ethers::core::types::{H256, I256};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{process::Stdio, sync::Arc};
+use tokio::{
+    io::AsyncWriteExt,
+    process::Command,
+    sync::Semaphore,
+    time::{timeout, Duration},
+};
+use tracing::{debug, info};
+
+/// Default optimizer runs, matching solc's own default
+const DEFAULT_OPTIMIZER_RUNS: u32 = 200;
+
+/// Failed transactions replayed per failure rollup request to decode their revert reason
+const REVERT_REASON_DECODE_LIMIT: i64 = 25;
+
+/// Recorded for failures whose replay no longer reverts
+const UNKNOWN_REVERT_REASON: &str = "unknown";
+
+/// Events decoded for contracts without a verified ABI
+const STANDARD_EVENTS: &[&str] = &[
+    "event Transfer(address indexed from, address indexed to, uint256 value)",
+    "event Transfer(address indexed from, address indexed to, uint256 indexed tokenId)",
+    "event Approval(address indexed owner, address indexed spender, uint256 value)",
+    "event Approval(address indexed owner, address indexed approved, uint256 indexed tokenId)",
+    "event ApprovalForAll(address indexed owner, address indexed operator, bool approved)",
+];
+
+/// Functions decoded for contracts without a verified ABI and unknown selectors
+const STANDARD_FUNCTIONS: &[&str] = &[
+    "function transfer(address to, uint256 value)",
+    "function transferFrom(address from, address to, uint256 value)",
+    "function approve(address spender, uint256 value)",
+    "function safeTransferFrom(address from, address to, uint256 tokenId)",
+    "function safeTransferFrom(address from, address to, uint256 tokenId, bytes data)",
+    "function setApprovalForAll(address operator, bool approved)",
+    "function deposit()",
+    "function withdraw(uint256 amount)",
+];
+
+/// Method call decoded from transaction input
+#[derive(Debug, Serialize)]
+pub struct DecodedInput {
+    pub method: String,
+    pub signature: String,
+    pub params: Value,
+    pub source: &'static str, // "verified_abi", "signature_database" or "standard"
+}
+
+/// Outcome of a verification attempt
+#[derive(Debug, Serialize)]
+pub struct VerificationResult {
+    pub address: String,
+    pub verified: bool,
+    pub match_type: Option<String>, // "full" when metadata matches too, "partial" otherwise
+    pub message: String,
+}
+
+/// Service verifying submitted Solidity sources against deployed bytecode
+pub struct ContractService {
+    db: Arc<DatabaseService>,
+    rpc: Arc<RpcClient>,
+    config: AppConfig,
+    compile_permits: Semaphore, // Compilations are CPU heavy, run them one at a time
+}
+
+impl ContractService {
+    /// Create a new contract service
+    pub fn new(db: Arc<DatabaseService>, rpc: Arc<RpcClient>, config: AppConfig) -> Self {
+        Self {
+            db,
+            rpc,
+            config,
+            compile_permits: Semaphore::new(1),
+        }
+    }
+
+    /// Compile the submitted source and compare it with the code deployed at `address`.
+    /// Matching contracts are stored together with their ABI.
+    pub async fn verify(
+        &self,
+        address: &str,
+        request: &VerifyContractRequest,
+    ) -> Result<VerificationResult> {
+        let address = address.to_lowercase();
+
+        let deployed = self.rpc.get_code(&address, None).await?;
+        let deployed = hex::decode(deployed.trim_start_matches("0x"))
+            .context("Invalid bytecode returned by RPC")?;
+        if deployed.is_empty() {
+            return Err(anyhow::anyhow!("No contract deployed at {}", address));
+        }
+
+        let solc = self.solc_command(request)?;
+        let compiler_version = self.compiler_version(&solc).await?;
+        if let Some(requested) = &request.compiler_version
+            && !compiler_version.starts_with(requested.trim_start_matches('v'))
+        {
+            return Err(anyhow::anyhow!(
+                "Compiler version mismatch: requested {}, configured compiler is {}",
+                requested,
+                compiler_version
+            ));
+        }
+
+        let file_name = request
+            .file_name
+            .clone()
+            .unwrap_or_else(|| format!("{}.sol", request.contract_name));
+        let output = self.compile(&solc, &file_name, request).await?;
+
+        let contract = &output["contracts"][&file_name][&request.contract_name];
+        if contract.is_null() {
+            return Err(anyhow::anyhow!(
+                "Contract {} not found in {}",
+                request.contract_name,
+                file_name
+            ));
+        }
+
+        let bytecode = &contract["evm"]["deployedBytecode"];
+        let compiled_hex = bytecode["object"].as_str().unwrap_or_default();
+        if compiled_hex.contains("__$") {
+            return Err(anyhow::anyhow!(
+                "Contracts linking external libraries are not supported"
+            ));
+        }
+        let compiled = hex::decode(compiled_hex).context("Invalid bytecode in compiler output")?;
+        let deployed = mask_immutables(deployed, &bytecode["immutableReferences"]);
+
+        let match_type = if deployed == compiled {
+            "full"
+        } else if strip_metadata(&deployed) == strip_metadata(&compiled) {
+            "partial"
+        } else {
+            return Ok(VerificationResult {
+                address,
+                verified: false,
+                match_type: None,
+                message: "Compiled bytecode does not match deployed bytecode".to_string(),
+            });
+        };
+
+        let verified = VerifiedContract {
+            address: address.clone(),
+            contract_name: request.contract_name.clone(),
+            compiler_version,
+            optimizer_enabled: request.optimizer_enabled.unwrap_or(false),
+            optimizer_runs: request.optimizer_runs.map(i64::from),
+            evm_version: request.evm_version.clone(),
+            source_code: request.source_code.clone(),
+            abi: contract["abi"].to_string(),
+            match_type: match_type.to_string(),
+            verified_at: None,
+        };
+        self.db.upsert_verified_contract(&verified).await?;
+        if let Ok(abi) = serde_json::from_str::<Abi>(&verified.abi) {
+            self.db
+                .insert_function_signatures(&function_signatures(&abi))
+                .await?;
+        }
+        info!(
+            "Verified contract {} at {} ({} match)",
+            request.contract_name, address, match_type
+        );
+
+        Ok(VerificationResult {
+            address,
+            verified: true,
+            match_type: Some(match_type.to_string()),
+            message: format!("Contract {} verified", request.contract_name),
+        })
+    }
+
+    /// ABI used to decode the logs of a contract: its verified ABI when available,
+    /// otherwise the standard token events
+    pub async fn event_abi(&self, address: &str) -> Result<Abi> {
+        if let Some(contract) = self.db.get_verified_contract(address).await?
+            && let Ok(abi) = serde_json::from_str::<Abi>(&contract.abi)
+        {
+            return Ok(abi);
+        }
+
+        Ok(ethers::abi::parse_abi(STANDARD_EVENTS)?)
+    }
+
+    /// Decode the method call of a transaction sent to `to`. Its verified ABI is tried
+    /// first, then functions of other verified contracts sharing the selector, then the
+    /// standard token functions. Returns None for unknown or truncated calls.
+    pub async fn decode_input(&self, to: &str, input: &str) -> Result<Option<DecodedInput>> {
+        let input = hex::decode(input.trim_start_matches("0x")).context("Invalid input data")?;
+        if input.len() < 4 {
+            return Ok(None);
+        }
+        let selector = &input[..4];
+
+        if let Some(contract) = self.db.get_verified_contract(to).await?
+            && let Ok(abi) = serde_json::from_str::<Abi>(&contract.abi)
+            && let Some(decoded) = abi
+                .functions()
+                .find_map(|function| decode_call(function, &input, "verified_abi"))
+        {
+            return Ok(Some(decoded));
+        }
+
+        let known = self
+            .db
+            .get_function_signatures(&format!("0x{}", hex::encode(selector)))
+            .await?;
+        if let Some(decoded) = known.iter().find_map(|known| {
+            let function = serde_json::from_str::<Function>(&known.abi).ok()?;
+            decode_call(&function, &input, "signature_database")
+        }) {
+            return Ok(Some(decoded));
+        }
+
+        let standard = ethers::abi::parse_abi(STANDARD_FUNCTIONS)?;
+        Ok(standard
+            .functions()
+            .find_map(|function| decode_call(function, &input, "standard")))
+    }
+
+    /// Decode the revert reasons of the most recent undecoded failed transactions sent to
+    /// a contract. Returns the number of transactions decoded.
+    pub async fn decode_revert_reasons(&self, address: &str) -> Result<usize> {
+        let hashes = self
+            .db
+            .get_failed_transactions_without_reason(address, REVERT_REASON_DECODE_LIMIT)
+            .await?;
+
+        let mut decoded = 0;
+        for hash in hashes {
+            match self.rpc.get_revert_reason(&hash).await {
+                Ok(reason) => {
+                    let reason = reason.unwrap_or_else(|| UNKNOWN_REVERT_REASON.to_string());
+                    self.db.set_revert_reason(&hash, &reason).await?;
+                    decoded += 1;
+                }
+                Err(e) => debug!("Failed to decode revert reason of {}: {:#}", hash, e),
+            }
+        }
+
+        Ok(decoded)
+    }
+
+    /// Resolve the compiler binary for a request
+    fn solc_command(&self, request: &VerifyContractRequest) -> Result<String> {
+        if !self.config.solc_path.contains("{version}") {
+            return Ok(self.config.solc_path.clone());
+        }
+
+        let version = request
+            .compiler_version
+            .as_deref()
+            .context("compiler_version is required to select a compiler")?;
+        if !version
+            .trim_start_matches('v')
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '+')
+        {
+            return Err(anyhow::anyhow!("Invalid compiler version: {}", version));
+        }
+
+        Ok(self
+            .config
+            .solc_path
+            .replace("{version}", version.trim_start_matches('v')))
+    }
+
+    /// Version string reported by the compiler, e.g. "0.8.24+commit.e11b9ed9"
+    async fn compiler_version(&self, solc: &str) -> Result<String> {
+        let output = timeout(
+            Duration::from_secs(self.config.solc_timeout_seconds),
+            Command::new(solc)
+                .arg("--version")
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .context("Timed out querying compiler version")?
+        .context(format!("Failed to run compiler: {}", solc))?;
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.strip_prefix("Version: "))
+            .map(|version| version.trim().to_string())
+            .context("Could not determine compiler version")
+    }
+
+    /// Compile a single source file with solc's standard JSON interface
+    async fn compile(
+        &self,
+        solc: &str,
+        file_name: &str,
+        request: &VerifyContractRequest,
+    ) -> Result<Value> {
+        let mut settings = json!({
+            "optimizer": {
+                "enabled": request.optimizer_enabled.unwrap_or(false),
+                "runs": request.optimizer_runs.unwrap_or(DEFAULT_OPTIMIZER_RUNS),
+            },
+            "outputSelection": {
+                "*": {
+                    "*": [
+                        "abi",
+                        "evm.deployedBytecode.object",
+                        "evm.deployedBytecode.immutableReferences"
+                    ]
+                }
+            }
+        });
+        if let Some(evm_version) = &request.evm_version {
+            settings["evmVersion"] = json!(evm_version);
+        }
+
+        let input = json!({
+            "language": "Solidity",
+            "sources": { file_name: { "content": request.source_code } },
+            "settings": settings,
+        });
+
+        let _permit = self.compile_permits.acquire().await?;
+        debug!("Compiling {} with {}", file_name, solc);
+
+        let mut child = Command::new(solc)
+            .arg("--standard-json")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context(format!("Failed to run compiler: {}", solc))?;
+
+        let mut stdin = child.stdin.take().context("Compiler stdin unavailable")?;
+        stdin.write_all(input.to_string().as_bytes()).await?;
+        drop(stdin);
+
+        let output = timeout(
+            Duration::from_secs(self.config.solc_timeout_seconds),
+            child.wait_with_output(),
+        )
+        .await
+        .context("Compilation timed out")??;
+
+        let output: Value =
+            serde_json::from_slice(&output.stdout).context("Invalid compiler output")?;
+
+        let errors: Vec<String> = output["errors"]
+            .as_array()
+            .map(|errors| {
+                errors
+                    .iter()
+                    .filter(|error| error["severity"] == "error")
+                    .map(|error| {
+                        error["formattedMessage"]
+                            .as_str()
+                            .or_else(|| error["message"].as_str())
+                            .unwrap_or("unknown error")
+                            .to_string()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Compilation failed: {}", errors.join("\n")));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Zero out immutable variable slots, which the compiler leaves empty in runtime code
+pub fn mask_immutables(mut code: Vec<u8>, references: &Value) -> Vec<u8> {
+    let ranges = references
+        .as_object()
+        .into_iter()
+        .flat_map(|references| references.values())
+        .filter_map(|ranges| ranges.as_array())
+        .flatten();
+
+    for range in ranges {
+        let start = range["start"].as_u64().unwrap_or_default() as usize;
+        let length = range["length"].as_u64().unwrap_or_default() as usize;
+        if let Some(slot) = code.get_mut(start..start + length) {
+            slot.fill(0);
+        }
+    }
+
+    code
+}
+
+/// Strip the CBOR metadata trailer appended by solc (its length is in the last two bytes)
+pub fn strip_metadata(code: &[u8]) -> &[u8] {
+    if code.len() < 2 {
+        return code;
+    }
+
+    let length = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize;
+    if length + 2 > code.len() {
+        return code;
+    }
+
+    let start = code.len() - length - 2;
+    match code[start] {
+        0xa1..=0xa5 => &code[..start],
+        _ => code,
+    }
+}
+
+/// Decode a stored log with an ABI, returning the event name and its named parameters
+pub fn decode_log(abi: &Abi, log: &Log) -> Option<(String, Value)> {
+    let topics = [&log.topic0, &log.topic1, &log.topic2, &log.topic3]
+        .into_iter()
+        .map_while(|topic| topic.as_deref()?.parse::<H256>().ok())
+        .collect::<Vec<_>>();
+    let data = log
+        .data
+        .as_deref()
+        .map(|data| hex::decode(data.trim_start_matches("0x")))
+        .transpose()
+        .ok()?
+        .unwrap_or_default();
+    let raw = RawLog { topics, data };
+
+    abi.events()
+        .filter(|event| Some(&event.signature()) == raw.topics.first())
+        .find_map(|event| {
+            event
+                .parse_log(raw.clone())
+                .ok()
+                .map(|parsed| (event, parsed))
+        })
+        .map(|(event, parsed)| {
+            let params = parsed
+                .params
+                .into_iter()
+                .map(|param| (param.name, token_to_json(param.value)))
+                .collect::<serde_json::Map<_, _>>();
+            (event.name.clone(), Value::Object(params))
+        })
+}
+
+/// Decode calldata with a function whose selector matches, naming unnamed parameters
+/// by position
+fn decode_call(function: &Function, input: &[u8], source: &'static str) -> Option<DecodedInput> {
+    if input.get(..4)? != function.short_signature() {
+        return None;
+    }
+
+    let values = function.decode_input(&input[4..]).ok()?;
+    let params = function
+        .inputs
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(index, (param, value))| {
+            let name = if param.name.is_empty() {
+                index.to_string()
+            } else {
+                param.name.clone()
+            };
+            (name, token_to_json(value))
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    Some(DecodedInput {
+        method: function.name.clone(),
+        signature: canonical_signature(function),
+        params: Value::Object(params),
+        source,
+    })
+}
+
+/// Signature the selector is derived from, e.g. "transfer(address,uint256)"
+fn canonical_signature(function: &Function) -> String {
+    let types: Vec<String> = function
+        .inputs
+        .iter()
+        .map(|param| param.kind.to_string())
+        .collect();
+    format!("{}({})", function.name, types.join(","))
+}
+
+/// Functions of an ABI with their selectors, for decoding calls to other contracts
+fn function_signatures(abi: &Abi) -> Vec<FunctionSignature> {
+    abi.functions()
+        .filter_map(|function| {
+            Some(FunctionSignature {
+                selector: format!("0x{}", hex::encode(function.short_signature())),
+                signature: canonical_signature(function),
+                abi: serde_json::to_string(function).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// JSON representation of a decoded ABI value, with integers as decimal strings
+fn token_to_json(token: Token) -> Value {
+    match token {
+        Token::Address(address) => json!(format!("{:#x}", address)),
+        Token::Uint(value) => json!(value.to_string()),
+        Token::Int(value) => json!(I256::from_raw(value).to_string()),
+        Token::Bool(value) => json!(value),
+        Token::String(value) => json!(value),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+            json!(format!("0x{}", hex::encode(bytes)))
+        }
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            Value::Array(tokens.into_iter().map(token_to_json).collect())
+        }
+    }
+}
@@ -0,0 +1,882 @@
+//! Atomic storage of indexed blocks.
+//!
+//! Everything the indexer derives from a block is written in a single SQLite transaction,
+//! so a crash or a failed insert never leaves a block row without its transactions, logs,
+//! transfers or accounts. As the block row is what the gapless resume logic looks at, a
+//! block is either fully stored or fetched again. Writing a block again first clears the
+//! rows of its previous write, which makes the write idempotent.
+
+use super::{
+    forget_contract_interactions, Account, ApprovalChange, Block, BlockData, BlockFeeRecipient,
+    ContractCode, ContractDeployment, DatabaseService, DelegationChange, Deposit, L2Block,
+    L2Transaction, Log, Receipt, TokenTransfer, Transaction, Withdrawal,
+};
+use anyhow::{Context, Result};
+use sqlx::SqliteConnection;
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// Tables holding rows written by `store_block_data`, keyed by block number. Dependent
+/// tables come first.
+pub(crate) const BLOCK_DATA_TABLES: [&str; 10] = [
+    "token_transfers",
+    "logs",
+    "l2_transactions",
+    "transaction_receipts",
+    "account_balance_history",
+    "transactions",
+    "withdrawals",
+    "deposits",
+    "l2_blocks",
+    "block_fee_recipients",
+];
+
+impl DatabaseService {
+    /// Store a block with every row derived from it in one transaction, replacing what a
+    /// previous write of the block stored. Balance history snapshots are taken every
+    /// `balance_history_interval` blocks, 0 disables them.
+    pub async fn store_block_data(
+        &self,
+        data: &BlockData,
+        balance_history_interval: i64,
+    ) -> Result<()> {
+        let block_number = data.block.number;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start block transaction")?;
+
+        // Counted from the stored transactions, so before they are deleted
+        forget_contract_interactions(&mut tx, block_number).await?;
+        forget_account_transactions(&mut tx, block_number).await?;
+        for table in BLOCK_DATA_TABLES {
+            sqlx::query(&format!("DELETE FROM {} WHERE block_number = ?", table))
+                .bind(block_number)
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to clear {} of block", table))?;
+        }
+
+        insert_block(&mut tx, &data.block).await?;
+        summarize_epochs(&mut tx, &data.block).await?;
+        if let Some(l2_block) = &data.l2_block {
+            insert_l2_block(&mut tx, l2_block).await?;
+        }
+        if let Some(fee_recipient) = &data.fee_recipient {
+            insert_block_fee_recipient(&mut tx, fee_recipient).await?;
+        }
+        insert_withdrawals(&mut tx, &data.withdrawals).await?;
+        insert_deposits(&mut tx, &data.deposits).await?;
+        insert_transactions(&mut tx, &data.transactions).await?;
+        name_methods_from_signatures(&mut tx, block_number, &data.transactions).await?;
+        record_contract_interactions(&mut tx, data.block.timestamp, &data.transactions).await?;
+        insert_receipts(&mut tx, &data.receipts).await?;
+        insert_l2_transactions(&mut tx, &data.l2_transactions).await?;
+        insert_logs(&mut tx, &data.logs).await?;
+        insert_token_transfers(&mut tx, &data.token_transfers).await?;
+        insert_accounts(&mut tx, &data.accounts).await?;
+        record_balance_history(
+            &mut tx,
+            &data.accounts,
+            block_number,
+            balance_history_interval,
+        )
+        .await?;
+        insert_contract_deployments(&mut tx, &data.contract_deployments, &data.contract_codes)
+            .await?;
+        apply_delegation_changes(&mut tx, &data.delegation_changes).await?;
+        apply_approval_changes(&mut tx, &data.approval_changes).await?;
+
+        #[cfg(feature = "chaos")]
+        if self
+            .faults
+            .as_ref()
+            .is_some_and(|faults| faults.db_write_failure())
+        {
+            return Err(anyhow::anyhow!(
+                "Injected write failure of block #{}",
+                block_number
+            ));
+        }
+
+        tx.commit()
+            .await
+            .context(format!("Failed to commit block #{}", block_number))?;
+        Ok(())
+    }
+}
+
+/// Insert or overwrite a block row
+pub(super) async fn insert_block(conn: &mut SqliteConnection, block: &Block) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO blocks (
+            number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+            miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+            nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+            slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+            eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+            attestation_count, slashing_count
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(number) DO UPDATE SET
+            hash = excluded.hash,
+            parent_hash = excluded.parent_hash,
+            timestamp = excluded.timestamp,
+            gas_used = excluded.gas_used,
+            gas_limit = excluded.gas_limit,
+            transaction_count = excluded.transaction_count,
+            miner = excluded.miner,
+            difficulty = excluded.difficulty,
+            size_bytes = excluded.size_bytes,
+            base_fee_per_gas = excluded.base_fee_per_gas,
+            extra_data = excluded.extra_data,
+            state_root = excluded.state_root,
+            nonce = excluded.nonce,
+            withdrawals_root = excluded.withdrawals_root,
+            blob_gas_used = excluded.blob_gas_used,
+            excess_blob_gas = excluded.excess_blob_gas,
+            withdrawal_count = excluded.withdrawal_count,
+            slot = excluded.slot,
+            proposer_index = excluded.proposer_index,
+            epoch = excluded.epoch,
+            slot_root = excluded.slot_root,
+            parent_root = excluded.parent_root,
+            block_deposit_count = excluded.block_deposit_count,
+            eth1_deposit_count = excluded.eth1_deposit_count,
+            graffiti = excluded.graffiti,
+            randao_reveal = excluded.randao_reveal,
+            randao_mix = excluded.randao_mix,
+            sync_participation = excluded.sync_participation,
+            attestation_count = excluded.attestation_count,
+            slashing_count = excluded.slashing_count
+        "#,
+    )
+    .bind(block.number)
+    .bind(&block.hash)
+    .bind(&block.parent_hash)
+    .bind(block.timestamp)
+    .bind(block.gas_used)
+    .bind(block.gas_limit)
+    .bind(block.transaction_count)
+    .bind(&block.miner)
+    .bind(&block.difficulty)
+    .bind(block.size_bytes)
+    .bind(&block.base_fee_per_gas)
+    .bind(&block.extra_data)
+    .bind(&block.state_root)
+    .bind(&block.nonce)
+    .bind(&block.withdrawals_root)
+    .bind(block.blob_gas_used)
+    .bind(block.excess_blob_gas)
+    .bind(block.withdrawal_count)
+    .bind(block.slot)
+    .bind(block.proposer_index)
+    .bind(block.epoch)
+    .bind(&block.slot_root)
+    .bind(&block.parent_root)
+    .bind(block.block_deposit_count)
+    .bind(block.eth1_deposit_count)
+    .bind(&block.graffiti)
+    .bind(&block.randao_reveal)
+    .bind(&block.randao_mix)
+    .bind(block.sync_participation)
+    .bind(block.attestation_count)
+    .bind(block.slashing_count)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to insert block")?;
+
+    Ok(())
+}
+
+/// Refresh the summary of the block's epoch, and of the previous epoch whose trailing
+/// slots only count as missed once a later block is stored
+async fn summarize_epochs(conn: &mut SqliteConnection, block: &Block) -> Result<()> {
+    let Some(epoch) = block.epoch else {
+        return Ok(());
+    };
+
+    for epoch in [epoch - 1, epoch] {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO epochs (
+                epoch, blocks_proposed, missed_slots, attestations, deposits, slashings,
+                avg_sync_participation, updated_at
+            )
+            SELECT ?1, COUNT(*),
+                   MIN(32, (SELECT MAX(slot) FROM blocks) - ?1 * 32 + 1) - COUNT(*),
+                   SUM(attestation_count), SUM(block_deposit_count), SUM(slashing_count),
+                   AVG(sync_participation), CURRENT_TIMESTAMP
+            FROM blocks
+            WHERE slot BETWEEN ?1 * 32 AND ?1 * 32 + 31
+            HAVING COUNT(*) > 0
+            "#,
+        )
+        .bind(epoch)
+        .execute(&mut *conn)
+        .await
+        .context(format!("Failed to summarize epoch {}", epoch))?;
+    }
+    Ok(())
+}
+
+/// Insert the withdrawals of a block, skipping those already stored
+async fn insert_withdrawals(conn: &mut SqliteConnection, withdrawals: &[Withdrawal]) -> Result<()> {
+    for withdrawal in withdrawals {
+        sqlx::query(
+            r#"
+            INSERT INTO withdrawals (
+                block_number, withdrawal_index, validator_index, address, amount
+            ) VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(block_number, withdrawal_index) DO NOTHING
+            "#,
+        )
+        .bind(withdrawal.block_number)
+        .bind(withdrawal.withdrawal_index)
+        .bind(withdrawal.validator_index)
+        .bind(&withdrawal.address)
+        .bind(&withdrawal.amount)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to insert withdrawal")?;
+    }
+
+    Ok(())
+}
+
+/// Insert the beacon deposits of a block
+async fn insert_deposits(conn: &mut SqliteConnection, deposits: &[Deposit]) -> Result<()> {
+    for deposit in deposits {
+        sqlx::query(
+            r#"
+            INSERT INTO deposits (
+                block_number, log_index, transaction_hash, deposit_index, depositor, pubkey,
+                withdrawal_credentials, amount, signature
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(block_number, log_index) DO NOTHING
+            "#,
+        )
+        .bind(deposit.block_number)
+        .bind(deposit.log_index)
+        .bind(&deposit.transaction_hash)
+        .bind(deposit.deposit_index)
+        .bind(&deposit.depositor)
+        .bind(&deposit.pubkey)
+        .bind(&deposit.withdrawal_credentials)
+        .bind(&deposit.amount)
+        .bind(&deposit.signature)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to insert deposit")?;
+    }
+
+    Ok(())
+}
+
+/// Insert the transactions of a block in a single batch
+async fn insert_transactions(
+    conn: &mut SqliteConnection,
+    transactions: &[Transaction],
+) -> Result<()> {
+    if transactions.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO transactions (hash, block_number, transaction_index, from_address, to_address, value, gas_used, gas_price, status, method_id, method_name, gas_limit, input, input_size) "
+    );
+
+    query_builder.push_values(transactions, |mut b, tx| {
+        b.push_bind(&tx.hash)
+            .push_bind(tx.block_number)
+            .push_bind(tx.transaction_index)
+            .push_bind(&tx.from_address)
+            .push_bind(&tx.to_address)
+            .push_bind(&tx.value)
+            .push_bind(tx.gas_used)
+            .push_bind(&tx.gas_price)
+            .push_bind(tx.status)
+            .push_bind(&tx.method_id)
+            .push_bind(&tx.method_name)
+            .push_bind(tx.gas_limit)
+            .push_bind(&tx.input)
+            .push_bind(tx.input_size);
+    });
+    // Left behind by a stale block the transaction was first included in
+    query_builder.push(
+        r#"
+        ON CONFLICT(hash) DO UPDATE SET
+            block_number = excluded.block_number,
+            transaction_index = excluded.transaction_index,
+            from_address = excluded.from_address,
+            to_address = excluded.to_address,
+            value = excluded.value,
+            gas_used = excluded.gas_used,
+            gas_price = excluded.gas_price,
+            status = excluded.status,
+            method_id = excluded.method_id,
+            method_name = excluded.method_name,
+            gas_limit = excluded.gas_limit,
+            input = excluded.input,
+            input_size = excluded.input_size
+        "#,
+    );
+
+    query_builder.build().execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// Name the methods the indexer doesn't know from the function signatures of verified
+/// contracts, the first signature stored winning for selectors shared by several
+async fn name_methods_from_signatures(
+    conn: &mut SqliteConnection,
+    block_number: i64,
+    transactions: &[Transaction],
+) -> Result<()> {
+    if transactions
+        .iter()
+        .all(|tx| tx.method_id.is_none() || tx.method_name.is_some())
+    {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE transactions SET method_name = (
+            SELECT substr(signature, 1, instr(signature, '(') - 1)
+            FROM function_signatures
+            WHERE selector = transactions.method_id
+            ORDER BY created_at
+            LIMIT 1
+        )
+        WHERE block_number = ? AND method_name IS NULL
+          AND method_id IN (SELECT selector FROM function_signatures)
+        "#,
+    )
+    .bind(block_number)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to name transaction methods")?;
+    Ok(())
+}
+
+/// Insert the receipts of a block's transactions in a single batch
+async fn insert_receipts(conn: &mut SqliteConnection, receipts: &[Receipt]) -> Result<()> {
+    if receipts.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT OR REPLACE INTO transaction_receipts (transaction_hash, block_number, cumulative_gas_used, effective_gas_price, contract_address, logs_bloom, transaction_type) ",
+    );
+    query_builder.push_values(receipts, |mut b, receipt| {
+        b.push_bind(&receipt.transaction_hash)
+            .push_bind(receipt.block_number)
+            .push_bind(receipt.cumulative_gas_used)
+            .push_bind(&receipt.effective_gas_price)
+            .push_bind(&receipt.contract_address)
+            .push_bind(&receipt.logs_bloom)
+            .push_bind(receipt.transaction_type);
+    });
+
+    query_builder
+        .build()
+        .execute(&mut *conn)
+        .await
+        .context("Failed to insert receipts")?;
+    Ok(())
+}
+
+/// Store the rollup data of a block's transactions
+async fn insert_l2_transactions(
+    conn: &mut SqliteConnection,
+    transactions: &[L2Transaction],
+) -> Result<()> {
+    // 11 bound values per row, stay well below SQLite's variable limit
+    for chunk in transactions.chunks(1000) {
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "INSERT OR REPLACE INTO l2_transactions (transaction_hash, block_number, kind, l1_fee, l1_gas_used, l1_gas_price, l1_fee_scalar, l1_blob_base_fee, l1_block_number, source_hash, mint) ",
+        );
+        query_builder.push_values(chunk, |mut b, transaction| {
+            b.push_bind(&transaction.transaction_hash)
+                .push_bind(transaction.block_number)
+                .push_bind(&transaction.kind)
+                .push_bind(&transaction.l1_fee)
+                .push_bind(transaction.l1_gas_used)
+                .push_bind(&transaction.l1_gas_price)
+                .push_bind(&transaction.l1_fee_scalar)
+                .push_bind(&transaction.l1_blob_base_fee)
+                .push_bind(transaction.l1_block_number)
+                .push_bind(&transaction.source_hash)
+                .push_bind(&transaction.mint);
+        });
+
+        query_builder
+            .build()
+            .execute(&mut *conn)
+            .await
+            .context("Failed to store L2 transaction data")?;
+    }
+
+    Ok(())
+}
+
+/// Store the L1 origin and batch references of a block
+async fn insert_l2_block(conn: &mut SqliteConnection, block: &L2Block) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO l2_blocks (
+            block_number, l1_block_number, l1_block_hash, l1_timestamp, sequence_number,
+            batcher_hash, send_count, send_root
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(block.block_number)
+    .bind(block.l1_block_number)
+    .bind(&block.l1_block_hash)
+    .bind(block.l1_timestamp)
+    .bind(block.sequence_number)
+    .bind(&block.batcher_hash)
+    .bind(block.send_count)
+    .bind(&block.send_root)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to store L2 block data")?;
+
+    Ok(())
+}
+
+/// Store the payload fee recipient of a block and the proposer payment it made
+async fn insert_block_fee_recipient(
+    conn: &mut SqliteConnection,
+    fee_recipient: &BlockFeeRecipient,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO block_fee_recipients (
+            block_number, slot, proposer_index, miner, fee_recipient, mismatch,
+            proposer_payee, proposer_payment
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(fee_recipient.block_number)
+    .bind(fee_recipient.slot)
+    .bind(fee_recipient.proposer_index)
+    .bind(&fee_recipient.miner)
+    .bind(&fee_recipient.fee_recipient)
+    .bind(fee_recipient.mismatch)
+    .bind(&fee_recipient.proposer_payee)
+    .bind(&fee_recipient.proposer_payment)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to store block fee recipient")?;
+
+    Ok(())
+}
+
+/// Update the delegate and voting power of the accounts of a block's delegation changes.
+/// A side is only overwritten by an event at or after the one that set it, so blocks
+/// stored out of order, or again, leave the newest state in place.
+async fn apply_delegation_changes(
+    conn: &mut SqliteConnection,
+    changes: &[DelegationChange],
+) -> Result<()> {
+    for change in changes {
+        let (sql, value) = match (&change.delegate, &change.votes) {
+            (Some(delegate), _) => (
+                r#"
+                INSERT INTO delegations (
+                    token_address, account_address, delegate, delegate_block, delegate_log_index
+                ) VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(token_address, account_address) DO UPDATE SET
+                    delegate = excluded.delegate,
+                    delegate_block = excluded.delegate_block,
+                    delegate_log_index = excluded.delegate_log_index
+                WHERE delegations.delegate_block IS NULL
+                   OR (excluded.delegate_block, excluded.delegate_log_index)
+                      >= (delegations.delegate_block, delegations.delegate_log_index)
+                "#,
+                delegate,
+            ),
+            (None, Some(votes)) => (
+                r#"
+                INSERT INTO delegations (
+                    token_address, account_address, votes, votes_block, votes_log_index
+                ) VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(token_address, account_address) DO UPDATE SET
+                    votes = excluded.votes,
+                    votes_block = excluded.votes_block,
+                    votes_log_index = excluded.votes_log_index
+                WHERE delegations.votes_block IS NULL
+                   OR (excluded.votes_block, excluded.votes_log_index)
+                      >= (delegations.votes_block, delegations.votes_log_index)
+                "#,
+                votes,
+            ),
+            (None, None) => continue,
+        };
+
+        sqlx::query(sql)
+            .bind(&change.token_address)
+            .bind(&change.account_address)
+            .bind(value)
+            .bind(change.block_number)
+            .bind(change.log_index)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to store delegation change")?;
+    }
+
+    Ok(())
+}
+
+/// Record the approvals of a block, one row per token, owner, spender and kind. Like
+/// delegations, a row is only overwritten by an event at or after the one that set it.
+async fn apply_approval_changes(
+    conn: &mut SqliteConnection,
+    changes: &[ApprovalChange],
+) -> Result<()> {
+    for change in changes {
+        sqlx::query(
+            r#"
+            INSERT INTO approvals (
+                token_address, owner_address, spender_address, kind, value,
+                transaction_hash, block_number, log_index
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(token_address, owner_address, spender_address, kind) DO UPDATE SET
+                value = excluded.value,
+                transaction_hash = excluded.transaction_hash,
+                block_number = excluded.block_number,
+                log_index = excluded.log_index
+            WHERE (excluded.block_number, excluded.log_index)
+                  >= (approvals.block_number, approvals.log_index)
+            "#,
+        )
+        .bind(&change.token_address)
+        .bind(&change.owner_address)
+        .bind(&change.spender_address)
+        .bind(&change.kind)
+        .bind(&change.value)
+        .bind(&change.transaction_hash)
+        .bind(change.block_number)
+        .bind(change.log_index)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to store approval change")?;
+    }
+
+    Ok(())
+}
+
+/// Insert the logs of a block in a single batch
+async fn insert_logs(conn: &mut SqliteConnection, logs: &[Log]) -> Result<()> {
+    if logs.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO logs (transaction_hash, log_index, address, topic0, topic1, topic2, topic3, data, block_number) "
+    );
+
+    query_builder.push_values(logs, |mut b, log| {
+        b.push_bind(&log.transaction_hash)
+            .push_bind(log.log_index)
+            .push_bind(&log.address)
+            .push_bind(&log.topic0)
+            .push_bind(&log.topic1)
+            .push_bind(&log.topic2)
+            .push_bind(&log.topic3)
+            .push_bind(&log.data)
+            .push_bind(log.block_number);
+    });
+    // Left behind by a stale block the transaction was first included in
+    query_builder.push(
+        r#"
+        ON CONFLICT(transaction_hash, log_index) DO UPDATE SET
+            address = excluded.address,
+            topic0 = excluded.topic0,
+            topic1 = excluded.topic1,
+            topic2 = excluded.topic2,
+            topic3 = excluded.topic3,
+            data = excluded.data,
+            block_number = excluded.block_number
+        "#,
+    );
+
+    query_builder.build().execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// Insert the token transfers of a block in a single batch
+async fn insert_token_transfers(
+    conn: &mut SqliteConnection,
+    transfers: &[TokenTransfer],
+) -> Result<()> {
+    if transfers.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO token_transfers (transaction_hash, token_address, from_address, to_address, amount, block_number, token_type, token_id, log_index) "
+    );
+
+    query_builder.push_values(transfers, |mut b, transfer| {
+        b.push_bind(&transfer.transaction_hash)
+            .push_bind(&transfer.token_address)
+            .push_bind(&transfer.from_address)
+            .push_bind(&transfer.to_address)
+            .push_bind(&transfer.amount)
+            .push_bind(transfer.block_number)
+            .push_bind(&transfer.token_type)
+            .push_bind(&transfer.token_id)
+            .push_bind(transfer.log_index);
+    });
+    // Left behind by a stale block the transaction was first included in
+    query_builder.push(
+        r#"
+        ON CONFLICT(transaction_hash, log_index) DO UPDATE SET
+            token_address = excluded.token_address,
+            from_address = excluded.from_address,
+            to_address = excluded.to_address,
+            amount = excluded.amount,
+            block_number = excluded.block_number,
+            token_type = excluded.token_type,
+            token_id = excluded.token_id
+        "#,
+    );
+
+    query_builder.build().execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// Take the transactions of a block about to be replaced out of the transaction counts of
+/// the accounts involved, matching the per-block counts `insert_accounts` adds
+pub(super) async fn forget_account_transactions(conn: &mut SqliteConnection, block_number: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE accounts SET transaction_count = MAX(accounts.transaction_count - t.transactions, 0)
+        FROM (
+            SELECT address, COUNT(*) AS transactions
+            FROM (
+                SELECT hash, from_address AS address FROM transactions WHERE block_number = ?1
+                UNION
+                SELECT hash, to_address FROM transactions WHERE block_number = ?1 AND to_address IS NOT NULL
+            )
+            GROUP BY address
+        ) AS t
+        WHERE accounts.address = t.address
+        "#,
+    )
+    .bind(block_number)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to remove replaced account transactions")?;
+
+    Ok(())
+}
+
+/// Merge the accounts seen in a block into the stored ones in a single batch: their
+/// transactions are added to the count, and balances only replace those read at an
+/// earlier block, as workers store blocks out of order
+async fn insert_accounts(conn: &mut SqliteConnection, accounts: &[Account]) -> Result<()> {
+    if accounts.is_empty() {
+        return Ok(());
+    }
+
+    info!("Starting batch insert of {} accounts", accounts.len());
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO accounts (address, balance, transaction_count, first_seen_block, last_seen_block, balance_at_block, account_type) "
+    );
+
+    query_builder.push_values(accounts, |mut b, account| {
+        b.push_bind(&account.address)
+            .push_bind(&account.balance)
+            .push_bind(account.transaction_count)
+            .push_bind(account.first_seen_block)
+            .push_bind(account.last_seen_block)
+            .push_bind(account.balance_at_block)
+            .push_bind(&account.account_type);
+    });
+    query_builder.push(
+        r#"
+        ON CONFLICT(address) DO UPDATE SET
+            balance = CASE WHEN excluded.last_seen_block >= accounts.last_seen_block
+                THEN excluded.balance ELSE accounts.balance END,
+            balance_at_block = CASE WHEN excluded.last_seen_block >= accounts.last_seen_block
+                THEN excluded.balance_at_block ELSE accounts.balance_at_block END,
+            transaction_count = accounts.transaction_count + excluded.transaction_count,
+            first_seen_block = MIN(accounts.first_seen_block, excluded.first_seen_block),
+            last_seen_block = MAX(accounts.last_seen_block, excluded.last_seen_block),
+            account_type = COALESCE(accounts.account_type, excluded.account_type),
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    );
+
+    let result = query_builder.build().execute(&mut *conn).await?;
+    info!(
+        "Batch insert completed: {} rows inserted/updated",
+        result.rows_affected()
+    );
+    Ok(())
+}
+
+/// Record the balances of accounts seen at block `block_number` in their balance history,
+/// replacing earlier snapshots of the same period of `interval` blocks
+async fn record_balance_history(
+    conn: &mut SqliteConnection,
+    accounts: &[Account],
+    block_number: i64,
+    interval: i64,
+) -> Result<()> {
+    if accounts.is_empty() || interval <= 0 {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO account_balance_history (address, period, block_number, balance, balance_at_block) ",
+    );
+    query_builder.push_values(accounts, |mut b, account| {
+        b.push_bind(&account.address)
+            .push_bind(block_number / interval)
+            .push_bind(block_number)
+            .push_bind(&account.balance)
+            .push_bind(account.balance_at_block);
+    });
+    // Blocks are processed concurrently, an older block must not overwrite a newer one
+    query_builder.push(
+        r#"
+        ON CONFLICT(address, period) DO UPDATE SET
+            block_number = excluded.block_number,
+            balance = excluded.balance,
+            balance_at_block = excluded.balance_at_block
+        WHERE excluded.block_number >= account_balance_history.block_number
+        "#,
+    );
+
+    query_builder
+        .build()
+        .execute(&mut *conn)
+        .await
+        .context("Failed to record balance history")?;
+    Ok(())
+}
+
+/// Store contract deployments together with their bytecode.
+/// Each bytecode is kept once, under the hash of its first deployment.
+async fn insert_contract_deployments(
+    conn: &mut SqliteConnection,
+    deployments: &[ContractDeployment],
+    codes: &[ContractCode],
+) -> Result<()> {
+    for code in codes {
+        sqlx::query(
+            r#"
+            INSERT INTO contract_code (code_hash, bytecode, code_size, first_seen_block)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(code_hash) DO UPDATE SET
+                first_seen_block = MIN(first_seen_block, excluded.first_seen_block)
+            "#,
+        )
+        .bind(&code.code_hash)
+        .bind(&code.bytecode)
+        .bind(code.code_size)
+        .bind(code.first_seen_block)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to insert contract code")?;
+    }
+
+    for deployment in deployments {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO contract_deployments (
+                address, code_hash, creator_address, transaction_hash, block_number,
+                factory_address
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&deployment.address)
+        .bind(&deployment.code_hash)
+        .bind(&deployment.creator_address)
+        .bind(&deployment.transaction_hash)
+        .bind(deployment.block_number)
+        .bind(&deployment.factory_address)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to insert contract deployment")?;
+
+        // Addresses may receive funds before a contract is deployed to them
+        sqlx::query("UPDATE accounts SET account_type = 'contract' WHERE address = ?")
+            .bind(&deployment.address)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to mark contract account")?;
+    }
+
+    Ok(())
+}
+
+/// Add the contract calls among the `transactions` of a block produced at `timestamp` to
+/// the hourly contract interaction rollup. Calls are transactions carrying a method
+/// selector.
+async fn record_contract_interactions(
+    conn: &mut SqliteConnection,
+    timestamp: i64,
+    transactions: &[Transaction],
+) -> Result<()> {
+    let hour_start = timestamp.div_euclid(3600) * 3600;
+    let mut callers: BTreeMap<(&str, &str), i64> = BTreeMap::new();
+    for tx in transactions {
+        if let (Some(contract), Some(_)) = (&tx.to_address, &tx.method_id) {
+            *callers
+                .entry((contract.as_str(), tx.from_address.as_str()))
+                .or_default() += 1;
+        }
+    }
+    if callers.is_empty() {
+        return Ok(());
+    }
+    let mut calls: BTreeMap<&str, i64> = BTreeMap::new();
+    for ((contract, _), count) in &callers {
+        *calls.entry(contract).or_default() += count;
+    }
+
+    let mut query = sqlx::QueryBuilder::new(
+        "INSERT INTO contract_interactions (contract_address, hour_start, calls) ",
+    );
+    query.push_values(&calls, |mut b, (contract, count)| {
+        b.push_bind(*contract)
+            .push_bind(hour_start)
+            .push_bind(*count);
+    });
+    query.push(
+        " ON CONFLICT (contract_address, hour_start) DO UPDATE SET calls = calls + excluded.calls",
+    );
+    query
+        .build()
+        .execute(&mut *conn)
+        .await
+        .context("Failed to record contract interactions")?;
+
+    let mut query = sqlx::QueryBuilder::new(
+        "INSERT INTO contract_callers (contract_address, hour_start, caller_address, calls) ",
+    );
+    query.push_values(&callers, |mut b, ((contract, caller), count)| {
+        b.push_bind(*contract)
+            .push_bind(hour_start)
+            .push_bind(*caller)
+            .push_bind(*count);
+    });
+    query.push(
+        " ON CONFLICT (contract_address, hour_start, caller_address) DO UPDATE SET calls = calls + excluded.calls",
+    );
+    query
+        .build()
+        .execute(&mut *conn)
+        .await
+        .context("Failed to record contract callers")?;
+
+    Ok(())
+}
@@ -0,0 +1,5 @@
+-- Migration 005: Balance Block Tracking
+-- Records the block each account balance was read at. Non-archive RPC nodes cannot
+-- serve historical state, in which case the latest balance is stored and this is NULL
+
+ALTER TABLE accounts ADD COLUMN balance_at_block INTEGER;               -- Block the balance was read at (NULL = latest state)
@@ -0,0 +1,6 @@
+-- Migration 006: Beacon Deposit Counts
+-- beacon_deposit_count held the number of deposits in the block body, not the chain's
+-- cumulative count its name implied. Both values are now stored under explicit names
+
+ALTER TABLE blocks RENAME COLUMN beacon_deposit_count TO block_deposit_count;   -- Deposits processed by this beacon block
+ALTER TABLE blocks ADD COLUMN eth1_deposit_count INTEGER;                       -- Cumulative deposit contract count (eth1_data.deposit_count)
@@ -0,0 +1,6 @@
+-- Migration 007: Withdrawals By Address
+-- Serves paginated per-address withdrawal lookups (newest first) straight from the index.
+-- Supersedes the single-column address index, which is a prefix of this one
+
+DROP INDEX IF EXISTS idx_withdrawals_address;
+CREATE INDEX IF NOT EXISTS idx_withdrawals_address_block ON withdrawals(address, block_number, withdrawal_index);
@@ -0,0 +1,16 @@
+-- Migration 008: Verified Contracts
+-- Stores contracts whose submitted source compiled to the deployed bytecode,
+-- together with their ABI for decoding and display
+
+CREATE TABLE IF NOT EXISTS verified_contracts (
+    address TEXT PRIMARY KEY,                      -- Contract address
+    contract_name TEXT NOT NULL,                   -- Name of the verified contract
+    compiler_version TEXT NOT NULL,                -- solc version reported by the compiler
+    optimizer_enabled BOOLEAN NOT NULL DEFAULT 0,  -- Whether the optimizer was enabled
+    optimizer_runs INTEGER,                        -- Optimizer runs setting
+    evm_version TEXT,                              -- Target EVM version (NULL = compiler default)
+    source_code TEXT NOT NULL,                     -- Submitted Solidity source
+    abi TEXT NOT NULL,                             -- Contract ABI as JSON
+    match_type TEXT NOT NULL,                      -- 'full' (metadata included) or 'partial'
+    verified_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,16 @@
+-- Migration 009: Indexer Events
+-- Structured log of significant operational events (failovers, failures, repairs, ...)
+-- so operators can reconstruct what the indexer did without scraping logs
+
+CREATE TABLE IF NOT EXISTS indexer_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,                            -- Event kind (e.g. 'rpc_failover', 'block_failed')
+    severity TEXT NOT NULL,                        -- 'info', 'warning' or 'error'
+    message TEXT NOT NULL,                         -- Human readable description
+    block_number INTEGER,                          -- Block the event relates to, if any
+    details TEXT,                                  -- Additional structured data as JSON
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_indexer_events_created ON indexer_events(created_at);
+CREATE INDEX IF NOT EXISTS idx_indexer_events_kind ON indexer_events(kind, created_at);
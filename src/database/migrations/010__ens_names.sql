@@ -0,0 +1,14 @@
+-- Migration 010: ENS Names
+-- Caches ENS forward (name -> address) and reverse (address -> primary name) resolutions.
+-- Negative results are cached too, with a NULL on the unresolved side
+
+CREATE TABLE IF NOT EXISTS ens_names (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT,                                     -- ENS name (NULL = address has no primary name)
+    address TEXT,                                  -- Address (NULL = name does not resolve)
+    is_reverse BOOLEAN NOT NULL,                   -- 1 = reverse record of address, 0 = forward record of name
+    resolved_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ens_names_forward ON ens_names(name) WHERE is_reverse = 0;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ens_names_reverse ON ens_names(address) WHERE is_reverse = 1;
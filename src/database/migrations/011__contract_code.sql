@@ -0,0 +1,28 @@
+-- Migration 011: Contract Code
+-- Stores each distinct runtime bytecode once, keyed by its keccak256 hash, and links
+-- every contract deployment to it so identical deployments (clones, proxies) can be found
+
+-- CONTRACT CODE TABLE - Reference copy of each distinct runtime bytecode
+CREATE TABLE IF NOT EXISTS contract_code (
+    code_hash TEXT PRIMARY KEY,                    -- keccak256 of the runtime bytecode
+    bytecode TEXT NOT NULL,                        -- Runtime bytecode as hex
+    code_size INTEGER NOT NULL,                    -- Bytecode size in bytes
+    first_seen_block INTEGER NOT NULL,             -- First block this bytecode was deployed in
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- CONTRACT DEPLOYMENTS TABLE - Contracts created by indexed transactions
+CREATE TABLE IF NOT EXISTS contract_deployments (
+    address TEXT PRIMARY KEY,                      -- Deployed contract address
+    code_hash TEXT NOT NULL,                       -- Hash of the runtime bytecode
+    creator_address TEXT NOT NULL,                 -- Sender of the creation transaction
+    transaction_hash TEXT NOT NULL,                -- Creation transaction
+    block_number INTEGER NOT NULL,                 -- Block of the creation transaction
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (code_hash) REFERENCES contract_code (code_hash)
+);
+
+-- Deployments of a bytecode in deployment order
+CREATE INDEX IF NOT EXISTS idx_contract_deployments_code_hash
+ON contract_deployments(code_hash, block_number);
+CREATE INDEX IF NOT EXISTS idx_contract_deployments_creator ON contract_deployments(creator_address);
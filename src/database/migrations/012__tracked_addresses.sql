@@ -0,0 +1,9 @@
+-- Migration 012: Tracked Addresses
+-- Watchlist restricting indexing to transactions, logs and balances involving these
+-- addresses. Merged with TRACKED_ADDRESSES; when both are empty the full chain is indexed
+
+CREATE TABLE IF NOT EXISTS tracked_addresses (
+    address TEXT PRIMARY KEY,                      -- Tracked address (lowercase)
+    label TEXT,                                    -- Optional description, e.g. protocol name
+    added_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,21 @@
+-- Migration 013: Pending Transactions
+-- Mempool snapshot maintained by the mempool watcher. Entries are removed once their
+-- transaction is mined (the block processor then stores it in transactions) or when
+-- they grow older than the configured retention
+
+CREATE TABLE IF NOT EXISTS pending_transactions (
+    hash TEXT PRIMARY KEY,                         -- Transaction hash
+    from_address TEXT NOT NULL,                    -- Sender address
+    to_address TEXT,                               -- Recipient address (null for contract creation)
+    nonce INTEGER NOT NULL,                        -- Sender nonce
+    value TEXT NOT NULL,                           -- Transaction value in wei
+    gas_limit INTEGER NOT NULL,                    -- Gas limit
+    gas_price TEXT NOT NULL,                       -- Gas price in wei (max fee for EIP-1559)
+    max_priority_fee_per_gas TEXT,                 -- Priority fee in wei (EIP-1559 only)
+    first_seen_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- A replacement transaction (same sender and nonce) supersedes the previous one
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pending_transactions_sender_nonce
+ON pending_transactions(from_address, nonce);
+CREATE INDEX IF NOT EXISTS idx_pending_transactions_first_seen ON pending_transactions(first_seen_at);
@@ -0,0 +1,7 @@
+-- Migration 014: Transaction Revert Reasons
+-- Revert reasons of failed transactions, decoded on demand by replaying them,
+-- and an index for per-contract failure rollups
+
+ALTER TABLE transactions ADD COLUMN revert_reason TEXT;  -- Decoded revert reason (NULL = not decoded yet)
+
+CREATE INDEX IF NOT EXISTS idx_transactions_to_status ON transactions(to_address, status);
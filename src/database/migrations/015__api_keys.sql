@@ -0,0 +1,14 @@
+-- Migration 015: API Keys
+-- Keys granting access to the API with their own rate limit and daily quota.
+-- Only the keccak256 hash of a key is stored, the key itself is shown once on creation
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_hash TEXT NOT NULL UNIQUE,                 -- keccak256 of the key (hex, 0x-prefixed)
+    name TEXT NOT NULL,                            -- Owner or purpose of the key
+    requests_per_minute INTEGER NOT NULL,          -- Max requests per minute (0 = unlimited)
+    daily_quota INTEGER,                           -- Max requests per UTC day (NULL = unlimited)
+    enabled BOOLEAN NOT NULL DEFAULT 1,            -- Disabled keys are rejected
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    last_used_at DATETIME
+);
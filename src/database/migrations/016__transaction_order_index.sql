@@ -0,0 +1,6 @@
+-- Migration 016: Transaction Order Index
+-- Covers the (block_number, transaction_index) ordering of transaction lists, so
+-- cursor pagination seeks straight to the requested position
+
+CREATE INDEX IF NOT EXISTS idx_transactions_block_index
+    ON transactions(block_number, transaction_index);
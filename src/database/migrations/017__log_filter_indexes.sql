@@ -0,0 +1,9 @@
+-- Migration 017: Log Filter Indexes
+-- Composite indexes for eth_getLogs-style queries by address and/or event signature
+-- over a block range. They supersede the single-column address and topic0 indexes
+
+CREATE INDEX IF NOT EXISTS idx_logs_address_topic0_block ON logs(address, topic0, block_number);
+CREATE INDEX IF NOT EXISTS idx_logs_topic0_block ON logs(topic0, block_number);
+
+DROP INDEX IF EXISTS idx_logs_address;
+DROP INDEX IF EXISTS idx_logs_topic0;
@@ -0,0 +1,20 @@
+-- Migration 018: Aggregate Statistics
+-- Per-day and per-hour activity totals maintained by the analytics job for explorer charts.
+-- Buckets are aligned to UTC and recomputed while they may still change
+
+CREATE TABLE IF NOT EXISTS aggregate_stats (
+    period TEXT NOT NULL,                          -- Bucket length: 'day' or 'hour'
+    bucket_start INTEGER NOT NULL,                 -- Unix timestamp of the bucket start (UTC)
+    block_count INTEGER NOT NULL,                  -- Indexed blocks in the bucket
+    transaction_count INTEGER NOT NULL,            -- Indexed transactions in the bucket
+    gas_used INTEGER NOT NULL,                     -- Total gas used by the blocks
+    avg_gas_price_gwei REAL,                       -- Average transaction gas price (NULL = no transactions)
+    active_addresses INTEGER NOT NULL,             -- Distinct senders and recipients
+    new_contracts INTEGER NOT NULL,                -- Contracts deployed in the bucket
+    burnt_fees_eth REAL NOT NULL,                  -- Sum of base_fee_per_gas * gas_used, in ETH
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (period, bucket_start)
+);
+
+-- New contracts per block range
+CREATE INDEX IF NOT EXISTS idx_contract_deployments_block ON contract_deployments(block_number);
@@ -0,0 +1,9 @@
+-- Migration 019: Transaction Method Selector
+-- Stores the 4-byte function selector of each transaction's input data, so transactions
+-- can be searched by the contract method they call. Transactions indexed before this
+-- migration keep a NULL selector.
+
+ALTER TABLE transactions ADD COLUMN method_id TEXT; -- "0x" + 8 hex chars, NULL for plain transfers
+
+CREATE INDEX IF NOT EXISTS idx_transactions_method_id
+    ON transactions(method_id, block_number, transaction_index);
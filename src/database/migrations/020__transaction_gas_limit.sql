@@ -0,0 +1,6 @@
+-- Migration 020: Transaction Gas Limit
+-- Stores the gas limit set by the sender next to the gas used from the receipt, so the
+-- API can report gas efficiency and tell out-of-gas failures apart from reverts.
+-- Transactions indexed before this migration keep a NULL gas limit.
+
+ALTER TABLE transactions ADD COLUMN gas_limit INTEGER; -- Gas limit of the transaction object
@@ -0,0 +1,11 @@
+-- Migration 021: Daily Leaderboards
+-- Top fee payers, gas guzzling transactions and biggest transfers of each UTC day,
+-- maintained by the analytics job alongside the daily aggregates
+
+CREATE TABLE IF NOT EXISTS leaderboards (
+    metric TEXT NOT NULL,                          -- 'fee-payers', 'gas-guzzlers' or 'transfers'
+    day_start INTEGER NOT NULL,                    -- Unix timestamp of the day start (UTC)
+    entries TEXT NOT NULL,                         -- JSON encoded ranking, best first
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (metric, day_start)
+);
@@ -0,0 +1,10 @@
+-- Migration 022: Beacon Slot Mappings
+-- Execution block to beacon slot pairs resolved through the beacon API. Missed slots make
+-- the offset between the two grow over time, so resolved pairs are kept both as a cache
+-- and as close starting points for resolving later blocks
+
+CREATE TABLE IF NOT EXISTS beacon_slot_mappings (
+    block_number INTEGER PRIMARY KEY,              -- Execution block number
+    slot INTEGER NOT NULL,                         -- Beacon slot whose payload is the block
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
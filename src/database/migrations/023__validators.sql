@@ -0,0 +1,19 @@
+-- Migration 023: Validator Registry
+-- Beacon chain validators synced periodically from the head state by the validator job
+
+CREATE TABLE IF NOT EXISTS validators (
+    validator_index INTEGER PRIMARY KEY,           -- Index in the beacon state registry
+    pubkey TEXT NOT NULL UNIQUE,                   -- BLS public key
+    withdrawal_credentials TEXT NOT NULL,
+    balance_gwei INTEGER NOT NULL,                 -- Current balance
+    effective_balance_gwei INTEGER NOT NULL,       -- Balance counted for rewards and penalties
+    status TEXT NOT NULL,                          -- e.g. 'active_ongoing', 'exited_unslashed'
+    slashed BOOLEAN NOT NULL,
+    activation_eligibility_epoch INTEGER,          -- NULL = not yet scheduled (far future epoch)
+    activation_epoch INTEGER,
+    exit_epoch INTEGER,
+    withdrawable_epoch INTEGER,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_validators_status ON validators(status, validator_index);
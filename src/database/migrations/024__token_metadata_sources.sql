@@ -0,0 +1,20 @@
+-- Migration 024: Token Metadata Provenance
+-- Resolver each token metadata field came from, and metadata set by hand for tokens with
+-- broken or missing on-chain metadata
+
+ALTER TABLE tokens ADD COLUMN name_source TEXT;      -- 'on_chain', 'token_list' or 'manual'
+ALTER TABLE tokens ADD COLUMN symbol_source TEXT;
+ALTER TABLE tokens ADD COLUMN decimals_source TEXT;
+
+-- Tokens discovered so far had their metadata read from the contract
+UPDATE tokens SET name_source = 'on_chain' WHERE name IS NOT NULL;
+UPDATE tokens SET symbol_source = 'on_chain' WHERE symbol IS NOT NULL;
+UPDATE tokens SET decimals_source = 'on_chain' WHERE decimals IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS token_metadata_overrides (
+    address TEXT PRIMARY KEY,                      -- Token contract address
+    name TEXT,                                     -- NULL = not overridden
+    symbol TEXT,
+    decimals INTEGER,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,6 @@
+-- Migration 025: Curated Tokens
+-- Tokens imported from token lists, whose metadata is preferred over discovered metadata.
+-- Tokens imported before being seen on chain have first and last seen block 0.
+
+ALTER TABLE tokens ADD COLUMN logo_uri TEXT;                    -- Logo from the token list
+ALTER TABLE tokens ADD COLUMN curated BOOLEAN NOT NULL DEFAULT 0; -- Imported from a token list
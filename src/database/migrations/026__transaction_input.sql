@@ -0,0 +1,14 @@
+-- Migration 026: Transaction Calldata
+-- Input data of transactions and function signatures known from verified contracts,
+-- used to decode method calls
+
+ALTER TABLE transactions ADD COLUMN input TEXT;        -- Calldata as hex, capped at TRANSACTION_INPUT_MAX_BYTES
+ALTER TABLE transactions ADD COLUMN input_size INTEGER; -- Full calldata length in bytes
+
+CREATE TABLE IF NOT EXISTS function_signatures (
+    selector TEXT NOT NULL,                        -- 4-byte selector, 0x-prefixed
+    signature TEXT NOT NULL,                       -- e.g. 'transfer(address,uint256)'
+    abi TEXT NOT NULL,                             -- JSON ABI of the function, with parameter names
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (selector, signature)
+);
@@ -0,0 +1,17 @@
+-- Migration 027: Block Rewards
+-- Proposer rewards of blocks as reported by the beacon API, with the balance change of the
+-- fee recipient on the execution layer. Rewards of a block never change, so rows are kept
+-- as a cache for block details
+
+CREATE TABLE IF NOT EXISTS block_rewards (
+    block_number INTEGER PRIMARY KEY,              -- Execution block number
+    slot INTEGER NOT NULL,                         -- Beacon slot whose payload is the block
+    proposer_index INTEGER NOT NULL,               -- Validator that proposed the block
+    total TEXT NOT NULL,                           -- Consensus reward of the proposer in Gwei
+    attestations TEXT NOT NULL,                    -- Share for included attestations in Gwei
+    sync_aggregate TEXT NOT NULL,                  -- Share for the sync aggregate in Gwei
+    proposer_slashings TEXT NOT NULL,              -- Share for proposer slashings in Gwei
+    attester_slashings TEXT NOT NULL,              -- Share for attester slashings in Gwei
+    fee_recipient_reward TEXT,                     -- Fee recipient balance change in Wei, NULL without archive state
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
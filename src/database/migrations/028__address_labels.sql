@@ -0,0 +1,15 @@
+-- Migration 028: Address Labels
+-- Names and categories of well-known addresses, shown on account pages and used by MEV
+-- analysis to recognise DEX and lending protocol interactions. Seed labels are refreshed
+-- on startup; labels set through the admin API are never overwritten by the seed
+
+CREATE TABLE IF NOT EXISTS address_labels (
+    address TEXT PRIMARY KEY,                      -- Lowercase address
+    label TEXT NOT NULL,                           -- Display name, e.g. 'Uniswap V2 Router'
+    category TEXT NOT NULL,                        -- e.g. 'dex', 'lending', 'staking', 'token'
+    source TEXT NOT NULL DEFAULT 'manual',         -- 'seed' or 'manual'
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_labels_category ON address_labels(category);
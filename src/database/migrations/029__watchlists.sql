@@ -0,0 +1,19 @@
+-- Migration 029: Watchlists
+-- Named sets of labelled addresses whose activity is reported together. Unlike
+-- tracked_addresses, watchlists do not restrict what gets indexed
+
+CREATE TABLE IF NOT EXISTS watchlists (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,                            -- e.g. 'Treasury wallets'
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS watchlist_addresses (
+    watchlist_id INTEGER NOT NULL,
+    address TEXT NOT NULL,                         -- Watched address (lowercase)
+    label TEXT,                                    -- Optional tag, e.g. 'hot wallet'
+    added_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (watchlist_id, address),
+    FOREIGN KEY (watchlist_id) REFERENCES watchlists (id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- Migration 030: Reorgs
+-- Chain reorganizations observed while following the head. Each row lists the indexed
+-- blocks that were replaced, for reorg statistics and the depth histogram
+
+CREATE TABLE IF NOT EXISTS reorgs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    first_block INTEGER NOT NULL,                  -- Oldest replaced block
+    depth INTEGER NOT NULL,                        -- Number of blocks replaced
+    replaced_blocks TEXT NOT NULL,                 -- JSON array of {number, old_hash, new_hash}
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_reorgs_depth ON reorgs(depth);
@@ -0,0 +1,26 @@
+-- Migration 031: Row History
+-- Previous versions of blocks and transactions rewritten by reorg handling or the
+-- consistency repair, kept as JSON snapshots so data disputes can be traced back
+
+CREATE TABLE IF NOT EXISTS blocks_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    block_number INTEGER NOT NULL,                 -- Block the version belongs to
+    hash TEXT NOT NULL,                            -- Hash of the replaced version
+    reason TEXT NOT NULL,                          -- 'reorg' or 'consistency_repair'
+    data TEXT NOT NULL,                            -- JSON snapshot of the replaced row
+    replaced_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_blocks_history_number ON blocks_history(block_number);
+
+CREATE TABLE IF NOT EXISTS transactions_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    hash TEXT NOT NULL,                            -- Transaction hash
+    block_number INTEGER NOT NULL,                 -- Block the replaced version was in
+    reason TEXT NOT NULL,                          -- 'reorg' or 'consistency_repair'
+    data TEXT NOT NULL,                            -- JSON snapshot of the replaced row
+    replaced_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_history_hash ON transactions_history(hash);
+CREATE INDEX IF NOT EXISTS idx_transactions_history_block ON transactions_history(block_number);
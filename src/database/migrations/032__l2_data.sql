@@ -0,0 +1,34 @@
+-- Migration 032: L2 Data
+-- Rollup-specific data of OP-stack and Arbitrum networks, indexed when NETWORK_PROFILE
+-- is an L2: L1 fee components of transactions, system transaction kinds and the L1
+-- origin / batch references of blocks
+
+CREATE TABLE IF NOT EXISTS l2_transactions (
+    transaction_hash TEXT PRIMARY KEY,
+    block_number INTEGER NOT NULL,
+    kind TEXT,                                     -- 'deposit', 'l1_attributes', 'withdrawal', 'retryable_*', ... NULL for user transactions
+    l1_fee TEXT,                                   -- Wei charged for L1 data (OP-stack)
+    l1_gas_used INTEGER,                           -- L1 gas of the data (OP-stack) or gas spent on L1 (Arbitrum)
+    l1_gas_price TEXT,                             -- L1 base fee in Wei used for the fee (OP-stack)
+    l1_fee_scalar TEXT,                            -- Fee scalar, decimal pre-Ecotone or the base fee scalar after (OP-stack)
+    l1_blob_base_fee TEXT,                         -- L1 blob base fee in Wei (OP-stack, Ecotone and later)
+    l1_block_number INTEGER,                       -- L1 block the transaction was sequenced at (Arbitrum)
+    source_hash TEXT,                              -- Deposit source hash (OP-stack deposits)
+    mint TEXT,                                     -- Wei minted on L2 by a deposit (OP-stack deposits)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_l2_transactions_block ON l2_transactions(block_number);
+CREATE INDEX IF NOT EXISTS idx_l2_transactions_kind ON l2_transactions(kind);
+
+CREATE TABLE IF NOT EXISTS l2_blocks (
+    block_number INTEGER PRIMARY KEY,
+    l1_block_number INTEGER,                       -- L1 origin (OP-stack) or latest L1 block seen by the sequencer (Arbitrum)
+    l1_block_hash TEXT,                            -- Hash of the L1 origin (OP-stack)
+    l1_timestamp INTEGER,                          -- Timestamp of the L1 origin (OP-stack)
+    sequence_number INTEGER,                       -- Position of the block within its L1 origin epoch (OP-stack)
+    batcher_hash TEXT,                             -- Batch submitter the block is posted by (OP-stack)
+    send_count INTEGER,                            -- L2-to-L1 messages sent so far (Arbitrum)
+    send_root TEXT,                                -- Merkle root of the outbox (Arbitrum)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
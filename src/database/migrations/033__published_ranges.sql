@@ -0,0 +1,14 @@
+-- Migration 033: Published Ranges
+-- Block ranges uploaded by the dataset publisher, one row per range, target and format.
+-- The manifest published next to the files is rebuilt from these rows
+
+CREATE TABLE IF NOT EXISTS published_ranges (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    target TEXT NOT NULL,                          -- s3://bucket/prefix or ipfs
+    format TEXT NOT NULL,                          -- 'parquet' or 'csv'
+    from_block INTEGER NOT NULL,
+    to_block INTEGER NOT NULL,
+    files TEXT NOT NULL,                           -- JSON array of {table, rows, bytes, sha256, location}
+    published_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(target, format, from_block)
+);
@@ -0,0 +1,22 @@
+-- Migration 034: Contract Interactions
+-- Hourly rollup of calls to contracts (transactions carrying a method selector), updated
+-- as blocks are stored, behind the trending contracts ranking. Callers are kept per hour
+-- so unique callers can be counted over any window
+
+CREATE TABLE IF NOT EXISTS contract_interactions (
+    contract_address TEXT NOT NULL,                -- Called contract
+    hour_start INTEGER NOT NULL,                   -- Unix timestamp of the hour start (UTC)
+    calls INTEGER NOT NULL,                        -- Calls made during the hour
+    PRIMARY KEY (contract_address, hour_start)
+);
+
+CREATE TABLE IF NOT EXISTS contract_callers (
+    contract_address TEXT NOT NULL,                -- Called contract
+    hour_start INTEGER NOT NULL,                   -- Unix timestamp of the hour start (UTC)
+    caller_address TEXT NOT NULL,                  -- Transaction sender
+    calls INTEGER NOT NULL,                        -- Calls of this sender during the hour
+    PRIMARY KEY (contract_address, hour_start, caller_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_contract_interactions_hour ON contract_interactions(hour_start);
+CREATE INDEX IF NOT EXISTS idx_contract_callers_hour ON contract_callers(hour_start);
@@ -0,0 +1,18 @@
+-- Migration 035: Transaction Receipts
+-- Receipt fields not kept in the transactions table, so contract creations can be tracked
+-- and transaction details show the full receipt
+
+CREATE TABLE IF NOT EXISTS transaction_receipts (
+    transaction_hash TEXT PRIMARY KEY,             -- Transaction the receipt belongs to
+    block_number INTEGER NOT NULL,                 -- Block of the transaction
+    cumulative_gas_used INTEGER NOT NULL,          -- Gas used in the block up to and including this transaction
+    effective_gas_price TEXT,                      -- Price paid per gas in wei (NULL when not reported)
+    contract_address TEXT,                         -- Contract created by the transaction
+    logs_bloom TEXT NOT NULL,                      -- Bloom filter of the logs as hex
+    transaction_type INTEGER,                      -- EIP-2718 type (0 legacy, 2 EIP-1559, 3 blob, ...)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_receipts_block ON transaction_receipts(block_number);
+CREATE INDEX IF NOT EXISTS idx_transaction_receipts_contract ON transaction_receipts(contract_address)
+WHERE contract_address IS NOT NULL;
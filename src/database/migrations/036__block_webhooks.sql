@@ -0,0 +1,26 @@
+-- Migration 036: Block Webhooks
+-- Subscriptions notified of every new indexed block, and the outbox their notifications
+-- wait in until delivered, so restarts and unreachable endpoints don't drop any
+
+CREATE TABLE IF NOT EXISTS block_webhooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL,                             -- Endpoint notifications are POSTed to
+    secret TEXT,                                   -- HMAC-SHA256 key signing the payloads
+    from_block INTEGER NOT NULL,                   -- First block notified, older blocks being backfilled are not
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS block_webhook_outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,          -- Delivery ID, sent along so receivers can drop duplicates
+    webhook_id INTEGER NOT NULL,
+    block_number INTEGER NOT NULL,
+    payload TEXT NOT NULL,                         -- JSON body, built when the block was stored
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,              -- Unix timestamp of the next delivery attempt
+    last_error TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (webhook_id) REFERENCES block_webhooks (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_webhook_outbox_due ON block_webhook_outbox(next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_block_webhook_outbox_webhook ON block_webhook_outbox(webhook_id, id);
@@ -0,0 +1,7 @@
+-- Migration 037: Account Types
+-- Whether an account is an externally owned account or a contract, detected when the
+-- indexer first stores the account so the account filters don't need an RPC call per row
+
+ALTER TABLE accounts ADD COLUMN account_type TEXT;                      -- 'eoa' or 'contract' (NULL = not detected yet)
+
+CREATE INDEX IF NOT EXISTS idx_accounts_type ON accounts(account_type);
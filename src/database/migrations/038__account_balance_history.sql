@@ -0,0 +1,14 @@
+-- Migration 038: Account Balance History
+-- Balance snapshots of indexed accounts for balance charts, at most one per account and
+-- period of BALANCE_HISTORY_INTERVAL_BLOCKS blocks (the latest balance read in the period)
+
+CREATE TABLE IF NOT EXISTS account_balance_history (
+    address TEXT NOT NULL,                         -- Account address (lowercase)
+    period INTEGER NOT NULL,                       -- block_number / BALANCE_HISTORY_INTERVAL_BLOCKS
+    block_number INTEGER NOT NULL,                 -- Block the account was last seen at in the period
+    balance TEXT NOT NULL,                         -- Balance in wei after that block
+    balance_at_block INTEGER,                      -- Block the balance was read at (NULL = latest state)
+    PRIMARY KEY (address, period)
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_balance_history_block ON account_balance_history(block_number);
@@ -0,0 +1,5 @@
+-- Migration 039: Search Indexes
+-- Case-insensitive indexes serving token name and symbol prefix search (LIKE 'query%')
+
+CREATE INDEX IF NOT EXISTS idx_tokens_symbol_nocase ON tokens(symbol COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS idx_tokens_name_nocase ON tokens(name COLLATE NOCASE);
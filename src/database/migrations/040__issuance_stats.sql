@@ -0,0 +1,6 @@
+-- Migration 040: Issuance Statistics
+-- Estimated consensus layer issuance per day, set by the analytics job from a sample of
+-- proposer rewards so it can be netted against burnt fees
+
+ALTER TABLE aggregate_stats ADD COLUMN issuance_eth REAL;        -- Estimated issuance in ETH (NULL = no rewards sampled)
+ALTER TABLE aggregate_stats ADD COLUMN issuance_samples INTEGER; -- Blocks whose rewards were sampled (NULL = not sampled yet)
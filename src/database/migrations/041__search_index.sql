@@ -0,0 +1,76 @@
+-- Migration 041: Full-Text Search Index
+-- FTS5 index over token names and symbols and address labels, serving name search without
+-- scanning the tokens table. Triggers keep it in sync with both tables.
+--
+-- The index is keyed by search_documents.id rather than the rowid of the indexed tables,
+-- which VACUUM may renumber as they have no INTEGER PRIMARY KEY
+
+CREATE TABLE IF NOT EXISTS search_documents (
+    id INTEGER PRIMARY KEY,                        -- Rowid of the document in search_index
+    kind TEXT NOT NULL,                            -- 'token' or 'label'
+    address TEXT NOT NULL,                         -- Token or labeled address
+    UNIQUE (kind, address)
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    name,                                          -- Token name or address label
+    symbol,                                        -- Token symbol, NULL for labels
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+-- Tokens
+CREATE TRIGGER IF NOT EXISTS tokens_search_insert AFTER INSERT ON tokens
+BEGIN
+    INSERT OR IGNORE INTO search_documents (kind, address) VALUES ('token', new.address);
+    INSERT INTO search_index (rowid, name, symbol)
+    SELECT id, new.name, new.symbol FROM search_documents
+    WHERE kind = 'token' AND address = new.address;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tokens_search_update AFTER UPDATE OF name, symbol ON tokens
+WHEN old.name IS NOT new.name OR old.symbol IS NOT new.symbol
+BEGIN
+    UPDATE search_index SET name = new.name, symbol = new.symbol
+    WHERE rowid = (SELECT id FROM search_documents WHERE kind = 'token' AND address = new.address);
+END;
+
+CREATE TRIGGER IF NOT EXISTS tokens_search_delete AFTER DELETE ON tokens
+BEGIN
+    DELETE FROM search_index
+    WHERE rowid = (SELECT id FROM search_documents WHERE kind = 'token' AND address = old.address);
+    DELETE FROM search_documents WHERE kind = 'token' AND address = old.address;
+END;
+
+-- Address labels
+CREATE TRIGGER IF NOT EXISTS address_labels_search_insert AFTER INSERT ON address_labels
+BEGIN
+    INSERT OR IGNORE INTO search_documents (kind, address) VALUES ('label', new.address);
+    INSERT INTO search_index (rowid, name, symbol)
+    SELECT id, new.label, NULL FROM search_documents
+    WHERE kind = 'label' AND address = new.address;
+END;
+
+CREATE TRIGGER IF NOT EXISTS address_labels_search_update AFTER UPDATE OF label ON address_labels
+WHEN old.label IS NOT new.label
+BEGIN
+    UPDATE search_index SET name = new.label
+    WHERE rowid = (SELECT id FROM search_documents WHERE kind = 'label' AND address = new.address);
+END;
+
+CREATE TRIGGER IF NOT EXISTS address_labels_search_delete AFTER DELETE ON address_labels
+BEGIN
+    DELETE FROM search_index
+    WHERE rowid = (SELECT id FROM search_documents WHERE kind = 'label' AND address = old.address);
+    DELETE FROM search_documents WHERE kind = 'label' AND address = old.address;
+END;
+
+-- Existing rows
+INSERT OR IGNORE INTO search_documents (kind, address) SELECT 'token', address FROM tokens;
+INSERT OR IGNORE INTO search_documents (kind, address) SELECT 'label', address FROM address_labels;
+INSERT INTO search_index (rowid, name, symbol)
+SELECT d.id, t.name, t.symbol FROM search_documents d JOIN tokens t ON t.address = d.address
+WHERE d.kind = 'token';
+INSERT INTO search_index (rowid, name, symbol)
+SELECT d.id, l.label, NULL FROM search_documents d JOIN address_labels l ON l.address = d.address
+WHERE d.kind = 'label';
@@ -0,0 +1,9 @@
+-- Migration 042: Sortable Token Transfer Amounts
+-- Amounts are decimal strings without leading zeros, so comparing them as text is wrong
+-- across lengths. Prefixing the two-digit length makes the text order the numeric order
+-- (uint256 values have at most 78 digits), which lets amount filters use an index
+
+ALTER TABLE token_transfers ADD COLUMN amount_sort TEXT
+    GENERATED ALWAYS AS (printf('%02d', length(amount)) || amount) VIRTUAL;
+
+CREATE INDEX IF NOT EXISTS idx_token_transfers_token_amount ON token_transfers(token_address, amount_sort);
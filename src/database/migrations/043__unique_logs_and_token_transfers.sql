@@ -0,0 +1,43 @@
+-- Migration 043: Unique Logs and Token Transfers
+-- Reprocessing a block inserted its logs and token transfers again. Duplicates are removed
+-- and (transaction_hash, log_index) made unique, so inserts can skip rows already stored
+
+-- Keep the first copy of every log
+DELETE FROM logs
+WHERE id NOT IN (SELECT MIN(id) FROM logs GROUP BY transaction_hash, log_index);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_logs_transaction_log_index ON logs(transaction_hash, log_index);
+
+-- Log a token transfer was decoded from
+ALTER TABLE token_transfers ADD COLUMN log_index INTEGER;
+
+-- Transfers are decoded one per Transfer log with from and to topics, in log order, so the
+-- n-th transfer of a transaction stored first comes from its n-th Transfer log
+UPDATE token_transfers
+SET log_index = matched.log_index
+FROM (
+    SELECT transfers.id, transfer_logs.log_index
+    FROM (
+        SELECT id, transaction_hash,
+               ROW_NUMBER() OVER (PARTITION BY transaction_hash ORDER BY id) AS position
+        FROM token_transfers
+    ) AS transfers
+    JOIN (
+        SELECT transaction_hash, log_index,
+               ROW_NUMBER() OVER (PARTITION BY transaction_hash ORDER BY log_index) AS position
+        FROM logs
+        WHERE topic0 = '0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef'
+          AND topic2 IS NOT NULL
+    ) AS transfer_logs
+      ON transfer_logs.transaction_hash = transfers.transaction_hash
+     AND transfer_logs.position = transfers.position
+) AS matched
+WHERE token_transfers.id = matched.id;
+
+-- Rows left without a log are copies inserted by reprocessing. Transfers of transactions
+-- whose logs aren't stored keep a NULL log index
+DELETE FROM token_transfers
+WHERE log_index IS NULL
+  AND transaction_hash IN (SELECT transaction_hash FROM logs);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_token_transfers_transaction_log_index ON token_transfers(transaction_hash, log_index);
@@ -0,0 +1,16 @@
+-- Migration 044: Sortable Balances and Values
+-- Balances and values are decimal strings of up to 78 digits, which REAL casts round and
+-- text comparison misorders across lengths. Like token_transfers.amount_sort, the
+-- two-digit length prefix makes the text order of these columns the numeric order
+
+ALTER TABLE accounts ADD COLUMN balance_sort TEXT
+    GENERATED ALWAYS AS (printf('%02d', length(balance)) || balance) VIRTUAL;
+
+ALTER TABLE token_balances ADD COLUMN balance_sort TEXT
+    GENERATED ALWAYS AS (printf('%02d', length(balance)) || balance) VIRTUAL;
+
+ALTER TABLE transactions ADD COLUMN value_sort TEXT
+    GENERATED ALWAYS AS (printf('%02d', length(value)) || value) VIRTUAL;
+
+CREATE INDEX IF NOT EXISTS idx_accounts_balance_sort ON accounts(balance_sort);
+CREATE INDEX IF NOT EXISTS idx_token_balances_token_balance ON token_balances(token_address, balance_sort);
@@ -0,0 +1,20 @@
+-- Migration 045: Block Fee Recipients
+-- Fee recipient of the beacon execution payload next to the miner of the execution block,
+-- and the proposer payment made by the fee recipient in the last transaction of the block,
+-- which shows which builders pay which proposers under MEV-Boost
+
+CREATE TABLE IF NOT EXISTS block_fee_recipients (
+    block_number INTEGER PRIMARY KEY,
+    slot INTEGER,
+    proposer_index INTEGER,
+    miner TEXT,                                    -- Coinbase of the execution block
+    fee_recipient TEXT NOT NULL,                   -- Fee recipient of the beacon execution payload
+    mismatch INTEGER NOT NULL DEFAULT 0,           -- 1 when the fee recipient isn't the miner
+    proposer_payee TEXT,                           -- Recipient of the fee recipient's payment in the last transaction
+    proposer_payment TEXT,                         -- Wei paid to the proposer payee
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_fee_recipients_fee_recipient ON block_fee_recipients(fee_recipient);
+CREATE INDEX IF NOT EXISTS idx_block_fee_recipients_proposer_index ON block_fee_recipients(proposer_index);
+CREATE INDEX IF NOT EXISTS idx_block_fee_recipients_mismatch ON block_fee_recipients(mismatch) WHERE mismatch = 1;
@@ -0,0 +1,8 @@
+-- Migration 046: Contract Factories
+-- Contracts deployed by other contracts (CREATE / CREATE2 found in call traces) record the
+-- factory that ran the creation, so deployments form creator -> created trees
+
+-- Contract that deployed the contract, NULL when the creation transaction deployed it
+ALTER TABLE contract_deployments ADD COLUMN factory_address TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_contract_deployments_factory ON contract_deployments(factory_address);
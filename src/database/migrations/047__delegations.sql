@@ -0,0 +1,20 @@
+-- Migration 047: Delegations
+-- Current delegate and voting power of accounts in ERC-20 Votes tokens, kept up to date
+-- from DelegateChanged and DelegateVotesChanged events. Each side remembers the event it
+-- was last set from, so blocks stored out of order never overwrite newer state
+
+CREATE TABLE IF NOT EXISTS delegations (
+    token_address TEXT NOT NULL,                   -- Votes token (lowercase)
+    account_address TEXT NOT NULL,                 -- Account (lowercase)
+    delegate TEXT,                                 -- Current delegate of the account (NULL = never delegated)
+    delegate_block INTEGER,                        -- Block of the DelegateChanged event setting it
+    delegate_log_index INTEGER,
+    votes TEXT NOT NULL DEFAULT '0',               -- Voting power delegated to the account, in base units
+    votes_block INTEGER,                           -- Block of the DelegateVotesChanged event setting it
+    votes_log_index INTEGER,
+    votes_sort TEXT GENERATED ALWAYS AS (printf('%02d', length(votes)) || votes) VIRTUAL,
+    PRIMARY KEY (token_address, account_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_delegations_token_votes ON delegations(token_address, votes_sort);
+CREATE INDEX IF NOT EXISTS idx_delegations_token_delegate ON delegations(token_address, delegate);
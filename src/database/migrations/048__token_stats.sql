@@ -0,0 +1,12 @@
+-- Migration 048: Token Statistics
+-- Total supply, holder count and recent transfer count of tokens, refreshed periodically
+-- by the token service so token lists can show and rank them
+
+ALTER TABLE tokens ADD COLUMN total_supply TEXT;            -- totalSupply() in base units (NULL = unknown)
+ALTER TABLE tokens ADD COLUMN total_supply_block INTEGER;   -- Head block when totalSupply() was last read
+ALTER TABLE tokens ADD COLUMN holder_count INTEGER;         -- Accounts with a non-zero stored balance
+ALTER TABLE tokens ADD COLUMN recent_transfers INTEGER;     -- Transfers in the last TOKEN_ACTIVITY_WINDOW_BLOCKS blocks
+
+CREATE INDEX IF NOT EXISTS idx_tokens_holder_count ON tokens(holder_count);
+CREATE INDEX IF NOT EXISTS idx_tokens_recent_transfers ON tokens(recent_transfers);
+CREATE INDEX IF NOT EXISTS idx_tokens_total_supply_block ON tokens(total_supply_block);
@@ -0,0 +1,5 @@
+-- Migration 049: Token Type Confidence
+-- New tokens get their standard from ERC-165 supportsInterface or the selectors in their
+-- bytecode before falling back to the shape of their Transfer event
+
+ALTER TABLE tokens ADD COLUMN token_type_confidence TEXT;   -- high (ERC-165), medium (bytecode), low (Transfer event), NULL = not detected
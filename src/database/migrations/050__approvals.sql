@@ -0,0 +1,19 @@
+-- Migration 050: Approvals
+-- Current ERC-20 allowances and ERC-721/1155 operator approvals granted by accounts, kept
+-- up to date from Approval and ApprovalForAll events. Each row remembers the event it was
+-- last set from, so blocks stored out of order never overwrite a newer approval
+
+CREATE TABLE IF NOT EXISTS approvals (
+    token_address TEXT NOT NULL,                   -- Token contract (lowercase)
+    owner_address TEXT NOT NULL,                   -- Account granting the approval (lowercase)
+    spender_address TEXT NOT NULL,                 -- Spender (allowance) or operator (lowercase)
+    kind TEXT NOT NULL,                            -- allowance (ERC-20 Approval) or operator (ApprovalForAll)
+    value TEXT NOT NULL,                           -- Allowance in base units, or 1 / 0 for operators
+    transaction_hash TEXT NOT NULL,                -- Transaction of the event setting it
+    block_number INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    PRIMARY KEY (token_address, owner_address, spender_address, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_approvals_owner ON approvals(owner_address, block_number);
+CREATE INDEX IF NOT EXISTS idx_approvals_spender ON approvals(spender_address);
@@ -0,0 +1,6 @@
+-- Migration 051: Withdrawals By Validator
+-- Serves paginated per-validator withdrawal lookups (newest first) straight from the index.
+-- Supersedes the single-column validator index, which is a prefix of this one
+
+DROP INDEX IF EXISTS idx_withdrawals_validator;
+CREATE INDEX IF NOT EXISTS idx_withdrawals_validator_block ON withdrawals(validator_index, block_number, withdrawal_index);
@@ -0,0 +1,12 @@
+-- Migration 052: Storage Snapshots
+-- Row counts and page sizes of every table, sampled once a day, from which the storage
+-- report derives growth rates
+
+CREATE TABLE IF NOT EXISTS storage_snapshots (
+    sampled_on TEXT NOT NULL,                      -- UTC date of the sample (YYYY-MM-DD)
+    table_name TEXT NOT NULL,
+    row_count INTEGER NOT NULL,
+    table_bytes INTEGER NOT NULL,                  -- Pages of the table b-tree
+    index_bytes INTEGER NOT NULL,                  -- Pages of the indexes on the table
+    PRIMARY KEY (sampled_on, table_name)
+);
@@ -0,0 +1,19 @@
+-- Migration 053: Deposits
+-- DepositEvent logs of the beacon deposit contract, one row per deposit, complementing
+-- the withdrawals. Cleared and rewritten with the rest of their block
+
+CREATE TABLE IF NOT EXISTS deposits (
+    block_number INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    deposit_index INTEGER NOT NULL,                -- Index in the deposit contract merkle tree
+    depositor TEXT NOT NULL,                       -- Sender of the deposit transaction (lowercase)
+    pubkey TEXT NOT NULL,                          -- BLS public key of the validator (lowercase)
+    withdrawal_credentials TEXT NOT NULL,
+    amount TEXT NOT NULL,                          -- Gwei
+    signature TEXT NOT NULL,
+    PRIMARY KEY (block_number, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deposits_depositor ON deposits(depositor, block_number, log_index);
+CREATE INDEX IF NOT EXISTS idx_deposits_pubkey ON deposits(pubkey, block_number, log_index);
@@ -0,0 +1,11 @@
+-- Migration 054: Finality Checkpoints
+-- Latest justified and finalized checkpoints of the beacon chain with the execution
+-- block of their checkpoint block, from which block statuses are derived
+
+CREATE TABLE IF NOT EXISTS finality_checkpoints (
+    kind TEXT PRIMARY KEY,                         -- justified or finalized
+    epoch INTEGER NOT NULL,
+    root TEXT NOT NULL,                            -- Beacon block root of the checkpoint
+    block_number INTEGER,                          -- Execution block of the checkpoint block
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,63 @@
+-- Migration 055: Transaction Method Name
+-- Stores the function name of each transaction's selector when it is known, so
+-- transaction lists can show it without decoding calldata per request. New transactions
+-- are named at index time from common token and router functions, then from the
+-- signatures of verified contracts; transactions indexed before are named here the same way.
+
+ALTER TABLE transactions ADD COLUMN method_name TEXT; -- e.g. 'transfer', NULL when unknown
+
+CREATE TEMPORARY TABLE known_methods (selector TEXT PRIMARY KEY, name TEXT NOT NULL);
+INSERT INTO known_methods (selector, name) VALUES
+    ('0xa9059cbb', 'transfer'),
+    ('0x23b872dd', 'transferFrom'),
+    ('0x095ea7b3', 'approve'),
+    ('0xd505accf', 'permit'),
+    ('0x42842e0e', 'safeTransferFrom'),
+    ('0xb88d4fde', 'safeTransferFrom'),
+    ('0xf242432a', 'safeTransferFrom'),
+    ('0x2eb2c2d6', 'safeBatchTransferFrom'),
+    ('0xa22cb465', 'setApprovalForAll'),
+    ('0x40c10f19', 'mint'),
+    ('0x42966c68', 'burn'),
+    ('0xd0e30db0', 'deposit'),
+    ('0x2e1a7d4d', 'withdraw'),
+    ('0xac9650d8', 'multicall'),
+    ('0x5ae401dc', 'multicall'),
+    ('0x252dba42', 'aggregate'),
+    ('0x82ad56cb', 'aggregate3'),
+    ('0x24856bc3', 'execute'),
+    ('0x3593564c', 'execute'),
+    ('0xc04b8d59', 'exactInput'),
+    ('0x414bf389', 'exactInputSingle'),
+    ('0xf28c0498', 'exactOutput'),
+    ('0xdb3e2198', 'exactOutputSingle'),
+    ('0x38ed1739', 'swapExactTokensForTokens'),
+    ('0x8803dbee', 'swapTokensForExactTokens'),
+    ('0x7ff36ab5', 'swapExactETHForTokens'),
+    ('0xfb3bdb41', 'swapETHForExactTokens'),
+    ('0x18cbafe5', 'swapExactTokensForETH'),
+    ('0x4a25d94a', 'swapTokensForExactETH'),
+    ('0x5c11d795', 'swapExactTokensForTokensSupportingFeeOnTransferTokens'),
+    ('0xb6f9de95', 'swapExactETHForTokensSupportingFeeOnTransferTokens'),
+    ('0x791ac947', 'swapExactTokensForETHSupportingFeeOnTransferTokens'),
+    ('0xe8e33700', 'addLiquidity'),
+    ('0xf305d719', 'addLiquidityETH'),
+    ('0xbaa2abde', 'removeLiquidity'),
+    ('0x02751cec', 'removeLiquidityETH');
+
+UPDATE transactions SET method_name = (
+    SELECT name FROM known_methods WHERE selector = transactions.method_id
+)
+WHERE method_id IN (SELECT selector FROM known_methods);
+
+UPDATE transactions SET method_name = (
+    SELECT substr(signature, 1, instr(signature, '(') - 1)
+    FROM function_signatures
+    WHERE selector = transactions.method_id
+    ORDER BY created_at
+    LIMIT 1
+)
+WHERE method_name IS NULL
+  AND method_id IN (SELECT selector FROM function_signatures);
+
+DROP TABLE known_methods;
@@ -0,0 +1,16 @@
+-- Migration 056: Sync Participation and Proposer Duties
+-- Sync committee participation read from the sync aggregate of each beacon block, and the
+-- validators scheduled to propose the blocks of each epoch, fetched once per epoch as
+-- blocks are indexed. Duties of slots without a block name the proposers that missed them
+
+ALTER TABLE blocks ADD COLUMN sync_participation REAL; -- Percentage of sync committee signatures, NULL before Altair
+
+CREATE TABLE IF NOT EXISTS proposer_duties (
+    slot INTEGER PRIMARY KEY,
+    epoch INTEGER NOT NULL,
+    validator_index INTEGER NOT NULL,
+    pubkey TEXT NOT NULL                           -- BLS public key of the scheduled proposer
+);
+
+CREATE INDEX IF NOT EXISTS idx_proposer_duties_epoch ON proposer_duties(epoch);
+CREATE INDEX IF NOT EXISTS idx_proposer_duties_validator ON proposer_duties(validator_index, slot);
@@ -0,0 +1,30 @@
+-- Migration 057: Epoch Summaries
+-- Attestations and slashings included in each beacon block, and per-epoch totals of the
+-- indexed blocks, refreshed as the blocks of an epoch and of the next one are stored
+
+ALTER TABLE blocks ADD COLUMN attestation_count INTEGER;
+ALTER TABLE blocks ADD COLUMN slashing_count INTEGER; -- Proposer and attester slashings
+
+CREATE TABLE IF NOT EXISTS epochs (
+    epoch INTEGER PRIMARY KEY,
+    blocks_proposed INTEGER NOT NULL,
+    missed_slots INTEGER NOT NULL,                 -- Slots without an indexed block, up to the newest indexed slot
+    attestations INTEGER,                          -- NULL for blocks indexed before attestations were counted
+    deposits INTEGER,
+    slashings INTEGER,
+    avg_sync_participation REAL,                   -- NULL before Altair
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Summaries of the epochs already indexed
+INSERT OR REPLACE INTO epochs (
+    epoch, blocks_proposed, missed_slots, attestations, deposits, slashings,
+    avg_sync_participation
+)
+SELECT slot / 32, COUNT(*),
+       MIN(32, (SELECT MAX(slot) FROM blocks) - (slot / 32) * 32 + 1) - COUNT(*),
+       SUM(attestation_count), SUM(block_deposit_count), SUM(slashing_count),
+       AVG(sync_participation)
+FROM blocks
+WHERE slot IS NOT NULL
+GROUP BY slot / 32;
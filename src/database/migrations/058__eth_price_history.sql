@@ -0,0 +1,11 @@
+-- Migration 058: ETH Price History
+-- ETH/USD price per hour, recorded by the market data service as it polls its providers,
+-- so token transfers can be valued at the price of their time. Hours before the service
+-- ran, or while every provider failed, have no price.
+
+CREATE TABLE IF NOT EXISTS eth_prices (
+    hour INTEGER PRIMARY KEY,          -- Unix time of the start of the hour
+    price_usd REAL NOT NULL,           -- Latest price fetched during the hour
+    source TEXT NOT NULL,              -- Market data provider, e.g. 'coingecko'
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
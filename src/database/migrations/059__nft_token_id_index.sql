@@ -0,0 +1,6 @@
+-- Migration 059: NFT Token ID Index
+-- Finds the transfers of one NFT, so the image proxy only serves tokens that were indexed
+
+CREATE INDEX IF NOT EXISTS idx_token_transfers_token_id
+    ON token_transfers(token_address, token_id)
+    WHERE token_id IS NOT NULL;
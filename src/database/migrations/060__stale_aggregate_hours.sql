@@ -0,0 +1,27 @@
+-- Migration 060: Stale Aggregate Hours
+-- Hours holding blocks written since the analytics job last read them, so aggregates are
+-- recomputed after gap repair, reindexing, backfills and reorgs. Triggers record the hour of
+-- every inserted or replaced block; pruned and sharded blocks leave their aggregates alone.
+--
+-- Each write replaces the hour's row, so an id above the last one the job read means the
+-- hour was written again while it was being aggregated. Rows are deleted and reinserted, as
+-- the upsert writing blocks would override an OR REPLACE conflict clause
+
+CREATE TABLE IF NOT EXISTS stale_aggregate_hours (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bucket_start INTEGER NOT NULL UNIQUE           -- Start of the hour (Unix seconds)
+);
+
+CREATE TRIGGER IF NOT EXISTS blocks_stale_aggregate_insert AFTER INSERT ON blocks
+BEGIN
+    DELETE FROM stale_aggregate_hours WHERE bucket_start = new.timestamp - new.timestamp % 3600;
+    INSERT INTO stale_aggregate_hours (bucket_start) VALUES (new.timestamp - new.timestamp % 3600);
+END;
+
+CREATE TRIGGER IF NOT EXISTS blocks_stale_aggregate_update AFTER UPDATE OF hash, timestamp ON blocks
+BEGIN
+    DELETE FROM stale_aggregate_hours WHERE bucket_start = old.timestamp - old.timestamp % 3600;
+    INSERT INTO stale_aggregate_hours (bucket_start) VALUES (old.timestamp - old.timestamp % 3600);
+    DELETE FROM stale_aggregate_hours WHERE bucket_start = new.timestamp - new.timestamp % 3600;
+    INSERT INTO stale_aggregate_hours (bucket_start) VALUES (new.timestamp - new.timestamp % 3600);
+END;
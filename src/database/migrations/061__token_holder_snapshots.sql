@@ -0,0 +1,40 @@
+-- Migration 061: Token Holder Snapshots
+-- Token balances at a block summed from the indexed transfers, built once per (token,
+-- block) by the holder export and paged from here. Writing a block drops the snapshots at
+-- or above it, as its transfers may change their sums.
+
+CREATE TABLE IF NOT EXISTS token_holder_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    token_address TEXT NOT NULL,
+    block_number INTEGER NOT NULL,                 -- Balances after this block
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (token_address, block_number)
+);
+
+CREATE TABLE IF NOT EXISTS token_holder_snapshot_balances (
+    snapshot_id INTEGER NOT NULL,
+    address TEXT NOT NULL,
+    balance TEXT NOT NULL,                         -- Base units, always positive
+    balance_block INTEGER NOT NULL,                -- Last block that moved the balance
+    balance_sort TEXT GENERATED ALWAYS AS (printf('%02d', length(balance)) || balance) VIRTUAL,
+    PRIMARY KEY (snapshot_id, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_holder_snapshot_balances_sort
+    ON token_holder_snapshot_balances(snapshot_id, balance_sort);
+
+CREATE TRIGGER IF NOT EXISTS blocks_holder_snapshot_insert AFTER INSERT ON blocks
+BEGIN
+    DELETE FROM token_holder_snapshot_balances WHERE snapshot_id IN (
+        SELECT id FROM token_holder_snapshots WHERE block_number >= new.number
+    );
+    DELETE FROM token_holder_snapshots WHERE block_number >= new.number;
+END;
+
+CREATE TRIGGER IF NOT EXISTS blocks_holder_snapshot_update AFTER UPDATE OF hash ON blocks
+BEGIN
+    DELETE FROM token_holder_snapshot_balances WHERE snapshot_id IN (
+        SELECT id FROM token_holder_snapshots WHERE block_number >= new.number
+    );
+    DELETE FROM token_holder_snapshots WHERE block_number >= new.number;
+END;
@@ -1,15 +1,35 @@
+mod block_store;
+pub(crate) use block_store::BLOCK_DATA_TABLES;
 mod models;
+mod shards;
 
+#[cfg(feature = "chaos")]
+use crate::chaos::FaultInjector;
 use anyhow::{Context, Result};
-use sqlx::{migrate::MigrateDatabase, pool::PoolOptions, Pool, Sqlite};
-use std::path::Path;
+use futures::stream::BoxStream;
+use sqlx::{
+    migrate::MigrateDatabase, pool::PoolOptions, query::QueryAs, sqlite::SqliteArguments,
+    Connection, Pool, Sqlite,
+};
+#[cfg(feature = "chaos")]
+use std::sync::Arc;
+use std::{collections::BTreeMap, path::Path};
 use tracing::{error, info};
 
 pub use models::*;
 
+/// Allowance of 2^256 - 1, which tokens treat as never decreasing
+const UNLIMITED_ALLOWANCE: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
 /// Service for database operations
 pub struct DatabaseService {
     pub pool: Pool<Sqlite>,
+    path: String,
+    shard_blocks: i64, // Blocks per shard file (0 = sharding disabled)
+    shards: std::sync::RwLock<BTreeMap<i64, Pool<Sqlite>>>, // Shard pools by first block
+    #[cfg(feature = "chaos")]
+    faults: Option<Arc<FaultInjector>>, // Fails block writes in resilience tests
 }
 
 impl DatabaseService {
@@ -20,112 +40,30 @@ impl DatabaseService {
             .unwrap_or(database_url)
             .to_string();
 
-        // Create database directory if needed
-        if let Some(db_path) = Path::new(&clean_url).parent() {
-            if !db_path.exists() {
-                std::fs::create_dir_all(db_path).context("Failed to create database directory")?;
-                info!("Created database directory: {:?}", db_path);
-            }
-        }
-
-        // Check if database exists, create if not
-        if !Sqlite::database_exists(&clean_url).await.unwrap_or(false) {
-            info!("Database does not exist, creating...");
-            Sqlite::create_database(&clean_url)
-                .await
-                .context("Failed to create database")?;
-        }
-
-        // Connect to the database
-        let pool = PoolOptions::new()
-            .max_connections(10)
-            .connect(&clean_url)
-            .await
-            .context("Failed to connect to database")?;
-
-        // Run migrations
-        info!("Running database migrations...");
-        sqlx::migrate!("./src/database/migrations")
-            .run(&pool)
-            .await
-            .context("Failed to run migrations")?;
+        let pool = open_database(&clean_url, 10).await?;
 
         info!("Database initialized successfully");
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            path: clean_url,
+            shard_blocks: 0,
+            shards: Default::default(),
+            #[cfg(feature = "chaos")]
+            faults: None,
+        })
     }
 
-    /// Insert a new block
-    pub async fn insert_block(&self, block: &Block) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO blocks (
-                number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
-                miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
-                nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
-                slot, proposer_index, epoch, slot_root, parent_root, beacon_deposit_count,
-                graffiti, randao_reveal, randao_mix
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(number) DO UPDATE SET
-                hash = excluded.hash,
-                parent_hash = excluded.parent_hash,
-                timestamp = excluded.timestamp,
-                gas_used = excluded.gas_used,
-                gas_limit = excluded.gas_limit,
-                transaction_count = excluded.transaction_count,
-                miner = excluded.miner,
-                difficulty = excluded.difficulty,
-                size_bytes = excluded.size_bytes,
-                base_fee_per_gas = excluded.base_fee_per_gas,
-                extra_data = excluded.extra_data,
-                state_root = excluded.state_root,
-                nonce = excluded.nonce,
-                withdrawals_root = excluded.withdrawals_root,
-                blob_gas_used = excluded.blob_gas_used,
-                excess_blob_gas = excluded.excess_blob_gas,
-                withdrawal_count = excluded.withdrawal_count,
-                slot = excluded.slot,
-                proposer_index = excluded.proposer_index,
-                epoch = excluded.epoch,
-                slot_root = excluded.slot_root,
-                parent_root = excluded.parent_root,
-                beacon_deposit_count = excluded.beacon_deposit_count,
-                graffiti = excluded.graffiti,
-                randao_reveal = excluded.randao_reveal,
-                randao_mix = excluded.randao_mix
-            "#,
-        )
-        .bind(block.number)
-        .bind(&block.hash)
-        .bind(&block.parent_hash)
-        .bind(block.timestamp)
-        .bind(block.gas_used)
-        .bind(block.gas_limit)
-        .bind(block.transaction_count)
-        .bind(&block.miner)
-        .bind(&block.difficulty)
-        .bind(block.size_bytes)
-        .bind(&block.base_fee_per_gas)
-        .bind(&block.extra_data)
-        .bind(&block.state_root)
-        .bind(&block.nonce)
-        .bind(&block.withdrawals_root)
-        .bind(block.blob_gas_used)
-        .bind(block.excess_blob_gas)
-        .bind(block.withdrawal_count)
-        .bind(block.slot)
-        .bind(block.proposer_index)
-        .bind(block.epoch)
-        .bind(&block.slot_root)
-        .bind(&block.parent_root)
-        .bind(block.beacon_deposit_count)
-        .bind(&block.graffiti)
-        .bind(&block.randao_reveal)
-        .bind(&block.randao_mix)
-        .execute(&self.pool)
-        .await
-        .context("Failed to insert block")?;
+    /// Fail a share of block writes before they commit, for resilience tests
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(mut self, faults: Arc<FaultInjector>) -> Self {
+        self.faults = Some(faults);
+        self
+    }
 
-        Ok(())
+    /// Insert a new block, see `store_block_data` for a block with everything derived from it
+    pub async fn insert_block(&self, block: &Block) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        block_store::insert_block(&mut conn, block).await
     }
 
     /// Insert a new transaction
@@ -133,8 +71,9 @@ impl DatabaseService {
         sqlx::query(
             r#"
             INSERT INTO transactions (
-                hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index,
+                method_id, method_name, gas_limit, input, input_size
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(hash) DO UPDATE SET
                 block_number = excluded.block_number,
                 from_address = excluded.from_address,
@@ -143,7 +82,12 @@ impl DatabaseService {
                 gas_used = excluded.gas_used,
                 gas_price = excluded.gas_price,
                 status = excluded.status,
-                transaction_index = excluded.transaction_index
+                transaction_index = excluded.transaction_index,
+                method_id = excluded.method_id,
+                method_name = excluded.method_name,
+                gas_limit = excluded.gas_limit,
+                input = excluded.input,
+                input_size = excluded.input_size
             "#,
         )
         .bind(&tx.hash)
@@ -155,6 +99,11 @@ impl DatabaseService {
         .bind(&tx.gas_price)
         .bind(tx.status)
         .bind(tx.transaction_index)
+        .bind(&tx.method_id)
+        .bind(&tx.method_name)
+        .bind(tx.gas_limit)
+        .bind(&tx.input)
+        .bind(tx.input_size)
         .execute(&self.pool)
         .await
         .context("Failed to insert transaction")?;
@@ -169,6 +118,7 @@ impl DatabaseService {
             INSERT INTO logs (
                 transaction_hash, block_number, address, topic0, topic1, topic2, topic3, data, log_index
             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(transaction_hash, log_index) DO NOTHING
             "#,
         )
         .bind(&log.transaction_hash)
@@ -192,12 +142,15 @@ impl DatabaseService {
         sqlx::query(
             r#"
             INSERT INTO accounts (
-                address, balance, transaction_count, first_seen_block, last_seen_block
-            ) VALUES (?, ?, ?, ?, ?)
+                address, balance, transaction_count, first_seen_block, last_seen_block, balance_at_block,
+                account_type
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(address) DO UPDATE SET
                 balance = excluded.balance,
                 transaction_count = excluded.transaction_count,
                 last_seen_block = excluded.last_seen_block,
+                balance_at_block = excluded.balance_at_block,
+                account_type = COALESCE(accounts.account_type, excluded.account_type),
                 updated_at = CURRENT_TIMESTAMP
             "#,
         )
@@ -206,6 +159,8 @@ impl DatabaseService {
         .bind(account.transaction_count)
         .bind(account.first_seen_block)
         .bind(account.last_seen_block)
+        .bind(account.balance_at_block)
+        .bind(&account.account_type)
         .execute(&self.pool)
         .await
         .context("Failed to update account")?;
@@ -213,47 +168,15 @@ impl DatabaseService {
         Ok(())
     }
 
-    /// Insert a new withdrawal
-    pub async fn insert_withdrawal(&self, withdrawal: &Withdrawal) -> Result<()> {
-        // First check if withdrawal already exists
-        let existing = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM withdrawals WHERE block_number = ? AND withdrawal_index = ?",
-        )
-        .bind(withdrawal.block_number)
-        .bind(withdrawal.withdrawal_index)
-        .fetch_one(&self.pool)
-        .await
-        .context("Failed to check existing withdrawal")?;
-
-        // Only insert if it doesn't exist
-        if existing == 0 {
-            sqlx::query(
-                r#"
-                INSERT INTO withdrawals (
-                    block_number, withdrawal_index, validator_index, address, amount
-                ) VALUES (?, ?, ?, ?, ?)
-                "#,
-            )
-            .bind(withdrawal.block_number)
-            .bind(withdrawal.withdrawal_index)
-            .bind(withdrawal.validator_index)
-            .bind(&withdrawal.address)
-            .bind(&withdrawal.amount)
-            .execute(&self.pool)
-            .await
-            .context("Failed to insert withdrawal")?;
-        }
-
-        Ok(())
-    }
-
     /// Insert a new token transfer
     pub async fn insert_token_transfer(&self, token_transfer: &TokenTransfer) -> Result<()> {
         sqlx::query(
             r#"
             INSERT INTO token_transfers (
-                transaction_hash, block_number, token_address, from_address, to_address, amount, token_type, token_id
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                transaction_hash, block_number, token_address, from_address, to_address, amount, token_type, token_id,
+                log_index
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(transaction_hash, log_index) DO NOTHING
             "#,
         )
         .bind(&token_transfer.transaction_hash)
@@ -264,6 +187,7 @@ impl DatabaseService {
         .bind(&token_transfer.amount)
         .bind(&token_transfer.token_type)
         .bind(&token_transfer.token_id)
+        .bind(token_transfer.log_index)
         .execute(&self.pool)
         .await
         .context("Failed to insert token transfer")?;
@@ -276,144 +200,86 @@ impl DatabaseService {
         &self,
         tx_hash: &str,
     ) -> Result<Vec<TokenTransfer>> {
-        let transfers = sqlx::query_as::<_, TokenTransfer>(
-            r#"
-            SELECT id, transaction_hash, token_address, from_address, to_address, amount, 
-                   block_number, token_type, token_id, created_at
-            FROM token_transfers 
-            WHERE transaction_hash = ? 
-            ORDER BY id
-            "#,
-        )
-        .bind(tx_hash)
-        .fetch_all(&self.pool)
-        .await
-        .context("Failed to get token transfers by transaction hash")?;
-
-        Ok(transfers)
-    }
-
-    /// Insert multiple transactions in a single batch for better performance
-    pub async fn insert_transactions_batch(&self, transactions: &[Transaction]) -> Result<()> {
-        if transactions.is_empty() {
-            return Ok(());
-        }
-
-        let mut query_builder = sqlx::QueryBuilder::new(
-            "INSERT INTO transactions (hash, block_number, transaction_index, from_address, to_address, value, gas_used, gas_price, status) "
-        );
-
-        query_builder.push_values(transactions, |mut b, tx| {
-            b.push_bind(&tx.hash)
-                .push_bind(tx.block_number)
-                .push_bind(tx.transaction_index)
-                .push_bind(&tx.from_address)
-                .push_bind(&tx.to_address)
-                .push_bind(&tx.value)
-                .push_bind(tx.gas_used)
-                .push_bind(&tx.gas_price)
-                .push_bind(tx.status);
-        });
-
-        query_builder.build().execute(&self.pool).await?;
-        Ok(())
-    }
-
-    /// Insert multiple logs in a single batch for better performance
-    pub async fn insert_logs_batch(&self, logs: &[Log]) -> Result<()> {
-        if logs.is_empty() {
-            return Ok(());
-        }
-
-        let mut query_builder = sqlx::QueryBuilder::new(
-            "INSERT INTO logs (transaction_hash, log_index, address, topic0, topic1, topic2, topic3, data, block_number) "
-        );
-
-        query_builder.push_values(logs, |mut b, log| {
-            b.push_bind(&log.transaction_hash)
-                .push_bind(log.log_index)
-                .push_bind(&log.address)
-                .push_bind(&log.topic0)
-                .push_bind(&log.topic1)
-                .push_bind(&log.topic2)
-                .push_bind(&log.topic3)
-                .push_bind(&log.data)
-                .push_bind(log.block_number);
-        });
-
-        query_builder.build().execute(&self.pool).await?;
-        Ok(())
-    }
+        let query = |pool: Pool<Sqlite>| async move {
+            sqlx::query_as::<_, TokenTransfer>(
+                r#"
+                SELECT id, transaction_hash, token_address, from_address, to_address, amount, 
+                       block_number, token_type, token_id, log_index, created_at
+                FROM token_transfers 
+                WHERE transaction_hash = ? 
+                ORDER BY id
+                "#,
+            )
+            .bind(tx_hash)
+            .fetch_all(&pool)
+            .await
+            .context("Failed to get token transfers by transaction hash")
+        };
 
-    /// Insert multiple token transfers in a single batch for better performance
-    pub async fn insert_token_transfers_batch(&self, transfers: &[TokenTransfer]) -> Result<()> {
+        let mut transfers = query(self.pool.clone()).await?;
         if transfers.is_empty() {
-            return Ok(());
+            for shard in self.shards_for_transaction(tx_hash).await? {
+                transfers = query(shard).await?;
+                if !transfers.is_empty() {
+                    break;
+                }
+            }
         }
 
-        let mut query_builder = sqlx::QueryBuilder::new(
-            "INSERT INTO token_transfers (transaction_hash, token_address, from_address, to_address, amount, block_number, token_type, token_id) "
-        );
-
-        query_builder.push_values(transfers, |mut b, transfer| {
-            b.push_bind(&transfer.transaction_hash)
-                .push_bind(&transfer.token_address)
-                .push_bind(&transfer.from_address)
-                .push_bind(&transfer.to_address)
-                .push_bind(&transfer.amount)
-                .push_bind(transfer.block_number)
-                .push_bind(&transfer.token_type)
-                .push_bind(&transfer.token_id);
-        });
-
-        query_builder.build().execute(&self.pool).await?;
-        Ok(())
+        Ok(transfers)
     }
 
-    /// Insert multiple accounts in a single batch for better performance
-    pub async fn insert_accounts_batch(&self, accounts: &[Account]) -> Result<()> {
-        if accounts.is_empty() {
-            return Ok(());
-        }
-
-        info!("Starting batch insert of {} accounts", accounts.len());
-
-        let mut query_builder = sqlx::QueryBuilder::new(
-            "INSERT OR IGNORE INTO accounts (address, balance, transaction_count, first_seen_block, last_seen_block) "
-        );
-
-        query_builder.push_values(accounts, |mut b, account| {
-            b.push_bind(&account.address)
-                .push_bind(&account.balance)
-                .push_bind(account.transaction_count)
-                .push_bind(account.first_seen_block)
-                .push_bind(account.last_seen_block);
-        });
+    /// Get the newest balance snapshots of an account within a block range, oldest first
+    pub async fn get_balance_history(
+        &self,
+        address: &str,
+        params: &BalanceHistoryParams,
+    ) -> Result<Vec<BalanceSnapshot>> {
+        let mut snapshots = sqlx::query_as::<_, BalanceSnapshot>(
+            r#"
+            SELECT h.block_number, b.timestamp, h.balance, h.balance_at_block
+            FROM account_balance_history h
+            LEFT JOIN blocks b ON b.number = h.block_number
+            WHERE h.address = ? AND h.block_number >= ? AND h.block_number <= ?
+            ORDER BY h.block_number DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(address)
+        .bind(params.from_block.unwrap_or(0))
+        .bind(params.to_block.unwrap_or(i64::MAX))
+        .bind(params.limit())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get balance history")?;
 
-        let result = query_builder.build().execute(&self.pool).await?;
-        info!(
-            "Batch insert completed: {} rows inserted/ignored",
-            result.rows_affected()
-        );
-        Ok(())
+        snapshots.reverse();
+        Ok(snapshots)
     }
 
     // ============================================================================
     // TOKEN MANAGEMENT
     // ============================================================================
 
-    /// Insert or update token information
+    /// Insert or update token information, keeping the metadata of curated tokens and
+    /// manually set fields
     pub async fn upsert_token(&self, token: &Token) -> Result<()> {
         sqlx::query(
             r#"
             INSERT INTO tokens (
                 address, name, symbol, decimals, token_type, 
-                first_seen_block, last_seen_block, total_transfers
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                first_seen_block, last_seen_block, total_transfers,
+                name_source, symbol_source, decimals_source, token_type_confidence
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(address) DO UPDATE SET
-                name = COALESCE(EXCLUDED.name, name),
-                symbol = COALESCE(EXCLUDED.symbol, symbol),
-                decimals = COALESCE(EXCLUDED.decimals, decimals),
+                token_type = CASE WHEN EXCLUDED.token_type_confidence IS NULL THEN token_type ELSE EXCLUDED.token_type END,
+                token_type_confidence = COALESCE(EXCLUDED.token_type_confidence, token_type_confidence),
+                name = CASE WHEN curated OR name_source = 'manual' THEN name ELSE COALESCE(EXCLUDED.name, name) END,
+                symbol = CASE WHEN curated OR symbol_source = 'manual' THEN symbol ELSE COALESCE(EXCLUDED.symbol, symbol) END,
+                decimals = CASE WHEN curated OR decimals_source = 'manual' THEN decimals ELSE COALESCE(EXCLUDED.decimals, decimals) END,
+                name_source = CASE WHEN curated OR name_source = 'manual' OR EXCLUDED.name IS NULL THEN name_source ELSE EXCLUDED.name_source END,
+                symbol_source = CASE WHEN curated OR symbol_source = 'manual' OR EXCLUDED.symbol IS NULL THEN symbol_source ELSE EXCLUDED.symbol_source END,
+                decimals_source = CASE WHEN curated OR decimals_source = 'manual' OR EXCLUDED.decimals IS NULL THEN decimals_source ELSE EXCLUDED.decimals_source END,
                 last_seen_block = MAX(last_seen_block, EXCLUDED.last_seen_block),
                 total_transfers = total_transfers + 1,
                 updated_at = CURRENT_TIMESTAMP
@@ -427,6 +293,10 @@ impl DatabaseService {
         .bind(token.first_seen_block)
         .bind(token.last_seen_block)
         .bind(token.total_transfers)
+        .bind(&token.name_source)
+        .bind(&token.symbol_source)
+        .bind(&token.decimals_source)
+        .bind(&token.token_type_confidence)
         .execute(&self.pool)
         .await
         .context("Failed to upsert token")?;
@@ -434,10 +304,80 @@ impl DatabaseService {
         Ok(())
     }
 
+    /// Insert or update tokens imported from a token list, marking them curated. Their
+    /// metadata replaces discovered metadata but not manual overrides. Returns the number
+    /// of tokens stored.
+    pub async fn upsert_curated_tokens(&self, tokens: &[Token]) -> Result<u64> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+
+        for token in tokens {
+            sqlx::query(
+                r#"
+                INSERT INTO tokens (
+                    address, name, symbol, decimals, token_type,
+                    first_seen_block, last_seen_block, total_transfers,
+                    name_source, symbol_source, decimals_source, logo_uri, curated
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1)
+                ON CONFLICT(address) DO UPDATE SET
+                    name = CASE WHEN name_source = 'manual' THEN name ELSE EXCLUDED.name END,
+                    symbol = CASE WHEN symbol_source = 'manual' THEN symbol ELSE EXCLUDED.symbol END,
+                    decimals = CASE WHEN decimals_source = 'manual' THEN decimals ELSE EXCLUDED.decimals END,
+                    name_source = CASE WHEN name_source = 'manual' THEN name_source ELSE EXCLUDED.name_source END,
+                    symbol_source = CASE WHEN symbol_source = 'manual' THEN symbol_source ELSE EXCLUDED.symbol_source END,
+                    decimals_source = CASE WHEN decimals_source = 'manual' THEN decimals_source ELSE EXCLUDED.decimals_source END,
+                    logo_uri = COALESCE(EXCLUDED.logo_uri, logo_uri),
+                    curated = 1,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(&token.address)
+            .bind(&token.name)
+            .bind(&token.symbol)
+            .bind(token.decimals)
+            .bind(&token.token_type)
+            .bind(token.first_seen_block)
+            .bind(token.last_seen_block)
+            .bind(token.total_transfers)
+            .bind(&token.name_source)
+            .bind(&token.symbol_source)
+            .bind(&token.decimals_source)
+            .bind(&token.logo_uri)
+            .execute(&mut *tx)
+            .await
+            .context(format!("Failed to import token {}", token.address))?;
+        }
+
+        // Tokens overridden by hand before they were imported
+        sqlx::query(
+            r#"
+            UPDATE tokens SET
+                name = COALESCE(o.name, tokens.name),
+                symbol = COALESCE(o.symbol, tokens.symbol),
+                decimals = COALESCE(o.decimals, tokens.decimals),
+                name_source = CASE WHEN o.name IS NULL THEN name_source ELSE 'manual' END,
+                symbol_source = CASE WHEN o.symbol IS NULL THEN symbol_source ELSE 'manual' END,
+                decimals_source = CASE WHEN o.decimals IS NULL THEN decimals_source ELSE 'manual' END
+            FROM token_metadata_overrides o
+            WHERE o.address = tokens.address AND tokens.curated
+            "#,
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to apply token metadata overrides")?;
+
+        tx.commit().await.context("Failed to commit transaction")?;
+
+        Ok(tokens.len() as u64)
+    }
+
     /// Get token by address
     pub async fn get_token_by_address(&self, address: &str) -> Result<Option<Token>> {
         let token = sqlx::query_as::<_, Token>(
-            "SELECT address, name, symbol, decimals, token_type, first_seen_block, last_seen_block, total_transfers, created_at, updated_at FROM tokens WHERE address = ?"
+            "SELECT address, name, symbol, decimals, token_type, first_seen_block, last_seen_block, total_transfers, created_at, updated_at, name_source, symbol_source, decimals_source, logo_uri, curated, total_supply, total_supply_block, holder_count, recent_transfers, token_type_confidence FROM tokens WHERE address = ?"
         )
         .bind(address)
         .fetch_optional(&self.pool)
@@ -447,11 +387,58 @@ impl DatabaseService {
         Ok(token)
     }
 
-    /// Get all tokens with pagination
-    pub async fn get_tokens(&self, offset: i64, limit: i64) -> Result<Vec<Token>> {
-        let tokens = sqlx::query_as::<_, Token>(
-            "SELECT address, name, symbol, decimals, token_type, first_seen_block, last_seen_block, total_transfers, created_at, updated_at FROM tokens ORDER BY total_transfers DESC LIMIT ? OFFSET ?"
+    /// Whether an NFT of a contract was transferred in an indexed block, `token_id` in
+    /// decimal
+    pub async fn nft_exists(&self, address: &str, token_id: &str) -> Result<bool> {
+        let exists: Option<i64> = sqlx::query_scalar(
+            "SELECT 1 FROM token_transfers WHERE token_address = ? AND token_id = ? LIMIT 1",
+        )
+        .bind(address)
+        .bind(token_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to look up NFT")?;
+
+        Ok(exists.is_some())
+    }
+
+    /// Get the token with a symbol, ignoring case. Curated tokens win over discovered ones
+    /// sharing the symbol, then the most transferred.
+    pub async fn get_token_by_symbol(&self, symbol: &str) -> Result<Option<Token>> {
+        let token = sqlx::query_as::<_, Token>(
+            r#"
+            SELECT address, name, symbol, decimals, token_type, first_seen_block, last_seen_block,
+                   total_transfers, created_at, updated_at, name_source, symbol_source,
+                   decimals_source, logo_uri, curated, total_supply, total_supply_block,
+                   holder_count, recent_transfers, token_type_confidence
+            FROM tokens
+            WHERE symbol = ? COLLATE NOCASE
+            ORDER BY curated DESC, total_transfers DESC
+            LIMIT 1
+            "#,
         )
+        .bind(symbol.trim())
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to get token by symbol")?;
+
+        Ok(token)
+    }
+
+    /// Get all tokens with pagination, ordered by `sort`: total transfers, holder count or
+    /// transfers in the activity window, most first
+    pub async fn get_tokens(&self, offset: i64, limit: i64, sort: TokenSort) -> Result<Vec<Token>> {
+        let order_by = match sort {
+            TokenSort::Transfers => "total_transfers DESC",
+            TokenSort::Holders => "holder_count IS NULL, holder_count DESC, total_transfers DESC",
+            TokenSort::Activity => {
+                "recent_transfers IS NULL, recent_transfers DESC, total_transfers DESC"
+            }
+        };
+        let tokens = sqlx::query_as::<_, Token>(&format!(
+            "SELECT address, name, symbol, decimals, token_type, first_seen_block, last_seen_block, total_transfers, created_at, updated_at, name_source, symbol_source, decimals_source, logo_uri, curated, total_supply, total_supply_block, holder_count, recent_transfers, token_type_confidence FROM tokens ORDER BY {} LIMIT ? OFFSET ?",
+            order_by
+        ))
         .bind(limit)
         .bind(offset)
         .fetch_all(&self.pool)
@@ -461,6 +448,280 @@ impl DatabaseService {
         Ok(tokens)
     }
 
+    /// Get the addresses of the most recently seen tokens, newest first
+    pub async fn get_recently_seen_token_addresses(&self, limit: i64) -> Result<Vec<String>> {
+        let addresses = sqlx::query_scalar::<_, String>(
+            "SELECT address FROM tokens ORDER BY last_seen_block DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get recently seen tokens")?;
+
+        Ok(addresses)
+    }
+
+    /// Recompute the holder count of every token from the stored balances, and its
+    /// transfers after `since_block`. Only tokens whose numbers changed are written.
+    /// Returns the number of tokens updated.
+    pub async fn update_token_activity_stats(&self, since_block: i64) -> Result<u64> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        let holders = sqlx::query(
+            r#"
+            UPDATE tokens
+            SET holder_count = stats.holders
+            FROM (
+                SELECT t.address, COUNT(b.token_address) AS holders
+                FROM tokens t
+                LEFT JOIN token_balances b ON b.token_address = t.address AND b.balance != '0'
+                GROUP BY t.address
+            ) AS stats
+            WHERE tokens.address = stats.address
+              AND tokens.holder_count IS NOT stats.holders
+            "#,
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update token holder counts")?;
+
+        // Transfers of the window are grouped through the block index, tokens without any
+        // drop to 0
+        let activity = sqlx::query(
+            r#"
+            UPDATE tokens
+            SET recent_transfers = stats.transfers
+            FROM (
+                SELECT t.address, COALESCE(recent.transfers, 0) AS transfers
+                FROM tokens t
+                LEFT JOIN (
+                    SELECT token_address, COUNT(*) AS transfers
+                    FROM token_transfers
+                    WHERE block_number > ?
+                    GROUP BY token_address
+                ) AS recent ON recent.token_address = t.address
+            ) AS stats
+            WHERE tokens.address = stats.address
+              AND tokens.recent_transfers IS NOT stats.transfers
+            "#,
+        )
+        .bind(since_block)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update token recent transfers")?;
+
+        tx.commit().await.context("Failed to commit transaction")?;
+
+        Ok(holders.rows_affected() + activity.rows_affected())
+    }
+
+    /// Get the addresses of up to `limit` tokens whose total supply was read longest ago,
+    /// tokens never read first, then the most recently seen
+    pub async fn get_tokens_for_supply_refresh(&self, limit: i64) -> Result<Vec<String>> {
+        let addresses = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT address FROM tokens
+            ORDER BY total_supply_block IS NOT NULL, total_supply_block, last_seen_block DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get tokens for supply refresh")?;
+
+        Ok(addresses)
+    }
+
+    /// Store total supplies read at `block_number`. A None supply (totalSupply reverted)
+    /// keeps the previous value but still marks the token as read.
+    pub async fn update_token_total_supplies(
+        &self,
+        supplies: &[(String, Option<String>)],
+        block_number: i64,
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+
+        for (address, supply) in supplies {
+            sqlx::query(
+                "UPDATE tokens SET total_supply = COALESCE(?, total_supply), total_supply_block = ? WHERE address = ?",
+            )
+            .bind(supply)
+            .bind(block_number)
+            .bind(address)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update token total supply")?;
+        }
+
+        tx.commit().await.context("Failed to commit transaction")?;
+
+        Ok(())
+    }
+
+    /// Transfers with an id above `after_id` stored as ERC-20 although their Transfer log
+    /// carries a token id topic (ERC-721 transfers misclassified by older versions), as
+    /// (transfer id, transaction hash) in id order
+    pub async fn get_transactions_with_misclassified_nft_transfers(
+        &self,
+        transfer_topic: &str,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<(i64, String)>> {
+        let hashes = sqlx::query_as(
+            r#"
+            SELECT DISTINCT tt.id, tt.transaction_hash
+            FROM token_transfers tt
+            JOIN logs l ON l.transaction_hash = tt.transaction_hash AND l.address = tt.token_address
+            WHERE tt.token_type = 'ERC20' AND l.topic0 = ? AND l.topic3 IS NOT NULL
+              AND substr(l.topic1, -40) = substr(tt.from_address, 3)
+              AND substr(l.topic2, -40) = substr(tt.to_address, 3)
+              AND tt.id > ?
+            ORDER BY tt.id
+            LIMIT ?
+            "#,
+        )
+        .bind(transfer_topic)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find misclassified token transfers")?;
+
+        Ok(hashes)
+    }
+
+    /// Mark a token transfer as an ERC-721 transfer of a single token
+    pub async fn reclassify_token_transfer_as_nft(&self, id: i64, token_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE token_transfers SET token_type = 'ERC721', token_id = ?, amount = '1' WHERE id = ?",
+        )
+        .bind(token_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .context("Failed to reclassify token transfer")?;
+
+        Ok(())
+    }
+
+    /// Set the token standard of a token
+    pub async fn set_token_type(&self, address: &str, token_type: &str) -> Result<()> {
+        sqlx::query("UPDATE tokens SET token_type = ? WHERE address = ?")
+            .bind(token_type)
+            .bind(address)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update token type")?;
+
+        Ok(())
+    }
+
+    /// Manual metadata overrides of the given tokens, by address
+    pub async fn get_token_metadata_overrides(
+        &self,
+        addresses: &[String],
+    ) -> Result<std::collections::HashMap<String, TokenMetadataOverride>> {
+        if addresses.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "SELECT address, name, symbol, decimals FROM token_metadata_overrides WHERE address IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for address in addresses {
+            separated.push_bind(address);
+        }
+        separated.push_unseparated(")");
+
+        let rows = query_builder
+            .build_query_as::<(String, Option<String>, Option<String>, Option<u8>)>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to get token metadata overrides")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(address, name, symbol, decimals)| {
+                (
+                    address,
+                    TokenMetadataOverride {
+                        name,
+                        symbol,
+                        decimals,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Store the manual metadata of a token and apply it to the token if already known.
+    /// Fields left None are not overridden.
+    pub async fn set_token_metadata_override(
+        &self,
+        address: &str,
+        metadata: &TokenMetadataOverride,
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO token_metadata_overrides (address, name, symbol, decimals)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(address) DO UPDATE SET
+                name = EXCLUDED.name,
+                symbol = EXCLUDED.symbol,
+                decimals = EXCLUDED.decimals,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(address)
+        .bind(&metadata.name)
+        .bind(&metadata.symbol)
+        .bind(metadata.decimals)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to store token metadata override")?;
+
+        sqlx::query(
+            r#"
+            UPDATE tokens SET
+                name = COALESCE(?1, name),
+                symbol = COALESCE(?2, symbol),
+                decimals = COALESCE(?3, decimals),
+                name_source = CASE WHEN ?1 IS NULL THEN name_source ELSE 'manual' END,
+                symbol_source = CASE WHEN ?2 IS NULL THEN symbol_source ELSE 'manual' END,
+                decimals_source = CASE WHEN ?3 IS NULL THEN decimals_source ELSE 'manual' END,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE address = ?4
+            "#,
+        )
+        .bind(&metadata.name)
+        .bind(&metadata.symbol)
+        .bind(metadata.decimals)
+        .bind(address)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to apply token metadata override")?;
+
+        tx.commit().await.context("Failed to commit transaction")?;
+
+        Ok(())
+    }
+
     // ============================================================================
     // TOKEN BALANCE MANAGEMENT
     // ============================================================================
@@ -498,8 +759,49 @@ impl DatabaseService {
         }
     }
 
-    /// Get token balance for specific account and token
-    pub async fn get_token_balance(
+    /// Upsert many token balances in one transaction. A stored balance read at a later
+    /// block than the new one is kept, as blocks are processed out of order.
+    pub async fn upsert_token_balances_batch(&self, balances: &[TokenBalance]) -> Result<()> {
+        if balances.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        // 5 bound values per row, stay well below SQLite's variable limit
+        for chunk in balances.chunks(1000) {
+            let mut query_builder = sqlx::QueryBuilder::new(
+                "INSERT INTO token_balances (account_address, token_address, balance, block_number, last_updated_block) ",
+            );
+            query_builder.push_values(chunk, |mut b, balance| {
+                b.push_bind(&balance.account_address)
+                    .push_bind(&balance.token_address)
+                    .push_bind(&balance.balance)
+                    .push_bind(balance.block_number)
+                    .push_bind(balance.last_updated_block);
+            });
+            query_builder.push(
+                r#"
+                ON CONFLICT(account_address, token_address) DO UPDATE SET
+                    balance = excluded.balance,
+                    last_updated_block = excluded.last_updated_block,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE excluded.last_updated_block >= token_balances.last_updated_block
+                "#,
+            );
+
+            query_builder
+                .build()
+                .execute(&mut *tx)
+                .await
+                .context("Failed to upsert token balances")?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Get token balance for specific account and token
+    pub async fn get_token_balance(
         &self,
         account_address: &str,
         token_address: &str,
@@ -532,6 +834,23 @@ impl DatabaseService {
         Ok(balances)
     }
 
+    /// Get token with balance information for an account
+    pub async fn get_account_token_info(
+        &self,
+        account_address: &str,
+    ) -> Result<Vec<(Token, TokenBalance)>> {
+        let balances = self.get_account_token_balances(account_address).await?;
+        let mut result = Vec::new();
+
+        for balance in balances {
+            if let Some(token) = self.get_token_by_address(&balance.token_address).await? {
+                result.push((token, balance));
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Get all accounts holding a specific token
     pub async fn get_token_holders(
         &self,
@@ -540,7 +859,7 @@ impl DatabaseService {
         limit: i64,
     ) -> Result<Vec<TokenBalance>> {
         let holders = sqlx::query_as::<_, TokenBalance>(
-            "SELECT id, account_address, token_address, balance, block_number, last_updated_block, created_at, updated_at FROM token_balances WHERE token_address = ? AND balance != '0' ORDER BY CAST(balance AS REAL) DESC LIMIT ? OFFSET ?"
+            "SELECT id, account_address, token_address, balance, block_number, last_updated_block, created_at, updated_at FROM token_balances WHERE token_address = ? AND balance != '0' ORDER BY balance_sort DESC LIMIT ? OFFSET ?"
         )
         .bind(token_address)
         .bind(limit)
@@ -552,6 +871,97 @@ impl DatabaseService {
         Ok(holders)
     }
 
+    /// Get the delegates of an ERC-20 Votes token with voting power, largest first, with
+    /// the number of accounts delegating to each, and how many there are
+    pub async fn get_token_delegates(
+        &self,
+        token_address: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<TokenDelegate>, i64)> {
+        let delegates = sqlx::query_as::<_, TokenDelegate>(
+            r#"
+            SELECT account_address AS address, votes, votes_block, delegate,
+                   (SELECT COUNT(*) FROM delegations AS delegators
+                    WHERE delegators.token_address = delegations.token_address
+                      AND delegators.delegate = delegations.account_address) AS delegators
+            FROM delegations
+            WHERE token_address = ? AND votes != '0'
+            ORDER BY votes_sort DESC, account_address
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(token_address)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get token delegates")?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM delegations WHERE token_address = ? AND votes != '0'",
+        )
+        .bind(token_address)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count token delegates")?;
+
+        Ok((delegates, total))
+    }
+
+    /// Get the approvals granted by an account, newest first, and how many there are.
+    /// Approvals set back to zero are left out unless `include_revoked`.
+    pub async fn get_account_approvals(
+        &self,
+        owner_address: &str,
+        token_address: Option<&str>,
+        include_revoked: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<TokenApproval>, i64)> {
+        let filter = r#"
+            WHERE approvals.owner_address = ?
+              AND (? IS NULL OR approvals.token_address = ?)
+              AND (? OR approvals.value != '0')
+        "#;
+
+        let approvals = sqlx::query_as::<_, TokenApproval>(&format!(
+            r#"
+            SELECT approvals.token_address, tokens.name AS token_name,
+                   tokens.symbol AS token_symbol, tokens.decimals AS token_decimals,
+                   tokens.token_type, approvals.spender_address, approvals.kind,
+                   approvals.value, approvals.value = '{}' AS unlimited,
+                   approvals.transaction_hash, approvals.block_number
+            FROM approvals
+            LEFT JOIN tokens ON tokens.address = approvals.token_address
+            {}
+            ORDER BY approvals.block_number DESC, approvals.log_index DESC
+            LIMIT ? OFFSET ?
+            "#,
+            UNLIMITED_ALLOWANCE, filter
+        ))
+        .bind(owner_address)
+        .bind(token_address)
+        .bind(token_address)
+        .bind(include_revoked)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get account approvals")?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM approvals {}", filter))
+            .bind(owner_address)
+            .bind(token_address)
+            .bind(token_address)
+            .bind(include_revoked)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count account approvals")?;
+
+        Ok((approvals, total))
+    }
+
     /// Get token balances that need updating (older than specified block)
     pub async fn get_stale_token_balances(
         &self,
@@ -570,175 +980,3807 @@ impl DatabaseService {
         Ok(balances)
     }
 
-    /// Get the latest block number
-    pub async fn get_latest_block_number(&self) -> Result<Option<i64>> {
-        let result: (Option<i64>,) = sqlx::query_as("SELECT MAX(number) FROM blocks")
-            .fetch_one(&self.pool)
-            .await
-            .context("Failed to query latest block number")?;
+    /// Count token balances last updated before `min_block`
+    pub async fn count_stale_token_balances(&self, min_block: i64) -> Result<i64> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM token_balances WHERE last_updated_block < ?")
+                .bind(min_block)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to count stale token balances")?;
 
-        Ok(result.0)
+        Ok(count)
     }
 
-    /// Get block by number
-    pub async fn get_block_by_number(&self, number: i64) -> Result<Option<Block>> {
-        let result = sqlx::query_as::<_, Block>(
+    // ============================================================================
+    // CONTRACT VERIFICATION
+    // ============================================================================
+
+    /// Insert or replace a verified contract
+    pub async fn upsert_verified_contract(&self, contract: &VerifiedContract) -> Result<()> {
+        sqlx::query(
             r#"
-            SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
-                   miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
-                   nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
-                   slot, proposer_index, epoch, slot_root, parent_root, beacon_deposit_count,
-                   graffiti, randao_reveal, randao_mix
-            FROM blocks
-            WHERE number = ?
+            INSERT INTO verified_contracts (
+                address, contract_name, compiler_version, optimizer_enabled, optimizer_runs,
+                evm_version, source_code, abi, match_type
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(address) DO UPDATE SET
+                contract_name = excluded.contract_name,
+                compiler_version = excluded.compiler_version,
+                optimizer_enabled = excluded.optimizer_enabled,
+                optimizer_runs = excluded.optimizer_runs,
+                evm_version = excluded.evm_version,
+                source_code = excluded.source_code,
+                abi = excluded.abi,
+                match_type = excluded.match_type,
+                verified_at = CURRENT_TIMESTAMP
             "#,
         )
-        .bind(number)
+        .bind(&contract.address)
+        .bind(&contract.contract_name)
+        .bind(&contract.compiler_version)
+        .bind(contract.optimizer_enabled)
+        .bind(contract.optimizer_runs)
+        .bind(&contract.evm_version)
+        .bind(&contract.source_code)
+        .bind(&contract.abi)
+        .bind(&contract.match_type)
+        .execute(&self.pool)
+        .await
+        .context("Failed to upsert verified contract")?;
+
+        Ok(())
+    }
+
+    /// Get a verified contract by address
+    pub async fn get_verified_contract(&self, address: &str) -> Result<Option<VerifiedContract>> {
+        let contract = sqlx::query_as::<_, VerifiedContract>(
+            "SELECT * FROM verified_contracts WHERE address = ?",
+        )
+        .bind(address)
         .fetch_optional(&self.pool)
         .await
-        .context("Failed to query block by number")?;
+        .context("Failed to get verified contract")?;
 
-        Ok(result)
+        Ok(contract)
     }
 
-    /// Get block by hash
-    pub async fn get_block_by_hash(&self, hash: &str) -> Result<Option<Block>> {
-        let result = sqlx::query_as::<_, Block>(
+    /// Store the functions of a verified ABI, skipping known signatures
+    pub async fn insert_function_signatures(&self, signatures: &[FunctionSignature]) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+
+        for signature in signatures {
+            sqlx::query(
+                "INSERT OR IGNORE INTO function_signatures (selector, signature, abi) VALUES (?, ?, ?)",
+            )
+            .bind(&signature.selector)
+            .bind(&signature.signature)
+            .bind(&signature.abi)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert function signature")?;
+        }
+
+        tx.commit().await.context("Failed to commit transaction")?;
+
+        Ok(())
+    }
+
+    /// Functions matching a 4-byte selector
+    pub async fn get_function_signatures(&self, selector: &str) -> Result<Vec<FunctionSignature>> {
+        let signatures = sqlx::query_as::<_, FunctionSignature>(
+            "SELECT selector, signature, abi FROM function_signatures WHERE selector = ? ORDER BY created_at",
+        )
+        .bind(selector)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get function signatures")?;
+
+        Ok(signatures)
+    }
+
+    /// Most recent failed transactions sent to a contract whose revert reason is not decoded
+    pub async fn get_failed_transactions_without_reason(
+        &self,
+        to_address: &str,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        let hashes = sqlx::query_scalar(
             r#"
-            SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
-                   miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
-                   nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
-                   slot, proposer_index, epoch, slot_root, parent_root, beacon_deposit_count,
-                   graffiti, randao_reveal, randao_mix
-            FROM blocks
-            WHERE hash = ?
+            SELECT hash FROM transactions
+            WHERE to_address = ? AND status = 0 AND revert_reason IS NULL
+            ORDER BY block_number DESC, transaction_index DESC
+            LIMIT ?
             "#,
         )
-        .bind(hash)
-        .fetch_optional(&self.pool)
+        .bind(to_address)
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await
-        .context("Failed to query block by hash")?;
+        .context("Failed to get failed transactions")?;
 
-        Ok(result)
+        Ok(hashes)
     }
 
-    /// Get transactions by block number
-    pub async fn get_transactions_by_block(&self, block_number: i64) -> Result<Vec<Transaction>> {
-        let result = sqlx::query_as::<_, Transaction>(
+    /// Store the decoded revert reason of a failed transaction
+    pub async fn set_revert_reason(&self, tx_hash: &str, reason: &str) -> Result<()> {
+        sqlx::query("UPDATE transactions SET revert_reason = ? WHERE hash = ?")
+            .bind(reason)
+            .bind(tx_hash)
+            .execute(&self.pool)
+            .await
+            .context("Failed to store revert reason")?;
+
+        Ok(())
+    }
+
+    /// Daily transaction and failure counts of a contract since `since_timestamp`, newest first
+    pub async fn get_contract_failure_days(
+        &self,
+        to_address: &str,
+        since_timestamp: i64,
+    ) -> Result<Vec<ContractFailureDay>> {
+        let days = sqlx::query_as::<_, ContractFailureDay>(
             r#"
-            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index
-            FROM transactions
-            WHERE block_number = ?
-            ORDER BY transaction_index
+            SELECT date(b.timestamp, 'unixepoch') AS day,
+                   COUNT(*) AS total_transactions,
+                   SUM(CASE WHEN t.status = 0 THEN 1 ELSE 0 END) AS failed_transactions
+            FROM transactions t
+            JOIN blocks b ON b.number = t.block_number
+            WHERE t.to_address = ? AND b.timestamp >= ?
+            GROUP BY day
+            ORDER BY day DESC
             "#,
         )
-        .bind(block_number)
+        .bind(to_address)
+        .bind(since_timestamp)
         .fetch_all(&self.pool)
         .await
-        .context("Failed to query transactions by block")?;
+        .context("Failed to get contract failure rollup")?;
 
-        Ok(result)
+        Ok(days)
     }
 
-    /// Get transaction by hash
-    pub async fn get_transaction_by_hash(&self, hash: &str) -> Result<Option<Transaction>> {
-        let result = sqlx::query_as::<_, Transaction>(
+    /// Failed transaction counts of a contract per day and revert reason (None = not decoded)
+    pub async fn get_contract_failure_reasons(
+        &self,
+        to_address: &str,
+        since_timestamp: i64,
+    ) -> Result<Vec<(String, Option<String>, i64)>> {
+        let reasons = sqlx::query_as::<_, (String, Option<String>, i64)>(
             r#"
-            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index
-            FROM transactions
-            WHERE hash = ?
+            SELECT date(b.timestamp, 'unixepoch') AS day, t.revert_reason, COUNT(*) AS failures
+            FROM transactions t
+            JOIN blocks b ON b.number = t.block_number
+            WHERE t.to_address = ? AND t.status = 0 AND b.timestamp >= ?
+            GROUP BY day, t.revert_reason
+            ORDER BY day DESC, failures DESC
             "#,
         )
-        .bind(hash)
+        .bind(to_address)
+        .bind(since_timestamp)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get contract failure reasons")?;
+
+        Ok(reasons)
+    }
+
+    // ============================================================================
+    // CONTRACT CODE
+    // ============================================================================
+
+    /// Get the receipt of a transaction included in block `block_number`
+    pub async fn get_receipt(&self, hash: &str, block_number: i64) -> Result<Option<Receipt>> {
+        for pool in self.pools_for_block(block_number) {
+            let receipt = sqlx::query_as::<_, Receipt>(
+                r#"
+                SELECT transaction_hash, block_number, cumulative_gas_used, effective_gas_price,
+                       contract_address, logs_bloom, transaction_type
+                FROM transaction_receipts
+                WHERE transaction_hash = ?
+                "#,
+            )
+            .bind(hash)
+            .fetch_optional(&pool)
+            .await
+            .context("Failed to query receipt")?;
+
+            if receipt.is_some() {
+                return Ok(receipt);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Get the bytecode stored for a code hash
+    pub async fn get_contract_code(&self, code_hash: &str) -> Result<Option<ContractCode>> {
+        let code =
+            sqlx::query_as::<_, ContractCode>("SELECT * FROM contract_code WHERE code_hash = ?")
+                .bind(code_hash)
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to get contract code")?;
+
+        Ok(code)
+    }
+
+    /// Get the deployment record of a contract
+    pub async fn get_contract_deployment(
+        &self,
+        address: &str,
+    ) -> Result<Option<ContractDeployment>> {
+        let deployment = sqlx::query_as::<_, ContractDeployment>(
+            "SELECT * FROM contract_deployments WHERE address = ?",
+        )
+        .bind(address)
         .fetch_optional(&self.pool)
         .await
-        .context("Failed to query transaction by hash")?;
+        .context("Failed to get contract deployment")?;
 
-        Ok(result)
+        Ok(deployment)
     }
 
-    /// Get logs by transaction hash
-    pub async fn get_logs_by_transaction(&self, tx_hash: &str) -> Result<Vec<Log>> {
-        let result = sqlx::query_as::<_, Log>(
+    /// Get all deployments of a bytecode in deployment order, with their total count
+    pub async fn get_deployments_by_code_hash(
+        &self,
+        code_hash: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<ContractDeployment>, i64)> {
+        let deployments = sqlx::query_as::<_, ContractDeployment>(
             r#"
-            SELECT id, transaction_hash, block_number, address, topic0, topic1, topic2, topic3, data, log_index
-            FROM logs
-            WHERE transaction_hash = ?
-            ORDER BY log_index
+            SELECT * FROM contract_deployments
+            WHERE code_hash = ?
+            ORDER BY block_number ASC, address ASC
+            LIMIT ? OFFSET ?
             "#,
         )
-        .bind(tx_hash)
+        .bind(code_hash)
+        .bind(limit)
+        .bind(offset)
         .fetch_all(&self.pool)
         .await
-        .context("Failed to query logs by transaction")?;
+        .context("Failed to get deployments by code hash")?;
 
-        Ok(result)
+        let total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM contract_deployments WHERE code_hash = ?")
+                .bind(code_hash)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to count deployments by code hash")?;
+
+        Ok((deployments, total))
     }
 
-    /// Get account by address
-    pub async fn get_account_by_address(&self, address: &str) -> Result<Option<Account>> {
-        let result = sqlx::query_as::<_, Account>(
+    /// Get the contracts deployed by `address` and, down to `max_depth` creation levels,
+    /// by those contracts, with their total count. Contracts deployed by transactions are
+    /// children of the sender, contracts deployed by contracts of their factory.
+    pub async fn get_contract_descendants(
+        &self,
+        address: &str,
+        max_depth: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<ContractLineage>, i64)> {
+        const DESCENDANTS: &str = r#"
+            WITH RECURSIVE descendants(address, parent_address, depth) AS (
+                SELECT address, COALESCE(factory_address, creator_address), 1
+                FROM contract_deployments
+                WHERE factory_address = ?1
+                   OR (factory_address IS NULL AND creator_address = ?1)
+                UNION ALL
+                SELECT d.address, d.factory_address, descendants.depth + 1
+                FROM contract_deployments d
+                JOIN descendants ON d.factory_address = descendants.address
+                WHERE descendants.depth < ?2
+            )
+        "#;
+
+        let descendants = sqlx::query_as::<_, ContractLineage>(&format!(
             r#"
-            SELECT address, balance, transaction_count, first_seen_block, last_seen_block
-            FROM accounts
-            WHERE address = ?
+            {DESCENDANTS}
+            SELECT d.address, descendants.parent_address, d.code_hash, d.creator_address,
+                   d.factory_address, d.transaction_hash, d.block_number, descendants.depth
+            FROM descendants
+            JOIN contract_deployments d ON d.address = descendants.address
+            ORDER BY descendants.depth ASC, d.block_number ASC, d.address ASC
+            LIMIT ?3 OFFSET ?4
+            "#
+        ))
+        .bind(address)
+        .bind(max_depth)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get contract descendants")?;
+
+        let total: i64 =
+            sqlx::query_scalar(&format!("{DESCENDANTS} SELECT COUNT(*) FROM descendants"))
+                .bind(address)
+                .bind(max_depth)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to count contract descendants")?;
+
+        Ok((descendants, total))
+    }
+
+    /// Get the deployment of a contract followed by those of the factories above it, up
+    /// to the contract a transaction deployed. Empty when the contract isn't indexed.
+    pub async fn get_contract_ancestry(&self, address: &str) -> Result<Vec<ContractLineage>> {
+        let ancestry = sqlx::query_as::<_, ContractLineage>(
+            r#"
+            WITH RECURSIVE ancestry(address, depth) AS (
+                SELECT ?, 0
+                UNION ALL
+                SELECT d.factory_address, ancestry.depth + 1
+                FROM ancestry
+                JOIN contract_deployments d ON d.address = ancestry.address
+                WHERE d.factory_address IS NOT NULL AND ancestry.depth < 64
+            )
+            SELECT d.address, COALESCE(d.factory_address, d.creator_address) AS parent_address,
+                   d.code_hash, d.creator_address, d.factory_address, d.transaction_hash,
+                   d.block_number, ancestry.depth
+            FROM ancestry
+            JOIN contract_deployments d ON d.address = ancestry.address
+            ORDER BY ancestry.depth ASC
             "#,
         )
         .bind(address)
-        .fetch_optional(&self.pool)
+        .fetch_all(&self.pool)
         .await
-        .context("Failed to query account by address")?;
+        .context("Failed to get contract ancestry")?;
 
-        Ok(result)
+        Ok(ancestry)
     }
 
-    /// Get recent blocks with pagination
-    pub async fn get_recent_blocks(&self, limit: i64, offset: i64) -> Result<Vec<Block>> {
-        let result = sqlx::query_as::<_, Block>(
+    // ============================================================================
+    // MEMPOOL
+    // ============================================================================
+
+    /// Insert pending transactions, replacing earlier ones with the same sender and nonce
+    pub async fn upsert_pending_transactions_batch(
+        &self,
+        transactions: &[PendingTransaction],
+    ) -> Result<()> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for pending in transactions {
+            sqlx::query(
+                r#"
+                INSERT INTO pending_transactions (
+                    hash, from_address, to_address, nonce, value, gas_limit, gas_price,
+                    max_priority_fee_per_gas
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(from_address, nonce) DO UPDATE SET
+                    hash = excluded.hash,
+                    to_address = excluded.to_address,
+                    value = excluded.value,
+                    gas_limit = excluded.gas_limit,
+                    gas_price = excluded.gas_price,
+                    max_priority_fee_per_gas = excluded.max_priority_fee_per_gas,
+                    first_seen_at = CURRENT_TIMESTAMP
+                WHERE hash != excluded.hash
+                "#,
+            )
+            .bind(&pending.hash)
+            .bind(&pending.from_address)
+            .bind(&pending.to_address)
+            .bind(pending.nonce)
+            .bind(&pending.value)
+            .bind(pending.gas_limit)
+            .bind(&pending.gas_price)
+            .bind(&pending.max_priority_fee_per_gas)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert pending transaction")?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remove pending transactions that were mined
+    pub async fn delete_pending_transactions(&self, hashes: &[String]) -> Result<u64> {
+        if hashes.is_empty() {
+            return Ok(0);
+        }
+
+        let mut query_builder =
+            sqlx::QueryBuilder::new("DELETE FROM pending_transactions WHERE hash IN (");
+        let mut separated = query_builder.separated(", ");
+        for hash in hashes {
+            separated.push_bind(hash);
+        }
+        separated.push_unseparated(")");
+
+        let result = query_builder.build().execute(&self.pool).await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Remove pending transactions first seen more than `max_age_seconds` ago
+    pub async fn prune_pending_transactions(&self, max_age_seconds: u64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM pending_transactions WHERE first_seen_at < datetime('now', '-' || ? || ' seconds')",
+        )
+        .bind(max_age_seconds as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to prune pending transactions")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get the most recently seen pending transactions with the total pending count
+    pub async fn get_pending_transactions(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<PendingTransaction>, i64)> {
+        let transactions = sqlx::query_as::<_, PendingTransaction>(
+            "SELECT * FROM pending_transactions ORDER BY first_seen_at DESC, hash LIMIT ? OFFSET ?",
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get pending transactions")?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pending_transactions")
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count pending transactions")?;
+
+        Ok((transactions, total))
+    }
+
+    /// Get gas prices and priority fees of all pending transactions
+    pub async fn get_pending_gas_prices(&self) -> Result<Vec<(String, Option<String>)>> {
+        let prices = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT gas_price, max_priority_fee_per_gas FROM pending_transactions",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get pending gas prices")?;
+
+        Ok(prices)
+    }
+
+    // ============================================================================
+    // WATCHLIST
+    // ============================================================================
+
+    /// Get the addresses of the tracked_addresses watchlist
+    pub async fn get_tracked_addresses(&self) -> Result<Vec<String>> {
+        let addresses = sqlx::query_scalar("SELECT address FROM tracked_addresses")
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to get tracked addresses")?;
+
+        Ok(addresses)
+    }
+
+    // ============================================================================
+    // VALIDATORS
+    // ============================================================================
+
+    /// Insert or update a batch of validators in a single transaction
+    pub async fn upsert_validators_batch(&self, validators: &[Validator]) -> Result<()> {
+        if validators.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for validator in validators {
+            sqlx::query(
+                r#"
+                INSERT INTO validators (
+                    validator_index, pubkey, withdrawal_credentials, balance_gwei,
+                    effective_balance_gwei, status, slashed, activation_eligibility_epoch,
+                    activation_epoch, exit_epoch, withdrawable_epoch, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+                ON CONFLICT(validator_index) DO UPDATE SET
+                    withdrawal_credentials = excluded.withdrawal_credentials,
+                    balance_gwei = excluded.balance_gwei,
+                    effective_balance_gwei = excluded.effective_balance_gwei,
+                    status = excluded.status,
+                    slashed = excluded.slashed,
+                    activation_eligibility_epoch = excluded.activation_eligibility_epoch,
+                    activation_epoch = excluded.activation_epoch,
+                    exit_epoch = excluded.exit_epoch,
+                    withdrawable_epoch = excluded.withdrawable_epoch,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(validator.validator_index)
+            .bind(&validator.pubkey)
+            .bind(&validator.withdrawal_credentials)
+            .bind(validator.balance_gwei)
+            .bind(validator.effective_balance_gwei)
+            .bind(&validator.status)
+            .bind(validator.slashed)
+            .bind(validator.activation_eligibility_epoch)
+            .bind(validator.activation_epoch)
+            .bind(validator.exit_epoch)
+            .bind(validator.withdrawable_epoch)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to upsert validator")?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Get validators by index, optionally restricted to a status or status prefix
+    /// ("active" matches "active_ongoing", "active_exiting" and "active_slashed")
+    pub async fn get_validators(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Validator>> {
+        let validators = sqlx::query_as::<_, Validator>(
+            r#"
+            SELECT * FROM validators
+            WHERE ?1 IS NULL OR status = ?1 OR status LIKE ?1 || '\_%' ESCAPE '\'
+            ORDER BY validator_index
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query validators")?;
+
+        Ok(validators)
+    }
+
+    /// Get the number of validators, optionally restricted like `get_validators`
+    pub async fn get_validator_count(&self, status: Option<&str>) -> Result<i64> {
+        let count = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM validators
+            WHERE ?1 IS NULL OR status = ?1 OR status LIKE ?1 || '\_%' ESCAPE '\'
+            "#,
+        )
+        .bind(status)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count validators")?;
+
+        Ok(count)
+    }
+
+    /// Get a validator by index
+    pub async fn get_validator_by_index(&self, index: i64) -> Result<Option<Validator>> {
+        let validator =
+            sqlx::query_as::<_, Validator>("SELECT * FROM validators WHERE validator_index = ?")
+                .bind(index)
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to query validator")?;
+
+        Ok(validator)
+    }
+
+    /// Get a validator by BLS public key
+    pub async fn get_validator_by_pubkey(&self, pubkey: &str) -> Result<Option<Validator>> {
+        let validator = sqlx::query_as::<_, Validator>("SELECT * FROM validators WHERE pubkey = ?")
+            .bind(pubkey)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to query validator")?;
+
+        Ok(validator)
+    }
+
+    /// Whether the proposer duties of an epoch were stored
+    pub async fn has_proposer_duties(&self, epoch: i64) -> Result<bool> {
+        let stored: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM proposer_duties WHERE epoch = ? LIMIT 1")
+                .bind(epoch)
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to query proposer duties")?;
+
+        Ok(stored.is_some())
+    }
+
+    /// Store the proposer duties of an epoch, duties already stored are kept
+    pub async fn insert_proposer_duties(&self, duties: &[ProposerDuty]) -> Result<()> {
+        if duties.is_empty() {
+            return Ok(());
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "INSERT OR IGNORE INTO proposer_duties (slot, epoch, validator_index, pubkey) ",
+        );
+        query_builder.push_values(duties, |mut b, duty| {
+            b.push_bind(duty.slot)
+                .push_bind(duty.epoch)
+                .push_bind(duty.validator_index)
+                .push_bind(&duty.pubkey);
+        });
+        query_builder
+            .build()
+            .execute(&self.pool)
+            .await
+            .context("Failed to insert proposer duties")?;
+
+        Ok(())
+    }
+
+    /// Get the proposer duties of the slots from `from_slot` to `to_slot`, inclusive
+    pub async fn get_proposer_duties(
+        &self,
+        from_slot: i64,
+        to_slot: i64,
+    ) -> Result<Vec<ProposerDuty>> {
+        sqlx::query_as::<_, ProposerDuty>(
+            r#"
+            SELECT slot, epoch, validator_index, pubkey FROM proposer_duties
+            WHERE slot BETWEEN ? AND ?
+            ORDER BY slot
+            "#,
+        )
+        .bind(from_slot)
+        .bind(to_slot)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch proposer duties")
+    }
+
+    // ============================================================================
+    // API KEYS
+    // ============================================================================
+
+    /// Store a new API key, returning its id
+    pub async fn insert_api_key(&self, key: &ApiKey) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO api_keys (key_hash, name, requests_per_minute, daily_quota, enabled)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&key.key_hash)
+        .bind(&key.name)
+        .bind(key.requests_per_minute)
+        .bind(key.daily_quota)
+        .bind(key.enabled)
+        .execute(&self.pool)
+        .await
+        .context("Failed to insert API key")?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get an API key by the hash of the key
+    pub async fn get_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE key_hash = ?")
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to get API key")?;
+
+        Ok(key)
+    }
+
+    /// Record that an API key was used
+    pub async fn touch_api_key(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update API key usage")?;
+
+        Ok(())
+    }
+
+    // ============================================================================
+    // ENS CACHE
+    // ============================================================================
+
+    /// Get the cached forward resolution of an ENS name, if younger than `max_age_seconds`
+    pub async fn get_cached_ens_forward(
+        &self,
+        name: &str,
+        max_age_seconds: u64,
+    ) -> Result<Option<EnsName>> {
+        let entry = sqlx::query_as::<_, EnsName>(
+            r#"
+            SELECT name, address, is_reverse, resolved_at FROM ens_names
+            WHERE is_reverse = 0 AND name = ?
+              AND resolved_at > datetime('now', '-' || ? || ' seconds')
+            "#,
+        )
+        .bind(name)
+        .bind(max_age_seconds as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query ENS cache")?;
+
+        Ok(entry)
+    }
+
+    /// Get the cached reverse resolution of an address, if younger than `max_age_seconds`
+    pub async fn get_cached_ens_reverse(
+        &self,
+        address: &str,
+        max_age_seconds: u64,
+    ) -> Result<Option<EnsName>> {
+        let entry = sqlx::query_as::<_, EnsName>(
+            r#"
+            SELECT name, address, is_reverse, resolved_at FROM ens_names
+            WHERE is_reverse = 1 AND address = ?
+              AND resolved_at > datetime('now', '-' || ? || ' seconds')
+            "#,
+        )
+        .bind(address)
+        .bind(max_age_seconds as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query ENS cache")?;
+
+        Ok(entry)
+    }
+
+    /// Store an ENS resolution, replacing the previous one for the same name (forward)
+    /// or address (reverse)
+    pub async fn upsert_ens_name(&self, entry: &EnsName) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        if entry.is_reverse {
+            sqlx::query("DELETE FROM ens_names WHERE is_reverse = 1 AND address = ?")
+                .bind(&entry.address)
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            sqlx::query("DELETE FROM ens_names WHERE is_reverse = 0 AND name = ?")
+                .bind(&entry.name)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("INSERT INTO ens_names (name, address, is_reverse) VALUES (?, ?, ?)")
+            .bind(&entry.name)
+            .bind(&entry.address)
+            .bind(entry.is_reverse)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to cache ENS name")?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // ============================================================================
+    // BEACON SLOT MAPPINGS
+    // ============================================================================
+
+    /// Get the resolved (block_number, slot) pair closest to `block_number` from below,
+    /// the exact mapping when it was resolved before
+    pub async fn get_nearest_slot_mapping(&self, block_number: i64) -> Result<Option<(i64, i64)>> {
+        let mapping = sqlx::query_as(
+            r#"
+            SELECT block_number, slot FROM beacon_slot_mappings
+            WHERE block_number <= ?
+            ORDER BY block_number DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(block_number)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query beacon slot mappings")?;
+
+        Ok(mapping)
+    }
+
+    /// Store the slot of an execution block
+    pub async fn insert_slot_mapping(&self, block_number: i64, slot: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO beacon_slot_mappings (block_number, slot) VALUES (?, ?)",
+        )
+        .bind(block_number)
+        .bind(slot)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store beacon slot mapping")?;
+
+        Ok(())
+    }
+
+    // ============================================================================
+    // FINALITY CHECKPOINTS
+    // ============================================================================
+
+    /// Store the latest `kind` checkpoint (justified or finalized)
+    pub async fn upsert_finality_checkpoint(
+        &self,
+        kind: &str,
+        epoch: i64,
+        root: &str,
+        block_number: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO finality_checkpoints (kind, epoch, root, block_number, updated_at)
+            VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(kind) DO UPDATE SET
+                epoch = excluded.epoch,
+                root = excluded.root,
+                block_number = excluded.block_number,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(kind)
+        .bind(epoch)
+        .bind(root)
+        .bind(block_number)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store finality checkpoint")?;
+
+        Ok(())
+    }
+
+    /// Get the latest justified and finalized checkpoints, empty until finality is tracked
+    pub async fn get_finality_checkpoints(&self) -> Result<Vec<FinalityCheckpoint>> {
+        sqlx::query_as::<_, FinalityCheckpoint>(
+            "SELECT kind, epoch, root, block_number, updated_at FROM finality_checkpoints ORDER BY kind",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch finality checkpoints")
+    }
+
+    /// Get the execution blocks covered by the latest checkpoints
+    pub async fn get_finality(&self) -> Result<Finality> {
+        Ok(Finality::from_checkpoints(
+            &self.get_finality_checkpoints().await?,
+        ))
+    }
+
+    // ============================================================================
+    // EPOCHS
+    // ============================================================================
+
+    /// Get the summary of an epoch, None until one of its blocks is indexed
+    pub async fn get_epoch(&self, epoch: i64) -> Result<Option<Epoch>> {
+        sqlx::query_as::<_, Epoch>(
+            r#"
+            SELECT epoch, blocks_proposed, missed_slots, attestations, deposits, slashings,
+                   avg_sync_participation, updated_at
+            FROM epochs
+            WHERE epoch = ?
+            "#,
+        )
+        .bind(epoch)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch epoch")
+    }
+
+    // ============================================================================
+    // ETH PRICES
+    // ============================================================================
+
+    /// Record the ETH price of the hour containing `timestamp`, replacing the price recorded
+    /// earlier in that hour
+    pub async fn record_eth_price(
+        &self,
+        timestamp: i64,
+        price_usd: f64,
+        source: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO eth_prices (hour, price_usd, source, updated_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(timestamp - timestamp.rem_euclid(3600))
+        .bind(price_usd)
+        .bind(source)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record ETH price")?;
+        Ok(())
+    }
+
+    /// ETH price of the hour each of `block_numbers` was produced in, for the indexed blocks
+    /// whose hour has a recorded price
+    pub async fn get_eth_prices_at_blocks(
+        &self,
+        block_numbers: &[i64],
+    ) -> Result<std::collections::HashMap<i64, f64>> {
+        if block_numbers.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "SELECT b.number, p.price_usd FROM blocks b JOIN eth_prices p ON p.hour = b.timestamp - b.timestamp % 3600 WHERE b.number IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for number in block_numbers {
+            separated.push_bind(number);
+        }
+        separated.push_unseparated(")");
+
+        let prices: Vec<(i64, f64)> = query_builder
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to get ETH prices of blocks")?;
+        Ok(prices.into_iter().collect())
+    }
+
+    // ============================================================================
+    // WATCHLISTS
+    // ============================================================================
+
+    /// Get all watchlists with their address counts
+    pub async fn get_watchlists(&self) -> Result<Vec<Watchlist>> {
+        let watchlists = sqlx::query_as::<_, Watchlist>(
+            r#"
+            SELECT w.id, w.name, COUNT(a.address) AS address_count, w.created_at, w.updated_at
+            FROM watchlists w
+            LEFT JOIN watchlist_addresses a ON a.watchlist_id = w.id
+            GROUP BY w.id
+            ORDER BY w.id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get watchlists")?;
+
+        Ok(watchlists)
+    }
+
+    /// Get a watchlist by id
+    pub async fn get_watchlist(&self, id: i64) -> Result<Option<Watchlist>> {
+        let watchlist = sqlx::query_as::<_, Watchlist>(
+            r#"
+            SELECT w.id, w.name,
+                   (SELECT COUNT(*) FROM watchlist_addresses WHERE watchlist_id = w.id) AS address_count,
+                   w.created_at, w.updated_at
+            FROM watchlists w
+            WHERE w.id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to get watchlist")?;
+
+        Ok(watchlist)
+    }
+
+    /// Get the addresses of a watchlist
+    pub async fn get_watchlist_addresses(&self, id: i64) -> Result<Vec<WatchlistAddress>> {
+        let addresses = sqlx::query_as::<_, WatchlistAddress>(
+            "SELECT address, label FROM watchlist_addresses WHERE watchlist_id = ? ORDER BY added_at, address",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get watchlist addresses")?;
+
+        Ok(addresses)
+    }
+
+    /// Create a watchlist, returns its id. Addresses are expected in lowercase.
+    pub async fn create_watchlist(
+        &self,
+        name: &str,
+        addresses: &[WatchlistAddress],
+    ) -> Result<i64> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+
+        let id = sqlx::query("INSERT INTO watchlists (name) VALUES (?)")
+            .bind(name)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to create watchlist")?
+            .last_insert_rowid();
+        Self::insert_watchlist_addresses(&mut tx, id, addresses).await?;
+
+        tx.commit().await.context("Failed to commit watchlist")?;
+        Ok(id)
+    }
+
+    /// Replace the name and addresses of a watchlist, returns whether it exists
+    pub async fn replace_watchlist(
+        &self,
+        id: i64,
+        name: &str,
+        addresses: &[WatchlistAddress],
+    ) -> Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+
+        let updated = sqlx::query(
+            "UPDATE watchlists SET name = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(name)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update watchlist")?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query("DELETE FROM watchlist_addresses WHERE watchlist_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear watchlist addresses")?;
+        Self::insert_watchlist_addresses(&mut tx, id, addresses).await?;
+
+        tx.commit().await.context("Failed to commit watchlist")?;
+        Ok(true)
+    }
+
+    async fn insert_watchlist_addresses(
+        tx: &mut sqlx::Transaction<'_, Sqlite>,
+        id: i64,
+        addresses: &[WatchlistAddress],
+    ) -> Result<()> {
+        for address in addresses {
+            sqlx::query(
+                r#"
+                INSERT INTO watchlist_addresses (watchlist_id, address, label) VALUES (?, ?, ?)
+                ON CONFLICT(watchlist_id, address) DO UPDATE SET label = EXCLUDED.label
+                "#,
+            )
+            .bind(id)
+            .bind(&address.address)
+            .bind(&address.label)
+            .execute(&mut **tx)
+            .await
+            .context("Failed to store watchlist address")?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete a watchlist, returns whether it existed
+    pub async fn delete_watchlist(&self, id: i64) -> Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+
+        sqlx::query("DELETE FROM watchlist_addresses WHERE watchlist_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete watchlist addresses")?;
+        let deleted = sqlx::query("DELETE FROM watchlists WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete watchlist")?
+            .rows_affected();
+
+        tx.commit().await.context("Failed to commit watchlist")?;
+        Ok(deleted > 0)
+    }
+
+    /// Get the newest transactions sent or received by any of the given lowercase addresses
+    pub async fn get_transactions_involving(
+        &self,
+        addresses: &[String],
+        limit: i64,
+    ) -> Result<Vec<Transaction>> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit FROM transactions WHERE from_address IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for address in addresses {
+            separated.push_bind(address);
+        }
+        query_builder.push(") OR to_address IN (");
+        let mut separated = query_builder.separated(", ");
+        for address in addresses {
+            separated.push_bind(address);
+        }
+        query_builder.push(") ORDER BY block_number DESC, transaction_index DESC LIMIT ");
+        query_builder.push_bind(limit);
+
+        let transactions = query_builder
+            .build_query_as::<Transaction>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to get transactions of addresses")?;
+
+        Ok(transactions)
+    }
+
+    /// Get the newest token transfers sent or received by any of the given lowercase addresses
+    pub async fn get_token_transfers_involving(
+        &self,
+        addresses: &[String],
+        limit: i64,
+    ) -> Result<Vec<TokenTransfer>> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "SELECT id, transaction_hash, block_number, token_address, from_address, to_address, amount, token_type, token_id, log_index FROM token_transfers WHERE from_address IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for address in addresses {
+            separated.push_bind(address);
+        }
+        query_builder.push(") OR to_address IN (");
+        let mut separated = query_builder.separated(", ");
+        for address in addresses {
+            separated.push_bind(address);
+        }
+        query_builder.push(") ORDER BY block_number DESC, id DESC LIMIT ");
+        query_builder.push_bind(limit);
+
+        let transfers = query_builder
+            .build_query_as::<TokenTransfer>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to get token transfers of addresses")?;
+
+        Ok(transfers)
+    }
+
+    /// Get the transactions sent between two lowercase addresses in either direction, newest
+    /// first
+    pub async fn get_transactions_between(&self, a: &str, b: &str) -> Result<Vec<Transaction>> {
+        let transactions = sqlx::query_as::<_, Transaction>(
+            r#"
+            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
+            FROM transactions
+            WHERE (from_address = ? AND to_address = ?) OR (from_address = ? AND to_address = ?)
+            ORDER BY block_number DESC, transaction_index DESC
+            "#,
+        )
+        .bind(a)
+        .bind(b)
+        .bind(b)
+        .bind(a)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get transactions between addresses")?;
+
+        Ok(transactions)
+    }
+
+    /// Get the token transfers between two lowercase addresses in either direction, newest
+    /// first
+    pub async fn get_token_transfers_between(
+        &self,
+        a: &str,
+        b: &str,
+    ) -> Result<Vec<TokenTransfer>> {
+        let transfers = sqlx::query_as::<_, TokenTransfer>(
+            r#"
+            SELECT id, transaction_hash, block_number, token_address, from_address, to_address, amount, token_type, token_id, log_index
+            FROM token_transfers
+            WHERE (from_address = ? AND to_address = ?) OR (from_address = ? AND to_address = ?)
+            ORDER BY block_number DESC, id DESC
+            "#,
+        )
+        .bind(a)
+        .bind(b)
+        .bind(b)
+        .bind(a)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get token transfers between addresses")?;
+
+        Ok(transfers)
+    }
+
+    // ============================================================================
+    // SEARCH
+    // ============================================================================
+
+    /// Get blocks whose hash starts with a lowercase hex `prefix`, newest first
+    pub async fn search_blocks_by_hash_prefix(
+        &self,
+        prefix: &str,
+        limit: i64,
+    ) -> Result<Vec<Block>> {
+        let blocks = sqlx::query_as::<_, Block>(
+            r#"
+            SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+                   miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+                   nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+                   slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+                   eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+                   attestation_count, slashing_count
+            FROM blocks
+            WHERE hash >= ? AND hash < ?
+            LIMIT ?
+            "#,
+        )
+        .bind(prefix)
+        .bind(hex_prefix_end(prefix))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to search blocks by hash prefix")?;
+
+        Ok(blocks)
+    }
+
+    /// Get transactions whose hash starts with a lowercase hex `prefix`
+    pub async fn search_transactions_by_hash_prefix(
+        &self,
+        prefix: &str,
+        limit: i64,
+    ) -> Result<Vec<Transaction>> {
+        let transactions = sqlx::query_as::<_, Transaction>(
+            r#"
+            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
+            FROM transactions
+            WHERE hash >= ? AND hash < ?
+            LIMIT ?
+            "#,
+        )
+        .bind(prefix)
+        .bind(hex_prefix_end(prefix))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to search transactions by hash prefix")?;
+
+        Ok(transactions)
+    }
+
+    /// Get accounts whose address starts with a lowercase hex `prefix`
+    pub async fn search_accounts_by_address_prefix(
+        &self,
+        prefix: &str,
+        limit: i64,
+    ) -> Result<Vec<Account>> {
+        let accounts = sqlx::query_as::<_, Account>(
+            r#"
+            SELECT address, balance, transaction_count, first_seen_block, last_seen_block, balance_at_block,
+                   account_type
+            FROM accounts
+            WHERE address >= ? AND address < ?
+            LIMIT ?
+            "#,
+        )
+        .bind(prefix)
+        .bind(hex_prefix_end(prefix))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to search accounts by address prefix")?;
+
+        Ok(accounts)
+    }
+
+    /// Get tokens whose address starts with a lowercase hex `prefix`, most transferred first
+    pub async fn search_tokens_by_address_prefix(
+        &self,
+        prefix: &str,
+        limit: i64,
+    ) -> Result<Vec<Token>> {
+        let tokens = sqlx::query_as::<_, Token>(
+            "SELECT address, name, symbol, decimals, token_type, first_seen_block, last_seen_block, total_transfers, created_at, updated_at, name_source, symbol_source, decimals_source, logo_uri, curated FROM tokens WHERE address >= ? AND address < ? ORDER BY total_transfers DESC LIMIT ?"
+        )
+        .bind(prefix)
+        .bind(hex_prefix_end(prefix))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to search tokens by address prefix")?;
+
+        Ok(tokens)
+    }
+
+    /// Get tokens whose name or symbol has words starting with every word of `query`,
+    /// through the full-text index. Exact symbol matches rank first, then exact names and
+    /// symbol prefixes, each by text relevance and number of transfers.
+    pub async fn search_tokens_by_name(&self, query: &str, limit: i64) -> Result<Vec<TokenMatch>> {
+        let Some(fts_query) = fts_prefix_query(query) else {
+            return Ok(Vec::new());
+        };
+        // Only used to rank matches, LIKE wildcards would rank unrelated symbols as prefixes
+        let query: String = query.chars().filter(|c| !matches!(c, '%' | '_')).collect();
+
+        let tokens = sqlx::query_as::<_, TokenMatch>(
+            r#"
+            SELECT t.address, t.name, t.symbol, t.decimals, t.token_type, t.first_seen_block,
+                   t.last_seen_block, t.total_transfers, t.created_at, t.updated_at,
+                   t.name_source, t.symbol_source, t.decimals_source, t.logo_uri, t.curated,
+                   CASE
+                       WHEN t.symbol = ?1 COLLATE NOCASE THEN 80
+                       WHEN t.name = ?1 COLLATE NOCASE THEN 70
+                       WHEN t.symbol LIKE ?2 THEN 60
+                       ELSE 50
+                   END AS score
+            -- CROSS JOIN keeps the full-text match as the outer loop
+            FROM search_index
+            CROSS JOIN search_documents d ON d.id = search_index.rowid AND d.kind = 'token'
+            JOIN tokens t ON t.address = d.address
+            WHERE search_index MATCH ?3
+            ORDER BY score DESC, search_index.rank, t.total_transfers DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(query.trim())
+        .bind(format!("{}%", query.trim()))
+        .bind(fts_query)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to search tokens by name")?;
+
+        Ok(tokens)
+    }
+
+    /// Get address labels with words starting with every word of `query`, exact labels
+    /// first
+    pub async fn search_address_labels(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<AddressLabelMatch>> {
+        let Some(fts_query) = fts_prefix_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let labels = sqlx::query_as::<_, AddressLabelMatch>(
+            r#"
+            SELECT l.address, l.label, l.category, l.source, l.updated_at,
+                   CASE WHEN l.label = ?1 COLLATE NOCASE THEN 75 ELSE 55 END AS score
+            FROM search_index
+            CROSS JOIN search_documents d ON d.id = search_index.rowid AND d.kind = 'label'
+            JOIN address_labels l ON l.address = d.address
+            WHERE search_index MATCH ?2
+            ORDER BY score DESC, search_index.rank
+            LIMIT ?3
+            "#,
+        )
+        .bind(query.trim())
+        .bind(fts_query)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to search address labels")?;
+
+        Ok(labels)
+    }
+
+    // ============================================================================
+    // BLOCK WEBHOOKS
+    // ============================================================================
+
+    /// Get all block webhooks with the number of notifications waiting for each
+    pub async fn get_block_webhooks(&self) -> Result<Vec<BlockWebhook>> {
+        let webhooks = sqlx::query_as::<_, BlockWebhook>(
+            r#"
+            SELECT w.id, w.url, w.secret, w.from_block,
+                   (SELECT COUNT(*) FROM block_webhook_outbox WHERE webhook_id = w.id) AS pending_deliveries,
+                   w.created_at
+            FROM block_webhooks w
+            ORDER BY w.id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get block webhooks")?;
+
+        Ok(webhooks)
+    }
+
+    /// Create a block webhook notified from block `from_block` on, returns its id
+    pub async fn create_block_webhook(
+        &self,
+        url: &str,
+        secret: Option<&str>,
+        from_block: i64,
+    ) -> Result<i64> {
+        let id =
+            sqlx::query("INSERT INTO block_webhooks (url, secret, from_block) VALUES (?, ?, ?)")
+                .bind(url)
+                .bind(secret)
+                .bind(from_block)
+                .execute(&self.pool)
+                .await
+                .context("Failed to create block webhook")?
+                .last_insert_rowid();
+
+        Ok(id)
+    }
+
+    /// Delete a block webhook with its pending notifications, returns whether it existed
+    pub async fn delete_block_webhook(&self, id: i64) -> Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+
+        sqlx::query("DELETE FROM block_webhook_outbox WHERE webhook_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete webhook deliveries")?;
+        let deleted = sqlx::query("DELETE FROM block_webhooks WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete block webhook")?
+            .rows_affected();
+
+        tx.commit()
+            .await
+            .context("Failed to commit block webhook")?;
+        Ok(deleted > 0)
+    }
+
+    /// Number of block webhooks to notify of block `block_number`
+    pub async fn count_block_webhooks_from(&self, block_number: i64) -> Result<i64> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM block_webhooks WHERE from_block <= ?")
+                .bind(block_number)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to count block webhooks")?;
+
+        Ok(count)
+    }
+
+    /// Queue a notification of block `block_number` for every webhook subscribed to it.
+    /// Returns the number of notifications queued.
+    pub async fn enqueue_block_webhooks(
+        &self,
+        block_number: i64,
+        payload: &str,
+        now: i64,
+    ) -> Result<u64> {
+        let queued = sqlx::query(
+            r#"
+            INSERT INTO block_webhook_outbox (webhook_id, block_number, payload, next_attempt_at)
+            SELECT id, ?1, ?2, ?3 FROM block_webhooks WHERE from_block <= ?1
+            "#,
+        )
+        .bind(block_number)
+        .bind(payload)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to queue block webhooks")?
+        .rows_affected();
+
+        Ok(queued)
+    }
+
+    /// Get notifications due at `now`, oldest first. Notifications queued behind one still
+    /// waiting for a retry are held back, so each webhook receives blocks in order.
+    pub async fn get_due_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+            r#"
+            SELECT o.id, o.webhook_id, o.block_number, o.payload, o.attempts, o.next_attempt_at,
+                   o.last_error
+            FROM block_webhook_outbox o
+            WHERE o.next_attempt_at <= ?1
+              AND NOT EXISTS (
+                  SELECT 1 FROM block_webhook_outbox earlier
+                  WHERE earlier.webhook_id = o.webhook_id
+                    AND earlier.id < o.id
+                    AND earlier.next_attempt_at > ?1
+              )
+            ORDER BY o.id
+            LIMIT ?2
+            "#,
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get webhook deliveries")?;
+
+        Ok(deliveries)
+    }
+
+    /// Remove a delivered or abandoned notification from the outbox
+    pub async fn delete_webhook_delivery(&self, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM block_webhook_outbox WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete webhook delivery")?;
+
+        Ok(())
+    }
+
+    /// Record a failed delivery attempt and when to try again
+    pub async fn reschedule_webhook_delivery(
+        &self,
+        id: i64,
+        next_attempt_at: i64,
+        error: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE block_webhook_outbox
+            SET attempts = attempts + 1, next_attempt_at = ?, last_error = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(next_attempt_at)
+        .bind(error)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .context("Failed to reschedule webhook delivery")?;
+
+        Ok(())
+    }
+
+    /// Aggregates of the transactions, logs and token transfers stored for a block
+    pub async fn get_block_activity(&self, block_number: i64) -> Result<BlockActivity> {
+        let activity = sqlx::query_as::<_, BlockActivity>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM transactions WHERE block_number = ?1 AND status = 0) AS failed_transactions,
+                (SELECT COUNT(DISTINCT from_address) FROM transactions WHERE block_number = ?1) AS unique_senders,
+                (SELECT COUNT(*) FROM transaction_receipts r
+                 JOIN transactions t ON t.hash = r.transaction_hash
+                 WHERE r.block_number = ?1 AND r.contract_address IS NOT NULL AND t.status = 1) AS contract_creations,
+                (SELECT COUNT(*) FROM logs WHERE block_number = ?1) AS log_count,
+                (SELECT COUNT(*) FROM token_transfers WHERE block_number = ?1) AS token_transfer_count
+            "#,
+        )
+        .bind(block_number)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to get block activity")?;
+
+        Ok(activity)
+    }
+
+    /// Wei values of the successful transactions of a block
+    pub async fn get_transaction_values_by_block(&self, block_number: i64) -> Result<Vec<String>> {
+        let values = sqlx::query_scalar(
+            "SELECT value FROM transactions WHERE block_number = ? AND status = 1",
+        )
+        .bind(block_number)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get transaction values")?;
+
+        Ok(values)
+    }
+
+    // ============================================================================
+    // ADDRESS LABELS
+    // ============================================================================
+
+    /// Store the labels of the bundled seed file. Labels set by hand are left alone.
+    pub async fn seed_address_labels(&self) -> Result<u64> {
+        let labels: Vec<AddressLabel> =
+            serde_json::from_str(include_str!("seeds/address_labels.json"))
+                .context("Invalid address label seed file")?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+        let mut seeded = 0;
+        for label in &labels {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO address_labels (address, label, category, source)
+                VALUES (?, ?, ?, 'seed')
+                ON CONFLICT(address) DO UPDATE SET
+                    label = EXCLUDED.label,
+                    category = EXCLUDED.category,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE address_labels.source = 'seed'
+                    AND (address_labels.label != EXCLUDED.label
+                        OR address_labels.category != EXCLUDED.category)
+                "#,
+            )
+            .bind(label.address.to_lowercase())
+            .bind(&label.label)
+            .bind(&label.category)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to seed address label")?;
+            seeded += result.rows_affected();
+        }
+        tx.commit()
+            .await
+            .context("Failed to commit address labels")?;
+
+        Ok(seeded)
+    }
+
+    /// Get all address labels, optionally of a single category
+    pub async fn get_address_labels(&self, category: Option<&str>) -> Result<Vec<AddressLabel>> {
+        let labels = sqlx::query_as::<_, AddressLabel>(
+            r#"
+            SELECT address, label, category, source, updated_at FROM address_labels
+            WHERE ?1 IS NULL OR category = ?1
+            ORDER BY category, label
+            "#,
+        )
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to get address labels")?;
+
+        Ok(labels)
+    }
+
+    /// Get the labels of the given lowercase addresses, keyed by address
+    pub async fn get_address_labels_for(
+        &self,
+        addresses: &[String],
+    ) -> Result<std::collections::HashMap<String, AddressLabel>> {
+        if addresses.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "SELECT address, label, category, source, updated_at FROM address_labels WHERE address IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for address in addresses {
+            separated.push_bind(address);
+        }
+        separated.push_unseparated(")");
+
+        let labels = query_builder
+            .build_query_as::<AddressLabel>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to get address labels")?;
+
+        Ok(labels
+            .into_iter()
+            .map(|label| (label.address.clone(), label))
+            .collect())
+    }
+
+    /// Get the label of an address
+    pub async fn get_address_label(&self, address: &str) -> Result<Option<AddressLabel>> {
+        let label = sqlx::query_as::<_, AddressLabel>(
+            "SELECT address, label, category, source, updated_at FROM address_labels WHERE address = ?",
+        )
+        .bind(address.to_lowercase())
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to get address label")?;
+
+        Ok(label)
+    }
+
+    /// Set the label of an address by hand, taking precedence over the seed
+    pub async fn set_address_label(
+        &self,
+        address: &str,
+        label: &str,
+        category: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO address_labels (address, label, category, source)
+            VALUES (?, ?, ?, 'manual')
+            ON CONFLICT(address) DO UPDATE SET
+                label = EXCLUDED.label,
+                category = EXCLUDED.category,
+                source = 'manual',
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(address.to_lowercase())
+        .bind(label)
+        .bind(category)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store address label")?;
+
+        Ok(())
+    }
+
+    /// Remove the label of an address, returns whether it had one
+    pub async fn delete_address_label(&self, address: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM address_labels WHERE address = ?")
+            .bind(address.to_lowercase())
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete address label")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ============================================================================
+    // BLOCK REWARDS
+    // ============================================================================
+
+    /// Get the stored proposer rewards of a block
+    pub async fn get_block_rewards(&self, block_number: i64) -> Result<Option<BlockRewards>> {
+        let rewards = sqlx::query_as::<_, BlockRewards>(
+            r#"
+            SELECT block_number, slot, proposer_index, total, attestations, sync_aggregate,
+                   proposer_slashings, attester_slashings, fee_recipient_reward
+            FROM block_rewards
+            WHERE block_number = ?
+            "#,
+        )
+        .bind(block_number)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query block rewards")?;
+
+        Ok(rewards)
+    }
+
+    /// Blocks from `from_block` on whose slot is known but whose rewards are not stored, or
+    /// lack the execution share when `include_partial`, newest first
+    pub async fn get_blocks_without_rewards(
+        &self,
+        from_block: i64,
+        include_partial: bool,
+        limit: i64,
+    ) -> Result<Vec<Block>> {
+        let blocks = sqlx::query_as::<_, Block>(
+            r#"
+            SELECT blocks.* FROM blocks
+            LEFT JOIN block_rewards ON block_rewards.block_number = blocks.number
+            WHERE blocks.number >= ?1
+              AND blocks.slot IS NOT NULL
+              AND (block_rewards.block_number IS NULL
+                   OR (?2 AND block_rewards.fee_recipient_reward IS NULL))
+            ORDER BY blocks.number DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(from_block)
+        .bind(include_partial)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query blocks without rewards")?;
+
+        Ok(blocks)
+    }
+
+    /// Store the proposer rewards of a block
+    pub async fn insert_block_rewards(&self, rewards: &BlockRewards) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO block_rewards (
+                block_number, slot, proposer_index, total, attestations, sync_aggregate,
+                proposer_slashings, attester_slashings, fee_recipient_reward
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(rewards.block_number)
+        .bind(rewards.slot)
+        .bind(rewards.proposer_index)
+        .bind(&rewards.total)
+        .bind(&rewards.attestations)
+        .bind(&rewards.sync_aggregate)
+        .bind(&rewards.proposer_slashings)
+        .bind(&rewards.attester_slashings)
+        .bind(&rewards.fee_recipient_reward)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store block rewards")?;
+
+        Ok(())
+    }
+
+    /// Get the rollup data of a transaction
+    pub async fn get_l2_transaction(&self, hash: &str) -> Result<Option<L2Transaction>> {
+        let transaction = sqlx::query_as::<_, L2Transaction>(
+            r#"
+            SELECT transaction_hash, block_number, kind, l1_fee, l1_gas_used, l1_gas_price,
+                   l1_fee_scalar, l1_blob_base_fee, l1_block_number, source_hash, mint
+            FROM l2_transactions
+            WHERE transaction_hash = ?
+            "#,
+        )
+        .bind(hash)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query L2 transaction data")?;
+
+        Ok(transaction)
+    }
+
+    /// Get the L1 origin and batch references of a block
+    pub async fn get_l2_block(&self, block_number: i64) -> Result<Option<L2Block>> {
+        let block = sqlx::query_as::<_, L2Block>(
+            r#"
+            SELECT block_number, l1_block_number, l1_block_hash, l1_timestamp, sequence_number,
+                   batcher_hash, send_count, send_root
+            FROM l2_blocks
+            WHERE block_number = ?
+            "#,
+        )
+        .bind(block_number)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query L2 block data")?;
+
+        Ok(block)
+    }
+
+    /// Get the payload fee recipient of a block and the proposer payment it made
+    pub async fn get_block_fee_recipient(
+        &self,
+        block_number: i64,
+    ) -> Result<Option<BlockFeeRecipient>> {
+        let fee_recipient = sqlx::query_as::<_, BlockFeeRecipient>(
+            r#"
+            SELECT block_number, slot, proposer_index, miner, fee_recipient, mismatch,
+                   proposer_payee, proposer_payment
+            FROM block_fee_recipients
+            WHERE block_number = ?
+            "#,
+        )
+        .bind(block_number)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query block fee recipient")?;
+
+        Ok(fee_recipient)
+    }
+
+    /// Get block fee recipients, newest blocks first, with the total matching the filters
+    pub async fn get_block_fee_recipients(
+        &self,
+        params: &FeeRecipientParams,
+    ) -> Result<(Vec<BlockFeeRecipient>, i64)> {
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT block_number, slot, proposer_index, miner, fee_recipient, mismatch, proposer_payee, proposer_payment FROM block_fee_recipients",
+        );
+        push_fee_recipient_filters(&mut query, params);
+        query
+            .push(" ORDER BY block_number DESC LIMIT ")
+            .push_bind(params.limit())
+            .push(" OFFSET ")
+            .push_bind(params.offset());
+        let fee_recipients = query
+            .build_query_as::<BlockFeeRecipient>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch block fee recipients")?;
+
+        let mut count = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM block_fee_recipients");
+        push_fee_recipient_filters(&mut count, params);
+        let total: (i64,) = count
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count block fee recipients")?;
+
+        Ok((fee_recipients, total.0))
+    }
+
+    /// Get the fee recipient and proposer payee pairs of the matching blocks, the pairs
+    /// with the most blocks first
+    pub async fn get_fee_recipient_relationships(
+        &self,
+        params: &FeeRecipientParams,
+    ) -> Result<Vec<FeeRecipientRelationship>> {
+        let mut query = sqlx::QueryBuilder::new(
+            r#"
+            SELECT fee_recipient, proposer_payee, COUNT(*) AS blocks,
+                   SUM(mismatch) AS mismatches,
+                   COUNT(DISTINCT proposer_index) AS proposers,
+                   COALESCE(SUM(CAST(proposer_payment AS REAL)), 0.0) / 1e18 AS total_payment_eth,
+                   MIN(block_number) AS first_block, MAX(block_number) AS last_block
+            FROM block_fee_recipients
+            "#,
+        );
+        push_fee_recipient_filters(&mut query, params);
+        query
+            .push(" GROUP BY fee_recipient, proposer_payee ORDER BY blocks DESC, last_block DESC LIMIT ")
+            .push_bind(params.limit())
+            .push(" OFFSET ")
+            .push_bind(params.offset());
+
+        let relationships = query
+            .build_query_as::<FeeRecipientRelationship>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch fee recipient relationships")?;
+
+        Ok(relationships)
+    }
+
+    // ============================================================================
+    // INDEXER EVENTS
+    // ============================================================================
+
+    /// Record an operational event
+    pub async fn insert_indexer_event(&self, event: &IndexerEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO indexer_events (kind, severity, message, block_number, details)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&event.kind)
+        .bind(&event.severity)
+        .bind(&event.message)
+        .bind(event.block_number)
+        .bind(&event.details)
+        .execute(&self.pool)
+        .await
+        .context("Failed to insert indexer event")?;
+
+        Ok(())
+    }
+
+    /// Get indexer events, newest first, optionally filtered by kind and severity.
+    /// Returns the requested page and the total number of matching events.
+    pub async fn get_indexer_events(
+        &self,
+        params: &IndexerEventParams,
+    ) -> Result<(Vec<IndexerEvent>, i64)> {
+        let filter = r#"
+            WHERE (?1 IS NULL OR kind = ?1)
+              AND (?2 IS NULL OR severity = ?2)
+        "#;
+
+        let events = sqlx::query_as::<_, IndexerEvent>(&format!(
+            "SELECT * FROM indexer_events {} ORDER BY id DESC LIMIT ?3 OFFSET ?4",
+            filter
+        ))
+        .bind(&params.kind)
+        .bind(&params.severity)
+        .bind(params.limit())
+        .bind(params.offset())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch indexer events")?;
+
+        let total: (i64,) =
+            sqlx::query_as(&format!("SELECT COUNT(*) FROM indexer_events {}", filter))
+                .bind(&params.kind)
+                .bind(&params.severity)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to count indexer events")?;
+
+        Ok((events, total.0))
+    }
+
+    // ============================================================================
+    // STORAGE STATISTICS
+    // ============================================================================
+
+    /// Measure the row count and the table and index page sizes of every table
+    pub async fn measure_table_storage(&self, sampled_on: &str) -> Result<Vec<StorageSnapshot>> {
+        // Pages are attributed to the table of each b-tree, indexes included
+        let sizes: Vec<(String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT schema.tbl_name,
+                   COALESCE(SUM(CASE WHEN schema.type = 'table' THEN stat.pgsize END), 0),
+                   COALESCE(SUM(CASE WHEN schema.type = 'index' THEN stat.pgsize END), 0)
+            FROM sqlite_schema AS schema
+            LEFT JOIN (SELECT name, pgsize FROM dbstat WHERE aggregate = TRUE) AS stat
+                ON stat.name = schema.name
+            WHERE schema.type IN ('table', 'index') AND schema.tbl_name NOT LIKE 'sqlite_%'
+            GROUP BY schema.tbl_name
+            ORDER BY schema.tbl_name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to measure table sizes")?;
+
+        let mut snapshots = Vec::with_capacity(sizes.len());
+        for (table_name, table_bytes, index_bytes) in sizes {
+            let row_count: i64 = sqlx::query_scalar(&format!(
+                "SELECT COUNT(*) FROM \"{}\"",
+                table_name.replace('"', "\"\"")
+            ))
+            .fetch_one(&self.pool)
+            .await
+            .context(format!("Failed to count rows of {}", table_name))?;
+
+            snapshots.push(StorageSnapshot {
+                sampled_on: sampled_on.to_string(),
+                table_name,
+                row_count,
+                table_bytes,
+                index_bytes,
+            });
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Measure every table and store the result as the sample of `sampled_on`, replacing an
+    /// earlier sample of the same day
+    pub async fn record_storage_snapshot(&self, sampled_on: &str) -> Result<Vec<StorageSnapshot>> {
+        let snapshots = self.measure_table_storage(sampled_on).await?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin storage snapshot transaction")?;
+        sqlx::query("DELETE FROM storage_snapshots WHERE sampled_on = ?")
+            .bind(sampled_on)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear storage snapshot")?;
+        for snapshot in &snapshots {
+            sqlx::query(
+                r#"
+                INSERT INTO storage_snapshots (
+                    sampled_on, table_name, row_count, table_bytes, index_bytes
+                ) VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&snapshot.sampled_on)
+            .bind(&snapshot.table_name)
+            .bind(snapshot.row_count)
+            .bind(snapshot.table_bytes)
+            .bind(snapshot.index_bytes)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert storage snapshot")?;
+        }
+        tx.commit()
+            .await
+            .context("Failed to commit storage snapshot")?;
+
+        Ok(snapshots)
+    }
+
+    /// Get the date of the latest storage sample
+    pub async fn get_latest_storage_sample_date(&self) -> Result<Option<String>> {
+        sqlx::query_scalar("SELECT MAX(sampled_on) FROM storage_snapshots")
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to query latest storage sample date")
+    }
+
+    /// Get the storage samples taken on or after a date, oldest first
+    pub async fn get_storage_snapshots(&self, since: &str) -> Result<Vec<StorageSnapshot>> {
+        sqlx::query_as::<_, StorageSnapshot>(
+            r#"
+            SELECT sampled_on, table_name, row_count, table_bytes, index_bytes
+            FROM storage_snapshots
+            WHERE sampled_on >= ?
+            ORDER BY sampled_on, table_name
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch storage snapshots")
+    }
+
+    // ============================================================================
+    // AGGREGATE STATISTICS
+    // ============================================================================
+
+    /// Get the timestamps of the oldest and newest indexed blocks
+    pub async fn get_block_timestamp_range(&self) -> Result<Option<(i64, i64)>> {
+        let result: (Option<i64>, Option<i64>) =
+            sqlx::query_as("SELECT MIN(timestamp), MAX(timestamp) FROM blocks")
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to query block timestamp range")?;
+
+        Ok(result.0.zip(result.1))
+    }
+
+    /// Get the blocks and transactions with a timestamp in (`start`, `end`], the oldest such
+    /// timestamp and whether an older block is indexed
+    pub async fn get_throughput_window(
+        &self,
+        start: i64,
+        end: i64,
+    ) -> Result<(i64, i64, Option<i64>, bool)> {
+        let window = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(transaction_count), 0), MIN(timestamp),
+                   EXISTS(SELECT 1 FROM blocks WHERE timestamp <= ?1)
+            FROM blocks
+            WHERE timestamp > ?1 AND timestamp <= ?2
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to query throughput window")?;
+
+        Ok(window)
+    }
+
+    /// Get the oldest `limit` hours whose blocks were written since they were last
+    /// aggregated, as (id, hour start)
+    pub async fn get_stale_aggregate_hours(&self, limit: i64) -> Result<Vec<(i64, i64)>> {
+        let hours = sqlx::query_as(
+            "SELECT id, bucket_start FROM stale_aggregate_hours ORDER BY id LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query stale aggregate hours")?;
+
+        Ok(hours)
+    }
+
+    /// Forget the stale hours read up to `last_id`. Hours written again since then got a
+    /// higher id and stay stale.
+    pub async fn clear_stale_aggregate_hours(&self, last_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM stale_aggregate_hours WHERE id <= ?")
+            .bind(last_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to clear stale aggregate hours")?;
+
+        Ok(())
+    }
+
+    /// Get the bucket starts already aggregated for a period
+    pub async fn get_aggregate_bucket_starts(&self, period: &str) -> Result<Vec<i64>> {
+        let starts =
+            sqlx::query_scalar("SELECT bucket_start FROM aggregate_stats WHERE period = ?")
+                .bind(period)
+                .fetch_all(&self.pool)
+                .await
+                .context("Failed to query aggregate buckets")?;
+
+        Ok(starts)
+    }
+
+    /// Get the bucket starts of a period whose issuance was estimated
+    pub async fn get_issuance_bucket_starts(&self, period: &str) -> Result<Vec<i64>> {
+        let starts = sqlx::query_scalar(
+            "SELECT bucket_start FROM aggregate_stats WHERE period = ? AND issuance_samples IS NOT NULL",
+        )
+        .bind(period)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query issuance buckets")?;
+
+        Ok(starts)
+    }
+
+    /// Compute the activity totals of the blocks with a timestamp in [start, end)
+    pub async fn compute_aggregate_stats(
+        &self,
+        period: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<AggregateStats> {
+        let (block_count, gas_used, burnt_fees_wei, first_block, last_block): (
+            i64,
+            i64,
+            f64,
+            Option<i64>,
+            Option<i64>,
+        ) = sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(*),
+                COALESCE(SUM(gas_used), 0),
+                COALESCE(SUM(CAST(base_fee_per_gas AS REAL) * gas_used), 0.0),
+                MIN(number),
+                MAX(number)
+            FROM blocks
+            WHERE timestamp >= ? AND timestamp < ?
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to aggregate blocks")?;
+
+        // Timestamps grow with block numbers, the rest is counted by block range
+        let (first_block, last_block) = (first_block.unwrap_or(0), last_block.unwrap_or(-1));
+
+        let (transaction_count, avg_gas_price_wei): (i64, Option<f64>) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), AVG(CAST(gas_price AS REAL))
+            FROM transactions
+            WHERE block_number BETWEEN ? AND ?
+            "#,
+        )
+        .bind(first_block)
+        .bind(last_block)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to aggregate transactions")?;
+
+        let (active_addresses,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM (
+                SELECT from_address FROM transactions WHERE block_number BETWEEN ?1 AND ?2
+                UNION
+                SELECT to_address FROM transactions
+                WHERE block_number BETWEEN ?1 AND ?2 AND to_address IS NOT NULL
+            )
+            "#,
+        )
+        .bind(first_block)
+        .bind(last_block)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count active addresses")?;
+
+        let (new_contracts,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM contract_deployments WHERE block_number BETWEEN ? AND ?",
+        )
+        .bind(first_block)
+        .bind(last_block)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count new contracts")?;
+
+        Ok(AggregateStats {
+            period: period.to_string(),
+            bucket_start: start,
+            block_count,
+            transaction_count,
+            gas_used,
+            avg_gas_price_gwei: avg_gas_price_wei.map(|price| price / 1e9),
+            active_addresses,
+            new_contracts,
+            burnt_fees_eth: burnt_fees_wei / 1e18,
+            issuance_eth: None,
+            issuance_samples: None,
+            updated_at: None,
+        })
+    }
+
+    /// Get up to `count` blocks spread evenly over those with a timestamp in [start, end)
+    pub async fn get_block_sample(&self, start: i64, end: i64, count: i64) -> Result<Vec<Block>> {
+        let blocks = sqlx::query_as::<_, Block>(
+            r#"
+            WITH bucket AS (
+                SELECT MIN(number) AS first, MAX(number) AS last
+                FROM blocks
+                WHERE timestamp >= ?1 AND timestamp < ?2
+            )
+            SELECT blocks.* FROM blocks, bucket
+            WHERE blocks.number BETWEEN bucket.first AND bucket.last
+              AND (blocks.number - bucket.first) % MAX((bucket.last - bucket.first + 1) / ?3, 1) = 0
+            ORDER BY blocks.number ASC
+            LIMIT ?3
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(count)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to sample blocks")?;
+
+        Ok(blocks)
+    }
+
+    /// Insert or replace the totals of a bucket
+    pub async fn upsert_aggregate_stats(&self, stats: &AggregateStats) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO aggregate_stats (
+                period, bucket_start, block_count, transaction_count, gas_used,
+                avg_gas_price_gwei, active_addresses, new_contracts, burnt_fees_eth,
+                issuance_eth, issuance_samples, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(&stats.period)
+        .bind(stats.bucket_start)
+        .bind(stats.block_count)
+        .bind(stats.transaction_count)
+        .bind(stats.gas_used)
+        .bind(stats.avg_gas_price_gwei)
+        .bind(stats.active_addresses)
+        .bind(stats.new_contracts)
+        .bind(stats.burnt_fees_eth)
+        .bind(stats.issuance_eth)
+        .bind(stats.issuance_samples)
+        .execute(&self.pool)
+        .await
+        .context("Failed to upsert aggregate stats")?;
+
+        Ok(())
+    }
+
+    /// Get the buckets of a period starting at or after `since`, oldest first
+    pub async fn get_aggregate_stats(
+        &self,
+        period: &str,
+        since: i64,
+    ) -> Result<Vec<AggregateStats>> {
+        let stats = sqlx::query_as::<_, AggregateStats>(
+            r#"
+            SELECT * FROM aggregate_stats
+            WHERE period = ? AND bucket_start >= ?
+            ORDER BY bucket_start ASC
+            "#,
+        )
+        .bind(period)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch aggregate stats")?;
+
+        Ok(stats)
+    }
+
+    /// Get the days whose leaderboards were computed for all `metrics`
+    pub async fn get_leaderboard_days(&self, metrics: usize) -> Result<Vec<i64>> {
+        let days = sqlx::query_scalar(
+            "SELECT day_start FROM leaderboards GROUP BY day_start HAVING COUNT(*) >= ?",
+        )
+        .bind(metrics as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query leaderboard days")?;
+
+        Ok(days)
+    }
+
+    /// Rank the transactions of the blocks with a timestamp in [start, end) by `metric`,
+    /// keeping the best `size` entries
+    pub async fn compute_leaderboard(
+        &self,
+        metric: &str,
+        start: i64,
+        end: i64,
+        size: i64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        let (first_block, last_block): (Option<i64>, Option<i64>) = sqlx::query_as(
+            "SELECT MIN(number), MAX(number) FROM blocks WHERE timestamp >= ? AND timestamp < ?",
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to query day block range")?;
+        let Some((first_block, last_block)) = first_block.zip(last_block) else {
+            return Ok(Vec::new());
+        };
+
+        let rows: Vec<(String, Option<String>, f64, Option<i64>)> = match metric {
+            "fee-payers" => {
+                sqlx::query_as(
+                    r#"
+                    SELECT from_address, NULL, SUM(CAST(gas_price AS REAL) * gas_used) / 1e18 AS fees, COUNT(*)
+                    FROM transactions
+                    WHERE block_number BETWEEN ? AND ?
+                    GROUP BY from_address
+                    ORDER BY fees DESC
+                    LIMIT ?
+                    "#,
+                )
+                .bind(first_block)
+                .bind(last_block)
+                .bind(size)
+                .fetch_all(&self.pool)
+                .await
+            }
+            "gas-guzzlers" => {
+                sqlx::query_as(
+                    r#"
+                    SELECT from_address, hash, CAST(gas_used AS REAL), NULL
+                    FROM transactions
+                    WHERE block_number BETWEEN ? AND ?
+                    ORDER BY gas_used DESC
+                    LIMIT ?
+                    "#,
+                )
+                .bind(first_block)
+                .bind(last_block)
+                .bind(size)
+                .fetch_all(&self.pool)
+                .await
+            }
+            "transfers" => {
+                sqlx::query_as(
+                    r#"
+                    SELECT from_address, hash, CAST(value AS REAL) / 1e18 AS amount, NULL
+                    FROM transactions
+                    WHERE block_number BETWEEN ? AND ? AND value != '0'
+                    ORDER BY value_sort DESC
+                    LIMIT ?
+                    "#,
+                )
+                .bind(first_block)
+                .bind(last_block)
+                .bind(size)
+                .fetch_all(&self.pool)
+                .await
+            }
+            other => return Err(anyhow::anyhow!("Unknown leaderboard metric: {}", other)),
+        }
+        .context(format!("Failed to rank {}", metric))?;
+
+        Ok(rows
+            .into_iter()
+            .enumerate()
+            .map(
+                |(i, (address, transaction_hash, value, transaction_count))| LeaderboardEntry {
+                    rank: i as i64 + 1,
+                    address,
+                    transaction_hash,
+                    value,
+                    transaction_count,
+                },
+            )
+            .collect())
+    }
+
+    /// Insert or replace the ranking of a metric for a day
+    pub async fn upsert_leaderboard(
+        &self,
+        metric: &str,
+        day_start: i64,
+        entries: &[LeaderboardEntry],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO leaderboards (metric, day_start, entries, updated_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(metric)
+        .bind(day_start)
+        .bind(serde_json::to_string(entries)?)
+        .execute(&self.pool)
+        .await
+        .context("Failed to upsert leaderboard")?;
+
+        Ok(())
+    }
+
+    /// Get the ranking of a metric for a day
+    pub async fn get_leaderboard(
+        &self,
+        metric: &str,
+        day_start: i64,
+    ) -> Result<Option<Leaderboard>> {
+        let leaderboard = sqlx::query_as::<_, Leaderboard>(
+            "SELECT * FROM leaderboards WHERE metric = ? AND day_start = ?",
+        )
+        .bind(metric)
+        .bind(day_start)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch leaderboard")?;
+
+        Ok(leaderboard)
+    }
+
+    /// Get the most called contracts since `since`, with their calls and unique callers
+    pub async fn get_top_contracts(
+        &self,
+        since: i64,
+        limit: i64,
+    ) -> Result<Vec<ContractPopularity>> {
+        let hour_start = since.div_euclid(3600) * 3600;
+        let contracts = sqlx::query_as::<_, ContractPopularity>(
+            r#"
+            SELECT contract_address, SUM(calls) AS calls,
+                   (SELECT COUNT(DISTINCT caller_address) FROM contract_callers c
+                    WHERE c.contract_address = i.contract_address AND c.hour_start >= ?) AS unique_callers
+            FROM contract_interactions i
+            WHERE hour_start >= ?
+            GROUP BY contract_address
+            ORDER BY calls DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(hour_start)
+        .bind(hour_start)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query top contracts")?;
+
+        Ok(contracts)
+    }
+
+    /// Delete contract interaction rollup hours older than `before`
+    pub async fn prune_contract_interactions(&self, before: i64) -> Result<u64> {
+        let mut deleted = 0;
+        for table in ["contract_interactions", "contract_callers"] {
+            deleted += sqlx::query(&format!("DELETE FROM {} WHERE hour_start < ?", table))
+                .bind(before)
+                .execute(&self.pool)
+                .await
+                .context(format!("Failed to prune {}", table))?
+                .rows_affected();
+        }
+
+        Ok(deleted)
+    }
+
+    // ============================================================================
+    // EXPORT STREAMING
+    // ============================================================================
+
+    /// Stream blocks in a number range row by row, without buffering the result set
+    pub fn stream_blocks(
+        &self,
+        from_block: i64,
+        to_block: i64,
+    ) -> BoxStream<'_, Result<Block, sqlx::Error>> {
+        sqlx::query_as::<_, Block>(
+            r#"
+            SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+                   miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+                   nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+                   slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+                   eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+                   attestation_count, slashing_count
+            FROM blocks
+            WHERE number >= ? AND number <= ?
+            ORDER BY number
+            "#,
+        )
+        .bind(from_block)
+        .bind(to_block)
+        .fetch(&self.pool)
+    }
+
+    /// Stream transactions in a block range row by row, without buffering the result set
+    pub fn stream_transactions(
+        &self,
+        from_block: i64,
+        to_block: i64,
+    ) -> BoxStream<'_, Result<Transaction, sqlx::Error>> {
+        sqlx::query_as::<_, Transaction>(
+            r#"
+            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
+            FROM transactions
+            WHERE block_number >= ? AND block_number <= ?
+            ORDER BY block_number, transaction_index
+            "#,
+        )
+        .bind(from_block)
+        .bind(to_block)
+        .fetch(&self.pool)
+    }
+
+    /// Stream logs in a block range row by row, without buffering the result set
+    pub fn stream_logs(
+        &self,
+        from_block: i64,
+        to_block: i64,
+    ) -> BoxStream<'_, Result<Log, sqlx::Error>> {
+        sqlx::query_as::<_, Log>(
+            r#"
+            SELECT id, transaction_hash, block_number, address, topic0, topic1, topic2, topic3, data, log_index
+            FROM logs
+            WHERE block_number >= ? AND block_number <= ?
+            ORDER BY block_number, log_index
+            "#,
+        )
+        .bind(from_block)
+        .bind(to_block)
+        .fetch(&self.pool)
+    }
+
+    /// Stream token transfers in a block range row by row, without buffering the result set
+    pub fn stream_token_transfers(
+        &self,
+        from_block: i64,
+        to_block: i64,
+    ) -> BoxStream<'_, Result<TokenTransfer, sqlx::Error>> {
+        sqlx::query_as::<_, TokenTransfer>(
+            r#"
+            SELECT id, transaction_hash, block_number, token_address, from_address, to_address,
+                   amount, token_type, token_id, log_index
+            FROM token_transfers
+            WHERE block_number >= ? AND block_number <= ?
+            ORDER BY block_number, id
+            "#,
+        )
+        .bind(from_block)
+        .bind(to_block)
+        .fetch(&self.pool)
+    }
+
+    /// Stream the ETH balances of accounts at `block_number` in holder export order: the
+    /// latest balance history snapshot of each account at or before the block
+    pub fn stream_eth_holders_at_block(
+        &self,
+        block_number: i64,
+        range: &HolderExportRange,
+    ) -> BoxStream<'_, Result<HolderBalance, sqlx::Error>> {
+        let query = sqlx::query_as::<_, HolderBalance>(
+            r#"
+            SELECT address, balance, balance_block
+            FROM (
+                SELECT address, balance, MAX(block_number) AS balance_block,
+                       printf('%02d', length(balance)) || balance AS balance_sort
+                FROM account_balance_history
+                WHERE block_number <= ?
+                GROUP BY address
+            )
+            WHERE balance_sort >= ?
+              AND (? IS NULL OR balance_sort < ? OR (balance_sort = ? AND address > ?))
+            ORDER BY balance_sort DESC, address
+            LIMIT ?
+            "#,
+        )
+        .bind(block_number);
+        bind_holder_range(query, range).fetch(&self.pool)
+    }
+
+    /// Stream the stored ETH balances of accounts, as last read, in holder export order
+    pub fn stream_eth_holders(
+        &self,
+        range: &HolderExportRange,
+    ) -> BoxStream<'_, Result<HolderBalance, sqlx::Error>> {
+        let query = sqlx::query_as::<_, HolderBalance>(
+            r#"
+            SELECT address, balance, COALESCE(balance_at_block, last_seen_block) AS balance_block
+            FROM accounts
+            WHERE balance_sort >= ?
+              AND (? IS NULL OR balance_sort < ? OR (balance_sort = ? AND address > ?))
+            ORDER BY balance_sort DESC, address
+            LIMIT ?
+            "#,
+        );
+        bind_holder_range(query, range).fetch(&self.pool)
+    }
+
+    /// Stream the stored balances of the holders of a token, as last read, in holder export
+    /// order
+    pub fn stream_token_holders(
+        &self,
+        token_address: &str,
+        range: &HolderExportRange,
+    ) -> BoxStream<'_, Result<HolderBalance, sqlx::Error>> {
+        let query = sqlx::query_as::<_, HolderBalance>(
+            r#"
+            SELECT account_address AS address, balance, last_updated_block AS balance_block
+            FROM token_balances
+            WHERE token_address = ?
+              AND balance_sort >= ?
+              AND (? IS NULL OR balance_sort < ? OR (balance_sort = ? AND account_address > ?))
+            ORDER BY balance_sort DESC, account_address
+            LIMIT ?
+            "#,
+        )
+        .bind(token_address.to_string());
+        bind_holder_range(query, range).fetch(&self.pool)
+    }
+
+    /// Get the id of the holder snapshot of a token at `block_number`, if built
+    pub async fn get_token_holder_snapshot(
+        &self,
+        token_address: &str,
+        block_number: i64,
+    ) -> Result<Option<i64>> {
+        let id = sqlx::query_scalar(
+            "SELECT id FROM token_holder_snapshots WHERE token_address = ? AND block_number = ?",
+        )
+        .bind(token_address)
+        .bind(block_number)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query token holder snapshot")?;
+
+        Ok(id)
+    }
+
+    /// Store the (address, balance, balance block) holders of a token at `block_number`,
+    /// keeping the newest `keep` snapshots. Returns the snapshot id, that of a snapshot
+    /// stored meanwhile when there is one.
+    pub async fn create_token_holder_snapshot(
+        &self,
+        token_address: &str,
+        block_number: i64,
+        holders: &[(String, String, i64)],
+        keep: i64,
+    ) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO token_holder_snapshots (token_address, block_number) VALUES (?, ?)",
+        )
+        .bind(token_address)
+        .bind(block_number)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to create token holder snapshot")?;
+        let id: i64 = sqlx::query_scalar(
+            "SELECT id FROM token_holder_snapshots WHERE token_address = ? AND block_number = ?",
+        )
+        .bind(token_address)
+        .bind(block_number)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to query token holder snapshot")?;
+        if inserted.rows_affected() == 0 {
+            return Ok(id);
+        }
+
+        for chunk in holders.chunks(500) {
+            let mut query = sqlx::QueryBuilder::new(
+                "INSERT INTO token_holder_snapshot_balances (snapshot_id, address, balance, balance_block) ",
+            );
+            query.push_values(chunk, |mut row, (address, balance, balance_block)| {
+                row.push_bind(id)
+                    .push_bind(address)
+                    .push_bind(balance)
+                    .push_bind(balance_block);
+            });
+            query
+                .build()
+                .execute(&mut *tx)
+                .await
+                .context("Failed to store token holder snapshot")?;
+        }
+
+        let evicted = "SELECT id FROM token_holder_snapshots ORDER BY id DESC LIMIT -1 OFFSET ?";
+        sqlx::query(&format!(
+            "DELETE FROM token_holder_snapshot_balances WHERE snapshot_id IN ({})",
+            evicted
+        ))
+        .bind(keep)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to evict token holder snapshots")?;
+        sqlx::query(&format!(
+            "DELETE FROM token_holder_snapshots WHERE id IN ({})",
+            evicted
+        ))
+        .bind(keep)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to evict token holder snapshots")?;
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Stream the balances of a token holder snapshot in holder export order
+    pub fn stream_token_holder_snapshot(
+        &self,
+        snapshot_id: i64,
+        range: &HolderExportRange,
+    ) -> BoxStream<'_, Result<HolderBalance, sqlx::Error>> {
+        let query = sqlx::query_as::<_, HolderBalance>(
+            r#"
+            SELECT address, balance, balance_block
+            FROM token_holder_snapshot_balances
+            WHERE snapshot_id = ?
+              AND balance_sort >= ?
+              AND (? IS NULL OR balance_sort < ? OR (balance_sort = ? AND address > ?))
+            ORDER BY balance_sort DESC, address
+            LIMIT ?
+            "#,
+        )
+        .bind(snapshot_id);
+        bind_holder_range(query, range).fetch(&self.pool)
+    }
+
+    /// Stream the (from, to, amount, block number) of the transfers of a token up to
+    /// `block_number`
+    pub fn stream_token_transfer_amounts(
+        &self,
+        token_address: &str,
+        block_number: i64,
+    ) -> BoxStream<'_, Result<(String, String, String, i64), sqlx::Error>> {
+        sqlx::query_as(
+            "SELECT from_address, to_address, amount, block_number FROM token_transfers WHERE token_address = ? AND block_number <= ?",
+        )
+        .bind(token_address.to_string())
+        .bind(block_number)
+        .fetch(&self.pool)
+    }
+
+    /// First indexed block at or below `block_number` and how many blocks are indexed from
+    /// it up to `block_number`
+    pub async fn get_block_coverage(&self, block_number: i64) -> Result<(Option<i64>, i64)> {
+        let coverage = sqlx::query_as("SELECT MIN(number), COUNT(*) FROM blocks WHERE number <= ?")
+            .bind(block_number)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to query indexed block coverage")?;
+
+        Ok(coverage)
+    }
+
+    /// Get the latest block number
+    pub async fn get_latest_block_number(&self) -> Result<Option<i64>> {
+        let result: (Option<i64>,) = sqlx::query_as("SELECT MAX(number) FROM blocks")
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to query latest block number")?;
+
+        Ok(result.0)
+    }
+
+    /// Get the number and timestamp of the latest indexed block
+    pub async fn get_latest_block_timestamp(&self) -> Result<Option<(i64, i64)>> {
+        sqlx::query_as("SELECT number, timestamp FROM blocks ORDER BY number DESC LIMIT 1")
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to query latest block timestamp")
+    }
+
+    /// Get the size of the database file in bytes
+    pub async fn get_database_size(&self) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to query database size")
+    }
+
+    /// Get the bytes of the database file held by free pages, reused before the file grows
+    pub async fn get_database_free_size(&self) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT freelist_count * page_size FROM pragma_freelist_count(), pragma_page_size()",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to query database free size")
+    }
+
+    /// Get the lowest and highest indexed block numbers
+    pub async fn get_block_number_range(&self) -> Result<Option<(i64, i64)>> {
+        let result: (Option<i64>, Option<i64>) =
+            sqlx::query_as("SELECT MIN(number), MAX(number) FROM blocks")
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to query block number range")?;
+
+        Ok(result.0.zip(result.1))
+    }
+
+    /// Get (block_number, slot, timestamp) of the newest `limit` indexed blocks, oldest first
+    pub async fn get_recent_block_slots(&self, limit: i64) -> Result<Vec<(i64, Option<i64>, i64)>> {
+        let mut blocks: Vec<(i64, Option<i64>, i64)> = sqlx::query_as(
+            "SELECT number, slot, timestamp FROM blocks ORDER BY number DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query recent block slots")?;
+        blocks.reverse();
+
+        Ok(blocks)
+    }
+
+    /// Get (block_number, declared transaction_count, stored transactions) of the indexed
+    /// blocks in an inclusive range
+    pub async fn get_block_transaction_counts(
+        &self,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<Vec<(i64, i64, i64)>> {
+        let counts = sqlx::query_as(
+            r#"
+            SELECT
+                number,
+                transaction_count,
+                (SELECT COUNT(*) FROM transactions WHERE block_number = blocks.number)
+            FROM blocks
+            WHERE number BETWEEN ? AND ?
+            ORDER BY number
+            "#,
+        )
+        .bind(from_block)
+        .bind(to_block)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query block transaction counts")?;
+
+        Ok(counts)
+    }
+
+    /// Delete the transactions, logs and token transfers of a block so it can be reprocessed
+    pub async fn delete_block_transactions(&self, block_number: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        // Counted from the stored transactions, so before they are deleted. Writing first
+        // makes the transaction wait for other writers instead of failing to upgrade a read.
+        forget_contract_interactions(&mut tx, block_number).await?;
+        block_store::forget_account_transactions(&mut tx, block_number).await?;
+        snapshot_block(&mut tx, block_number, "consistency_repair").await?;
+
+        for table in ["token_transfers", "logs", "l2_transactions", "transactions"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE block_number = ?", table))
+                .bind(block_number)
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to delete {} of block", table))?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Hash of an indexed block
+    pub async fn get_block_hash(&self, number: i64) -> Result<Option<String>> {
+        let hash = sqlx::query_scalar("SELECT hash FROM blocks WHERE number = ?")
+            .bind(number)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to query block hash")?;
+
+        Ok(hash)
+    }
+
+    /// Delete everything stored for a block replaced by a reorg or a reindex except the block
+    /// row, which is overwritten when the block is stored again. The block and its
+    /// transactions are kept in the history tables under `reason`.
+    pub async fn delete_replaced_block_data(&self, block_number: i64, reason: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        // Counted from the stored transactions, so before they are deleted. Writing first
+        // makes the transaction wait for other writers instead of failing to upgrade a read.
+        forget_contract_interactions(&mut tx, block_number).await?;
+        block_store::forget_account_transactions(&mut tx, block_number).await?;
+        snapshot_block(&mut tx, block_number, reason).await?;
+
+        for table in [
+            "token_transfers",
+            "logs",
+            "l2_transactions",
+            "transaction_receipts",
+            "account_balance_history",
+            "transactions",
+            "withdrawals",
+            "deposits",
+            "block_rewards",
+            "beacon_slot_mappings",
+            "l2_blocks",
+            "block_fee_recipients",
+        ] {
+            sqlx::query(&format!("DELETE FROM {} WHERE block_number = ?", table))
+                .bind(block_number)
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to delete {} of replaced block", table))?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get the previous versions of a block, newest first
+    pub async fn get_block_versions(&self, block_number: i64) -> Result<Vec<BlockVersion>> {
+        let versions = sqlx::query_as::<_, BlockVersion>(
+            "SELECT * FROM blocks_history WHERE block_number = ? ORDER BY id DESC",
+        )
+        .bind(block_number)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query block history")?;
+
+        Ok(versions)
+    }
+
+    /// Get the previous versions of a transaction, newest first
+    pub async fn get_transaction_versions(&self, hash: &str) -> Result<Vec<TransactionVersion>> {
+        let versions = sqlx::query_as::<_, TransactionVersion>(
+            "SELECT * FROM transactions_history WHERE hash = ? ORDER BY id DESC",
+        )
+        .bind(hash.to_lowercase())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query transaction history")?;
+
+        Ok(versions)
+    }
+
+    /// Record an observed reorg
+    pub async fn insert_reorg(
+        &self,
+        first_block: i64,
+        depth: i64,
+        replaced_blocks: &str,
+    ) -> Result<()> {
+        sqlx::query("INSERT INTO reorgs (first_block, depth, replaced_blocks) VALUES (?, ?, ?)")
+            .bind(first_block)
+            .bind(depth)
+            .bind(replaced_blocks)
+            .execute(&self.pool)
+            .await
+            .context("Failed to insert reorg")?;
+
+        Ok(())
+    }
+
+    /// Get the number of reorgs per depth, shallowest first
+    pub async fn get_reorg_depth_histogram(&self) -> Result<Vec<(i64, i64)>> {
+        let histogram =
+            sqlx::query_as("SELECT depth, COUNT(*) FROM reorgs GROUP BY depth ORDER BY depth")
+                .fetch_all(&self.pool)
+                .await
+                .context("Failed to query reorg depth histogram")?;
+
+        Ok(histogram)
+    }
+
+    /// Get the most recent reorgs, newest first
+    pub async fn get_recent_reorgs(&self, limit: i64) -> Result<Vec<Reorg>> {
+        let reorgs = sqlx::query_as::<_, Reorg>("SELECT * FROM reorgs ORDER BY id DESC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to query recent reorgs")?;
+
+        Ok(reorgs)
+    }
+
+    /// Record a block range uploaded by the dataset publisher
+    pub async fn insert_published_range(
+        &self,
+        target: &str,
+        format: &str,
+        from_block: i64,
+        to_block: i64,
+        files: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO published_ranges (target, format, from_block, to_block, files) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(target)
+        .bind(format)
+        .bind(from_block)
+        .bind(to_block)
+        .bind(files)
+        .execute(&self.pool)
+        .await
+        .context("Failed to insert published range")?;
+
+        Ok(())
+    }
+
+    /// Get the block ranges published to a target in a format, oldest first
+    pub async fn get_published_ranges(
+        &self,
+        target: &str,
+        format: &str,
+    ) -> Result<Vec<PublishedRange>> {
+        let ranges = sqlx::query_as::<_, PublishedRange>(
+            "SELECT * FROM published_ranges WHERE target = ? AND format = ? ORDER BY from_block",
+        )
+        .bind(target)
+        .bind(format)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query published ranges")?;
+
+        Ok(ranges)
+    }
+
+    /// Get the number of indexed blocks in a range
+    pub async fn count_blocks_in_range(&self, from_block: i64, to_block: i64) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM blocks WHERE number BETWEEN ? AND ?")
+            .bind(from_block)
+            .bind(to_block)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count blocks in range")
+    }
+
+    /// Lowest block number referenced by a block data table, None when it is empty
+    pub async fn get_lowest_block_number_in(&self, table: &str) -> Result<Option<i64>> {
+        let lowest: Option<i64> = sqlx::query_scalar(&format!(
+            "SELECT MIN({}) FROM {}",
+            block_column(table),
+            table
+        ))
+        .fetch_one(&self.pool)
+        .await
+        .context(format!("Failed to query lowest block of {}", table))?;
+
+        Ok(lowest)
+    }
+
+    /// Delete the rows of `tables` belonging to blocks `from..to` in a single transaction,
+    /// copying them into the SQLite file at `archive` first when given. Tables are cleared
+    /// in order, so dependent tables must come before the blocks they reference.
+    /// Returns the rows deleted per table.
+    pub async fn prune_block_range(
+        &self,
+        tables: &[&str],
+        from: i64,
+        to: i64,
+        archive: Option<&str>,
+    ) -> Result<Vec<(String, u64)>> {
+        let mut conn = self.pool.acquire().await?;
+        if let Some(path) = archive {
+            // Connections are opened without the create flag, which ATTACH inherits
+            if !Sqlite::database_exists(path).await.unwrap_or(false) {
+                Sqlite::create_database(path)
+                    .await
+                    .context(format!("Failed to create archive database {}", path))?;
+            }
+            sqlx::query("ATTACH DATABASE ? AS archive")
+                .bind(path)
+                .execute(&mut *conn)
+                .await
+                .context(format!("Failed to attach archive database {}", path))?;
+        }
+
+        let pruned = async {
+            let mut tx = conn.begin().await?;
+            let mut deleted = Vec::new();
+
+            for &table in tables {
+                let column = block_column(table);
+                if archive.is_some() {
+                    let columns = archive_columns(&mut tx, table).await?;
+                    sqlx::query(&format!(
+                        "INSERT INTO archive.{table} ({columns}) SELECT {columns} FROM main.{table} WHERE {column} >= ? AND {column} < ?"
+                    ))
+                    .bind(from)
+                    .bind(to)
+                    .execute(&mut *tx)
+                    .await
+                    .context(format!("Failed to archive {}", table))?;
+                }
+
+                let result = sqlx::query(&format!(
+                    "DELETE FROM main.{table} WHERE {column} >= ? AND {column} < ?"
+                ))
+                .bind(from)
+                .bind(to)
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to prune {}", table))?;
+                deleted.push((table.to_string(), result.rows_affected()));
+            }
+
+            tx.commit().await?;
+            anyhow::Ok(deleted)
+        }
+        .await;
+
+        if archive.is_some() {
+            let detached = sqlx::query("DETACH DATABASE archive")
+                .execute(&mut *conn)
+                .await;
+            if let Err(e) = detached {
+                // A connection left with the archive attached would fail the next run
+                error!("Failed to detach archive database: {}", e);
+                conn.close_on_drop();
+            }
+        }
+
+        pruned
+    }
+
+    /// Rebuild the database file to return the space of deleted rows to the filesystem.
+    /// Writers wait for it to finish.
+    pub async fn vacuum(&self) -> Result<()> {
+        sqlx::query("VACUUM")
+            .execute(&self.pool)
+            .await
+            .context("Failed to vacuum database")?;
+
+        Ok(())
+    }
+
+    /// Get block by number
+    pub async fn get_block_by_number(&self, number: i64) -> Result<Option<Block>> {
+        for pool in self.pools_for_block(number) {
+            let result = sqlx::query_as::<_, Block>(
+                r#"
+                SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+                       miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+                       nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+                       slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+                       eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+                       attestation_count, slashing_count
+                FROM blocks
+                WHERE number = ?
+                "#,
+            )
+            .bind(number)
+            .fetch_optional(&pool)
+            .await
+            .context("Failed to query block by number")?;
+
+            if result.is_some() {
+                return Ok(result);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Get block by hash
+    pub async fn get_block_by_hash(&self, hash: &str) -> Result<Option<Block>> {
+        let result = sqlx::query_as::<_, Block>(
+            r#"
+            SELECT number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+                   miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+                   nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+                   slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+                   eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+                   attestation_count, slashing_count
+            FROM blocks
+            WHERE hash = ?
+            "#,
+        )
+        .bind(hash)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query block by hash")?;
+
+        Ok(result)
+    }
+
+    /// Get transactions by block number
+    pub async fn get_transactions_by_block(&self, block_number: i64) -> Result<Vec<Transaction>> {
+        let mut result = Vec::new();
+        for pool in self.pools_for_block(block_number) {
+            result = sqlx::query_as::<_, Transaction>(
+                r#"
+                SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
+                FROM transactions
+                WHERE block_number = ?
+                ORDER BY transaction_index
+                "#,
+            )
+            .bind(block_number)
+            .fetch_all(&pool)
+            .await
+            .context("Failed to query transactions by block")?;
+
+            if !result.is_empty() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get the receipts of the transactions of a block
+    pub async fn get_receipts_by_block(&self, block_number: i64) -> Result<Vec<Receipt>> {
+        let mut result = Vec::new();
+        for pool in self.pools_for_block(block_number) {
+            result = sqlx::query_as::<_, Receipt>(
+                r#"
+                SELECT transaction_hash, block_number, cumulative_gas_used, effective_gas_price,
+                       contract_address, logs_bloom, transaction_type
+                FROM transaction_receipts
+                WHERE block_number = ?
+                ORDER BY cumulative_gas_used
+                "#,
+            )
+            .bind(block_number)
+            .fetch_all(&pool)
+            .await
+            .context("Failed to query receipts by block")?;
+
+            if !result.is_empty() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get the logs of a block
+    pub async fn get_logs_by_block(&self, block_number: i64) -> Result<Vec<Log>> {
+        let mut result = Vec::new();
+        for pool in self.pools_for_block(block_number) {
+            result = sqlx::query_as::<_, Log>(
+                r#"
+                SELECT id, transaction_hash, block_number, address, topic0, topic1, topic2, topic3, data, log_index
+                FROM logs
+                WHERE block_number = ?
+                ORDER BY log_index
+                "#,
+            )
+            .bind(block_number)
+            .fetch_all(&pool)
+            .await
+            .context("Failed to query logs by block")?;
+
+            if !result.is_empty() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get token transfers matching a filter, oldest first, with the total number of matches
+    pub async fn get_filtered_token_transfers(
+        &self,
+        filter: &TokenTransferFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<TokenTransfer>, i64)> {
+        let push_filters = |query: &mut sqlx::QueryBuilder<'_, Sqlite>| {
+            query.push(" WHERE 1 = 1");
+            for (column, value) in [
+                ("token_address", &filter.token_address),
+                ("from_address", &filter.from_address),
+                ("to_address", &filter.to_address),
+            ] {
+                if let Some(value) = value {
+                    query
+                        .push(format!(" AND {} = ", column))
+                        .push_bind(value.clone());
+                }
+            }
+            if let Some(min_amount) = &filter.min_amount {
+                query
+                    .push(" AND amount_sort >= ")
+                    .push_bind(sortable_amount(min_amount));
+            }
+            if let Some(max_amount) = &filter.max_amount {
+                query
+                    .push(" AND amount_sort <= ")
+                    .push_bind(sortable_amount(max_amount));
+            }
+            if let Some(from_block) = filter.from_block {
+                query.push(" AND block_number >= ").push_bind(from_block);
+            }
+            if let Some(to_block) = filter.to_block {
+                query.push(" AND block_number <= ").push_bind(to_block);
+            }
+        };
+
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT id, transaction_hash, token_address, from_address, to_address, amount, block_number, token_type, token_id, log_index, created_at FROM token_transfers",
+        );
+        push_filters(&mut query);
+        query
+            .push(" ORDER BY block_number ASC, id ASC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        let transfers = query
+            .build_query_as::<TokenTransfer>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch filtered token transfers")?;
+
+        let mut count = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM token_transfers");
+        push_filters(&mut count);
+        let total: (i64,) = count
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count filtered token transfers")?;
+
+        Ok((transfers, total.0))
+    }
+
+    /// Get the token transfers of a block
+    pub async fn get_token_transfers_by_block(
+        &self,
+        block_number: i64,
+    ) -> Result<Vec<TokenTransfer>> {
+        let mut result = Vec::new();
+        for pool in self.pools_for_block(block_number) {
+            result = sqlx::query_as::<_, TokenTransfer>(
+                r#"
+                SELECT id, transaction_hash, token_address, from_address, to_address, amount,
+                       block_number, token_type, token_id, log_index, created_at
+                FROM token_transfers
+                WHERE block_number = ?
+                ORDER BY id
+                "#,
+            )
+            .bind(block_number)
+            .fetch_all(&pool)
+            .await
+            .context("Failed to query token transfers by block")?;
+
+            if !result.is_empty() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get transaction by hash
+    pub async fn get_transaction_by_hash(&self, hash: &str) -> Result<Option<Transaction>> {
+        let query = |pool: Pool<Sqlite>| async move {
+            sqlx::query_as::<_, Transaction>(
+                r#"
+                SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit,
+                       input, input_size
+                FROM transactions
+                WHERE hash = ?
+                "#,
+            )
+            .bind(hash)
+            .fetch_optional(&pool)
+            .await
+            .context("Failed to query transaction by hash")
+        };
+
+        let mut result = query(self.pool.clone()).await?;
+        if result.is_none() {
+            for shard in self.shards_for_transaction(hash).await? {
+                result = query(shard).await?;
+                if result.is_some() {
+                    break;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get the page of logs matching eth_getLogs-style filters after `cursor`, oldest first
+    pub async fn get_filtered_logs(
+        &self,
+        params: &LogFilterParams,
+        cursor: Option<LogCursor>,
+    ) -> Result<Vec<Log>> {
+        let addresses = params.addresses();
+        let topics = params.topics();
+
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT id, transaction_hash, block_number, address, topic0, topic1, topic2, topic3, data, log_index FROM logs WHERE 1 = 1",
+        );
+        if let Some(cursor) = cursor {
+            query
+                .push(" AND (block_number, log_index) > (")
+                .push_bind(cursor.block_number)
+                .push(", ")
+                .push_bind(cursor.log_index)
+                .push(")");
+        }
+        if let Some(from_block) = params.from_block {
+            query.push(" AND block_number >= ").push_bind(from_block);
+        }
+        if let Some(to_block) = params.to_block {
+            query.push(" AND block_number <= ").push_bind(to_block);
+        }
+        if !addresses.is_empty() {
+            query.push(" AND address IN (");
+            let mut values = query.separated(", ");
+            for address in &addresses {
+                values.push_bind(address.clone());
+            }
+            query.push(")");
+        }
+        for (position, alternatives) in topics.iter().enumerate() {
+            if alternatives.is_empty() {
+                continue;
+            }
+            query.push(format!(" AND topic{} IN (", position));
+            let mut values = query.separated(", ");
+            for topic in alternatives {
+                values.push_bind(topic.clone());
+            }
+            query.push(")");
+        }
+        query
+            .push(" ORDER BY block_number ASC, log_index ASC LIMIT ")
+            .push_bind(params.limit());
+
+        let logs = query
+            .build_query_as::<Log>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch filtered logs")?;
+
+        Ok(logs)
+    }
+
+    /// Get logs by transaction hash
+    pub async fn get_logs_by_transaction(&self, tx_hash: &str) -> Result<Vec<Log>> {
+        let query = |pool: Pool<Sqlite>| async move {
+            sqlx::query_as::<_, Log>(
+                r#"
+                SELECT id, transaction_hash, block_number, address, topic0, topic1, topic2, topic3, data, log_index
+                FROM logs
+                WHERE transaction_hash = ?
+                ORDER BY log_index
+                "#,
+            )
+            .bind(tx_hash)
+            .fetch_all(&pool)
+            .await
+            .context("Failed to query logs by transaction")
+        };
+
+        let mut result = query(self.pool.clone()).await?;
+        if result.is_empty() {
+            for shard in self.shards_for_transaction(tx_hash).await? {
+                result = query(shard).await?;
+                if !result.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get account by address
+    pub async fn get_account_by_address(&self, address: &str) -> Result<Option<Account>> {
+        let result = sqlx::query_as::<_, Account>(
+            r#"
+            SELECT address, balance, transaction_count, first_seen_block, last_seen_block, balance_at_block,
+                   account_type
+            FROM accounts
+            WHERE address = ?
+            "#,
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to query account by address")?;
+
+        Ok(result)
+    }
+
+    /// Get the most recently seen accounts, newest first
+    pub async fn get_recently_seen_accounts(&self, limit: i64) -> Result<Vec<Account>> {
+        let accounts = sqlx::query_as::<_, Account>(
+            r#"
+            SELECT address, balance, transaction_count, first_seen_block, last_seen_block, balance_at_block,
+                   account_type
+            FROM accounts
+            ORDER BY last_seen_block DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query recently seen accounts")?;
+
+        Ok(accounts)
+    }
+
+    /// Get recent blocks with pagination
+    pub async fn get_recent_blocks(&self, limit: i64, offset: i64) -> Result<Vec<Block>> {
+        let result = sqlx::query_as::<_, Block>(
+            r#"
+            SELECT 
+                number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+                miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+                nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+                slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+                eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+                attestation_count, slashing_count
+            FROM blocks
+            ORDER BY number DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query recent blocks")?;
+
+        Ok(result)
+    }
+
+    /// Get recent transactions with pagination
+    pub async fn get_recent_transactions(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Transaction>> {
+        let result = sqlx::query_as::<_, Transaction>(
+            r#"
+            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
+            FROM transactions
+            ORDER BY block_number DESC, transaction_index DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query recent transactions")?;
+
+        Ok(result)
+    }
+
+    /// Get the transactions of blocks from `from_block` onwards, oldest first
+    pub async fn get_transactions_from_block(&self, from_block: i64) -> Result<Vec<Transaction>> {
+        let result = sqlx::query_as::<_, Transaction>(
+            r#"
+            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
+            FROM transactions
+            WHERE block_number >= ?
+            ORDER BY block_number, transaction_index
+            "#,
+        )
+        .bind(from_block)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query transactions from block")?;
+
+        Ok(result)
+    }
+
+    /// Get the token transfers of blocks from `from_block` onwards, oldest first
+    pub async fn get_token_transfers_from_block(
+        &self,
+        from_block: i64,
+    ) -> Result<Vec<TokenTransfer>> {
+        let result = sqlx::query_as::<_, TokenTransfer>(
             r#"
-            SELECT 
-                number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
-                miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
-                nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
-                slot, proposer_index, epoch, slot_root, parent_root, beacon_deposit_count,
-                graffiti, randao_reveal, randao_mix
-            FROM blocks
-            ORDER BY number DESC
-            LIMIT ? OFFSET ?
+            SELECT id, transaction_hash, token_address, from_address, to_address, amount,
+                   block_number, token_type, token_id, log_index, created_at
+            FROM token_transfers
+            WHERE block_number >= ?
+            ORDER BY block_number, id
             "#,
         )
-        .bind(limit)
-        .bind(offset)
+        .bind(from_block)
         .fetch_all(&self.pool)
         .await
-        .context("Failed to query recent blocks")?;
+        .context("Failed to query token transfers from block")?;
 
         Ok(result)
     }
 
-    /// Get recent transactions with pagination
-    pub async fn get_recent_transactions(
+    /// Get the blocks below `before` (exclusive), newest first.
+    /// Keyset counterpart of `get_recent_blocks` that stays fast deep into the chain.
+    pub async fn get_blocks_before(&self, before: i64, limit: i64) -> Result<Vec<Block>> {
+        self.collect_before(
+            before,
+            limit,
+            |block: &Block| block.number,
+            |block: &Block| block.number,
+            |pool| async move {
+                sqlx::query_as::<_, Block>(
+                    r#"
+                    SELECT
+                        number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count,
+                        miner, difficulty, size_bytes, base_fee_per_gas, extra_data, state_root,
+                        nonce, withdrawals_root, blob_gas_used, excess_blob_gas, withdrawal_count,
+                        slot, proposer_index, epoch, slot_root, parent_root, block_deposit_count,
+                        eth1_deposit_count, graffiti, randao_reveal, randao_mix, sync_participation,
+                        attestation_count, slashing_count
+                    FROM blocks
+                    WHERE number < ?
+                    ORDER BY number DESC
+                    LIMIT ?
+                    "#,
+                )
+                .bind(before)
+                .bind(limit)
+                .fetch_all(&pool)
+                .await
+                .context("Failed to query blocks before cursor")
+            },
+        )
+        .await
+    }
+
+    /// Get the transactions ordered after `cursor`, newest first.
+    /// Keyset counterpart of `get_recent_transactions` that stays fast deep into the chain.
+    pub async fn get_transactions_before(
+        &self,
+        cursor: TransactionCursor,
+        limit: i64,
+    ) -> Result<Vec<Transaction>> {
+        self.collect_before(
+            // The cursor's own block may still hold transactions below its index
+            cursor.block_number.saturating_add(1),
+            limit,
+            |tx: &Transaction| tx.block_number,
+            |tx: &Transaction| (tx.block_number, tx.transaction_index),
+            |pool| async move {
+                sqlx::query_as::<_, Transaction>(
+                    r#"
+                    SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
+                    FROM transactions
+                    WHERE (block_number, transaction_index) < (?, ?)
+                    ORDER BY block_number DESC, transaction_index DESC
+                    LIMIT ?
+                    "#,
+                )
+                .bind(cursor.block_number)
+                .bind(cursor.transaction_index)
+                .bind(limit)
+                .fetch_all(&pool)
+                .await
+                .context("Failed to query transactions before cursor")
+            },
+        )
+        .await
+    }
+
+    /// Get the transactions calling the method with the given 4-byte selector, newest first,
+    /// starting after `cursor` when given
+    pub async fn get_transactions_by_method(
         &self,
+        method_id: &str,
+        cursor: Option<TransactionCursor>,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<Transaction>> {
+        let cursor = cursor.unwrap_or(TransactionCursor {
+            block_number: i64::MAX,
+            transaction_index: i64::MAX,
+        });
         let result = sqlx::query_as::<_, Transaction>(
             r#"
-            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index
+            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
             FROM transactions
+            WHERE method_id = ? AND (block_number, transaction_index) < (?, ?)
             ORDER BY block_number DESC, transaction_index DESC
             LIMIT ? OFFSET ?
             "#,
         )
+        .bind(method_id)
+        .bind(cursor.block_number)
+        .bind(cursor.transaction_index)
         .bind(limit)
         .bind(offset)
         .fetch_all(&self.pool)
         .await
-        .context("Failed to query recent transactions")?;
+        .context("Failed to query transactions by method")?;
 
         Ok(result)
     }
 
+    /// Get the number of transactions calling the method with the given selector
+    pub async fn get_transaction_count_by_method(&self, method_id: &str) -> Result<i64> {
+        let result: (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM transactions WHERE method_id = ?")
+                .bind(method_id)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to query transaction count by method")?;
+
+        Ok(result.0)
+    }
+
     /// Get total number of blocks
     pub async fn get_block_count(&self) -> Result<i64> {
         let result: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM blocks")
@@ -792,6 +4834,179 @@ impl DatabaseService {
         Ok(withdrawals)
     }
 
+    /// Get withdrawals received by an address, newest first
+    pub async fn get_withdrawals_by_address(
+        &self,
+        address: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Withdrawal>> {
+        self.get_withdrawals(Some(address), None, limit, offset)
+            .await
+    }
+
+    /// Get lifetime withdrawal count and total amount (in Gwei) received by an address
+    pub async fn get_withdrawal_totals_by_address(&self, address: &str) -> Result<(i64, i64)> {
+        self.get_withdrawal_totals(Some(address), None).await
+    }
+
+    /// Get withdrawals of a validator, newest first
+    pub async fn get_withdrawals_by_validator(
+        &self,
+        validator_index: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Withdrawal>> {
+        self.get_withdrawals(None, Some(validator_index), limit, offset)
+            .await
+    }
+
+    /// Get lifetime withdrawal count and total amount (in Gwei) of a validator
+    pub async fn get_withdrawal_totals_by_validator(
+        &self,
+        validator_index: i64,
+    ) -> Result<(i64, i64)> {
+        self.get_withdrawal_totals(None, Some(validator_index))
+            .await
+    }
+
+    /// Get withdrawals received by an address and/or made by a validator, newest first
+    pub async fn get_withdrawals(
+        &self,
+        address: Option<&str>,
+        validator_index: Option<i64>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Withdrawal>> {
+        let mut query = sqlx::QueryBuilder::new("SELECT * FROM withdrawals");
+        push_withdrawal_filters(&mut query, address, validator_index);
+        query
+            .push(" ORDER BY block_number DESC, withdrawal_index DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let withdrawals = query
+            .build_query_as::<Withdrawal>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch withdrawals")?;
+
+        Ok(withdrawals)
+    }
+
+    /// Get the count and total amount (in Gwei) of the withdrawals matching the filters of
+    /// `get_withdrawals`
+    pub async fn get_withdrawal_totals(
+        &self,
+        address: Option<&str>,
+        validator_index: Option<i64>,
+    ) -> Result<(i64, i64)> {
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT COUNT(*), COALESCE(SUM(CAST(amount AS INTEGER)), 0) FROM withdrawals",
+        );
+        push_withdrawal_filters(&mut query, address, validator_index);
+
+        let totals: (i64, i64) = query
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to query withdrawal totals")?;
+
+        Ok(totals)
+    }
+
+    /// Get beacon deposits made in a block
+    pub async fn get_deposits_by_block(&self, block_number: i64) -> Result<Vec<Deposit>> {
+        sqlx::query_as::<_, Deposit>(
+            "SELECT * FROM deposits WHERE block_number = ? ORDER BY log_index",
+        )
+        .bind(block_number)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch deposits")
+    }
+
+    /// Get beacon deposits, optionally made by a depositor and/or for a validator pubkey,
+    /// newest first, with the index of their validator when it was synced
+    pub async fn get_deposits(
+        &self,
+        depositor: Option<&str>,
+        pubkey: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Deposit>> {
+        let mut query = sqlx::QueryBuilder::new(
+            r#"
+            SELECT deposits.*, validators.validator_index
+            FROM deposits
+            LEFT JOIN validators ON validators.pubkey = deposits.pubkey
+            "#,
+        );
+        push_deposit_filters(&mut query, depositor, pubkey);
+        query
+            .push(" ORDER BY deposits.block_number DESC, deposits.log_index DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let deposits = query
+            .build_query_as::<Deposit>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch deposits")?;
+
+        Ok(deposits)
+    }
+
+    /// Get the count and total amount (in Gwei) of the deposits matching the filters of
+    /// `get_deposits`
+    pub async fn get_deposit_totals(
+        &self,
+        depositor: Option<&str>,
+        pubkey: Option<&str>,
+    ) -> Result<(i64, i64)> {
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT COUNT(*), COALESCE(SUM(CAST(amount AS INTEGER)), 0) FROM deposits",
+        );
+        push_deposit_filters(&mut query, depositor, pubkey);
+
+        let totals: (i64, i64) = query
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to query deposit totals")?;
+
+        Ok(totals)
+    }
+
+    /// Get the number of indexed blocks, the stored transactions and the sum of declared
+    /// transaction counts within an inclusive block range
+    pub async fn get_range_indexing_counts(
+        &self,
+        from_block: i64,
+        to_block: i64,
+    ) -> Result<(i64, i64, i64)> {
+        let (blocks, declared): (i64, Option<i64>) = sqlx::query_as(
+            "SELECT COUNT(*), SUM(transaction_count) FROM blocks WHERE number BETWEEN ? AND ?",
+        )
+        .bind(from_block)
+        .bind(to_block)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to query indexed blocks in range")?;
+
+        let (indexed,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM transactions WHERE block_number BETWEEN ? AND ?")
+                .bind(from_block)
+                .bind(to_block)
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to query indexed transactions in range")?;
+
+        Ok((blocks, indexed, declared.unwrap_or(0)))
+    }
+
     /// Get current block transaction information
     pub async fn get_current_block_transaction_info(&self) -> Result<(i64, i64)> {
         // Get the latest block number
@@ -891,6 +5106,7 @@ impl DatabaseService {
             match status.as_str() {
                 "success" => where_clauses.push("status = 1"),
                 "failed" => where_clauses.push("status = 0"),
+                "out_of_gas" => where_clauses.push("status = 0 AND gas_used >= gas_limit"),
                 _ => {} // "all" or unknown - no filter
             }
         }
@@ -912,7 +5128,7 @@ impl DatabaseService {
 
         let query = format!(
             r#"
-            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index
+            SELECT hash, block_number, from_address, to_address, value, gas_used, gas_price, status, transaction_index, method_id, method_name, gas_limit
             FROM transactions
             {}
             ORDER BY block_number DESC, transaction_index DESC
@@ -959,164 +5175,399 @@ impl DatabaseService {
         result.context("Failed to query filtered transactions")
     }
 
-    /// Get accounts with filtering
+    /// Get accounts with filtering, together with the number of matching accounts
     pub async fn get_filtered_accounts(
         &self,
         filters: &crate::database::AccountFilterParams,
-    ) -> Result<Vec<Account>> {
-        // Build the base query with filtering
-        let mut where_clauses = Vec::new();
+    ) -> Result<(Vec<Account>, i64)> {
+        let mut query = sqlx::QueryBuilder::new(
+            r#"
+            SELECT address, balance, transaction_count, first_seen_block, last_seen_block,
+                   balance_at_block, account_type
+            FROM accounts
+            "#,
+        );
+        push_account_filters(&mut query, filters)?;
 
-        // Add account type filter
-        if let Some(account_type) = &filters.account_type {
-            match account_type.as_str() {
-                "eoa" | "contract" | "unknown" => {
-                    where_clauses.push("account_type = ?");
-                }
-                _ => {} // "all" or unknown - no filter
-            }
+        let direction = if filters.order.as_deref() == Some("asc") {
+            "ASC"
+        } else {
+            "DESC"
+        };
+        let order_clause = match filters.sort.as_deref().unwrap_or("last_activity") {
+            "balance" => format!("balance_sort {}", direction),
+            "tx_count" => format!("transaction_count {}", direction),
+            "first_seen" => format!("first_seen_block {}", direction),
+            _ => format!("last_seen_block {}", direction),
+        };
+        query
+            .push(format!(" ORDER BY {}, address LIMIT ", order_clause))
+            .push_bind(filters.limit())
+            .push(" OFFSET ")
+            .push_bind(filters.offset());
+
+        let accounts = query
+            .build_query_as::<Account>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to query filtered accounts")?;
+
+        let mut count = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM accounts");
+        push_account_filters(&mut count, filters)?;
+        let total: i64 = count
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count filtered accounts")?;
+
+        Ok((accounts, total))
+    }
+}
+
+/// Open the SQLite file at `path`, creating it and its directory when missing, and bring
+/// its schema up to date
+async fn open_database(path: &str, max_connections: u32) -> Result<Pool<Sqlite>> {
+    // Create database directory if needed
+    if let Some(db_path) = Path::new(path).parent()
+        && !db_path.as_os_str().is_empty()
+        && !db_path.exists()
+    {
+        std::fs::create_dir_all(db_path).context("Failed to create database directory")?;
+        info!("Created database directory: {:?}", db_path);
+    }
+
+    // Check if database exists, create if not
+    if !Sqlite::database_exists(path).await.unwrap_or(false) {
+        info!("Database {} does not exist, creating...", path);
+        Sqlite::create_database(path)
+            .await
+            .context("Failed to create database")?;
+    }
+
+    // Connect to the database
+    let pool = PoolOptions::new()
+        .max_connections(max_connections)
+        .connect(path)
+        .await
+        .context("Failed to connect to database")?;
+
+    // Run migrations
+    info!("Running database migrations on {}...", path);
+    sqlx::migrate!("./src/database/migrations")
+        .run(&pool)
+        .await
+        .context("Failed to run migrations")?;
+
+    Ok(pool)
+}
+
+/// Column holding the block number of a block data table
+fn block_column(table: &str) -> &'static str {
+    if table == "blocks" {
+        "number"
+    } else {
+        "block_number"
+    }
+}
+
+/// Append the WHERE clause of the account filters. Errors on balances that are not
+/// decimal wei amounts.
+fn push_account_filters(
+    query: &mut sqlx::QueryBuilder<'_, Sqlite>,
+    filters: &AccountFilterParams,
+) -> Result<()> {
+    query.push(" WHERE 1 = 1");
+
+    match filters.account_type.as_deref() {
+        Some(account_type @ ("eoa" | "contract")) => {
+            query
+                .push(" AND account_type = ")
+                .push_bind(account_type.to_string());
         }
+        Some("unknown") => {
+            query.push(" AND account_type IS NULL");
+        }
+        _ => {} // "all" or unrecognized, no filter
+    }
+
+    if let Some(min_tx_count) = filters.min_tx_count {
+        query
+            .push(" AND transaction_count >= ")
+            .push_bind(min_tx_count);
+    }
+    if let Some(max_tx_count) = filters.max_tx_count {
+        query
+            .push(" AND transaction_count <= ")
+            .push_bind(max_tx_count);
+    }
+
+    for (bound, operator) in [(&filters.min_balance, ">="), (&filters.max_balance, "<=")] {
+        let Some(bound) = bound.as_deref().map(str::trim) else {
+            continue;
+        };
+        anyhow::ensure!(
+            !bound.is_empty() && bound.bytes().all(|b| b.is_ascii_digit()),
+            "Balance bound {} is not a decimal wei amount",
+            bound
+        );
+        let bound = match bound.trim_start_matches('0') {
+            "" => "0",
+            digits => digits,
+        };
+
+        query
+            .push(format!(" AND balance_sort {} ", operator))
+            .push_bind(sortable_amount(bound));
+    }
+
+    Ok(())
+}
 
-        // Add transaction count range filters
-        if filters.min_tx_count.is_some() {
-            where_clauses.push("transaction_count >= ?");
+/// Append the WHERE clause of the block fee recipient filters, addresses compared in
+/// lowercase
+fn push_fee_recipient_filters(
+    query: &mut sqlx::QueryBuilder<'_, Sqlite>,
+    filters: &FeeRecipientParams,
+) {
+    query.push(" WHERE 1 = 1");
+    for (column, value) in [
+        ("fee_recipient", &filters.fee_recipient),
+        ("proposer_payee", &filters.proposer_payee),
+    ] {
+        if let Some(value) = value {
+            query
+                .push(format!(" AND {} = ", column))
+                .push_bind(value.trim().to_lowercase());
         }
+    }
+    if let Some(proposer_index) = filters.proposer_index {
+        query
+            .push(" AND proposer_index = ")
+            .push_bind(proposer_index);
+    }
+    if let Some(mismatch) = filters.mismatch {
+        query.push(" AND mismatch = ").push_bind(mismatch);
+    }
+}
+
+/// Append the WHERE clause of the withdrawal filters
+fn push_withdrawal_filters(
+    query: &mut sqlx::QueryBuilder<'_, Sqlite>,
+    address: Option<&str>,
+    validator_index: Option<i64>,
+) {
+    query.push(" WHERE 1 = 1");
+    if let Some(address) = address {
+        query.push(" AND address = ").push_bind(address.to_string());
+    }
+    if let Some(validator_index) = validator_index {
+        query
+            .push(" AND validator_index = ")
+            .push_bind(validator_index);
+    }
+}
+
+/// Append the WHERE clause of the deposit filters
+fn push_deposit_filters(
+    query: &mut sqlx::QueryBuilder<'_, Sqlite>,
+    depositor: Option<&str>,
+    pubkey: Option<&str>,
+) {
+    query.push(" WHERE 1 = 1");
+    if let Some(depositor) = depositor {
+        query
+            .push(" AND deposits.depositor = ")
+            .push_bind(depositor.to_string());
+    }
+    if let Some(pubkey) = pubkey {
+        query
+            .push(" AND deposits.pubkey = ")
+            .push_bind(pubkey.to_string());
+    }
+}
+
+/// Bind the balance bounds, the keyset of the `after` row and the limit of a holder export
+/// query, in the order of its placeholders
+fn bind_holder_range<'q>(
+    query: QueryAs<'q, Sqlite, HolderBalance, SqliteArguments<'q>>,
+    range: &HolderExportRange,
+) -> QueryAs<'q, Sqlite, HolderBalance, SqliteArguments<'q>> {
+    let after_sort = range
+        .after
+        .as_ref()
+        .map(|(balance, _)| sortable_amount(balance));
+    query
+        .bind(sortable_amount(&range.min_balance))
+        .bind(after_sort.clone())
+        .bind(after_sort.clone())
+        .bind(after_sort)
+        .bind(range.after.as_ref().map(|(_, address)| address.clone()))
+        .bind(range.limit.unwrap_or(-1))
+}
+
+/// Encoding of a decimal amount without leading zeros whose text order is its numeric
+/// order, matching the generated `*_sort` columns (token_transfers.amount_sort,
+/// accounts.balance_sort, token_balances.balance_sort, transactions.value_sort)
+fn sortable_amount(amount: &str) -> String {
+    format!("{:02}{}", amount.len(), amount)
+}
+
+/// Exclusive upper bound of the lowercase hex strings starting with `prefix`, 'g' sorting
+/// after every hex digit
+fn hex_prefix_end(prefix: &str) -> String {
+    format!("{}g", prefix)
+}
+
+/// FTS5 query matching documents with a word starting with each word of `query`, in any
+/// order. None when the query has no letters or digits.
+fn fts_prefix_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Create or extend an archive table so it has every column of its main table, whose
+/// schema may have grown since the last run. Returns the column list to copy.
+async fn archive_columns(conn: &mut sqlx::SqliteConnection, table: &str) -> Result<String> {
+    let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?, 'main')")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await
+        .context(format!("Failed to read columns of {}", table))?;
+    let archived: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM pragma_table_info(?, 'archive')")
+            .bind(table)
+            .fetch_all(&mut *conn)
+            .await
+            .context(format!("Failed to read archived columns of {}", table))?;
 
-        if filters.max_tx_count.is_some() {
-            where_clauses.push("transaction_count <= ?");
+    if archived.is_empty() {
+        sqlx::query(&format!(
+            "CREATE TABLE archive.{table} AS SELECT * FROM main.{table} WHERE 0"
+        ))
+        .execute(&mut *conn)
+        .await
+        .context(format!("Failed to create archive table {}", table))?;
+    } else {
+        for column in columns.iter().filter(|column| !archived.contains(column)) {
+            sqlx::query(&format!(
+                "ALTER TABLE archive.{} ADD COLUMN {}",
+                table, column
+            ))
+            .execute(&mut *conn)
+            .await
+            .context(format!(
+                "Failed to add {} to archive table {}",
+                column, table
+            ))?;
         }
+    }
 
-        let where_clause = if where_clauses.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_clauses.join(" AND "))
-        };
+    Ok(columns.join(", "))
+}
 
-        // Add sorting
-        let sort_field = filters.sort.as_deref().unwrap_or("last_activity");
-        let order = filters.order.as_deref().unwrap_or("desc");
-
-        let order_clause = match sort_field {
-            "balance" => format!(
-                "ORDER BY CAST(balance AS INTEGER) {}",
-                if order == "asc" { "ASC" } else { "DESC" }
-            ),
-            "tx_count" => format!(
-                "ORDER BY transaction_count {}",
-                if order == "asc" { "ASC" } else { "DESC" }
-            ),
-            "first_seen" => format!(
-                "ORDER BY first_seen {}",
-                if order == "asc" { "ASC" } else { "DESC" }
-            ),
-            "last_activity" => format!(
-                "ORDER BY last_activity {}",
-                if order == "asc" { "ASC" } else { "DESC" }
-            ),
-            _ => format!(
-                "ORDER BY last_activity {}",
-                if order == "asc" { "ASC" } else { "DESC" }
-            ),
-        };
+/// Take the contract calls of a block about to be replaced out of the interaction rollup
+async fn forget_contract_interactions(
+    conn: &mut sqlx::SqliteConnection,
+    block_number: i64,
+) -> Result<()> {
+    let calls = r#"
+        (SELECT to_address, from_address, COUNT(*) AS calls,
+                (SELECT timestamp - timestamp % 3600 FROM blocks WHERE number = ?) AS hour_start
+         FROM transactions
+         WHERE block_number = ? AND to_address IS NOT NULL AND method_id IS NOT NULL
+         GROUP BY to_address, from_address)
+    "#;
 
-        let query = format!(
-            r#"
-            SELECT address, balance, transaction_count, account_type, first_seen, last_activity
-            FROM accounts
-            {}
-            {}
-            LIMIT ? OFFSET ?
-            "#,
-            where_clause, order_clause
-        );
+    sqlx::query(&format!(
+        r#"
+        UPDATE contract_callers SET calls = contract_callers.calls - c.calls
+        FROM {calls} AS c
+        WHERE contract_callers.contract_address = c.to_address
+          AND contract_callers.caller_address = c.from_address
+          AND contract_callers.hour_start = c.hour_start
+        "#
+    ))
+    .bind(block_number)
+    .bind(block_number)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to remove replaced contract callers")?;
 
-        let limit = filters.limit();
-        let offset = filters.offset();
+    sqlx::query(&format!(
+        r#"
+        UPDATE contract_interactions SET calls = contract_interactions.calls - c.calls
+        FROM (SELECT to_address, hour_start, SUM(calls) AS calls FROM {calls} GROUP BY to_address, hour_start) AS c
+        WHERE contract_interactions.contract_address = c.to_address
+          AND contract_interactions.hour_start = c.hour_start
+        "#
+    ))
+    .bind(block_number)
+    .bind(block_number)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to remove replaced contract interactions")?;
 
-        // Execute query based on filters
-        let result = match (
-            &filters.account_type,
-            filters.min_tx_count,
-            filters.max_tx_count,
-        ) {
-            (Some(account_type), Some(min_tx), Some(max_tx))
-                if matches!(account_type.as_str(), "eoa" | "contract" | "unknown") =>
-            {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(account_type)
-                    .bind(min_tx)
-                    .bind(max_tx)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-            (Some(account_type), Some(min_tx), None)
-                if matches!(account_type.as_str(), "eoa" | "contract" | "unknown") =>
-            {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(account_type)
-                    .bind(min_tx)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-            (Some(account_type), None, Some(max_tx))
-                if matches!(account_type.as_str(), "eoa" | "contract" | "unknown") =>
-            {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(account_type)
-                    .bind(max_tx)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-            (Some(account_type), None, None)
-                if matches!(account_type.as_str(), "eoa" | "contract" | "unknown") =>
-            {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(account_type)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-            (None, Some(min_tx), Some(max_tx)) => {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(min_tx)
-                    .bind(max_tx)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-            (None, Some(min_tx), None) => {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(min_tx)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-            (None, None, Some(max_tx)) => {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(max_tx)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-            _ => {
-                sqlx::query_as::<_, Account>(&query)
-                    .bind(limit)
-                    .bind(offset)
-                    .fetch_all(&self.pool)
-                    .await
-            }
-        };
+    for table in ["contract_interactions", "contract_callers"] {
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE hour_start = (SELECT timestamp - timestamp % 3600 FROM blocks WHERE number = ?) AND calls <= 0",
+            table
+        ))
+        .bind(block_number)
+        .execute(&mut *conn)
+        .await
+        .context(format!("Failed to clear emptied {}", table))?;
+    }
 
-        result.context("Failed to query filtered accounts")
+    Ok(())
+}
+
+/// Copy the block row and transactions of a block into the history tables before they are
+/// rewritten, tagged with the reason
+async fn snapshot_block(
+    conn: &mut sqlx::SqliteConnection,
+    block_number: i64,
+    reason: &str,
+) -> Result<()> {
+    let block = sqlx::query_as::<_, Block>("SELECT * FROM blocks WHERE number = ?")
+        .bind(block_number)
+        .fetch_optional(&mut *conn)
+        .await
+        .context("Failed to read block for history")?;
+    if let Some(block) = block {
+        sqlx::query(
+            "INSERT INTO blocks_history (block_number, hash, reason, data) VALUES (?, ?, ?, ?)",
+        )
+        .bind(block.number)
+        .bind(&block.hash)
+        .bind(reason)
+        .bind(serde_json::to_string(&block)?)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to insert block history")?;
+    }
+
+    let transactions =
+        sqlx::query_as::<_, Transaction>("SELECT * FROM transactions WHERE block_number = ?")
+            .bind(block_number)
+            .fetch_all(&mut *conn)
+            .await
+            .context("Failed to read transactions for history")?;
+    for transaction in transactions {
+        sqlx::query(
+            "INSERT INTO transactions_history (hash, block_number, reason, data) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&transaction.hash)
+        .bind(transaction.block_number)
+        .bind(reason)
+        .bind(serde_json::to_string(&transaction)?)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to insert transaction history")?;
     }
+
+    Ok(())
 }
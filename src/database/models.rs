@@ -27,15 +27,22 @@ pub struct Block {
     pub withdrawal_count: Option<i64>,    // Number of withdrawals in block
 
     // Beacon Chain fields (requires separate API connection)
-    pub slot: Option<i64>,                 // Beacon chain slot
-    pub proposer_index: Option<i64>,       // Validator proposer index
-    pub epoch: Option<i64>,                // Beacon chain epoch
-    pub slot_root: Option<String>,         // Slot root hash
-    pub parent_root: Option<String>,       // Parent root hash
-    pub beacon_deposit_count: Option<i64>, // Beacon chain deposit count
-    pub graffiti: Option<String>,          // Proposer graffiti
-    pub randao_reveal: Option<String>,     // Randao reveal signature
-    pub randao_mix: Option<String>,        // Block randomness
+    pub slot: Option<i64>,                // Beacon chain slot
+    pub proposer_index: Option<i64>,      // Validator proposer index
+    pub epoch: Option<i64>,               // Beacon chain epoch
+    pub slot_root: Option<String>,        // Slot root hash
+    pub parent_root: Option<String>,      // Parent root hash
+    pub block_deposit_count: Option<i64>, // Deposits included in this beacon block
+    pub eth1_deposit_count: Option<i64>,  // Cumulative deposit contract count seen by this block
+    pub graffiti: Option<String>,         // Proposer graffiti
+    pub randao_reveal: Option<String>,    // Randao reveal signature
+    pub randao_mix: Option<String>,       // Block randomness
+    #[sqlx(default)]
+    pub sync_participation: Option<f64>, // Sync committee signatures in the sync aggregate, as a percentage
+    #[sqlx(default)]
+    pub attestation_count: Option<i64>, // Attestations included in this beacon block
+    #[sqlx(default)]
+    pub slashing_count: Option<i64>, // Proposer and attester slashings included in this beacon block
 }
 
 impl Block {
@@ -83,23 +90,10 @@ impl Block {
         }
     }
 
-    /// Get block status based on block age and network finality
-    pub fn status(&self, latest_block: i64) -> String {
-        let block_age = latest_block - self.number;
-
-        if block_age >= 32 {
-            // Block is older than 32 blocks (2 epochs), considered finalized
-            "finalized".to_string()
-        } else if block_age >= 12 {
-            // Block is older than 12 blocks, considered safe
-            "safe".to_string()
-        } else if block_age >= 1 {
-            // Block has at least 1 confirmation
-            "pending".to_string()
-        } else {
-            // Block is the latest block
-            "latest".to_string()
-        }
+    /// Get block status based on network finality, or on block age while no finality
+    /// checkpoint is known
+    pub fn status(&self, latest_block: i64, finality: &Finality) -> String {
+        block_status(self.number, latest_block, finality).to_string()
     }
 
     /// Check if block has withdrawals (post-Shanghai)
@@ -153,126 +147,1129 @@ impl Block {
     }
 }
 
-/// Transaction data structure
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
-pub struct Transaction {
-    pub hash: String,
-    pub block_number: i64,
-    pub from_address: String,
-    pub to_address: Option<String>,
-    pub value: String,
-    pub gas_used: i64,
-    pub gas_price: String,
-    pub status: i64,
-    pub transaction_index: i64,
+/// Transaction data structure
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Transaction {
+    pub hash: String,
+    pub block_number: i64,
+    pub from_address: String,
+    pub to_address: Option<String>,
+    pub value: String,
+    pub gas_used: i64,
+    pub gas_price: String,
+    pub status: i64,
+    pub transaction_index: i64,
+    #[sqlx(default)]
+    pub method_id: Option<String>, // 4-byte selector of the input data, None without calldata
+    #[sqlx(default)]
+    pub method_name: Option<String>, // Function name of the selector when known, e.g. "transfer"
+    #[sqlx(default)]
+    pub gas_limit: Option<i64>, // None for transactions indexed before gas limits were stored
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>, // Calldata as hex, possibly truncated; only loaded for transaction details
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_size: Option<i64>, // Full calldata length in bytes
+}
+
+impl Transaction {
+    /// Share of the gas limit used, as a percentage
+    pub fn gas_efficiency(&self) -> Option<f64> {
+        self.gas_limit
+            .filter(|gas_limit| *gas_limit > 0)
+            .map(|gas_limit| self.gas_used as f64 / gas_limit as f64 * 100.0)
+    }
+
+    /// Whether the transaction failed by running out of gas rather than reverting
+    pub fn is_out_of_gas(&self) -> bool {
+        self.status == 0
+            && self
+                .gas_limit
+                .is_some_and(|gas_limit| self.gas_used >= gas_limit)
+    }
+}
+
+/// Transaction response structure for API with calculated fields
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionResponse {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+
+    // Calculated fields
+    pub gas_efficiency: Option<f64>, // Percentage of the gas limit used
+    pub out_of_gas: bool,
+}
+
+impl From<Transaction> for TransactionResponse {
+    fn from(transaction: Transaction) -> Self {
+        Self {
+            gas_efficiency: transaction.gas_efficiency(),
+            out_of_gas: transaction.is_out_of_gas(),
+            transaction,
+        }
+    }
+}
+
+/// Log data structure
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Log {
+    #[sqlx(default)]
+    pub id: Option<i64>,
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub address: String,
+    pub topic0: Option<String>,
+    pub topic1: Option<String>,
+    pub topic2: Option<String>,
+    pub topic3: Option<String>,
+    pub data: Option<String>,
+    pub log_index: i64,
+}
+
+/// Account data structure
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Account {
+    pub address: String,
+    pub balance: String,
+    pub transaction_count: i64,
+    pub first_seen_block: i64,
+    pub last_seen_block: i64,
+    #[sqlx(default)]
+    pub balance_at_block: Option<i64>, // Block the balance was read at, None when latest state was used
+    #[sqlx(default)]
+    pub account_type: Option<String>, // "eoa" or "contract", None when not detected yet
+}
+
+/// Balance of an account at the end of a balance history period
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub block_number: i64,
+    pub timestamp: Option<i64>, // Timestamp of the block, None once the block was pruned
+    pub balance: String,
+    pub balance_at_block: Option<i64>, // Block the balance was read at, None when latest state was used
+}
+
+/// Token transfer data structure
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TokenTransfer {
+    #[sqlx(default)]
+    pub id: Option<i64>,
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub token_address: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: String,
+    #[sqlx(default)]
+    pub token_type: Option<String>, // ERC20, ERC721, ERC1155
+    #[sqlx(default)]
+    pub token_id: Option<String>, // For NFTs
+    #[sqlx(default)]
+    pub log_index: Option<i64>, // Log the transfer was decoded from
+}
+
+/// Token information structure
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Token {
+    pub address: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    pub token_type: String, // ERC20, ERC721, ERC1155
+    pub first_seen_block: i64,
+    pub last_seen_block: i64,
+    pub total_transfers: i64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    #[sqlx(default)]
+    pub name_source: Option<String>, // Resolver the name came from: on_chain, token_list or manual
+    #[sqlx(default)]
+    pub symbol_source: Option<String>,
+    #[sqlx(default)]
+    pub decimals_source: Option<String>,
+    #[sqlx(default)]
+    pub logo_uri: Option<String>,
+    #[sqlx(default)]
+    pub curated: bool, // Imported from a token list, preferred over discovered metadata
+    #[sqlx(default)]
+    pub total_supply: Option<String>, // totalSupply() in base units, refreshed periodically
+    #[sqlx(default)]
+    pub total_supply_block: Option<i64>,
+    #[sqlx(default)]
+    pub holder_count: Option<i64>, // Accounts with a non-zero stored balance
+    #[sqlx(default)]
+    pub recent_transfers: Option<i64>, // Transfers in the activity window
+    #[sqlx(default)]
+    pub token_type_confidence: Option<String>, // How token_type was detected: high, medium or low
+}
+
+/// Order of token lists, most first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenSort {
+    #[default]
+    Transfers, // All-time transfers
+    Holders,
+    Activity, // Transfers in the activity window
+}
+
+impl TokenSort {
+    /// Parse a sort name, "transfers", "holders" or "activity"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "transfers" => Some(TokenSort::Transfers),
+            "holders" => Some(TokenSort::Holders),
+            "activity" => Some(TokenSort::Activity),
+            _ => None,
+        }
+    }
+}
+
+/// Token metadata set by hand, taking precedence over every other source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenMetadataOverride {
+    pub name: Option<String>, // None = not overridden
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+/// Token list to import through the admin API
+#[derive(Debug, Deserialize)]
+pub struct TokenListImportRequest {
+    pub url: String,           // One of TOKEN_LIST_URLS, in the Uniswap token list format
+    pub chain_id: Option<u64>, // None = chain of the RPC node
+}
+
+/// Name and category of a well-known address
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AddressLabel {
+    pub address: String,
+    pub label: String,
+    pub category: String, // 'dex' and 'lending' feed MEV analysis
+    #[serde(default = "AddressLabel::default_source")]
+    pub source: String, // 'seed' or 'manual'
+    #[sqlx(default)]
+    pub updated_at: Option<String>,
+}
+
+impl AddressLabel {
+    fn default_source() -> String {
+        "seed".to_string()
+    }
+}
+
+/// Label to set through the admin API
+#[derive(Debug, Deserialize)]
+pub struct AddressLabelRequest {
+    pub label: String,
+    pub category: String,
+}
+
+/// Indexer settings to change through the admin API, omitted fields are left unchanged
+#[derive(Debug, Deserialize)]
+pub struct IndexerSettingsRequest {
+    pub worker_pool_size: Option<usize>,
+    pub eth_rpc_max_concurrent: Option<usize>,
+    pub eth_rpc_min_interval_ms: Option<u64>,
+    pub beacon_rpc_max_concurrent: Option<usize>,
+    pub beacon_rpc_min_interval_ms: Option<u64>,
+}
+
+/// Log directives to apply through the admin API, e.g. `info,indexer=debug`
+#[derive(Debug, Deserialize)]
+pub struct LogFilterRequest {
+    pub directives: String,
+}
+
+/// Block range to reindex through the admin API
+#[derive(Debug, Deserialize)]
+pub struct ReindexRequest {
+    pub from_block: i64,
+    pub to_block: i64,
+}
+
+/// Search parameters
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: Option<String>, // Query of /search?q=, the path segment otherwise
+    pub limit: Option<usize>,
+}
+
+impl SearchParams {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(10).clamp(1, 50)
+    }
+}
+
+/// Token matching a search by name or symbol
+#[derive(Debug, Clone, FromRow)]
+pub struct TokenMatch {
+    #[sqlx(flatten)]
+    pub token: Token,
+    pub score: i64, // Relevance, exact symbol matches first
+}
+
+/// Address label matching a search by name
+#[derive(Debug, Clone, FromRow)]
+pub struct AddressLabelMatch {
+    #[sqlx(flatten)]
+    pub label: AddressLabel,
+    pub score: i64,
+}
+
+/// Block replay parameters
+#[derive(Debug, Deserialize)]
+pub struct DebugBlockParams {
+    pub apply: Option<bool>, // Reindex the block after the replay
+}
+
+/// Address label filter parameters
+#[derive(Debug, Deserialize)]
+pub struct AddressLabelParams {
+    pub category: Option<String>,
+}
+
+/// Named set of addresses whose activity is reported together
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Watchlist {
+    pub id: i64,
+    pub name: String,
+    pub address_count: i64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// Address of a watchlist
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct WatchlistAddress {
+    pub address: String,
+    pub label: Option<String>,
+}
+
+/// Watchlist to create or replace through the API
+#[derive(Debug, Deserialize)]
+pub struct WatchlistRequest {
+    pub name: String,
+    pub addresses: Vec<WatchlistAddress>,
+}
+
+/// Watchlist activity parameters
+#[derive(Debug, Deserialize)]
+pub struct WatchlistActivityParams {
+    pub limit: Option<i64>,
+}
+
+impl WatchlistActivityParams {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, 500)
+    }
+}
+
+/// Balance history parameters
+#[derive(Debug, Deserialize)]
+pub struct BalanceHistoryParams {
+    pub from_block: Option<i64>,
+    pub to_block: Option<i64>,
+    pub limit: Option<i64>, // Newest snapshots returned, oldest first
+}
+
+impl BalanceHistoryParams {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(500).clamp(1, 5000)
+    }
+}
+
+/// Subscription notified of every new indexed block
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BlockWebhook {
+    pub id: i64,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: Option<String>, // HMAC-SHA256 key, never returned by the API
+    pub from_block: i64,
+    #[sqlx(default)]
+    pub pending_deliveries: i64, // Notifications waiting in the outbox
+    pub created_at: Option<String>,
+}
+
+/// Block webhook to create through the API
+#[derive(Debug, Deserialize)]
+pub struct BlockWebhookRequest {
+    pub url: String,
+    pub secret: Option<String>,
+    pub from_block: Option<i64>, // Defaults to the block after the newest indexed one
+}
+
+/// Block notification waiting in the webhook outbox
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub block_number: i64,
+    pub payload: String,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+}
+
+/// Aggregates of what was stored for a block, sent with block notifications
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+pub struct BlockActivity {
+    pub failed_transactions: i64,
+    pub unique_senders: i64,
+    pub contract_creations: i64,
+    pub log_count: i64,
+    pub token_transfer_count: i64,
+}
+
+/// Transfers between two addresses parameters
+#[derive(Debug, Deserialize)]
+pub struct TransfersBetweenParams {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i64>, // Transfers listed next to the totals
+}
+
+impl TransfersBetweenParams {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(50).clamp(0, 500) as usize
+    }
+}
+
+/// Token transfer query parameters. Amount bounds are in token units (e.g. 1000.5) and
+/// need a token with known decimals.
+#[derive(Debug, Deserialize)]
+pub struct TokenTransferFilterParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub token: Option<String>, // Token address or symbol, e.g. USDC
+    pub from: Option<String>,  // Sender address
+    pub to: Option<String>,    // Recipient address
+    pub min_amount: Option<String>,
+    pub max_amount: Option<String>,
+    pub from_block: Option<i64>,
+    pub to_block: Option<i64>,
+}
+
+impl TokenTransferFilterParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(100).min(1000) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) as i64) * self.limit()
+    }
+}
+
+/// USD values of token transfer lists, off by default as they cost a token and price lookup
+#[derive(Debug, Deserialize)]
+pub struct TransferValueParams {
+    pub usd: Option<bool>, // Add the approximate value_usd of each transfer
+}
+
+/// Token transfer filter with the token resolved and amounts in base units
+#[derive(Debug, Default)]
+pub struct TokenTransferFilter {
+    pub token_address: Option<String>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub min_amount: Option<String>, // Decimal base units without leading zeros
+    pub max_amount: Option<String>,
+    pub from_block: Option<i64>,
+    pub to_block: Option<i64>,
+}
+
+/// Function known from a verified contract ABI, used to decode calls by selector
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct FunctionSignature {
+    pub selector: String,  // 4-byte selector, 0x-prefixed
+    pub signature: String, // e.g. "transfer(address,uint256)"
+    pub abi: String,       // JSON ABI of the function
+}
+
+/// Contract whose source code was verified against its deployed bytecode
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct VerifiedContract {
+    pub address: String,
+    pub contract_name: String,
+    pub compiler_version: String,
+    pub optimizer_enabled: bool,
+    pub optimizer_runs: Option<i64>,
+    pub evm_version: Option<String>,
+    pub source_code: String,
+    pub abi: String,        // ABI as JSON
+    pub match_type: String, // "full" or "partial"
+    pub verified_at: Option<String>,
+}
+
+/// Distinct runtime bytecode, stored once per code hash
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ContractCode {
+    pub code_hash: String, // keccak256 of the bytecode
+    pub bytecode: String,
+    pub code_size: i64,
+    pub first_seen_block: i64,
+    pub created_at: Option<String>,
+}
+
+/// Receipt fields of a transaction that are not kept in the transactions table
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Receipt {
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub cumulative_gas_used: i64,
+    pub effective_gas_price: Option<String>, // Wei, None when the node doesn't report it
+    pub contract_address: Option<String>,    // Contract created by the transaction
+    pub logs_bloom: String,
+    pub transaction_type: Option<i64>, // EIP-2718 type, None for nodes omitting it
+}
+
+/// Contract created by an indexed transaction
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ContractDeployment {
+    pub address: String,
+    pub code_hash: String,
+    pub creator_address: String, // Sender of the creation transaction
+    pub transaction_hash: String,
+    pub block_number: i64,
+    #[sqlx(default)]
+    pub factory_address: Option<String>, // Contract that ran the CREATE, None for transactions
+    pub created_at: Option<String>,
+}
+
+/// Deployment in a contract family, `depth` creations away from the contract it was
+/// looked up from
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ContractLineage {
+    pub address: String,
+    pub parent_address: String, // Factory, or the transaction sender
+    pub code_hash: String,
+    pub creator_address: String,
+    pub factory_address: Option<String>,
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub depth: i64,
+}
+
+/// Contract descendants query parameters
+#[derive(Debug, Deserialize)]
+pub struct ContractChildrenParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub depth: Option<u32>, // Creation levels below the contract, 1 = direct children
+}
+
+impl ContractChildrenParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(50).min(500) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+
+    pub fn depth(&self) -> i64 {
+        self.depth.unwrap_or(1).clamp(1, 10) as i64
+    }
+}
+
+/// Transaction waiting in the mempool
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub hash: String,
+    pub from_address: String,
+    pub to_address: Option<String>,
+    pub nonce: i64,
+    pub value: String, // Value in wei
+    pub gas_limit: i64,
+    pub gas_price: String, // Gas price in wei, max fee for EIP-1559 transactions
+    pub max_priority_fee_per_gas: Option<String>,
+    pub first_seen_at: Option<String>,
+}
+
+/// Beacon chain validator, as of the last registry sync
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Validator {
+    pub validator_index: i64,
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub balance_gwei: i64,
+    pub effective_balance_gwei: i64,
+    pub status: String, // Beacon API status, e.g. "active_ongoing"
+    pub slashed: bool,
+    pub activation_eligibility_epoch: Option<i64>, // None = far future epoch
+    pub activation_epoch: Option<i64>,
+    pub exit_epoch: Option<i64>,
+    pub withdrawable_epoch: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
+/// Validator scheduled to propose the block of a slot
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ProposerDuty {
+    pub slot: i64,
+    pub epoch: i64,
+    pub validator_index: i64,
+    pub pubkey: String,
+}
+
+/// Validator list parameters
+#[derive(Debug, Deserialize)]
+pub struct ValidatorParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub status: Option<String>, // Beacon API status, or "active", "pending", "exited", "withdrawal"
+}
+
+impl ValidatorParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(25).min(100) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+}
+
+/// Withdrawal query parameters, at least one of address and validator is required
+#[derive(Debug, Deserialize)]
+pub struct WithdrawalParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub address: Option<String>, // Recipient of the withdrawals
+    pub validator: Option<i64>,  // Index of the validator withdrawn from
+}
+
+impl WithdrawalParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(25).min(100) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+}
+
+/// Storage report query parameters
+#[derive(Debug, Deserialize)]
+pub struct StorageParams {
+    pub days: Option<i64>, // Days of samples growth rates are averaged over
+}
+
+impl StorageParams {
+    pub fn days(&self) -> i64 {
+        self.days.unwrap_or(30).clamp(1, 365)
+    }
+}
+
+/// Deposit query parameters
+#[derive(Debug, Deserialize)]
+pub struct DepositParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub depositor: Option<String>, // Sender of the deposit transactions
+    pub pubkey: Option<String>,    // BLS public key of the validator
+}
+
+impl DepositParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(25).min(100) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+}
+
+/// Block fee recipient query parameters
+#[derive(Debug, Deserialize)]
+pub struct FeeRecipientParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub fee_recipient: Option<String>,
+    pub proposer_payee: Option<String>,
+    pub proposer_index: Option<i64>,
+    pub mismatch: Option<bool>, // Only blocks whose fee recipient is (not) the miner
+}
+
+impl FeeRecipientParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(25).min(100) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+}
+
+/// Token delegate query parameters
+#[derive(Debug, Deserialize)]
+pub struct DelegateParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl DelegateParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(25).min(100) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+}
+
+/// Query parameters of the approvals of an account
+#[derive(Debug, Deserialize)]
+pub struct ApprovalParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub token: Option<String>,         // Only approvals of this token
+    pub include_revoked: Option<bool>, // Also list approvals set back to zero
+}
+
+impl ApprovalParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(25).min(100) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+}
+
+/// Cached ENS resolution
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct EnsName {
+    pub name: Option<String>,
+    pub address: Option<String>,
+    pub is_reverse: bool, // Reverse (address -> primary name) or forward (name -> address) record
+    pub resolved_at: Option<String>,
+}
+
+/// Source code and compiler settings submitted for contract verification
+#[derive(Debug, Deserialize)]
+pub struct VerifyContractRequest {
+    pub contract_name: String,
+    pub source_code: String,
+    pub file_name: Option<String>, // Source unit name, defaults to "<contract_name>.sol"
+    pub compiler_version: Option<String>, // e.g. "0.8.24", must match the configured compiler
+    pub optimizer_enabled: Option<bool>,
+    pub optimizer_runs: Option<u32>,
+    pub evm_version: Option<String>,
+}
+
+/// Token balance structure for storing account token balances
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TokenBalance {
+    #[sqlx(default)]
+    pub id: Option<i64>,
+    pub account_address: String,
+    pub token_address: String,
+    pub balance: String,
+    pub block_number: i64,
+    pub last_updated_block: i64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// Stats structure for API responses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexerStats {
+    pub latest_block: i64,
+    pub total_blocks: i64,
+    pub total_transactions: i64,
+    pub total_transactions_declared: i64,
+    pub total_transactions_indexed: i64,
+    pub real_transactions_indexed: i64, // Only transactions from start_block onwards
+    pub total_blockchain_transactions: i64, // Historical + indexed transactions
+    pub total_accounts: i64,
+    pub indexer_status: String,
+    pub sync_percentage: f64, // Indexed share of the blocks from start_block to the chain tip
+    pub transaction_indexing_percentage: f64, // Stored vs declared transactions of indexed blocks from start_block
+    pub start_block: i64,
+    pub current_block_tx_indexed: i64,
+    pub current_block_tx_declared: i64,
+    pub block_consistency: ConsistencyReport,
+}
+
+/// Results of the block consistency checker since the indexer started
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    pub checked_blocks: i64,
+    pub mismatched_blocks: i64, // Blocks whose stored transactions differ from transaction_count
+    pub requeued_blocks: i64,   // Mismatched blocks cleared and queued for reprocessing
+    pub missing_blocks: i64,    // Blocks absent from the indexed range, queued again
+    pub mismatch_percentage: f64,
+}
+
+/// Pagination parameters
+#[derive(Debug, Deserialize)]
+pub struct PaginationParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub cursor: Option<String>, // next_cursor of a previous page, takes precedence over page
+}
+
+impl PaginationParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(10).min(100) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.per_page.unwrap_or(10)) as i64
+    }
+}
+
+/// Keyset position in the transaction list, encoded as "block_number:transaction_index"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionCursor {
+    pub block_number: i64,
+    pub transaction_index: i64,
+}
+
+impl TransactionCursor {
+    /// Cursor pointing after a transaction
+    pub fn after(tx: &Transaction) -> Self {
+        Self {
+            block_number: tx.block_number,
+            transaction_index: tx.transaction_index,
+        }
+    }
+
+    /// Parse a "block_number:transaction_index" cursor
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let (block_number, transaction_index) = cursor.split_once(':')?;
+        Some(Self {
+            block_number: block_number.trim().parse().ok()?,
+            transaction_index: transaction_index.trim().parse().ok()?,
+        })
+    }
+}
+
+impl std::fmt::Display for TransactionCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.block_number, self.transaction_index)
+    }
+}
+
+/// Method filter of the transaction list
+#[derive(Debug, Deserialize)]
+pub struct TransactionMethodParams {
+    pub method: Option<String>, // 4-byte selector, e.g. 0xa9059cbb
+}
+
+impl TransactionMethodParams {
+    /// Normalized selector, Err when it is not "0x" followed by 8 hex characters
+    pub fn method_id(&self) -> Result<Option<String>, String> {
+        let Some(method) = self.method.as_deref().map(str::trim) else {
+            return Ok(None);
+        };
+
+        match method.strip_prefix("0x") {
+            Some(selector) if selector.len() == 8 && hex::decode(selector).is_ok() => {
+                Ok(Some(method.to_lowercase()))
+            }
+            _ => Err(format!(
+                "Invalid method selector {}, expected 0x followed by 8 hex characters",
+                method
+            )),
+        }
+    }
+}
+
+/// API key granting access to the API
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ApiKey {
+    #[sqlx(default)]
+    pub id: Option<i64>,
+    pub key_hash: String, // keccak256 of the key
+    pub name: String,
+    pub requests_per_minute: i64, // 0 = unlimited
+    pub daily_quota: Option<i64>, // None = unlimited
+    pub enabled: bool,
+    pub created_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+/// Operational event recorded by the indexer
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct IndexerEvent {
+    #[sqlx(default)]
+    pub id: Option<i64>,
+    pub kind: String,
+    pub severity: String, // "info", "warning" or "error"
+    pub message: String,
+    pub block_number: Option<i64>,
+    pub details: Option<String>, // JSON encoded
+    pub created_at: Option<String>,
+}
+
+/// Indexer event filter parameters
+#[derive(Debug, Deserialize)]
+pub struct IndexerEventParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub kind: Option<String>,
+    pub severity: Option<String>,
+}
+
+impl IndexerEventParams {
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(50).min(500) as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1).saturating_sub(1) * self.limit() as u64) as i64
+    }
+}
+
+/// Daily transaction and failure counts of a contract
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ContractFailureDay {
+    pub day: String, // YYYY-MM-DD (UTC)
+    pub total_transactions: i64,
+    pub failed_transactions: i64,
+}
+
+/// Contract failure rollup parameters
+#[derive(Debug, Deserialize)]
+pub struct ContractFailureParams {
+    pub days: Option<u32>,
+}
+
+impl ContractFailureParams {
+    pub fn days(&self) -> i64 {
+        self.days.unwrap_or(30).clamp(1, 365) as i64
+    }
+}
+
+/// Activity totals of a day or hour, see the analytics job
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AggregateStats {
+    pub period: String,    // "day" or "hour"
+    pub bucket_start: i64, // Unix timestamp of the bucket start (UTC)
+    pub block_count: i64,
+    pub transaction_count: i64,
+    pub gas_used: i64,
+    pub avg_gas_price_gwei: Option<f64>,
+    pub active_addresses: i64,
+    pub new_contracts: i64,
+    pub burnt_fees_eth: f64,
+    pub issuance_eth: Option<f64>, // Estimated consensus issuance, day buckets only
+    pub issuance_samples: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
+/// Ranking of one leaderboard metric for a UTC day, see the analytics job
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub metric: String,
+    pub day_start: i64,  // Unix timestamp of the day start (UTC)
+    pub entries: String, // JSON encoded Vec<LeaderboardEntry>
+    pub updated_at: Option<String>,
+}
+
+/// Position on a leaderboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub address: String,                  // Fee payer or transaction sender
+    pub transaction_hash: Option<String>, // None for per-address metrics
+    pub value: f64,                       // ETH for fees and transfers, gas for gas guzzlers
+    pub transaction_count: Option<i64>,   // Transactions of the address, per-address metrics
+}
+
+/// Leaderboard query parameters
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardParams {
+    pub date: Option<String>, // UTC day as YYYY-MM-DD, today by default
+    pub limit: Option<u32>,
+}
+
+impl LeaderboardParams {
+    /// Start of the requested day, None for an invalid date
+    pub fn day_start(&self) -> Option<i64> {
+        let date = match self.date.as_deref() {
+            Some(date) => chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()?,
+            None => chrono::Utc::now().date_naive(),
+        };
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp())
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(25).clamp(1, 100) as usize
+    }
+}
+
+/// Calls to a contract over a window, see the contract interaction rollup
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ContractPopularity {
+    pub contract_address: String,
+    pub calls: i64,
+    pub unique_callers: i64, // Distinct transaction senders
+}
+
+/// Trending contracts query parameters
+#[derive(Debug, Deserialize)]
+pub struct TopContractsParams {
+    pub window: Option<String>, // "<n>h" or "<n>d", 24h by default, at most 30d
+    pub limit: Option<u32>,
+}
+
+impl TopContractsParams {
+    /// Longest window the contract interaction rollup is kept for
+    pub const MAX_WINDOW_SECONDS: i64 = 30 * 86_400;
+
+    /// Window length in seconds, None when malformed or too long
+    pub fn window_seconds(&self) -> Option<i64> {
+        let window = self.window.as_deref().unwrap_or("24h").trim();
+        let (count, unit) = match window.strip_suffix('h') {
+            Some(hours) => (hours, 3_600),
+            None => (window.strip_suffix('d')?, 86_400),
+        };
+        let seconds = count
+            .parse::<i64>()
+            .ok()
+            .filter(|count| *count > 0)?
+            .checked_mul(unit)?;
+        (seconds <= Self::MAX_WINDOW_SECONDS).then_some(seconds)
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(25).clamp(1, 100) as i64
+    }
+}
+
+/// Image proxy query parameters
+#[derive(Debug, Deserialize)]
+pub struct ImageParams {
+    pub size: Option<u32>, // Largest side in pixels
+}
+
+/// Sizes images are served in, so each image is resized and cached at most this many times
+pub const IMAGE_SIZES: [u32; 5] = [32, 64, 128, 256, 512];
+
+impl ImageParams {
+    /// Smallest of `IMAGE_SIZES` at least as large as the requested size
+    pub fn size(&self) -> u32 {
+        let size = self.size.unwrap_or(128);
+        IMAGE_SIZES
+            .into_iter()
+            .find(|bucket| *bucket >= size)
+            .unwrap_or(IMAGE_SIZES[IMAGE_SIZES.len() - 1])
+    }
+}
+
+/// Chart query parameters
+#[derive(Debug, Deserialize)]
+pub struct ChartParams {
+    pub interval: Option<String>, // "day" (default) or "hour"
+    pub days: Option<u32>,        // How far back the series goes
+}
+
+impl ChartParams {
+    /// Bucket period, None for an unknown interval
+    pub fn period(&self) -> Option<&'static str> {
+        match self.interval.as_deref() {
+            None | Some("day") => Some("day"),
+            Some("hour") => Some("hour"),
+            Some(_) => None,
+        }
+    }
+
+    pub fn days(&self) -> i64 {
+        let default = if self.period() == Some("hour") { 2 } else { 30 };
+        self.days.unwrap_or(default).clamp(1, 365) as i64
+    }
+}
+
+/// Live log stream parameters
+#[derive(Debug, Deserialize)]
+pub struct LogStreamParams {
+    pub address: String,        // Comma-separated contract addresses
+    pub topic0: Option<String>, // Only stream this event signature
 }
 
-/// Log data structure
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
-pub struct Log {
-    #[sqlx(default)]
-    pub id: Option<i64>,
-    pub transaction_hash: String,
-    pub block_number: i64,
-    pub address: String,
-    pub topic0: Option<String>,
+/// Log query parameters, mirroring eth_getLogs filters
+#[derive(Debug, Deserialize)]
+pub struct LogFilterParams {
+    pub per_page: Option<u64>,
+    pub cursor: Option<String>,  // next_cursor of the previous page
+    pub address: Option<String>, // Comma-separated emitting contracts, any of them matches
+    pub topic0: Option<String>,  // Comma-separated alternatives for each topic position
     pub topic1: Option<String>,
     pub topic2: Option<String>,
     pub topic3: Option<String>,
-    pub data: Option<String>,
-    pub log_index: i64,
+    pub from_block: Option<i64>,
+    pub to_block: Option<i64>,
 }
 
-/// Account data structure
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
-pub struct Account {
-    pub address: String,
-    pub balance: String,
-    pub transaction_count: i64,
-    pub first_seen_block: i64,
-    pub last_seen_block: i64,
-}
+impl LogFilterParams {
+    /// Widest block range queried without an address or topic0 filter, which the log
+    /// indexes can't narrow
+    pub const MAX_UNFILTERED_BLOCKS: i64 = 10_000;
 
-/// Token transfer data structure
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
-pub struct TokenTransfer {
-    #[sqlx(default)]
-    pub id: Option<i64>,
-    pub transaction_hash: String,
-    pub block_number: i64,
-    pub token_address: String,
-    pub from_address: String,
-    pub to_address: String,
-    pub amount: String,
-    #[sqlx(default)]
-    pub token_type: Option<String>, // ERC20, ERC721, ERC1155
-    #[sqlx(default)]
-    pub token_id: Option<String>, // For NFTs
-}
+    pub fn limit(&self) -> i64 {
+        self.per_page.unwrap_or(100).min(1000) as i64
+    }
 
-/// Token information structure
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
-pub struct Token {
-    pub address: String,
-    pub name: Option<String>,
-    pub symbol: Option<String>,
-    pub decimals: Option<u8>,
-    pub token_type: String, // ERC20, ERC721, ERC1155
-    pub first_seen_block: i64,
-    pub last_seen_block: i64,
-    pub total_transfers: i64,
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>,
+    /// Whether an address or topic0 filter narrows the query through an index
+    pub fn is_indexed(&self) -> bool {
+        !self.addresses().is_empty() || !self.topics()[0].is_empty()
+    }
+
+    /// Requested emitting contracts (lowercase), empty matches any
+    pub fn addresses(&self) -> Vec<String> {
+        split_filter_values(self.address.as_deref())
+    }
+
+    /// Requested alternatives for each topic position (lowercase), empty matches any
+    pub fn topics(&self) -> [Vec<String>; 4] {
+        [&self.topic0, &self.topic1, &self.topic2, &self.topic3]
+            .map(|topic| split_filter_values(topic.as_deref()))
+    }
 }
 
-/// Token balance structure for storing account token balances
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
-pub struct TokenBalance {
-    #[sqlx(default)]
-    pub id: Option<i64>,
-    pub account_address: String,
-    pub token_address: String,
-    pub balance: String,
+/// Keyset position in the log list, encoded as "block_number:log_index"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogCursor {
     pub block_number: i64,
-    pub last_updated_block: i64,
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>,
+    pub log_index: i64,
 }
 
-/// Stats structure for API responses
-#[derive(Debug, Serialize, Deserialize)]
-pub struct IndexerStats {
-    pub latest_block: i64,
-    pub total_blocks: i64,
-    pub total_transactions: i64,
-    pub total_transactions_declared: i64,
-    pub total_transactions_indexed: i64,
-    pub real_transactions_indexed: i64, // Only transactions from start_block onwards
-    pub total_blockchain_transactions: i64, // Historical + indexed transactions
-    pub total_accounts: i64,
-    pub indexer_status: String,
-    pub sync_percentage: f64,
-    pub transaction_indexing_percentage: f64,
-    pub start_block: i64,
-    pub current_block_tx_indexed: i64,
-    pub current_block_tx_declared: i64,
-}
+impl LogCursor {
+    /// Cursor pointing after a log
+    pub fn after(log: &Log) -> Self {
+        Self {
+            block_number: log.block_number,
+            log_index: log.log_index,
+        }
+    }
 
-/// Pagination parameters
-#[derive(Debug, Deserialize)]
-pub struct PaginationParams {
-    pub page: Option<u64>,
-    pub per_page: Option<u64>,
+    /// Parse a "block_number:log_index" cursor
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let (block_number, log_index) = cursor.split_once(':')?;
+        Some(Self {
+            block_number: block_number.trim().parse().ok()?,
+            log_index: log_index.trim().parse().ok()?,
+        })
+    }
 }
 
-impl PaginationParams {
-    pub fn limit(&self) -> i64 {
-        self.per_page.unwrap_or(10).min(100) as i64
+impl std::fmt::Display for LogCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.block_number, self.log_index)
     }
+}
 
-    pub fn offset(&self) -> i64 {
-        (self.page.unwrap_or(1).saturating_sub(1) * self.per_page.unwrap_or(10)) as i64
-    }
+fn split_filter_values(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty())
+        .collect()
 }
 
 /// Transaction filter parameters
@@ -280,7 +1277,7 @@ impl PaginationParams {
 pub struct TransactionFilterParams {
     pub page: Option<u64>,
     pub per_page: Option<u64>,
-    pub status: Option<String>,    // "success", "failed", or "all"
+    pub status: Option<String>,    // "success", "failed", "out_of_gas", or "all"
     pub min_value: Option<String>, // minimum value in Wei
     pub max_value: Option<String>, // maximum value in Wei
     pub from_block: Option<i64>,   // minimum block number
@@ -321,6 +1318,53 @@ impl AccountFilterParams {
     }
 }
 
+/// Export parameters
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub from_block: Option<i64>, // first block included in the export
+    pub to_block: Option<i64>,   // last block included in the export
+    pub format: Option<String>,  // "csv" or "ndjson"
+}
+
+impl ExportParams {
+    pub fn from_block(&self) -> i64 {
+        self.from_block.unwrap_or(0).max(0)
+    }
+
+    pub fn to_block(&self) -> i64 {
+        self.to_block.unwrap_or(i64::MAX)
+    }
+}
+
+/// Holder snapshot export parameters
+#[derive(Debug, Deserialize)]
+pub struct HolderExportParams {
+    pub token: Option<String>, // Token address, ETH balances when unset or "eth"
+    pub block: Option<i64>,    // Snapshot block (default: latest indexed block)
+    pub snapshot: Option<String>, // "block" (default) or "latest"
+    pub min_balance: Option<String>, // Smallest balance in base units (default: 1)
+    pub after: Option<String>, // "<balance>:<address>" of the last row received
+    pub limit: Option<i64>,    // Rows exported (default: all)
+    pub format: Option<String>, // "csv" or "ndjson"
+}
+
+/// Balance of a holder in a holder snapshot export
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct HolderBalance {
+    pub address: String,
+    pub balance: String,            // Base units
+    pub balance_block: Option<i64>, // Block the balance was last changed or read at
+}
+
+/// Bounds of a holder export page: balances from `min_balance`, after the `after` row in
+/// export order (largest balance first, then by address)
+#[derive(Debug, Clone, Default)]
+pub struct HolderExportRange {
+    pub min_balance: String,
+    pub after: Option<(String, String)>, // (balance, address)
+    pub limit: Option<i64>,
+}
+
 /// Block response structure for API with calculated fields
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockResponse {
@@ -351,10 +1395,14 @@ pub struct BlockResponse {
     pub epoch: Option<i64>,
     pub slot_root: Option<String>,
     pub parent_root: Option<String>,
-    pub beacon_deposit_count: Option<i64>,
+    pub block_deposit_count: Option<i64>,
+    pub eth1_deposit_count: Option<i64>,
     pub graffiti: Option<String>,
     pub randao_reveal: Option<String>,
     pub randao_mix: Option<String>,
+    pub sync_participation: Option<f64>,
+    pub attestation_count: Option<i64>,
+    pub slashing_count: Option<i64>,
 
     // Calculated fields
     pub burnt_fees: Option<String>,
@@ -365,6 +1413,9 @@ pub struct BlockResponse {
     pub priority_fees: Option<String>,
     pub base_validator_reward: Option<String>,
     pub mev_reward: Option<String>,
+    pub fee_recipient_reward: Option<String>,
+    pub reward_source: String, // "estimated" or "beacon_api"
+    pub consensus_rewards: Option<BlockRewards>,
     pub blob_transactions: Option<i64>,
     pub blob_size: Option<i64>,
     pub blob_gas_price: Option<String>,
@@ -398,10 +1449,14 @@ impl From<&Block> for BlockResponse {
             epoch: block.epoch,
             slot_root: block.slot_root.clone(),
             parent_root: block.parent_root.clone(),
-            beacon_deposit_count: block.beacon_deposit_count,
+            block_deposit_count: block.block_deposit_count,
+            eth1_deposit_count: block.eth1_deposit_count,
             graffiti: block.graffiti.clone(),
             randao_reveal: block.randao_reveal.clone(),
             randao_mix: block.randao_mix.clone(),
+            sync_participation: block.sync_participation,
+            attestation_count: block.attestation_count,
+            slashing_count: block.slashing_count,
 
             // Calculate fields dynamically (using defaults for now)
             burnt_fees: block.burnt_fees(),
@@ -412,6 +1467,9 @@ impl From<&Block> for BlockResponse {
             priority_fees: None, // Calculated separately with transaction data
             base_validator_reward: None, // Calculated separately with transaction data
             mev_reward: None,    // Calculated separately with transaction data
+            fee_recipient_reward: None, // Set with beacon rewards
+            reward_source: "estimated".to_string(),
+            consensus_rewards: None,
             blob_transactions: None, // Calculated separately with transaction data
             blob_size: block.blob_size(),
             blob_gas_price: block.blob_gas_price(),
@@ -420,19 +1478,9 @@ impl From<&Block> for BlockResponse {
 }
 
 impl BlockResponse {
-    /// Calculate status based on latest block
-    pub fn calculate_status(&mut self, latest_block: i64) {
-        let block_age = latest_block - self.number;
-
-        self.status = if block_age >= 32 {
-            "finalized".to_string()
-        } else if block_age >= 12 {
-            "safe".to_string()
-        } else if block_age >= 1 {
-            "pending".to_string()
-        } else {
-            "latest".to_string()
-        };
+    /// Calculate status based on network finality and the latest block
+    pub fn calculate_status(&mut self, latest_block: i64, finality: &Finality) {
+        self.status = block_status(self.number, latest_block, finality).to_string();
     }
 
     /// Calculate blob transactions count with transaction data
@@ -447,12 +1495,18 @@ impl BlockResponse {
     }
 
     /// Calculate block reward with transaction data
-    /// This includes priority fees (tips) from all transactions in the block
-    pub fn calculate_block_reward_with_transactions(&mut self, transactions: &[Transaction]) {
+    /// This includes priority fees (tips) from all transactions in the block. `labels` holds
+    /// the labels of the transaction recipients, used to spot DEX and lending interactions.
+    pub fn calculate_block_reward_with_transactions(
+        &mut self,
+        transactions: &[Transaction],
+        labels: &HashMap<String, AddressLabel>,
+    ) {
         let beacon_data = self.extract_beacon_data();
         self.calculate_block_reward_with_transactions_and_beacon(
             transactions,
             beacon_data.as_ref(),
+            labels,
         );
     }
 
@@ -462,6 +1516,7 @@ impl BlockResponse {
         &mut self,
         transactions: &[Transaction],
         beacon_data: Option<&serde_json::Value>,
+        labels: &HashMap<String, AddressLabel>,
     ) {
         let mut total_priority_fees = 0u128;
 
@@ -494,7 +1549,7 @@ impl BlockResponse {
         self.base_validator_reward = Some(base_validator_reward.to_string());
 
         // Calculate MEV reward (simplified estimation)
-        let mev_reward = self.estimate_mev_reward(transactions, total_priority_fees);
+        let mev_reward = self.estimate_mev_reward(transactions, total_priority_fees, labels);
         self.mev_reward = Some(mev_reward.to_string());
 
         // Calculate total block reward
@@ -504,6 +1559,35 @@ impl BlockResponse {
         self.block_reward = Some(total_reward.to_string());
     }
 
+    /// Replace the estimated reward breakdown with the proposer rewards of the beacon API.
+    /// Expects priority fees to be calculated already.
+    pub fn apply_rewards(&mut self, rewards: &BlockRewards, transactions: &[Transaction]) {
+        let consensus_reward = rewards.total.parse::<u128>().unwrap_or(0) * 1_000_000_000;
+
+        let builder_payment = self
+            .miner
+            .as_ref()
+            .and_then(|miner| proposer_payment(miner, transactions))
+            .and_then(|tx| tx.value.parse::<u128>().ok());
+
+        let execution_reward = builder_payment.unwrap_or_else(|| {
+            rewards
+                .fee_recipient_reward
+                .as_ref()
+                .and_then(|reward| reward.parse::<i128>().ok())
+                .map(|reward| reward.max(0) as u128)
+                .or_else(|| self.priority_fees.as_ref()?.parse().ok())
+                .unwrap_or(0)
+        });
+
+        self.base_validator_reward = Some(consensus_reward.to_string());
+        self.mev_reward = Some(builder_payment.unwrap_or(0).to_string());
+        self.fee_recipient_reward = rewards.fee_recipient_reward.clone();
+        self.block_reward = Some((consensus_reward + execution_reward).to_string());
+        self.reward_source = "beacon_api".to_string();
+        self.consensus_rewards = Some(rewards.clone());
+    }
+
     /// Calculate base validator reward using beacon chain data
     /// Uses real Ethereum PoS reward calculation formulas
     fn calculate_base_validator_reward(&self, beacon_data: Option<&serde_json::Value>) -> u128 {
@@ -558,9 +1642,10 @@ impl BlockResponse {
         // Apply inclusion rewards and sync committee rewards if applicable
         let mut total_reward = proposer_reward;
 
-        // Add sync committee reward if proposer is in sync committee
-        if self.is_sync_committee_period(slot) {
-            let sync_reward = base_reward_per_epoch / 4; // ~25% bonus for sync committee
+        // Add the sync aggregate reward, proportional to the sync committee signatures
+        // the block includes (~25% bonus at full participation)
+        if let Some(participation) = self.sync_participation {
+            let sync_reward = (base_reward_per_epoch as f64 / 4.0 * participation / 100.0) as u128;
             total_reward += sync_reward;
         }
 
@@ -601,22 +1686,6 @@ impl BlockResponse {
         current_staked_gwei.min(MAX_STAKED_ETH_GWEI)
     }
 
-    /// Check if the slot is in a sync committee period
-    fn is_sync_committee_period(&self, slot: u64) -> bool {
-        // Sync committee changes every 256 epochs (8192 slots)
-        // Assume 1/512 chance of being in sync committee (512 validators per sync committee)
-        let sync_period = slot / 8192;
-
-        // Use proposer index if available for more accuracy
-        if let Some(proposer_index) = self.proposer_index {
-            // Simple heuristic: check if proposer index aligns with sync committee
-            (proposer_index as u64 + sync_period) % 512 == 0
-        } else {
-            // Default to no sync committee reward
-            false
-        }
-    }
-
     /// Calculate fallback validator reward when beacon data is unavailable
     fn calculate_fallback_validator_reward(&self) -> u128 {
         // Use time-based estimation for more accuracy
@@ -644,7 +1713,12 @@ impl BlockResponse {
 
     /// Estimate MEV (Maximum Extractable Value) reward
     /// Enhanced analysis of transaction patterns for more accurate MEV detection
-    fn estimate_mev_reward(&self, transactions: &[Transaction], priority_fees: u128) -> u128 {
+    fn estimate_mev_reward(
+        &self,
+        transactions: &[Transaction],
+        priority_fees: u128,
+        labels: &HashMap<String, AddressLabel>,
+    ) -> u128 {
         if transactions.is_empty() {
             return 0;
         }
@@ -652,7 +1726,7 @@ impl BlockResponse {
         let mut mev_indicators = MevAnalysis::new();
 
         // Analyze transaction patterns for MEV indicators
-        self.analyze_transaction_patterns(transactions, &mut mev_indicators);
+        self.analyze_transaction_patterns(transactions, labels, &mut mev_indicators);
 
         // Calculate MEV based on different strategies
         let arbitrage_mev = self.calculate_arbitrage_mev(&mev_indicators, priority_fees);
@@ -667,6 +1741,7 @@ impl BlockResponse {
     fn analyze_transaction_patterns(
         &self,
         transactions: &[Transaction],
+        labels: &HashMap<String, AddressLabel>,
         analysis: &mut MevAnalysis,
     ) {
         let base_fee = self
@@ -697,13 +1772,15 @@ impl BlockResponse {
             }
 
             // Check for DEX/DeFi contract interactions
-            if let Some(to_addr) = &tx.to_address {
-                if self.is_dex_contract(to_addr) {
-                    analysis.dex_interactions.push(i);
-                }
-                if self.is_lending_contract(to_addr) {
-                    analysis.lending_interactions.push(i);
-                }
+            let category = tx
+                .to_address
+                .as_ref()
+                .and_then(|to_addr| labels.get(&to_addr.to_lowercase()))
+                .map(|label| label.category.as_str());
+            match category {
+                Some("dex") => analysis.dex_interactions.push(i),
+                Some("lending") => analysis.lending_interactions.push(i),
+                _ => {}
             }
 
             // Detect potential sandwich patterns (high-low-high gas prices)
@@ -803,39 +1880,6 @@ impl BlockResponse {
         flash_loan_users * 2_000_000_000_000_000_000 // 2 ETH per operation
     }
 
-    /// Check if address is a known DEX contract
-    fn is_dex_contract(&self, address: &str) -> bool {
-        // Known DEX contract addresses (Uniswap, SushiSwap, 1inch, etc.)
-        const DEX_CONTRACTS: &[&str] = &[
-            "0x7a250d5630b4cf539739df2c5dacb4c659f2488d", // Uniswap V2 Router
-            "0xe592427a0aece92de3edee1f18e0157c05861564", // Uniswap V3 Router
-            "0xd9e1ce17f2641f24ae83637ab66a2cca9c378b9f", // SushiSwap Router
-            "0x1111111254fb6c44bac0bed2854e76f90643097d", // 1inch V4 Router
-            "0x11111112542d85b3ef69ae05771c2dccff4faa26", // 1inch V3 Router
-            "0xdef171fe48cf0115b1d80b88dc8eab59176fee57", // ParaSwap Router
-        ];
-
-        let addr_lower = address.to_lowercase();
-        DEX_CONTRACTS.iter().any(|&dex| dex == addr_lower)
-    }
-
-    /// Check if address is a known lending protocol contract
-    fn is_lending_contract(&self, address: &str) -> bool {
-        // Known lending protocol addresses (Aave, Compound, MakerDAO, etc.)
-        const LENDING_CONTRACTS: &[&str] = &[
-            "0x7d2768de32b0b80b7a3454c06bdac94a69ddc7a9", // Aave V2 Pool
-            "0x87870bca3f3fd6335c3f4ce8392d69350b4fa4e2", // Aave V3 Pool
-            "0x3d9819210a31b4961b30ef54be2aed79b9c9cd3b", // Compound cDAI
-            "0x35a18000230da775cac24873d00ff85bccded550", // cUNI
-            "0x9759a6ac90977b93b58547b4a71c78317f391a28", // MakerDAO PSM
-        ];
-
-        let addr_lower = address.to_lowercase();
-        LENDING_CONTRACTS
-            .iter()
-            .any(|&lending| lending == addr_lower)
-    }
-
     /// Calculate priority fees (tips) from transactions
     pub fn calculate_priority_fees(&self, transactions: &[Transaction]) -> Option<String> {
         let mut total_priority_fees = 0u128;
@@ -873,10 +1917,14 @@ impl BlockResponse {
                 "epoch": self.epoch,
                 "slot_root": self.slot_root,
                 "parent_root": self.parent_root,
-                "beacon_deposit_count": self.beacon_deposit_count,
+                "block_deposit_count": self.block_deposit_count,
+                "eth1_deposit_count": self.eth1_deposit_count,
                 "graffiti": self.graffiti,
                 "randao_reveal": self.randao_reveal,
-                "randao_mix": self.randao_mix
+                "randao_mix": self.randao_mix,
+                "sync_participation": self.sync_participation,
+                "attestation_count": self.attestation_count,
+                "slashing_count": self.slashing_count
             }))
         } else {
             None
@@ -968,6 +2016,243 @@ impl BlockResponse {
     }
 }
 
+/// Under MEV-boost the builder is the fee recipient and pays the proposer in the last
+/// transaction of the block: that transaction when it moves value from `fee_recipient` to
+/// another address
+pub fn proposer_payment<'a>(
+    fee_recipient: &str,
+    transactions: &'a [Transaction],
+) -> Option<&'a Transaction> {
+    transactions
+        .iter()
+        .max_by_key(|tx| tx.transaction_index)
+        .filter(|tx| {
+            tx.from_address.eq_ignore_ascii_case(fee_recipient)
+                && tx
+                    .to_address
+                    .as_ref()
+                    .is_some_and(|to| !to.eq_ignore_ascii_case(fee_recipient))
+                && tx.value.parse::<u128>().is_ok_and(|value| value > 0)
+        })
+}
+
+/// Every row the indexer derives from a block, written together by `store_block_data`
+#[derive(Debug, Serialize)]
+pub struct BlockData {
+    pub block: Block,
+    pub withdrawals: Vec<Withdrawal>,
+    pub deposits: Vec<Deposit>, // Beacon deposits made by the block's transactions
+    pub transactions: Vec<Transaction>,
+    pub receipts: Vec<Receipt>,
+    pub logs: Vec<Log>,
+    pub token_transfers: Vec<TokenTransfer>,
+    pub accounts: Vec<Account>, // Accounts involved, counting the block's transactions only
+    pub contract_deployments: Vec<ContractDeployment>,
+    #[serde(skip)]
+    pub contract_codes: Vec<ContractCode>, // Bytecode of the deployed contracts
+    pub l2_block: Option<L2Block>,
+    pub l2_transactions: Vec<L2Transaction>,
+    pub fee_recipient: Option<BlockFeeRecipient>, // When the beacon block was fetched
+    pub delegation_changes: Vec<DelegationChange>,
+    pub approval_changes: Vec<ApprovalChange>,
+}
+
+/// Proposer rewards of a block, from the beacon API and the fee recipient balance
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BlockRewards {
+    pub block_number: i64,
+    pub slot: i64,
+    pub proposer_index: i64,
+    pub total: String, // Consensus layer amounts in Gwei
+    pub attestations: String,
+    pub sync_aggregate: String,
+    pub proposer_slashings: String,
+    pub attester_slashings: String,
+    pub fee_recipient_reward: Option<String>, // Signed Wei, None without archive state
+}
+
+/// Chain reorganization observed while indexing the head
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Reorg {
+    pub id: i64,
+    pub first_block: i64,        // Oldest replaced block
+    pub depth: i64,              // Number of blocks replaced
+    pub replaced_blocks: String, // JSON encoded [{number, old_hash, new_hash}]
+    pub created_at: Option<String>,
+}
+
+/// Block range uploaded by the dataset publisher
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PublishedRange {
+    pub id: i64,
+    pub target: String, // s3://bucket/prefix or ipfs
+    pub format: String, // "parquet" or "csv"
+    pub from_block: i64,
+    pub to_block: i64,
+    pub files: String, // JSON encoded [{table, rows, bytes, sha256, location}]
+    pub published_at: Option<String>,
+}
+
+/// Previous version of a block, kept when reorg handling or a repair rewrote it
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BlockVersion {
+    pub id: i64,
+    pub block_number: i64,
+    pub hash: String,
+    pub reason: String, // "reorg" or "consistency_repair"
+    pub data: String,   // JSON snapshot of the replaced row
+    pub replaced_at: Option<String>,
+}
+
+/// Previous version of a transaction, kept when reorg handling or a repair rewrote it
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TransactionVersion {
+    pub id: i64,
+    pub hash: String,
+    pub block_number: i64,
+    pub reason: String, // "reorg" or "consistency_repair"
+    pub data: String,   // JSON snapshot of the replaced row
+    pub replaced_at: Option<String>,
+}
+
+/// Rollup-specific data of a transaction on an OP-stack or Arbitrum network
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+pub struct L2Transaction {
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub kind: Option<String>, // System transaction kind, None for user transactions
+    pub l1_fee: Option<String>, // Wei
+    pub l1_gas_used: Option<i64>,
+    pub l1_gas_price: Option<String>, // Wei
+    pub l1_fee_scalar: Option<String>,
+    pub l1_blob_base_fee: Option<String>, // Wei
+    pub l1_block_number: Option<i64>,
+    pub source_hash: Option<String>, // OP-stack deposits only
+    pub mint: Option<String>,        // Wei, OP-stack deposits only
+}
+
+/// L1 origin and batch references of a block on an OP-stack or Arbitrum network
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+pub struct L2Block {
+    pub block_number: i64,
+    pub l1_block_number: Option<i64>,
+    pub l1_block_hash: Option<String>,
+    pub l1_timestamp: Option<i64>,
+    pub sequence_number: Option<i64>,
+    pub batcher_hash: Option<String>,
+    pub send_count: Option<i64>,
+    pub send_root: Option<String>,
+}
+
+/// Change of an account in an ERC-20 Votes token, decoded from a DelegateChanged event
+/// (`delegate` set) or a DelegateVotesChanged event (`votes` set)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationChange {
+    pub token_address: String,
+    pub account_address: String, // Delegator, or delegate whose voting power changed
+    pub delegate: Option<String>,
+    pub votes: Option<String>, // New voting power in base units
+    pub block_number: i64,
+    pub log_index: i64,
+}
+
+/// Delegate of an ERC-20 Votes token with its current voting power
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TokenDelegate {
+    pub address: String,
+    pub votes: String, // Base units
+    pub votes_block: Option<i64>,
+    pub delegators: i64,          // Accounts currently delegating to it
+    pub delegate: Option<String>, // Account the delegate itself delegates to
+}
+
+/// Approval granted by an account, decoded from an ERC-20 Approval event (`allowance`)
+/// or an ERC-721/1155 ApprovalForAll event (`operator`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalChange {
+    pub token_address: String,
+    pub owner_address: String,
+    pub spender_address: String, // Spender, or operator
+    pub kind: String,            // allowance or operator
+    pub value: String,           // Allowance in base units, or 1 / 0 for operators
+    pub transaction_hash: String,
+    pub block_number: i64,
+    pub log_index: i64,
+}
+
+/// Current approval of an account with the metadata of its token
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TokenApproval {
+    pub token_address: String,
+    pub token_name: Option<String>,
+    pub token_symbol: Option<String>,
+    pub token_decimals: Option<u8>,
+    pub token_type: Option<String>, // None for tokens not discovered
+    pub spender_address: String,
+    pub kind: String,
+    pub value: String,
+    pub unlimited: bool, // Allowance of 2^256 - 1, never decreased by transfers
+    pub transaction_hash: String,
+    pub block_number: i64,
+}
+
+/// Row count and page sizes of a table on the day it was sampled
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct StorageSnapshot {
+    pub sampled_on: String, // UTC date, YYYY-MM-DD
+    pub table_name: String,
+    pub row_count: i64,
+    pub table_bytes: i64,
+    pub index_bytes: i64,
+}
+
+/// Latest storage of a table with its average daily growth
+#[derive(Debug, Clone, Serialize)]
+pub struct TableStorage {
+    pub table_name: String,
+    pub row_count: i64,
+    pub table_bytes: i64,
+    pub index_bytes: i64,
+    pub total_bytes: i64,
+    pub growth: Option<StorageGrowth>, // None until the table was sampled on two days
+}
+
+/// Growth of a table between its oldest sample in the report window and the latest one
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageGrowth {
+    pub since: String,
+    pub days: i64,
+    pub rows_per_day: f64,
+    pub bytes_per_day: f64, // Table and index bytes
+}
+
+/// Fee recipient of the beacon execution payload of a block, cross-checked with its miner,
+/// and the payment the fee recipient made to the proposer in the last transaction
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+pub struct BlockFeeRecipient {
+    pub block_number: i64,
+    pub slot: Option<i64>,
+    pub proposer_index: Option<i64>,
+    pub miner: Option<String>,
+    pub fee_recipient: String,
+    pub mismatch: bool, // Fee recipient differs from the miner
+    pub proposer_payee: Option<String>,
+    pub proposer_payment: Option<String>, // Wei
+}
+
+/// Blocks built by a fee recipient paying a proposer payee, from `block_fee_recipients`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct FeeRecipientRelationship {
+    pub fee_recipient: String,
+    pub proposer_payee: Option<String>, // None for blocks without a proposer payment
+    pub blocks: i64,
+    pub mismatches: i64,
+    pub proposers: i64, // Distinct proposer indexes
+    pub total_payment_eth: f64,
+    pub first_block: i64,
+    pub last_block: i64,
+}
+
 /// Withdrawal data structure (EIP-4895 - Beacon chain push withdrawals)
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Withdrawal {
@@ -981,6 +2266,93 @@ pub struct Withdrawal {
     pub created_at: Option<String>,
 }
 
+/// Beacon chain deposit, decoded from a DepositEvent of the deposit contract
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Deposit {
+    pub block_number: i64,
+    pub log_index: i64,
+    pub transaction_hash: String,
+    pub deposit_index: i64,
+    pub depositor: String, // Sender of the deposit transaction
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub amount: String, // Gwei
+    pub signature: String,
+    #[sqlx(default)]
+    pub validator_index: Option<i64>, // Once the validator registry sync has seen the pubkey
+}
+
+/// Latest justified or finalized checkpoint of the beacon chain
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct FinalityCheckpoint {
+    pub kind: String, // justified or finalized
+    pub epoch: i64,
+    pub root: String,
+    pub block_number: Option<i64>, // None while the checkpoint block is pre-merge or unknown
+    pub updated_at: Option<String>,
+}
+
+/// Newest execution blocks covered by the justified and finalized checkpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Finality {
+    pub justified_block: Option<i64>,
+    pub finalized_block: Option<i64>,
+}
+
+impl Finality {
+    pub fn from_checkpoints(checkpoints: &[FinalityCheckpoint]) -> Self {
+        let block = |kind: &str| {
+            checkpoints
+                .iter()
+                .find(|checkpoint| checkpoint.kind == kind)
+                .and_then(|checkpoint| checkpoint.block_number)
+        };
+        Self {
+            justified_block: block("justified"),
+            finalized_block: block("finalized"),
+        }
+    }
+}
+
+/// Totals of the indexed blocks of a beacon epoch
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Epoch {
+    pub epoch: i64,
+    pub blocks_proposed: i64,
+    pub missed_slots: i64, // Slots without an indexed block, up to the newest indexed slot
+    pub attestations: Option<i64>,
+    pub deposits: Option<i64>,
+    pub slashings: Option<i64>,
+    pub avg_sync_participation: Option<f64>, // Percentage, None before Altair
+    pub updated_at: Option<String>,
+}
+
+/// Status of block `number`: finalized and safe (justified) from the finality checkpoints
+/// when known, otherwise from its age, counting 32 blocks (2 epochs) for finality and 12
+/// for safety. Blocks with a confirmation are pending, the newest one is latest.
+pub fn block_status(number: i64, latest_block: i64, finality: &Finality) -> &'static str {
+    let block_age = latest_block - number;
+    let (finalized, safe) = match finality.finalized_block {
+        Some(finalized_block) => (
+            number <= finalized_block,
+            finality
+                .justified_block
+                .is_some_and(|justified_block| number <= justified_block),
+        ),
+        None => (block_age >= 32, block_age >= 12),
+    };
+
+    if finalized {
+        "finalized"
+    } else if safe {
+        "safe"
+    } else if block_age >= 1 {
+        "pending"
+    } else {
+        "latest"
+    }
+}
+
 /// MEV analysis helper structure
 #[derive(Debug, Default)]
 struct MevAnalysis {
@@ -0,0 +1,275 @@
+//! Optional partitioning of block data into one SQLite file per range of blocks.
+//!
+//! The main database keeps receiving every write. The sharding job later moves finalized
+//! ranges into `<database>.shard-<first block>.db` files, so the file the indexer writes to
+//! stays small. Lookups by block number or hash fall back to the shard holding the block,
+//! and the keyset pagination paths continue into older shards once the main database runs
+//! out of rows. Aggregates and counts only cover the main database.
+
+use super::{archive_columns, block_column, open_database, DatabaseService};
+use anyhow::{Context, Result};
+use sqlx::{Connection, Pool, Sqlite};
+use std::{future::Future, path::Path};
+use tracing::{error, info};
+
+impl DatabaseService {
+    /// Split block data into shard files of `shard_blocks` blocks, opening the shards
+    /// already on disk. 0 leaves sharding disabled.
+    pub async fn with_shards(mut self, shard_blocks: i64) -> Result<Self> {
+        self.shard_blocks = shard_blocks.max(0);
+        if self.shard_blocks == 0 {
+            return Ok(self);
+        }
+
+        let path = Path::new(&self.path);
+        let prefix = format!(
+            "{}.shard-",
+            path.file_stem().unwrap_or_default().to_string_lossy()
+        );
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let mut starts = Vec::new();
+        for entry in std::fs::read_dir(dir).context("Failed to list database directory")? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if let Some(start) = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".db"))
+                .and_then(|start| start.parse::<i64>().ok())
+            {
+                starts.push(start);
+            }
+        }
+
+        for start in starts {
+            // Shards written with another size would be routed to the wrong file
+            anyhow::ensure!(
+                start % self.shard_blocks == 0,
+                "Shard {} does not start at a multiple of DATABASE_SHARD_BLOCKS={}",
+                self.shard_path(start),
+                self.shard_blocks
+            );
+            self.shard_pool(start).await?;
+        }
+
+        info!(
+            "Database sharding enabled, {} blocks per shard, {} shards open",
+            self.shard_blocks,
+            self.shard_starts().len()
+        );
+        Ok(self)
+    }
+
+    /// Blocks per shard file, 0 when sharding is disabled
+    pub fn shard_blocks(&self) -> i64 {
+        self.shard_blocks
+    }
+
+    /// First block of the shard holding `block_number`
+    pub fn shard_start(&self, block_number: i64) -> i64 {
+        block_number - block_number.rem_euclid(self.shard_blocks.max(1))
+    }
+
+    /// First blocks of the open shards, oldest first
+    pub fn shard_starts(&self) -> Vec<i64> {
+        self.shards.read().unwrap().keys().copied().collect()
+    }
+
+    /// File of the shard starting at block `start`, next to the main database
+    fn shard_path(&self, start: i64) -> String {
+        let path = Path::new(&self.path);
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{}.shard-{}.db", stem, start))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Pool of the shard starting at block `start`, creating its file when missing
+    async fn shard_pool(&self, start: i64) -> Result<Pool<Sqlite>> {
+        if let Some(pool) = self.shards.read().unwrap().get(&start) {
+            return Ok(pool.clone());
+        }
+
+        let pool = open_database(&self.shard_path(start), 4).await?;
+        Ok(self
+            .shards
+            .write()
+            .unwrap()
+            .entry(start)
+            .or_insert(pool)
+            .clone())
+    }
+
+    /// Pools that may hold `block_number`: the main database, then its shard when open
+    pub(super) fn pools_for_block(&self, block_number: i64) -> Vec<Pool<Sqlite>> {
+        let mut pools = vec![self.pool.clone()];
+        if self.shard_blocks > 0
+            && let Some(shard) = self
+                .shards
+                .read()
+                .unwrap()
+                .get(&self.shard_start(block_number))
+        {
+            pools.push(shard.clone());
+        }
+        pools
+    }
+
+    /// Shards to search for a transaction missing from the main database, newest first.
+    /// Empty when the main database holds the transaction, so its logs and transfers are
+    /// never looked up in every shard.
+    pub(super) async fn shards_for_transaction(&self, hash: &str) -> Result<Vec<Pool<Sqlite>>> {
+        if self.shards.read().unwrap().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let stored: Option<i64> = sqlx::query_scalar("SELECT 1 FROM transactions WHERE hash = ?")
+            .bind(hash)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to look up transaction")?;
+        if stored.is_some() {
+            return Ok(Vec::new());
+        }
+
+        Ok(self
+            .shards
+            .read()
+            .unwrap()
+            .values()
+            .rev()
+            .cloned()
+            .collect())
+    }
+
+    /// Run a newest-first keyset query below block `before` on the main database, then on
+    /// the older shards until `limit` rows are found. Rows are merged by `key`, as blocks
+    /// backfilled after a rotation sit in the main database until the next one.
+    pub(super) async fn collect_before<T, K, F, Fut>(
+        &self,
+        before: i64,
+        limit: i64,
+        block_of: impl Fn(&T) -> i64,
+        key: impl Fn(&T) -> K,
+        query: F,
+    ) -> Result<Vec<T>>
+    where
+        K: Ord,
+        F: Fn(Pool<Sqlite>) -> Fut,
+        Fut: Future<Output = Result<Vec<T>>>,
+    {
+        let mut rows = query(self.pool.clone()).await?;
+        let shards: Vec<(i64, Pool<Sqlite>)> = self
+            .shards
+            .read()
+            .unwrap()
+            .range(..before)
+            .rev()
+            .map(|(start, pool)| (*start, pool.clone()))
+            .collect();
+
+        let limit = usize::try_from(limit).unwrap_or(0);
+        for (start, pool) in shards {
+            // Every row still to come is older than the ones already collected
+            let oldest = rows.iter().map(&block_of).min();
+            if rows.len() >= limit
+                && oldest.is_some_and(|oldest| oldest >= start + self.shard_blocks)
+            {
+                break;
+            }
+            rows.extend(query(pool).await?);
+            rows.sort_by_key(|row| std::cmp::Reverse(key(row)));
+            rows.truncate(limit);
+        }
+
+        Ok(rows)
+    }
+
+    /// Move the rows of `tables` belonging to blocks `from..to` into their shard in a single
+    /// transaction. The range must not span several shards. Tables are moved in order, so
+    /// dependent tables must come before the blocks they reference. Returns the rows moved
+    /// per table.
+    pub async fn move_block_range_to_shard(
+        &self,
+        tables: &[&str],
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<(String, u64)>> {
+        let start = self.shard_start(from);
+        anyhow::ensure!(
+            self.shard_blocks > 0 && to <= start + self.shard_blocks,
+            "Blocks {}..{} do not fit in a single shard",
+            from,
+            to
+        );
+
+        // Opening the shard runs the migrations, so its tables get the main schema's indexes
+        self.shard_pool(start).await?;
+        let path = self.shard_path(start);
+
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("ATTACH DATABASE ? AS archive")
+            .bind(&path)
+            .execute(&mut *conn)
+            .await
+            .context(format!("Failed to attach shard {}", path))?;
+
+        let moved = async {
+            let mut tx = conn.begin().await?;
+            // Dependent rows reach the shard before the blocks they reference
+            sqlx::query("PRAGMA defer_foreign_keys = ON")
+                .execute(&mut *tx)
+                .await?;
+            let mut moved = Vec::new();
+
+            for &table in tables {
+                let column = block_column(table);
+                let columns = archive_columns(&mut tx, table).await?;
+                // Blocks reindexed after they were sharded replace their old rows
+                sqlx::query(&format!(
+                    "DELETE FROM archive.{table} WHERE {column} IN (SELECT number FROM main.blocks WHERE number >= ? AND number < ?)"
+                ))
+                .bind(from)
+                .bind(to)
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to clear reindexed rows of shard {}", table))?;
+                sqlx::query(&format!(
+                    "INSERT INTO archive.{table} ({columns}) SELECT {columns} FROM main.{table} WHERE {column} >= ? AND {column} < ?"
+                ))
+                .bind(from)
+                .bind(to)
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to copy {} to shard", table))?;
+
+                let result = sqlx::query(&format!(
+                    "DELETE FROM main.{table} WHERE {column} >= ? AND {column} < ?"
+                ))
+                .bind(from)
+                .bind(to)
+                .execute(&mut *tx)
+                .await
+                .context(format!("Failed to remove sharded rows of {}", table))?;
+                moved.push((table.to_string(), result.rows_affected()));
+            }
+
+            tx.commit().await?;
+            anyhow::Ok(moved)
+        }
+        .await;
+
+        let detached = sqlx::query("DETACH DATABASE archive")
+            .execute(&mut *conn)
+            .await;
+        if let Err(e) = detached {
+            // A connection left with the shard attached would fail the next move
+            error!("Failed to detach shard database: {}", e);
+            conn.close_on_drop();
+        }
+
+        moved
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, EnsName},
+    rpc::RpcClient,
+};
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// Time a failed primary name lookup is not retried for
+const FAILED_LOOKUP_TTL: Duration = Duration::from_secs(300);
+
+/// Addresses whose failed lookups are remembered at once
+const MAX_FAILED_LOOKUPS: usize = 10_000;
+
+/// Service resolving ENS names and primary names, cached in the ens_names table. Failed
+/// primary name lookups are remembered for a while, so pages enriched with primary names
+/// don't call the node on every request while it fails.
+pub struct EnsService {
+    db: Arc<DatabaseService>,
+    rpc: Arc<RpcClient>,
+    config: AppConfig,
+    failed_lookups: Mutex<HashMap<String, Instant>>, // Failure time by lowercase address
+}
+
+impl EnsService {
+    /// Create a new ENS service
+    pub fn new(db: Arc<DatabaseService>, rpc: Arc<RpcClient>, config: AppConfig) -> Self {
+        Self {
+            db,
+            rpc,
+            config,
+            failed_lookups: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether a search query looks like an ENS name (e.g. "vitalik.eth")
+    pub fn is_ens_name(query: &str) -> bool {
+        !query.starts_with("0x")
+            && query.contains('.')
+            && !query.starts_with('.')
+            && !query.ends_with('.')
+            && !query.chars().any(char::is_whitespace)
+    }
+
+    /// Resolve an ENS name to an address
+    pub async fn resolve_name(&self, name: &str) -> Result<Option<String>> {
+        let name = name.trim().to_lowercase();
+
+        if let Some(cached) = self
+            .db
+            .get_cached_ens_forward(&name, self.config.ens_cache_ttl_seconds)
+            .await?
+        {
+            return Ok(cached.address);
+        }
+
+        let address = self.rpc.resolve_ens_name(&name).await?;
+        self.store(EnsName {
+            name: Some(name),
+            address: address.clone(),
+            is_reverse: false,
+            resolved_at: None,
+        })
+        .await;
+
+        Ok(address)
+    }
+
+    /// Get the primary ENS name of an address
+    pub async fn lookup_address(&self, address: &str) -> Result<Option<String>> {
+        let address = address.to_lowercase();
+
+        if let Some(cached) = self
+            .db
+            .get_cached_ens_reverse(&address, self.config.ens_cache_ttl_seconds)
+            .await?
+        {
+            return Ok(cached.name);
+        }
+
+        let name = self.rpc.lookup_ens_name(&address).await?;
+        self.store(EnsName {
+            name: name.clone(),
+            address: Some(address),
+            is_reverse: true,
+            resolved_at: None,
+        })
+        .await;
+
+        Ok(name)
+    }
+
+    /// Primary ENS name of an address for response enrichment, None on any failure or
+    /// within FAILED_LOOKUP_TTL of a failure
+    pub async fn primary_name(&self, address: &str) -> Option<String> {
+        let address = address.to_lowercase();
+        if self
+            .failed_lookups
+            .lock()
+            .unwrap()
+            .get(&address)
+            .is_some_and(|failed_at| failed_at.elapsed() < FAILED_LOOKUP_TTL)
+        {
+            return None;
+        }
+
+        match self.lookup_address(&address).await {
+            Ok(name) => name,
+            Err(e) => {
+                debug!("ENS lookup failed for {}: {:#}", address, e);
+                let mut failed_lookups = self.failed_lookups.lock().unwrap();
+                if failed_lookups.len() >= MAX_FAILED_LOOKUPS {
+                    failed_lookups.retain(|_, failed_at| failed_at.elapsed() < FAILED_LOOKUP_TTL);
+                }
+                if failed_lookups.len() < MAX_FAILED_LOOKUPS {
+                    failed_lookups.insert(address, Instant::now());
+                }
+                None
+            }
+        }
+    }
+
+    async fn store(&self, entry: EnsName) {
+        if let Err(e) = self.db.upsert_ens_name(&entry).await {
+            debug!("Failed to cache ENS resolution: {}", e);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::database::{DatabaseService, IndexerEvent};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::error;
+
+/// Kinds of operational events persisted to the indexer_events table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexerEventKind {
+    IndexerStarted,
+    IndexerStopped,
+    IndexerPaused,
+    IndexerResumed,
+    IndexerReconfigured,
+    BlockFailed,
+    RpcFailover,
+    ArchiveFallback,
+    ReorgHandled,
+    GapRepaired,
+    BlockRequeued,
+    RangeReindexed,
+    PruningRun,
+    ShardRotated,
+    DatasetPublished,
+    AlertFired,
+    WebhookDropped,
+}
+
+impl IndexerEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IndexerEventKind::IndexerStarted => "indexer_started",
+            IndexerEventKind::IndexerStopped => "indexer_stopped",
+            IndexerEventKind::IndexerPaused => "indexer_paused",
+            IndexerEventKind::IndexerResumed => "indexer_resumed",
+            IndexerEventKind::IndexerReconfigured => "indexer_reconfigured",
+            IndexerEventKind::BlockFailed => "block_failed",
+            IndexerEventKind::RpcFailover => "rpc_failover",
+            IndexerEventKind::ArchiveFallback => "archive_fallback",
+            IndexerEventKind::ReorgHandled => "reorg_handled",
+            IndexerEventKind::GapRepaired => "gap_repaired",
+            IndexerEventKind::BlockRequeued => "block_requeued",
+            IndexerEventKind::RangeReindexed => "range_reindexed",
+            IndexerEventKind::PruningRun => "pruning_run",
+            IndexerEventKind::ShardRotated => "shard_rotated",
+            IndexerEventKind::DatasetPublished => "dataset_published",
+            IndexerEventKind::AlertFired => "alert_fired",
+            IndexerEventKind::WebhookDropped => "webhook_dropped",
+        }
+    }
+}
+
+/// Severity of an operational event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl EventSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventSeverity::Info => "info",
+            EventSeverity::Warning => "warning",
+            EventSeverity::Error => "error",
+        }
+    }
+}
+
+/// Event waiting to be recorded
+#[derive(Debug, Clone)]
+pub struct NewIndexerEvent {
+    kind: IndexerEventKind,
+    severity: EventSeverity,
+    message: String,
+    block_number: Option<i64>,
+    details: Option<Value>,
+}
+
+impl NewIndexerEvent {
+    pub fn info(kind: IndexerEventKind, message: impl Into<String>) -> Self {
+        Self::new(kind, EventSeverity::Info, message)
+    }
+
+    pub fn warning(kind: IndexerEventKind, message: impl Into<String>) -> Self {
+        Self::new(kind, EventSeverity::Warning, message)
+    }
+
+    pub fn error(kind: IndexerEventKind, message: impl Into<String>) -> Self {
+        Self::new(kind, EventSeverity::Error, message)
+    }
+
+    fn new(kind: IndexerEventKind, severity: EventSeverity, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            severity,
+            message: message.into(),
+            block_number: None,
+            details: None,
+        }
+    }
+
+    /// Attach the block the event relates to
+    pub fn at_block(mut self, block_number: i64) -> Self {
+        self.block_number = Some(block_number);
+        self
+    }
+
+    /// Attach structured details
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+/// Records operational events without blocking the caller.
+///
+/// Events are queued and written by a background task, so recording is safe from
+/// hot paths such as RPC requests and block workers.
+#[derive(Clone, Debug)]
+pub struct EventLog {
+    sender: mpsc::UnboundedSender<NewIndexerEvent>,
+}
+
+impl EventLog {
+    /// Create the event log and start its writer task
+    pub fn new(db: Arc<DatabaseService>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<NewIndexerEvent>();
+
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                let event = IndexerEvent {
+                    id: None,
+                    kind: event.kind.as_str().to_string(),
+                    severity: event.severity.as_str().to_string(),
+                    message: event.message,
+                    block_number: event.block_number,
+                    details: event.details.map(|details| details.to_string()),
+                    created_at: None,
+                };
+
+                if let Err(e) = db.insert_indexer_event(&event).await {
+                    error!("Failed to record indexer event {}: {}", event.kind, e);
+                }
+            }
+        });
+
+        Self { sender }
+    }
+
+    /// Queue an event for recording
+    pub fn record(&self, event: NewIndexerEvent) {
+        if self.sender.send(event).is_err() {
+            error!("Indexer event writer stopped, event dropped");
+        }
+    }
+}
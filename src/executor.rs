@@ -1,27 +1,219 @@
+use crate::rpc::request_id::{current_request_id, with_request_id};
 use anyhow::Result;
+use serde::Serialize;
 use std::{
-    sync::Arc,
-    time::{Duration},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 use tokio::{
     sync::{mpsc, oneshot, Semaphore},
     time,
 };
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn, Instrument, Span};
+
+/// Interval applied while throttled when the configured one is shorter
+const MIN_THROTTLED_INTERVAL_MS: u64 = 100;
+
+/// Interval throttling never goes beyond
+const MAX_THROTTLED_INTERVAL_MS: u64 = 10_000;
+
+/// Longest pause honored for a provider's Retry-After
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
 
 /// Request wrapper for the RPC executor
 pub struct RpcRequest<T, R> {
     pub operation: T,
     pub response_sender: oneshot::Sender<Result<R>>,
+    pub request_id: Option<Arc<str>>, // API request the call is made for
+    pub span: Span,                   // Span of the caller, requests run in their own task
+    pub queued_at: Instant,
+}
+
+/// Concurrency and rate limits of an executor
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RateLimits {
+    pub max_concurrent: usize,
+    pub min_interval_ms: u64,
+}
+
+/// Error of a request refused because the executor queue is full. Callers should back off
+/// before retrying rather than fail for good.
+#[derive(Debug, thiserror::Error)]
+#[error("{executor} RPC queue is full ({capacity} requests waiting), retry later")]
+pub struct RpcOverloaded {
+    pub executor: String,
+    pub capacity: usize,
+}
+
+impl RpcOverloaded {
+    /// Whether an error or one of its causes is a refused request
+    pub fn is(error: &anyhow::Error) -> bool {
+        error.chain().any(|cause| cause.is::<Self>())
+    }
+}
+
+/// Queue depth and wait times of an executor
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct QueueStats {
+    pub capacity: usize,
+    pub depth: usize,     // Requests waiting for a concurrency slot
+    pub started: u64,     // Requests that got a slot
+    pub rejected: u64,    // Requests refused because the queue was full
+    pub avg_wait_ms: f64, // Mean time between queueing and getting a slot
+    pub max_wait_ms: u64,
+    pub rate_limited: u64,             // Rate limits reported by the upstream
+    pub throttled_for_ms: Option<u64>, // Cooldown left while limits are lowered
+}
+
+/// Rate limiting reported by the transport behind an executor. The executor lowers its
+/// limits for `cooldown` after every report, and restores them once a cooldown passes
+/// without another one. A zero cooldown ignores reports.
+#[derive(Debug, Default)]
+pub struct RateLimitFeedback {
+    cooldown: Duration,
+    reports: AtomicU64,
+    pending: Mutex<Option<Option<Duration>>>, // Retry-After of a report not yet applied
+}
+
+impl RateLimitFeedback {
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            ..Default::default()
+        }
+    }
+
+    /// Report that the upstream rate limited a request, with the delay it asked for
+    pub fn report(&self, retry_after: Option<Duration>) {
+        if self.cooldown.is_zero() {
+            return;
+        }
+        self.reports.fetch_add(1, Ordering::Relaxed);
+        let mut pending = self.pending.lock().unwrap();
+        // Reports made before the executor applied the previous one collapse into one
+        let retry_after = retry_after.max(pending.flatten());
+        *pending = Some(retry_after);
+    }
+
+    /// Report not yet applied, if any
+    fn take(&self) -> Option<Option<Duration>> {
+        self.pending.lock().unwrap().take()
+    }
+}
+
+/// Limits lowered after a rate limit, with the ones to restore
+#[derive(Debug, Clone, Copy)]
+struct Throttle {
+    restore: RateLimits,
+    until: Instant,
 }
 
-/// RPC Executor with rate limiting and concurrency control
+/// Limits shared between an executor and its dispatch task
+#[derive(Debug)]
+struct LimitState {
+    semaphore: Arc<Semaphore>,
+    max_concurrent: AtomicUsize,
+    min_interval_ms: AtomicU64, // Read by every request, adjustable at runtime
+    throttle: Mutex<Option<Throttle>>,
+}
+
+impl LimitState {
+    fn limits(&self) -> RateLimits {
+        RateLimits {
+            max_concurrent: self.max_concurrent.load(Ordering::Relaxed),
+            min_interval_ms: self.min_interval_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Change the limits of subsequent requests, in-flight requests finish under the old ones
+    fn apply(&self, limits: RateLimits) {
+        let max_concurrent = limits.max_concurrent.max(1);
+        let previous = self.max_concurrent.swap(max_concurrent, Ordering::Relaxed);
+        if max_concurrent > previous {
+            self.semaphore.add_permits(max_concurrent - previous);
+        } else if max_concurrent < previous {
+            // Retire the surplus permits once the requests holding them complete
+            let semaphore = self.semaphore.clone();
+            tokio::spawn(async move {
+                if let Ok(permits) = semaphore
+                    .acquire_many((previous - max_concurrent) as u32)
+                    .await
+                {
+                    permits.forget();
+                }
+            });
+        }
+        self.min_interval_ms
+            .store(limits.min_interval_ms, Ordering::Relaxed);
+    }
+
+    /// Halve concurrency and double the interval for `cooldown`, on top of any throttle
+    /// in effect
+    fn throttle(&self, name: &str, cooldown: Duration) {
+        let mut throttle = self.throttle.lock().unwrap();
+        let current = self.limits();
+        let restore = throttle.map_or(current, |throttle| throttle.restore);
+        let lowered = RateLimits {
+            max_concurrent: (current.max_concurrent / 2).max(1),
+            min_interval_ms: (current.min_interval_ms * 2)
+                .clamp(MIN_THROTTLED_INTERVAL_MS, MAX_THROTTLED_INTERVAL_MS),
+        };
+        self.apply(lowered);
+        *throttle = Some(Throttle {
+            restore,
+            until: Instant::now() + cooldown,
+        });
+
+        warn!(
+            "{} RPC rate limited, lowering limits to max_concurrent={}, min_interval={}ms for {}s",
+            name,
+            lowered.max_concurrent,
+            lowered.min_interval_ms,
+            cooldown.as_secs()
+        );
+    }
+
+    /// Restore the limits lowered by a throttle whose cooldown passed
+    fn restore_expired(&self, name: &str) {
+        let mut throttle = self.throttle.lock().unwrap();
+        if let Some(expired) = throttle.filter(|throttle| Instant::now() >= throttle.until) {
+            self.apply(expired.restore);
+            *throttle = None;
+            info!(
+                "{} RPC rate limit cooldown over, restoring max_concurrent={}, min_interval={}ms",
+                name, expired.restore.max_concurrent, expired.restore.min_interval_ms
+            );
+        }
+    }
+}
+
+/// Counters behind `QueueStats`, shared with the dispatch task
+#[derive(Debug, Default)]
+struct QueueMetrics {
+    depth: AtomicUsize,
+    started: AtomicU64,
+    rejected: AtomicU64,
+    total_wait_ms: AtomicU64,
+    max_wait_ms: AtomicU64,
+}
+
+/// RPC Executor with rate limiting and concurrency control. Requests wait for a
+/// concurrency slot in a bounded queue; once it is full they are refused with
+/// `RpcOverloaded` instead of piling up behind a stalled upstream.
 pub struct RpcExecutor<T, R>
 where
     T: Send + 'static,
     R: Send + 'static,
 {
-    request_sender: mpsc::UnboundedSender<RpcRequest<T, R>>,
+    name: String,
+    request_sender: mpsc::Sender<RpcRequest<T, R>>,
+    queue_capacity: usize,
+    queue_metrics: Arc<QueueMetrics>,
+    limits: Arc<LimitState>,
+    rate_limits: Arc<RateLimitFeedback>,
     _handle: tokio::task::JoinHandle<()>,
 }
 
@@ -30,47 +222,107 @@ where
     T: Send + 'static,
     R: Send + 'static,
 {
-    /// Create a new RPC executor with rate limiting
+    /// Create a new RPC executor with rate limiting, queueing up to `queue_capacity`
+    /// requests
     pub fn new<F, Fut>(
         name: String,
         max_concurrent: usize,
         min_interval_ms: u64,
+        queue_capacity: usize,
+        executor_fn: F,
+    ) -> Self
+    where
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+    {
+        Self::with_rate_limit_feedback(
+            name,
+            max_concurrent,
+            min_interval_ms,
+            queue_capacity,
+            Arc::new(RateLimitFeedback::default()),
+            executor_fn,
+        )
+    }
+
+    /// Create a new RPC executor that also lowers its limits while `rate_limits`
+    /// reports upstream rate limiting, pausing dispatch for the Retry-After it carries
+    pub fn with_rate_limit_feedback<F, Fut>(
+        name: String,
+        max_concurrent: usize,
+        min_interval_ms: u64,
+        queue_capacity: usize,
+        rate_limits: Arc<RateLimitFeedback>,
         executor_fn: F,
     ) -> Self
     where
         F: Fn(T) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<R>> + Send + 'static,
     {
-        let (request_sender, mut request_receiver) = mpsc::unbounded_channel::<RpcRequest<T, R>>();
+        let queue_capacity = queue_capacity.max(1);
+        let (request_sender, mut request_receiver) =
+            mpsc::channel::<RpcRequest<T, R>>(queue_capacity);
         let executor_fn = Arc::new(executor_fn);
-        let semaphore = Arc::new(Semaphore::new(max_concurrent));
-        let min_interval = Duration::from_millis(min_interval_ms);
+        let limits = Arc::new(LimitState {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent: AtomicUsize::new(max_concurrent),
+            min_interval_ms: AtomicU64::new(min_interval_ms),
+            throttle: Mutex::new(None),
+        });
+        let queue_metrics = Arc::new(QueueMetrics::default());
 
         debug!(
-            "{} RPC Executor starting: max_concurrent={}, min_interval={}ms",
-            name, max_concurrent, min_interval_ms
+            "{} RPC Executor starting: max_concurrent={}, min_interval={}ms, queue_capacity={}",
+            name, max_concurrent, min_interval_ms, queue_capacity
         );
 
+        let handle_limits = limits.clone();
+        let handle_rate_limits = rate_limits.clone();
+        let handle_metrics = queue_metrics.clone();
+        let executor_name = name.clone();
         let handle = tokio::spawn(async move {
             while let Some(request) = request_receiver.recv().await {
+                // Rate limits reported since the last request lower the limits before it
+                // is dispatched, and the dispatch waits out the delay the upstream asked for
+                if let Some(retry_after) = handle_rate_limits.take() {
+                    handle_limits.throttle(&name, handle_rate_limits.cooldown);
+                    if let Some(retry_after) = retry_after {
+                        time::sleep(retry_after.min(MAX_RETRY_AFTER)).await;
+                    }
+                } else {
+                    handle_limits.restore_expired(&name);
+                }
+
+                // Requests stay queued until a concurrency slot frees up, so the queue
+                // bound covers every request not yet running
+                let acquired = handle_limits.semaphore.clone().acquire_owned().await;
+                handle_metrics.depth.fetch_sub(1, Ordering::Relaxed);
+                let permit = match acquired {
+                    Ok(permit) => permit,
+                    Err(_) => {
+                        error!("{} RPC failed to acquire semaphore permit", name);
+                        let _ = request
+                            .response_sender
+                            .send(Err(anyhow::anyhow!("Failed to acquire semaphore permit")));
+                        continue;
+                    }
+                };
+                let wait_ms = request.queued_at.elapsed().as_millis() as u64;
+                handle_metrics.started.fetch_add(1, Ordering::Relaxed);
+                handle_metrics
+                    .total_wait_ms
+                    .fetch_add(wait_ms, Ordering::Relaxed);
+                handle_metrics
+                    .max_wait_ms
+                    .fetch_max(wait_ms, Ordering::Relaxed);
+
                 let executor_fn = executor_fn.clone();
-                let semaphore = semaphore.clone();
+                let min_interval =
+                    Duration::from_millis(handle_limits.min_interval_ms.load(Ordering::Relaxed));
                 let request_name = name.clone();
 
-                // Spawn task to handle the request with concurrency control and rate limiting
+                // Spawn task to handle the request, holding the permit until it completes
                 tokio::spawn(async move {
-                    // Acquire semaphore permit for concurrency control
-                    let _permit = match semaphore.acquire().await {
-                        Ok(permit) => permit,
-                        Err(_) => {
-                            error!("{} RPC failed to acquire semaphore permit", request_name);
-                            let _ = request
-                                .response_sender
-                                .send(Err(anyhow::anyhow!("Failed to acquire semaphore permit")));
-                            return;
-                        }
-                    };
-
                     // Rate limiting per request (after acquiring permit)
                     if min_interval > Duration::ZERO {
                         time::sleep(min_interval).await;
@@ -78,13 +330,17 @@ where
 
                     debug!("{} RPC executing request", request_name);
 
-                    // Execute the request
-                    let result = executor_fn(request.operation).await;
+                    // Execute the request on behalf of the caller
+                    let result =
+                        with_request_id(request.request_id, executor_fn(request.operation))
+                            .instrument(request.span)
+                            .await;
 
                     // Send response back
                     if let Err(_) = request.response_sender.send(result) {
                         debug!("{} RPC response receiver dropped", request_name);
                     }
+                    drop(permit);
                 });
             }
 
@@ -92,24 +348,97 @@ where
         });
 
         Self {
+            name: executor_name,
             request_sender,
+            queue_capacity,
+            queue_metrics,
+            limits,
+            rate_limits,
             _handle: handle,
         }
     }
 
-    /// Execute a request through the rate-limited executor
+    /// Current concurrency and rate limits
+    pub fn limits(&self) -> RateLimits {
+        self.limits.limits()
+    }
+
+    /// Current queue depth and wait times
+    pub fn queue_stats(&self) -> QueueStats {
+        let metrics = &self.queue_metrics;
+        let started = metrics.started.load(Ordering::Relaxed);
+        QueueStats {
+            capacity: self.queue_capacity,
+            depth: metrics.depth.load(Ordering::Relaxed),
+            started,
+            rejected: metrics.rejected.load(Ordering::Relaxed),
+            avg_wait_ms: if started > 0 {
+                metrics.total_wait_ms.load(Ordering::Relaxed) as f64 / started as f64
+            } else {
+                0.0
+            },
+            max_wait_ms: metrics.max_wait_ms.load(Ordering::Relaxed),
+            rate_limited: self.rate_limits.reports.load(Ordering::Relaxed),
+            throttled_for_ms: self.limits.throttle.lock().unwrap().map(|throttle| {
+                throttle
+                    .until
+                    .saturating_duration_since(Instant::now())
+                    .as_millis() as u64
+            }),
+        }
+    }
+
+    /// Change the limits of subsequent requests, in-flight requests finish under the old
+    /// ones. Limits set by hand end any rate limit cooldown.
+    pub fn set_limits(&self, limits: RateLimits) {
+        let mut throttle = self.limits.throttle.lock().unwrap();
+        *throttle = None;
+        self.limits.apply(limits);
+
+        debug!(
+            "RPC Executor limits changed: max_concurrent={}, min_interval={}ms",
+            limits.max_concurrent.max(1),
+            limits.min_interval_ms
+        );
+    }
+
+    /// Execute a request through the rate-limited executor, failing with `RpcOverloaded`
+    /// when the queue is full
     pub async fn execute(&self, operation: T) -> Result<R> {
         let (response_sender, response_receiver) = oneshot::channel();
 
         let request = RpcRequest {
             operation,
             response_sender,
+            request_id: current_request_id(),
+            span: Span::current(),
+            queued_at: Instant::now(),
         };
 
-        // Send request to executor
-        self.request_sender
-            .send(request)
-            .map_err(|_| anyhow::anyhow!("RPC executor receiver dropped"))?;
+        // Counted before sending, the dispatch task may dequeue the request right away. The
+        // request the dispatch task holds while waiting for a slot counts too, so the
+        // channel itself never fills up.
+        let depth = self.queue_metrics.depth.fetch_add(1, Ordering::Relaxed);
+        if depth >= self.queue_capacity {
+            self.queue_metrics.depth.fetch_sub(1, Ordering::Relaxed);
+            let rejected = self.queue_metrics.rejected.fetch_add(1, Ordering::Relaxed);
+            // Logged once per thousand refusals, callers retry in a tight loop
+            if rejected.is_multiple_of(1000) {
+                warn!(
+                    "{} RPC queue is full ({} requests waiting), refusing requests",
+                    self.name, self.queue_capacity
+                );
+            }
+            return Err(RpcOverloaded {
+                executor: self.name.clone(),
+                capacity: self.queue_capacity,
+            }
+            .into());
+        }
+        if self.request_sender.try_send(request).is_err() {
+            self.queue_metrics.depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(anyhow::anyhow!("RPC executor receiver dropped"));
+        }
 
         // Wait for response
         response_receiver
@@ -123,7 +452,16 @@ where
 pub enum EthRpcOperation {
     GetLatestBlockNumber,
     GetBlockByNumber(u64),
+    GetBlockHeader(u64),
+    GetTransaction(String),
     GetTransactionReceipt(String),
+    GetBlockReceipts(u64),
+    TraceBlockCalls(u64),
+    Call {
+        to: String,
+        data: Vec<u8>,
+        block_number: Option<u64>,
+    },
     CheckConnection,
 }
 
@@ -133,9 +471,15 @@ pub enum BeaconRpcOperation {
     TestConnection,
     GetBlockHeader(u64),
     GetBlock(u64),
-    GetSlotByExecutionBlock(u64),
+    GetSlotByExecutionBlock(u64, u64), // Block number, timestamp
     GetDepositCount,
-    GetBeaconDataForBlock(u64),
+    GetBeaconDataForBlock(u64, u64), // Block number, timestamp
+    GetValidators(Vec<u64>),
+    GetFinalityCheckpoints,
+    GetExecutionBlockNumber(String), // Beacon block id: slot, root, "head" or "finalized"
+    GetProposerDuties(u64),          // Epoch
+    GetRandaoMix(u64),               // Slot
+    GetBlockRewards(u64),            // Slot
 }
 
 /// Response types for Beacon RPC operations
@@ -147,4 +491,10 @@ pub enum BeaconRpcResponse {
     SlotByExecutionBlock(Option<u64>),
     DepositCount(u64),
     BeaconDataForBlock(serde_json::Value),
+    Validators(Vec<serde_json::Value>),
+    FinalityCheckpoints(serde_json::Value),
+    ExecutionBlockNumber(Option<u64>),
+    ProposerDuties(Vec<serde_json::Value>),
+    RandaoMix(Option<String>),
+    BlockRewards(Option<serde_json::Value>),
 }
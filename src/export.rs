@@ -0,0 +1,530 @@
+use crate::database::{Block, DatabaseService, HolderBalance, Log, TokenTransfer, Transaction};
+use anyhow::{Context, Result};
+use arrow_array::{
+    builder::{Int64Builder, StringBuilder},
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema};
+use futures::{stream::BoxStream, StreamExt};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+/// Tables a cold-storage export can dump
+pub const EXPORT_TABLES: [&str; 4] = ["blocks", "transactions", "logs", "token_transfers"];
+
+/// Rows written per Parquet record batch
+const PARQUET_BATCH_ROWS: usize = 8192;
+
+/// Type of an exported column
+#[derive(Debug, Clone, Copy)]
+pub enum ColumnType {
+    Integer,
+    Text, // Also used for 256-bit amounts, which don't fit any integer column
+}
+
+/// Value of an exported column, None for NULL
+#[derive(Debug)]
+pub enum ExportValue {
+    Integer(Option<i64>),
+    Text(Option<String>),
+}
+
+impl ExportValue {
+    fn to_csv(&self) -> String {
+        match self {
+            ExportValue::Integer(value) => value.map(|v| v.to_string()).unwrap_or_default(),
+            ExportValue::Text(value) => csv_escape(value.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+/// Rows that can be exported, through the export endpoints or to cold-storage files
+pub trait ExportRow: Serialize + Send + 'static {
+    /// Column names and types, in output order
+    const COLUMNS: &'static [(&'static str, ColumnType)];
+
+    /// Values of the row, in the order of `COLUMNS`
+    fn values(&self) -> Vec<ExportValue>;
+}
+
+/// CSV header line of a row type, without line break
+pub fn csv_header<T: ExportRow>() -> String {
+    T::COLUMNS
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Encode a row as a CSV line, without line break
+pub fn csv_line<T: ExportRow>(row: &T) -> String {
+    row.values()
+        .iter()
+        .map(ExportValue::to_csv)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Quote a CSV field when it contains separators, quotes or line breaks
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn integer(value: i64) -> ExportValue {
+    ExportValue::Integer(Some(value))
+}
+
+fn text(value: &str) -> ExportValue {
+    ExportValue::Text(Some(value.to_string()))
+}
+
+fn optional_text(value: &Option<String>) -> ExportValue {
+    ExportValue::Text(value.clone())
+}
+
+impl ExportRow for Block {
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("number", ColumnType::Integer),
+        ("hash", ColumnType::Text),
+        ("parent_hash", ColumnType::Text),
+        ("timestamp", ColumnType::Integer),
+        ("gas_used", ColumnType::Integer),
+        ("gas_limit", ColumnType::Integer),
+        ("transaction_count", ColumnType::Integer),
+        ("miner", ColumnType::Text),
+        ("base_fee_per_gas", ColumnType::Text),
+        ("size_bytes", ColumnType::Integer),
+        ("withdrawal_count", ColumnType::Integer),
+        ("blob_gas_used", ColumnType::Integer),
+        ("excess_blob_gas", ColumnType::Integer),
+        ("slot", ColumnType::Integer),
+        ("proposer_index", ColumnType::Integer),
+        ("epoch", ColumnType::Integer),
+    ];
+
+    fn values(&self) -> Vec<ExportValue> {
+        vec![
+            integer(self.number),
+            text(&self.hash),
+            text(&self.parent_hash),
+            integer(self.timestamp),
+            integer(self.gas_used),
+            integer(self.gas_limit),
+            integer(self.transaction_count),
+            optional_text(&self.miner),
+            optional_text(&self.base_fee_per_gas),
+            ExportValue::Integer(self.size_bytes),
+            ExportValue::Integer(self.withdrawal_count),
+            ExportValue::Integer(self.blob_gas_used),
+            ExportValue::Integer(self.excess_blob_gas),
+            ExportValue::Integer(self.slot),
+            ExportValue::Integer(self.proposer_index),
+            ExportValue::Integer(self.epoch),
+        ]
+    }
+}
+
+impl ExportRow for Transaction {
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("hash", ColumnType::Text),
+        ("block_number", ColumnType::Integer),
+        ("transaction_index", ColumnType::Integer),
+        ("from_address", ColumnType::Text),
+        ("to_address", ColumnType::Text),
+        ("value", ColumnType::Text),
+        ("gas_used", ColumnType::Integer),
+        ("gas_price", ColumnType::Text),
+        ("status", ColumnType::Integer),
+    ];
+
+    fn values(&self) -> Vec<ExportValue> {
+        vec![
+            text(&self.hash),
+            integer(self.block_number),
+            integer(self.transaction_index),
+            text(&self.from_address),
+            optional_text(&self.to_address),
+            text(&self.value),
+            integer(self.gas_used),
+            text(&self.gas_price),
+            integer(self.status),
+        ]
+    }
+}
+
+impl ExportRow for Log {
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("transaction_hash", ColumnType::Text),
+        ("block_number", ColumnType::Integer),
+        ("log_index", ColumnType::Integer),
+        ("address", ColumnType::Text),
+        ("topic0", ColumnType::Text),
+        ("topic1", ColumnType::Text),
+        ("topic2", ColumnType::Text),
+        ("topic3", ColumnType::Text),
+        ("data", ColumnType::Text),
+    ];
+
+    fn values(&self) -> Vec<ExportValue> {
+        vec![
+            text(&self.transaction_hash),
+            integer(self.block_number),
+            integer(self.log_index),
+            text(&self.address),
+            optional_text(&self.topic0),
+            optional_text(&self.topic1),
+            optional_text(&self.topic2),
+            optional_text(&self.topic3),
+            optional_text(&self.data),
+        ]
+    }
+}
+
+impl ExportRow for TokenTransfer {
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("transaction_hash", ColumnType::Text),
+        ("block_number", ColumnType::Integer),
+        ("token_address", ColumnType::Text),
+        ("from_address", ColumnType::Text),
+        ("to_address", ColumnType::Text),
+        ("amount", ColumnType::Text),
+        ("token_type", ColumnType::Text),
+        ("token_id", ColumnType::Text),
+    ];
+
+    fn values(&self) -> Vec<ExportValue> {
+        vec![
+            text(&self.transaction_hash),
+            integer(self.block_number),
+            text(&self.token_address),
+            text(&self.from_address),
+            text(&self.to_address),
+            text(&self.amount),
+            optional_text(&self.token_type),
+            optional_text(&self.token_id),
+        ]
+    }
+}
+
+impl ExportRow for HolderBalance {
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("address", ColumnType::Text),
+        ("balance", ColumnType::Text),
+        ("balance_block", ColumnType::Integer),
+    ];
+
+    fn values(&self) -> Vec<ExportValue> {
+        vec![
+            text(&self.address),
+            text(&self.balance),
+            ExportValue::Integer(self.balance_block),
+        ]
+    }
+}
+
+/// File format of cold-storage exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColdStorageFormat {
+    Csv,
+    Parquet,
+}
+
+impl ColdStorageFormat {
+    /// Parse a format name, "parquet" or "csv"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "parquet" => Some(ColdStorageFormat::Parquet),
+            "csv" => Some(ColdStorageFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.extension()
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ColdStorageFormat::Csv => "csv",
+            ColdStorageFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Options of the `export` command
+#[derive(Debug, Clone)]
+pub struct ColdExportOptions {
+    pub from_block: i64,
+    pub to_block: i64,
+    pub format: ColdStorageFormat,
+    pub output_dir: PathBuf,
+    pub tables: Vec<String>, // Subset of EXPORT_TABLES
+}
+
+impl ColdExportOptions {
+    /// Build options from command line arguments (`--from-block`, `--to-block`, `--format`,
+    /// `--output`, `--tables`)
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        const USAGE: &str = "Usage: export --from-block N --to-block N [--format parquet|csv] [--output DIR] [--tables blocks,transactions,logs,token_transfers]";
+        let mut from_block = None;
+        let mut to_block = None;
+        let mut format = ColdStorageFormat::Parquet;
+        let mut output_dir = PathBuf::from("data/export");
+        let mut tables: Vec<String> = EXPORT_TABLES.iter().map(|t| t.to_string()).collect();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().context(format!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--from-block" => {
+                    from_block = Some(value()?.parse().context("Invalid --from-block")?)
+                }
+                "--to-block" => to_block = Some(value()?.parse().context("Invalid --to-block")?),
+                "--format" => {
+                    let value = value()?;
+                    format = ColdStorageFormat::parse(value)
+                        .context(format!("Unknown export format: {}", value))?;
+                }
+                "--output" => output_dir = PathBuf::from(value()?),
+                "--tables" => {
+                    tables = value()?
+                        .split(',')
+                        .map(|table| table.trim().to_string())
+                        .filter(|table| !table.is_empty())
+                        .collect();
+                }
+                other => return Err(anyhow::anyhow!("Unknown export argument: {}", other)),
+            }
+        }
+
+        if let Some(table) = tables
+            .iter()
+            .find(|table| !EXPORT_TABLES.contains(&table.as_str()))
+        {
+            return Err(anyhow::anyhow!("Unknown export table: {}", table));
+        }
+        let (from_block, to_block) = from_block.zip(to_block).context(USAGE)?;
+        if from_block < 0 || to_block < from_block {
+            return Err(anyhow::anyhow!(
+                "Invalid block range {}..={}",
+                from_block,
+                to_block
+            ));
+        }
+
+        Ok(Self {
+            from_block,
+            to_block,
+            format,
+            output_dir,
+            tables,
+        })
+    }
+}
+
+/// File written by a cold-storage export
+#[derive(Debug)]
+pub struct ExportedFile {
+    pub table: String,
+    pub path: PathBuf,
+    pub rows: u64,
+}
+
+/// Dump the selected tables for a block range into one file per table, named
+/// `<table>_<from>_<to>.<csv|parquet>`. Rows are streamed from the database, so memory
+/// stays bounded whatever the range.
+pub async fn export_block_range(
+    db: &DatabaseService,
+    options: &ColdExportOptions,
+) -> Result<Vec<ExportedFile>> {
+    std::fs::create_dir_all(&options.output_dir).context(format!(
+        "Failed to create export directory {}",
+        options.output_dir.display()
+    ))?;
+
+    let (from, to) = (options.from_block, options.to_block);
+    let mut files = Vec::new();
+    for table in &options.tables {
+        let path = options.output_dir.join(format!(
+            "{}_{}_{}.{}",
+            table,
+            from,
+            to,
+            options.format.extension()
+        ));
+        let rows = match table.as_str() {
+            "blocks" => write_rows(db.stream_blocks(from, to), &path, options.format).await,
+            "transactions" => {
+                write_rows(db.stream_transactions(from, to), &path, options.format).await
+            }
+            "logs" => write_rows(db.stream_logs(from, to), &path, options.format).await,
+            "token_transfers" => {
+                write_rows(db.stream_token_transfers(from, to), &path, options.format).await
+            }
+            other => Err(anyhow::anyhow!("Unknown export table: {}", other)),
+        }
+        .context(format!("Failed to export {}", table))?;
+
+        info!("Exported {} {} rows to {}", rows, table, path.display());
+        files.push(ExportedFile {
+            table: table.clone(),
+            path,
+            rows,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Write a stream of rows to a file, returns the number of rows written
+async fn write_rows<T: ExportRow>(
+    mut rows: BoxStream<'_, Result<T, sqlx::Error>>,
+    path: &Path,
+    format: ColdStorageFormat,
+) -> Result<u64> {
+    let file = File::create(path).context(format!("Failed to create {}", path.display()))?;
+    let mut written = 0;
+
+    match format {
+        ColdStorageFormat::Csv => {
+            let mut writer = BufWriter::new(file);
+            writeln!(writer, "{}", csv_header::<T>())?;
+            while let Some(row) = rows.next().await {
+                writeln!(writer, "{}", csv_line(&row?))?;
+                written += 1;
+            }
+            writer.flush()?;
+        }
+        ColdStorageFormat::Parquet => {
+            let mut writer = ParquetRowWriter::<T>::new(file)?;
+            while let Some(row) = rows.next().await {
+                writer.push(&row?)?;
+                written += 1;
+            }
+            writer.close()?;
+        }
+    }
+
+    Ok(written)
+}
+
+/// Column builder of a Parquet record batch
+enum ColumnBuilder {
+    Integer(Int64Builder),
+    Text(StringBuilder),
+}
+
+/// Buffers rows column by column and writes them as Parquet record batches
+struct ParquetRowWriter<T: ExportRow> {
+    writer: ArrowWriter<File>,
+    schema: Arc<Schema>,
+    columns: Vec<ColumnBuilder>,
+    buffered: usize,
+    _rows: std::marker::PhantomData<T>,
+}
+
+impl<T: ExportRow> ParquetRowWriter<T> {
+    fn new(file: File) -> Result<Self> {
+        let schema = Arc::new(Schema::new(
+            T::COLUMNS
+                .iter()
+                .map(|(name, column_type)| {
+                    let data_type = match column_type {
+                        ColumnType::Integer => DataType::Int64,
+                        ColumnType::Text => DataType::Utf8,
+                    };
+                    Field::new(*name, data_type, true)
+                })
+                .collect::<Vec<_>>(),
+        ));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))
+            .context("Failed to create Parquet writer")?;
+
+        Ok(Self {
+            writer,
+            schema,
+            columns: Self::builders(),
+            buffered: 0,
+            _rows: std::marker::PhantomData,
+        })
+    }
+
+    fn builders() -> Vec<ColumnBuilder> {
+        T::COLUMNS
+            .iter()
+            .map(|(_, column_type)| match column_type {
+                ColumnType::Integer => ColumnBuilder::Integer(Int64Builder::new()),
+                ColumnType::Text => ColumnBuilder::Text(StringBuilder::new()),
+            })
+            .collect()
+    }
+
+    fn push(&mut self, row: &T) -> Result<()> {
+        for (column, value) in self.columns.iter_mut().zip(row.values()) {
+            match (column, value) {
+                (ColumnBuilder::Integer(builder), ExportValue::Integer(value)) => {
+                    builder.append_option(value)
+                }
+                (ColumnBuilder::Text(builder), ExportValue::Text(value)) => {
+                    builder.append_option(value)
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Export value does not match its column type"
+                    ))
+                }
+            }
+        }
+
+        self.buffered += 1;
+        if self.buffered >= PARQUET_BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+
+        let arrays: Vec<ArrayRef> = self
+            .columns
+            .iter_mut()
+            .map(|column| match column {
+                ColumnBuilder::Integer(builder) => Arc::new(builder.finish()) as ArrayRef,
+                ColumnBuilder::Text(builder) => Arc::new(builder.finish()) as ArrayRef,
+            })
+            .collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)
+            .context("Failed to build Parquet record batch")?;
+        self.writer
+            .write(&batch)
+            .context("Failed to write Parquet record batch")?;
+        self.buffered = 0;
+        Ok(())
+    }
+
+    fn close(mut self) -> Result<()> {
+        self.flush()?;
+        self.writer
+            .close()
+            .context("Failed to finish Parquet file")?;
+        Ok(())
+    }
+}
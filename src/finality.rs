@@ -0,0 +1,99 @@
+//! Beacon chain finality tracking.
+//!
+//! The justified and finalized checkpoints of the beacon head state are polled and stored
+//! with the execution block of their checkpoint block, so block statuses come from real
+//! finality rather than from block age. A checkpoint block is resolved once per new root.
+
+use crate::{
+    beacon::{client::Checkpoint, BeaconClient},
+    config::AppConfig,
+    database::DatabaseService,
+};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Job polling the finality checkpoints of the beacon head state
+pub struct FinalityService {
+    db: Arc<DatabaseService>,
+    beacon: Arc<BeaconClient>,
+    config: AppConfig,
+}
+
+impl FinalityService {
+    /// Create a new finality service
+    pub fn new(db: Arc<DatabaseService>, beacon: Arc<BeaconClient>, config: AppConfig) -> Self {
+        Self { db, beacon, config }
+    }
+
+    /// Start polling the checkpoints in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Finality tracking starting");
+            let mut interval = time::interval(Duration::from_secs(
+                service.config.finality_poll_interval_seconds,
+            ));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.update().await {
+                    warn!("Failed to update finality checkpoints: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Store the current justified and finalized checkpoints when they moved
+    async fn update(&self) -> Result<()> {
+        let checkpoints = self.beacon.get_finality_checkpoints().await?;
+        let stored = self.db.get_finality_checkpoints().await?;
+
+        for (kind, checkpoint) in [
+            ("justified", &checkpoints.current_justified),
+            ("finalized", &checkpoints.finalized),
+        ] {
+            if stored
+                .iter()
+                .any(|stored| stored.kind == kind && stored.root == checkpoint.root)
+            {
+                continue;
+            }
+
+            let epoch: i64 = checkpoint
+                .epoch
+                .parse()
+                .context(format!("Invalid {} epoch {}", kind, checkpoint.epoch))?;
+            let block_number = self.checkpoint_block(checkpoint).await?;
+            self.db
+                .upsert_finality_checkpoint(kind, epoch, &checkpoint.root, block_number)
+                .await?;
+            debug!(
+                "{} checkpoint moved to epoch {} (block {:?})",
+                kind, epoch, block_number
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Execution block of a checkpoint block, None for the zero root the beacon API
+    /// reports before the first checkpoint
+    async fn checkpoint_block(&self, checkpoint: &Checkpoint) -> Result<Option<i64>> {
+        if checkpoint
+            .root
+            .trim_start_matches("0x")
+            .bytes()
+            .all(|b| b == b'0')
+        {
+            return Ok(None);
+        }
+
+        let block_number = self
+            .beacon
+            .get_execution_block_number(&checkpoint.root)
+            .await?;
+        Ok(block_number.map(|number| number as i64))
+    }
+}
@@ -2,14 +2,21 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tokio::time;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::rpc::RpcClient;
+#[cfg(feature = "beacon")]
+use crate::beacon::BeaconClient;
+use crate::{database::DatabaseService, rpc::RpcClient};
 
 /// Cache for health check information
 #[derive(Debug, Clone)]
 pub struct HealthStatus {
     pub rpc_connected: bool,
+    pub database_connected: bool,
+    pub database_size_bytes: Option<i64>,
+    pub beacon_healthy: Option<bool>, // None without a beacon client
+    pub last_indexed_block: Option<i64>,
+    pub last_block_timestamp: Option<i64>,
     pub last_checked: Instant,
 }
 
@@ -17,27 +24,45 @@ impl Default for HealthStatus {
     fn default() -> Self {
         Self {
             rpc_connected: false,
+            database_connected: false,
+            database_size_bytes: None,
+            beacon_healthy: None,
+            last_indexed_block: None,
+            last_block_timestamp: None,
             last_checked: Instant::now(),
         }
     }
 }
 
-/// Health cache service that periodically checks RPC connection
+/// Health cache service that periodically checks the RPC, database and beacon connections
 pub struct HealthCacheService {
     rpc: Arc<RpcClient>,
+    db: Arc<DatabaseService>,
+    #[cfg(feature = "beacon")]
+    beacon: Option<Arc<BeaconClient>>,
     cached_status: Arc<RwLock<HealthStatus>>,
     cache_duration: Duration,
 }
 
 impl HealthCacheService {
-    pub fn new(rpc: Arc<RpcClient>) -> Self {
+    pub fn new(rpc: Arc<RpcClient>, db: Arc<DatabaseService>) -> Self {
         Self {
             rpc,
+            db,
+            #[cfg(feature = "beacon")]
+            beacon: None,
             cached_status: Arc::new(RwLock::new(HealthStatus::default())),
             cache_duration: Duration::from_secs(60), // 60 seconds cache
         }
     }
 
+    /// Also check the health of the beacon nodes
+    #[cfg(feature = "beacon")]
+    pub fn with_beacon(mut self, beacon: Arc<BeaconClient>) -> Self {
+        self.beacon = Some(beacon);
+        self
+    }
+
     /// Start the background service to periodically update health status
     pub async fn start_background_updates(self: Arc<Self>) {
         let service = Arc::clone(&self);
@@ -61,8 +86,38 @@ impl HealthCacheService {
 
         let is_connected = self.rpc.check_connection().await.unwrap_or(false);
 
+        // The size query doubles as the database connectivity check
+        let database_size_bytes = match self.db.get_database_size().await {
+            Ok(size) => Some(size),
+            Err(e) => {
+                warn!("Database health check failed: {:#}", e);
+                None
+            }
+        };
+
+        let latest_block = match self.db.get_latest_block_timestamp().await {
+            Ok(latest) => latest,
+            Err(e) => {
+                warn!("Failed to read latest indexed block: {:#}", e);
+                None
+            }
+        };
+
+        #[cfg(feature = "beacon")]
+        let beacon_healthy = match &self.beacon {
+            Some(beacon) => Some(beacon.test_connection().await.is_ok()),
+            None => None,
+        };
+        #[cfg(not(feature = "beacon"))]
+        let beacon_healthy = None;
+
         let new_status = HealthStatus {
             rpc_connected: is_connected,
+            database_connected: database_size_bytes.is_some(),
+            database_size_bytes,
+            beacon_healthy,
+            last_indexed_block: latest_block.map(|(number, _)| number),
+            last_block_timestamp: latest_block.map(|(_, timestamp)| timestamp),
             last_checked: Instant::now(),
         };
 
@@ -71,7 +126,11 @@ impl HealthCacheService {
             *cached = new_status;
         }
 
-        debug!("Health status updated: rpc_connected={}", is_connected);
+        debug!(
+            "Health status updated: rpc_connected={}, database_connected={}",
+            is_connected,
+            database_size_bytes.is_some()
+        );
     }
 
     /// Get the cached health status
@@ -80,6 +139,12 @@ impl HealthCacheService {
         cached.clone()
     }
 
+    /// Whether the service can serve traffic: database and RPC reachable, beacon not failing
+    pub async fn is_ready(&self) -> bool {
+        let status = self.get_health_status().await;
+        status.database_connected && status.rpc_connected && status.beacon_healthy != Some(false)
+    }
+
     /// Force an immediate health status update (useful for startup)
     pub async fn force_update(&self) {
         self.update_health_status().await;
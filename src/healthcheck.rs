@@ -0,0 +1,231 @@
+use crate::api::auth::API_KEY_HEADER;
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde_json::Value;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Settings of the deployment smoke test
+#[derive(Debug, Clone)]
+pub struct HealthcheckOptions {
+    pub base_url: String, // Root URL of the deployment, e.g. http://127.0.0.1:3000
+    pub max_block_age_seconds: u64, // Latest indexed block must be younger than this
+    pub timeout_seconds: u64, // Timeout of each HTTP request
+    pub api_key: Option<String>, // Sent in the x-api-key header when the API requires keys
+}
+
+impl HealthcheckOptions {
+    /// Build options from command line arguments (`--url`, `--max-block-age`, `--timeout`,
+    /// `--api-key`), falling back to HEALTHCHECK_URL / API_PORT / HEALTHCHECK_API_KEY from the
+    /// environment
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut options = Self {
+            base_url: std::env::var("HEALTHCHECK_URL").unwrap_or_else(|_| {
+                format!(
+                    "http://127.0.0.1:{}",
+                    std::env::var("API_PORT").unwrap_or_else(|_| "3000".to_string())
+                )
+            }),
+            max_block_age_seconds: 300,
+            timeout_seconds: 10,
+            api_key: std::env::var("HEALTHCHECK_API_KEY").ok(),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().context(format!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--url" => options.base_url = value()?.clone(),
+                "--max-block-age" => {
+                    options.max_block_age_seconds =
+                        value()?.parse().context("Invalid --max-block-age")?
+                }
+                "--timeout" => {
+                    options.timeout_seconds = value()?.parse().context("Invalid --timeout")?
+                }
+                "--api-key" => options.api_key = Some(value()?.clone()),
+                other => return Err(anyhow::anyhow!("Unknown healthcheck argument: {}", other)),
+            }
+        }
+
+        options.base_url = options.base_url.trim_end_matches('/').to_string();
+        Ok(options)
+    }
+}
+
+/// Exercise a running deployment through its public API and print a diagnostic line per check.
+/// Returns whether every check passed.
+pub async fn run(options: &HealthcheckOptions) -> bool {
+    let mut headers = HeaderMap::new();
+    if let Some(key) = &options.api_key {
+        match HeaderValue::from_str(key) {
+            Ok(value) => {
+                headers.insert(API_KEY_HEADER, value);
+            }
+            Err(e) => {
+                eprintln!("[FAIL] api key: {}", e);
+                return false;
+            }
+        }
+    }
+
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(options.timeout_seconds))
+        .default_headers(headers)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[FAIL] client: {}", e);
+            return false;
+        }
+    };
+    let api = Api {
+        client,
+        base_url: format!("{}/api", options.base_url),
+    };
+
+    println!("Healthcheck of {}", options.base_url);
+    let mut passed = true;
+
+    passed &= report(
+        "stats",
+        api.get("/stats").await.map(|_| "reachable".to_string()),
+    );
+
+    let latest_block = api.get("/blocks?per_page=1").await.and_then(|response| {
+        response["blocks"][0]
+            .as_object()
+            .cloned()
+            .context("No indexed blocks")
+    });
+    let latest_block = match latest_block {
+        Ok(block) => block,
+        Err(e) => {
+            report::<String>("latest block", Err(e));
+            println!("Unhealthy");
+            return false;
+        }
+    };
+
+    let number = latest_block["number"].as_i64().unwrap_or_default();
+    let timestamp = latest_block["timestamp"].as_i64().unwrap_or_default();
+    passed &= report(
+        "latest block freshness",
+        check_freshness(number, timestamp, options),
+    );
+
+    passed &= report(
+        "block detail",
+        api.get(&format!("/blocks/{}", number))
+            .await
+            .and_then(|response| match response["block"]["number"].as_i64() {
+                Some(found) if found == number => Ok(format!("block #{}", number)),
+                _ => Err(anyhow::anyhow!("Block #{} missing from response", number)),
+            }),
+    );
+
+    passed &= report("transaction detail", check_transaction(&api).await);
+
+    passed &= report(
+        "search",
+        api.get(&format!("/search/{}", number))
+            .await
+            .and_then(|response| match response["type"].as_str() {
+                Some("block") => Ok(format!("found block #{}", number)),
+                other => Err(anyhow::anyhow!("Expected a block result, got {:?}", other)),
+            }),
+    );
+
+    println!("{}", if passed { "Healthy" } else { "Unhealthy" });
+    passed
+}
+
+/// Latest indexed block must be recent enough
+fn check_freshness(number: i64, timestamp: i64, options: &HealthcheckOptions) -> Result<String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let age = now - timestamp;
+
+    if age > options.max_block_age_seconds as i64 {
+        return Err(anyhow::anyhow!(
+            "Latest block #{} is {}s old (max {}s)",
+            number,
+            age,
+            options.max_block_age_seconds
+        ));
+    }
+
+    Ok(format!("block #{} is {}s old", number, age))
+}
+
+/// Fetch the most recent transaction by hash
+async fn check_transaction(api: &Api) -> Result<String> {
+    let response = api.get("/transactions?per_page=1").await?;
+    let Some(hash) = response["transactions"][0]["hash"].as_str() else {
+        return Ok("skipped, no indexed transactions".to_string());
+    };
+
+    let detail = api.get(&format!("/transactions/{}", hash)).await?;
+    match detail["transaction"]["hash"].as_str() {
+        Some(found) if found.eq_ignore_ascii_case(hash) => Ok(format!("transaction {}", hash)),
+        _ => Err(anyhow::anyhow!(
+            "Transaction {} missing from response",
+            hash
+        )),
+    }
+}
+
+/// Print the outcome of a check, returns whether it passed
+fn report<T: std::fmt::Display>(name: &str, result: Result<T>) -> bool {
+    match result {
+        Ok(detail) => {
+            println!("[ok]   {}: {}", name, detail);
+            true
+        }
+        Err(e) => {
+            println!("[FAIL] {}: {:#}", name, e);
+            false
+        }
+    }
+}
+
+struct Api {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl Api {
+    /// GET a JSON endpoint, failing on HTTP errors and `{"error": ...}` bodies
+    async fn get(&self, path: &str) -> Result<Value> {
+        let started = Instant::now();
+        let url = format!("{}{}", self.base_url, path);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context(format!("GET {} failed", url))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("GET {} returned {}", url, status));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .context(format!("GET {} returned invalid JSON", url))?;
+        if let Some(error) = body.get("error") {
+            return Err(anyhow::anyhow!("GET {} returned error: {}", url, error));
+        }
+
+        if started.elapsed() > Duration::from_secs(2) {
+            println!(
+                "       slow response: GET {} took {}ms",
+                path,
+                started.elapsed().as_millis()
+            );
+        }
+
+        Ok(body)
+    }
+}
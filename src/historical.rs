@@ -1,7 +1,10 @@
 use anyhow::Result;
+#[cfg(feature = "bigquery")]
 use serde_json::{json, Value};
 use std::sync::{Arc, RwLock};
-use tracing::{error, info, warn};
+#[cfg(feature = "bigquery")]
+use tracing::error;
+use tracing::{info, warn};
 
 use crate::config::AppConfig;
 use crate::database::DatabaseService;
@@ -9,6 +12,7 @@ use crate::database::DatabaseService;
 /// Service for managing historical transaction counts with BigQuery integration
 pub struct HistoricalTransactionService {
     db: Arc<DatabaseService>,
+    #[cfg_attr(not(feature = "bigquery"), allow(dead_code))]
     config: AppConfig,
     cached_historical_count: Arc<RwLock<Option<i64>>>,
 }
@@ -79,6 +83,7 @@ impl HistoricalTransactionService {
     }
 
     /// Fetch historical transaction count from BigQuery
+    #[cfg(feature = "bigquery")]
     async fn fetch_from_bigquery(&self, target_block: i64) -> Result<i64> {
         info!(
             "Fetching historical transaction count for block {} from BigQuery",
@@ -207,4 +212,10 @@ impl HistoricalTransactionService {
 
         Err(anyhow::anyhow!("Unexpected BigQuery response format"))
     }
+
+    /// Without the bigquery feature only a count cached in the database is used
+    #[cfg(not(feature = "bigquery"))]
+    async fn fetch_from_bigquery(&self, _target_block: i64) -> Result<i64> {
+        Err(anyhow::anyhow!("Built without the bigquery feature"))
+    }
 }
@@ -0,0 +1,404 @@
+use crate::{config::AppConfig, database::DatabaseService, rpc::RpcClient};
+use anyhow::{Context, Result};
+use ethers::{types::U256, utils::keccak256};
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+use reqwest::{redirect, Client, Url};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::debug;
+
+/// Max time a single image or metadata download may take
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Largest width or height decoded, guarding against decompression bombs
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+
+/// Redirects followed per download, each must stay on an allowed host
+const MAX_REDIRECTS: usize = 5;
+
+/// Time a failed image lookup is answered from memory before it is tried again
+const FAILURE_TTL: Duration = Duration::from_secs(3600);
+
+/// Failed lookups remembered, the oldest is forgotten past this
+const MAX_CACHED_FAILURES: usize = 10_000;
+
+/// Share of IMAGE_CACHE_MAX_BYTES the cache is trimmed down to once it outgrows it
+const CACHE_TRIM_RATIO: f64 = 0.9;
+
+/// Fetches token logos and NFT images referenced by stored metadata, resizes them and
+/// caches the result on disk, so the explorer never hotlinks IPFS gateways or token list
+/// hosts.
+///
+/// Only hosts of IMAGE_PROXY_ALLOWED_HOSTS (and their subdomains) are contacted and
+/// downloads stop at IMAGE_PROXY_MAX_BYTES. Images are re-encoded as PNG, which also
+/// strips anything but pixels from what is served. Only indexed tokens are looked up,
+/// failed lookups are remembered for `FAILURE_TTL`, and the least recently used images are
+/// deleted once the cache outgrows IMAGE_CACHE_MAX_BYTES.
+pub struct ImageProxy {
+    db: Arc<DatabaseService>,
+    rpc: Arc<RpcClient>,
+    config: AppConfig,
+    client: Client,
+    failures: Mutex<HashMap<String, (Instant, Option<String>)>>, // Error, None without an image
+    cache_bytes: tokio::sync::Mutex<Option<u64>>, // Size of the cache, None until measured
+}
+
+impl ImageProxy {
+    /// Create a new image proxy
+    pub fn new(db: Arc<DatabaseService>, rpc: Arc<RpcClient>, config: AppConfig) -> Result<Self> {
+        let allowed_hosts = config.image_proxy_allowed_hosts.clone();
+        let client = Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .redirect(redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if is_allowed_host(&allowed_hosts, attempt.url()) {
+                    attempt.follow()
+                } else {
+                    attempt.error("redirect to a host that is not allowed")
+                }
+            }))
+            .build()
+            .context("Failed to build image proxy HTTP client")?;
+
+        Ok(Self {
+            db,
+            rpc,
+            config,
+            client,
+            failures: Mutex::new(HashMap::new()),
+            cache_bytes: tokio::sync::Mutex::new(None),
+        })
+    }
+
+    /// Logo of a token as a PNG of at most `size` pixels a side, None without a logo
+    pub async fn token_logo(&self, address: &str, size: u32) -> Result<Option<Vec<u8>>> {
+        let Some(logo_uri) = self
+            .db
+            .get_token_by_address(address)
+            .await?
+            .and_then(|token| token.logo_uri)
+        else {
+            return Ok(None);
+        };
+
+        let key = format!("{}:{}", logo_uri, size);
+        if let Some(cached) = self.read_cache(&key).await {
+            return Ok(Some(cached));
+        }
+        if let Some(failure) = self.cached_failure(&logo_uri) {
+            return failure;
+        }
+
+        let image = self.fetch_image(&logo_uri, size).await;
+        self.remember(&logo_uri, image.as_ref().map(|_| Some(())));
+        let image = image?;
+        self.write_cache(&key, &image).await;
+        Ok(Some(image))
+    }
+
+    /// Image of an NFT as a PNG of at most `size` pixels a side, resolved through the
+    /// token's tokenURI metadata. None when the token was never transferred in an indexed
+    /// block, or has no metadata or image.
+    pub async fn nft_image(
+        &self,
+        address: &str,
+        token_id: U256,
+        size: u32,
+    ) -> Result<Option<Vec<u8>>> {
+        // NFT images rarely change, cache them by token rather than by URL to skip the
+        // tokenURI call and metadata download
+        let key = format!("nft:{}:{}:{}", address, token_id, size);
+        if let Some(cached) = self.read_cache(&key).await {
+            return Ok(Some(cached));
+        }
+        let token = format!("nft:{}:{}", address, token_id);
+        if let Some(failure) = self.cached_failure(&token) {
+            return failure;
+        }
+        if !self.db.nft_exists(address, &token_id.to_string()).await? {
+            return Ok(None);
+        }
+
+        let image = self.fetch_nft_image(address, token_id, size).await;
+        self.remember(
+            &token,
+            image.as_ref().map(|image| image.as_ref().map(|_| ())),
+        );
+        match image? {
+            Some(image) => {
+                self.write_cache(&key, &image).await;
+                Ok(Some(image))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Resolve the image of an NFT through its tokenURI metadata and resize it
+    async fn fetch_nft_image(
+        &self,
+        address: &str,
+        token_id: U256,
+        size: u32,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some(token_uri) = self.rpc.get_token_uri(address, token_id).await? else {
+            return Ok(None);
+        };
+        let metadata: serde_json::Value = serde_json::from_slice(&self.download(&token_uri).await?)
+            .context(format!("Invalid NFT metadata at {}", token_uri))?;
+        let Some(image_uri) = metadata["image"]
+            .as_str()
+            .or_else(|| metadata["image_url"].as_str())
+        else {
+            return Ok(None);
+        };
+
+        self.fetch_image(image_uri, size).await.map(Some)
+    }
+
+    /// Outcome of a lookup that failed less than `FAILURE_TTL` ago: its error, or None
+    /// when there was no image
+    fn cached_failure(&self, key: &str) -> Option<Result<Option<Vec<u8>>>> {
+        let failures = self.failures.lock().unwrap();
+        let (failed_at, error) = failures.get(key)?;
+        if failed_at.elapsed() >= FAILURE_TTL {
+            return None;
+        }
+        Some(match error {
+            Some(error) => Err(anyhow::anyhow!("{}", error)),
+            None => Ok(None),
+        })
+    }
+
+    /// Remember a failed lookup, `Ok(None)` meaning there was no image, and forget a
+    /// successful one
+    fn remember(&self, key: &str, outcome: Result<Option<()>, &anyhow::Error>) {
+        let mut failures = self.failures.lock().unwrap();
+        let error = match outcome {
+            Ok(Some(())) => {
+                failures.remove(key);
+                return;
+            }
+            Ok(None) => None,
+            Err(e) => Some(format!("{:#}", e)),
+        };
+
+        if failures.len() >= MAX_CACHED_FAILURES {
+            failures.retain(|_, (failed_at, _)| failed_at.elapsed() < FAILURE_TTL);
+        }
+        if failures.len() >= MAX_CACHED_FAILURES
+            && let Some(oldest) = failures
+                .iter()
+                .min_by_key(|(_, (failed_at, _))| *failed_at)
+                .map(|(key, _)| key.clone())
+        {
+            failures.remove(&oldest);
+        }
+        failures.insert(key.to_string(), (Instant::now(), error));
+    }
+
+    /// Download an image and resize it to fit in `size` x `size` pixels
+    async fn fetch_image(&self, uri: &str, size: u32) -> Result<Vec<u8>> {
+        let bytes = self.download(uri).await?;
+
+        // Decoding and resizing are CPU bound
+        tokio::task::spawn_blocking(move || {
+            let mut reader = ImageReader::new(Cursor::new(bytes))
+                .with_guessed_format()
+                .context("Failed to read image")?;
+            let mut limits = Limits::default();
+            limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+            limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+            reader.limits(limits);
+            let image = reader.decode().context("Unsupported or oversized image")?;
+            let resized = if image.width() > size || image.height() > size {
+                image.resize(size, size, FilterType::Lanczos3)
+            } else {
+                image
+            };
+
+            let mut png = Vec::new();
+            resized
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .context("Failed to encode image")?;
+            Ok(png)
+        })
+        .await?
+    }
+
+    /// Download a document from an allowed host, stopping at IMAGE_PROXY_MAX_BYTES
+    async fn download(&self, uri: &str) -> Result<Vec<u8>> {
+        let url = self.resolve_uri(uri)?;
+        if !is_allowed_host(&self.config.image_proxy_allowed_hosts, &url) {
+            return Err(anyhow::anyhow!(
+                "Host of {} is not in IMAGE_PROXY_ALLOWED_HOSTS",
+                url
+            ));
+        }
+
+        let max_bytes = self.config.image_proxy_max_bytes;
+        let mut response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context(format!("Failed to fetch {}", url))?;
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_bytes as u64)
+        {
+            return Err(anyhow::anyhow!(
+                "{} is larger than {} bytes",
+                url,
+                max_bytes
+            ));
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .context(format!("Failed to fetch {}", url))?
+        {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(anyhow::anyhow!(
+                    "{} is larger than {} bytes",
+                    url,
+                    max_bytes
+                ));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        debug!("Fetched {} bytes from {}", bytes.len(), url);
+        Ok(bytes)
+    }
+
+    /// URL to fetch a metadata URI from, ipfs:// URIs go through IPFS_GATEWAY_URL
+    fn resolve_uri(&self, uri: &str) -> Result<Url> {
+        let uri = uri.trim();
+        let resolved = match uri.strip_prefix("ipfs://") {
+            Some(path) => format!(
+                "{}/{}",
+                self.config.ipfs_gateway_url.trim_end_matches('/'),
+                path.trim_start_matches("ipfs/")
+            ),
+            None => uri.to_string(),
+        };
+
+        let url = Url::parse(&resolved).context(format!("Invalid image URI: {}", uri))?;
+        match url.scheme() {
+            "http" | "https" => Ok(url),
+            scheme => Err(anyhow::anyhow!("Unsupported URI scheme: {}", scheme)),
+        }
+    }
+
+    /// Cache file of a key
+    fn cache_path(&self, key: &str) -> PathBuf {
+        PathBuf::from(&self.config.image_cache_dir)
+            .join(format!("{}.png", hex::encode(keccak256(key.as_bytes()))))
+    }
+
+    /// Cached image of a key, marking it as recently used
+    async fn read_cache(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.cache_path(key);
+        let image = tokio::fs::read(&path).await.ok()?;
+        // The modification time orders evictions
+        let touched = std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        if let Err(e) = touched {
+            debug!("Failed to touch cached image {}: {}", path.display(), e);
+        }
+        Some(image)
+    }
+
+    /// Store an image in the cache, failures only cost a later refetch
+    async fn write_cache(&self, key: &str, image: &[u8]) {
+        let path = self.cache_path(key);
+        let written = async {
+            tokio::fs::create_dir_all(&self.config.image_cache_dir).await?;
+            tokio::fs::write(&path, image).await
+        }
+        .await;
+
+        match written {
+            Ok(()) => self.enforce_cache_limit(image.len() as u64).await,
+            Err(e) => debug!("Failed to cache image at {}: {}", path.display(), e),
+        }
+    }
+
+    /// Delete the least recently used images once the cache outgrows IMAGE_CACHE_MAX_BYTES,
+    /// down to `CACHE_TRIM_RATIO` of it
+    async fn enforce_cache_limit(&self, written: u64) {
+        let mut cache_bytes = self.cache_bytes.lock().await;
+        let total = match *cache_bytes {
+            Some(total) => total + written,
+            // Measured once, images written before included
+            None => match cached_files(&self.config.image_cache_dir).await {
+                Ok(files) => files.iter().map(|(_, _, size)| size).sum(),
+                Err(e) => {
+                    debug!("Failed to measure the image cache: {}", e);
+                    return;
+                }
+            },
+        };
+        let max_bytes = self.config.image_cache_max_bytes;
+        if total <= max_bytes {
+            *cache_bytes = Some(total);
+            return;
+        }
+
+        let mut files = match cached_files(&self.config.image_cache_dir).await {
+            Ok(files) => files,
+            Err(e) => {
+                debug!("Failed to list the image cache: {}", e);
+                return;
+            }
+        };
+        files.sort_by_key(|(_, modified, _)| *modified);
+        let target = (max_bytes as f64 * CACHE_TRIM_RATIO) as u64;
+        let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+        for (path, _, size) in files {
+            if total <= target {
+                break;
+            }
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => total -= size,
+                Err(e) => debug!("Failed to evict cached image {}: {}", path.display(), e),
+            }
+        }
+        *cache_bytes = Some(total);
+    }
+}
+
+/// Images of the cache directory with their modification time and size
+async fn cached_files(dir: &str) -> std::io::Result<Vec<(PathBuf, SystemTime, u64)>> {
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if metadata.is_file() {
+            files.push((entry.path(), metadata.modified()?, metadata.len()));
+        }
+    }
+    Ok(files)
+}
+
+/// Whether a URL points to an allowed host or one of its subdomains
+fn is_allowed_host(allowed_hosts: &[String], url: &Url) -> bool {
+    let Some(host) = url.host_str().map(str::to_lowercase) else {
+        return false;
+    };
+
+    allowed_hosts
+        .iter()
+        .any(|allowed| host == *allowed || host.ends_with(&format!(".{}", allowed)))
+}
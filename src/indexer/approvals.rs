@@ -0,0 +1,47 @@
+use super::governance::topic_address;
+use crate::database::{ApprovalChange, Log};
+use ethers::types::U256;
+
+/// Approval(address,address,uint256) event signature, shared by ERC-20 and ERC-721
+pub const APPROVAL_TOPIC: &str =
+    "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+
+/// ApprovalForAll(address,address,bool) event signature of ERC-721 and ERC-1155 tokens
+pub const APPROVAL_FOR_ALL_TOPIC: &str =
+    "0x17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31";
+
+/// Approval changes decoded from the logs of a block, in log order. ERC-721 Approval
+/// events (token id indexed) approve a single token and are skipped, like logs that don't
+/// match the expected layout.
+pub fn approval_changes(logs: &[Log]) -> Vec<ApprovalChange> {
+    logs.iter().filter_map(approval_change).collect()
+}
+
+fn approval_change(log: &Log) -> Option<ApprovalChange> {
+    let kind = match log.topic0.as_deref()? {
+        // Approval(owner indexed, spender indexed, value)
+        APPROVAL_TOPIC if log.topic3.is_none() => "allowance",
+        // ApprovalForAll(owner indexed, operator indexed, approved)
+        APPROVAL_FOR_ALL_TOPIC => "operator",
+        _ => return None,
+    };
+    let data = hex::decode(log.data.as_deref()?.trim_start_matches("0x")).ok()?;
+    if data.len() != 32 {
+        return None;
+    }
+    let value = U256::from_big_endian(&data);
+    if kind == "operator" && value > U256::one() {
+        return None;
+    }
+
+    Some(ApprovalChange {
+        token_address: log.address.clone(),
+        owner_address: topic_address(log.topic1.as_deref()?)?,
+        spender_address: topic_address(log.topic2.as_deref()?)?,
+        kind: kind.to_string(),
+        value: value.to_string(),
+        transaction_hash: log.transaction_hash.clone(),
+        block_number: log.block_number,
+        log_index: log.log_index,
+    })
+}
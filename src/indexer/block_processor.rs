@@ -1,200 +1,515 @@
+#[cfg(feature = "token-service")]
+use crate::token_service::TokenService;
+#[cfg(feature = "beacon")]
+use crate::{beacon::BeaconClient, database::ProposerDuty};
 use crate::{
-    beacon::BeaconClient,
-    database::{Block, DatabaseService, Withdrawal},
+    config::{AppConfig, BlockRange, NetworkProfile},
+    database::{
+        proposer_payment, Block, BlockData, BlockFeeRecipient, DatabaseService, Transaction,
+        Withdrawal,
+    },
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+    live::LiveDispatcher,
     rpc::RpcClient,
+    webhooks,
 };
 use anyhow::{Context, Result};
-use ethers::core::types::{Block as EthBlock, Transaction as EthTransaction};
+use ethers::core::types::{Block as EthBlock, Transaction as EthTransaction, H256};
+use serde_json::json;
+#[cfg(feature = "beacon")]
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
-use super::transaction_processor::TransactionProcessor;
+use super::{
+    approvals, debug::StepTiming, deposits, governance, l2,
+    transaction_processor::TransactionProcessor,
+};
+
+/// Stale blocks replaced per reorg, well past the finality depth of two epochs
+const MAX_REORG_DEPTH: usize = 64;
+
+/// Fetches of a block whose parent keeps changing before it is given up on
+const MAX_PARENT_CHECKS: u32 = 3;
+
+/// Block fetched for storage: with its transactions, or only its header in the ranges
+/// indexed headers only
+enum FetchedBlock {
+    Full(EthBlock<EthTransaction>),
+    Header(EthBlock<H256>),
+}
+
+impl FetchedBlock {
+    fn number(&self) -> Option<u64> {
+        match self {
+            Self::Full(block) => block.number,
+            Self::Header(block) => block.number,
+        }
+        .map(|number| number.as_u64())
+    }
+
+    fn hash(&self) -> Option<H256> {
+        match self {
+            Self::Full(block) => block.hash,
+            Self::Header(block) => block.hash,
+        }
+    }
+
+    fn parent_hash(&self) -> H256 {
+        match self {
+            Self::Full(block) => block.parent_hash,
+            Self::Header(block) => block.parent_hash,
+        }
+    }
+}
 
 /// Processor for handling block data
 #[derive(Clone)]
 pub struct BlockProcessor {
     db: Arc<DatabaseService>,
     rpc: Arc<RpcClient>,
-    beacon: Arc<BeaconClient>,          // Now mandatory
+    #[cfg(feature = "beacon")]
+    beacon: Option<Arc<BeaconClient>>, // Slot data of blocks
+    #[cfg(feature = "beacon")]
+    duties_epoch: Arc<AtomicI64>, // Last epoch whose proposer duties were looked up
     tx_processor: TransactionProcessor, // Shared transaction processor
+    live: LiveDispatcher,               // Publishes indexed logs to live subscribers
+    events: EventLog,
+    profile: NetworkProfile, // Chain family, L2 data is indexed for rollups
+    balance_history_interval: i64, // Blocks per balance history snapshot, 0 = disabled
+    headers_only: Vec<BlockRange>, // Ranges stored without transactions and receipts
+    deposit_contract: Option<String>, // Contract whose DepositEvent logs are indexed
 }
 
 impl BlockProcessor {
-    /// Create a new block processor with mandatory Beacon Chain support
+    /// Create a new block processor with the indexing settings of `config`
     pub fn new(
         db: Arc<DatabaseService>,
         rpc: Arc<RpcClient>,
-        beacon: Arc<BeaconClient>,
         tx_processor: TransactionProcessor,
+        live: LiveDispatcher,
+        events: EventLog,
+        config: &AppConfig,
     ) -> Self {
         Self {
             db,
             rpc,
-            beacon,
+            #[cfg(feature = "beacon")]
+            beacon: None,
+            #[cfg(feature = "beacon")]
+            duties_epoch: Arc::new(AtomicI64::new(-1)),
             tx_processor,
+            live,
+            events,
+            profile: config.network_profile,
+            balance_history_interval: config.balance_history_interval_blocks,
+            headers_only: config.headers_only_ranges.clone(),
+            deposit_contract: config.deposit_contract_address.clone(),
         }
     }
 
-    pub async fn process_block(&self, block_number: u64) -> Result<()> {
-        let start_time = std::time::Instant::now();
+    /// Fetch Beacon Chain data of blocks from the given client
+    #[cfg(feature = "beacon")]
+    pub fn set_beacon(&mut self, beacon: Arc<BeaconClient>) {
+        self.beacon = Some(beacon);
+    }
 
-        let block_fetch_start = std::time::Instant::now();
-        let eth_block = self
-            .rpc
-            .get_block_by_number(block_number)
-            .await?
-            .context(format!("Block #{} not found", block_number))?;
-        let block_fetch_time = block_fetch_start.elapsed();
+    /// Hand token transfers to the token service for discovery and balance updates
+    #[cfg(feature = "token-service")]
+    pub fn set_token_service(&mut self, token_service: Arc<TokenService>) {
+        self.tx_processor.set_token_service(token_service);
+    }
 
-        // Convert to our Block model and save
-        let block = self.convert_block(&eth_block).await?;
+    /// Preload the caches of the transaction processor
+    pub async fn warm_caches(&self) -> Result<usize> {
+        self.tx_processor.warm_account_cache().await
+    }
 
-        let block_insert_start = std::time::Instant::now();
-        self.db.insert_block(&block).await?;
-        let block_insert_time = block_insert_start.elapsed();
+    pub async fn process_block(&self, block_number: u64) -> Result<()> {
+        let start_time = Instant::now();
 
-        debug!(
-            "Block #{} insert time: {}ms",
-            block_number,
-            block_insert_time.as_millis()
-        );
+        let mut attempts = 0;
+        let (fetched, block_fetch_time) = loop {
+            let block_fetch_start = Instant::now();
+            let fetched = self.fetch_block(block_number).await?;
+            let block_fetch_time = block_fetch_start.elapsed();
+
+            if block_number == 0 {
+                break (fetched, block_fetch_time);
+            }
+
+            // A parent hash differing from the stored parent means the head was reorganized
+            self.replace_reorged_blocks(block_number - 1, fetched.parent_hash())
+                .await?;
 
-        // Process withdrawals if present (Shanghai fork)
-        if let Some(withdrawals) = &eth_block.withdrawals {
-            let withdrawals_start = std::time::Instant::now();
-            for (index, withdrawal) in withdrawals.iter().enumerate() {
-                let withdrawal_data = Withdrawal {
-                    id: None,
-                    block_number: block_number as i64,
-                    withdrawal_index: index as i64,
-                    validator_index: withdrawal.validator_index.as_u64() as i64,
-                    address: format!("{:?}", withdrawal.address),
-                    amount: withdrawal.amount.to_string(), // Amount in Gwei
-                    created_at: None,
-                };
-
-                if let Err(e) = self.db.insert_withdrawal(&withdrawal_data).await {
-                    error!("Failed to insert withdrawal {}: {}", index, e);
+            // The head may move again while stale blocks are replaced, the block is only
+            // stored once it extends the stored chain
+            let parent_hash = format!("{:?}", fetched.parent_hash());
+            match self.db.get_block_hash(block_number as i64 - 1).await? {
+                Some(stored_hash) if stored_hash != parent_hash => {
+                    attempts += 1;
+                    if attempts == MAX_PARENT_CHECKS {
+                        return Err(anyhow::anyhow!(
+                            "Block #{} parent {} does not match stored block #{} {}",
+                            block_number,
+                            parent_hash,
+                            block_number - 1,
+                            stored_hash
+                        ));
+                    }
+                    warn!(
+                        "Block #{} does not extend stored block #{}, fetching it again",
+                        block_number,
+                        block_number - 1
+                    );
                 }
+                _ => break (fetched, block_fetch_time),
             }
-            let withdrawals_time = withdrawals_start.elapsed();
-            debug!(
-                "Block #{} withdrawals processing time: {}ms",
-                block_number,
-                withdrawals_time.as_millis()
+        };
+
+        self.store_block(fetched, start_time, block_fetch_time)
+            .await
+    }
+
+    /// Whether a block is stored without its transactions and receipts
+    fn is_headers_only(&self, block_number: u64) -> bool {
+        self.headers_only
+            .iter()
+            .any(|range| range.contains(block_number as i64))
+    }
+
+    /// Fetch a block with its transactions, or only its header in headers-only ranges
+    async fn fetch_block(&self, block_number: u64) -> Result<FetchedBlock> {
+        let fetched = if self.is_headers_only(block_number) {
+            self.rpc
+                .get_block_header(block_number)
+                .await?
+                .map(FetchedBlock::Header)
+        } else {
+            self.rpc
+                .get_block_by_number(block_number)
+                .await?
+                .map(FetchedBlock::Full)
+        };
+        fetched.context(format!("Block #{} not found", block_number))
+    }
+
+    /// Delete everything stored for an indexed block, then fetch and store it again
+    pub async fn reindex_block(&self, block_number: u64) -> Result<()> {
+        self.db
+            .delete_replaced_block_data(block_number as i64, "reindex")
+            .await?;
+        self.process_block(block_number).await
+    }
+
+    /// Walk back from block `number`, whose canonical hash is `canonical_hash`, while the
+    /// stored blocks differ from the canonical chain. Stale blocks are then replaced oldest
+    /// first and the reorg is recorded.
+    async fn replace_reorged_blocks(
+        &self,
+        mut number: u64,
+        mut canonical_hash: H256,
+    ) -> Result<()> {
+        let mut stale = Vec::new();
+        while stale.len() < MAX_REORG_DEPTH {
+            let Some(stored_hash) = self.db.get_block_hash(number as i64).await? else {
+                break;
+            };
+            if stored_hash == format!("{:?}", canonical_hash) {
+                break;
+            }
+
+            let canonical = self.fetch_block(number).await?;
+            canonical_hash = canonical.parent_hash();
+            stale.push((number, stored_hash, canonical));
+
+            if number == 0 {
+                break;
+            }
+            number -= 1;
+        }
+
+        if stale.is_empty() {
+            return Ok(());
+        }
+        if stale.len() == MAX_REORG_DEPTH {
+            error!(
+                "Reorg deeper than {} blocks, older stale blocks are left in place",
+                MAX_REORG_DEPTH
             );
         }
 
-        if !eth_block.transactions.is_empty() {
+        let depth = stale.len();
+        let first_block = stale[depth - 1].0 as i64;
+        let mut replaced_blocks = Vec::new();
+        for (block_number, old_hash, canonical) in stale.into_iter().rev() {
+            let new_hash = canonical.hash().map(|hash| format!("{:?}", hash));
+            self.db
+                .delete_replaced_block_data(block_number as i64, "reorg")
+                .await?;
+            self.store_block(canonical, Instant::now(), Duration::ZERO)
+                .await?;
+            replaced_blocks.push(json!({
+                "number": block_number,
+                "old_hash": old_hash,
+                "new_hash": new_hash
+            }));
+        }
+
+        warn!(
+            "Reorg of depth {} replaced blocks #{}..=#{}",
+            depth,
+            first_block,
+            first_block + depth as i64 - 1
+        );
+        let replaced_blocks = serde_json::Value::Array(replaced_blocks);
+        self.db
+            .insert_reorg(first_block, depth as i64, &replaced_blocks.to_string())
+            .await?;
+        self.events.record(
+            NewIndexerEvent::warning(
+                IndexerEventKind::ReorgHandled,
+                format!("Reorg of depth {} handled", depth),
+            )
+            .at_block(first_block)
+            .with_details(json!({
+                "depth": depth,
+                "replaced_blocks": replaced_blocks
+            })),
+        );
+
+        Ok(())
+    }
+
+    /// Store a fetched block with its withdrawals, transactions, logs and token transfers in
+    /// one database transaction, then hand it to live subscribers, the token service and
+    /// webhooks. Nothing of the block is stored when any of it fails.
+    async fn store_block(
+        &self,
+        fetched: FetchedBlock,
+        start_time: Instant,
+        block_fetch_time: Duration,
+    ) -> Result<()> {
+        let block_number = fetched.number().context("Block number missing")?;
+
+        let decode_start = Instant::now();
+        let data = self
+            .derive_fetched_block(&fetched, &mut Vec::new())
+            .await
+            .context(format!("Failed to process block #{}", block_number))?;
+        let decode_time = decode_start.elapsed();
+        debug!(
+            "Block #{} collected data: {} transactions, {} logs, {} token_transfers, {} accounts",
+            block_number,
+            data.transactions.len(),
+            data.logs.len(),
+            data.token_transfers.len(),
+            data.accounts.len()
+        );
+
+        let batch_db_start = Instant::now();
+        self.db
+            .store_block_data(&data, self.balance_history_interval)
+            .await?;
+        let batch_db_time = batch_db_start.elapsed();
+
+        // Mined transactions leave the mempool
+        if let FetchedBlock::Full(eth_block) = &fetched
+            && !eth_block.transactions.is_empty()
+        {
             let tx_hashes: Vec<String> = eth_block
                 .transactions
                 .iter()
                 .map(|tx| format!("{:?}", tx.hash))
                 .collect();
-
-            let receipts_start = std::time::Instant::now();
-            let receipts = self
-                .tx_processor
-                .get_transaction_receipts_batch(&tx_hashes)
-                .await?;
-            let receipts_time = receipts_start.elapsed();
-
-            let mut tx_receipt_pairs = Vec::new();
-            for (tx, receipt) in eth_block.transactions.iter().zip(receipts.iter()) {
-                if let Some(receipt) = receipt {
-                    tx_receipt_pairs.push((tx.clone(), receipt.clone()));
-                }
+            if let Err(e) = self.db.delete_pending_transactions(&tx_hashes).await {
+                error!("Failed to remove mined pending transactions: {}", e);
             }
+        }
+
+        self.live.publish_block(&data.block);
+        self.live.publish_transactions(&data.transactions);
+        self.live.publish_logs(&data.logs);
+        self.live.publish_token_transfers(&data.token_transfers);
 
-            // Process entire block's transactions in one optimized batch
-            match self
+        // Process token transfers for token discovery and balance updates
+        #[cfg(feature = "token-service")]
+        if !data.token_transfers.is_empty()
+            && let Err(e) = self
                 .tx_processor
-                .collect_block_transaction_data(&tx_receipt_pairs)
+                .process_token_transfers_with_balances(&data.token_transfers, block_number as i64)
                 .await
-            {
-                Ok((all_transactions, all_logs, all_token_transfers, all_accounts)) => {
-                    debug!(
-                        "Block #{} collected data: {} transactions, {} logs, {} token_transfers, {} accounts",
-                        block_number,
-                        all_transactions.len(),
-                        all_logs.len(),
-                        all_token_transfers.len(),
-                        all_accounts.len()
-                    );
+        {
+            error!("Failed to process token transfers for balances: {}", e);
+        }
 
-                    // Batch insert all data at once for maximum performance
-                    let batch_db_start = std::time::Instant::now();
+        info!(
+            "Block #{} performance: block_fetch={}ms, decode={}ms, batch_db={}ms, total={}ms",
+            block_number,
+            block_fetch_time.as_millis(),
+            decode_time.as_millis(),
+            batch_db_time.as_millis(),
+            start_time.elapsed().as_millis()
+        );
 
-                    if !all_transactions.is_empty() {
-                        if let Err(e) = self.db.insert_transactions_batch(&all_transactions).await {
-                            error!("Failed to batch insert transactions: {}", e);
-                        }
-                    }
+        // Notify block webhooks once everything of the block is stored
+        if let Err(e) = webhooks::enqueue_block(&self.db, &data.block).await {
+            error!(
+                "Failed to queue webhooks of block #{}: {:#}",
+                block_number, e
+            );
+        }
+        Ok(())
+    }
 
-                    if !all_logs.is_empty() {
-                        if let Err(e) = self.db.insert_logs_batch(&all_logs).await {
-                            error!("Failed to batch insert logs: {}", e);
-                        }
-                    }
+    /// Fetch a block and derive every row `store_block` would write for it, without writing
+    /// anything. The time spent on each step is appended to `timings`.
+    pub(super) async fn decode_block(
+        &self,
+        block_number: u64,
+        timings: &mut Vec<StepTiming>,
+    ) -> Result<BlockData> {
+        let started = Instant::now();
+        let fetched = self.fetch_block(block_number).await?;
+        timings.push(StepTiming::since("block_fetch", started));
 
-                    if !all_token_transfers.is_empty() {
-                        if let Err(e) = self
-                            .db
-                            .insert_token_transfers_batch(&all_token_transfers)
-                            .await
-                        {
-                            error!("Failed to batch insert token transfers: {}", e);
-                        }
-
-                        // Process token transfers for token discovery and balance updates
-                        if let Err(e) = self
-                            .tx_processor
-                            .process_token_transfers_with_balances(
-                                &all_token_transfers,
-                                block_number as i64,
-                            )
-                            .await
-                        {
-                            error!("Failed to process token transfers for balances: {}", e);
-                        }
-                    }
+        self.derive_fetched_block(&fetched, timings).await
+    }
 
-                    if !all_accounts.is_empty() {
-                        if let Err(e) = self.db.insert_accounts_batch(&all_accounts).await {
-                            error!("Failed to batch insert accounts: {}", e);
-                        } else {
-                            info!(
-                                "Successfully inserted {} accounts from block #{}",
-                                all_accounts.len(),
-                                block_number
-                            );
-                        }
-                    } else {
-                        info!("No accounts to insert for block #{}", block_number);
-                    }
+    /// Derive the rows of a fetched block, full or headers only
+    async fn derive_fetched_block(
+        &self,
+        fetched: &FetchedBlock,
+        timings: &mut Vec<StepTiming>,
+    ) -> Result<BlockData> {
+        match fetched {
+            FetchedBlock::Full(eth_block) => self.derive_block_data(eth_block, timings).await,
+            FetchedBlock::Header(eth_block) => self.derive_header_data(eth_block, timings).await,
+        }
+    }
 
-                    let batch_db_time = batch_db_start.elapsed();
+    /// Derive the rows of a block indexed headers only: the block and its withdrawals.
+    /// Its transaction count is the declared one, although no transaction is stored.
+    async fn derive_header_data(
+        &self,
+        eth_block: &EthBlock<H256>,
+        timings: &mut Vec<StepTiming>,
+    ) -> Result<BlockData> {
+        let started = Instant::now();
+        // The fee recipient is left out, its proposer payment is among the transactions
+        let (block, _) = self.convert_block(eth_block).await?;
+        let withdrawals = convert_withdrawals(eth_block);
+        timings.push(StepTiming::since("block_convert", started));
 
-                    info!("Block #{} performance: block_fetch={}ms, receipts_fetch={}ms, batch_db={}ms, total={}ms", 
-                          block_number,
-                          block_fetch_time.as_millis(),
-                          receipts_time.as_millis(),
-                          batch_db_time.as_millis(),
-                          start_time.elapsed().as_millis());
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to process block {} transactions: {}",
-                        block_number, e
-                    );
-                }
-            }
-        }
-        Ok(())
+        Ok(BlockData {
+            block,
+            withdrawals,
+            deposits: Vec::new(),
+            transactions: Vec::new(),
+            receipts: Vec::new(),
+            logs: Vec::new(),
+            token_transfers: Vec::new(),
+            accounts: Vec::new(),
+            contract_deployments: Vec::new(),
+            contract_codes: Vec::new(),
+            l2_block: None,
+            l2_transactions: Vec::new(),
+            fee_recipient: None,
+            delegation_changes: Vec::new(),
+            approval_changes: Vec::new(),
+        })
     }
 
-    /// Convert Ethereum block to our Block model
-    async fn convert_block(&self, eth_block: &EthBlock<EthTransaction>) -> Result<Block> {
+    /// Derive the rows of a fetched block, fetching its receipts. The time spent on each
+    /// step is appended to `timings`.
+    async fn derive_block_data(
+        &self,
+        eth_block: &EthBlock<EthTransaction>,
+        timings: &mut Vec<StepTiming>,
+    ) -> Result<BlockData> {
+        let block_number = eth_block.number.context("Block number missing")?.as_u64();
+
+        let started = Instant::now();
+        let (block, payload_fee_recipient) = self.convert_block(eth_block).await?;
+        let withdrawals = convert_withdrawals(eth_block);
+        let l2_block = l2::l2_block(self.profile, eth_block);
+        timings.push(StepTiming::since("block_convert", started));
+
+        let started = Instant::now();
+        let tx_hashes: Vec<String> = eth_block
+            .transactions
+            .iter()
+            .map(|tx| format!("{:?}", tx.hash))
+            .collect();
+        let receipts = if tx_hashes.is_empty() {
+            Vec::new()
+        } else {
+            self.tx_processor
+                .get_block_receipts(block_number, &tx_hashes)
+                .await?
+        };
+        let tx_receipt_pairs: Vec<_> = eth_block
+            .transactions
+            .iter()
+            .zip(receipts)
+            .filter_map(|(tx, receipt)| Some((tx.clone(), receipt?)))
+            .collect();
+        let tx_receipt_pairs = self.tx_processor.filter_tracked(tx_receipt_pairs);
+        timings.push(StepTiming::since("receipts_fetch", started));
+
+        let started = Instant::now();
+        let (transactions, logs, token_transfers, accounts) = self
+            .tx_processor
+            .collect_block_transaction_data(&tx_receipt_pairs)
+            .await?;
+        let fee_recipient = payload_fee_recipient
+            .map(|fee_recipient| block_fee_recipient(&block, fee_recipient, &transactions));
+        let delegation_changes = governance::delegation_changes(&logs);
+        let approval_changes = approvals::approval_changes(&logs);
+        let deposits = self
+            .deposit_contract
+            .as_deref()
+            .map(|contract| deposits::deposits(&logs, &transactions, contract))
+            .unwrap_or_default();
+        let receipts = self
+            .tx_processor
+            .collect_receipts(&tx_receipt_pairs, block_number as i64);
+        let l2_transactions = tx_receipt_pairs
+            .iter()
+            .filter_map(|(tx, receipt)| l2::l2_transaction(self.profile, tx, receipt))
+            .collect();
+        timings.push(StepTiming::since("transaction_data", started));
+
+        let started = Instant::now();
+        let (contract_deployments, contract_codes) = self
+            .tx_processor
+            .collect_contract_deployments(&tx_receipt_pairs, block_number as i64)
+            .await?;
+        timings.push(StepTiming::since("contract_deployments", started));
+
+        Ok(BlockData {
+            block,
+            withdrawals,
+            deposits,
+            transactions,
+            receipts,
+            logs,
+            token_transfers,
+            accounts,
+            contract_deployments,
+            contract_codes,
+            l2_block,
+            l2_transactions,
+            fee_recipient,
+            delegation_changes,
+            approval_changes,
+        })
+    }
+
+    /// Convert Ethereum block to our Block model, with the fee recipient of its beacon
+    /// execution payload when the beacon block was fetched
+    async fn convert_block<TX>(&self, eth_block: &EthBlock<TX>) -> Result<(Block, Option<String>)> {
         let gas_used = eth_block.gas_used.as_u64();
         let base_fee = eth_block.base_fee_per_gas.map(|fee| fee.to_string());
 
@@ -207,17 +522,34 @@ impl BlockProcessor {
 
         let block_number = eth_block.number.context("Block number missing")?.as_u64();
 
-        // Get Beacon Chain data (now always available)
-        let beacon_data = match self.beacon.get_beacon_data_for_block(block_number).await {
-            Ok(data) => Some(data),
-            Err(e) => {
-                debug!(
-                    "Failed to fetch beacon data for block {}: {}",
-                    block_number, e
-                );
-                None
-            }
+        // Get Beacon Chain data, rollups have no beacon chain
+        #[cfg(feature = "beacon")]
+        let beacon_data = match self.beacon.as_ref().filter(|_| !self.profile.is_l2()) {
+            Some(beacon) => match beacon
+                .get_beacon_data_for_block(block_number, eth_block.timestamp.as_u64())
+                .await
+            {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    debug!(
+                        "Failed to fetch beacon data for block {}: {}",
+                        block_number, e
+                    );
+                    None
+                }
+            },
+            None => None,
         };
+        #[cfg(not(feature = "beacon"))]
+        let beacon_data: Option<serde_json::Value> = None;
+
+        #[cfg(feature = "beacon")]
+        if let (Some(beacon), Some(epoch)) = (
+            &self.beacon,
+            beacon_data.as_ref().and_then(|d| d["epoch"].as_i64()),
+        ) {
+            self.record_proposer_duties(beacon, epoch).await;
+        }
 
         let block = Block {
             number: block_number as i64,
@@ -251,9 +583,12 @@ impl BlockProcessor {
             parent_root: beacon_data
                 .as_ref()
                 .and_then(|d| d["parent_root"].as_str().map(|s| s.to_string())),
-            beacon_deposit_count: beacon_data
+            block_deposit_count: beacon_data
+                .as_ref()
+                .and_then(|d| d["block_deposit_count"].as_i64()),
+            eth1_deposit_count: beacon_data
                 .as_ref()
-                .and_then(|d| d["beacon_deposit_count"].as_i64()),
+                .and_then(|d| d["eth1_deposit_count"].as_i64()),
             graffiti: beacon_data
                 .as_ref()
                 .and_then(|d| d["graffiti"].as_str().map(|s| s.to_string())),
@@ -263,8 +598,103 @@ impl BlockProcessor {
             randao_mix: beacon_data
                 .as_ref()
                 .and_then(|d| d["randao_mix"].as_str().map(|s| s.to_string())),
+            sync_participation: beacon_data
+                .as_ref()
+                .and_then(|d| d["sync_participation"].as_f64()),
+            attestation_count: beacon_data
+                .as_ref()
+                .and_then(|d| d["attestation_count"].as_i64()),
+            slashing_count: beacon_data
+                .as_ref()
+                .and_then(|d| d["slashing_count"].as_i64()),
         };
 
-        Ok(block)
+        let fee_recipient = beacon_data
+            .as_ref()
+            .and_then(|d| d["fee_recipient"].as_str().map(|s| s.to_lowercase()));
+
+        Ok((block, fee_recipient))
     }
+
+    /// Store the proposer duties of an epoch when one of its blocks is first indexed.
+    /// Failures are only logged: nodes without historical states can't serve old epochs.
+    #[cfg(feature = "beacon")]
+    async fn record_proposer_duties(&self, beacon: &BeaconClient, epoch: i64) {
+        if self.duties_epoch.swap(epoch, Ordering::Relaxed) == epoch {
+            return;
+        }
+
+        let recorded = async {
+            if self.db.has_proposer_duties(epoch).await? {
+                return Ok(());
+            }
+            let duties = beacon
+                .get_proposer_duties(epoch as u64)
+                .await?
+                .into_iter()
+                .map(|duty| {
+                    Ok(ProposerDuty {
+                        slot: duty.slot.parse().context("Invalid duty slot")?,
+                        epoch,
+                        validator_index: duty
+                            .validator_index
+                            .parse()
+                            .context("Invalid duty validator index")?,
+                        pubkey: duty.pubkey.to_lowercase(),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.db.insert_proposer_duties(&duties).await
+        }
+        .await;
+        if let Err(e) = recorded {
+            debug!(
+                "Failed to record proposer duties of epoch {}: {:#}",
+                epoch, e
+            );
+        }
+    }
+}
+
+/// Payload fee recipient of a block cross-checked with its miner, with the proposer
+/// payment of the fee recipient among the block's transactions
+fn block_fee_recipient(
+    block: &Block,
+    fee_recipient: String,
+    transactions: &[Transaction],
+) -> BlockFeeRecipient {
+    let payment = proposer_payment(&fee_recipient, transactions);
+    BlockFeeRecipient {
+        block_number: block.number,
+        slot: block.slot,
+        proposer_index: block.proposer_index,
+        miner: block.miner.clone(),
+        mismatch: block
+            .miner
+            .as_ref()
+            .is_some_and(|miner| !miner.eq_ignore_ascii_case(&fee_recipient)),
+        proposer_payee: payment.and_then(|tx| tx.to_address.clone()),
+        proposer_payment: payment.map(|tx| tx.value.clone()),
+        fee_recipient,
+    }
+}
+
+/// Validator withdrawals of a block, indexed by their position in the block
+fn convert_withdrawals<TX>(eth_block: &EthBlock<TX>) -> Vec<Withdrawal> {
+    let block_number = eth_block.number.map_or(0, |number| number.as_u64() as i64);
+    eth_block
+        .withdrawals
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, withdrawal)| Withdrawal {
+            id: None,
+            block_number,
+            withdrawal_index: index as i64,
+            validator_index: withdrawal.validator_index.as_u64() as i64,
+            address: format!("{:?}", withdrawal.address),
+            amount: withdrawal.amount.to_string(), // Amount in Gwei
+            created_at: None,
+        })
+        .collect()
 }
@@ -0,0 +1,215 @@
+use crate::{
+    config::AppConfig,
+    database::{ConsistencyReport, DatabaseService},
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::sync::{
+    atomic::{AtomicBool, AtomicI64, Ordering},
+    Arc,
+};
+use tokio::sync::mpsc;
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Compares the declared transaction count of indexed blocks with the transactions actually
+/// stored, sweeping the indexed range a window at a time. Mismatched blocks have their
+/// transactions, logs and token transfers cleared and are queued for reprocessing, and
+/// blocks missing from the range, whose processing failed, are queued again.
+///
+/// The newest blocks are left alone since workers may still be storing their transactions.
+#[derive(Clone)]
+pub struct ConsistencyChecker {
+    db: Arc<DatabaseService>,
+    config: AppConfig,
+    events: EventLog,
+    checked: Arc<AtomicI64>,
+    mismatched: Arc<AtomicI64>,
+    requeued: Arc<AtomicI64>,
+    missing: Arc<AtomicI64>,
+}
+
+impl ConsistencyChecker {
+    /// Create a checker without results
+    pub fn new(db: Arc<DatabaseService>, config: AppConfig, events: EventLog) -> Self {
+        Self {
+            db,
+            config,
+            events,
+            checked: Arc::new(AtomicI64::new(0)),
+            mismatched: Arc::new(AtomicI64::new(0)),
+            requeued: Arc::new(AtomicI64::new(0)),
+            missing: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Results since the indexer started
+    pub fn report(&self) -> ConsistencyReport {
+        let checked_blocks = self.checked.load(Ordering::Relaxed);
+        let mismatched_blocks = self.mismatched.load(Ordering::Relaxed);
+
+        ConsistencyReport {
+            checked_blocks,
+            mismatched_blocks,
+            requeued_blocks: self.requeued.load(Ordering::Relaxed),
+            missing_blocks: self.missing.load(Ordering::Relaxed),
+            mismatch_percentage: if checked_blocks > 0 {
+                mismatched_blocks as f64 / checked_blocks as f64 * 100.0
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Start checking in the background, re-queuing mismatched blocks on `block_sender`
+    pub fn start(
+        &self,
+        block_sender: mpsc::Sender<i64>,
+        is_running: Arc<AtomicBool>,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        if self.config.consistency_check_interval_seconds == 0 {
+            info!("Block consistency checker disabled");
+            return None;
+        }
+
+        let checker = self.clone();
+        Some(tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(
+                checker.config.consistency_check_interval_seconds,
+            ));
+            let mut next_from = None;
+
+            while is_running.load(Ordering::Relaxed) {
+                interval.tick().await;
+                match checker.check_window(next_from, &block_sender).await {
+                    Ok(next) => next_from = next,
+                    Err(e) => warn!("Block consistency check failed: {:#}", e),
+                }
+            }
+        }))
+    }
+
+    /// Check the window starting at `from` (the lowest block when None).
+    /// Returns where the next window starts, None to wrap around.
+    async fn check_window(
+        &self,
+        from: Option<i64>,
+        block_sender: &mpsc::Sender<i64>,
+    ) -> Result<Option<i64>> {
+        let Some((lowest, highest)) = self.db.get_block_number_range().await? else {
+            return Ok(None);
+        };
+
+        // Blocks still queued or in progress may not have all transactions stored yet
+        let in_flight = (self.config.worker_pool_size * self.config.block_queue_size_multiplier
+            + self.config.max_concurrent_blocks) as i64;
+        let upper = highest - in_flight;
+        if upper < lowest {
+            return Ok(None);
+        }
+
+        let from = from
+            .filter(|from| (lowest..=upper).contains(from))
+            .unwrap_or(lowest);
+        let to = (from + self.config.consistency_check_range.max(1) - 1).min(upper);
+
+        let counts = self.db.get_block_transaction_counts(from, to).await?;
+        let mismatched: Vec<(i64, i64, i64)> = counts
+            .iter()
+            .copied()
+            .filter(|(number, declared, stored)| {
+                declared != stored && !self.config.is_headers_only(*number)
+            })
+            .collect();
+        self.checked
+            .fetch_add(counts.len() as i64, Ordering::Relaxed);
+        self.mismatched
+            .fetch_add(mismatched.len() as i64, Ordering::Relaxed);
+
+        debug!(
+            "Consistency check of blocks {}..={}: {} checked, {} mismatched",
+            from,
+            to,
+            counts.len(),
+            mismatched.len()
+        );
+
+        let mut requeued = Vec::new();
+        for (block_number, declared, stored) in &mismatched {
+            if let Err(e) = self.db.delete_block_transactions(*block_number).await {
+                warn!(
+                    "Failed to clear block #{} for reprocessing: {:#}",
+                    block_number, e
+                );
+                continue;
+            }
+            if block_sender.send(*block_number).await.is_err() {
+                // Workers stopped, the block is picked up again on the next run
+                break;
+            }
+
+            info!(
+                "Re-queued block #{}: {} transactions declared, {} stored",
+                block_number, declared, stored
+            );
+            requeued.push(*block_number);
+        }
+
+        if !requeued.is_empty() {
+            self.requeued
+                .fetch_add(requeued.len() as i64, Ordering::Relaxed);
+            self.events.record(
+                NewIndexerEvent::warning(
+                    IndexerEventKind::BlockRequeued,
+                    format!(
+                        "Re-queued {} blocks with mismatched transaction counts",
+                        requeued.len()
+                    ),
+                )
+                .at_block(requeued[0])
+                .with_details(serde_json::json!({ "blocks": requeued })),
+            );
+        }
+
+        self.repair_gaps(from, to, &counts, block_sender).await;
+
+        Ok((to < upper).then_some(to + 1))
+    }
+
+    /// Queue the blocks of `from..=to` missing from `counts`, the stored blocks of the range
+    async fn repair_gaps(
+        &self,
+        from: i64,
+        to: i64,
+        counts: &[(i64, i64, i64)],
+        block_sender: &mpsc::Sender<i64>,
+    ) {
+        let stored: HashSet<i64> = counts.iter().map(|(number, _, _)| *number).collect();
+        let mut queued = Vec::new();
+        for block_number in (from..=to).filter(|number| !stored.contains(number)) {
+            if block_sender.send(block_number).await.is_err() {
+                break;
+            }
+            info!("Re-queued missing block #{}", block_number);
+            queued.push(block_number);
+        }
+
+        if queued.is_empty() {
+            return;
+        }
+        self.missing
+            .fetch_add(queued.len() as i64, Ordering::Relaxed);
+        self.events.record(
+            NewIndexerEvent::warning(
+                IndexerEventKind::GapRepaired,
+                format!(
+                    "Re-queued {} blocks missing from the indexed range",
+                    queued.len()
+                ),
+            )
+            .at_block(queued[0])
+            .with_details(serde_json::json!({ "blocks": queued })),
+        );
+    }
+}
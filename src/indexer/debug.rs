@@ -0,0 +1,363 @@
+//! Replay of a single block for investigating bad data reports.
+//!
+//! The block is fetched and decoded exactly like the workers do, but nothing is written:
+//! the decoded rows are returned together with every RPC call made, the time spent per
+//! step and the differences with what the database holds for the block. Only `--apply`
+//! (or `apply=true` on the admin endpoint) reindexes the block afterwards.
+
+use super::IndexerService;
+use crate::{
+    database::BlockData,
+    events::{IndexerEventKind, NewIndexerEvent},
+    rpc::{request_id::with_request_id, RpcCall},
+};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::Arc,
+    time::Instant,
+};
+use tracing::info;
+
+/// Fields left out of diffs: generated by the database, completed while storing (method
+/// names from verified signatures), or not loaded with stored rows
+const IGNORED_FIELDS: [&str; 6] = [
+    "id",
+    "created_at",
+    "updated_at",
+    "method_name",
+    "input",
+    "input_size",
+];
+
+/// Time spent on one step of a replay
+#[derive(Debug, Clone, Serialize)]
+pub struct StepTiming {
+    pub step: &'static str,
+    pub duration_ms: u64,
+}
+
+impl StepTiming {
+    /// Timing of a step started at `started`
+    pub fn since(step: &'static str, started: Instant) -> Self {
+        Self {
+            step,
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Rows of one table that differ between the replay and the database
+#[derive(Debug, Serialize)]
+pub struct TableDiff {
+    pub table: &'static str,
+    pub decoded: usize,
+    pub stored: usize,
+    pub missing: Vec<Value>,    // Decoded but not stored
+    pub unexpected: Vec<Value>, // Stored but not decoded
+    pub changed: Vec<RowChange>,
+}
+
+impl TableDiff {
+    /// Whether the stored rows match the decoded ones
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Fields of a row whose stored value differs from the decoded one
+#[derive(Debug, Serialize)]
+pub struct RowChange {
+    pub key: String,
+    pub fields: BTreeMap<String, Value>, // {"decoded": ..., "stored": ...} per field
+}
+
+/// Outcome of a block replay
+#[derive(Debug, Serialize)]
+pub struct BlockDebugReport {
+    pub block_number: i64,
+    pub consistent: bool, // Whether the stored block matches the replay
+    pub applied: bool,    // Whether the block was reindexed afterwards
+    pub timings: Vec<StepTiming>,
+    pub rpc_calls: Vec<RpcCall>,
+    pub decoded: BlockData,
+    pub diffs: Vec<TableDiff>, // Tables with differences only
+}
+
+impl IndexerService {
+    /// Fetch and decode block `block_number` like the workers do, recording its RPC calls
+    /// and step timings, and diff the result against the stored block. Nothing is written
+    /// unless `apply`, which reindexes the block once the report is built.
+    pub async fn debug_block(&self, block_number: i64, apply: bool) -> Result<BlockDebugReport> {
+        if block_number < 0 {
+            return Err(anyhow!("Invalid block number {}", block_number));
+        }
+
+        // Calls are attributed to their own request ID, so concurrent traffic isn't traced
+        let request_id: Arc<str> = format!(
+            "debug-block-{}-{}",
+            block_number,
+            chrono::Utc::now().timestamp_millis()
+        )
+        .into();
+        let mut timings = Vec::new();
+        self.rpc.start_call_trace(request_id.clone());
+        let decoded = with_request_id(
+            Some(request_id.clone()),
+            self.block_processor
+                .decode_block(block_number as u64, &mut timings),
+        )
+        .await;
+        let rpc_calls = self.rpc.finish_call_trace(&request_id);
+        let decoded = decoded?;
+
+        let started = Instant::now();
+        let diffs: Vec<TableDiff> = self
+            .diff_stored_block(block_number, &decoded)
+            .await?
+            .into_iter()
+            .filter(|diff| !diff.is_empty())
+            .collect();
+        timings.push(StepTiming::since("stored_diff", started));
+        info!(
+            "Replayed block #{}: {} RPC calls, {} tables differ from the database",
+            block_number,
+            rpc_calls.len(),
+            diffs.len()
+        );
+
+        if apply {
+            let started = Instant::now();
+            self.block_processor
+                .reindex_block(block_number as u64)
+                .await?;
+            timings.push(StepTiming::since("apply", started));
+            self.events.record(
+                NewIndexerEvent::info(
+                    IndexerEventKind::RangeReindexed,
+                    format!("Reindexed block #{} after a debug replay", block_number),
+                )
+                .at_block(block_number)
+                .with_details(json!({
+                    "tables_differing": diffs.iter().map(|diff| diff.table).collect::<Vec<_>>()
+                })),
+            );
+        }
+
+        Ok(BlockDebugReport {
+            block_number,
+            consistent: diffs.is_empty(),
+            applied: apply,
+            timings,
+            rpc_calls,
+            decoded,
+            diffs,
+        })
+    }
+
+    /// Compare the decoded rows of a block with the stored ones, table by table
+    async fn diff_stored_block(
+        &self,
+        block_number: i64,
+        decoded: &BlockData,
+    ) -> Result<Vec<TableDiff>> {
+        let stored_block = self.db.get_block_by_number(block_number).await?;
+        Ok(vec![
+            diff_rows(
+                "blocks",
+                std::slice::from_ref(&decoded.block),
+                stored_block.as_slice(),
+                &["number"],
+            )?,
+            diff_rows(
+                "withdrawals",
+                &decoded.withdrawals,
+                &self.db.get_withdrawals_by_block(block_number).await?,
+                &["withdrawal_index"],
+            )?,
+            diff_rows(
+                "deposits",
+                &decoded.deposits,
+                &self.db.get_deposits_by_block(block_number).await?,
+                &["log_index"],
+            )?,
+            diff_rows(
+                "transactions",
+                &decoded.transactions,
+                &self.db.get_transactions_by_block(block_number).await?,
+                &["hash"],
+            )?,
+            diff_rows(
+                "transaction_receipts",
+                &decoded.receipts,
+                &self.db.get_receipts_by_block(block_number).await?,
+                &["transaction_hash"],
+            )?,
+            diff_rows(
+                "logs",
+                &decoded.logs,
+                &self.db.get_logs_by_block(block_number).await?,
+                &["transaction_hash", "log_index"],
+            )?,
+            diff_rows(
+                "token_transfers",
+                &decoded.token_transfers,
+                &self.db.get_token_transfers_by_block(block_number).await?,
+                &["transaction_hash", "log_index"],
+            )?,
+            diff_rows(
+                "block_fee_recipients",
+                decoded.fee_recipient.as_slice(),
+                self.db
+                    .get_block_fee_recipient(block_number)
+                    .await?
+                    .as_slice(),
+                &["block_number"],
+            )?,
+        ])
+    }
+}
+
+/// Match decoded and stored rows by the `key` fields and report the differences.
+/// Rows sharing a key are paired in order.
+fn diff_rows<T: Serialize>(
+    table: &'static str,
+    decoded: &[T],
+    stored: &[T],
+    key: &[&str],
+) -> Result<TableDiff> {
+    let decoded = decoded.iter().map(row_value).collect::<Result<Vec<_>>>()?;
+    let stored = stored.iter().map(row_value).collect::<Result<Vec<_>>>()?;
+
+    let mut stored_by_key: HashMap<String, VecDeque<Map<String, Value>>> = HashMap::new();
+    for row in &stored {
+        stored_by_key
+            .entry(row_key(row, key))
+            .or_default()
+            .push_back(row.clone());
+    }
+
+    let mut diff = TableDiff {
+        table,
+        decoded: decoded.len(),
+        stored: stored.len(),
+        missing: Vec::new(),
+        unexpected: Vec::new(),
+        changed: Vec::new(),
+    };
+    for row in decoded {
+        let row_key = row_key(&row, key);
+        match stored_by_key
+            .get_mut(&row_key)
+            .and_then(VecDeque::pop_front)
+        {
+            Some(stored_row) if stored_row != row => diff.changed.push(RowChange {
+                key: row_key,
+                fields: changed_fields(&row, &stored_row),
+            }),
+            Some(_) => {}
+            None => diff.missing.push(Value::Object(row)),
+        }
+    }
+    diff.unexpected = stored_by_key
+        .into_values()
+        .flatten()
+        .map(Value::Object)
+        .collect();
+
+    Ok(diff)
+}
+
+/// Fields of a row as compared, without the ignored ones
+fn row_value<T: Serialize>(row: &T) -> Result<Map<String, Value>> {
+    let Value::Object(mut fields) = serde_json::to_value(row)? else {
+        return Err(anyhow!("Row did not serialize to an object"));
+    };
+    for field in IGNORED_FIELDS {
+        fields.remove(field);
+    }
+    Ok(fields)
+}
+
+/// Values of the `key` fields of a row joined with ':'
+fn row_key(row: &Map<String, Value>, key: &[&str]) -> String {
+    key.iter()
+        .map(|field| match row.get(*field) {
+            Some(Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+            None => "null".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Fields whose values differ between two rows
+fn changed_fields(
+    decoded: &Map<String, Value>,
+    stored: &Map<String, Value>,
+) -> BTreeMap<String, Value> {
+    let fields: BTreeSet<&String> = decoded.keys().chain(stored.keys()).collect();
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let decoded = decoded.get(field).unwrap_or(&Value::Null);
+            let stored = stored.get(field).unwrap_or(&Value::Null);
+            (decoded != stored).then(|| {
+                (
+                    field.clone(),
+                    json!({ "decoded": decoded, "stored": stored }),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Options of the `debug-block` command
+#[derive(Debug, Clone)]
+pub struct DebugBlockOptions {
+    pub block_number: i64,
+    pub apply: bool,             // Reindex the block after the replay
+    pub network: Option<String>, // Default network when unset
+}
+
+impl DebugBlockOptions {
+    /// Build options from command line arguments (`<number>`, `--apply`, `--network`)
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut block_number = None;
+        let mut apply = false;
+        let mut network = None;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--apply" => apply = true,
+                "--network" => {
+                    network = Some(
+                        args.next()
+                            .ok_or_else(|| anyhow!("Missing value for --network"))?
+                            .to_lowercase(),
+                    )
+                }
+                number if block_number.is_none() && !number.starts_with("--") => {
+                    block_number = Some(
+                        number
+                            .parse::<i64>()
+                            .ok()
+                            .filter(|number| *number >= 0)
+                            .ok_or_else(|| anyhow!("Invalid block number: {}", number))?,
+                    )
+                }
+                other => return Err(anyhow!("Unknown debug-block argument: {}", other)),
+            }
+        }
+
+        Ok(Self {
+            block_number: block_number
+                .ok_or_else(|| anyhow!("Usage: debug-block <number> [--apply] [--network NAME]"))?,
+            apply,
+            network,
+        })
+    }
+}
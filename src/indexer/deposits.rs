@@ -0,0 +1,70 @@
+use crate::database::{Deposit, Log, Transaction};
+use ethers::types::U256;
+use std::collections::HashMap;
+
+/// DepositEvent(bytes,bytes,bytes,bytes,bytes) event signature of the beacon deposit contract
+pub const DEPOSIT_EVENT_TOPIC: &str =
+    "0x649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5";
+
+/// Byte lengths of the DepositEvent fields: pubkey, withdrawal_credentials, amount,
+/// signature and index
+const DEPOSIT_FIELD_LENGTHS: [usize; 5] = [48, 32, 8, 96, 8];
+
+/// Deposits decoded from the logs of a block emitted by `deposit_contract`, in log order,
+/// with the sender of their transaction as depositor. Logs that don't match the deposit
+/// contract layout are skipped.
+pub fn deposits(
+    logs: &[Log],
+    transactions: &[Transaction],
+    deposit_contract: &str,
+) -> Vec<Deposit> {
+    let senders: HashMap<&str, &str> = transactions
+        .iter()
+        .map(|tx| (tx.hash.as_str(), tx.from_address.as_str()))
+        .collect();
+
+    logs.iter()
+        .filter(|log| log.address.eq_ignore_ascii_case(deposit_contract))
+        .filter(|log| log.topic0.as_deref() == Some(DEPOSIT_EVENT_TOPIC))
+        .filter_map(|log| deposit(log, senders.get(log.transaction_hash.as_str())?))
+        .collect()
+}
+
+fn deposit(log: &Log, depositor: &str) -> Option<Deposit> {
+    let data = hex::decode(log.data.as_deref()?.trim_start_matches("0x")).ok()?;
+    let fields = decode_bytes_fields(&data)?;
+    let [pubkey, withdrawal_credentials, amount, signature, index] = fields;
+
+    Some(Deposit {
+        block_number: log.block_number,
+        log_index: log.log_index,
+        transaction_hash: log.transaction_hash.clone(),
+        deposit_index: i64::try_from(u64::from_le_bytes(index.try_into().ok()?)).ok()?,
+        depositor: depositor.to_lowercase(),
+        pubkey: format!("0x{}", hex::encode(pubkey)),
+        withdrawal_credentials: format!("0x{}", hex::encode(withdrawal_credentials)),
+        // Little-endian Gwei, as the beacon chain encodes it
+        amount: u64::from_le_bytes(amount.try_into().ok()?).to_string(),
+        signature: format!("0x{}", hex::encode(signature)),
+        validator_index: None,
+    })
+}
+
+/// The five ABI-encoded `bytes` fields of a DepositEvent, None unless each has its
+/// expected length
+fn decode_bytes_fields(data: &[u8]) -> Option<[&[u8]; 5]> {
+    let word = |offset: usize| -> Option<usize> {
+        let word = U256::from_big_endian(data.get(offset..offset + 32)?);
+        (word <= U256::from(data.len())).then(|| word.as_usize())
+    };
+
+    let mut fields = [&data[..0]; 5];
+    for (i, length) in DEPOSIT_FIELD_LENGTHS.into_iter().enumerate() {
+        let offset = word(i * 32)?;
+        if word(offset)? != length {
+            return None;
+        }
+        fields[i] = data.get(offset + 32..offset + 32 + length)?;
+    }
+    Some(fields)
+}
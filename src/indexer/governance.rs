@@ -0,0 +1,51 @@
+use crate::database::{DelegationChange, Log};
+use ethers::types::U256;
+
+/// DelegateChanged(address,address,address) event signature of ERC-20 Votes tokens
+pub const DELEGATE_CHANGED_TOPIC: &str =
+    "0x3134e8a2e6d97e929a7e54011ea5485d7d196dd5f0ba4d4ef95803e8e3fc257f";
+
+/// DelegateVotesChanged(address,uint256,uint256) event signature of ERC-20 Votes tokens
+pub const DELEGATE_VOTES_CHANGED_TOPIC: &str =
+    "0xdec2bacdd2f05b59de34da9b523dff8be42e5e38e818c82fdb0bae774387a724";
+
+/// Delegation changes decoded from the logs of a block, in log order. Logs that don't
+/// match the ERC-20 Votes layout (all addresses indexed, the new voting power as the
+/// second data word) are skipped.
+pub fn delegation_changes(logs: &[Log]) -> Vec<DelegationChange> {
+    logs.iter().filter_map(delegation_change).collect()
+}
+
+fn delegation_change(log: &Log) -> Option<DelegationChange> {
+    let change = DelegationChange {
+        token_address: log.address.clone(),
+        account_address: topic_address(log.topic1.as_deref()?)?,
+        delegate: None,
+        votes: None,
+        block_number: log.block_number,
+        log_index: log.log_index,
+    };
+
+    match log.topic0.as_deref()? {
+        // DelegateChanged(delegator indexed, fromDelegate indexed, toDelegate indexed)
+        DELEGATE_CHANGED_TOPIC => Some(DelegationChange {
+            delegate: Some(topic_address(log.topic3.as_deref()?)?),
+            ..change
+        }),
+        // DelegateVotesChanged(delegate indexed, previousVotes, newVotes)
+        DELEGATE_VOTES_CHANGED_TOPIC if log.topic2.is_none() => {
+            let data = hex::decode(log.data.as_deref()?.trim_start_matches("0x")).ok()?;
+            (data.len() == 64).then(|| DelegationChange {
+                votes: Some(U256::from_big_endian(&data[32..]).to_string()),
+                ..change
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Address in the last 20 bytes of a 32-byte topic
+pub(super) fn topic_address(topic: &str) -> Option<String> {
+    let digits = topic.strip_prefix("0x")?;
+    (digits.len() == 64).then(|| format!("0x{}", &digits[24..]))
+}
@@ -0,0 +1,201 @@
+use crate::{
+    config::NetworkProfile,
+    database::{L2Block, L2Transaction},
+};
+use ethers::core::types::{
+    Block as EthBlock, OtherFields, Transaction as EthTransaction, TransactionReceipt, H256, U256,
+};
+
+/// OP-stack deposit transaction type
+const OP_DEPOSIT_TX_TYPE: u64 = 0x7e;
+
+/// Sender of the L1 attributes transaction opening every OP-stack block
+const OP_L1_ATTRIBUTES_DEPOSITOR: &str = "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001";
+
+/// OP-stack predeploy withdrawals are initiated through
+const OP_L2_TO_L1_MESSAGE_PASSER: &str = "0x4200000000000000000000000000000000000016";
+
+/// Arbitrum precompile withdrawals are initiated through
+const ARB_SYS: &str = "0x0000000000000000000000000000000000000064";
+
+/// ArbSys withdrawEth(address) and sendTxToL1(address,bytes) selectors
+const ARB_SYS_WITHDRAWAL_SELECTORS: [[u8; 4]; 2] =
+    [[0x25, 0xe1, 0x60, 0x63], [0x92, 0x8c, 0x16, 0x9a]];
+
+/// L1 attributes setters of the OP-stack L1Block predeploy
+const SET_L1_BLOCK_VALUES: [u8; 4] = [0x01, 0x5d, 0x8e, 0xb9]; // Bedrock, ABI encoded
+const SET_L1_BLOCK_VALUES_ECOTONE: [u8; 4] = [0x44, 0x0a, 0x5e, 0x20]; // Packed
+const SET_L1_BLOCK_VALUES_ISTHMUS: [u8; 4] = [0x09, 0x89, 0x99, 0xbe]; // Packed, Ecotone layout extended
+
+/// Rollup data of a transaction, None on L1 networks
+pub fn l2_transaction(
+    profile: NetworkProfile,
+    tx: &EthTransaction,
+    receipt: &TransactionReceipt,
+) -> Option<L2Transaction> {
+    if !profile.is_l2() {
+        return None;
+    }
+
+    let receipt_fields = &receipt.other;
+    let mut l2 = L2Transaction {
+        transaction_hash: format!("{:?}", tx.hash),
+        block_number: receipt.block_number?.as_u64() as i64,
+        kind: system_kind(profile, tx),
+        ..Default::default()
+    };
+
+    match profile {
+        NetworkProfile::Ethereum => {}
+        NetworkProfile::OpStack => {
+            l2.l1_fee = quantity(receipt_fields, "l1Fee");
+            l2.l1_gas_used = quantity(receipt_fields, "l1GasUsed").and_then(|gas| gas.parse().ok());
+            l2.l1_gas_price = quantity(receipt_fields, "l1GasPrice");
+            // Pre-Ecotone nodes report a decimal scalar, later ones the base fee scalar
+            l2.l1_fee_scalar = quantity(receipt_fields, "l1BaseFeeScalar")
+                .or_else(|| quantity(receipt_fields, "l1FeeScalar"));
+            l2.l1_blob_base_fee = quantity(receipt_fields, "l1BlobBaseFee");
+            l2.source_hash = tx
+                .other
+                .get_deserialized::<H256>("sourceHash")
+                .and_then(Result::ok)
+                .map(|hash| format!("{:?}", hash));
+            l2.mint = quantity(&tx.other, "mint");
+        }
+        NetworkProfile::Arbitrum => {
+            l2.l1_gas_used =
+                quantity(receipt_fields, "gasUsedForL1").and_then(|gas| gas.parse().ok());
+            l2.l1_block_number =
+                quantity(receipt_fields, "l1BlockNumber").and_then(|number| number.parse().ok());
+        }
+    }
+
+    Some(l2)
+}
+
+/// L1 origin and batch references of a block, None on L1 networks or when the node
+/// doesn't report them
+pub fn l2_block(profile: NetworkProfile, block: &EthBlock<EthTransaction>) -> Option<L2Block> {
+    let block_number = block.number?.as_u64() as i64;
+
+    match profile {
+        NetworkProfile::Ethereum => None,
+        NetworkProfile::OpStack => {
+            let attributes = block
+                .transactions
+                .first()
+                .filter(|tx| format!("{:?}", tx.from) == OP_L1_ATTRIBUTES_DEPOSITOR)?;
+            decode_l1_attributes(block_number, &attributes.input)
+        }
+        NetworkProfile::Arbitrum => {
+            let l2 = L2Block {
+                block_number,
+                l1_block_number: quantity(&block.other, "l1BlockNumber")
+                    .and_then(|number| number.parse().ok()),
+                send_count: quantity(&block.other, "sendCount")
+                    .and_then(|count| count.parse().ok()),
+                send_root: block
+                    .other
+                    .get_deserialized::<H256>("sendRoot")
+                    .and_then(Result::ok)
+                    .map(|root| format!("{:?}", root)),
+                ..Default::default()
+            };
+            (l2.l1_block_number.is_some() || l2.send_root.is_some()).then_some(l2)
+        }
+    }
+}
+
+/// Kind of a rollup system transaction, None for user transactions
+fn system_kind(profile: NetworkProfile, tx: &EthTransaction) -> Option<String> {
+    let tx_type = tx.transaction_type.map(|tx_type| tx_type.as_u64());
+    let to = tx.to.map(|to| format!("{:?}", to));
+
+    let kind = match profile {
+        NetworkProfile::Ethereum => return None,
+        NetworkProfile::OpStack => {
+            if tx_type == Some(OP_DEPOSIT_TX_TYPE) {
+                if format!("{:?}", tx.from) == OP_L1_ATTRIBUTES_DEPOSITOR {
+                    "l1_attributes"
+                } else {
+                    "deposit"
+                }
+            } else if to.as_deref() == Some(OP_L2_TO_L1_MESSAGE_PASSER) {
+                "withdrawal"
+            } else {
+                return None;
+            }
+        }
+        NetworkProfile::Arbitrum => match tx_type {
+            Some(0x64) => "deposit",
+            Some(0x65) => "unsigned",
+            Some(0x66) => "contract",
+            Some(0x68) => "retryable_redeem",
+            Some(0x69) => "retryable_submission",
+            Some(0x6a) => "internal",
+            _ if to.as_deref() == Some(ARB_SYS)
+                && tx.input.len() >= 4
+                && ARB_SYS_WITHDRAWAL_SELECTORS.contains(&[
+                    tx.input[0],
+                    tx.input[1],
+                    tx.input[2],
+                    tx.input[3],
+                ]) =>
+            {
+                "withdrawal"
+            }
+            _ => return None,
+        },
+    };
+
+    Some(kind.to_string())
+}
+
+/// Decode the calldata of an OP-stack L1 attributes transaction
+fn decode_l1_attributes(block_number: i64, input: &[u8]) -> Option<L2Block> {
+    let (selector, data) = input.split_at_checked(4)?;
+    let word = |index: usize| data.get(index * 32..(index + 1) * 32);
+    let u64_at = |bytes: &[u8]| Some(U256::from_big_endian(bytes).low_u64() as i64);
+    let hash_at = |bytes: &[u8]| Some(format!("{:?}", H256::from_slice(bytes)));
+
+    if selector == SET_L1_BLOCK_VALUES {
+        // number, timestamp, basefee, hash, sequenceNumber, batcherHash, overhead, scalar
+        Some(L2Block {
+            block_number,
+            l1_block_number: u64_at(word(0)?),
+            l1_timestamp: u64_at(word(1)?),
+            l1_block_hash: hash_at(word(3)?),
+            sequence_number: u64_at(word(4)?),
+            batcher_hash: hash_at(word(5)?),
+            ..Default::default()
+        })
+    } else if selector == SET_L1_BLOCK_VALUES_ECOTONE || selector == SET_L1_BLOCK_VALUES_ISTHMUS {
+        // baseFeeScalar (4), blobBaseFeeScalar (4), sequenceNumber (8), timestamp (8),
+        // number (8), basefee (32), blobBaseFee (32), hash (32), batcherHash (32)
+        Some(L2Block {
+            block_number,
+            sequence_number: u64_at(data.get(8..16)?),
+            l1_timestamp: u64_at(data.get(16..24)?),
+            l1_block_number: u64_at(data.get(24..32)?),
+            l1_block_hash: hash_at(data.get(96..128)?),
+            batcher_hash: hash_at(data.get(128..160)?),
+            ..Default::default()
+        })
+    } else {
+        None
+    }
+}
+
+/// Decimal value of a quantity field reported by rollup nodes, as hex or decimal
+fn quantity(fields: &OtherFields, key: &str) -> Option<String> {
+    match fields.get(key)? {
+        serde_json::Value::String(value) => match value.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16)
+                .ok()
+                .map(|value| value.to_string()),
+            None => Some(value.clone()),
+        },
+        serde_json::Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
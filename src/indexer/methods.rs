@@ -0,0 +1,64 @@
+use crate::rpc::multicall::selector;
+use std::{collections::HashMap, sync::OnceLock};
+
+/// Functions named at index time, a selector matching several keeping the first name.
+/// Selectors missing here are named afterwards from the signatures of verified contracts.
+const KNOWN_METHODS: &[&str] = &[
+    // ERC-20, ERC-721 and ERC-1155
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+    "safeTransferFrom(address,address,uint256)",
+    "safeTransferFrom(address,address,uint256,bytes)",
+    "safeTransferFrom(address,address,uint256,uint256,bytes)",
+    "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+    "setApprovalForAll(address,bool)",
+    "mint(address,uint256)",
+    "burn(uint256)",
+    // WETH
+    "deposit()",
+    "withdraw(uint256)",
+    // Batching
+    "multicall(bytes[])",
+    "multicall(uint256,bytes[])",
+    "aggregate((address,bytes)[])",
+    "aggregate3((address,bool,bytes)[])",
+    // Uniswap routers
+    "execute(bytes,bytes[])",
+    "execute(bytes,bytes[],uint256)",
+    "exactInput((bytes,address,uint256,uint256,uint256))",
+    "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+    "exactOutput((bytes,address,uint256,uint256,uint256))",
+    "exactOutputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+    "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+    "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokens(uint256,address[],address,uint256)",
+    "swapETHForExactTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+    "swapTokensForExactETH(uint256,uint256,address[],address,uint256)",
+    "swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokensSupportingFeeOnTransferTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForETHSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+    "addLiquidity(address,address,uint256,uint256,uint256,uint256,address,uint256)",
+    "addLiquidityETH(address,uint256,uint256,uint256,address,uint256)",
+    "removeLiquidity(address,address,uint256,uint256,uint256,address,uint256)",
+    "removeLiquidityETH(address,uint256,uint256,uint256,address,uint256)",
+];
+
+/// Name of the function a 0x-prefixed selector calls, when it is one of `KNOWN_METHODS`
+pub fn method_name(method_id: &str) -> Option<&'static str> {
+    static NAMES: OnceLock<HashMap<String, &'static str>> = OnceLock::new();
+    let names = NAMES.get_or_init(|| {
+        let mut names = HashMap::new();
+        for signature in KNOWN_METHODS {
+            let name = &signature[..signature.find('(').unwrap_or(signature.len())];
+            names
+                .entry(format!("0x{}", hex::encode(selector(signature))))
+                .or_insert(name);
+        }
+        names
+    });
+
+    names.get(method_id).copied()
+}
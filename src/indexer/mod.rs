@@ -1,21 +1,51 @@
+mod approvals;
 mod block_processor;
+mod consistency;
+mod debug;
+mod deposits;
+mod governance;
+mod l2;
+mod methods;
 mod transaction_processor;
+mod watchlist;
 
+#[cfg(feature = "beacon")]
+use crate::beacon::BeaconClient;
+#[cfg(feature = "token-service")]
+use crate::token_service::TokenService;
 use crate::{
-    beacon::BeaconClient, config::AppConfig, database::DatabaseService, rpc::RpcClient,
-    token_service::TokenService,
+    config::AppConfig,
+    database::{ConsistencyReport, DatabaseService},
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+    executor::RpcOverloaded,
+    live::LiveDispatcher,
+    rpc::RpcClient,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use futures::{stream, StreamExt};
+use serde::Serialize;
 use std::sync::{
-    atomic::{AtomicBool, AtomicI64, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering},
+    Arc, Mutex,
 };
 use tokio::sync::mpsc;
 use tokio::time::{self, Duration};
 use tracing::{debug, error, info, warn};
 
 use block_processor::BlockProcessor;
+use consistency::ConsistencyChecker;
+pub use debug::{BlockDebugReport, DebugBlockOptions};
+pub use methods::method_name;
 use transaction_processor::TransactionProcessor;
+use watchlist::Watchlist;
+
+/// Transfer(address,address,uint256) event signature, shared by ERC-20 and ERC-721
+pub const TRANSFER_EVENT_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// First and longest wait of a worker before retrying a block refused by a full RPC queue
+const OVERLOAD_BACKOFF_MIN: Duration = Duration::from_millis(250);
+const OVERLOAD_BACKOFF_MAX: Duration = Duration::from_secs(10);
 
 /// Service for indexing blockchain data with continuous block fetching
 pub struct IndexerService {
@@ -24,10 +54,19 @@ pub struct IndexerService {
     // beacon: Arc<BeaconClient>,
     config: AppConfig,
     is_running: Arc<AtomicBool>,
+    is_paused: Arc<AtomicBool>,
     block_processor: BlockProcessor,
     // tx_processor: TransactionProcessor,
     next_block_to_fetch: Arc<AtomicI64>,
     latest_network_block: Arc<AtomicI64>,
+    block_queue: Arc<Mutex<Option<mpsc::WeakSender<i64>>>>, // Queue of the running indexer
+    last_block_indexed_at: Arc<AtomicI64>, // Unix time of the last processed block, 0 if none
+    worker_pool_size: Arc<AtomicUsize>,    // Workers with a higher id retire after their block
+    worker_pool: Mutex<Option<WorkerPool>>, // Shared state of the running workers
+    worker_stats: Arc<Mutex<Vec<WorkerStats>>>, // Indexed by worker id
+    events: EventLog,
+    watchlist: Watchlist,
+    consistency: ConsistencyChecker,
 }
 
 impl IndexerService {
@@ -35,58 +74,63 @@ impl IndexerService {
     pub fn new(
         db: Arc<DatabaseService>,
         rpc: Arc<RpcClient>,
-        beacon: Arc<BeaconClient>,
         config: AppConfig,
+        events: EventLog,
+        live: LiveDispatcher,
     ) -> Self {
         let tx_processor = TransactionProcessor::new(db.clone(), rpc.clone(), config.clone());
+        let watchlist = tx_processor.watchlist();
         let block_processor = BlockProcessor::new(
             db.clone(),
             rpc.clone(),
-            beacon.clone(),
-            tx_processor.clone(),
+            tx_processor,
+            live,
+            events.clone(),
+            &config,
         );
 
+        let consistency = ConsistencyChecker::new(db.clone(), config.clone(), events.clone());
+        let worker_pool_size = Arc::new(AtomicUsize::new(config.worker_pool_size));
+
         Self {
             db,
             rpc,
             config,
             is_running: Arc::new(AtomicBool::new(false)),
+            is_paused: Arc::new(AtomicBool::new(false)),
             block_processor,
             next_block_to_fetch: Arc::new(AtomicI64::new(0)),
             latest_network_block: Arc::new(AtomicI64::new(0)),
+            block_queue: Arc::new(Mutex::new(None)),
+            last_block_indexed_at: Arc::new(AtomicI64::new(0)),
+            worker_pool_size,
+            worker_pool: Mutex::new(None),
+            worker_stats: Arc::new(Mutex::new(Vec::new())),
+            events,
+            watchlist,
+            consistency,
         }
     }
 
-    /// Create a new indexer service with token service support
-    pub fn with_token_service(
-        db: Arc<DatabaseService>,
-        rpc: Arc<RpcClient>,
-        beacon: Arc<BeaconClient>,
-        token_service: Arc<TokenService>,
-        config: AppConfig,
-    ) -> Self {
-        let tx_processor = TransactionProcessor::with_token_service(
-            db.clone(),
-            rpc.clone(),
-            config.clone(),
-            token_service,
-        );
-        let block_processor = BlockProcessor::new(
-            db.clone(),
-            rpc.clone(),
-            beacon.clone(),
-            tx_processor.clone(),
-        );
+    /// Fill in Beacon Chain data of indexed blocks
+    #[cfg(feature = "beacon")]
+    pub fn with_beacon(mut self, beacon: Arc<BeaconClient>) -> Self {
+        self.block_processor.set_beacon(beacon);
+        self
+    }
 
-        Self {
-            db,
-            rpc,
-            config,
-            is_running: Arc::new(AtomicBool::new(false)),
-            block_processor,
-            next_block_to_fetch: Arc::new(AtomicI64::new(0)),
-            latest_network_block: Arc::new(AtomicI64::new(0)),
-        }
+    /// Discover tokens and track balances of indexed token transfers
+    #[cfg(feature = "token-service")]
+    pub fn with_token_service(mut self, token_service: Arc<TokenService>) -> Self {
+        self.block_processor.set_token_service(token_service);
+        self
+    }
+
+    /// Preload the account cache from the database before workers start
+    pub async fn warm_caches(&self) -> Result<()> {
+        let accounts = self.block_processor.warm_caches().await?;
+        info!("Account cache warmed with {} accounts", accounts);
+        Ok(())
     }
 
     /// Start the indexer service with continuous block fetching
@@ -107,11 +151,24 @@ impl IndexerService {
                 // Initialize starting block
                 self.initialize_start_block().await?;
 
+                let tracked = self.watchlist.load(&self.db, &self.config).await?;
+                if tracked > 0 {
+                    info!(
+                        "Watchlist mode: indexing only {} tracked addresses",
+                        tracked
+                    );
+                }
+                self.events.record(
+                    NewIndexerEvent::info(IndexerEventKind::IndexerStarted, "Indexer started")
+                        .at_block(self.next_block_to_fetch.load(Ordering::Relaxed)),
+                );
+
                 // Create block queue channel
                 let queue_size =
                     self.config.worker_pool_size * self.config.block_queue_size_multiplier;
                 let (block_sender, block_receiver) = mpsc::channel::<i64>(queue_size);
                 let receiver = Arc::new(tokio::sync::Mutex::new(block_receiver));
+                *self.block_queue.lock().unwrap() = Some(block_sender.downgrade());
 
                 // Start the block fetcher task (independent loop)
                 let fetcher_handle = self.start_block_fetcher(block_sender.clone());
@@ -119,6 +176,12 @@ impl IndexerService {
                 // Start worker tasks for processing blocks
                 let worker_handles = self.start_worker_pool(receiver).await;
 
+                // Watchlist mode stores only some transactions of each block
+                if !self.watchlist.is_enabled() {
+                    self.consistency
+                        .start(block_sender.clone(), self.is_running.clone());
+                }
+
                 // Wait for either fetcher or workers to complete (they shouldn't unless error)
                 tokio::select! {
                     result = fetcher_handle => {
@@ -139,6 +202,10 @@ impl IndexerService {
                 warn!("Failed to connect to RPC endpoint");
                 self.is_running.store(false, Ordering::Relaxed);
                 warn!("Indexer stopped due to RPC connection failure");
+                self.events.record(NewIndexerEvent::error(
+                    IndexerEventKind::IndexerStopped,
+                    "Indexer stopped due to RPC connection failure",
+                ));
             }
         }
 
@@ -183,6 +250,7 @@ impl IndexerService {
         let is_running = self.is_running.clone();
         let next_block_to_fetch = self.next_block_to_fetch.clone();
         let latest_network_block = self.latest_network_block.clone();
+        let is_paused = self.is_paused.clone();
         let poll_interval =
             Duration::from_secs(self.config.block_fetch_interval_seconds.unwrap_or(3) as u64);
 
@@ -193,6 +261,11 @@ impl IndexerService {
             );
 
             while is_running.load(Ordering::Relaxed) {
+                if is_paused.load(Ordering::Relaxed) {
+                    time::sleep(poll_interval).await;
+                    continue;
+                }
+
                 match Self::fetch_and_queue_blocks(
                     &rpc,
                     &block_sender,
@@ -286,81 +359,302 @@ impl IndexerService {
         &self,
         receiver: Arc<tokio::sync::Mutex<mpsc::Receiver<i64>>>,
     ) -> Vec<tokio::task::JoinHandle<()>> {
-        let worker_count = self.config.worker_pool_size;
-        let mut worker_handles = Vec::new();
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(
-            self.config.max_concurrent_blocks,
-        ));
+        let worker_count = self.worker_pool_size.load(Ordering::Relaxed);
+        let pool = WorkerPool {
+            receiver,
+            semaphore: Arc::new(tokio::sync::Semaphore::new(
+                self.config.max_concurrent_blocks,
+            )),
+        };
 
         info!("Starting {} workers for block processing", worker_count);
 
-        for worker_id in 0..worker_count {
-            let receiver_clone = receiver.clone();
-            let block_processor = self.block_processor.clone();
-            let semaphore_clone = semaphore.clone();
-            let is_running = self.is_running.clone();
-            let worker_timeout_seconds = self.config.worker_timeout_seconds;
-
-            let worker_handle = tokio::spawn(async move {
-                info!("Worker {} started and ready for blocks", worker_id);
-
-                while is_running.load(Ordering::Relaxed) {
-                    // Get next block from queue
-                    let block_number = {
-                        let mut rx = receiver_clone.lock().await;
-                        match time::timeout(Duration::from_secs(worker_timeout_seconds), rx.recv())
-                            .await
-                        {
-                            Ok(Some(block)) => block,
-                            Ok(None) => {
-                                info!(
-                                    "Worker {} received shutdown signal (channel closed)",
-                                    worker_id
-                                );
-                                break;
-                            }
-                            Err(_) => {
-                                // Timeout - no blocks available, continue waiting
-                                debug!("Worker {} timeout waiting for blocks", worker_id);
-                                continue;
-                            }
-                        }
-                    };
+        let mut stats = self.worker_stats.lock().unwrap();
+        stats.clear();
+        let worker_handles = (0..worker_count)
+            .map(|worker_id| {
+                stats.push(WorkerStats::new(worker_id));
+                self.spawn_worker(worker_id, &pool)
+            })
+            .collect();
+        *self.worker_pool.lock().unwrap() = Some(pool);
 
-                    // Acquire processing permit
-                    let permit = match semaphore_clone.acquire().await {
-                        Ok(permit) => permit,
-                        Err(_) => {
-                            error!(
-                                "Worker {} failed to acquire semaphore permit for block #{}",
-                                worker_id, block_number
+        worker_handles
+    }
+
+    /// Spawn a worker taking blocks from the queue until stopped or retired
+    fn spawn_worker(&self, worker_id: usize, pool: &WorkerPool) -> tokio::task::JoinHandle<()> {
+        let receiver_clone = pool.receiver.clone();
+        let semaphore_clone = pool.semaphore.clone();
+        let block_processor = self.block_processor.clone();
+        let is_running = self.is_running.clone();
+        let is_paused = self.is_paused.clone();
+        let worker_pool_size = self.worker_pool_size.clone();
+        let worker_stats = self.worker_stats.clone();
+        let worker_timeout_seconds = self.config.worker_timeout_seconds;
+        let events = self.events.clone();
+        let last_block_indexed_at = self.last_block_indexed_at.clone();
+
+        tokio::spawn(async move {
+            info!("Worker {} started and ready for blocks", worker_id);
+
+            while is_running.load(Ordering::Relaxed) {
+                {
+                    // Checked under the stats lock so a resize sees whether this worker is gone
+                    let mut stats = worker_stats.lock().unwrap();
+                    if worker_id >= worker_pool_size.load(Ordering::Relaxed) {
+                        stats[worker_id].active = false;
+                        info!("Worker {} retired by pool resize", worker_id);
+                        break;
+                    }
+                }
+
+                if is_paused.load(Ordering::Relaxed) {
+                    time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+
+                // Get next block from queue
+                let block_number = {
+                    let mut rx = receiver_clone.lock().await;
+                    match time::timeout(Duration::from_secs(worker_timeout_seconds), rx.recv())
+                        .await
+                    {
+                        Ok(Some(block)) => block,
+                        Ok(None) => {
+                            info!(
+                                "Worker {} received shutdown signal (channel closed)",
+                                worker_id
                             );
+                            break;
+                        }
+                        Err(_) => {
+                            // Timeout - no blocks available, continue waiting
+                            debug!("Worker {} timeout waiting for blocks", worker_id);
                             continue;
                         }
-                    };
+                    }
+                };
+
+                // Acquire processing permit
+                let permit = match semaphore_clone.acquire().await {
+                    Ok(permit) => permit,
+                    Err(_) => {
+                        error!(
+                            "Worker {} failed to acquire semaphore permit for block #{}",
+                            worker_id, block_number
+                        );
+                        continue;
+                    }
+                };
 
-                    info!("Worker {} processing block #{}", worker_id, block_number);
+                info!("Worker {} processing block #{}", worker_id, block_number);
+                worker_stats.lock().unwrap()[worker_id].current_block = Some(block_number);
+                let mut backoff = OVERLOAD_BACKOFF_MIN;
+                let result = loop {
                     match block_processor.process_block(block_number as u64).await {
-                        Ok(_) => {
-                            info!("Worker {} completed block #{}", worker_id, block_number);
-                        }
-                        Err(e) => {
-                            error!(
-                                "Worker {} failed to process block #{}: {}",
-                                worker_id, block_number, e
+                        // A full RPC queue is not the block's fault, retry it once it drains
+                        Err(e) if RpcOverloaded::is(&e) && is_running.load(Ordering::Relaxed) => {
+                            warn!(
+                                "Worker {} backing off {:?} on block #{}: {}",
+                                worker_id, backoff, block_number, e
                             );
+                            time::sleep(backoff).await;
+                            backoff = (backoff * 2).min(OVERLOAD_BACKOFF_MAX);
                         }
+                        result => break result,
+                    }
+                };
+                let now = chrono::Utc::now().timestamp();
+                {
+                    let mut stats = worker_stats.lock().unwrap();
+                    let stats = &mut stats[worker_id];
+                    stats.current_block = None;
+                    stats.last_block = Some(block_number);
+                    stats.last_block_at = Some(now);
+                    if result.is_ok() {
+                        stats.blocks_processed += 1;
+                    } else {
+                        stats.blocks_failed += 1;
                     }
-                    drop(permit); // Release permit for next block
                 }
+                match result {
+                    Ok(_) => {
+                        info!("Worker {} completed block #{}", worker_id, block_number);
+                        last_block_indexed_at.store(now, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        error!(
+                            "Worker {} failed to process block #{}: {}",
+                            worker_id, block_number, e
+                        );
+                        events.record(
+                            NewIndexerEvent::error(
+                                IndexerEventKind::BlockFailed,
+                                format!("Failed to process block #{}", block_number),
+                            )
+                            .at_block(block_number)
+                            .with_details(serde_json::json!({ "error": format!("{:#}", e) })),
+                        );
+                    }
+                }
+                drop(permit); // Release permit for next block
+            }
 
-                info!("Worker {} shutting down", worker_id);
-            });
+            info!("Worker {} shutting down", worker_id);
+        })
+    }
 
-            worker_handles.push(worker_handle);
+    /// Change the number of workers, extra workers retire after finishing their block
+    pub fn set_worker_pool_size(&self, size: usize) -> Result<()> {
+        if size == 0 {
+            return Err(anyhow!("worker_pool_size must be at least 1"));
         }
 
-        worker_handles
+        let mut stats = self.worker_stats.lock().unwrap();
+        let previous = self.worker_pool_size.swap(size, Ordering::Relaxed);
+
+        // Before the indexer started the new size simply applies at startup
+        if let Some(pool) = self.worker_pool.lock().unwrap().as_ref() {
+            for worker_id in 0..size {
+                if worker_id == stats.len() {
+                    stats.push(WorkerStats::new(worker_id));
+                } else if stats[worker_id].active {
+                    continue;
+                } else {
+                    stats[worker_id].active = true;
+                }
+                self.spawn_worker(worker_id, pool);
+            }
+        }
+
+        if size != previous {
+            info!("Worker pool resized from {} to {}", previous, size);
+            self.events.record(
+                NewIndexerEvent::info(
+                    IndexerEventKind::IndexerReconfigured,
+                    format!("Worker pool resized from {} to {}", previous, size),
+                )
+                .with_details(serde_json::json!({ "worker_pool_size": size })),
+            );
+        }
+        Ok(())
+    }
+
+    /// Stop fetching and processing blocks until resumed, blocks in progress complete
+    pub fn pause(&self) {
+        if !self.is_paused.swap(true, Ordering::Relaxed) {
+            info!("Indexer paused");
+            self.events.record(NewIndexerEvent::info(
+                IndexerEventKind::IndexerPaused,
+                "Indexer paused",
+            ));
+        }
+    }
+
+    /// Resume fetching and processing blocks after a pause
+    pub fn resume(&self) {
+        if self.is_paused.swap(false, Ordering::Relaxed) {
+            info!("Indexer resumed");
+            self.events.record(NewIndexerEvent::info(
+                IndexerEventKind::IndexerResumed,
+                "Indexer resumed",
+            ));
+        }
+    }
+
+    /// Queue an already fetched block to be processed again
+    pub fn requeue_block(&self, block_number: i64) -> Result<()> {
+        let sender = self
+            .block_queue
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(mpsc::WeakSender::upgrade)
+            .ok_or_else(|| anyhow!("Indexer is not running"))?;
+
+        sender.try_send(block_number).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => anyhow!("Block queue is full"),
+            mpsc::error::TrySendError::Closed(_) => anyhow!("Indexer is not running"),
+        })?;
+
+        info!("Re-queued block #{} for reprocessing", block_number);
+        self.events.record(
+            NewIndexerEvent::info(
+                IndexerEventKind::BlockRequeued,
+                format!("Re-queued block #{} for reprocessing", block_number),
+            )
+            .at_block(block_number),
+        );
+        Ok(())
+    }
+
+    /// Delete and re-fetch every block of a range, with its receipts, logs and token
+    /// transfers, using up to `worker_pool_size` blocks at a time. Previous rows are kept in
+    /// the history tables; blocks that fail are reported instead of aborting the range.
+    pub async fn reindex_range(&self, from_block: i64, to_block: i64) -> Result<ReindexSummary> {
+        if from_block < 0 || to_block < from_block {
+            return Err(anyhow!("Invalid block range {}..={}", from_block, to_block));
+        }
+
+        info!("Reindexing blocks {}..={}", from_block, to_block);
+        let concurrency = self.worker_pool_size.load(Ordering::Relaxed).max(1);
+        let mut failed: Vec<i64> = stream::iter(from_block..=to_block)
+            .map(|block_number| async move {
+                match self
+                    .block_processor
+                    .reindex_block(block_number as u64)
+                    .await
+                {
+                    Ok(()) => None,
+                    Err(e) => {
+                        error!("Failed to reindex block #{}: {:#}", block_number, e);
+                        Some(block_number)
+                    }
+                }
+            })
+            .buffer_unordered(concurrency)
+            .filter_map(|failed| async move { failed })
+            .collect()
+            .await;
+        failed.sort_unstable();
+
+        let summary = ReindexSummary {
+            from_block,
+            to_block,
+            reindexed: (to_block - from_block + 1) - failed.len() as i64,
+            failed,
+        };
+        info!(
+            "Reindexed {} blocks of {}..={}, {} failed",
+            summary.reindexed,
+            from_block,
+            to_block,
+            summary.failed.len()
+        );
+        let event = if summary.failed.is_empty() {
+            NewIndexerEvent::info(
+                IndexerEventKind::RangeReindexed,
+                format!("Reindexed blocks #{}..=#{}", from_block, to_block),
+            )
+        } else {
+            NewIndexerEvent::warning(
+                IndexerEventKind::RangeReindexed,
+                format!(
+                    "Reindexed blocks #{}..=#{}, {} failed",
+                    from_block,
+                    to_block,
+                    summary.failed.len()
+                ),
+            )
+        };
+        self.events.record(
+            event
+                .at_block(from_block)
+                .with_details(serde_json::json!(summary)),
+        );
+
+        Ok(summary)
     }
 
     /// Start the indexer service
@@ -373,6 +667,10 @@ impl IndexerService {
         if self.is_running.load(Ordering::Relaxed) {
             info!("Stopping indexer service");
             self.is_running.store(false, Ordering::Relaxed);
+            self.events.record(NewIndexerEvent::info(
+                IndexerEventKind::IndexerStopped,
+                "Indexer stopped",
+            ));
         } else {
             warn!("Indexer is not running");
         }
@@ -383,19 +681,130 @@ impl IndexerService {
         self.is_running.load(Ordering::Relaxed)
     }
 
+    /// Results of the block consistency checker
+    pub fn consistency_report(&self) -> ConsistencyReport {
+        self.consistency.report()
+    }
+
     /// Get indexing status for monitoring
     pub fn get_status(&self) -> IndexerStatus {
         IndexerStatus {
             is_running: self.is_running.load(Ordering::Relaxed),
+            is_paused: self.is_paused.load(Ordering::Relaxed),
             next_block_to_fetch: self.next_block_to_fetch.load(Ordering::Relaxed),
             latest_network_block: self.latest_network_block.load(Ordering::Relaxed),
+            queue_depth: self
+                .block_queue
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(mpsc::WeakSender::upgrade)
+                .map_or(0, |sender| sender.max_capacity() - sender.capacity()),
+            last_block_indexed_at: match self.last_block_indexed_at.load(Ordering::Relaxed) {
+                0 => None,
+                timestamp => Some(timestamp),
+            },
+            worker_pool_size: self.worker_pool_size.load(Ordering::Relaxed),
+            workers: self.worker_stats.lock().unwrap().clone(),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct IndexerStatus {
     pub is_running: bool,
+    pub is_paused: bool,
     pub next_block_to_fetch: i64,
     pub latest_network_block: i64,
+    pub queue_depth: usize,                 // Blocks queued for the workers
+    pub last_block_indexed_at: Option<i64>, // Unix time of the last processed block
+    pub worker_pool_size: usize,
+    pub workers: Vec<WorkerStats>,
+}
+
+/// Options of the `reindex` command
+#[derive(Debug, Clone)]
+pub struct ReindexOptions {
+    pub from_block: i64,
+    pub to_block: i64,
+    pub network: Option<String>, // Default network when unset
+}
+
+impl ReindexOptions {
+    /// Build options from command line arguments (`--from`, `--to`, `--network`)
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut from_block = None;
+        let mut to_block = None;
+        let mut network = None;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("Missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--from" => {
+                    from_block = Some(value()?.parse().map_err(|_| anyhow!("Invalid --from"))?)
+                }
+                "--to" => to_block = Some(value()?.parse().map_err(|_| anyhow!("Invalid --to"))?),
+                "--network" => network = Some(value()?.to_lowercase()),
+                other => return Err(anyhow!("Unknown reindex argument: {}", other)),
+            }
+        }
+
+        let (from_block, to_block) = from_block
+            .zip(to_block)
+            .ok_or_else(|| anyhow!("Usage: reindex --from N --to M [--network NAME]"))?;
+        if from_block < 0 || to_block < from_block {
+            return Err(anyhow!("Invalid block range {}..={}", from_block, to_block));
+        }
+
+        Ok(Self {
+            from_block,
+            to_block,
+            network,
+        })
+    }
+}
+
+/// Outcome of a range reindex
+#[derive(Debug, Clone, Serialize)]
+pub struct ReindexSummary {
+    pub from_block: i64,
+    pub to_block: i64,
+    pub reindexed: i64,
+    pub failed: Vec<i64>, // Blocks that could not be fetched or stored
+}
+
+/// Queue and processing permits shared by the workers of a running indexer
+struct WorkerPool {
+    receiver: Arc<tokio::sync::Mutex<mpsc::Receiver<i64>>>,
+    semaphore: Arc<tokio::sync::Semaphore>,
+}
+
+/// Blocks handled by a single worker
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerStats {
+    pub id: usize,
+    pub active: bool, // False once retired by a pool resize
+    pub current_block: Option<i64>,
+    pub blocks_processed: u64,
+    pub blocks_failed: u64,
+    pub last_block: Option<i64>,
+    pub last_block_at: Option<i64>, // Unix time the last block finished
+}
+
+impl WorkerStats {
+    fn new(id: usize) -> Self {
+        Self {
+            id,
+            active: true,
+            current_block: None,
+            blocks_processed: 0,
+            blocks_failed: 0,
+            last_block: None,
+            last_block_at: None,
+        }
+    }
 }
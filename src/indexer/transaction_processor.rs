@@ -1,14 +1,28 @@
+#[cfg(feature = "token-service")]
+use crate::token_service::TokenService;
 use crate::{
     config::AppConfig,
-    database::{Account, DatabaseService, Log, TokenTransfer, Transaction},
+    database::{
+        Account, ContractCode, ContractDeployment, DatabaseService, Log, Receipt, TokenTransfer,
+        Transaction,
+    },
     rpc::RpcClient,
-    token_service::TokenService,
 };
 use anyhow::{Context, Result};
-use ethers::core::types::{Log as EthLog, Transaction as EthTransaction, TransactionReceipt};
-use std::{collections::HashMap, sync::Arc};
+use ethers::core::types::{
+    CallFrame, Log as EthLog, NameOrAddress, Transaction as EthTransaction, TransactionReceipt,
+    H160,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use tokio::sync::RwLock;
-use tracing::{debug, error, warn};
+use tracing::debug;
+#[cfg(feature = "token-service")]
+use tracing::{error, warn};
+
+use super::{methods::method_name, watchlist::Watchlist, TRANSFER_EVENT_TOPIC};
 
 /// Processor for handling transaction data
 #[derive(Clone)]
@@ -16,8 +30,11 @@ pub struct TransactionProcessor {
     db: Arc<DatabaseService>,
     rpc: Arc<RpcClient>,
     config: AppConfig,
+    #[cfg(feature = "token-service")]
     token_service: Option<Arc<TokenService>>,
     account_cache: Arc<RwLock<HashMap<String, Option<Account>>>>,
+    code_cache: Arc<RwLock<HashMap<String, bool>>>, // Whether an address has code, by address
+    watchlist: Watchlist, // Addresses indexing is restricted to, empty for the full chain
 }
 
 impl TransactionProcessor {
@@ -27,25 +44,59 @@ impl TransactionProcessor {
             db,
             rpc,
             config,
+            #[cfg(feature = "token-service")]
             token_service: None,
             account_cache: Arc::new(RwLock::new(HashMap::new())),
+            code_cache: Arc::new(RwLock::new(HashMap::new())),
+            watchlist: Watchlist::default(),
         }
     }
 
-    /// Create a new transaction processor with token service
-    pub fn with_token_service(
-        db: Arc<DatabaseService>,
-        rpc: Arc<RpcClient>,
-        config: AppConfig,
-        token_service: Arc<TokenService>,
-    ) -> Self {
-        Self {
-            db,
-            rpc,
-            config,
-            token_service: Some(token_service),
-            account_cache: Arc::new(RwLock::new(HashMap::new())),
+    /// Hand token transfers to the given token service
+    #[cfg(feature = "token-service")]
+    pub fn set_token_service(&mut self, token_service: Arc<TokenService>) {
+        self.token_service = Some(token_service);
+    }
+
+    /// Preload the most recently seen accounts, which are the likeliest to be seen again
+    pub async fn warm_account_cache(&self) -> Result<usize> {
+        if self.config.cache_warm_accounts == 0 {
+            return Ok(0);
+        }
+
+        let accounts = self
+            .db
+            .get_recently_seen_accounts(self.config.cache_warm_accounts as i64)
+            .await?;
+        let count = accounts.len();
+        let mut cache = self.account_cache.write().await;
+        for account in accounts {
+            // Accounts cached by blocks already processed are newer
+            cache
+                .entry(account.address.clone())
+                .or_insert(Some(account));
+        }
+        Ok(count)
+    }
+
+    /// Watchlist shared with the indexer service
+    pub fn watchlist(&self) -> Watchlist {
+        self.watchlist.clone()
+    }
+
+    /// Keep only the transactions involving tracked addresses when running in watchlist mode
+    pub fn filter_tracked(
+        &self,
+        transactions_with_receipts: Vec<(EthTransaction, TransactionReceipt)>,
+    ) -> Vec<(EthTransaction, TransactionReceipt)> {
+        if !self.watchlist.is_enabled() {
+            return transactions_with_receipts;
         }
+
+        transactions_with_receipts
+            .into_iter()
+            .filter(|(tx, receipt)| self.watchlist.involves(tx, receipt))
+            .collect()
     }
 
     /// Get transaction receipts in batch for better performance
@@ -80,6 +131,43 @@ impl TransactionProcessor {
         Ok(results)
     }
 
+    /// Get the receipts of a block's transactions, in the order of `tx_hashes`.
+    /// Uses a single eth_getBlockReceipts request when the node supports it and
+    /// falls back to per-transaction requests otherwise.
+    pub async fn get_block_receipts(
+        &self,
+        block_number: u64,
+        tx_hashes: &[String],
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        match self.rpc.get_block_receipts(block_number).await {
+            Ok(Some(receipts)) if receipts.len() == tx_hashes.len() => {
+                let mut by_hash: HashMap<String, TransactionReceipt> = receipts
+                    .into_iter()
+                    .map(|receipt| (format!("{:?}", receipt.transaction_hash), receipt))
+                    .collect();
+
+                return Ok(tx_hashes.iter().map(|hash| by_hash.remove(hash)).collect());
+            }
+            Ok(Some(receipts)) => {
+                debug!(
+                    "eth_getBlockReceipts returned {} receipts for {} transactions in block {}, fetching individually",
+                    receipts.len(),
+                    tx_hashes.len(),
+                    block_number
+                );
+            }
+            Ok(None) => {}
+            Err(e) => {
+                debug!(
+                    "eth_getBlockReceipts failed for block {}: {:#}, fetching individually",
+                    block_number, e
+                );
+            }
+        }
+
+        self.get_transaction_receipts_batch(tx_hashes).await
+    }
+
     /// Collect data for multiple transactions efficiently (block-level batch processing)
     pub async fn collect_block_transaction_data(
         &self,
@@ -88,7 +176,9 @@ impl TransactionProcessor {
         let mut all_transactions = Vec::new();
         let mut all_logs = Vec::new();
         let mut all_token_transfers = Vec::new();
-        let mut unique_addresses = std::collections::HashSet::new();
+        // Transactions of the block involving each address, a self-transfer counting once
+        let mut transaction_counts: HashMap<String, i64> = HashMap::new();
+        let mut senders = HashSet::new();
 
         // First pass: collect all data without account processing
         for (eth_tx, receipt) in transactions_with_receipts {
@@ -102,28 +192,34 @@ impl TransactionProcessor {
                 // Check if it's a token transfer
                 if eth_log.topics.len() >= 3
                     && format!("0x{}", hex::encode(eth_log.topics[0].as_bytes()))
-                        == "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+                        == TRANSFER_EVENT_TOPIC
                 {
-                    if let Ok(transfer) = self.process_erc20_transfer(&tx, eth_log).await {
+                    if let Ok(transfer) = self.process_token_transfer(&tx, eth_log).await {
                         all_token_transfers.push(transfer);
                     }
                 }
             }
 
-            // Collect unique addresses
+            // Count the transaction for its sender and recipient
             let from_address = format!("{:#x}", eth_tx.from);
-            unique_addresses.insert(from_address);
+            senders.insert(from_address.clone());
+            *transaction_counts.entry(from_address).or_default() += 1;
 
-            if let Some(to_addr) = eth_tx.to {
+            if let Some(to_addr) = eth_tx.to.filter(|to| *to != eth_tx.from) {
                 let to_address = format!("{:#x}", to_addr);
-                unique_addresses.insert(to_address);
+                *transaction_counts.entry(to_address).or_default() += 1;
             }
 
             all_transactions.push(tx);
         }
 
-        // Second pass: batch process accounts for unique addresses only
-        let unique_addresses: Vec<String> = unique_addresses.into_iter().collect();
+        // Second pass: batch process accounts for unique addresses only,
+        // restricted to tracked accounts in watchlist mode
+        let unique_addresses: Vec<String> = transaction_counts
+            .keys()
+            .filter(|address| !self.watchlist.is_enabled() || self.watchlist.contains(address))
+            .cloned()
+            .collect();
 
         // Use the first transaction's block number as reference
         let block_number = if let Some((first_tx, _)) = transactions_with_receipts.first() {
@@ -137,7 +233,12 @@ impl TransactionProcessor {
 
         // Use optimized batch processing for accounts
         let all_accounts = self
-            .prepare_accounts_batch(&unique_addresses, block_number)
+            .prepare_accounts_batch(
+                &unique_addresses,
+                &senders,
+                &transaction_counts,
+                block_number,
+            )
             .await?;
         debug!(
             "Prepared {} accounts for batch insertion",
@@ -152,8 +253,9 @@ impl TransactionProcessor {
         ))
     }
 
-    /// Process ERC20 transfer from log
-    async fn process_erc20_transfer(
+    /// Process a Transfer log. ERC-20 and ERC-721 share the event signature, but ERC-721
+    /// indexes the token id as a third topic where ERC-20 puts the amount in the data.
+    async fn process_token_transfer(
         &self,
         tx: &Transaction,
         eth_log: &EthLog,
@@ -171,6 +273,26 @@ impl TransactionProcessor {
             "0x0000000000000000000000000000000000000000".to_string()
         };
 
+        let log_index = eth_log.log_index.map(|index| index.as_u64() as i64);
+
+        // ERC-721: a single token moves, identified by the indexed token id
+        if eth_log.topics.len() == 4 {
+            let token_id = ethers::types::U256::from_big_endian(eth_log.topics[3].as_bytes());
+
+            return Ok(TokenTransfer {
+                id: None,
+                transaction_hash: tx.hash.clone(),
+                token_address: format!("{:#x}", eth_log.address),
+                from_address,
+                to_address,
+                amount: "1".to_string(),
+                block_number: tx.block_number,
+                token_type: Some("ERC721".to_string()),
+                token_id: Some(token_id.to_string()),
+                log_index,
+            });
+        }
+
         // Extract amount from data
         let amount = if eth_log.data.0.len() >= 32 {
             let mut amount_bytes = [0u8; 32];
@@ -195,6 +317,7 @@ impl TransactionProcessor {
             block_number: tx.block_number,
             token_type: Some("ERC20".to_string()),
             token_id: None,
+            log_index,
         };
 
         Ok(transfer)
@@ -206,6 +329,17 @@ impl TransactionProcessor {
         eth_tx: &EthTransaction,
         receipt: &TransactionReceipt,
     ) -> Result<Transaction> {
+        let max_input = match self.config.transaction_input_max_bytes {
+            0 => eth_tx.input.len(),
+            max => max.min(eth_tx.input.len()),
+        };
+
+        // Contract creations carry init code rather than a method call
+        let method_id = eth_tx
+            .to
+            .and(eth_tx.input.get(..4))
+            .map(|selector| format!("0x{}", hex::encode(selector)));
+
         let tx = Transaction {
             hash: format!("{:#x}", eth_tx.hash),
             block_number: eth_tx
@@ -222,6 +356,14 @@ impl TransactionProcessor {
                 .context("Transaction status missing")?
                 .as_u64() as i64,
             transaction_index: receipt.transaction_index.as_u64() as i64,
+            method_name: method_id
+                .as_deref()
+                .and_then(method_name)
+                .map(str::to_string),
+            method_id,
+            gas_limit: Some(eth_tx.gas.as_u64() as i64),
+            input: Some(format!("0x{}", hex::encode(&eth_tx.input[..max_input]))),
+            input_size: Some(eth_tx.input.len() as i64),
         };
 
         Ok(tx)
@@ -265,10 +407,120 @@ impl TransactionProcessor {
         Ok(log)
     }
 
-    /// Prepare accounts for batch insertion with optimized balance fetching
+    /// Receipt fields of a block's transactions that the transactions table doesn't keep
+    pub fn collect_receipts(
+        &self,
+        transactions_with_receipts: &[(EthTransaction, TransactionReceipt)],
+        block_number: i64,
+    ) -> Vec<Receipt> {
+        transactions_with_receipts
+            .iter()
+            .map(|(eth_tx, receipt)| Receipt {
+                transaction_hash: format!("{:?}", eth_tx.hash),
+                block_number,
+                cumulative_gas_used: receipt.cumulative_gas_used.low_u64() as i64,
+                effective_gas_price: receipt.effective_gas_price.map(|price| price.to_string()),
+                contract_address: receipt
+                    .contract_address
+                    .map(|address| format!("{:?}", address)),
+                logs_bloom: format!("0x{}", hex::encode(receipt.logs_bloom.as_bytes())),
+                transaction_type: receipt.transaction_type.map(|kind| kind.as_u64() as i64),
+            })
+            .collect()
+    }
+
+    /// Collect contracts created by a block's transactions together with their runtime code.
+    /// Contracts created by other contracts are found in call traces when
+    /// `TRACE_CONTRACT_CREATIONS` is enabled and the node serves them.
+    pub async fn collect_contract_deployments(
+        &self,
+        transactions_with_receipts: &[(EthTransaction, TransactionReceipt)],
+        block_number: i64,
+    ) -> Result<(Vec<ContractDeployment>, Vec<ContractCode>)> {
+        let successful: Vec<&EthTransaction> = transactions_with_receipts
+            .iter()
+            .filter(|(_, receipt)| receipt.status.map(|s| s.as_u64()) == Some(1))
+            .map(|(tx, _)| tx)
+            .collect();
+
+        // (transaction, factory, created contract), factories being None for transactions
+        let mut creations: Vec<(&EthTransaction, Option<H160>, H160)> = Vec::new();
+        for (eth_tx, receipt) in transactions_with_receipts {
+            if let Some(contract_address) = receipt.contract_address
+                && receipt.status.map(|s| s.as_u64()) == Some(1)
+            {
+                creations.push((eth_tx, None, contract_address));
+            }
+        }
+        if self.config.trace_contract_creations
+            && !successful.is_empty()
+            && let Some(traces) = self.rpc.trace_block_calls(block_number as u64).await?
+        {
+            for eth_tx in successful {
+                let Some(frame) = eth_tx
+                    .transaction_index
+                    .and_then(|index| traces.get(index.as_usize()))
+                else {
+                    continue;
+                };
+                let mut created = Vec::new();
+                internal_creations(frame, &mut created);
+                creations.extend(
+                    created
+                        .into_iter()
+                        .map(|(factory, address)| (eth_tx, Some(factory), address)),
+                );
+            }
+        }
+
+        let mut deployments = Vec::new();
+        let mut codes: HashMap<String, ContractCode> = HashMap::new();
+        for (eth_tx, factory, contract_address) in creations {
+            let address = format!("{:?}", contract_address);
+            let code = match self.rpc.get_code(&address, Some(block_number as u64)).await {
+                Ok(code) => code,
+                // Pruned nodes may not serve historical state, fall back to the latest code
+                Err(_) => self.rpc.get_code(&address, None).await?,
+            };
+            let bytecode = hex::decode(code.trim_start_matches("0x"))
+                .context("Invalid bytecode returned by RPC")?;
+            if bytecode.is_empty() {
+                debug!("Contract {} has no code, skipping", address);
+                continue;
+            }
+
+            let code_hash = format!("0x{}", hex::encode(ethers::utils::keccak256(&bytecode)));
+            codes.entry(code_hash.clone()).or_insert(ContractCode {
+                code_hash: code_hash.clone(),
+                bytecode: code,
+                code_size: bytecode.len() as i64,
+                first_seen_block: block_number,
+                created_at: None,
+            });
+
+            deployments.push(ContractDeployment {
+                address,
+                code_hash,
+                creator_address: format!("{:?}", eth_tx.from),
+                transaction_hash: format!("{:?}", eth_tx.hash),
+                block_number,
+                factory_address: factory.map(|factory| format!("{:?}", factory)),
+                created_at: None,
+            });
+        }
+
+        Ok((deployments, codes.into_values().collect()))
+    }
+
+    /// Prepare accounts for batch insertion with optimized balance fetching. Accounts
+    /// without a type get one detected, `senders` being known to be EOAs. Their
+    /// `transaction_count` is the number of the block's transactions involving them from
+    /// `transaction_counts`, added to the stored count on insert.
     pub async fn prepare_accounts_batch(
         &self,
         addresses: &[String],
+        senders: &HashSet<String>,
+        transaction_counts: &HashMap<String, i64>,
         block_number: i64,
     ) -> Result<Vec<Account>> {
         if addresses.is_empty() {
@@ -276,64 +528,51 @@ impl TransactionProcessor {
         }
 
         let mut all_accounts = Vec::new();
-        let batch_size = self.config.rpc_batch_size;
+        // Multicall resolves a whole chunk in one request, so it can take far larger chunks
+        let batch_size = if self.rpc.multicall_enabled() {
+            self.config.multicall_batch_size
+        } else {
+            self.config.rpc_batch_size
+        }
+        .max(1);
 
         for chunk in addresses.chunks(batch_size) {
             let mut batch_accounts = Vec::new();
 
-            // Create semaphore to limit concurrent balance fetches
-            let semaphore = Arc::new(tokio::sync::Semaphore::new(
-                self.config.max_concurrent_balance_fetches,
-            ));
+            let balance_results = self.fetch_balances(chunk, block_number).await?;
+
+            // Process each account with its balance, the stored account only providing
+            // its type as the insert merges the rest
+            for (address, balance, balance_at_block) in balance_results {
+                let account_type = self
+                    .get_account_cached(&address)
+                    .await?
+                    .and_then(|existing| existing.account_type);
+
+                batch_accounts.push(Account {
+                    transaction_count: transaction_counts.get(&address).copied().unwrap_or(0),
+                    address,
+                    balance,
+                    first_seen_block: block_number,
+                    last_seen_block: block_number,
+                    balance_at_block,
+                    account_type,
+                });
+            }
 
-            // Fetch balances concurrently within the batch
-            let balance_tasks: Vec<_> = chunk
+            // Types are detected once, accounts keep the type they were stored with
+            let untyped: Vec<String> = batch_accounts
                 .iter()
-                .map(|address| {
-                    let rpc = self.rpc.clone();
-                    let address = address.clone();
-                    let semaphore = semaphore.clone();
-
-                    async move {
-                        let _permit = semaphore.acquire().await?;
-                        let balance =
-                            match rpc.get_balance(&address, Some(block_number as u64)).await {
-                                Ok(bal) => bal.to_string(),
-                                Err(e) => {
-                                    debug!("Failed to get balance for {}: {}, using 0", address, e);
-                                    "0".to_string()
-                                }
-                            };
-                        Ok::<(String, String), anyhow::Error>((address, balance))
-                    }
-                })
+                .filter(|account| account.account_type.is_none())
+                .map(|account| account.address.clone())
                 .collect();
-
-            // Execute balance fetches concurrently
-            let balance_results = futures::future::try_join_all(balance_tasks).await?;
-
-            // Process each account with its balance
-            for (address, balance) in balance_results {
-                // Get existing account or create new one using cache
-                let existing_account = self.get_account_cached(&address).await?;
-
-                let account = if let Some(mut existing) = existing_account {
-                    existing.balance = balance;
-                    existing.transaction_count += 1;
-                    existing.last_seen_block = block_number;
-                    existing
-                } else {
-                    let new_account = Account {
-                        address: address.clone(),
-                        balance,
-                        transaction_count: 1,
-                        first_seen_block: block_number,
-                        last_seen_block: block_number,
-                    };
-                    new_account
-                };
-
-                batch_accounts.push(account);
+            let mut detected = self
+                .detect_account_types(&untyped, senders, block_number)
+                .await;
+            for account in &mut batch_accounts {
+                if account.account_type.is_none() {
+                    account.account_type = detected.remove(&account.address);
+                }
             }
 
             all_accounts.extend(batch_accounts);
@@ -350,6 +589,136 @@ impl TransactionProcessor {
         Ok(all_accounts)
     }
 
+    /// Fetch balances for a chunk of addresses, using a single multicall when available
+    /// and falling back to concurrent per-address requests otherwise
+    async fn fetch_balances(
+        &self,
+        addresses: &[String],
+        block_number: i64,
+    ) -> Result<Vec<(String, String, Option<i64>)>> {
+        if self.rpc.multicall_enabled() {
+            match self
+                .rpc
+                .get_balances_batch(addresses, block_number as u64)
+                .await
+            {
+                Ok((balances, at_block)) => {
+                    return Ok(addresses
+                        .iter()
+                        .cloned()
+                        .zip(balances)
+                        .map(|(address, balance)| (address, balance, at_block.map(|b| b as i64)))
+                        .collect());
+                }
+                Err(e) => {
+                    debug!(
+                        "Multicall balance fetch failed for {} addresses: {:#}, fetching individually",
+                        addresses.len(),
+                        e
+                    );
+                }
+            }
+        }
+
+        // Create semaphore to limit concurrent balance fetches
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(
+            self.config.max_concurrent_balance_fetches,
+        ));
+
+        // Fetch balances concurrently within the batch
+        let balance_tasks: Vec<_> = addresses
+            .iter()
+            .map(|address| {
+                let rpc = self.rpc.clone();
+                let address = address.clone();
+                let semaphore = semaphore.clone();
+
+                async move {
+                    let _permit = semaphore.acquire().await?;
+                    let (balance, balance_at_block) = match rpc
+                        .get_balance_at_block(&address, block_number as u64)
+                        .await
+                    {
+                        Ok((bal, at_block)) => (bal, at_block.map(|b| b as i64)),
+                        Err(e) => {
+                            debug!("Failed to get balance for {}: {}, using 0", address, e);
+                            ("0".to_string(), None)
+                        }
+                    };
+                    Ok::<(String, String, Option<i64>), anyhow::Error>((
+                        address,
+                        balance,
+                        balance_at_block,
+                    ))
+                }
+            })
+            .collect();
+
+        futures::future::try_join_all(balance_tasks).await
+    }
+
+    /// Detect whether addresses are EOAs or contracts. Senders are EOAs, other addresses
+    /// are looked up with eth_getCode once and cached; addresses whose code can't be
+    /// fetched are left out.
+    async fn detect_account_types(
+        &self,
+        addresses: &[String],
+        senders: &HashSet<String>,
+        block_number: i64,
+    ) -> HashMap<String, String> {
+        let mut types = HashMap::new();
+        let mut lookups = Vec::new();
+        {
+            let cache = self.code_cache.read().await;
+            for address in addresses {
+                if senders.contains(address) {
+                    types.insert(address.clone(), "eoa".to_string());
+                } else if let Some(has_code) = cache.get(address) {
+                    types.insert(address.clone(), account_type(*has_code).to_string());
+                } else {
+                    lookups.push(address.clone());
+                }
+            }
+        }
+        if lookups.is_empty() {
+            return types;
+        }
+
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(
+            self.config.max_concurrent_balance_fetches,
+        ));
+        let code_tasks = lookups.into_iter().map(|address| {
+            let rpc = self.rpc.clone();
+            let semaphore = semaphore.clone();
+
+            async move {
+                let _permit = semaphore.acquire().await.ok()?;
+                let code = match rpc.get_code(&address, Some(block_number as u64)).await {
+                    Ok(code) => code,
+                    // Pruned nodes may not serve historical state, fall back to the latest code
+                    Err(_) => match rpc.get_code(&address, None).await {
+                        Ok(code) => code,
+                        Err(e) => {
+                            debug!("Failed to get code of {}: {}", address, e);
+                            return None;
+                        }
+                    },
+                };
+                // EIP-7702 delegation designators leave the account an EOA
+                let code = code.trim_start_matches("0x");
+                Some((address, !code.is_empty() && !code.starts_with("ef0100")))
+            }
+        });
+        let fetched = futures::future::join_all(code_tasks).await;
+
+        let mut cache = self.code_cache.write().await;
+        for (address, has_code) in fetched.into_iter().flatten() {
+            cache.insert(address.clone(), has_code);
+            types.insert(address, account_type(has_code).to_string());
+        }
+        types
+    }
+
     /// Get account with caching to reduce database queries
     async fn get_account_cached(&self, address: &str) -> Result<Option<Account>> {
         // Check cache first
@@ -373,6 +742,7 @@ impl TransactionProcessor {
     }
 
     /// Process token transfers in a block and update balances
+    #[cfg(feature = "token-service")]
     pub async fn process_token_transfers_with_balances(
         &self,
         transfers: &[TokenTransfer],
@@ -389,27 +759,38 @@ impl TransactionProcessor {
         }
 
         let token_service = self.token_service.as_ref().unwrap();
-        let mut token_updates = Vec::new();
-
-        for transfer in transfers.iter() {
-            // Discover token if not seen before
-            if let Err(e) = token_service
-                .discover_token(&transfer.token_address, block_number)
-                .await
-            {
-                debug!("Failed to discover token {}: {}", transfer.token_address, e);
-            } else {
-                debug!("Token discovery completed for {}", transfer.token_address);
-            }
 
-            // Collect accounts that need balance updates
-            token_updates.push((
-                transfer.token_address.clone(),
-                transfer.from_address.clone(),
-                transfer.to_address.clone(),
-            ));
+        // Discover tokens not seen before, in one batch per block
+        let mut seen_tokens = std::collections::HashSet::new();
+        let tokens: Vec<(String, String)> = transfers
+            .iter()
+            .filter(|transfer| seen_tokens.insert(transfer.token_address.clone()))
+            .map(|transfer| {
+                (
+                    transfer.token_address.clone(),
+                    transfer
+                        .token_type
+                        .clone()
+                        .unwrap_or_else(|| "ERC20".to_string()),
+                )
+            })
+            .collect();
+        if let Err(e) = token_service.discover_tokens(&tokens, block_number).await {
+            debug!("Failed to discover tokens of block {}: {}", block_number, e);
         }
 
+        // Collect accounts that need balance updates
+        let token_updates: Vec<(String, String, String)> = transfers
+            .iter()
+            .map(|transfer| {
+                (
+                    transfer.token_address.clone(),
+                    transfer.from_address.clone(),
+                    transfer.to_address.clone(),
+                )
+            })
+            .collect();
+
         debug!(
             "Collected {} token balance updates for block {}",
             token_updates.len(),
@@ -433,3 +814,29 @@ impl TransactionProcessor {
         Ok(())
     }
 }
+
+/// Account type stored for an address with or without code
+fn account_type(has_code: bool) -> &'static str {
+    if has_code {
+        "contract"
+    } else {
+        "eoa"
+    }
+}
+
+/// (factory, contract) pairs of the successful CREATE and CREATE2 frames below `frame`.
+/// Frames that reverted are skipped with everything they called, as their creations
+/// were undone.
+fn internal_creations(frame: &CallFrame, created: &mut Vec<(H160, H160)>) {
+    for call in frame.calls.iter().flatten() {
+        if call.error.is_some() {
+            continue;
+        }
+        if matches!(call.typ.as_str(), "CREATE" | "CREATE2")
+            && let Some(NameOrAddress::Address(address)) = call.to
+        {
+            created.push((call.from, address));
+        }
+        internal_creations(call, created);
+    }
+}
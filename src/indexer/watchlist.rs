@@ -0,0 +1,78 @@
+use crate::{config::AppConfig, database::DatabaseService};
+use anyhow::Result;
+use ethers::core::types::{Transaction as EthTransaction, TransactionReceipt, H160, H256};
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+
+/// Addresses the indexer is restricted to.
+///
+/// When empty the full chain is indexed. Otherwise only transactions involving a tracked
+/// address (as sender, recipient, created contract, log emitter or indexed log topic) are
+/// persisted, and only tracked accounts get balance updates.
+#[derive(Clone, Default)]
+pub struct Watchlist {
+    addresses: Arc<RwLock<HashSet<H160>>>,
+}
+
+impl Watchlist {
+    /// Load tracked addresses from TRACKED_ADDRESSES and the tracked_addresses table
+    pub async fn load(&self, db: &DatabaseService, config: &AppConfig) -> Result<usize> {
+        let mut addresses = HashSet::new();
+
+        for address in config
+            .tracked_addresses
+            .iter()
+            .chain(db.get_tracked_addresses().await?.iter())
+        {
+            addresses.insert(
+                address
+                    .parse::<H160>()
+                    .map_err(|e| anyhow::anyhow!("Invalid tracked address {}: {}", address, e))?,
+            );
+        }
+
+        let count = addresses.len();
+        *self.addresses.write().unwrap() = addresses;
+        Ok(count)
+    }
+
+    /// Whether indexing is restricted to tracked addresses
+    pub fn is_enabled(&self) -> bool {
+        !self.addresses.read().unwrap().is_empty()
+    }
+
+    /// Whether an address (as formatted by the indexer) is tracked
+    pub fn contains(&self, address: &str) -> bool {
+        address
+            .parse::<H160>()
+            .is_ok_and(|address| self.addresses.read().unwrap().contains(&address))
+    }
+
+    /// Whether a transaction involves any tracked address
+    pub fn involves(&self, tx: &EthTransaction, receipt: &TransactionReceipt) -> bool {
+        let addresses = self.addresses.read().unwrap();
+
+        addresses.contains(&tx.from)
+            || tx.to.is_some_and(|to| addresses.contains(&to))
+            || receipt
+                .contract_address
+                .is_some_and(|created| addresses.contains(&created))
+            || receipt.logs.iter().any(|log| {
+                addresses.contains(&log.address)
+                    || log.topics.iter().skip(1).any(|topic| {
+                        topic_address(topic).is_some_and(|address| addresses.contains(&address))
+                    })
+            })
+    }
+}
+
+/// Address held by an indexed log topic, if the topic is a left-padded address
+fn topic_address(topic: &H256) -> Option<H160> {
+    let bytes = topic.as_bytes();
+    bytes[..12]
+        .iter()
+        .all(|b| *b == 0)
+        .then(|| H160::from_slice(&bytes[12..]))
+}
@@ -1,28 +1,88 @@
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+compile_error!("Enable the native-tls or rustls feature, HTTPS endpoints need a TLS backend");
+
+pub mod analytics; // Aggregate statistics job
 pub mod api;
+#[cfg(feature = "beacon")]
 pub mod beacon;
+#[cfg(feature = "chaos")]
+pub mod chaos; // Failure injection for resilience tests
 pub mod config;
+pub mod contracts; // Contract verification service
 pub mod database;
+pub mod ens; // ENS name resolution service
+pub mod events; // Operational event log
 pub mod executor; // Generic RPC executor
+pub mod export; // Cold-storage export to CSV and Parquet files
+#[cfg(feature = "beacon")]
+pub mod finality; // Beacon finality checkpoint tracking
 pub mod health_cache; // Health cache service
+pub mod healthcheck; // Deployment smoke test
 pub mod historical; // Add historical module
+pub mod images; // Token logo and NFT image proxy
 pub mod indexer;
+pub mod live; // Live data dispatcher
+pub mod liveness; // Block production liveness monitor
+pub mod log_filter; // Runtime-reloadable log directives
+pub mod market_data; // ETH price and market cap for the explorer header
+pub mod mempool; // Pending transaction watcher
 pub mod network_stats; // Add network stats module
+pub mod networks; // Per-network service bundles
+pub mod pruning; // Retention and pruning job
+pub mod publisher; // Dataset publisher to S3 or IPFS
+pub mod recent_cache; // In-memory cache of the newest blocks
+#[cfg(feature = "beacon")]
+pub mod rewards; // Block reward breakdowns
 pub mod rpc;
+pub mod sharding; // Database shard rotation job
+pub mod storage_stats; // Daily table size sampling
+pub mod streaming; // Message broker event streaming
+pub mod token_metadata; // Token metadata resolver chain
+#[cfg(feature = "token-service")]
 pub mod token_service; // Add token service module
+pub mod token_standard; // ERC-165 and bytecode token standard detection
+#[cfg(feature = "beacon")]
+pub mod validators; // Validator registry sync
+#[cfg(feature = "web-ui")]
 pub mod web;
+pub mod webhooks; // New block webhook delivery
 
+use crate::analytics::AnalyticsService;
+use crate::contracts::ContractService;
+use crate::ens::EnsService;
+#[cfg(feature = "beacon")]
+use crate::finality::FinalityService;
 use crate::health_cache::HealthCacheService;
 use crate::historical::HistoricalTransactionService;
+use crate::images::ImageProxy;
+use crate::liveness::LivenessMonitor;
+use crate::market_data::MarketDataService;
+use crate::mempool::MempoolService;
 use crate::network_stats::NetworkStatsService;
+use crate::pruning::PruningService;
+use crate::publisher::DatasetPublisher;
+use crate::recent_cache::RecentCache;
+#[cfg(feature = "beacon")]
+use crate::rewards::RewardsService;
+use crate::sharding::ShardingService;
+use crate::storage_stats::StorageStatsService;
+#[cfg(feature = "token-service")]
 use crate::token_service::TokenService;
+#[cfg(feature = "beacon")]
+use crate::validators::ValidatorService;
+use crate::webhooks::WebhookService;
 use anyhow::Result;
+#[cfg(feature = "beacon")]
 use beacon::BeaconClient;
 use config::AppConfig;
 use database::DatabaseService;
+use events::EventLog;
 use indexer::IndexerService;
+use live::{LiveDispatcher, LiveLimits};
 use rpc::RpcClient;
+use streaming::BrokerStream;
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Represents the core application with all its services
 #[derive(Clone)]
@@ -30,45 +90,131 @@ pub struct App {
     pub config: AppConfig,
     pub db: Arc<DatabaseService>,
     pub rpc: Arc<RpcClient>,
+    #[cfg(feature = "beacon")]
     pub beacon: Arc<BeaconClient>,
     pub indexer: Arc<IndexerService>,
     pub historical: Arc<HistoricalTransactionService>,
     pub network_stats: Arc<NetworkStatsService>,
+    pub market_data: Arc<MarketDataService>,
+    #[cfg(feature = "token-service")]
     pub token_service: Arc<TokenService>,
     pub health_cache: Arc<HealthCacheService>,
+    pub contracts: Arc<ContractService>,
+    pub events: EventLog,
+    pub ens: Arc<EnsService>,
+    pub mempool: Arc<MempoolService>,
+    pub live: LiveDispatcher,
+    pub recent: Arc<RecentCache>,
+    pub liveness: Arc<LivenessMonitor>,
+    pub images: Arc<ImageProxy>,
+    #[cfg(feature = "beacon")]
+    pub rewards: Arc<RewardsService>,
 }
 
 impl App {
     /// Initialize a new application instance
     pub async fn init(mut config: AppConfig) -> Result<Self> {
         // Initialize database
-        let db = Arc::new(DatabaseService::new(&config.database_url).await?);
+        let db = Arc::new(
+            DatabaseService::new(&config.database_url)
+                .await?
+                .with_shards(config.database_shard_blocks)
+                .await?,
+        );
         info!("Database initialized");
 
+        // Refresh the bundled address labels
+        let seeded = db.seed_address_labels().await?;
+        if seeded > 0 {
+            info!("Seeded {} address labels", seeded);
+        }
+
+        // Initialize operational event log
+        let events = EventLog::new(db.clone());
+
         // Initialize RPC client
-        let rpc = Arc::new(RpcClient::new(&config.eth_rpc_url, config.clone())?);
-        info!("RPC client connected to {}", config.eth_rpc_url);
+        let rpc = Arc::new(RpcClient::new(
+            &config.eth_rpc_url,
+            config.clone(),
+            events.clone(),
+        )?);
+        info!(
+            "RPC client connected to {}",
+            rpc.provider_stats()
+                .iter()
+                .map(|provider| provider.url.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        // Detect whether the node can serve historical balances, and keep checking
+        rpc.detect_archive_support().await;
+        rpc.clone().start_archive_probes();
 
         // Resolve start_block using database configuration and RPC (for -1 case)
         config.resolve_start_block(&db, Some(&rpc)).await?;
 
         // Initialize Beacon client with rate limiting
-        let beacon = Arc::new(BeaconClient::new(&config.beacon_rpc_url, &config));
-        info!("Beacon client connected to {}", config.beacon_rpc_url);
+        #[cfg(feature = "beacon")]
+        let beacon = Arc::new(BeaconClient::new(
+            &config.beacon_rpc_url,
+            &config,
+            db.clone(),
+            events.clone(),
+        )?);
+        #[cfg(feature = "beacon")]
+        info!(
+            "Beacon client connected to {}",
+            beacon.node_labels().join(", ")
+        );
 
         // Initialize token service
+        #[cfg(feature = "token-service")]
         let token_service = Arc::new(TokenService::new(db.clone(), rpc.clone(), config.clone()));
+        #[cfg(feature = "token-service")]
+        if let Err(e) = token_service.warm().await {
+            warn!("Failed to warm token cache: {:#}", e);
+        }
+        #[cfg(feature = "token-service")]
         info!("Token service initialized");
 
-        // Initialize indexer service with token service
-        let indexer = Arc::new(IndexerService::with_token_service(
+        // Initialize live data dispatcher, streaming to the message broker when configured
+        let live = match &config.stream_broker_url {
+            Some(url) => LiveDispatcher::with_broker(
+                BrokerStream::connect(url, &config.stream_topic_prefix).await?,
+            ),
+            None => LiveDispatcher::new(),
+        };
+        let live = live.with_limits(LiveLimits {
+            max_subscribers: config.live_max_subscribers,
+            buffer_size: config.live_buffer_size,
+            slow_consumer_timeout: std::time::Duration::from_secs(
+                config.live_slow_consumer_seconds,
+            ),
+        });
+
+        // Initialize recent data cache, fed by the live dispatcher
+        let recent = Arc::new(RecentCache::new(config.recent_cache_blocks));
+        recent.warm(&db).await?;
+        let live = live.with_recent_cache(recent.clone());
+
+        // Initialize indexer service with the optional beacon and token services
+        let indexer = IndexerService::new(
             db.clone(),
             rpc.clone(),
-            beacon.clone(),
-            token_service.clone(),
             config.clone(),
-        ));
-        info!("Indexer service initialized with token support");
+            events.clone(),
+            live.clone(),
+        );
+        #[cfg(feature = "beacon")]
+        let indexer = indexer.with_beacon(beacon.clone());
+        #[cfg(feature = "token-service")]
+        let indexer = indexer.with_token_service(token_service.clone());
+        let indexer = Arc::new(indexer);
+        if let Err(e) = indexer.warm_caches().await {
+            warn!("Failed to warm indexer caches: {:#}", e);
+        }
+        info!("Indexer service initialized");
 
         // Initialize historical transaction service
         let historical = Arc::new(HistoricalTransactionService::new(
@@ -85,29 +231,180 @@ impl App {
         info!("Historical transaction service initialized");
 
         // Initialize network stats service
-        let network_stats = Arc::new(NetworkStatsService::new(Arc::clone(&rpc)));
+        let network_stats = Arc::new(NetworkStatsService::new(Arc::clone(&rpc), db.clone()));
+        network_stats.warm().await;
 
         // Start background updates for network stats
         network_stats.clone().start_background_updates().await;
         info!("Network stats service initialized");
 
+        // Initialize market data, only polled when providers are configured
+        let market_data = Arc::new(MarketDataService::new(config.clone(), db.clone())?);
+        if !config.market_data_providers.is_empty() {
+            market_data.clone().start_background_updates().await;
+            info!("Market data service initialized");
+        }
+
         // Initialize health cache service
-        let health_cache = Arc::new(HealthCacheService::new(Arc::clone(&rpc)));
+        let health_cache = HealthCacheService::new(Arc::clone(&rpc), db.clone());
+        #[cfg(feature = "beacon")]
+        let health_cache = health_cache.with_beacon(beacon.clone());
+        let health_cache = Arc::new(health_cache);
 
         // Start background updates for health cache
         health_cache.clone().start_background_updates().await;
         info!("Health cache service initialized");
 
+        // Initialize contract verification service
+        let contracts = Arc::new(ContractService::new(
+            db.clone(),
+            rpc.clone(),
+            config.clone(),
+        ));
+        info!("Contract verification service initialized");
+
+        // Initialize ENS resolution service
+        let ens = Arc::new(EnsService::new(db.clone(), rpc.clone(), config.clone()));
+
+        // Initialize mempool watcher
+        let mempool = Arc::new(MempoolService::new(db.clone(), rpc.clone(), config.clone()));
+        if config.mempool_enabled {
+            mempool.clone().start_background_updates().await;
+            info!("Mempool watcher initialized");
+        }
+
+        // Initialize block rewards service
+        #[cfg(feature = "beacon")]
+        let rewards = Arc::new(RewardsService::new(
+            db.clone(),
+            rpc.clone(),
+            beacon.clone(),
+            config.clone(),
+        ));
+        #[cfg(feature = "beacon")]
+        if config.rewards_interval_seconds > 0 {
+            rewards.clone().start_background_updates().await;
+            info!("Block rewards job initialized");
+        }
+
+        // Initialize aggregate statistics job
+        if config.analytics_interval_seconds > 0 {
+            let analytics = AnalyticsService::new(db.clone(), config.clone());
+            #[cfg(feature = "beacon")]
+            let analytics = analytics.with_rewards(rewards.clone());
+            let analytics = Arc::new(analytics);
+            analytics.start_background_updates().await;
+            info!("Analytics job initialized");
+        }
+
+        // Initialize retention and pruning job
+        if config.prune_interval_seconds > 0 {
+            let pruning = Arc::new(PruningService::new(
+                db.clone(),
+                events.clone(),
+                config.clone(),
+            ));
+            pruning.start_background_updates().await;
+            info!("Pruning job initialized");
+        }
+
+        // Initialize storage statistics job
+        if config.storage_stats_interval_seconds > 0 {
+            let storage_stats = Arc::new(StorageStatsService::new(db.clone(), config.clone()));
+            storage_stats.start_background_updates().await;
+            info!("Storage statistics job initialized");
+        }
+
+        // Initialize database shard rotation job
+        if config.database_shard_blocks > 0 && config.database_shard_interval_seconds > 0 {
+            let sharding = Arc::new(ShardingService::new(
+                db.clone(),
+                events.clone(),
+                config.clone(),
+            ));
+            sharding.start_background_updates().await;
+            info!("Shard rotation job initialized");
+        }
+
+        // Initialize dataset publisher
+        if config.publish_interval_seconds > 0
+            && let Some(publisher) =
+                DatasetPublisher::new(db.clone(), events.clone(), config.clone())?
+        {
+            Arc::new(publisher).start_background_updates().await;
+            info!("Dataset publisher initialized");
+        }
+
+        // Initialize block webhook delivery
+        if config.webhook_interval_seconds > 0 {
+            let webhooks = Arc::new(WebhookService::new(
+                db.clone(),
+                events.clone(),
+                config.clone(),
+            )?);
+            webhooks.start_background_updates().await;
+            info!("Block webhook delivery initialized");
+        }
+
+        // Initialize liveness monitor
+        let liveness = Arc::new(LivenessMonitor::new(
+            db.clone(),
+            network_stats.clone(),
+            events.clone(),
+            config.clone(),
+        ));
+        if config.liveness_check_interval_seconds > 0 {
+            liveness.clone().start_background_checks().await;
+            info!("Liveness monitor initialized");
+        }
+
+        // Initialize image proxy
+        let images = Arc::new(ImageProxy::new(db.clone(), rpc.clone(), config.clone())?);
+
+        // Initialize validator registry sync
+        #[cfg(feature = "beacon")]
+        if config.validator_sync_interval_seconds > 0 {
+            let validators =
+                Arc::new(ValidatorService::new(db.clone(), beacon.clone(), config.clone()));
+            validators.start_background_updates().await;
+            info!("Validator registry sync initialized");
+        }
+
+        // Initialize finality checkpoint tracking, rollups have no beacon chain
+        #[cfg(feature = "beacon")]
+        if config.finality_poll_interval_seconds > 0 && !config.network_profile.is_l2() {
+            let finality = Arc::new(FinalityService::new(
+                db.clone(),
+                beacon.clone(),
+                config.clone(),
+            ));
+            finality.start_background_updates().await;
+            info!("Finality tracking initialized");
+        }
+
         Ok(Self {
             config,
             db,
             rpc,
+            #[cfg(feature = "beacon")]
             beacon,
             indexer,
             historical,
             network_stats,
+            market_data,
+            #[cfg(feature = "token-service")]
             token_service,
             health_cache,
+            contracts,
+            events,
+            ens,
+            mempool,
+            live,
+            recent,
+            liveness,
+            images,
+            #[cfg(feature = "beacon")]
+            rewards,
         })
     }
 
@@ -121,6 +418,39 @@ impl App {
             }
         });
 
+        // Fix ERC-721 transfers stored as ERC-20 by earlier versions
+        #[cfg(feature = "token-service")]
+        let token_service = self.token_service.clone();
+        #[cfg(feature = "token-service")]
+        tokio::spawn(async move {
+            match token_service.repair_nft_transfers().await {
+                Ok(0) => {}
+                Ok(repaired) => info!("Reclassified {} ERC-721 token transfers", repaired),
+                Err(e) => error!("Token transfer repair failed: {}", e),
+            }
+        });
+
+        // Keep stored token balances from going stale
+        #[cfg(feature = "token-service")]
+        if self.config.token_balance_refresh_interval_seconds > 0 {
+            let token_service = self.token_service.clone();
+            tokio::spawn(token_service.start_background_refresh(
+                std::time::Duration::from_secs(self.config.token_balance_refresh_interval_seconds),
+                self.config.token_balance_refresh_max_age_blocks as i64,
+            ));
+        }
+
+        // Keep token supplies, holder counts and recent activity current
+        #[cfg(feature = "token-service")]
+        if self.config.token_stats_interval_seconds > 0 {
+            let token_service = self.token_service.clone();
+            tokio::spawn(
+                token_service.start_stats_updates(std::time::Duration::from_secs(
+                    self.config.token_stats_interval_seconds,
+                )),
+            );
+        }
+
         info!("Application started successfully");
         Ok(())
     }
@@ -0,0 +1,287 @@
+use crate::{
+    database::{Block, Log, TokenTransfer, Transaction},
+    recent_cache::RecentCache,
+    streaming::BrokerStream,
+};
+use futures::stream::{self, Stream};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Item delivered to a live log subscriber
+#[derive(Debug, Clone)]
+pub enum LiveLog {
+    Log(Arc<Log>),
+    Lagged(u64), // Subscriber fell behind and this many logs were skipped
+    Evicted,     // Last item: the subscriber stayed behind too long and was disconnected
+}
+
+/// Bounds keeping the memory live subscribers hold in the API process fixed
+#[derive(Debug, Clone, Copy)]
+pub struct LiveLimits {
+    pub max_subscribers: usize, // Concurrent subscribers, further ones are refused (0 = unlimited)
+    pub buffer_size: usize,     // Items buffered per subscriber before they are dropped
+    pub slow_consumer_timeout: Duration, // Time a buffer may stay full before its subscriber is evicted
+}
+
+impl Default for LiveLimits {
+    fn default() -> Self {
+        Self {
+            max_subscribers: 100,
+            buffer_size: 1024,
+            slow_consumer_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Live subscribers and what happened to the items sent to them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LiveStats {
+    pub subscribers: usize,
+    pub max_subscribers: usize,
+    pub buffer_size: usize,
+    pub delivered: u64, // Items queued to a subscriber
+    pub dropped: u64,   // Items dropped because the buffer of their subscriber was full
+    pub rejected: u64,  // Subscriptions refused at `max_subscribers`
+    pub evicted: u64,   // Subscribers disconnected as slow consumers
+}
+
+#[derive(Debug, Default)]
+struct LiveMetrics {
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    rejected: AtomicU64,
+    evicted: AtomicU64,
+}
+
+/// Send side of a live log subscription
+#[derive(Debug)]
+struct LogSubscriber {
+    filter: LogFilter,
+    sender: mpsc::Sender<LiveLog>,
+    skipped: u64, // Logs dropped since the last delivery, reported as `Lagged`
+    full_since: Option<Instant>, // First drop since the last delivery
+    evicted: Arc<AtomicBool>, // Tells the receiving stream to end with `Evicted`
+}
+
+impl LogSubscriber {
+    /// Queue a log without waiting, dropping it when the buffer is full. A pending
+    /// `Lagged` notice goes first, so both need room.
+    fn offer(&mut self, log: &Arc<Log>, metrics: &LiveMetrics) {
+        let needed = if self.skipped > 0 { 2 } else { 1 };
+        if self.sender.capacity() < needed {
+            self.skipped += 1;
+            self.full_since.get_or_insert_with(Instant::now);
+            metrics.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        // Only the dispatcher sends, under its lock, so the room checked above stays free
+        if self.skipped > 0 {
+            let _ = self.sender.try_send(LiveLog::Lagged(self.skipped));
+            self.skipped = 0;
+        }
+        let _ = self.sender.try_send(LiveLog::Log(log.clone()));
+        self.full_since = None;
+        metrics.delivered.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Filter selecting the logs a subscriber receives
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub addresses: HashSet<String>, // Emitting contracts (lowercase), empty matches any
+    pub topic0: Option<String>,     // Event signature hash
+}
+
+impl LogFilter {
+    pub fn matches(&self, log: &Log) -> bool {
+        (self.addresses.is_empty() || self.addresses.contains(&log.address))
+            && self
+                .topic0
+                .as_ref()
+                .is_none_or(|topic0| log.topic0.as_ref() == Some(topic0))
+    }
+}
+
+/// Fans newly indexed data out to live subscribers such as SSE endpoints, to the recent
+/// data cache, and to the message broker when event streaming is configured.
+///
+/// Publishing never blocks the indexer: each subscriber gets a bounded buffer, items
+/// that don't fit are dropped and the subscriber is told how many it missed. Subscribers
+/// whose buffer stays full past the slow consumer timeout are disconnected.
+#[derive(Clone, Debug)]
+pub struct LiveDispatcher {
+    logs: Arc<Mutex<Vec<LogSubscriber>>>,
+    limits: LiveLimits,
+    metrics: Arc<LiveMetrics>,
+    broker: Option<BrokerStream>,
+    recent: Option<Arc<RecentCache>>,
+}
+
+impl Default for LiveDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveDispatcher {
+    /// Create a dispatcher without subscribers
+    pub fn new() -> Self {
+        Self {
+            logs: Arc::default(),
+            limits: LiveLimits::default(),
+            metrics: Arc::default(),
+            broker: None,
+            recent: None,
+        }
+    }
+
+    /// Create a dispatcher that also streams indexed data to a message broker
+    pub fn with_broker(broker: BrokerStream) -> Self {
+        Self {
+            broker: Some(broker),
+            ..Self::new()
+        }
+    }
+
+    /// Also keep newly indexed data in the recent data cache
+    pub fn with_recent_cache(mut self, recent: Arc<RecentCache>) -> Self {
+        self.recent = Some(recent);
+        self
+    }
+
+    /// Bound the subscribers and their buffers
+    pub fn with_limits(mut self, limits: LiveLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Current subscribers and delivery counters
+    pub fn stats(&self) -> LiveStats {
+        let subscribers = self.logs.lock().unwrap();
+        LiveStats {
+            subscribers: subscribers.iter().filter(|s| !s.sender.is_closed()).count(),
+            max_subscribers: self.limits.max_subscribers,
+            buffer_size: self.limits.buffer_size,
+            delivered: self.metrics.delivered.load(Ordering::Relaxed),
+            dropped: self.metrics.dropped.load(Ordering::Relaxed),
+            rejected: self.metrics.rejected.load(Ordering::Relaxed),
+            evicted: self.metrics.evicted.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Publish a newly indexed block
+    pub fn publish_block(&self, block: &Block) {
+        if let Some(recent) = &self.recent {
+            recent.insert_block(block);
+        }
+        if let Some(broker) = &self.broker {
+            broker.publish("blocks", block.number.to_string(), block);
+        }
+    }
+
+    /// Publish transactions of a newly indexed block
+    pub fn publish_transactions(&self, transactions: &[Transaction]) {
+        if let Some(recent) = &self.recent {
+            recent.insert_transactions(transactions);
+        }
+        if let Some(broker) = &self.broker {
+            for transaction in transactions {
+                broker.publish("transactions", transaction.hash.clone(), transaction);
+            }
+        }
+    }
+
+    /// Publish token transfers of a newly indexed block
+    pub fn publish_token_transfers(&self, transfers: &[TokenTransfer]) {
+        if let Some(recent) = &self.recent {
+            recent.insert_token_transfers(transfers);
+        }
+        if let Some(broker) = &self.broker {
+            for transfer in transfers {
+                broker.publish(
+                    "token_transfers",
+                    transfer.transaction_hash.clone(),
+                    transfer,
+                );
+            }
+        }
+    }
+
+    /// Publish logs of a newly indexed block
+    pub fn publish_logs(&self, logs: &[Log]) {
+        let mut subscribers = self.logs.lock().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+
+        let logs: Vec<Arc<Log>> = logs.iter().cloned().map(Arc::new).collect();
+        subscribers.retain_mut(|subscriber| {
+            if subscriber.sender.is_closed() {
+                return false;
+            }
+            for log in &logs {
+                if subscriber.filter.matches(log) {
+                    subscriber.offer(log, &self.metrics);
+                }
+            }
+
+            let stuck = subscriber
+                .full_since
+                .is_some_and(|since| since.elapsed() >= self.limits.slow_consumer_timeout);
+            if stuck {
+                warn!(
+                    "Disconnecting live subscriber that skipped {} logs",
+                    subscriber.skipped
+                );
+                subscriber.evicted.store(true, Ordering::Relaxed);
+                self.metrics.evicted.fetch_add(1, Ordering::Relaxed);
+            }
+            !stuck
+        });
+    }
+
+    /// Subscribe to logs matching a filter, None when `max_subscribers` are connected
+    pub fn subscribe_logs(&self, filter: LogFilter) -> Option<impl Stream<Item = LiveLog> + use<>> {
+        let mut subscribers = self.logs.lock().unwrap();
+        subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+        if self.limits.max_subscribers > 0 && subscribers.len() >= self.limits.max_subscribers {
+            self.metrics.rejected.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        // Room for a `Lagged` notice and a log
+        let (sender, receiver) = mpsc::channel(self.limits.buffer_size.max(2));
+        let evicted = Arc::new(AtomicBool::new(false));
+        subscribers.push(LogSubscriber {
+            filter,
+            sender,
+            skipped: 0,
+            full_since: None,
+            evicted: evicted.clone(),
+        });
+
+        // The buffer is drained before an evicted subscriber is told and its stream ends
+        Some(stream::unfold(
+            (receiver, evicted),
+            |(mut receiver, evicted)| async move {
+                match receiver.recv().await {
+                    Some(item) => Some((item, (receiver, evicted))),
+                    None if evicted.swap(false, Ordering::Relaxed) => {
+                        Some((LiveLog::Evicted, (receiver, evicted)))
+                    }
+                    None => None,
+                }
+            },
+        ))
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    config::AppConfig,
+    database::DatabaseService,
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+    network_stats::NetworkStatsService,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::time::{self, Duration};
+use tracing::{info, warn};
+
+/// Recent blocks the background check looks at
+const CHECK_WINDOW_BLOCKS: i64 = 100;
+
+/// Slot delay of an indexed block
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockSlotDelay {
+    pub number: i64,
+    pub slot: i64,
+    pub slot_delay: i64, // Slots missed since the previous block
+}
+
+/// Consecutive slots without a block
+#[derive(Debug, Clone, Serialize)]
+pub struct MissedSlotStreak {
+    pub first_slot: i64,
+    pub length: i64,
+    pub next_block: i64, // Block that ended the streak
+}
+
+/// Head of the upstream node as seen by the monitor
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeHead {
+    pub block_number: u64,
+    pub seconds_since_advanced: u64,
+}
+
+/// Liveness condition currently met
+#[derive(Debug, Clone, Serialize)]
+pub struct LivenessAlert {
+    pub condition: &'static str, // "missed_slots" or "node_stalled"
+    pub message: String,
+}
+
+/// Block production liveness over the newest indexed blocks
+#[derive(Debug, Clone, Serialize)]
+pub struct LivenessReport {
+    pub status: &'static str, // "healthy", "degraded" or "stalled"
+    pub alerts: Vec<LivenessAlert>,
+    pub blocks_checked: usize,
+    pub slots_spanned: i64,
+    pub missed_slots: i64,
+    pub missed_slot_percentage: f64,
+    pub longest_streak: i64,
+    pub seconds_since_last_block: Option<i64>,
+    pub node_head: Option<NodeHead>,
+    pub streaks: Vec<MissedSlotStreak>,
+    pub blocks: Vec<BlockSlotDelay>,
+}
+
+/// Watches block production through the slots of indexed blocks and the head of the
+/// upstream node.
+///
+/// A block landing later than the slot after its parent means the slots in between were
+/// missed. Long streaks of missed slots, or a node head that stops advancing, fire an
+/// `alert_fired` event once per episode.
+pub struct LivenessMonitor {
+    db: Arc<DatabaseService>,
+    network_stats: Arc<NetworkStatsService>,
+    events: EventLog,
+    config: AppConfig,
+    node_head: Mutex<Option<(u64, Instant)>>, // Latest node head and when it was first seen
+    active_alerts: Mutex<Vec<&'static str>>,
+}
+
+impl LivenessMonitor {
+    /// Create a new liveness monitor
+    pub fn new(
+        db: Arc<DatabaseService>,
+        network_stats: Arc<NetworkStatsService>,
+        events: EventLog,
+        config: AppConfig,
+    ) -> Self {
+        Self {
+            db,
+            network_stats,
+            events,
+            config,
+            node_head: Mutex::new(None),
+            active_alerts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Start checking liveness in the background
+    pub async fn start_background_checks(self: Arc<Self>) {
+        let monitor = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Liveness monitor starting");
+            let mut interval = time::interval(Duration::from_secs(
+                monitor.config.liveness_check_interval_seconds,
+            ));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = monitor.check().await {
+                    warn!("Liveness check failed: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Liveness over the newest `window` indexed blocks
+    pub async fn report(&self, window: i64) -> Result<LivenessReport> {
+        let node_head = self.observe_node_head().await;
+        let blocks = self.db.get_recent_block_slots(window).await?;
+
+        let mut delays = Vec::new();
+        let mut streaks = Vec::new();
+        for pair in blocks.windows(2) {
+            let ((parent, parent_slot, _), (number, slot, _)) = (pair[0], pair[1]);
+            // Gaps in the indexed range or blocks without a slot say nothing about slots
+            let (Some(parent_slot), Some(slot)) = (parent_slot, slot) else {
+                continue;
+            };
+            if number != parent + 1 || slot <= parent_slot {
+                continue;
+            }
+
+            let slot_delay = slot - parent_slot - 1;
+            if slot_delay > 0 {
+                streaks.push(MissedSlotStreak {
+                    first_slot: parent_slot + 1,
+                    length: slot_delay,
+                    next_block: number,
+                });
+            }
+            delays.push(BlockSlotDelay {
+                number,
+                slot,
+                slot_delay,
+            });
+        }
+
+        let missed_slots: i64 = streaks.iter().map(|streak| streak.length).sum();
+        let slots_spanned = missed_slots + delays.len() as i64;
+        let longest_streak = streaks
+            .iter()
+            .map(|streak| streak.length)
+            .max()
+            .unwrap_or(0);
+        let seconds_since_last_block = blocks
+            .last()
+            .map(|(_, _, timestamp)| chrono::Utc::now().timestamp() - timestamp);
+
+        let mut alerts = Vec::new();
+        let mut status = "healthy";
+        if longest_streak >= self.config.liveness_max_missed_slots {
+            status = "degraded";
+            alerts.push(LivenessAlert {
+                condition: "missed_slots",
+                message: format!(
+                    "{} consecutive missed slots in the last {} blocks",
+                    longest_streak,
+                    blocks.len()
+                ),
+            });
+        }
+        if let Some(head) = &node_head
+            && head.seconds_since_advanced >= self.config.liveness_stall_seconds
+        {
+            status = "stalled";
+            alerts.push(LivenessAlert {
+                condition: "node_stalled",
+                message: format!(
+                    "Node head stuck at block {} for {}s",
+                    head.block_number, head.seconds_since_advanced
+                ),
+            });
+        }
+
+        Ok(LivenessReport {
+            status,
+            alerts,
+            blocks_checked: blocks.len(),
+            slots_spanned,
+            missed_slots,
+            missed_slot_percentage: if slots_spanned > 0 {
+                missed_slots as f64 / slots_spanned as f64 * 100.0
+            } else {
+                0.0
+            },
+            longest_streak,
+            seconds_since_last_block,
+            node_head,
+            streaks,
+            blocks: delays,
+        })
+    }
+
+    /// Record an alert event for each condition that started since the previous check
+    async fn check(&self) -> Result<()> {
+        let report = self.report(CHECK_WINDOW_BLOCKS).await?;
+
+        let mut active_alerts = self.active_alerts.lock().unwrap();
+        for alert in &report.alerts {
+            if !active_alerts.contains(&alert.condition) {
+                active_alerts.push(alert.condition);
+                self.events.record(
+                    NewIndexerEvent::warning(
+                        IndexerEventKind::AlertFired,
+                        format!("Liveness alert: {}", alert.message),
+                    )
+                    .with_details(serde_json::json!({
+                        "condition": alert.condition,
+                        "longest_streak": report.longest_streak,
+                        "missed_slot_percentage": report.missed_slot_percentage,
+                        "node_head": report.node_head
+                    })),
+                );
+            }
+        }
+        active_alerts.retain(|condition| {
+            let firing = report
+                .alerts
+                .iter()
+                .any(|alert| alert.condition == *condition);
+            if !firing {
+                info!("Liveness condition {} cleared", condition);
+            }
+            firing
+        });
+
+        Ok(())
+    }
+
+    /// Record the node head, tracking how long it has not advanced
+    async fn observe_node_head(&self) -> Option<NodeHead> {
+        let latest = self.network_stats.get_latest_network_block().await;
+        let mut node_head = self.node_head.lock().unwrap();
+
+        if let Some(latest) = latest
+            && node_head.is_none_or(|(head, _)| latest > head)
+        {
+            *node_head = Some((latest, Instant::now()));
+        }
+
+        node_head.map(|(block_number, seen_at)| NodeHead {
+            block_number,
+            seconds_since_advanced: seen_at.elapsed().as_secs(),
+        })
+    }
+}
@@ -0,0 +1,151 @@
+//! Runtime log filtering.
+//!
+//! `LOG_LEVEL` takes tracing directives such as `info,indexer=debug,rpc=warn`, where the
+//! modules of this crate can be named without the crate prefix. The filter is installed
+//! behind a reload layer, so the admin API can replace the directives of a running process
+//! to debug one subsystem without a restart.
+
+use anyhow::{anyhow, Context, Result};
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+/// Target prefix of the events logged by this crate
+const CRATE_TARGET: &str = "eth_indexer_rs";
+
+/// Top-level modules of this crate, which directives may name without `CRATE_TARGET`
+const CRATE_MODULES: [&str; 38] = [
+    "analytics",
+    "api",
+    "beacon",
+    "chaos",
+    "config",
+    "contracts",
+    "database",
+    "ens",
+    "events",
+    "executor",
+    "export",
+    "finality",
+    "health_cache",
+    "healthcheck",
+    "historical",
+    "images",
+    "indexer",
+    "live",
+    "liveness",
+    "log_filter",
+    "market_data",
+    "mempool",
+    "network_stats",
+    "networks",
+    "pruning",
+    "publisher",
+    "recent_cache",
+    "rewards",
+    "rpc",
+    "sharding",
+    "storage_stats",
+    "streaming",
+    "token_metadata",
+    "token_service",
+    "token_standard",
+    "validators",
+    "web",
+    "webhooks",
+];
+
+/// Filter installed by `init`, None when the process logs through another subscriber
+static RUNTIME_FILTER: OnceLock<LogFilter> = OnceLock::new();
+
+/// Handle on the installed filter, with the directives it was built from
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    default_directives: String, // LOG_LEVEL at startup
+    directives: Mutex<String>,
+}
+
+impl LogFilter {
+    /// Directives currently applied
+    pub fn directives(&self) -> String {
+        self.directives.lock().unwrap().clone()
+    }
+
+    /// Directives the process started with
+    pub fn default_directives(&self) -> &str {
+        &self.default_directives
+    }
+
+    /// Replace the directives, rejecting them all when one is invalid
+    pub fn set(&self, directives: &str) -> Result<()> {
+        let filter = parse_directives(directives)?;
+        let mut current = self.directives.lock().unwrap();
+        self.handle
+            .reload(filter)
+            .context("Failed to reload log filter")?;
+        *current = directives.trim().to_string();
+        Ok(())
+    }
+
+    /// Go back to the directives the process started with
+    pub fn reset(&self) -> Result<()> {
+        self.set(&self.default_directives)
+    }
+}
+
+/// Install the global subscriber, logging to stdout with `directives`. Invalid directives
+/// are ignored, as tracing does for `RUST_LOG`.
+pub fn init(directives: &str) {
+    let filter = EnvFilter::builder().parse_lossy(expand_directives(directives));
+    let (filter, handle) = reload::Layer::new(filter);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let _ = RUNTIME_FILTER.set(LogFilter {
+        handle,
+        default_directives: directives.trim().to_string(),
+        directives: Mutex::new(directives.trim().to_string()),
+    });
+}
+
+/// Filter installed by `init`, if any
+pub fn runtime() -> Option<&'static LogFilter> {
+    RUNTIME_FILTER.get()
+}
+
+/// Build a filter from comma-separated directives, failing on the first invalid one
+pub fn parse_directives(directives: &str) -> Result<EnvFilter> {
+    if directives.trim().is_empty() {
+        return Err(anyhow!("No log directives given"));
+    }
+    EnvFilter::builder()
+        .parse(expand_directives(directives))
+        .map_err(|e| anyhow!("Invalid log directives {:?}: {}", directives, e))
+}
+
+/// Prefix the directive targets naming a module of this crate with the crate name, e.g.
+/// `indexer=debug` becomes `eth_indexer_rs::indexer=debug`. Bare levels and the targets of
+/// other crates are kept as they are.
+pub fn expand_directives(directives: &str) -> String {
+    directives
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| {
+            let target_end = directive.find(['[', '=']).unwrap_or(directive.len());
+            let target = &directive[..target_end];
+            let module = target.split("::").next().unwrap_or_default();
+            let is_level = target_end == directive.len() && target.parse::<LevelFilter>().is_ok();
+
+            if !is_level && CRATE_MODULES.contains(&module) {
+                format!("{}::{}", CRATE_TARGET, directive)
+            } else {
+                directive.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -1,38 +1,148 @@
+use eth_indexer_rs::api::auth::{self, NewApiKeyOptions};
 use eth_indexer_rs::config::AppConfig;
-use eth_indexer_rs::{api, App};
+use eth_indexer_rs::database::DatabaseService;
+use eth_indexer_rs::events::EventLog;
+use eth_indexer_rs::export::{self, ColdExportOptions};
+use eth_indexer_rs::healthcheck::{self, HealthcheckOptions};
+use eth_indexer_rs::indexer::{DebugBlockOptions, ReindexOptions};
+use eth_indexer_rs::rpc::RpcClient;
+use eth_indexer_rs::token_metadata::{self, TokenListImportOptions};
+use eth_indexer_rs::{api, log_filter, networks::Networks, App};
 use std::sync::Arc;
 use tracing::{error, info};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("healthcheck") {
+        // Smoke test a running deployment, usable as a container HEALTHCHECK
+        let _ = dotenvy::dotenv();
+        let options = HealthcheckOptions::from_args(&args[1..])?;
+        let healthy = healthcheck::run(&options).await;
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
+
     let app_config = AppConfig::load()?;
     info!("Application configuration loaded");
 
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            app_config.log_level.clone(),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    if args.first().map(String::as_str) == Some("create-api-key") {
+        // Issue a key for the API, it is printed once and only its hash is stored
+        let options = NewApiKeyOptions::from_args(&args[1..], &app_config)?;
+        let db = DatabaseService::new(&app_config.database_url).await?;
+        let key = auth::create_api_key(&db, &options).await?;
+        println!("API key for {}: {}", options.name, key);
+        return Ok(());
+    }
+
+    if args.first().map(String::as_str) == Some("tokens")
+        && args.get(1).map(String::as_str) == Some("import-list")
+    {
+        // Import a token list as curated tokens, for the RPC node's chain unless --chain-id is given
+        let options = TokenListImportOptions::from_args(&args[2..])?;
+        let db = Arc::new(DatabaseService::new(&app_config.database_url).await?);
+        let chain_id = match options.chain_id {
+            Some(chain_id) => chain_id,
+            None => {
+                let events = EventLog::new(db.clone());
+                RpcClient::new(&app_config.eth_rpc_url, app_config.clone(), events)?
+                    .get_chain_id()
+                    .await?
+            }
+        };
+        let import = token_metadata::import_token_list(&db, &options.location, chain_id).await?;
+        println!(
+            "Imported {} tokens of chain {} from {} ({} entries skipped)",
+            import.imported, import.chain_id, import.list_name, import.skipped
+        );
+        return Ok(());
+    }
+
+    if args.first().map(String::as_str) == Some("export") {
+        // Dump a block range to CSV or Parquet files for data pipelines
+        let options = ColdExportOptions::from_args(&args[1..])?;
+        let db = DatabaseService::new(&app_config.database_url).await?;
+        for file in export::export_block_range(&db, &options).await? {
+            println!(
+                "{}: {} rows -> {}",
+                file.table,
+                file.rows,
+                file.path.display()
+            );
+        }
+        return Ok(());
+    }
+
+    log_filter::init(&app_config.log_level);
+
+    if args.first().map(String::as_str) == Some("reindex") {
+        // Delete and re-fetch a block range of one network, without serving the API
+        let options = ReindexOptions::from_args(&args[1..])?;
+        let network = options
+            .network
+            .clone()
+            .unwrap_or_else(|| app_config.network_name.clone());
+        let config = app_config
+            .network_configs()?
+            .into_iter()
+            .find(|config| config.network_name == network)
+            .ok_or_else(|| anyhow::anyhow!("Unknown network: {}", network))?;
+        let app = App::init(config).await?;
+        let summary = app
+            .indexer
+            .reindex_range(options.from_block, options.to_block)
+            .await?;
+        println!(
+            "Reindexed {} blocks of {}..={} on {}",
+            summary.reindexed, summary.from_block, summary.to_block, network
+        );
+        if !summary.failed.is_empty() {
+            println!("Failed blocks: {:?}", summary.failed);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.first().map(String::as_str) == Some("debug-block") {
+        // Replay one block against the stored data and print the report, writing only with --apply
+        let options = DebugBlockOptions::from_args(&args[1..])?;
+        let network = options
+            .network
+            .clone()
+            .unwrap_or_else(|| app_config.network_name.clone());
+        let config = app_config
+            .network_configs()?
+            .into_iter()
+            .find(|config| config.network_name == network)
+            .ok_or_else(|| anyhow::anyhow!("Unknown network: {}", network))?;
+        let app = App::init(config).await?;
+        let report = app
+            .indexer
+            .debug_block(options.block_number, options.apply)
+            .await?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.consistent {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-    let app = match App::init(app_config).await {
-        Ok(app) => Arc::new(app),
+    let networks = match Networks::init(app_config).await {
+        Ok(networks) => Arc::new(networks),
         Err(e) => {
-            error!("Failed to initialize application: {}", e);
+            error!("Failed to initialize application: {:#}", e);
             return Err(e);
         }
     };
 
-    let app_clone = app.clone();
+    let networks_clone = networks.clone();
     let indexer_handle = tokio::spawn(async move {
-        if let Err(e) = app_clone.start().await {
-            error!("Failed to start indexer: {}", e);
+        if let Err(e) = networks_clone.start().await {
+            error!("Failed to start indexer: {:#}", e);
         }
     });
 
     let api_handle = tokio::spawn(async move {
-        if let Err(e) = api::start_server(app).await {
+        if let Err(e) = api::start_server(networks).await {
             error!("Failed to start API server: {}", e);
         }
     });
@@ -0,0 +1,353 @@
+//! ETH market data for the explorer header.
+//!
+//! Price, 24h change and market cap are polled from the providers of MARKET_DATA_PROVIDERS,
+//! the first one answering winning, and served from memory so requests never reach the
+//! providers. A provider answering 429 is skipped for as long as its Retry-After header
+//! asks, and prices are no longer served once they are older than `MAX_AGE`.
+//!
+//! Each price fetched is also recorded as the ETH price of its hour, which values token
+//! transfers at query time: curated stablecoins at one dollar, wrapped ETH at the price of
+//! the hour of its block, or the latest price when that hour has none.
+
+use crate::{
+    config::{AppConfig, MarketDataProvider},
+    database::{DatabaseService, Token, TokenTransfer},
+};
+use anyhow::{anyhow, Context, Result};
+use reqwest::{header::RETRY_AFTER, Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::time;
+use tracing::{debug, warn};
+
+/// ETH price in USD and BTC with its market cap and 24h change, from CoinGecko
+const COINGECKO_URL: &str = "https://api.coingecko.com/api/v3/simple/price?ids=ethereum&vs_currencies=usd,btc&include_market_cap=true&include_24hr_change=true";
+
+/// 24h open and last trade of the ETH-USD pair on Coinbase Exchange
+const COINBASE_URL: &str = "https://api.exchange.coinbase.com/products/ETH-USD/stats";
+
+/// Max time a provider request may take
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shortest interval between updates, whatever MARKET_DATA_INTERVAL_SECONDS asks for
+const MIN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Time a rate limited provider is skipped when it doesn't send Retry-After
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Age past which market data is no longer served
+const MAX_AGE: Duration = Duration::from_secs(900);
+
+/// Symbols of the curated tokens worth one US dollar
+const USD_PEGGED_SYMBOLS: &[&str] = &[
+    "USDC", "USDT", "DAI", "USDS", "PYUSD", "USDP", "GUSD", "TUSD", "LUSD", "FRAX",
+];
+
+/// Symbols of the curated tokens worth one ETH
+const ETH_PEGGED_SYMBOLS: &[&str] = &["WETH"];
+
+/// ETH market data at one point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketData {
+    pub price_usd: f64,
+    pub price_btc: Option<f64>,
+    pub change_24h_percentage: Option<f64>,
+    pub market_cap_usd: Option<f64>,
+    pub source: MarketDataProvider,
+    pub updated_at: i64,
+}
+
+impl MarketData {
+    /// Read a CoinGecko simple price response
+    pub fn from_coingecko(response: &Value) -> Option<Self> {
+        let ethereum = response.get("ethereum")?;
+        Some(Self {
+            price_usd: ethereum.get("usd")?.as_f64()?,
+            price_btc: ethereum.get("btc").and_then(Value::as_f64),
+            change_24h_percentage: ethereum.get("usd_24h_change").and_then(Value::as_f64),
+            market_cap_usd: ethereum
+                .get("usd_market_cap")
+                .and_then(Value::as_f64)
+                .filter(|market_cap| *market_cap > 0.0),
+            source: MarketDataProvider::Coingecko,
+            updated_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Read a Coinbase Exchange product stats response, whose prices are quoted strings
+    pub fn from_coinbase(response: &Value) -> Option<Self> {
+        let price = |field: &str| response.get(field)?.as_str()?.parse::<f64>().ok();
+        let last = price("last")?;
+        Some(Self {
+            price_usd: last,
+            price_btc: None,
+            change_24h_percentage: price("open")
+                .filter(|open| *open > 0.0)
+                .map(|open| (last - open) / open * 100.0),
+            market_cap_usd: None,
+            source: MarketDataProvider::Coinbase,
+            updated_at: chrono::Utc::now().timestamp(),
+        })
+    }
+}
+
+/// Asset a token is pegged to, which prices it without a price feed of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPeg {
+    Usd,
+    Eth,
+}
+
+impl TokenPeg {
+    /// Peg of a curated ERC-20 token, by its symbol. Discovered tokens are never pegged, as
+    /// anyone can deploy a token named USDC.
+    pub fn of(token: &Token) -> Option<Self> {
+        if !token.curated || token.token_type != "ERC20" {
+            return None;
+        }
+        let symbol = token.symbol.as_deref()?.to_uppercase();
+        if USD_PEGGED_SYMBOLS.contains(&symbol.as_str()) {
+            Some(Self::Usd)
+        } else if ETH_PEGGED_SYMBOLS.contains(&symbol.as_str()) {
+            Some(Self::Eth)
+        } else {
+            None
+        }
+    }
+}
+
+/// Price a transfer was valued with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    Peg,        // Stablecoin counted at one dollar
+    Historical, // ETH price of the hour of the transfer's block
+    Current,    // Latest ETH price, the hour of the block having none
+}
+
+/// Approximate USD value of a token transfer, None for transfers of tokens without a price
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TransferValue {
+    pub value_usd: Option<f64>,
+    pub price_source: Option<PriceSource>,
+}
+
+impl TransferValue {
+    /// Value `amount` base units of `token`, pegged tokens to ETH with the price of the hour
+    /// of the transfer when known, otherwise the current one
+    pub fn of(
+        amount: &str,
+        token: &Token,
+        historical_eth_usd: Option<f64>,
+        current_eth_usd: Option<f64>,
+    ) -> Self {
+        let (Some(peg), Some(decimals)) = (TokenPeg::of(token), token.decimals) else {
+            return Self::default();
+        };
+        let (unit_price, source) = match peg {
+            TokenPeg::Usd => (Some(1.0), PriceSource::Peg),
+            TokenPeg::Eth => match historical_eth_usd {
+                Some(price) => (Some(price), PriceSource::Historical),
+                None => (current_eth_usd, PriceSource::Current),
+            },
+        };
+        let Some(unit_price) = unit_price else {
+            return Self::default();
+        };
+
+        match amount.parse::<f64>() {
+            Ok(amount) => Self {
+                value_usd: Some(amount / 10f64.powi(decimals as i32) * unit_price),
+                price_source: Some(source),
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// Polls ETH market data in the background and keeps the latest in memory
+pub struct MarketDataService {
+    client: Client,
+    config: AppConfig,
+    db: Arc<DatabaseService>,
+    latest: RwLock<Option<(MarketData, Instant)>>,
+    rate_limited: Mutex<HashMap<MarketDataProvider, Instant>>, // Skipped until then
+}
+
+impl MarketDataService {
+    pub fn new(config: AppConfig, db: Arc<DatabaseService>) -> Result<Self> {
+        let client = Client::builder()
+            .user_agent(concat!("eth-indexer-rs/", env!("CARGO_PKG_VERSION")))
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .context("Failed to build market data HTTP client")?;
+
+        Ok(Self {
+            client,
+            config,
+            db,
+            latest: RwLock::new(None),
+            rate_limited: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Latest market data, None until a provider answered or once it is older than `MAX_AGE`
+    pub fn latest(&self) -> Option<MarketData> {
+        self.latest
+            .read()
+            .ok()?
+            .as_ref()
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < MAX_AGE)
+            .map(|(data, _)| data.clone())
+    }
+
+    /// Approximate USD value of each of `transfers`, in the same order. Transfers of
+    /// tokens that couldn't be loaded are left without a value.
+    pub async fn transfer_values(&self, transfers: &[TokenTransfer]) -> Vec<TransferValue> {
+        let mut tokens: HashMap<&str, Option<Token>> = HashMap::new();
+        for transfer in transfers {
+            if !tokens.contains_key(transfer.token_address.as_str()) {
+                let token = self
+                    .db
+                    .get_token_by_address(&transfer.token_address)
+                    .await
+                    .unwrap_or_else(|e| {
+                        debug!("Failed to get token {}: {:#}", transfer.token_address, e);
+                        None
+                    });
+                tokens.insert(&transfer.token_address, token);
+            }
+        }
+
+        // Historical prices are only needed for ETH-pegged tokens
+        let block_numbers: Vec<i64> = transfers
+            .iter()
+            .filter(|transfer| {
+                tokens[transfer.token_address.as_str()]
+                    .as_ref()
+                    .and_then(TokenPeg::of)
+                    == Some(TokenPeg::Eth)
+            })
+            .map(|transfer| transfer.block_number)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let historical = self
+            .db
+            .get_eth_prices_at_blocks(&block_numbers)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to get historical ETH prices: {:#}", e);
+                HashMap::new()
+            });
+        let current = self.latest().map(|market| market.price_usd);
+
+        transfers
+            .iter()
+            .map(|transfer| match &tokens[transfer.token_address.as_str()] {
+                Some(token) => TransferValue::of(
+                    &transfer.amount,
+                    token,
+                    historical.get(&transfer.block_number).copied(),
+                    current,
+                ),
+                None => TransferValue::default(),
+            })
+            .collect()
+    }
+
+    /// Start polling the providers every `market_data_interval_seconds`
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let interval =
+            Duration::from_secs(self.config.market_data_interval_seconds).max(MIN_INTERVAL);
+        tokio::spawn(async move {
+            let mut interval = time::interval(interval);
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.update().await {
+                    warn!("Failed to update market data: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Fetch market data from the first provider answering
+    async fn update(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        for &provider in &self.config.market_data_providers {
+            let rate_limited_until = self.rate_limited.lock().unwrap().get(&provider).copied();
+            if rate_limited_until.is_some_and(|until| Instant::now() < until) {
+                debug!("Skipping rate limited market data provider {:?}", provider);
+                continue;
+            }
+
+            match self.fetch(provider).await {
+                Ok(data) => {
+                    if let Err(e) = self
+                        .db
+                        .record_eth_price(data.updated_at, data.price_usd, provider.name())
+                        .await
+                    {
+                        warn!("Failed to record ETH price: {:#}", e);
+                    }
+                    *self.latest.write().unwrap() = Some((data, Instant::now()));
+                    return Ok(());
+                }
+                Err(e) => errors.push(format!("{:?}: {:#}", provider, e)),
+            }
+        }
+
+        if errors.is_empty() {
+            return Err(anyhow!("Every market data provider is rate limited"));
+        }
+        Err(anyhow!(errors.join("; ")))
+    }
+
+    /// Fetch market data from one provider, recording when it rate limits us
+    async fn fetch(&self, provider: MarketDataProvider) -> Result<MarketData> {
+        let request = match provider {
+            MarketDataProvider::Coingecko => {
+                let request = self.client.get(COINGECKO_URL);
+                match &self.config.coingecko_api_key {
+                    Some(key) => request.header("x-cg-demo-api-key", key),
+                    None => request,
+                }
+            }
+            MarketDataProvider::Coinbase => self.client.get(COINBASE_URL),
+        };
+        let response = request.send().await.context("Request failed")?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+                .map_or(RATE_LIMIT_BACKOFF, Duration::from_secs);
+            self.rate_limited
+                .lock()
+                .unwrap()
+                .insert(provider, Instant::now() + retry_after);
+            return Err(anyhow!(
+                "Rate limited for {} seconds",
+                retry_after.as_secs()
+            ));
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Returned status {}", response.status()));
+        }
+
+        let body: Value = response.json().await.context("Invalid response")?;
+        match provider {
+            MarketDataProvider::Coingecko => MarketData::from_coingecko(&body),
+            MarketDataProvider::Coinbase => MarketData::from_coinbase(&body),
+        }
+        .ok_or_else(|| anyhow!("Response without an ETH price"))
+    }
+}
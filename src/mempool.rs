@@ -0,0 +1,168 @@
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, PendingTransaction},
+    rpc::RpcClient,
+};
+use anyhow::Result;
+use ethers::core::types::{Transaction as EthTransaction, U256};
+use futures::future;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Percentiles of a gas price sample, in gwei
+#[derive(Debug, Serialize)]
+pub struct GasPriceDistribution {
+    pub min: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl GasPriceDistribution {
+    /// Distribution of wei amounts, None for an empty sample
+    pub fn from_wei(values: impl IntoIterator<Item = u128>) -> Option<Self> {
+        let mut gwei: Vec<f64> = values.into_iter().map(|v| v as f64 / 1e9).collect();
+        if gwei.is_empty() {
+            return None;
+        }
+        gwei.sort_by(f64::total_cmp);
+
+        let percentile = |p: f64| gwei[((gwei.len() - 1) as f64 * p).round() as usize];
+        Some(Self {
+            min: gwei[0],
+            p25: percentile(0.25),
+            median: percentile(0.5),
+            p75: percentile(0.75),
+            p90: percentile(0.9),
+            max: gwei[gwei.len() - 1],
+        })
+    }
+}
+
+/// Service watching the node's mempool and keeping the pending_transactions table current.
+///
+/// Uses txpool_content when the node exposes it and otherwise polls a pending transaction
+/// filter, fetching each new transaction by hash. Mined entries are removed by the block
+/// processor, which stores them in the transactions table.
+pub struct MempoolService {
+    db: Arc<DatabaseService>,
+    rpc: Arc<RpcClient>,
+    config: AppConfig,
+    filter_id: Mutex<Option<U256>>, // Installed pending transaction filter, if any
+}
+
+impl MempoolService {
+    /// Create a new mempool service
+    pub fn new(db: Arc<DatabaseService>, rpc: Arc<RpcClient>, config: AppConfig) -> Self {
+        Self {
+            db,
+            rpc,
+            config,
+            filter_id: Mutex::new(None),
+        }
+    }
+
+    /// Start polling the mempool in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Mempool watcher starting");
+            let mut interval = time::interval(Duration::from_millis(
+                service.config.mempool_poll_interval_ms,
+            ));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.poll().await {
+                    warn!("Mempool poll failed: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Fetch pending transactions once and store them, dropping stale entries
+    async fn poll(&self) -> Result<()> {
+        let transactions = match self.rpc.get_txpool_pending().await? {
+            Some(transactions) => transactions,
+            None => self.poll_filter().await?,
+        };
+
+        let pending: Vec<PendingTransaction> =
+            transactions.iter().map(convert_transaction).collect();
+        self.db.upsert_pending_transactions_batch(&pending).await?;
+
+        let pruned = self
+            .db
+            .prune_pending_transactions(self.config.mempool_max_age_seconds)
+            .await?;
+        debug!(
+            "Mempool poll stored {} pending transactions, pruned {}",
+            pending.len(),
+            pruned
+        );
+
+        Ok(())
+    }
+
+    /// New pending transactions from the pending transaction filter
+    async fn poll_filter(&self) -> Result<Vec<EthTransaction>> {
+        let mut filter_id = self.filter_id.lock().await;
+        let id = match *filter_id {
+            Some(id) => id,
+            None => *filter_id.insert(self.rpc.new_pending_transaction_filter().await?),
+        };
+
+        let hashes = match self.rpc.get_pending_transaction_hashes(id).await {
+            Ok(hashes) => hashes,
+            Err(e) => {
+                // Filters expire when not polled and are lost on provider failover
+                *filter_id = None;
+                return Err(e);
+            }
+        };
+        drop(filter_id);
+
+        let semaphore = Semaphore::new(self.config.max_concurrent_tx_receipts);
+        let tasks = hashes
+            .iter()
+            .take(self.config.mempool_fetch_limit)
+            .map(|hash| {
+                let semaphore = &semaphore;
+                async move {
+                    let _permit = semaphore.acquire().await?;
+                    self.rpc.get_transaction(&format!("{:?}", hash)).await
+                }
+            });
+
+        Ok(future::join_all(tasks)
+            .await
+            .into_iter()
+            .filter_map(|result| result.ok().flatten())
+            .filter(|tx| tx.block_number.is_none())
+            .collect())
+    }
+}
+
+/// Convert an RPC transaction to a pending transaction entry
+fn convert_transaction(tx: &EthTransaction) -> PendingTransaction {
+    PendingTransaction {
+        hash: format!("{:?}", tx.hash),
+        from_address: format!("{:?}", tx.from),
+        to_address: tx.to.map(|to| format!("{:?}", to)),
+        nonce: tx.nonce.low_u64() as i64,
+        value: tx.value.to_string(),
+        gas_limit: tx.gas.low_u64() as i64,
+        gas_price: tx
+            .max_fee_per_gas
+            .or(tx.gas_price)
+            .unwrap_or_default()
+            .to_string(),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|fee| fee.to_string()),
+        first_seen_at: None,
+    }
+}
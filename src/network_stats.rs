@@ -1,25 +1,57 @@
-use crate::rpc::RpcClient;
-use anyhow::{Context, Result};
+use crate::{database::DatabaseService, rpc::RpcClient};
+#[cfg(feature = "etherscan")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "etherscan")]
 use regex::Regex;
+#[cfg(feature = "etherscan")]
 use reqwest::Client;
+use serde::Serialize;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use tokio::time;
-use tracing::{debug, error, warn};
+#[cfg(feature = "etherscan")]
+use tracing::debug;
+use tracing::{error, warn};
+
+/// Transactions per second over one window ending at the newest indexed block
+#[derive(Debug, Clone, Serialize)]
+pub struct TpsWindow {
+    pub minutes: i64,
+    pub tps: Option<f64>, // None without indexed blocks in the window
+    pub blocks: i64,
+    pub transactions: i64,
+    pub seconds: i64, // Time covered, shorter than the window until enough blocks are indexed
+}
+
+/// Throughput of the newest indexed blocks
+#[derive(Debug, Clone, Serialize)]
+pub struct TpsStats {
+    pub latest_block_timestamp: Option<i64>,
+    pub windows: Vec<TpsWindow>,
+    pub updated_at: i64,
+}
 
 /// Service for fetching and caching network-wide statistics
 pub struct NetworkStatsService {
+    #[cfg(feature = "etherscan")]
     client: Client,
     rpc: Arc<RpcClient>,
+    db: Arc<DatabaseService>,
     cached_network_accounts: Arc<RwLock<Option<(u64, Instant)>>>,
     cached_latest_block: Arc<RwLock<Option<(u64, Instant)>>>,
+    cached_tps: Arc<RwLock<Option<(TpsStats, Instant)>>>,
 }
 
 impl NetworkStatsService {
     const CACHE_DURATION: Duration = Duration::from_secs(43200); // 12 hours cache
+    #[cfg(feature = "etherscan")]
     const ETHERSCAN_URL: &'static str = "https://etherscan.io/chart/address";
+    const TPS_WINDOWS_MINUTES: [i64; 3] = [1, 5, 15];
+    const TPS_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
 
-    pub fn new(rpc: Arc<RpcClient>) -> Self {
+    pub fn new(rpc: Arc<RpcClient>, db: Arc<DatabaseService>) -> Self {
+        #[cfg(feature = "etherscan")]
         let client = Client::builder()
             .user_agent(
                 "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:140.0) Gecko/20100101 Firefox/140.0",
@@ -29,10 +61,24 @@ impl NetworkStatsService {
             .unwrap();
 
         Self {
+            #[cfg(feature = "etherscan")]
             client,
             rpc,
+            db,
             cached_network_accounts: Arc::new(RwLock::new(None)),
             cached_latest_block: Arc::new(RwLock::new(None)),
+            cached_tps: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Compute the statistics served from the cache before the first background update,
+    /// so requests right after startup don't all compute them at once
+    pub async fn warm(&self) {
+        if let Err(e) = self.update_tps().await {
+            warn!("Failed to warm TPS: {:#}", e);
+        }
+        if let Err(e) = self.update_latest_block().await {
+            warn!("Failed to warm latest block: {}", e);
         }
     }
 
@@ -51,6 +97,7 @@ impl NetworkStatsService {
                 }
 
                 // Update network accounts (every 12 hours)
+                #[cfg(feature = "etherscan")]
                 if service.should_update_accounts() {
                     if let Err(e) = service.update_network_accounts().await {
                         warn!("Failed to update network accounts: {}", e);
@@ -58,6 +105,77 @@ impl NetworkStatsService {
                 }
             }
         });
+
+        // TPS feeds a live counter, refresh it on its own faster schedule
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            let mut interval = time::interval(Self::TPS_UPDATE_INTERVAL);
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.update_tps().await {
+                    warn!("Failed to update TPS: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Get transactions per second over the last 1, 5 and 15 minutes of indexed blocks
+    pub async fn get_tps(&self) -> Result<TpsStats> {
+        if let Ok(guard) = self.cached_tps.read()
+            && let Some((stats, timestamp)) = &*guard
+            && timestamp.elapsed() < Self::TPS_UPDATE_INTERVAL * 2
+        {
+            return Ok(stats.clone());
+        }
+
+        self.update_tps().await
+    }
+
+    /// Compute TPS from the timestamps of indexed blocks rather than assuming a block time,
+    /// so missed slots lower it
+    async fn update_tps(&self) -> Result<TpsStats> {
+        let latest_block_timestamp = self
+            .db
+            .get_block_timestamp_range()
+            .await?
+            .map(|(_, newest)| newest);
+
+        let mut windows = Vec::new();
+        for minutes in Self::TPS_WINDOWS_MINUTES {
+            let (blocks, transactions, seconds) = match latest_block_timestamp {
+                Some(end) => {
+                    let start = end - minutes * 60;
+                    let (blocks, transactions, oldest, covered) =
+                        self.db.get_throughput_window(start, end).await?;
+                    // Before a full window is indexed, only the time since the oldest block counts
+                    let seconds = match oldest {
+                        Some(oldest) if !covered => end - oldest,
+                        _ => minutes * 60,
+                    };
+                    (blocks, transactions, seconds)
+                }
+                None => (0, 0, 0),
+            };
+
+            windows.push(TpsWindow {
+                minutes,
+                tps: (blocks > 0 && seconds > 0).then(|| transactions as f64 / seconds as f64),
+                blocks,
+                transactions,
+                seconds,
+            });
+        }
+
+        let stats = TpsStats {
+            latest_block_timestamp,
+            windows,
+            updated_at: chrono::Utc::now().timestamp(),
+        };
+        if let Ok(mut guard) = self.cached_tps.write() {
+            *guard = Some((stats.clone(), Instant::now()));
+        }
+        Ok(stats)
     }
 
     /// Get the latest network block number
@@ -87,7 +205,7 @@ impl NetworkStatsService {
         }
     }
 
-    /// Get total network accounts from Etherscan
+    /// Get total network accounts from Etherscan, always None without the etherscan feature
     pub async fn get_total_network_accounts(&self) -> Option<u64> {
         if let Ok(guard) = self.cached_network_accounts.read() {
             if let Some((value, timestamp)) = *guard {
@@ -107,6 +225,7 @@ impl NetworkStatsService {
         Ok(())
     }
 
+    #[cfg(feature = "etherscan")]
     async fn update_network_accounts(&self) -> Result<()> {
         let response = self
             .client
@@ -175,6 +294,7 @@ impl NetworkStatsService {
         }
     }
 
+    #[cfg(feature = "etherscan")]
     fn should_update_accounts(&self) -> bool {
         if let Ok(guard) = self.cached_network_accounts.read() {
             if let Some((_, timestamp)) = *guard {
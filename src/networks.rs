@@ -0,0 +1,81 @@
+use crate::{api::API_ROOT_SEGMENTS, config::AppConfig, App};
+use anyhow::{Context, Result};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::info;
+
+/// Service bundles of every network indexed by this process, keyed by network name.
+///
+/// Each network is a full `App` with its own RPC, beacon node and database, so networks
+/// never share indexed data. The default network is the one configured by the unprefixed
+/// variables and is also served under the plain /api prefix.
+pub struct Networks {
+    default: String,
+    apps: BTreeMap<String, Arc<App>>,
+}
+
+impl Networks {
+    /// Initialize the services of every configured network
+    pub async fn init(config: AppConfig) -> Result<Self> {
+        let default = config.network_name.clone();
+        let mut apps = BTreeMap::new();
+
+        for network_config in config.network_configs()? {
+            let name = network_config.network_name.clone();
+            // /api/<network> would be shadowed by the default network's routes
+            if API_ROOT_SEGMENTS.contains(&name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Network name {} clashes with the /api/{} endpoints",
+                    name,
+                    name
+                ));
+            }
+
+            info!("Initializing network {}", name);
+            let app = App::init(network_config)
+                .await
+                .context(format!("Failed to initialize network {}", name))?;
+            apps.insert(name, Arc::new(app));
+        }
+
+        Ok(Self { default, apps })
+    }
+
+    /// A single network
+    pub fn single(app: Arc<App>) -> Self {
+        let default = app.config.network_name.clone();
+        Self {
+            apps: BTreeMap::from([(default.clone(), app)]),
+            default,
+        }
+    }
+
+    /// Name of the default network
+    pub fn default_name(&self) -> &str {
+        &self.default
+    }
+
+    /// Services of the default network
+    pub fn default_app(&self) -> &Arc<App> {
+        &self.apps[&self.default]
+    }
+
+    /// Services of a network
+    pub fn get(&self, name: &str) -> Option<&Arc<App>> {
+        self.apps.get(name)
+    }
+
+    /// Networks by name
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Arc<App>)> {
+        self.apps.iter()
+    }
+
+    /// Start the services of every network
+    pub async fn start(&self) -> Result<()> {
+        for (name, app) in &self.apps {
+            app.start()
+                .await
+                .context(format!("Failed to start network {}", name))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, BLOCK_DATA_TABLES},
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+};
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Tables cleared when whole blocks are pruned: every table a block is stored with, the
+/// rewards fetched for it later, then the blocks they reference
+pub(crate) fn block_tables() -> Vec<&'static str> {
+    BLOCK_DATA_TABLES
+        .into_iter()
+        .chain(["block_rewards", "blocks"])
+        .collect()
+}
+
+/// Job enforcing the retention policy, so long-running deployments don't grow unbounded.
+///
+/// Logs and token transfers, the largest tables, can be kept for a shorter window than
+/// blocks. Blocks are pruned together with everything stored for them in one transaction,
+/// so the consistency checker never sees a block without its transactions. Retention is
+/// counted in blocks behind the newest indexed block.
+pub struct PruningService {
+    db: Arc<DatabaseService>,
+    events: EventLog,
+    config: AppConfig,
+}
+
+impl PruningService {
+    /// Create a new pruning service
+    pub fn new(db: Arc<DatabaseService>, events: EventLog, config: AppConfig) -> Self {
+        Self { db, events, config }
+    }
+
+    /// Start pruning in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Pruning job starting");
+            let mut interval =
+                time::interval(Duration::from_secs(service.config.prune_interval_seconds));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.run().await {
+                    warn!("Pruning run failed: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Prune everything past its retention window, then VACUUM when rows were deleted
+    pub async fn run(&self) -> Result<()> {
+        let Some(latest) = self.db.get_latest_block_number().await? else {
+            return Ok(());
+        };
+        if let Some(dir) = self
+            .config
+            .prune_archive_database
+            .as_deref()
+            .and_then(|path| Path::new(path).parent())
+            .filter(|dir| !dir.as_os_str().is_empty())
+        {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .context("Failed to create archive database directory")?;
+        }
+
+        let block_tables = block_tables();
+        let policies: [(&[&str], i64); 3] = [
+            (&["logs"], self.config.prune_logs_older_than),
+            (
+                &["token_transfers"],
+                self.config.prune_token_transfers_older_than,
+            ),
+            (&block_tables, self.config.prune_blocks_older_than),
+        ];
+
+        let mut deleted: BTreeMap<String, u64> = BTreeMap::new();
+        for (tables, retention) in policies {
+            if retention <= 0 {
+                continue;
+            }
+            // Keep the newest `retention` blocks
+            let cutoff = latest - retention + 1;
+            for (table, rows) in self.prune_before(tables, cutoff).await? {
+                *deleted.entry(table).or_default() += rows;
+            }
+        }
+
+        let total: u64 = deleted.values().sum();
+        if total == 0 {
+            debug!("Pruning run found nothing to delete");
+            return Ok(());
+        }
+
+        info!("Pruned {} rows: {:?}", total, deleted);
+        if self.config.prune_vacuum {
+            self.db.vacuum().await?;
+            info!("Vacuumed database after pruning");
+        }
+
+        self.events.record(
+            NewIndexerEvent::info(
+                IndexerEventKind::PruningRun,
+                format!("Pruned {} rows older than their retention window", total),
+            )
+            .at_block(latest)
+            .with_details(json!({
+                "deleted": deleted,
+                "archived": self.config.prune_archive_database.is_some(),
+                "vacuumed": self.config.prune_vacuum,
+            })),
+        );
+        Ok(())
+    }
+
+    /// Delete rows of `tables` below block `cutoff`, one batch of blocks per transaction
+    async fn prune_before(&self, tables: &[&str], cutoff: i64) -> Result<Vec<(String, u64)>> {
+        let mut deleted = Vec::new();
+        // The last table is the one the others reference, so it holds the lowest block
+        let Some(mut from) = self
+            .db
+            .get_lowest_block_number_in(tables[tables.len() - 1])
+            .await?
+        else {
+            return Ok(deleted);
+        };
+
+        while from < cutoff {
+            let to = (from + self.config.prune_batch_blocks.max(1)).min(cutoff);
+            let pruned = self
+                .db
+                .prune_block_range(
+                    tables,
+                    from,
+                    to,
+                    self.config.prune_archive_database.as_deref(),
+                )
+                .await?;
+            debug!("Pruned blocks {}..{}: {:?}", from, to, pruned);
+            deleted.extend(pruned);
+            from = to;
+
+            // Let the indexer's writes through between batches
+            tokio::task::yield_now().await;
+        }
+
+        Ok(deleted)
+    }
+}
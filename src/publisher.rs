@@ -0,0 +1,422 @@
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, PublishedRange},
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+    export::{self, ColdExportOptions, EXPORT_TABLES},
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tokio::time;
+use tracing::{debug, info, warn};
+
+/// Max time a single upload may take
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Name of the manifest listing every published range
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// File of a published range, as listed in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedFile {
+    pub table: String,
+    pub rows: u64,
+    pub bytes: u64,
+    pub sha256: String,
+    pub location: String, // Object key relative to the target prefix, or IPFS CID
+}
+
+/// Where snapshots are uploaded
+enum PublishTarget {
+    S3 {
+        endpoint: Url,
+        bucket: String,
+        prefix: String, // Without leading or trailing slash, may be empty
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+    },
+    Ipfs {
+        api_url: String,
+        ipns_key: Option<String>,
+    },
+}
+
+/// Job publishing Parquet or CSV exports of finalized block ranges to S3-compatible
+/// storage or IPFS, so the dataset can be consumed without running the indexer.
+///
+/// Ranges are PUBLISH_RANGE_BLOCKS long and aligned to multiples of it. A range is
+/// published once it trails the newest indexed block by PUBLISH_CONFIRMATIONS blocks and
+/// every block of it is indexed; published ranges are recorded in `published_ranges`.
+/// After each upload `manifest.json` is rewritten with every range, its files, row counts
+/// and SHA-256 hashes. On IPFS the manifest gets a new CID each time, which is published
+/// to IPNS when PUBLISH_IPNS_KEY is set.
+pub struct DatasetPublisher {
+    db: Arc<DatabaseService>,
+    events: EventLog,
+    config: AppConfig,
+    target: PublishTarget,
+    client: Client,
+}
+
+impl DatasetPublisher {
+    /// Create a new publisher, None when PUBLISH_TARGET is unset
+    pub fn new(
+        db: Arc<DatabaseService>,
+        events: EventLog,
+        config: AppConfig,
+    ) -> Result<Option<Self>> {
+        let Some(target) = config.publish_target.as_deref() else {
+            return Ok(None);
+        };
+
+        let target = if target == "ipfs" {
+            PublishTarget::Ipfs {
+                api_url: config
+                    .publish_ipfs_api_url
+                    .trim_end_matches('/')
+                    .to_string(),
+                ipns_key: config.publish_ipns_key.clone(),
+            }
+        } else {
+            let location = target
+                .strip_prefix("s3://")
+                .context("PUBLISH_TARGET must be s3://bucket/prefix or ipfs")?;
+            let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+            PublishTarget::S3 {
+                endpoint: Url::parse(&config.publish_s3_endpoint)
+                    .context("Invalid PUBLISH_S3_ENDPOINT")?,
+                bucket: bucket.to_string(),
+                prefix: prefix.trim_matches('/').to_string(),
+                region: config.publish_s3_region.clone(),
+                access_key_id: config
+                    .publish_s3_access_key_id
+                    .clone()
+                    .context("PUBLISH_S3_ACCESS_KEY_ID is required for S3 targets")?,
+                secret_access_key: config
+                    .publish_s3_secret_access_key
+                    .clone()
+                    .context("PUBLISH_S3_SECRET_ACCESS_KEY is required for S3 targets")?,
+            }
+        };
+
+        let client = Client::builder()
+            .timeout(UPLOAD_TIMEOUT)
+            .build()
+            .context("Failed to build publisher HTTP client")?;
+
+        Ok(Some(Self {
+            db,
+            events,
+            config,
+            target,
+            client,
+        }))
+    }
+
+    /// Start publishing in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Dataset publisher starting");
+            let mut interval =
+                time::interval(Duration::from_secs(service.config.publish_interval_seconds));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.run().await {
+                    warn!("Dataset publisher run failed: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Publish every finalized range not published yet, oldest first
+    pub async fn run(&self) -> Result<()> {
+        let Some((lowest, latest)) = self.db.get_block_number_range().await? else {
+            return Ok(());
+        };
+        let target = self.config.publish_target.clone().unwrap_or_default();
+        let format = self.config.publish_format.as_str();
+        let range_blocks = self.config.publish_range_blocks.max(1);
+        let final_block = latest - self.config.publish_confirmations.max(0);
+
+        let published = self.db.get_published_ranges(&target, format).await?;
+        // Ranges partly below the oldest indexed block can never be complete
+        let mut from = match published.last() {
+            Some(range) => range.to_block + 1,
+            None => (lowest + range_blocks - 1) / range_blocks * range_blocks,
+        };
+
+        let mut uploaded = 0;
+        while from + range_blocks - 1 <= final_block {
+            let to = from + range_blocks - 1;
+            let indexed = self.db.count_blocks_in_range(from, to).await?;
+            if indexed < range_blocks {
+                // Published ranges are immutable, wait for the gap repair to fill it
+                debug!(
+                    "Range {}..={} has {} of {} blocks indexed, not publishing yet",
+                    from, to, indexed, range_blocks
+                );
+                break;
+            }
+
+            let files = self.publish_range(from, to).await?;
+            self.db
+                .insert_published_range(&target, format, from, to, &serde_json::to_string(&files)?)
+                .await?;
+            info!("Published blocks {}..={} to {}", from, to, target);
+            uploaded += 1;
+            from = to + 1;
+        }
+
+        if uploaded == 0 {
+            debug!("Dataset publisher found no new range to publish");
+            return Ok(());
+        }
+
+        let ranges = self.db.get_published_ranges(&target, format).await?;
+        let manifest = self.publish_manifest(&ranges).await?;
+        self.events.record(
+            NewIndexerEvent::info(
+                IndexerEventKind::DatasetPublished,
+                format!("Published {} block ranges to {}", uploaded, target),
+            )
+            .at_block(from - 1)
+            .with_details(json!({
+                "ranges": uploaded,
+                "to_block": from - 1,
+                "manifest": manifest,
+            })),
+        );
+        Ok(())
+    }
+
+    /// Export a range to the work directory, upload its files and remove them locally
+    async fn publish_range(&self, from: i64, to: i64) -> Result<Vec<PublishedFile>> {
+        let options = ColdExportOptions {
+            from_block: from,
+            to_block: to,
+            format: self.config.publish_format,
+            output_dir: PathBuf::from(&self.config.publish_work_dir),
+            tables: EXPORT_TABLES.iter().map(|t| t.to_string()).collect(),
+        };
+
+        let mut published = Vec::new();
+        for file in export::export_block_range(&self.db, &options).await? {
+            let data = tokio::fs::read(&file.path)
+                .await
+                .context(format!("Failed to read {}", file.path.display()))?;
+            let name = file
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let bytes = data.len() as u64;
+            let sha256 = hex::encode(Sha256::digest(&data));
+            let location = self
+                .upload(&format!("{}_{}/{}", from, to, name), data)
+                .await
+                .context(format!("Failed to upload {}", name))?;
+
+            published.push(PublishedFile {
+                table: file.table,
+                rows: file.rows,
+                bytes,
+                sha256,
+                location,
+            });
+            if let Err(e) = tokio::fs::remove_file(&file.path).await {
+                warn!("Failed to remove {}: {}", file.path.display(), e);
+            }
+        }
+
+        Ok(published)
+    }
+
+    /// Upload the manifest, returning its location
+    async fn publish_manifest(&self, ranges: &[PublishedRange]) -> Result<String> {
+        let manifest = json!({
+            "network": self.config.network_name,
+            "format": self.config.publish_format.as_str(),
+            "range_blocks": self.config.publish_range_blocks,
+            "updated_at": Utc::now().to_rfc3339(),
+            "ranges": ranges
+                .iter()
+                .map(|range| {
+                    json!({
+                        "from_block": range.from_block,
+                        "to_block": range.to_block,
+                        "published_at": range.published_at,
+                        "files": serde_json::from_str::<serde_json::Value>(&range.files).ok(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        let location = self
+            .upload(MANIFEST_FILE, serde_json::to_vec_pretty(&manifest)?)
+            .await
+            .context("Failed to upload manifest")?;
+
+        if let PublishTarget::Ipfs {
+            api_url,
+            ipns_key: Some(key),
+        } = &self.target
+        {
+            self.client
+                .post(format!("{}/api/v0/name/publish", api_url))
+                .query(&[("arg", format!("/ipfs/{}", location)), ("key", key.clone())])
+                .send()
+                .await?
+                .error_for_status()
+                .context("Failed to publish manifest to IPNS")?;
+            info!("Published manifest {} to IPNS key {}", location, key);
+        }
+
+        Ok(location)
+    }
+
+    /// Upload a file, returning its object key or CID
+    async fn upload(&self, name: &str, data: Vec<u8>) -> Result<String> {
+        match &self.target {
+            PublishTarget::S3 {
+                endpoint,
+                bucket,
+                prefix,
+                region,
+                access_key_id,
+                secret_access_key,
+            } => {
+                let key = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+                let path = format!("/{}/{}", bucket, uri_encode(&key));
+                let url = endpoint.join(&path)?;
+                let host = match url.port() {
+                    Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+                    None => url.host_str().unwrap_or_default().to_string(),
+                };
+                let headers = sign_s3_put(
+                    &host,
+                    &path,
+                    &data,
+                    region,
+                    access_key_id,
+                    secret_access_key,
+                );
+
+                let mut request = self.client.put(url);
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                request.body(data).send().await?.error_for_status()?;
+                Ok(key)
+            }
+            PublishTarget::Ipfs { api_url, .. } => {
+                #[derive(Deserialize)]
+                struct Added {
+                    #[serde(rename = "Hash")]
+                    hash: String,
+                }
+
+                // The add endpoint only takes multipart uploads
+                let boundary = format!("eth-indexer-{}", hex::encode(Sha256::digest(name)));
+                let file_name = name.rsplit('/').next().unwrap_or(name);
+                let mut body = format!(
+                    "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                    boundary, file_name
+                )
+                .into_bytes();
+                body.extend_from_slice(&data);
+                body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+                let added: Added = self
+                    .client
+                    .post(format!("{}/api/v0/add", api_url))
+                    .query(&[("pin", "true"), ("cid-version", "1")])
+                    .header(
+                        "content-type",
+                        format!("multipart/form-data; boundary={}", boundary),
+                    )
+                    .body(body)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                Ok(added.hash)
+            }
+        }
+    }
+}
+
+/// Headers of an S3 PUT request signed with AWS Signature Version 4
+fn sign_s3_put(
+    host: &str,
+    path: &str,
+    body: &[u8],
+    region: &str,
+    access_key_id: &str,
+    secret_access_key: &str,
+) -> Vec<(&'static str, String)> {
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex::encode(Sha256::digest(body));
+
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        path, host, payload_hash, amz_date, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = [region, "s3", "aws4_request"].iter().fold(
+        hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), &date),
+        |key, part| hmac_sha256(&key, part),
+    );
+    let signature = hex::encode(hmac_sha256(&signing_key, &string_to_sign));
+
+    vec![
+        ("x-amz-date", amz_date),
+        ("x-amz-content-sha256", payload_hash),
+        (
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                access_key_id, scope, signed_headers, signature
+            ),
+        ),
+    ]
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode an object key as S3 expects in the canonical request, keeping slashes
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
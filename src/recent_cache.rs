@@ -0,0 +1,254 @@
+use crate::database::{Block, DatabaseService, TokenTransfer, Transaction};
+use anyhow::Result;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::RwLock,
+};
+use tracing::info;
+
+/// A cached block with what was stored for it
+#[derive(Debug, Clone)]
+struct CachedBlock {
+    block: Block,
+    transactions: Vec<Transaction>,
+    token_transfers: Vec<TokenTransfer>,
+}
+
+/// In-memory copy of the newest indexed blocks, their transactions and token transfers,
+/// serving the dashboard's latest-data endpoints without querying the database.
+///
+/// The cache is warmed from the database at startup and then fed by the live dispatcher,
+/// so every stored block at or above the lowest cached block is cached too. Reads it can't
+/// answer completely return None and fall back to SQL. Transaction calldata is not kept.
+#[derive(Debug)]
+pub struct RecentCache {
+    capacity: usize,
+    blocks: RwLock<BTreeMap<i64, CachedBlock>>,
+}
+
+impl RecentCache {
+    /// Create a cache holding up to `capacity` blocks, 0 disables it
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            blocks: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Load the newest blocks from the database
+    pub async fn warm(&self, db: &DatabaseService) -> Result<()> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+
+        let blocks = db.get_recent_blocks(self.capacity as i64, 0).await?;
+        let Some(lowest) = blocks.last().map(|block| block.number) else {
+            return Ok(());
+        };
+        let transactions = db.get_transactions_from_block(lowest).await?;
+        let token_transfers = db.get_token_transfers_from_block(lowest).await?;
+
+        let mut cached: BTreeMap<i64, CachedBlock> = blocks
+            .into_iter()
+            .map(|block| {
+                let entry = CachedBlock {
+                    block,
+                    transactions: Vec::new(),
+                    token_transfers: Vec::new(),
+                };
+                (entry.block.number, entry)
+            })
+            .collect();
+        for transaction in transactions {
+            if let Some(entry) = cached.get_mut(&transaction.block_number) {
+                entry.transactions.push(transaction);
+            }
+        }
+        for transfer in token_transfers {
+            if let Some(entry) = cached.get_mut(&transfer.block_number) {
+                entry.token_transfers.push(transfer);
+            }
+        }
+
+        info!("Recent data cache warmed with {} blocks", cached.len());
+        // Blocks published while warming are newer than what was loaded
+        let mut blocks = self.blocks.write().unwrap();
+        for (number, entry) in cached {
+            blocks.entry(number).or_insert(entry);
+        }
+        Self::evict(&mut blocks, self.capacity);
+        Ok(())
+    }
+
+    /// Cache a newly stored block, replacing a previous version after a reorg or reindex
+    pub fn insert_block(&self, block: &Block) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut blocks = self.blocks.write().unwrap();
+        // Older blocks would leave a hole below the cached ones when the cache is full
+        if blocks.len() >= self.capacity
+            && blocks
+                .first_key_value()
+                .is_some_and(|(lowest, _)| block.number < *lowest)
+        {
+            return;
+        }
+        blocks.insert(
+            block.number,
+            CachedBlock {
+                block: block.clone(),
+                transactions: Vec::new(),
+                token_transfers: Vec::new(),
+            },
+        );
+        Self::evict(&mut blocks, self.capacity);
+    }
+
+    /// Cache stored transactions of cached blocks
+    pub fn insert_transactions(&self, transactions: &[Transaction]) {
+        let mut blocks = self.blocks.write().unwrap();
+        let mut updated = BTreeSet::new();
+        for transaction in transactions {
+            if let Some(entry) = blocks.get_mut(&transaction.block_number) {
+                entry.transactions.push(Transaction {
+                    input: None,
+                    ..transaction.clone()
+                });
+                updated.insert(transaction.block_number);
+            }
+        }
+        for number in updated {
+            if let Some(entry) = blocks.get_mut(&number) {
+                entry.transactions.sort_by_key(|tx| tx.transaction_index);
+            }
+        }
+    }
+
+    /// Cache stored token transfers of cached blocks
+    pub fn insert_token_transfers(&self, transfers: &[TokenTransfer]) {
+        let mut blocks = self.blocks.write().unwrap();
+        for transfer in transfers {
+            if let Some(entry) = blocks.get_mut(&transfer.block_number) {
+                entry.token_transfers.push(transfer.clone());
+            }
+        }
+    }
+
+    /// Newest blocks, when the cache holds the whole page
+    pub fn recent_blocks(&self, limit: i64, offset: i64) -> Option<Vec<Block>> {
+        let (limit, offset) = Self::page(limit, offset)?;
+        let blocks = self.blocks.read().unwrap();
+        if offset + limit > blocks.len() {
+            return None;
+        }
+
+        Some(
+            blocks
+                .values()
+                .rev()
+                .skip(offset)
+                .take(limit)
+                .map(|entry| entry.block.clone())
+                .collect(),
+        )
+    }
+
+    /// Newest blocks above `since`, when they are all cached
+    pub fn blocks_since(&self, since: i64, limit: i64) -> Option<Vec<Block>> {
+        let blocks = self.blocks.read().unwrap();
+        let (lowest, _) = blocks.first_key_value()?;
+        if since + 1 < *lowest {
+            return None;
+        }
+
+        Some(
+            blocks
+                .range(since + 1..)
+                .rev()
+                .take(usize::try_from(limit).unwrap_or(0))
+                .map(|(_, entry)| entry.block.clone())
+                .collect(),
+        )
+    }
+
+    /// Newest transactions, when the cache holds the whole page
+    pub fn recent_transactions(&self, limit: i64, offset: i64) -> Option<Vec<Transaction>> {
+        let (limit, offset) = Self::page(limit, offset)?;
+        let blocks = self.blocks.read().unwrap();
+        let transactions: Vec<Transaction> = blocks
+            .values()
+            .rev()
+            .flat_map(|entry| entry.transactions.iter().rev())
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect();
+
+        (transactions.len() == limit).then_some(transactions)
+    }
+
+    /// Newest transactions ordered after the transaction `hash`, when it is cached
+    pub fn transactions_since(&self, hash: &str, limit: i64) -> Option<Vec<Transaction>> {
+        let blocks = self.blocks.read().unwrap();
+        let reference = blocks
+            .values()
+            .rev()
+            .flat_map(|entry| entry.transactions.iter())
+            .find(|tx| tx.hash.eq_ignore_ascii_case(hash))?;
+        let position = (reference.block_number, reference.transaction_index);
+
+        Some(
+            blocks
+                .range(reference.block_number..)
+                .rev()
+                .flat_map(|(_, entry)| entry.transactions.iter().rev())
+                .take_while(|tx| (tx.block_number, tx.transaction_index) > position)
+                .take(usize::try_from(limit).unwrap_or(0))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Transactions of a cached block
+    pub fn transactions_by_block(&self, block_number: i64) -> Option<Vec<Transaction>> {
+        self.blocks
+            .read()
+            .unwrap()
+            .get(&block_number)
+            .map(|entry| entry.transactions.clone())
+    }
+
+    /// Token transfers of a transaction in a cached block
+    pub fn token_transfers_by_transaction(&self, hash: &str) -> Option<Vec<TokenTransfer>> {
+        let blocks = self.blocks.read().unwrap();
+        let entry = blocks.values().rev().find(|entry| {
+            entry
+                .transactions
+                .iter()
+                .any(|tx| tx.hash.eq_ignore_ascii_case(hash))
+        })?;
+
+        Some(
+            entry
+                .token_transfers
+                .iter()
+                .filter(|transfer| transfer.transaction_hash.eq_ignore_ascii_case(hash))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Page bounds as indexes, None for negative values
+    fn page(limit: i64, offset: i64) -> Option<(usize, usize)> {
+        Some((usize::try_from(limit).ok()?, usize::try_from(offset).ok()?))
+    }
+
+    /// Drop the oldest blocks beyond `capacity`
+    fn evict(blocks: &mut BTreeMap<i64, CachedBlock>, capacity: usize) {
+        while blocks.len() > capacity {
+            blocks.pop_first();
+        }
+    }
+}
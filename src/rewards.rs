@@ -0,0 +1,199 @@
+use crate::{
+    beacon::BeaconClient,
+    config::AppConfig,
+    database::{Block, BlockRewards, DatabaseService},
+    rpc::RpcClient,
+};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Wei per Gwei, the unit of beacon API amounts
+const WEI_PER_GWEI: i128 = 1_000_000_000;
+
+/// Blocks behind the newest indexed block whose missing rewards are still fetched, as
+/// beacon nodes without historical states can't serve older ones
+const REWARDS_LOOKBACK_BLOCKS: i64 = 7200;
+
+/// Blocks whose rewards are fetched per run
+const REWARDS_BATCH_BLOCKS: i64 = 64;
+
+/// Fetches the rewards blocks paid their proposers in the background, replacing the
+/// estimated breakdown of block details with real values.
+///
+/// The consensus share comes from the beacon rewards API. The execution share is the
+/// balance change of the fee recipient over the block, which needs an archive node.
+/// Results are stored as soon as the consensus share is known, and the execution share is
+/// filled in by later runs while the node serves archive state.
+pub struct RewardsService {
+    db: Arc<DatabaseService>,
+    rpc: Arc<RpcClient>,
+    beacon: Arc<BeaconClient>,
+    config: AppConfig,
+}
+
+impl RewardsService {
+    /// Create a new rewards service
+    pub fn new(
+        db: Arc<DatabaseService>,
+        rpc: Arc<RpcClient>,
+        beacon: Arc<BeaconClient>,
+        config: AppConfig,
+    ) -> Self {
+        Self {
+            db,
+            rpc,
+            beacon,
+            config,
+        }
+    }
+
+    /// Start fetching the rewards of new blocks in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Block rewards job starting");
+            let mut interval =
+                time::interval(Duration::from_secs(service.config.rewards_interval_seconds));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.update().await {
+                    warn!("Failed to update block rewards: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Fetch the rewards of recent blocks that have none, or lack the execution share
+    /// while archive state is available
+    async fn update(&self) -> Result<()> {
+        let Some(latest) = self.db.get_latest_block_number().await? else {
+            return Ok(());
+        };
+
+        let blocks = self
+            .db
+            .get_blocks_without_rewards(
+                latest - REWARDS_LOOKBACK_BLOCKS,
+                self.rpc.is_archive_node(),
+                REWARDS_BATCH_BLOCKS,
+            )
+            .await?;
+        let mut stored = 0;
+        for block in &blocks {
+            match self.block_rewards(block).await {
+                Ok(Some(_)) => stored += 1,
+                Ok(None) => {}
+                Err(e) => warn!("Failed to get rewards of block {}: {:#}", block.number, e),
+            }
+        }
+
+        if stored > 0 {
+            debug!("Stored rewards of {} blocks", stored);
+        }
+        Ok(())
+    }
+
+    /// Proposer rewards of a block, fetched and stored when missing. None for pre-merge
+    /// blocks or when the beacon node no longer has them.
+    pub async fn block_rewards(&self, block: &Block) -> Result<Option<BlockRewards>> {
+        let mut rewards = match self.db.get_block_rewards(block.number).await? {
+            Some(rewards)
+                if rewards.fee_recipient_reward.is_some() || !self.rpc.is_archive_node() =>
+            {
+                return Ok(Some(rewards));
+            }
+            // Only the execution share is missing
+            Some(rewards) => rewards,
+            None => {
+                let slot = match block.slot {
+                    Some(slot) => slot as u64,
+                    None => match self
+                        .beacon
+                        .get_slot_by_execution_block(block.number as u64, block.timestamp as u64)
+                        .await?
+                    {
+                        Some(slot) => slot,
+                        None => return Ok(None),
+                    },
+                };
+                let Some(beacon_rewards) = self.beacon.get_block_rewards(slot).await? else {
+                    return Ok(None);
+                };
+
+                BlockRewards {
+                    block_number: block.number,
+                    slot: slot as i64,
+                    proposer_index: beacon_rewards
+                        .proposer_index
+                        .parse()
+                        .context("Invalid proposer index in beacon block rewards")?,
+                    total: beacon_rewards.total,
+                    attestations: beacon_rewards.attestations,
+                    sync_aggregate: beacon_rewards.sync_aggregate,
+                    proposer_slashings: beacon_rewards.proposer_slashings,
+                    attester_slashings: beacon_rewards.attester_slashings,
+                    fee_recipient_reward: None,
+                }
+            }
+        };
+
+        if let Some(miner) = &block.miner {
+            match self.fee_recipient_reward(block.number, miner).await {
+                Ok(reward) => {
+                    rewards.fee_recipient_reward = reward.map(|reward| reward.to_string())
+                }
+                Err(e) => warn!(
+                    "Failed to get fee recipient reward of block {}: {:#}",
+                    block.number, e
+                ),
+            }
+        }
+
+        // Without archive state the execution share is filled in by a later run
+        self.db.insert_block_rewards(&rewards).await?;
+
+        Ok(Some(rewards))
+    }
+
+    /// Balance change of the fee recipient over a block, adding back what it spent in its
+    /// own transactions and leaving out withdrawals it received. None without archive state.
+    async fn fee_recipient_reward(&self, block_number: i64, miner: &str) -> Result<Option<i128>> {
+        if !self.rpc.is_archive_node() || block_number == 0 {
+            return Ok(None);
+        }
+
+        let (after, after_block) = self
+            .rpc
+            .get_balance_at_block(miner, block_number as u64)
+            .await?;
+        let (before, before_block) = self
+            .rpc
+            .get_balance_at_block(miner, block_number as u64 - 1)
+            .await?;
+        // Either read fell back to the latest state
+        if after_block.is_none() || before_block.is_none() {
+            return Ok(None);
+        }
+
+        let mut reward = after.parse::<i128>().context("Invalid balance")?
+            - before.parse::<i128>().context("Invalid balance")?;
+
+        for tx in self.db.get_transactions_by_block(block_number).await? {
+            if tx.from_address.eq_ignore_ascii_case(miner) {
+                let value = tx.value.parse::<i128>().unwrap_or(0);
+                let fee = tx.gas_price.parse::<i128>().unwrap_or(0) * tx.gas_used as i128;
+                reward += value + fee;
+            }
+        }
+        for withdrawal in self.db.get_withdrawals_by_block(block_number).await? {
+            if withdrawal.address.eq_ignore_ascii_case(miner) {
+                reward -= withdrawal.amount.parse::<i128>().unwrap_or(0) * WEI_PER_GWEI;
+            }
+        }
+
+        Ok(Some(reward))
+    }
+}
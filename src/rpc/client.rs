@@ -1,46 +1,102 @@
 use crate::config::AppConfig;
-use crate::executor::{EthRpcOperation, RpcExecutor};
+use crate::events::{EventLog, IndexerEventKind, NewIndexerEvent};
+use crate::executor::{EthRpcOperation, QueueStats, RateLimitFeedback, RateLimits, RpcExecutor};
+use crate::rpc::fallback::{FallbackTransport, MethodUsage, ProviderStats, RpcCall};
+use crate::rpc::multicall::{
+    decode_aggregate3, decode_bool, decode_decimals, decode_string, decode_uint,
+    encode_address_call, encode_aggregate3, encode_supports_interface_call, selector,
+    MulticallCall,
+};
 use anyhow::{Context, Result};
 use ethers::{
     core::types::{
-        Block as EthBlock, BlockNumber, Bytes, Transaction as EthTransaction, TransactionReceipt,
-        TransactionRequest, H160, H256, U64,
+        Block as EthBlock, BlockNumber, Bytes, CallFrame, Transaction as EthTransaction,
+        TransactionReceipt, TransactionRequest, H160, H256, U256, U64,
     },
-    providers::{Http, Middleware, Provider},
+    providers::{FilterKind, Middleware, Provider, ProviderError, RpcError},
     utils::keccak256,
 };
+use std::collections::BTreeMap;
 use std::str::FromStr;
-use std::sync::Arc;
-use tracing::{debug, error};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// How far behind the head the archive probe reads state, well past any full-node pruning window
+const ARCHIVE_PROBE_DEPTH: u64 = 10_000;
+
+/// Interval between archive probes after startup, so a node switched to latest balances
+/// goes back to historical ones once it serves them again
+const ARCHIVE_PROBE_INTERVAL: Duration = Duration::from_secs(600);
 
 /// Response types for ETH RPC operations
 #[derive(Debug)]
 pub enum EthRpcResponse {
     LatestBlockNumber(u64),
     Block(Option<EthBlock<EthTransaction>>),
+    BlockHeader(Option<EthBlock<H256>>),
+    Transaction(Option<EthTransaction>),
     TransactionReceipt(Option<TransactionReceipt>),
+    BlockReceipts(Vec<TransactionReceipt>),
+    BlockCallTraces(Vec<CallFrame>),
+    CallResult(Bytes),
     ConnectionCheck(bool),
 }
 
+/// ERC-20 metadata of a token, None for values the token did not provide
+#[derive(Debug, Clone, Default)]
+pub struct TokenMetadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
 /// Client for interacting with Ethereum RPC
 pub struct RpcClient {
-    provider: Arc<Provider<Http>>,
+    provider: Arc<Provider<Arc<FallbackTransport>>>,
+    transport: Arc<FallbackTransport>,
     executor: RpcExecutor<EthRpcOperation, EthRpcResponse>,
+    archive_node: AtomicBool, // Whether the node serves state for old blocks
+    multicall_address: Option<H160>, // Multicall3 contract used to batch eth_calls
+    block_receipts_supported: AtomicBool, // Whether the node implements eth_getBlockReceipts
+    txpool_supported: AtomicBool, // Whether the node exposes the txpool namespace
+    call_traces_supported: AtomicBool, // Whether the node implements debug_traceBlockByNumber
+    events: EventLog,
 }
 
 impl RpcClient {
     /// Create a new RPC client
-    pub fn new(rpc_url: &str, config: AppConfig) -> Result<Self> {
-        let provider = Provider::<Http>::try_from(rpc_url)
-            .context(format!("Failed to connect to RPC URL: {}", rpc_url))?;
-        let provider = Arc::new(provider);
+    pub fn new(rpc_url: &str, config: AppConfig, events: EventLog) -> Result<Self> {
+        let rate_limits = Arc::new(RateLimitFeedback::new(Duration::from_secs(
+            config.rpc_rate_limit_cooldown_seconds,
+        )));
+        let transport = Arc::new(
+            FallbackTransport::new(rpc_url, events.clone(), rate_limits.clone())
+                .context("Failed to configure RPC providers")?,
+        );
+        let provider = Arc::new(Provider::new(transport.clone()));
+
+        let multicall_address = config
+            .multicall_address
+            .as_deref()
+            .map(|address| {
+                address
+                    .parse::<H160>()
+                    .context(format!("Invalid multicall address: {}", address))
+            })
+            .transpose()?;
 
         // Create RPC executor with rate limiting
         let provider_clone = provider.clone();
-        let executor = RpcExecutor::new(
+        let executor = RpcExecutor::with_rate_limit_feedback(
             "ETH".to_string(),
             config.eth_rpc_max_concurrent,
             config.eth_rpc_min_interval_ms,
+            config.eth_rpc_queue_capacity,
+            rate_limits,
             move |operation| {
                 let provider = provider_clone.clone();
                 async move {
@@ -55,11 +111,70 @@ impl RpcClient {
                                 .await?;
                             Ok(EthRpcResponse::Block(block))
                         }
+                        EthRpcOperation::GetBlockHeader(block_num) => {
+                            let block = provider
+                                .get_block(BlockNumber::Number(U64::from(block_num)))
+                                .await?;
+                            Ok(EthRpcResponse::BlockHeader(block))
+                        }
+                        EthRpcOperation::GetTransaction(tx_hash) => {
+                            let hash = H256::from_str(&tx_hash)?;
+                            let transaction = provider.get_transaction(hash).await?;
+                            Ok(EthRpcResponse::Transaction(transaction))
+                        }
                         EthRpcOperation::GetTransactionReceipt(tx_hash) => {
                             let hash = H256::from_str(&tx_hash)?;
                             let receipt = provider.get_transaction_receipt(hash).await?;
                             Ok(EthRpcResponse::TransactionReceipt(receipt))
                         }
+                        EthRpcOperation::GetBlockReceipts(block_num) => {
+                            let receipts = provider
+                                .get_block_receipts(BlockNumber::Number(U64::from(block_num)))
+                                .await?;
+                            Ok(EthRpcResponse::BlockReceipts(receipts))
+                        }
+                        EthRpcOperation::TraceBlockCalls(block_num) => {
+                            // Geth wraps each frame as {"txHash", "result"}, others don't
+                            let traces: Vec<serde_json::Value> = provider
+                                .request(
+                                    "debug_traceBlockByNumber",
+                                    (
+                                        U64::from(block_num),
+                                        serde_json::json!({ "tracer": "callTracer" }),
+                                    ),
+                                )
+                                .await?;
+                            let frames = traces
+                                .into_iter()
+                                .map(|trace| match trace.get("result") {
+                                    Some(result) => serde_json::from_value(result.clone()),
+                                    None => serde_json::from_value(trace),
+                                })
+                                .collect::<Result<Vec<CallFrame>, _>>()?;
+                            Ok(EthRpcResponse::BlockCallTraces(frames))
+                        }
+                        EthRpcOperation::Call {
+                            to,
+                            data,
+                            block_number,
+                        } => {
+                            let to = H160::from_str(&to)?;
+                            let block_id = block_number.map(|num| {
+                                ethers::core::types::BlockId::Number(BlockNumber::Number(
+                                    U64::from(num),
+                                ))
+                            });
+                            let result = provider
+                                .call(
+                                    &TransactionRequest::new()
+                                        .to(to)
+                                        .data(Bytes::from(data))
+                                        .into(),
+                                    block_id,
+                                )
+                                .await?;
+                            Ok(EthRpcResponse::CallResult(result))
+                        }
                         EthRpcOperation::CheckConnection => {
                             match provider.get_block_number().await {
                                 Ok(_) => Ok(EthRpcResponse::ConnectionCheck(true)),
@@ -71,7 +186,431 @@ impl RpcClient {
             },
         );
 
-        Ok(Self { provider, executor })
+        Ok(Self {
+            provider,
+            transport,
+            executor,
+            archive_node: AtomicBool::new(true),
+            multicall_address,
+            block_receipts_supported: AtomicBool::new(true),
+            txpool_supported: AtomicBool::new(true),
+            call_traces_supported: AtomicBool::new(true),
+            events,
+        })
+    }
+
+    /// Probe the node for historical state access and remember the result.
+    /// Non-archive nodes only keep recent state, so block-pinned balance queries
+    /// are switched to latest-balance mode when the node reports the state missing.
+    /// Other failures, like timeouts, keep the current mode.
+    pub async fn detect_archive_support(&self) -> bool {
+        let latest_block = match self.get_latest_block_number().await {
+            Ok(block) => block,
+            Err(e) => {
+                warn!("Could not probe RPC node for archive support: {}", e);
+                return self.is_archive_node();
+            }
+        };
+
+        let probe_block = latest_block.saturating_sub(ARCHIVE_PROBE_DEPTH);
+        let supported = match self
+            .get_balance(&format!("{:?}", H160::zero()), Some(probe_block))
+            .await
+        {
+            Ok(_) => true,
+            Err(e) if is_missing_state_error(&e) => false,
+            Err(e) => {
+                warn!(
+                    "Archive probe at block {} failed, keeping the current balance mode: {:#}",
+                    probe_block, e
+                );
+                return self.is_archive_node();
+            }
+        };
+
+        match (
+            self.archive_node.swap(supported, Ordering::Relaxed),
+            supported,
+        ) {
+            (false, true) => {
+                info!("RPC node serves historical state again, balances are read at their block")
+            }
+            (true, true) => debug!("RPC node serves historical state"),
+            (true, false) => {
+                warn!(
+                    "RPC node has no state for block {}, historical balances fall back to latest",
+                    probe_block
+                );
+                self.events.record(
+                    NewIndexerEvent::warning(
+                        IndexerEventKind::ArchiveFallback,
+                        "RPC node does not serve historical state, balances are read at latest",
+                    )
+                    .at_block(probe_block as i64),
+                );
+            }
+            (false, false) => debug!("RPC node still has no historical state"),
+        }
+
+        supported
+    }
+
+    /// Probe the node for historical state every `ARCHIVE_PROBE_INTERVAL` in the background
+    pub fn start_archive_probes(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ARCHIVE_PROBE_INTERVAL);
+            // The first tick completes immediately, startup already probed
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                self.detect_archive_support().await;
+            }
+        });
+    }
+
+    /// Concurrency and rate limits applied to RPC requests
+    pub fn rate_limits(&self) -> RateLimits {
+        self.executor.limits()
+    }
+
+    /// Change the concurrency and rate limits applied to RPC requests
+    pub fn set_rate_limits(&self, limits: RateLimits) {
+        self.executor.set_limits(limits);
+    }
+
+    /// Depth and wait times of the queue of RPC requests waiting for a slot
+    pub fn queue_stats(&self) -> QueueStats {
+        self.executor.queue_stats()
+    }
+
+    /// Fail, delay or corrupt a share of the requests of this client, for resilience tests
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(self, faults: Arc<crate::chaos::FaultInjector>) -> Self {
+        self.transport.set_fault_injector(faults);
+        self
+    }
+
+    /// Request and health statistics of each configured RPC provider
+    pub fn provider_stats(&self) -> Vec<ProviderStats> {
+        self.transport.stats()
+    }
+
+    /// Requests per JSON-RPC method, split between API traffic and indexing
+    pub fn method_usage(&self) -> BTreeMap<String, MethodUsage> {
+        self.transport.usage()
+    }
+
+    /// Start recording the calls made on behalf of the request ID `request_id`
+    pub fn start_call_trace(&self, request_id: Arc<str>) {
+        self.transport.start_trace(request_id);
+    }
+
+    /// Stop recording the calls of `request_id` and return them
+    pub fn finish_call_trace(&self, request_id: &str) -> Vec<RpcCall> {
+        self.transport.finish_trace(request_id)
+    }
+
+    /// Whether historical state queries are expected to succeed
+    pub fn is_archive_node(&self) -> bool {
+        self.archive_node.load(Ordering::Relaxed)
+    }
+
+    /// Get account balance at a block, falling back to the latest balance on non-archive nodes.
+    /// Returns the balance together with the block it was read at (None when latest state was used).
+    pub async fn get_balance_at_block(
+        &self,
+        address: &str,
+        block_number: u64,
+    ) -> Result<(String, Option<u64>)> {
+        if self.is_archive_node() {
+            match self.get_balance(address, Some(block_number)).await {
+                Ok(balance) => return Ok((balance, Some(block_number))),
+                Err(e) if is_missing_state_error(&e) => self.mark_state_missing(block_number, &e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let balance = self.get_balance(address, None).await?;
+        Ok((balance, None))
+    }
+
+    /// Switch to latest-balance mode after the node reported pruned state, until the next
+    /// archive probe finds it again
+    fn mark_state_missing(&self, block_number: u64, error: &anyhow::Error) {
+        if self.archive_node.swap(false, Ordering::Relaxed) {
+            warn!(
+                "RPC node lost historical state at block {} ({:#}), switching to latest balances",
+                block_number, error
+            );
+            self.events.record(
+                NewIndexerEvent::warning(
+                    IndexerEventKind::ArchiveFallback,
+                    "RPC node lost historical state, switching to latest balances",
+                )
+                .at_block(block_number as i64)
+                .with_details(serde_json::json!({ "error": format!("{:#}", error) })),
+            );
+        }
+    }
+
+    /// Whether a Multicall3 contract is configured for batched calls
+    pub fn multicall_enabled(&self) -> bool {
+        self.multicall_address.is_some()
+    }
+
+    /// Execute a read-only contract call
+    pub async fn call(&self, to: H160, data: Vec<u8>, block_number: Option<u64>) -> Result<Bytes> {
+        match self
+            .executor
+            .execute(EthRpcOperation::Call {
+                to: format!("{:?}", to),
+                data,
+                block_number,
+            })
+            .await?
+        {
+            EthRpcResponse::CallResult(result) => Ok(result),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Aggregate calls into a single eth_call through Multicall3.
+    /// Returns the return data of each call in order, None for calls that reverted.
+    pub async fn multicall(
+        &self,
+        calls: &[MulticallCall],
+        block_number: Option<u64>,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        let multicall = self.multicall_address.context("Multicall is disabled")?;
+
+        let result = self
+            .call(multicall, encode_aggregate3(calls), block_number)
+            .await
+            .context("Multicall aggregate3 call failed")?;
+        let results = decode_aggregate3(&result)?;
+
+        if results.len() != calls.len() {
+            return Err(anyhow::anyhow!(
+                "Multicall returned {} results for {} calls",
+                results.len(),
+                calls.len()
+            ));
+        }
+
+        Ok(results)
+    }
+
+    /// Get ETH balances for many addresses with a single Multicall3 request, falling back
+    /// to latest balances on non-archive nodes like `get_balance_at_block`.
+    /// Returns the balances in input order together with the block they were read at.
+    pub async fn get_balances_batch(
+        &self,
+        addresses: &[String],
+        block_number: u64,
+    ) -> Result<(Vec<String>, Option<u64>)> {
+        let multicall = self.multicall_address.context("Multicall is disabled")?;
+
+        let calls = addresses
+            .iter()
+            .map(|address| {
+                let address = address
+                    .parse::<H160>()
+                    .context(format!("Invalid Ethereum address: {}", address))?;
+                Ok((
+                    multicall,
+                    encode_address_call("getEthBalance(address)", address),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut at_block = self.is_archive_node().then_some(block_number);
+        let results = match self.multicall(&calls, at_block).await {
+            Ok(results) => results,
+            Err(e) if at_block.is_some() && is_missing_state_error(&e) => {
+                self.mark_state_missing(block_number, &e);
+                at_block = None;
+                self.multicall(&calls, None).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        let balances = results
+            .iter()
+            .zip(addresses)
+            .map(|(result, address)| {
+                result
+                    .as_deref()
+                    .and_then(decode_uint)
+                    .map(|balance| balance.to_string())
+                    .context(format!("getEthBalance failed for address: {}", address))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((balances, at_block))
+    }
+
+    /// Get ERC-20 balances for many (token, account) pairs with a single Multicall3 request.
+    /// Returns balances in input order, None where balanceOf reverted.
+    pub async fn get_token_balances_batch(
+        &self,
+        pairs: &[(String, String)],
+        block_number: Option<u64>,
+    ) -> Result<Vec<Option<String>>> {
+        let calls = pairs
+            .iter()
+            .map(|(token_address, account_address)| {
+                let token_contract = token_address
+                    .parse::<H160>()
+                    .context(format!("Invalid token contract address: {}", token_address))?;
+                let account = account_address
+                    .parse::<H160>()
+                    .context(format!("Invalid account address: {}", account_address))?;
+                Ok((
+                    token_contract,
+                    encode_address_call("balanceOf(address)", account),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let results = self
+            .multicall(&calls, block_number.filter(|_| self.is_archive_node()))
+            .await?;
+
+        Ok(results
+            .iter()
+            .map(|result| {
+                result
+                    .as_deref()
+                    .and_then(decode_uint)
+                    .map(|balance| balance.to_string())
+            })
+            .collect())
+    }
+
+    /// Get name, symbol and decimals of many tokens with a single Multicall3 request.
+    /// Returns metadata in input order; calls that reverted or returned undecodable data
+    /// leave the corresponding field None without failing the batch.
+    pub async fn get_token_metadata_batch(
+        &self,
+        token_addresses: &[String],
+    ) -> Result<Vec<TokenMetadata>> {
+        let mut calls = Vec::with_capacity(token_addresses.len() * 3);
+        for token_address in token_addresses {
+            let token_contract = token_address
+                .parse::<H160>()
+                .context(format!("Invalid token contract address: {}", token_address))?;
+            for signature in ["name()", "symbol()", "decimals()"] {
+                calls.push((token_contract, selector(signature).to_vec()));
+            }
+        }
+
+        let results = self.multicall(&calls, None).await?;
+
+        Ok(results
+            .chunks(3)
+            .map(|results| TokenMetadata {
+                name: results[0].as_deref().and_then(decode_string),
+                symbol: results[1].as_deref().and_then(decode_string),
+                decimals: results[2].as_deref().and_then(decode_decimals),
+            })
+            .collect())
+    }
+
+    /// Get the totalSupply() of a token at the latest block, None when the call returned
+    /// no decodable amount
+    pub async fn get_token_total_supply(&self, token_address: &str) -> Result<Option<String>> {
+        let token_contract = token_address
+            .parse::<H160>()
+            .context(format!("Invalid token contract address: {}", token_address))?;
+
+        let result = self
+            .call(token_contract, selector("totalSupply()").to_vec(), None)
+            .await
+            .context(format!(
+                "totalSupply call failed for token: {}",
+                token_address
+            ))?;
+
+        Ok(decode_uint(&result).map(|supply| supply.to_string()))
+    }
+
+    /// Get the totalSupply() of many tokens at the latest block with a single Multicall3
+    /// request. Returns supplies in input order, None where totalSupply reverted.
+    pub async fn get_token_total_supplies_batch(
+        &self,
+        token_addresses: &[String],
+    ) -> Result<Vec<Option<String>>> {
+        let calls = token_addresses
+            .iter()
+            .map(|token_address| {
+                let token_contract = token_address
+                    .parse::<H160>()
+                    .context(format!("Invalid token contract address: {}", token_address))?;
+                Ok((token_contract, selector("totalSupply()").to_vec()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let results = self.multicall(&calls, None).await?;
+
+        Ok(results
+            .iter()
+            .map(|result| {
+                result
+                    .as_deref()
+                    .and_then(decode_uint)
+                    .map(|supply| supply.to_string())
+            })
+            .collect())
+    }
+
+    /// Ask a contract whether it supports an ERC-165 interface, None when the call
+    /// returned no boolean
+    pub async fn supports_interface(
+        &self,
+        address: &str,
+        interface_id: [u8; 4],
+    ) -> Result<Option<bool>> {
+        let contract = address
+            .parse::<H160>()
+            .context(format!("Invalid contract address: {}", address))?;
+
+        let result = self
+            .call(contract, encode_supports_interface_call(interface_id), None)
+            .await
+            .context(format!("supportsInterface call failed for: {}", address))?;
+
+        Ok(decode_bool(&result))
+    }
+
+    /// Ask many contracts whether they support each of `interface_ids` with a single
+    /// Multicall3 request. Returns the answers per contract in input order, None where
+    /// supportsInterface reverted or returned no boolean.
+    pub async fn supports_interfaces_batch(
+        &self,
+        addresses: &[String],
+        interface_ids: &[[u8; 4]],
+    ) -> Result<Vec<Vec<Option<bool>>>> {
+        let mut calls = Vec::with_capacity(addresses.len() * interface_ids.len());
+        for address in addresses {
+            let contract = address
+                .parse::<H160>()
+                .context(format!("Invalid contract address: {}", address))?;
+            for interface_id in interface_ids {
+                calls.push((contract, encode_supports_interface_call(*interface_id)));
+            }
+        }
+
+        let results = self.multicall(&calls, None).await?;
+
+        Ok(results
+            .chunks(interface_ids.len().max(1))
+            .map(|results| {
+                results
+                    .iter()
+                    .map(|result| result.as_deref().and_then(decode_bool))
+                    .collect()
+            })
+            .collect())
     }
 
     /// Get the latest block number
@@ -101,6 +640,18 @@ impl RpcClient {
         }
     }
 
+    /// Get the header of a block by number, with transaction hashes instead of transactions
+    pub async fn get_block_header(&self, number: u64) -> Result<Option<EthBlock<H256>>> {
+        match self
+            .executor
+            .execute(EthRpcOperation::GetBlockHeader(number))
+            .await?
+        {
+            EthRpcResponse::BlockHeader(block) => Ok(block),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
+    }
+
     /// Get block by hash
     pub async fn get_block_by_hash(&self, hash: &str) -> Result<Option<EthBlock<EthTransaction>>> {
         let hash = H256::from_str(hash).context(format!("Invalid block hash: {}", hash))?;
@@ -129,6 +680,205 @@ impl RpcClient {
         }
     }
 
+    /// Get all receipts of a block with a single eth_getBlockReceipts call.
+    /// Returns None when the node does not implement the method, so callers can
+    /// fall back to per-transaction receipt requests.
+    pub async fn get_block_receipts(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<TransactionReceipt>>> {
+        if !self.block_receipts_supported.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        match self
+            .executor
+            .execute(EthRpcOperation::GetBlockReceipts(block_number))
+            .await
+        {
+            Ok(EthRpcResponse::BlockReceipts(receipts)) => Ok(Some(receipts)),
+            Ok(_) => Err(anyhow::anyhow!("Unexpected response type")),
+            Err(e) if is_unsupported_method_error(&e) => {
+                if self.block_receipts_supported.swap(false, Ordering::Relaxed) {
+                    warn!(
+                        "RPC node does not support eth_getBlockReceipts ({:#}), fetching receipts per transaction",
+                        e
+                    );
+                }
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the call tree of every transaction of a block, in block order, with
+    /// debug_traceBlockByNumber and the callTracer. Returns None when the node does not
+    /// implement the method or no longer has the state of the block.
+    pub async fn trace_block_calls(&self, block_number: u64) -> Result<Option<Vec<CallFrame>>> {
+        if !self.call_traces_supported.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        match self
+            .executor
+            .execute(EthRpcOperation::TraceBlockCalls(block_number))
+            .await
+        {
+            Ok(EthRpcResponse::BlockCallTraces(frames)) => Ok(Some(frames)),
+            Ok(_) => Err(anyhow::anyhow!("Unexpected response type")),
+            Err(e) if is_unsupported_method_error(&e) => {
+                if self.call_traces_supported.swap(false, Ordering::Relaxed) {
+                    warn!(
+                        "RPC node does not support debug_traceBlockByNumber ({:#}), contracts deployed by contracts are not indexed",
+                        e
+                    );
+                }
+                Ok(None)
+            }
+            Err(e) if is_missing_state_error(&e) => {
+                debug!(
+                    "State of block {} is pruned, not tracing it: {:#}",
+                    block_number, e
+                );
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get a transaction by hash
+    pub async fn get_transaction(&self, tx_hash: &str) -> Result<Option<EthTransaction>> {
+        match self
+            .executor
+            .execute(EthRpcOperation::GetTransaction(tx_hash.to_string()))
+            .await?
+        {
+            EthRpcResponse::Transaction(transaction) => Ok(transaction),
+            _ => Err(anyhow::anyhow!("Unexpected response type")),
+        }
+    }
+
+    /// Get the pending transactions of the node's txpool with txpool_content.
+    /// Returns None when the node does not expose the txpool namespace, so callers
+    /// can fall back to a pending transaction filter.
+    pub async fn get_txpool_pending(&self) -> Result<Option<Vec<EthTransaction>>> {
+        if !self.txpool_supported.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        match self
+            .provider
+            .txpool_content()
+            .await
+            .context("Failed to get txpool content")
+        {
+            Ok(content) => Ok(Some(
+                content
+                    .pending
+                    .into_values()
+                    .flat_map(|by_nonce| by_nonce.into_values())
+                    .collect(),
+            )),
+            Err(e) if is_unsupported_method_error(&e) => {
+                if self.txpool_supported.swap(false, Ordering::Relaxed) {
+                    warn!(
+                        "RPC node does not support txpool_content ({:#}), polling a pending transaction filter",
+                        e
+                    );
+                }
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Install a filter for new pending transaction hashes
+    pub async fn new_pending_transaction_filter(&self) -> Result<U256> {
+        self.provider
+            .new_filter(FilterKind::PendingTransactions)
+            .await
+            .context("Failed to create pending transaction filter")
+    }
+
+    /// Get the pending transaction hashes seen by a filter since its last poll
+    pub async fn get_pending_transaction_hashes(&self, filter_id: U256) -> Result<Vec<H256>> {
+        self.provider
+            .get_filter_changes(filter_id)
+            .await
+            .context("Failed to poll pending transaction filter")
+    }
+
+    /// Reproduce the revert reason of a failed transaction by replaying it with eth_call
+    /// on top of its parent block. Returns None when the replay does not revert, which
+    /// happens when the failure depended on earlier transactions of the same block.
+    pub async fn get_revert_reason(&self, tx_hash: &str) -> Result<Option<String>> {
+        let tx = self
+            .get_transaction(tx_hash)
+            .await?
+            .context(format!("Transaction not found: {}", tx_hash))?;
+        let block_number = tx
+            .block_number
+            .context(format!("Transaction {} is not mined", tx_hash))?;
+
+        let mut request = TransactionRequest::new()
+            .from(tx.from)
+            .value(tx.value)
+            .gas(tx.gas)
+            .data(tx.input.clone());
+        if let Some(to) = tx.to {
+            request = request.to(to);
+        }
+
+        let parent = ethers::core::types::BlockId::Number(BlockNumber::Number(
+            block_number.saturating_sub(U64::one()),
+        ));
+        match self.provider.call(&request.into(), Some(parent)).await {
+            Ok(_) => Ok(None),
+            Err(e) => {
+                let reason = e
+                    .as_error_response()
+                    .and_then(|error| error.as_revert_data())
+                    .and_then(|data| decode_revert_data(&data))
+                    .or_else(|| {
+                        e.as_error_response().map(|error| {
+                            error
+                                .message
+                                .trim_start_matches("execution reverted: ")
+                                .to_string()
+                        })
+                    });
+                match reason {
+                    Some(reason) => Ok(Some(reason)),
+                    None => Err(e).context(format!("Failed to replay transaction {}", tx_hash)),
+                }
+            }
+        }
+    }
+
+    /// Resolve an ENS name to an address, None when the name does not resolve
+    pub async fn resolve_ens_name(&self, name: &str) -> Result<Option<String>> {
+        match self.provider.resolve_name(name).await {
+            Ok(address) if address.is_zero() => Ok(None),
+            Ok(address) => Ok(Some(format!("{:#x}", address))),
+            Err(ProviderError::EnsError(_)) => Ok(None),
+            Err(e) => Err(e).context(format!("Failed to resolve ENS name: {}", name)),
+        }
+    }
+
+    /// Get the primary ENS name of an address, verified by forward resolution
+    pub async fn lookup_ens_name(&self, address: &str) -> Result<Option<String>> {
+        let addr = address
+            .parse::<H160>()
+            .context(format!("Invalid Ethereum address: {}", address))?;
+
+        match self.provider.lookup_address(addr).await {
+            Ok(name) if name.is_empty() => Ok(None),
+            Ok(name) => Ok(Some(name)),
+            Err(ProviderError::EnsError(_)) | Err(ProviderError::EnsNotOwned(_)) => Ok(None),
+            Err(e) => Err(e).context(format!("Failed to look up ENS name of {}", address)),
+        }
+    }
+
     /// Get account balance
     pub async fn get_balance(&self, address: &str, block_number: Option<u64>) -> Result<String> {
         let address = address
@@ -192,7 +942,7 @@ impl RpcClient {
         data.extend_from_slice(&[0u8; 12]); // 12 bytes of padding
         data.extend_from_slice(account.as_bytes()); // 20 bytes address
 
-        let block_id = match block_number {
+        let block_id = match block_number.filter(|_| self.is_archive_node()) {
             Some(num) => Some(ethers::core::types::BlockId::Number(BlockNumber::Number(
                 U64::from(num),
             ))),
@@ -344,6 +1094,28 @@ impl RpcClient {
         String::from_utf8(string_bytes.to_vec()).context("Failed to decode UTF-8 string")
     }
 
+    /// Get the metadata URI of an ERC-721 token using tokenURI(uint256), None when the
+    /// call reverts or returns no string
+    pub async fn get_token_uri(
+        &self,
+        token_address: &str,
+        token_id: U256,
+    ) -> Result<Option<String>> {
+        let token_contract = token_address
+            .parse::<H160>()
+            .context(format!("Invalid token contract address: {}", token_address))?;
+
+        let mut data = selector("tokenURI(uint256)").to_vec();
+        let mut encoded_id = [0u8; 32];
+        token_id.to_big_endian(&mut encoded_id);
+        data.extend_from_slice(&encoded_id);
+
+        match self.call(token_contract, data, None).await {
+            Ok(result) => Ok(decode_string(&result)),
+            Err(_) => Ok(None),
+        }
+    }
+
     /// Check connection to RPC
     pub async fn check_connection(&self) -> Result<bool> {
         match self
@@ -363,6 +1135,17 @@ impl RpcClient {
         }
     }
 
+    /// Get the chain id of the network
+    pub async fn get_chain_id(&self) -> Result<u64> {
+        let chain_id = self
+            .provider
+            .get_chainid()
+            .await
+            .context("Failed to get chain id")?;
+
+        Ok(chain_id.as_u64())
+    }
+
     /// Get bytecode at an address
     pub async fn get_code(&self, address: &str, block_number: Option<u64>) -> Result<String> {
         let addr = address
@@ -387,3 +1170,71 @@ impl RpcClient {
         Ok(format!("0x{}", hex::encode(code)))
     }
 }
+
+/// Human readable form of revert data: the message of Error(string), the code of
+/// Panic(uint256) or the selector of a custom error
+fn decode_revert_data(data: &[u8]) -> Option<String> {
+    const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+    const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+    if data.is_empty() {
+        return Some("execution reverted".to_string());
+    }
+    if data.len() < 4 {
+        return None;
+    }
+
+    let (selector, args) = data.split_at(4);
+    if selector == ERROR_SELECTOR {
+        let tokens = ethers::abi::decode(&[ethers::abi::ParamType::String], args).ok()?;
+        return tokens.into_iter().next()?.into_string();
+    }
+    if selector == PANIC_SELECTOR {
+        let tokens = ethers::abi::decode(&[ethers::abi::ParamType::Uint(256)], args).ok()?;
+        return Some(format!(
+            "Panic(0x{:x})",
+            tokens.into_iter().next()?.into_uint()?
+        ));
+    }
+
+    Some(format!("custom error 0x{}", hex::encode(selector)))
+}
+
+/// Whether an RPC error means the node does not implement the requested method: the
+/// JSON-RPC "method not found" code -32601, or the wording nodes use for it, like geth's
+/// "the method debug_traceBlockByNumber does not exist/is not available"
+pub fn is_unsupported_method_error(error: &anyhow::Error) -> bool {
+    let code = error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<ProviderError>())
+        .find_map(|e| e.as_error_response())
+        .map(|response| response.code);
+    if code == Some(-32601) {
+        return true;
+    }
+
+    let message = format!("{:#}", error).to_lowercase();
+    ["-32601", "method not found", "unsupported method"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+        || message.match_indices("method ").any(|(start, pattern)| {
+            message[start + pattern.len()..]
+                .split_once(' ')
+                .is_some_and(|(method, rest)| {
+                    !method.is_empty() && rest.starts_with("does not exist")
+                })
+        })
+}
+
+/// Whether an RPC error means the node has pruned the requested state. Only the wording
+/// nodes use for missing state counts, so timeouts or rate limits never downgrade a node.
+pub fn is_missing_state_error(error: &anyhow::Error) -> bool {
+    let message = format!("{:#}", error).to_lowercase();
+    [
+        "missing trie node",
+        "state not available",
+        "state is not available",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
@@ -0,0 +1,432 @@
+use super::request_id::current_request_id;
+#[cfg(feature = "chaos")]
+use crate::chaos::FaultInjector;
+use crate::events::{EventLog, IndexerEventKind, NewIndexerEvent};
+use crate::executor::RateLimitFeedback;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Consecutive transport failures after which a provider is benched
+const MAX_CONSECUTIVE_FAILURES: u64 = 3;
+
+/// How long a benched provider is skipped before it is tried again
+const BENCH_DURATION: Duration = Duration::from_secs(30);
+
+/// JSON-RPC error codes providers use for rate limiting
+const RATE_LIMIT_CODES: [i64; 2] = [429, -32005];
+
+/// Health counters of a single RPC provider
+#[derive(Debug)]
+struct Endpoint {
+    transport: Http,
+    label: String, // Scheme and host only, URLs often embed API keys
+    requests: AtomicU64,
+    failures: AtomicU64,
+    consecutive_failures: AtomicU64,
+    latency_ms: AtomicU64, // Moving average of successful request latency
+    benched_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_benched(&self) -> bool {
+        self.benched_until
+            .lock()
+            .unwrap()
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    fn record_success(&self, latency: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+
+        let sample = latency.as_millis() as u64;
+        let previous = self.latency_ms.load(Ordering::Relaxed);
+        let average = if previous == 0 {
+            sample
+        } else {
+            (previous * 4 + sample) / 5
+        };
+        self.latency_ms.store(average, Ordering::Relaxed);
+    }
+
+    /// Record a failed request, returns whether the provider just got benched
+    fn record_failure(&self) -> bool {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        let consecutive = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if consecutive < MAX_CONSECUTIVE_FAILURES {
+            return false;
+        }
+
+        let mut benched_until = self.benched_until.lock().unwrap();
+        let newly_benched = !benched_until.is_some_and(|until| Instant::now() < until);
+        *benched_until = Some(Instant::now() + BENCH_DURATION);
+        newly_benched
+    }
+
+    /// Success rate in percent, 100 for providers that were never used
+    fn health_score(&self) -> f64 {
+        let requests = self.requests.load(Ordering::Relaxed);
+        if requests == 0 {
+            return 100.0;
+        }
+        let failures = self.failures.load(Ordering::Relaxed);
+        (requests - failures) as f64 * 100.0 / requests as f64
+    }
+}
+
+/// Per-provider statistics exposed by the health endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderStats {
+    pub url: String,
+    pub requests: u64,
+    pub failures: u64,
+    pub consecutive_failures: u64,
+    pub avg_latency_ms: u64,
+    pub health_score: f64,
+    pub available: bool,
+}
+
+/// Requests of a JSON-RPC method, split by what triggered them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MethodUsage {
+    pub api_requests: u64,      // Made while serving an API request
+    pub indexing_requests: u64, // Made by the indexer and background jobs
+}
+
+/// A request made on behalf of a traced request ID, one per provider tried
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcCall {
+    pub method: String,
+    pub provider: String,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// JSON-RPC transport spreading requests over several HTTP providers.
+///
+/// Requests go to the healthiest available provider; transport errors and rate limits
+/// fail over to the next one. Providers failing repeatedly are benched for a while so
+/// a single flaky endpoint does not stall indexing.
+#[derive(Debug)]
+pub struct FallbackTransport {
+    endpoints: Vec<Endpoint>,
+    events: EventLog,
+    rate_limits: Arc<RateLimitFeedback>, // Told when every provider tried rate limited a request
+    usage: Mutex<BTreeMap<String, MethodUsage>>, // Requests per method, failovers counted once
+    traces: Mutex<HashMap<Arc<str>, Vec<RpcCall>>>, // Calls of the request IDs being traced
+    #[cfg(feature = "chaos")]
+    faults: std::sync::OnceLock<Arc<FaultInjector>>, // Fails requests in resilience tests
+}
+
+impl FallbackTransport {
+    /// Create a transport from a comma-separated list of HTTP RPC URLs, reporting requests
+    /// no provider would serve because of rate limits to `rate_limits`
+    pub fn new(
+        rpc_urls: &str,
+        events: EventLog,
+        rate_limits: Arc<RateLimitFeedback>,
+    ) -> Result<Self> {
+        let endpoints = rpc_urls
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .enumerate()
+            .map(|(index, url)| {
+                let transport =
+                    Http::from_str(url).context(format!("Invalid RPC URL #{}", index + 1))?;
+                let label = format!(
+                    "#{} {}://{}",
+                    index + 1,
+                    transport.url().scheme(),
+                    transport.url().host_str().unwrap_or_default()
+                );
+
+                Ok(Endpoint {
+                    transport,
+                    label,
+                    requests: AtomicU64::new(0),
+                    failures: AtomicU64::new(0),
+                    consecutive_failures: AtomicU64::new(0),
+                    latency_ms: AtomicU64::new(0),
+                    benched_until: Mutex::new(None),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if endpoints.is_empty() {
+            return Err(anyhow::anyhow!("No RPC URL configured"));
+        }
+
+        Ok(Self {
+            endpoints,
+            events,
+            rate_limits,
+            usage: Mutex::new(BTreeMap::new()),
+            traces: Mutex::new(HashMap::new()),
+            #[cfg(feature = "chaos")]
+            faults: Default::default(),
+        })
+    }
+
+    /// Fail, delay or corrupt a share of requests from now on, for resilience tests
+    #[cfg(feature = "chaos")]
+    pub fn set_fault_injector(&self, faults: Arc<FaultInjector>) {
+        let _ = self.faults.set(faults);
+    }
+
+    /// Provider statistics in configuration order
+    pub fn stats(&self) -> Vec<ProviderStats> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| ProviderStats {
+                url: endpoint.label.clone(),
+                requests: endpoint.requests.load(Ordering::Relaxed),
+                failures: endpoint.failures.load(Ordering::Relaxed),
+                consecutive_failures: endpoint.consecutive_failures.load(Ordering::Relaxed),
+                avg_latency_ms: endpoint.latency_ms.load(Ordering::Relaxed),
+                health_score: endpoint.health_score(),
+                available: !endpoint.is_benched(),
+            })
+            .collect()
+    }
+
+    /// Requests per JSON-RPC method, split between API traffic and indexing
+    pub fn usage(&self) -> BTreeMap<String, MethodUsage> {
+        self.usage.lock().unwrap().clone()
+    }
+
+    /// Count a request towards the API or indexing usage of its method
+    fn record_usage(&self, method: &str, from_api: bool) {
+        let mut usage = self.usage.lock().unwrap();
+        let entry = usage.entry(method.to_string()).or_default();
+        if from_api {
+            entry.api_requests += 1;
+        } else {
+            entry.indexing_requests += 1;
+        }
+    }
+
+    /// Start recording every request made on behalf of `request_id`
+    pub fn start_trace(&self, request_id: Arc<str>) {
+        self.traces.lock().unwrap().insert(request_id, Vec::new());
+    }
+
+    /// Stop recording the requests of `request_id`, returning them in the order they ended
+    pub fn finish_trace(&self, request_id: &str) -> Vec<RpcCall> {
+        self.traces
+            .lock()
+            .unwrap()
+            .remove(request_id)
+            .unwrap_or_default()
+    }
+
+    /// Record a request of a traced request ID
+    fn record_call(
+        &self,
+        request_id: &Option<Arc<str>>,
+        method: &str,
+        endpoint: &Endpoint,
+        started: Instant,
+        error: Option<&HttpClientError>,
+    ) {
+        let Some(request_id) = request_id else {
+            return;
+        };
+        if let Some(calls) = self.traces.lock().unwrap().get_mut(request_id) {
+            calls.push(RpcCall {
+                method: method.to_string(),
+                provider: endpoint.label.clone(),
+                duration_ms: started.elapsed().as_millis() as u64,
+                error: error.map(ToString::to_string),
+            });
+        }
+    }
+
+    /// Send a request to one provider, through the fault injector when one is set
+    async fn send<R: DeserializeOwned + Send>(
+        &self,
+        endpoint: &Endpoint,
+        method: &str,
+        params: &Value,
+    ) -> Result<R, HttpClientError> {
+        #[cfg(feature = "chaos")]
+        if let Some(faults) = self.faults.get() {
+            if let Some(delay) = faults.rpc_delay() {
+                tokio::time::sleep(delay).await;
+            }
+            if faults.rpc_error() {
+                return Err(HttpClientError::SerdeJson {
+                    err: serde::de::Error::custom("injected RPC failure"),
+                    text: String::new(),
+                });
+            }
+
+            let mut result: Value = endpoint.transport.request(method, params).await?;
+            faults.malform_receipts(method, &mut result);
+            return serde_json::from_value(result.clone()).map_err(|err| {
+                HttpClientError::SerdeJson {
+                    err,
+                    text: result.to_string(),
+                }
+            });
+        }
+
+        endpoint.transport.request(method, params).await
+    }
+
+    /// Endpoint indexes in the order they should be tried: available providers by
+    /// consecutive failures then latency, benched providers last as a final resort
+    fn ranked_endpoints(&self) -> Vec<usize> {
+        let mut ranked: Vec<usize> = (0..self.endpoints.len()).collect();
+        ranked.sort_by_key(|&index| {
+            let endpoint = &self.endpoints[index];
+            (
+                endpoint.is_benched(),
+                endpoint.consecutive_failures.load(Ordering::Relaxed),
+                endpoint.latency_ms.load(Ordering::Relaxed),
+                index,
+            )
+        });
+        ranked
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for FallbackTransport {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, HttpClientError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        // Serialize once so the same params can be replayed against every provider
+        let params = serde_json::to_value(params).map_err(|err| HttpClientError::SerdeJson {
+            err,
+            text: String::new(),
+        })?;
+
+        let request_id = current_request_id();
+        self.record_usage(method, request_id.is_some());
+
+        let mut last_error = None;
+        let mut rate_limited = None; // Longest Retry-After of the providers rate limiting
+        for index in self.ranked_endpoints() {
+            let endpoint = &self.endpoints[index];
+            let started = Instant::now();
+
+            match self.send(endpoint, method, &params).await {
+                Ok(result) => {
+                    endpoint.record_success(started.elapsed());
+                    self.record_call(&request_id, method, endpoint, started, None);
+                    if let Some(request_id) = &request_id {
+                        debug!(
+                            "RPC {} for API request {} took {}ms on {}",
+                            method,
+                            request_id,
+                            started.elapsed().as_millis(),
+                            endpoint.label
+                        );
+                    }
+                    return Ok(result);
+                }
+                Err(e) if is_provider_fault(&e) => {
+                    self.record_call(&request_id, method, endpoint, started, Some(&e));
+                    if let Some(retry_after) = rate_limit(&e) {
+                        rate_limited = Some(retry_after.max(rate_limited.flatten()));
+                    }
+                    if endpoint.record_failure() {
+                        self.events.record(
+                            NewIndexerEvent::warning(
+                                IndexerEventKind::RpcFailover,
+                                format!(
+                                    "RPC provider {} benched for {}s after {} consecutive failures",
+                                    endpoint.label,
+                                    BENCH_DURATION.as_secs(),
+                                    MAX_CONSECUTIVE_FAILURES
+                                ),
+                            )
+                            .with_details(json!({
+                                "provider": endpoint.label,
+                                "method": method,
+                                "request_id": request_id.as_deref(),
+                                "error": e.to_string(),
+                            })),
+                        );
+                    }
+                    if self.endpoints.len() > 1 {
+                        warn!(
+                            "RPC provider {} failed on {}: {}, trying next provider",
+                            endpoint.label, method, e
+                        );
+                    }
+                    last_error = Some(e);
+                }
+                Err(e) => {
+                    // The provider answered, the request itself was rejected
+                    endpoint.record_success(started.elapsed());
+                    self.record_call(&request_id, method, endpoint, started, Some(&e));
+                    return Err(e);
+                }
+            }
+        }
+
+        if let Some(retry_after) = rate_limited {
+            self.rate_limits.report(retry_after);
+        }
+        Err(last_error.expect("at least one RPC provider is configured"))
+    }
+}
+
+/// Whether an error points at the provider (unreachable, rate limited, garbage response)
+/// rather than at the request, so another provider may succeed
+fn is_provider_fault(error: &HttpClientError) -> bool {
+    match error {
+        HttpClientError::JsonRpcError(_) => rate_limit(error).is_some(),
+        _ => true,
+    }
+}
+
+/// Whether an error is a rate limit, with the delay the provider asked to wait if any.
+/// The HTTP transport hides response headers, so the delay comes from the error data.
+fn rate_limit(error: &HttpClientError) -> Option<Option<Duration>> {
+    match error {
+        HttpClientError::JsonRpcError(e)
+            if RATE_LIMIT_CODES.contains(&e.code)
+                || e.message.to_lowercase().contains("rate limit") =>
+        {
+            Some(e.data.as_ref().and_then(retry_after_hint))
+        }
+        // Plain HTTP 429 responses have no JSON-RPC body
+        HttpClientError::SerdeJson { text, .. }
+            if text.to_lowercase().contains("too many requests") =>
+        {
+            Some(None)
+        }
+        _ => None,
+    }
+}
+
+/// Backoff in the data of a rate limit error: `rate.backoff_seconds` (Infura) or
+/// `retry_after` seconds
+fn retry_after_hint(data: &Value) -> Option<Duration> {
+    data.pointer("/rate/backoff_seconds")
+        .or_else(|| data.get("retry_after"))
+        .and_then(Value::as_f64)
+        .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+        .map(Duration::from_secs_f64)
+}
@@ -1,3 +1,7 @@
 mod client;
+mod fallback;
+pub mod multicall;
+pub mod request_id;
 
 pub use client::*;
+pub use fallback::*;
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    core::types::{H160, U256},
+    utils::keccak256,
+};
+
+/// Canonical Multicall3 deployment (same address on mainnet and most EVM chains)
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// A single call to be aggregated: target contract and ABI-encoded calldata
+pub type MulticallCall = (H160, Vec<u8>);
+
+/// First 4 bytes of keccak256 of a function signature
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Encode a call taking a single address argument (balanceOf, getEthBalance, ...)
+pub fn encode_address_call(signature: &str, address: H160) -> Vec<u8> {
+    let mut data = selector(signature).to_vec();
+    data.extend(encode(&[Token::Address(address)]));
+    data
+}
+
+/// Encode an ERC-165 `supportsInterface(bytes4)` call
+pub fn encode_supports_interface_call(interface_id: [u8; 4]) -> Vec<u8> {
+    let mut data = selector("supportsInterface(bytes4)").to_vec();
+    data.extend(encode(&[Token::FixedBytes(interface_id.to_vec())]));
+    data
+}
+
+/// Encode `aggregate3((address,bool,bytes)[])` with every call allowed to fail
+pub fn encode_aggregate3(calls: &[MulticallCall]) -> Vec<u8> {
+    let calls = Token::Array(
+        calls
+            .iter()
+            .map(|(target, data)| {
+                Token::Tuple(vec![
+                    Token::Address(*target),
+                    Token::Bool(true),
+                    Token::Bytes(data.clone()),
+                ])
+            })
+            .collect(),
+    );
+
+    let mut data = selector("aggregate3((address,bool,bytes)[])").to_vec();
+    data.extend(encode(&[calls]));
+    data
+}
+
+/// Decode the `(bool success, bytes returnData)[]` result of aggregate3.
+/// Failed calls are returned as None.
+pub fn decode_aggregate3(data: &[u8]) -> Result<Vec<Option<Vec<u8>>>> {
+    let result_type = ParamType::Array(Box::new(ParamType::Tuple(vec![
+        ParamType::Bool,
+        ParamType::Bytes,
+    ])));
+
+    let tokens = decode(&[result_type], data).context("Failed to decode aggregate3 result")?;
+    let results = match tokens.into_iter().next() {
+        Some(Token::Array(results)) => results,
+        _ => return Err(anyhow::anyhow!("Unexpected aggregate3 result layout")),
+    };
+
+    results
+        .into_iter()
+        .map(|result| match result {
+            Token::Tuple(fields) => match fields.as_slice() {
+                [Token::Bool(success), Token::Bytes(return_data)] => {
+                    Ok(success.then(|| return_data.clone()))
+                }
+                _ => Err(anyhow::anyhow!("Unexpected aggregate3 result entry")),
+            },
+            _ => Err(anyhow::anyhow!("Unexpected aggregate3 result entry")),
+        })
+        .collect()
+}
+
+/// Decode a single uint256 return value
+pub fn decode_uint(data: &[u8]) -> Option<U256> {
+    if data.len() >= 32 {
+        Some(U256::from_big_endian(&data[..32]))
+    } else {
+        None
+    }
+}
+
+/// Decode a bool return value, None for anything but an encoded true or false
+pub fn decode_bool(data: &[u8]) -> Option<bool> {
+    match decode_uint(data)? {
+        value if value.is_zero() => Some(false),
+        value if value == U256::one() => Some(true),
+        _ => None,
+    }
+}
+
+/// Decode a string return value, also accepting the bytes32 encoding used by some older
+/// tokens (e.g. MKR). Returns None for empty or undecodable data.
+pub fn decode_string(data: &[u8]) -> Option<String> {
+    if let Ok(tokens) = decode(&[ParamType::String], data)
+        && let Some(Token::String(value)) = tokens.into_iter().next()
+    {
+        return Some(value).filter(|value| !value.is_empty());
+    }
+
+    if data.len() == 32 {
+        let value: Vec<u8> = data.iter().copied().take_while(|byte| *byte != 0).collect();
+        return String::from_utf8(value)
+            .ok()
+            .filter(|value| !value.is_empty());
+    }
+
+    None
+}
+
+/// Decode a decimals() return value, None when it does not fit in a u8
+pub fn decode_decimals(data: &[u8]) -> Option<u8> {
+    decode_uint(data)
+        .filter(|decimals| *decimals <= U256::from(u8::MAX))
+        .map(|decimals| decimals.as_u32() as u8)
+}
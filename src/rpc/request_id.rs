@@ -0,0 +1,25 @@
+//! Attribution of RPC calls to the API request that triggered them.
+//!
+//! The API assigns every request an ID and runs its handler inside [`with_request_id`].
+//! RPC calls made while serving it read the ID back with [`current_request_id`], so their
+//! logs and usage counters can tell API traffic apart from indexing. Calls without an ID
+//! come from the indexer and background jobs.
+
+use std::{future::Future, sync::Arc};
+
+tokio::task_local! {
+    static REQUEST_ID: Arc<str>;
+}
+
+/// Run a future on behalf of the API request `request_id`, as is when None
+pub async fn with_request_id<F: Future>(request_id: Option<Arc<str>>, future: F) -> F::Output {
+    match request_id {
+        Some(request_id) => REQUEST_ID.scope(request_id, future).await,
+        None => future.await,
+    }
+}
+
+/// ID of the API request the current task serves
+pub fn current_request_id() -> Option<Arc<str>> {
+    REQUEST_ID.try_with(Arc::clone).ok()
+}
@@ -0,0 +1,112 @@
+use crate::{
+    config::AppConfig,
+    database::DatabaseService,
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+    pruning::block_tables,
+};
+use anyhow::Result;
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Job moving finalized block ranges out of the main database into their shard files.
+///
+/// The main database keeps the shard the head is in, plus the previous one until the head
+/// is `database_shard_confirmations` blocks into the new shard, so reorgs are always
+/// handled in the main file. Blocks are moved together with everything stored for them,
+/// one batch per transaction.
+pub struct ShardingService {
+    db: Arc<DatabaseService>,
+    events: EventLog,
+    config: AppConfig,
+}
+
+impl ShardingService {
+    /// Create a new sharding service
+    pub fn new(db: Arc<DatabaseService>, events: EventLog, config: AppConfig) -> Self {
+        Self { db, events, config }
+    }
+
+    /// Start shard rotation in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Shard rotation job starting");
+            let mut interval = time::interval(Duration::from_secs(
+                service.config.database_shard_interval_seconds,
+            ));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.run().await {
+                    warn!("Shard rotation failed: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Move every block below the finalized head's shard into its shard file
+    pub async fn run(&self) -> Result<()> {
+        if self.db.shard_blocks() == 0 {
+            return Ok(());
+        }
+        let Some(latest) = self.db.get_latest_block_number().await? else {
+            return Ok(());
+        };
+        let cutoff = self
+            .db
+            .shard_start(latest - self.config.database_shard_confirmations);
+
+        let mut moved: BTreeMap<String, u64> = BTreeMap::new();
+        let mut shards = BTreeSet::new();
+        // Moved blocks leave the main database, so the lowest block skips over gaps
+        while let Some(from) = self.db.get_lowest_block_number_in("blocks").await?
+            && from < cutoff
+        {
+            let shard = self.db.shard_start(from);
+            let to = (from + self.config.prune_batch_blocks.max(1))
+                .min(shard + self.db.shard_blocks())
+                .min(cutoff);
+            let rows = self
+                .db
+                .move_block_range_to_shard(&block_tables(), from, to)
+                .await?;
+            debug!(
+                "Moved blocks {}..{} to shard {}: {:?}",
+                from, to, shard, rows
+            );
+            for (table, count) in rows {
+                *moved.entry(table).or_default() += count;
+            }
+            shards.insert(shard);
+
+            // Let the indexer's writes through between batches
+            tokio::task::yield_now().await;
+        }
+
+        let total: u64 = moved.values().sum();
+        if total == 0 {
+            debug!("Shard rotation found nothing to move");
+            return Ok(());
+        }
+
+        info!("Moved {} rows to shards {:?}: {:?}", total, shards, moved);
+        self.events.record(
+            NewIndexerEvent::info(
+                IndexerEventKind::ShardRotated,
+                format!("Moved {} rows into {} shards", total, shards.len()),
+            )
+            .at_block(latest)
+            .with_details(json!({
+                "moved": moved,
+                "shards": shards,
+                "shard_blocks": self.db.shard_blocks(),
+            })),
+        );
+        Ok(())
+    }
+}
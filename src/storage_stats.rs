@@ -0,0 +1,110 @@
+//! Storage statistics.
+//!
+//! Once a day the row count and page sizes of every table are stored in
+//! `storage_snapshots`. The storage report compares the latest sample with the oldest one
+//! of a window to tell how fast each table grows, for disk capacity planning and pruning
+//! decisions. Shard files are not measured.
+
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, StorageGrowth, StorageSnapshot, TableStorage},
+};
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use std::{cmp::Reverse, collections::BTreeMap, sync::Arc};
+use tokio::time::{self, Duration};
+use tracing::{debug, info, warn};
+
+/// Job taking the daily storage sample
+pub struct StorageStatsService {
+    db: Arc<DatabaseService>,
+    config: AppConfig,
+}
+
+impl StorageStatsService {
+    /// Create a new storage statistics service
+    pub fn new(db: Arc<DatabaseService>, config: AppConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Start sampling in the background. Each check samples unless today's sample exists,
+    /// so restarts don't measure the database again.
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Storage statistics job starting");
+            let mut interval = time::interval(Duration::from_secs(
+                service.config.storage_stats_interval_seconds,
+            ));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.run().await {
+                    warn!("Storage sampling failed: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Take today's sample if it wasn't taken yet
+    pub async fn run(&self) -> Result<()> {
+        let today = Utc::now().date_naive().to_string();
+        if self.db.get_latest_storage_sample_date().await?.as_deref() == Some(today.as_str()) {
+            debug!("Storage already sampled on {}", today);
+            return Ok(());
+        }
+
+        let snapshots = self.db.record_storage_snapshot(&today).await?;
+        info!("Sampled storage of {} tables", snapshots.len());
+        Ok(())
+    }
+}
+
+/// Latest storage of every table in `snapshots` (oldest first), largest first, with its
+/// growth since its oldest sample
+pub fn storage_report(snapshots: &[StorageSnapshot]) -> Vec<TableStorage> {
+    let mut samples: BTreeMap<&str, (&StorageSnapshot, &StorageSnapshot)> = BTreeMap::new();
+    for snapshot in snapshots {
+        samples
+            .entry(&snapshot.table_name)
+            .and_modify(|(_, latest)| *latest = snapshot)
+            .or_insert((snapshot, snapshot));
+    }
+    let latest_date = snapshots
+        .last()
+        .map(|snapshot| snapshot.sampled_on.as_str());
+
+    let mut tables: Vec<TableStorage> = samples
+        .into_values()
+        // Tables dropped since are left out
+        .filter(|(_, latest)| Some(latest.sampled_on.as_str()) == latest_date)
+        .map(|(oldest, latest)| {
+            let total_bytes = latest.table_bytes + latest.index_bytes;
+            let days = match (
+                NaiveDate::parse_from_str(&oldest.sampled_on, "%Y-%m-%d"),
+                NaiveDate::parse_from_str(&latest.sampled_on, "%Y-%m-%d"),
+            ) {
+                (Ok(oldest), Ok(latest)) => (latest - oldest).num_days(),
+                _ => 0,
+            };
+            let growth = (days > 0).then(|| StorageGrowth {
+                since: oldest.sampled_on.clone(),
+                days,
+                rows_per_day: (latest.row_count - oldest.row_count) as f64 / days as f64,
+                bytes_per_day: (total_bytes - oldest.table_bytes - oldest.index_bytes) as f64
+                    / days as f64,
+            });
+
+            TableStorage {
+                table_name: latest.table_name.clone(),
+                row_count: latest.row_count,
+                table_bytes: latest.table_bytes,
+                index_bytes: latest.index_bytes,
+                total_bytes,
+                growth,
+            }
+        })
+        .collect();
+    tables.sort_by_key(|table| Reverse(table.total_bytes));
+    tables
+}
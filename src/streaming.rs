@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Messages buffered between the indexer and the broker publisher
+const STREAM_BUFFER_MESSAGES: usize = 50_000;
+
+/// Message waiting to be published
+#[derive(Debug)]
+struct BrokerMessage {
+    topic: String,
+    #[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+    key: String, // Block number or transaction hash, the Kafka partition key
+    payload: Vec<u8>,
+}
+
+/// Message broker indexed data is published to
+#[async_trait]
+trait BrokerSink: Send + Sync {
+    async fn publish(&self, message: &BrokerMessage) -> Result<()>;
+}
+
+struct NatsSink {
+    client: async_nats::Client,
+}
+
+#[async_trait]
+impl BrokerSink for NatsSink {
+    async fn publish(&self, message: &BrokerMessage) -> Result<()> {
+        self.client
+            .publish(message.topic.clone(), message.payload.clone().into())
+            .await
+            .context("Failed to publish to NATS")?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "kafka")]
+struct KafkaSink {
+    producer: rdkafka::producer::FutureProducer,
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait]
+impl BrokerSink for KafkaSink {
+    async fn publish(&self, message: &BrokerMessage) -> Result<()> {
+        let record = rdkafka::producer::FutureRecord::to(&message.topic)
+            .key(&message.key)
+            .payload(&message.payload);
+        self.producer
+            .send(record, std::time::Duration::from_secs(5))
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Failed to publish to Kafka: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Connect to the broker of a `nats://` or `kafka://` URL
+async fn connect(url: &str) -> Result<Box<dyn BrokerSink>> {
+    match url.split_once("://") {
+        Some(("nats", _)) => {
+            // Keeps reconnecting in the background, so a broker that is down at startup
+            // doesn't stop the indexer
+            let client = async_nats::ConnectOptions::new()
+                .retry_on_initial_connect()
+                .connect(url)
+                .await
+                .context(format!("Failed to connect to NATS at {}", url))?;
+            Ok(Box::new(NatsSink { client }))
+        }
+        #[cfg(feature = "kafka")]
+        Some(("kafka", brokers)) => {
+            let producer = rdkafka::ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("message.timeout.ms", "30000")
+                .create()
+                .context("Failed to create Kafka producer")?;
+            Ok(Box::new(KafkaSink { producer }))
+        }
+        #[cfg(not(feature = "kafka"))]
+        Some(("kafka", _)) => Err(anyhow::anyhow!(
+            "Kafka streaming requires building with --features kafka"
+        )),
+        _ => Err(anyhow::anyhow!(
+            "STREAM_BROKER_URL must start with nats:// or kafka://"
+        )),
+    }
+}
+
+/// Publishes indexed blocks, transactions and token transfers to a message broker topic
+/// per kind, as JSON.
+///
+/// Like the operational event log, messages are queued and published by a background
+/// task, so a slow or unreachable broker never holds up indexing. When the queue is full
+/// messages are dropped and counted.
+#[derive(Clone, Debug)]
+pub struct BrokerStream {
+    sender: mpsc::Sender<BrokerMessage>,
+    topic_prefix: String,
+    dropped: Arc<AtomicU64>,
+}
+
+impl BrokerStream {
+    /// Connect to the broker and start the publisher task
+    pub async fn connect(url: &str, topic_prefix: &str) -> Result<Self> {
+        let sink = connect(url).await?;
+        let (sender, mut receiver) = mpsc::channel::<BrokerMessage>(STREAM_BUFFER_MESSAGES);
+
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                if let Err(e) = sink.publish(&message).await {
+                    warn!("Failed to stream message to {}: {:#}", message.topic, e);
+                }
+            }
+        });
+
+        info!(
+            "Streaming indexed data to {} under {}.*",
+            url.split('@').next_back().unwrap_or(url),
+            topic_prefix
+        );
+        Ok(Self {
+            sender,
+            topic_prefix: topic_prefix.to_string(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Queue an item for the `<prefix>.<kind>` topic
+    pub fn publish<T: Serialize>(&self, kind: &str, key: String, item: &T) {
+        let payload = match serde_json::to_vec(item) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize {} for streaming: {}", kind, e);
+                return;
+            }
+        };
+
+        let message = BrokerMessage {
+            topic: format!("{}.{}", self.topic_prefix, kind),
+            key,
+            payload,
+        };
+        if self.sender.try_send(message).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!(
+                    "Broker stream queue full, {} messages dropped so far",
+                    dropped
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,535 @@
+use crate::{
+    config::AppConfig,
+    database::{DatabaseService, Token},
+    rpc::{RpcClient, TokenMetadata},
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::types::{H160, U256};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Wait before loading the token lists again after one of them failed to load
+const TOKEN_LIST_RETRY: Duration = Duration::from_secs(600);
+
+/// Most decimals a token amount can have, a uint256 holding 78 digits
+const MAX_TOKEN_DECIMALS: u8 = 77;
+
+/// Token metadata along with the resolver each field came from
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedTokenMetadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    pub name_source: Option<&'static str>,
+    pub symbol_source: Option<&'static str>,
+    pub decimals_source: Option<&'static str>,
+}
+
+impl ResolvedTokenMetadata {
+    /// Layer the fields provided by `source` over the ones resolved so far
+    fn apply(&mut self, metadata: TokenMetadata, source: &'static str) {
+        if let Some(name) = metadata.name {
+            self.name = Some(name);
+            self.name_source = Some(source);
+        }
+        if let Some(symbol) = metadata.symbol {
+            self.symbol = Some(symbol);
+            self.symbol_source = Some(source);
+        }
+        if let Some(decimals) = metadata.decimals {
+            self.decimals = Some(decimals);
+            self.decimals_source = Some(source);
+        }
+    }
+
+    /// Whether no resolver provided any field
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.symbol.is_none() && self.decimals.is_none()
+    }
+}
+
+/// A source of token metadata
+#[async_trait]
+pub trait TokenMetadataResolver: Send + Sync {
+    /// Provenance recorded for the fields this resolver provides
+    fn source(&self) -> &'static str;
+
+    /// Metadata of the given tokens, in input order. Fields the resolver knows nothing
+    /// about are left None.
+    async fn resolve_batch(&self, token_addresses: &[String]) -> Result<Vec<TokenMetadata>>;
+}
+
+/// Reads name(), symbol() and decimals() from the token contracts, through Multicall3
+/// when available
+pub struct OnChainResolver {
+    rpc: Arc<RpcClient>,
+    batch_size: usize, // Tokens per multicall request
+}
+
+impl OnChainResolver {
+    pub fn new(rpc: Arc<RpcClient>, config: &AppConfig) -> Self {
+        Self {
+            rpc,
+            batch_size: (config.multicall_batch_size / 3).max(1),
+        }
+    }
+
+    /// Three calls per token, calls that fail leave the field None
+    async fn resolve_individually(&self, token_address: &str) -> TokenMetadata {
+        TokenMetadata {
+            name: self.rpc.get_token_name(token_address).await.unwrap_or(None),
+            symbol: self
+                .rpc
+                .get_token_symbol(token_address)
+                .await
+                .unwrap_or(None),
+            decimals: self
+                .rpc
+                .get_token_decimals(token_address)
+                .await
+                .unwrap_or(None),
+        }
+    }
+
+    /// Drop fields no token would return: blank or control-character names and symbols,
+    /// and more decimals than an amount has digits
+    fn valid(metadata: TokenMetadata) -> TokenMetadata {
+        let text = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty() && !value.chars().any(char::is_control))
+        };
+
+        TokenMetadata {
+            name: text(metadata.name),
+            symbol: text(metadata.symbol),
+            decimals: metadata
+                .decimals
+                .filter(|decimals| *decimals <= MAX_TOKEN_DECIMALS),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenMetadataResolver for OnChainResolver {
+    fn source(&self) -> &'static str {
+        "on_chain"
+    }
+
+    async fn resolve_batch(&self, token_addresses: &[String]) -> Result<Vec<TokenMetadata>> {
+        let mut resolved = Vec::with_capacity(token_addresses.len());
+
+        if !self.rpc.multicall_enabled() {
+            for token_address in token_addresses {
+                resolved.push(self.resolve_individually(token_address).await);
+            }
+            return Ok(resolved.into_iter().map(Self::valid).collect());
+        }
+
+        for chunk in token_addresses.chunks(self.batch_size) {
+            match self.rpc.get_token_metadata_batch(chunk).await {
+                Ok(metadata) => resolved.extend(metadata),
+                Err(e) => {
+                    debug!(
+                        "Multicall token metadata fetch failed for {} tokens: {:#}, fetching individually",
+                        chunk.len(),
+                        e
+                    );
+                    for token_address in chunk {
+                        resolved.push(self.resolve_individually(token_address).await);
+                    }
+                }
+            }
+        }
+
+        Ok(resolved.into_iter().map(Self::valid).collect())
+    }
+}
+
+/// Token list in the Uniswap token list format
+#[derive(Debug, Deserialize)]
+pub struct TokenList {
+    pub name: String,
+    pub tokens: Vec<TokenListEntry>,
+}
+
+/// Token of a token list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenListEntry {
+    pub chain_id: u64,
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(rename = "logoURI")]
+    pub logo_uri: Option<String>,
+}
+
+/// Fetch a token list from an http(s) URL or read it from a local path
+pub async fn load_token_list(location: &str) -> Result<TokenList> {
+    let list = if location.starts_with("http://") || location.starts_with("https://") {
+        reqwest::get(location)
+            .await
+            .and_then(|response| response.error_for_status())
+            .context(format!("Failed to fetch token list {}", location))?
+            .json()
+            .await
+            .context(format!("Failed to parse token list {}", location))?
+    } else {
+        let content = tokio::fs::read_to_string(location)
+            .await
+            .context(format!("Failed to read token list {}", location))?;
+        serde_json::from_str(&content)
+            .context(format!("Failed to parse token list {}", location))?
+    };
+
+    Ok(list)
+}
+
+/// Token lists as last loaded
+struct LoadedTokenLists {
+    entries: Arc<HashMap<String, TokenMetadata>>, // Metadata by lowercase address
+    complete: bool,                               // Every list loaded
+    loaded_at: Instant,
+}
+
+/// Looks tokens up in the token lists of TOKEN_LIST_URLS, keeping only the entries of the
+/// indexed chain. Lists are loaded on first use, and again every TOKEN_LIST_RETRY while
+/// one of them fails to load.
+pub struct TokenListResolver {
+    rpc: Arc<RpcClient>,
+    locations: Vec<String>,
+    loaded: Mutex<Option<LoadedTokenLists>>,
+}
+
+impl TokenListResolver {
+    pub fn new(rpc: Arc<RpcClient>, locations: Vec<String>) -> Self {
+        Self {
+            rpc,
+            locations,
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// Entries of the lists, loading them when not loaded yet or due for a retry
+    async fn entries(&self) -> Result<Arc<HashMap<String, TokenMetadata>>> {
+        let mut loaded = self.loaded.lock().await;
+        if let Some(lists) = loaded.as_ref()
+            && (lists.complete || lists.loaded_at.elapsed() < TOKEN_LIST_RETRY)
+        {
+            return Ok(lists.entries.clone());
+        }
+
+        match self.load().await {
+            Ok((entries, complete)) => {
+                let entries = Arc::new(entries);
+                *loaded = Some(LoadedTokenLists {
+                    entries: entries.clone(),
+                    complete,
+                    loaded_at: Instant::now(),
+                });
+                Ok(entries)
+            }
+            // Lists loaded before keep serving until the next retry
+            Err(e) => match loaded.as_mut() {
+                Some(lists) => {
+                    warn!("Failed to reload token lists: {:#}", e);
+                    lists.loaded_at = Instant::now();
+                    Ok(lists.entries.clone())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Load every configured list, skipping lists that fail to load, and tell whether all
+    /// of them loaded. Earlier lists win when several list the same token.
+    async fn load(&self) -> Result<(HashMap<String, TokenMetadata>, bool)> {
+        let chain_id = self.rpc.get_chain_id().await?;
+        let mut entries = HashMap::new();
+        let mut complete = true;
+
+        for location in &self.locations {
+            let list = match load_token_list(location).await {
+                Ok(list) => list,
+                Err(e) => {
+                    warn!("Skipping token list: {:#}", e);
+                    complete = false;
+                    continue;
+                }
+            };
+
+            let before = entries.len();
+            for token in list.tokens {
+                if token.chain_id != chain_id {
+                    continue;
+                }
+                entries
+                    .entry(token.address.to_lowercase())
+                    .or_insert(TokenMetadata {
+                        name: Some(token.name),
+                        symbol: Some(token.symbol),
+                        decimals: Some(token.decimals),
+                    });
+            }
+
+            info!(
+                "Loaded {} tokens of chain {} from token list {}",
+                entries.len() - before,
+                chain_id,
+                list.name
+            );
+        }
+
+        Ok((entries, complete))
+    }
+}
+
+#[async_trait]
+impl TokenMetadataResolver for TokenListResolver {
+    fn source(&self) -> &'static str {
+        "token_list"
+    }
+
+    async fn resolve_batch(&self, token_addresses: &[String]) -> Result<Vec<TokenMetadata>> {
+        let entries = self.entries().await?;
+
+        Ok(token_addresses
+            .iter()
+            .map(|address| {
+                entries
+                    .get(&address.to_lowercase())
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect())
+    }
+}
+
+/// Metadata set by admins through the API
+pub struct ManualResolver {
+    db: Arc<DatabaseService>,
+}
+
+impl ManualResolver {
+    pub fn new(db: Arc<DatabaseService>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl TokenMetadataResolver for ManualResolver {
+    fn source(&self) -> &'static str {
+        "manual"
+    }
+
+    async fn resolve_batch(&self, token_addresses: &[String]) -> Result<Vec<TokenMetadata>> {
+        let mut overrides = self
+            .db
+            .get_token_metadata_overrides(token_addresses)
+            .await?;
+
+        Ok(token_addresses
+            .iter()
+            .map(|address| {
+                overrides
+                    .remove(address)
+                    .map(|metadata| TokenMetadata {
+                        name: metadata.name,
+                        symbol: metadata.symbol,
+                        decimals: metadata.decimals,
+                    })
+                    .unwrap_or_default()
+            })
+            .collect())
+    }
+}
+
+/// Resolves token metadata through a chain of resolvers, each one overriding the fields
+/// provided by the ones before it: token lists, then valid on-chain values, then manual
+/// overrides. A failing resolver is skipped so the others still apply.
+pub struct TokenMetadataResolvers {
+    resolvers: Vec<Box<dyn TokenMetadataResolver>>,
+}
+
+impl TokenMetadataResolvers {
+    /// Chain of the given resolvers, lowest precedence first
+    pub fn new(resolvers: Vec<Box<dyn TokenMetadataResolver>>) -> Self {
+        Self { resolvers }
+    }
+
+    /// The default chain, with token lists only when TOKEN_LIST_URLS is set
+    pub fn from_config(db: Arc<DatabaseService>, rpc: Arc<RpcClient>, config: &AppConfig) -> Self {
+        let mut resolvers: Vec<Box<dyn TokenMetadataResolver>> = Vec::new();
+        if !config.token_list_urls.is_empty() {
+            resolvers.push(Box::new(TokenListResolver::new(
+                rpc.clone(),
+                config.token_list_urls.clone(),
+            )));
+        }
+        resolvers.push(Box::new(OnChainResolver::new(rpc, config)));
+        resolvers.push(Box::new(ManualResolver::new(db)));
+
+        Self::new(resolvers)
+    }
+
+    /// Metadata of the given tokens, in input order
+    pub async fn resolve_batch(&self, token_addresses: &[String]) -> Vec<ResolvedTokenMetadata> {
+        let mut resolved = vec![ResolvedTokenMetadata::default(); token_addresses.len()];
+
+        for resolver in &self.resolvers {
+            match resolver.resolve_batch(token_addresses).await {
+                Ok(metadata) => {
+                    for (resolved, metadata) in resolved.iter_mut().zip(metadata) {
+                        resolved.apply(metadata, resolver.source());
+                    }
+                }
+                Err(e) => warn!(
+                    "Token metadata resolver {} failed: {:#}",
+                    resolver.source(),
+                    e
+                ),
+            }
+        }
+
+        resolved
+    }
+
+    /// Metadata of a single token
+    pub async fn resolve(&self, token_address: &str) -> ResolvedTokenMetadata {
+        self.resolve_batch(&[token_address.to_string()])
+            .await
+            .pop()
+            .unwrap_or_default()
+    }
+}
+
+/// Outcome of a token list import
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenListImport {
+    pub list_name: String,
+    pub chain_id: u64,
+    pub imported: u64,
+    pub skipped: usize, // Entries of other chains or with an invalid address
+}
+
+/// Settings of a token list import
+#[derive(Debug, Clone)]
+pub struct TokenListImportOptions {
+    pub location: String,      // http(s) URL or local path of the list
+    pub chain_id: Option<u64>, // None = chain of the RPC node
+}
+
+impl TokenListImportOptions {
+    /// Build options from command line arguments (`<url|path>`, `--chain-id`)
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut location = None;
+        let mut chain_id = None;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--chain-id" => {
+                    let value = args.next().context("Missing value for --chain-id")?;
+                    chain_id = Some(value.parse().context("Invalid --chain-id")?);
+                }
+                other if other.starts_with("--") => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown tokens import-list argument: {}",
+                        other
+                    ));
+                }
+                other => location = Some(other.to_string()),
+            }
+        }
+
+        Ok(Self {
+            location: location.context("Usage: tokens import-list <url|path> [--chain-id N]")?,
+            chain_id,
+        })
+    }
+}
+
+/// Import the tokens of `chain_id` from a token list into the tokens table as curated tokens
+pub async fn import_token_list(
+    db: &DatabaseService,
+    location: &str,
+    chain_id: u64,
+) -> Result<TokenListImport> {
+    let list = load_token_list(location).await?;
+    let total = list.tokens.len();
+
+    let tokens: Vec<Token> = list
+        .tokens
+        .into_iter()
+        .filter(|entry| entry.chain_id == chain_id && entry.address.parse::<H160>().is_ok())
+        .map(|entry| Token {
+            address: entry.address.to_lowercase(),
+            name: Some(entry.name),
+            symbol: Some(entry.symbol),
+            decimals: Some(entry.decimals),
+            token_type: "ERC20".to_string(),
+            first_seen_block: 0,
+            last_seen_block: 0,
+            total_transfers: 0,
+            created_at: None,
+            updated_at: None,
+            name_source: Some("token_list".to_string()),
+            symbol_source: Some("token_list".to_string()),
+            decimals_source: Some("token_list".to_string()),
+            logo_uri: entry.logo_uri,
+            curated: true,
+            total_supply: None,
+            total_supply_block: None,
+            holder_count: None,
+            recent_transfers: None,
+            token_type_confidence: None,
+        })
+        .collect();
+
+    let imported = db.upsert_curated_tokens(&tokens).await?;
+    info!(
+        "Imported {} tokens of chain {} from token list {}",
+        imported, chain_id, list.name
+    );
+
+    Ok(TokenListImport {
+        list_name: list.name,
+        chain_id,
+        imported,
+        skipped: total - tokens.len(),
+    })
+}
+
+/// Amount in token units as decimal base units, e.g. "1.5" with 6 decimals is "1500000"
+pub fn to_base_units(amount: &str, decimals: u8) -> Result<String, String> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(format!("Invalid amount: {}", amount));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "Amount {} has more than {} decimals",
+            amount, decimals
+        ));
+    }
+
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    U256::from_dec_str(&digits)
+        .map(|amount| amount.to_string())
+        .map_err(|_| format!("Amount {} is out of range", amount))
+}
@@ -1,49 +1,184 @@
 use crate::{
     config::AppConfig,
     database::{DatabaseService, Token, TokenBalance},
+    indexer::TRANSFER_EVENT_TOPIC,
     rpc::RpcClient,
+    token_metadata::{ResolvedTokenMetadata, TokenMetadataResolvers},
+    token_standard::{DetectedStandard, TokenStandardDetector},
 };
-use anyhow::Result;
-use std::sync::Arc;
-use tokio::time::{sleep, Duration};
+use anyhow::{Context, Result};
+use ethers::{
+    core::rand::{self, Rng},
+    types::U256,
+};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+use tokio::time::{sleep, Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+/// Balances read per batch by the stale balance refresh
+const STALE_BALANCE_BATCH: usize = 100;
+
+/// Progress of the background balance refresh, times are unix timestamps
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TokenRefreshStatus {
+    pub enabled: bool, // Whether the refresh loop was started
+    pub interval_seconds: u64,
+    pub max_age_blocks: i64,
+    pub running: bool, // Whether a run is in progress
+    pub runs: u64,     // Completed runs
+    pub last_started_at: Option<i64>,
+    pub last_finished_at: Option<i64>,
+    pub last_duration_ms: Option<u64>,
+    pub last_block: Option<i64>, // Block the last successful run read balances at
+    pub refreshed_last_run: usize, // Balances stored by the current or last run
+    pub refreshed_total: u64,    // Balances stored since startup
+    pub stale_remaining: Option<i64>, // Stale balances left after the last run
+    pub last_error: Option<String>, // Error of the last run, if it failed
+    pub next_run_at: Option<i64>,
+}
+
+/// Outcome of one refresh run
+struct RefreshRun {
+    block_number: i64,
+    refreshed: usize,
+    stale_remaining: Option<i64>,
+}
+
 /// Service for managing token information and balances
 pub struct TokenService {
     db: Arc<DatabaseService>,
     rpc: Arc<RpcClient>,
     config: AppConfig,
+    metadata: TokenMetadataResolvers,
+    standards: TokenStandardDetector,
+    known_tokens: RwLock<HashSet<String>>, // Addresses of tokens already stored
+    refresh_status: RwLock<TokenRefreshStatus>,
 }
 
 impl TokenService {
     /// Create a new token service
     pub fn new(db: Arc<DatabaseService>, rpc: Arc<RpcClient>, config: AppConfig) -> Self {
-        Self { db, rpc, config }
+        let metadata = TokenMetadataResolvers::from_config(db.clone(), rpc.clone(), &config);
+        let standards = TokenStandardDetector::new(rpc.clone(), &config);
+        Self {
+            db,
+            rpc,
+            config,
+            metadata,
+            standards,
+            known_tokens: RwLock::new(HashSet::new()),
+            refresh_status: RwLock::new(TokenRefreshStatus::default()),
+        }
+    }
+
+    /// Preload the most recently seen tokens, so the first blocks after a restart don't
+    /// look every token up in the database
+    pub async fn warm(&self) -> Result<()> {
+        if self.config.cache_warm_tokens == 0 {
+            return Ok(());
+        }
+
+        let addresses = self
+            .db
+            .get_recently_seen_token_addresses(self.config.cache_warm_tokens as i64)
+            .await?;
+        info!("Token cache warmed with {} tokens", addresses.len());
+        self.known_tokens.write().unwrap().extend(addresses);
+        Ok(())
+    }
+
+    /// Whether a token is known to be stored
+    fn is_known(&self, token_address: &str) -> bool {
+        self.known_tokens.read().unwrap().contains(token_address)
+    }
+
+    /// Remember a stored token
+    fn mark_known(&self, token_address: &str) {
+        self.known_tokens
+            .write()
+            .unwrap()
+            .insert(token_address.to_string());
     }
 
-    /// Discover token information from contract address
-    pub async fn discover_token(&self, token_address: &str, block_number: i64) -> Result<Token> {
+    /// Discover token information from contract address. `token_type` is the standard
+    /// implied by the token's Transfer event, used when detection finds nothing better.
+    pub async fn discover_token(
+        &self,
+        token_address: &str,
+        token_type: &str,
+        block_number: i64,
+    ) -> Result<Token> {
         // Check if token already exists in database
         if let Some(existing_token) = self.db.get_token_by_address(token_address).await? {
             return Ok(existing_token);
         }
 
-        // First verify this is actually a contract and supports basic ERC-20 methods
-        // Try to get token name/symbol as a basic validation
-        let name = self.rpc.get_token_name(token_address).await.unwrap_or(None);
-        let symbol = self
-            .rpc
-            .get_token_symbol(token_address)
+        let metadata = self.metadata.resolve(token_address).await;
+        let standard = self
+            .standards
+            .detect_batch(&[(token_address.to_string(), token_type.to_string())])
             .await
-            .unwrap_or(None);
-        let decimals = self
-            .rpc
-            .get_token_decimals(token_address)
+            .remove(0);
+        self.store_discovered_token(token_address, standard, block_number, metadata)
             .await
-            .unwrap_or(None);
+    }
+
+    /// Discover the tokens of a block at once, given as (token_address, token_type) pairs.
+    /// Metadata and standards of unknown tokens are resolved in a single batch each;
+    /// tokens failing validation are skipped.
+    pub async fn discover_tokens(
+        &self,
+        tokens: &[(String, String)],
+        block_number: i64,
+    ) -> Result<()> {
+        let mut unknown = Vec::new();
+        for (token_address, token_type) in tokens {
+            if self.is_known(token_address) {
+                continue;
+            }
+            if self.db.get_token_by_address(token_address).await?.is_none() {
+                unknown.push((token_address.clone(), token_type.clone()));
+            } else {
+                self.mark_known(token_address);
+            }
+        }
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        let addresses: Vec<String> = unknown.iter().map(|(address, _)| address.clone()).collect();
+        let metadata = self.metadata.resolve_batch(&addresses).await;
+        let standards = self.standards.detect_batch(&unknown).await;
+
+        for (((token_address, _), metadata), standard) in
+            unknown.iter().zip(metadata).zip(standards)
+        {
+            if let Err(e) = self
+                .store_discovered_token(token_address, standard, block_number, metadata)
+                .await
+            {
+                debug!("Failed to discover token {}: {}", token_address, e);
+            }
+        }
+
+        Ok(())
+    }
 
-        // If we can't get any token metadata, it's likely not a valid ERC-20 contract
-        if name.is_none() && symbol.is_none() && decimals.is_none() {
+    /// Validate resolved token metadata and store the new token
+    async fn store_discovered_token(
+        &self,
+        token_address: &str,
+        standard: DetectedStandard,
+        block_number: i64,
+        metadata: ResolvedTokenMetadata,
+    ) -> Result<Token> {
+        // If no resolver knows anything about the token, it's likely not a valid ERC-20 contract.
+        // ERC-721 and ERC-1155 metadata is optional, detection already identified them.
+        if standard.token_type == "ERC20" && metadata.is_empty() {
             return Err(anyhow::anyhow!(
                 "Token address {} does not appear to be a valid ERC-20 contract (no name, symbol, or decimals)",
                 token_address
@@ -52,19 +187,30 @@ impl TokenService {
 
         let token = Token {
             address: token_address.to_string(),
-            name,
-            symbol,
-            decimals,
-            token_type: "ERC20".to_string(), // Default to ERC20
+            name: metadata.name,
+            symbol: metadata.symbol,
+            decimals: metadata.decimals,
+            token_type: standard.token_type,
             first_seen_block: block_number,
             last_seen_block: block_number,
             total_transfers: 1,
             created_at: None,
             updated_at: None,
+            name_source: metadata.name_source.map(String::from),
+            symbol_source: metadata.symbol_source.map(String::from),
+            decimals_source: metadata.decimals_source.map(String::from),
+            logo_uri: None,
+            curated: false,
+            total_supply: None,
+            total_supply_block: None,
+            holder_count: None,
+            recent_transfers: None,
+            token_type_confidence: Some(standard.confidence.to_string()),
         };
 
         // Save to database
         self.db.upsert_token(&token).await?;
+        self.mark_known(token_address);
 
         debug!(
             "Discovered token: {} ({}) at {}",
@@ -76,33 +222,24 @@ impl TokenService {
         Ok(token)
     }
 
-    /// Update token balance for an account
-    pub async fn update_token_balance(
+    /// Fetch the token balance of an account, None when balanceOf fails
+    async fn fetch_token_balance(
         &self,
         account_address: &str,
         token_address: &str,
         block_number: i64,
-    ) -> Result<()> {
-        // Get current balance from RPC
+    ) -> Option<TokenBalance> {
         match self
             .rpc
             .get_token_balance(token_address, account_address, Some(block_number as u64))
             .await
         {
-            Ok(balance) => {
-                let token_balance = TokenBalance {
-                    id: None,
-                    account_address: account_address.to_string(),
-                    token_address: token_address.to_string(),
-                    balance: balance.clone(),
-                    block_number,
-                    last_updated_block: block_number,
-                    created_at: None,
-                    updated_at: None,
-                };
-
-                self.db.upsert_token_balance(&token_balance).await?;
-            }
+            Ok(balance) => Some(token_balance(
+                account_address,
+                token_address,
+                balance,
+                block_number,
+            )),
             Err(e) => {
                 let error_msg = e.to_string();
                 if error_msg.contains("not a contract") {
@@ -110,8 +247,6 @@ impl TokenService {
                         "Skipping token balance update for {} holding {} - address is not a contract",
                         account_address, token_address
                     );
-                    // Mark this token as invalid to avoid future attempts
-                    // You could implement a blacklist mechanism here
                 } else if error_msg.contains("does not implement ERC-20") {
                     debug!(
                         "Skipping token balance update for {} holding {} - contract does not implement ERC-20 balanceOf",
@@ -123,10 +258,9 @@ impl TokenService {
                         account_address, token_address, e
                     );
                 }
+                None
             }
         }
-
-        Ok(())
     }
 
     /// Update token balances for all accounts affected by token transfers in a block
@@ -153,113 +287,473 @@ impl TokenService {
             unique_updates.len()
         );
 
-        // Update balances for each unique pair
-        for (_i, (account_address, token_address)) in unique_updates.iter().enumerate() {
-            // Skip null address (0x0000...)
-            if account_address.starts_with("0x0000000000000000000000000000000000000000") {
-                debug!("Skipping null address: {}", account_address);
-                continue;
-            }
-
-            if let Err(e) = self
-                .update_token_balance(account_address, token_address, block_number)
-                .await
-            {
-                error!(
-                    "Failed to update token balance for {} holding {}: {}",
-                    account_address, token_address, e
-                );
-            }
+        // Skip null address (0x0000...)
+        let pairs: Vec<(String, String)> = unique_updates
+            .into_iter()
+            .filter(|(account_address, _)| {
+                !account_address.starts_with("0x0000000000000000000000000000000000000000")
+            })
+            .collect();
 
-            // Small delay to avoid overwhelming the RPC
-            sleep(Duration::from_millis(
+        if let Err(e) = self
+            .update_token_balances(
+                &pairs,
+                block_number,
                 self.config.token_balance_update_interval_ms,
-            ))
-            .await;
+            )
+            .await
+        {
+            error!("{:#}", e);
         }
 
         info!("Completed balance updates for block {}", block_number);
         Ok(())
     }
 
-    /// Refresh stale token balances
+    /// Refresh the `STALE_BALANCE_BATCH` least recently updated balances older than
+    /// `max_age_blocks`, returning how many were stored
     pub async fn refresh_stale_balances(
         &self,
         current_block: i64,
         max_age_blocks: i64,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         let min_block = current_block - max_age_blocks;
-        let stale_balances = self.db.get_stale_token_balances(min_block, 100).await?;
+        let stale_balances = self
+            .db
+            .get_stale_token_balances(min_block, STALE_BALANCE_BATCH as i64)
+            .await?;
 
-        info!(
+        debug!(
             "Found {} stale token balances to refresh",
             stale_balances.len()
         );
 
-        for balance in stale_balances {
-            if let Err(e) = self
-                .update_token_balance(
-                    &balance.account_address,
-                    &balance.token_address,
-                    current_block,
-                )
-                .await
-            {
-                error!(
-                    "Failed to refresh token balance for {} holding {}: {}",
-                    balance.account_address, balance.token_address, e
-                );
-            }
+        let pairs: Vec<(String, String)> = stale_balances
+            .into_iter()
+            .map(|balance| (balance.account_address, balance.token_address))
+            .collect();
 
-            // Small delay to avoid overwhelming the RPC
-            sleep(Duration::from_millis(self.config.token_refresh_interval_ms)).await;
+        self.update_token_balances(&pairs, current_block, self.config.token_refresh_interval_ms)
+            .await
+    }
+
+    /// Update balances of (account, token) pairs, batching balanceOf calls through Multicall3
+    /// when available. Otherwise or when a multicall fails, pairs are fetched
+    /// `max_concurrent_balance_fetches` at a time, `interval_ms` apart. The balances are
+    /// stored with a single batch upsert; fetch failures are logged per pair. Returns how
+    /// many balances were stored.
+    async fn update_token_balances(
+        &self,
+        pairs: &[(String, String)],
+        block_number: i64,
+        interval_ms: u64,
+    ) -> Result<usize> {
+        let mut balances = Vec::with_capacity(pairs.len());
+        if self.rpc.multicall_enabled() {
+            for chunk in pairs.chunks(self.config.multicall_batch_size.max(1)) {
+                match self
+                    .fetch_token_balances_multicall(chunk, block_number)
+                    .await
+                {
+                    Ok(fetched) => balances.extend(fetched),
+                    Err(e) => {
+                        debug!(
+                            "Multicall token balance fetch failed for {} pairs: {:#}, fetching individually",
+                            chunk.len(),
+                            e
+                        );
+                        balances.extend(
+                            self.fetch_token_balances_individually(
+                                chunk,
+                                block_number,
+                                interval_ms,
+                            )
+                            .await,
+                        );
+                    }
+                }
+            }
+        } else {
+            balances = self
+                .fetch_token_balances_individually(pairs, block_number, interval_ms)
+                .await;
         }
 
-        Ok(())
+        self.db
+            .upsert_token_balances_batch(&balances)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to store {} token balances at block {}",
+                    balances.len(),
+                    block_number
+                )
+            })?;
+        Ok(balances.len())
     }
 
-    /// Get token with balance information for an account
-    pub async fn get_account_token_info(
+    /// Fetch the balances of a chunk of pairs with a single multicall
+    async fn fetch_token_balances_multicall(
         &self,
-        account_address: &str,
-    ) -> Result<Vec<(Token, TokenBalance)>> {
-        let balances = self.db.get_account_token_balances(account_address).await?;
-        let mut result = Vec::new();
+        pairs: &[(String, String)],
+        block_number: i64,
+    ) -> Result<Vec<TokenBalance>> {
+        let calls: Vec<(String, String)> = pairs
+            .iter()
+            .map(|(account_address, token_address)| {
+                (token_address.clone(), account_address.clone())
+            })
+            .collect();
+        let balances = self
+            .rpc
+            .get_token_balances_batch(&calls, Some(block_number as u64))
+            .await?;
+
+        Ok(pairs
+            .iter()
+            .zip(balances)
+            .filter_map(|((account_address, token_address), balance)| {
+                let Some(balance) = balance else {
+                    // balanceOf reverted or returned nothing, e.g. the token is not a contract
+                    debug!(
+                        "Skipping token balance update for {} holding {} - balanceOf failed",
+                        account_address, token_address
+                    );
+                    return None;
+                };
+                Some(token_balance(
+                    account_address,
+                    token_address,
+                    balance,
+                    block_number,
+                ))
+            })
+            .collect())
+    }
+
+    /// Fetch balances with one balanceOf call per pair, `max_concurrent_balance_fetches`
+    /// calls at a time
+    async fn fetch_token_balances_individually(
+        &self,
+        pairs: &[(String, String)],
+        block_number: i64,
+        interval_ms: u64,
+    ) -> Vec<TokenBalance> {
+        let mut balances = Vec::with_capacity(pairs.len());
+        for chunk in pairs.chunks(self.config.max_concurrent_balance_fetches.max(1)) {
+            let fetched =
+                futures::future::join_all(chunk.iter().map(|(account_address, token_address)| {
+                    self.fetch_token_balance(account_address, token_address, block_number)
+                }))
+                .await;
+            balances.extend(fetched.into_iter().flatten());
+
+            // Small delay to avoid overwhelming the RPC
+            sleep(Duration::from_millis(interval_ms)).await;
+        }
+        balances
+    }
+
+    /// Fix ERC-721 transfers that older versions stored as ERC-20 transfers with a bogus
+    /// amount, using the token id topic of their stored Transfer log. Returns the number of
+    /// transfers fixed.
+    pub async fn repair_nft_transfers(&self) -> Result<u64> {
+        let mut repaired = 0;
+        let mut nft_tokens = HashSet::new();
+        let mut last_id = 0;
+
+        loop {
+            // Transfers that could not be paired (e.g. duplicate rows) stay behind the cursor
+            let page = self
+                .db
+                .get_transactions_with_misclassified_nft_transfers(
+                    TRANSFER_EVENT_TOPIC,
+                    last_id,
+                    100,
+                )
+                .await?;
+            let Some((id, _)) = page.last() else {
+                break;
+            };
+            last_id = *id;
+
+            let mut hashes: Vec<String> = page.into_iter().map(|(_, hash)| hash).collect();
+            hashes.dedup();
+            for hash in hashes {
+                let mut transfers = self
+                    .db
+                    .get_token_transfers_by_transaction_hash(&hash)
+                    .await?;
+                let logs = self.db.get_logs_by_transaction(&hash).await?;
+
+                // Pair each ERC-721 Transfer log with the first unrepaired ERC-20 row it produced
+                for log in logs.iter().filter(|log| {
+                    log.topic0.as_deref() == Some(TRANSFER_EVENT_TOPIC) && log.topic3.is_some()
+                }) {
+                    let (Some(topic1), Some(topic2), Some(topic3)) =
+                        (&log.topic1, &log.topic2, &log.topic3)
+                    else {
+                        continue;
+                    };
+                    let from = format!("0x{}", &topic1[topic1.len() - 40..]);
+                    let to = format!("0x{}", &topic2[topic2.len() - 40..]);
 
-        for balance in balances {
-            if let Some(token) = self.db.get_token_by_address(&balance.token_address).await? {
-                result.push((token, balance));
+                    let Some(transfer) = transfers.iter_mut().find(|transfer| {
+                        transfer.token_type.as_deref() == Some("ERC20")
+                            && transfer.token_address == log.address
+                            && transfer.from_address == from
+                            && transfer.to_address == to
+                    }) else {
+                        continue;
+                    };
+                    let Some(id) = transfer.id else {
+                        continue;
+                    };
+
+                    let token_id = U256::from_str_radix(topic3.trim_start_matches("0x"), 16)?;
+                    self.db
+                        .reclassify_token_transfer_as_nft(id, &token_id.to_string())
+                        .await?;
+                    transfer.token_type = Some("ERC721".to_string());
+                    nft_tokens.insert(log.address.clone());
+                    repaired += 1;
+                }
             }
         }
 
-        Ok(result)
+        for token in &nft_tokens {
+            self.db.set_token_type(token, "ERC721").await?;
+        }
+
+        Ok(repaired)
+    }
+
+    /// Progress of the background balance refresh
+    pub fn refresh_status(&self) -> TokenRefreshStatus {
+        self.refresh_status.read().unwrap().clone()
     }
 
-    /// Start background service to periodically refresh token balances
+    /// Periodically refresh balances not updated in the last `max_age_blocks` blocks.
+    /// Every run works through the stale balances in batches until none are left or a
+    /// batch stores nothing, and runs are delayed by up to a tenth of the interval so
+    /// instances started together don't refresh at the same time.
     pub async fn start_background_refresh(
-        &self,
+        self: Arc<Self>,
         refresh_interval: Duration,
         max_age_blocks: i64,
-    ) -> Result<()> {
-        info!("Starting token balance refresh service");
+    ) {
+        info!(
+            "Starting token balance refresh every {}s for balances older than {} blocks",
+            refresh_interval.as_secs(),
+            max_age_blocks
+        );
+        {
+            let mut status = self.refresh_status.write().unwrap();
+            status.enabled = true;
+            status.interval_seconds = refresh_interval.as_secs();
+            status.max_age_blocks = max_age_blocks;
+        }
 
+        let mut delay = refresh_jitter(refresh_interval);
         loop {
-            // Get current block number
-            match self.rpc.get_latest_block_number().await {
-                Ok(current_block) => {
-                    if let Err(e) = self
-                        .refresh_stale_balances(current_block as i64, max_age_blocks)
-                        .await
-                    {
-                        error!("Failed to refresh stale token balances: {}", e);
+            self.refresh_status.write().unwrap().next_run_at =
+                Some(chrono::Utc::now().timestamp() + delay.as_secs() as i64);
+            sleep(delay).await;
+
+            let started = Instant::now();
+            {
+                let mut status = self.refresh_status.write().unwrap();
+                status.running = true;
+                status.next_run_at = None;
+                status.last_started_at = Some(chrono::Utc::now().timestamp());
+            }
+
+            let result = self.refresh_all_stale_balances(max_age_blocks).await;
+
+            {
+                let mut status = self.refresh_status.write().unwrap();
+                status.running = false;
+                status.runs += 1;
+                status.last_finished_at = Some(chrono::Utc::now().timestamp());
+                status.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+                match result {
+                    Ok(run) => {
+                        status.last_block = Some(run.block_number);
+                        status.refreshed_last_run = run.refreshed;
+                        status.refreshed_total += run.refreshed as u64;
+                        status.stale_remaining = run.stale_remaining;
+                        status.last_error = None;
+                        info!(
+                            "Refreshed {} stale token balances at block {}",
+                            run.refreshed, run.block_number
+                        );
+                    }
+                    Err(e) => {
+                        error!("Failed to refresh stale token balances: {:#}", e);
+                        status.refreshed_last_run = 0;
+                        status.last_error = Some(format!("{:#}", e));
                     }
                 }
-                Err(e) => {
-                    error!("Failed to get current block number: {}", e);
+            }
+
+            delay = refresh_interval + refresh_jitter(refresh_interval);
+        }
+    }
+
+    /// One run of the background refresh
+    async fn refresh_all_stale_balances(&self, max_age_blocks: i64) -> Result<RefreshRun> {
+        let block_number = self
+            .rpc
+            .get_latest_block_number()
+            .await
+            .context("Failed to get current block number")? as i64;
+
+        let mut refreshed = 0;
+        loop {
+            let stored = self
+                .refresh_stale_balances(block_number, max_age_blocks)
+                .await?;
+            refreshed += stored;
+            self.refresh_status.write().unwrap().refreshed_last_run = refreshed;
+            if stored == 0 {
+                break;
+            }
+        }
+
+        let stale_remaining = self
+            .db
+            .count_stale_token_balances(block_number - max_age_blocks)
+            .await
+            .ok();
+        Ok(RefreshRun {
+            block_number,
+            refreshed,
+            stale_remaining,
+        })
+    }
+
+    /// Periodically update token statistics: holder counts and transfers in the activity
+    /// window for every token, and totalSupply() for the `token_stats_batch_size` tokens
+    /// read longest ago. Runs are jittered like the balance refresh.
+    pub async fn start_stats_updates(self: Arc<Self>, interval: Duration) {
+        info!(
+            "Starting token statistics updates every {}s",
+            interval.as_secs()
+        );
+
+        loop {
+            sleep(refresh_jitter(interval)).await;
+
+            match self.update_token_stats().await {
+                Ok((updated, supplies)) => info!(
+                    "Updated statistics of {} tokens and {} total supplies",
+                    updated, supplies
+                ),
+                Err(e) => error!("Failed to update token statistics: {:#}", e),
+            }
+
+            sleep(interval).await;
+        }
+    }
+
+    /// One statistics update. Returns the number of tokens whose holder or transfer counts
+    /// changed and the number of total supplies read.
+    pub async fn update_token_stats(&self) -> Result<(u64, usize)> {
+        let block_number = self
+            .rpc
+            .get_latest_block_number()
+            .await
+            .context("Failed to get current block number")? as i64;
+
+        let updated = self
+            .db
+            .update_token_activity_stats(
+                block_number - self.config.token_activity_window_blocks as i64,
+            )
+            .await?;
+
+        let tokens = self
+            .db
+            .get_tokens_for_supply_refresh(self.config.token_stats_batch_size as i64)
+            .await?;
+        let supplies = self.fetch_total_supplies(&tokens).await;
+        self.db
+            .update_token_total_supplies(&supplies, block_number)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to store {} token supplies at block {}",
+                    supplies.len(),
+                    block_number
+                )
+            })?;
+
+        Ok((updated, supplies.len()))
+    }
+
+    /// Read totalSupply() of tokens through Multicall3 when available, falling back to one
+    /// call per token like balance updates. Tokens whose call failed get None.
+    async fn fetch_total_supplies(&self, tokens: &[String]) -> Vec<(String, Option<String>)> {
+        let mut supplies = Vec::with_capacity(tokens.len());
+        for chunk in tokens.chunks(self.config.multicall_batch_size.max(1)) {
+            if self.rpc.multicall_enabled() {
+                match self.rpc.get_token_total_supplies_batch(chunk).await {
+                    Ok(fetched) => {
+                        supplies.extend(chunk.iter().cloned().zip(fetched));
+                        continue;
+                    }
+                    Err(e) => debug!(
+                        "Multicall total supply fetch failed for {} tokens: {:#}, fetching individually",
+                        chunk.len(),
+                        e
+                    ),
                 }
             }
 
-            sleep(refresh_interval).await;
+            for tokens in chunk.chunks(self.config.max_concurrent_balance_fetches.max(1)) {
+                let fetched = futures::future::join_all(
+                    tokens
+                        .iter()
+                        .map(|token| self.rpc.get_token_total_supply(token)),
+                )
+                .await;
+                for (token, supply) in tokens.iter().zip(fetched) {
+                    let supply = supply.unwrap_or_else(|e| {
+                        debug!("Failed to get total supply of {}: {:#}", token, e);
+                        None
+                    });
+                    supplies.push((token.clone(), supply));
+                }
+
+                // Small delay to avoid overwhelming the RPC
+                sleep(Duration::from_millis(self.config.token_refresh_interval_ms)).await;
+            }
         }
+        supplies
+    }
+}
+
+/// Random delay of up to a tenth of `interval`
+fn refresh_jitter(interval: Duration) -> Duration {
+    let max_ms = interval.as_millis() as u64 / 10;
+    Duration::from_millis(rand::thread_rng().gen_range(0..=max_ms))
+}
+
+/// Balance of an account in a token, read at `block_number`
+fn token_balance(
+    account_address: &str,
+    token_address: &str,
+    balance: String,
+    block_number: i64,
+) -> TokenBalance {
+    TokenBalance {
+        id: None,
+        account_address: account_address.to_string(),
+        token_address: token_address.to_string(),
+        balance,
+        block_number,
+        last_updated_block: block_number,
+        created_at: None,
+        updated_at: None,
     }
 }
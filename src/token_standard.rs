@@ -0,0 +1,182 @@
+//! Token standard detection.
+//!
+//! Transfer events only tell ERC-20 from ERC-721 transfers by their number of indexed
+//! topics, which some tokens get wrong. New tokens are asked through ERC-165
+//! `supportsInterface` first; contracts that don't implement it are classified by the
+//! function selectors found in their bytecode, and only then by their Transfer event.
+
+use crate::{
+    config::AppConfig,
+    rpc::{multicall::selector, RpcClient},
+};
+use std::sync::Arc;
+use tracing::debug;
+
+/// ERC-165 interface ids
+const ERC165_INTERFACE: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+const INVALID_INTERFACE: [u8; 4] = [0xff; 4]; // Never supported by ERC-165 contracts
+pub const ERC721_INTERFACE: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+pub const ERC1155_INTERFACE: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+
+/// Interfaces asked for per token, in the order `erc165_standard` expects the answers
+pub const QUERIED_INTERFACES: [[u8; 4]; 4] = [
+    ERC165_INTERFACE,
+    INVALID_INTERFACE,
+    ERC1155_INTERFACE,
+    ERC721_INTERFACE,
+];
+
+/// PUSH4 opcode, with which function dispatchers load the selectors they compare
+const PUSH4: u8 = 0x63;
+
+/// Standard of a token and how it was established
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedStandard {
+    pub token_type: String,       // ERC20, ERC721, ERC1155
+    pub confidence: &'static str, // high (ERC-165), medium (bytecode), low (Transfer event)
+}
+
+/// Standard a contract declares through ERC-165, given its answers for
+/// `QUERIED_INTERFACES`. None when it doesn't implement ERC-165 correctly or declares
+/// neither ERC-1155 nor ERC-721.
+pub fn erc165_standard(supported: &[Option<bool>]) -> Option<&'static str> {
+    match supported {
+        [Some(true), Some(false), Some(true), _] => Some("ERC1155"),
+        [Some(true), Some(false), _, Some(true)] => Some("ERC721"),
+        _ => None,
+    }
+}
+
+/// Standard guessed from the function selectors pushed by a contract's bytecode, None
+/// when it has the functions of none of them (e.g. a proxy)
+pub fn bytecode_standard(code: &[u8]) -> Option<&'static str> {
+    let has = |signature: &str| {
+        let selector = selector(signature);
+        code.windows(5)
+            .any(|window| window[0] == PUSH4 && window[1..] == selector)
+    };
+
+    if has("balanceOfBatch(address[],uint256[])")
+        && has("safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)")
+    {
+        Some("ERC1155")
+    } else if has("ownerOf(uint256)") && has("safeTransferFrom(address,address,uint256)") {
+        Some("ERC721")
+    } else if has("transfer(address,uint256)") && has("allowance(address,address)") {
+        Some("ERC20")
+    } else {
+        None
+    }
+}
+
+/// Detects token standards through ERC-165, bytecode and Transfer events, in this order
+pub struct TokenStandardDetector {
+    rpc: Arc<RpcClient>,
+    batch_size: usize, // Tokens per multicall request
+}
+
+impl TokenStandardDetector {
+    pub fn new(rpc: Arc<RpcClient>, config: &AppConfig) -> Self {
+        Self {
+            rpc,
+            batch_size: (config.multicall_batch_size / QUERIED_INTERFACES.len()).max(1),
+        }
+    }
+
+    /// Standards of tokens given as (token_address, type implied by their Transfer event)
+    /// pairs, in input order. Detection never fails: tokens nothing else identifies keep
+    /// the type of their Transfer event with low confidence.
+    pub async fn detect_batch(&self, tokens: &[(String, String)]) -> Vec<DetectedStandard> {
+        let addresses: Vec<String> = tokens.iter().map(|(address, _)| address.clone()).collect();
+        let supported = self.supported_interfaces(&addresses).await;
+
+        let mut detected = Vec::with_capacity(tokens.len());
+        for ((token_address, event_type), supported) in tokens.iter().zip(supported) {
+            detected.push(self.detect(token_address, event_type, &supported).await);
+        }
+        detected
+    }
+
+    /// Standard of one token given its ERC-165 answers
+    async fn detect(
+        &self,
+        token_address: &str,
+        event_type: &str,
+        supported: &[Option<bool>],
+    ) -> DetectedStandard {
+        if let Some(token_type) = erc165_standard(supported) {
+            return DetectedStandard {
+                token_type: token_type.to_string(),
+                confidence: "high",
+            };
+        }
+
+        match self.rpc.get_code(token_address, None).await {
+            Ok(code) => {
+                let code = hex::decode(code.trim_start_matches("0x")).unwrap_or_default();
+                if let Some(token_type) = bytecode_standard(&code) {
+                    return DetectedStandard {
+                        token_type: token_type.to_string(),
+                        confidence: "medium",
+                    };
+                }
+            }
+            Err(e) => debug!("Failed to get code of token {}: {:#}", token_address, e),
+        }
+
+        DetectedStandard {
+            token_type: event_type.to_string(),
+            confidence: "low",
+        }
+    }
+
+    /// Answers of every token for `QUERIED_INTERFACES`, through Multicall3 when available
+    async fn supported_interfaces(&self, token_addresses: &[String]) -> Vec<Vec<Option<bool>>> {
+        let mut supported = Vec::with_capacity(token_addresses.len());
+        for chunk in token_addresses.chunks(self.batch_size) {
+            if self.rpc.multicall_enabled() {
+                match self
+                    .rpc
+                    .supports_interfaces_batch(chunk, &QUERIED_INTERFACES)
+                    .await
+                {
+                    Ok(answers) => {
+                        supported.extend(answers);
+                        continue;
+                    }
+                    Err(e) => debug!(
+                        "Multicall supportsInterface failed for {} tokens: {:#}, asking individually",
+                        chunk.len(),
+                        e
+                    ),
+                }
+            }
+
+            for token_address in chunk {
+                supported.push(self.supported_interfaces_individually(token_address).await);
+            }
+        }
+        supported
+    }
+
+    /// One call per interface, stopping at the first answer ruling ERC-165 out
+    async fn supported_interfaces_individually(&self, token_address: &str) -> Vec<Option<bool>> {
+        let mut supported = vec![None; QUERIED_INTERFACES.len()];
+        for (index, interface_id) in QUERIED_INTERFACES.iter().enumerate() {
+            supported[index] = self
+                .rpc
+                .supports_interface(token_address, *interface_id)
+                .await
+                .unwrap_or(None);
+            let implements_erc165 = match index {
+                0 => supported[index] == Some(true),
+                1 => supported[index] == Some(false),
+                _ => true,
+            };
+            if !implements_erc165 {
+                break;
+            }
+        }
+        supported
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{
+    beacon::{client::BeaconValidator, BeaconClient},
+    config::AppConfig,
+    database::{DatabaseService, Validator},
+};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tokio::time::{self, Duration, Instant};
+use tracing::{info, warn};
+
+/// Epoch value the beacon API uses for events that are not scheduled
+const FAR_FUTURE_EPOCH: u64 = u64::MAX;
+
+/// Job syncing the validators table from the beacon head state.
+///
+/// Validator indices are contiguous, so each sync requests batches of indices from 0 until
+/// the beacon node returns a short batch.
+pub struct ValidatorService {
+    db: Arc<DatabaseService>,
+    beacon: Arc<BeaconClient>,
+    config: AppConfig,
+}
+
+impl ValidatorService {
+    /// Create a new validator service
+    pub fn new(db: Arc<DatabaseService>, beacon: Arc<BeaconClient>, config: AppConfig) -> Self {
+        Self { db, beacon, config }
+    }
+
+    /// Start syncing the registry in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Validator registry sync starting");
+            let mut interval = time::interval(Duration::from_secs(
+                service.config.validator_sync_interval_seconds,
+            ));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.sync().await {
+                    warn!("Failed to sync validator registry: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Sync every validator of the head state
+    async fn sync(&self) -> Result<()> {
+        let started = Instant::now();
+        let batch_size = self.config.validator_sync_batch_size.max(1) as u64;
+        let mut next_index = 0;
+
+        loop {
+            let indices: Vec<u64> = (next_index..next_index + batch_size).collect();
+            let validators = self.beacon.get_validators(&indices).await?;
+            let fetched = validators.len() as u64;
+
+            let validators = validators
+                .iter()
+                .map(convert_validator)
+                .collect::<Result<Vec<_>>>()?;
+            self.db.upsert_validators_batch(&validators).await?;
+
+            next_index += fetched;
+            if fetched < batch_size {
+                break;
+            }
+        }
+
+        info!(
+            "Synced {} validators in {:.1}s",
+            next_index,
+            started.elapsed().as_secs_f64()
+        );
+        Ok(())
+    }
+}
+
+/// Convert a beacon API validator to our Validator model
+fn convert_validator(validator: &BeaconValidator) -> Result<Validator> {
+    let number = |value: &str, field: &str| -> Result<u64> {
+        value
+            .parse()
+            .context(format!("Invalid validator {}: {}", field, value))
+    };
+    let epoch = |value: &str, field: &str| -> Result<Option<i64>> {
+        let epoch = number(value, field)?;
+        Ok((epoch != FAR_FUTURE_EPOCH).then_some(epoch as i64))
+    };
+    let info = &validator.validator;
+
+    Ok(Validator {
+        validator_index: number(&validator.index, "index")? as i64,
+        pubkey: info.pubkey.to_lowercase(),
+        withdrawal_credentials: info.withdrawal_credentials.to_lowercase(),
+        balance_gwei: number(&validator.balance, "balance")? as i64,
+        effective_balance_gwei: number(&info.effective_balance, "effective balance")? as i64,
+        status: validator.status.clone(),
+        slashed: info.slashed,
+        activation_eligibility_epoch: epoch(
+            &info.activation_eligibility_epoch,
+            "activation eligibility epoch",
+        )?,
+        activation_epoch: epoch(&info.activation_epoch, "activation epoch")?,
+        exit_epoch: epoch(&info.exit_epoch, "exit epoch")?,
+        withdrawable_epoch: epoch(&info.withdrawable_epoch, "withdrawable epoch")?,
+        updated_at: None,
+    })
+}
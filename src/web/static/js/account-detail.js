@@ -155,6 +155,11 @@ function displayAccountDetails(account) {
       "Unknown" },
   ];
   
+  // Labels are user-provided, render them as text
+  if (account.label) {
+    details.splice(1, 0, { label: "Label", value: `${account.label.label} (${account.label.category})`, plainText: true });
+  }
+  
   // Add note if account is not fully indexed
   if (account.note) {
     details.push({ label: "Note", value: account.note });
@@ -178,6 +183,9 @@ function displayAccountDetails(account) {
         ${valueContent}
       </td>
     `;
+    if (detail.plainText) {
+      row.lastElementChild.textContent = detail.value;
+    }
     detailsTable.appendChild(row);
   });
   
@@ -159,7 +159,8 @@ function displayBlockDetails(block) {
     { label: "Epoch", value: formatNumber(block.epoch) || "N/A" },
     { label: "Slot Root", value: block.slot_root || "N/A", copyable: !!block.slot_root },
     { label: "Parent Root", value: block.parent_root || "N/A", copyable: !!block.parent_root },
-    { label: "Beacon Deposit Count", value: formatNumber(block.beacon_deposit_count) || "N/A" },
+    { label: "Block Deposits", value: formatNumber(block.block_deposit_count) || "N/A" },
+    { label: "Eth1 Deposit Count", value: formatNumber(block.eth1_deposit_count) || "N/A" },
     { label: "Graffiti", value: formatGraffiti(block.graffiti) },
     { label: "Randao Reveal", value: block.randao_reveal || "N/A", copyable: !!block.randao_reveal },
     { label: "Randao Mix", value: block.randao_mix || "N/A", copyable: !!block.randao_mix }
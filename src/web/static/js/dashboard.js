@@ -190,10 +190,11 @@ async function loadStats() {
     const controller = new AbortController();
     const timeoutId = setTimeout(() => controller.abort(), 10000); // 10 second timeout
 
-    const [statsResponse, networkResponse, networkStatsResponse] = await Promise.all([
+    const [statsResponse, networkResponse, networkStatsResponse, tpsResponse] = await Promise.all([
       fetch(`${API_BASE}/stats`, { signal: controller.signal }),
       fetch(`${API_BASE}/network/latest`, { signal: controller.signal }),
-      fetch(`${API_BASE}/network/stats`, { signal: controller.signal })
+      fetch(`${API_BASE}/network/stats`, { signal: controller.signal }),
+      fetch(`${API_BASE}/stats/tps`, { signal: controller.signal })
     ]);
 
     clearTimeout(timeoutId);
@@ -232,6 +233,11 @@ async function loadStats() {
     updateStatWithAnimation("total-indexed-blocks", formatNumber(data.total_blocks));
     updateStatWithAnimation("sync-status", data.indexer_status);
 
+    // Update live TPS counter (1 minute window, 5 and 15 minutes for context)
+    if (tpsResponse.ok) {
+      updateTpsCounter(await tpsResponse.json());
+    }
+
     // Update total blockchain transactions with availability indicator
     updateTotalBlockchainTransactions(data);
 
@@ -694,6 +700,16 @@ document.addEventListener("DOMContentLoaded", () => {
   setInterval(updateSyncStatus, 1000);
 });
 
+// Render TPS windows as "12.3 TPS (5m 11.8 · 15m 12.1)"
+function updateTpsCounter(tpsData) {
+  const element = document.getElementById("current-tps");
+  if (!element || !Array.isArray(tpsData.windows)) return;
+
+  const formatTps = (window) => window && window.tps != null ? window.tps.toFixed(1) : "–";
+  const [oneMinute, fiveMinutes, fifteenMinutes] = tpsData.windows;
+  element.textContent = `${formatTps(oneMinute)} TPS (5m ${formatTps(fiveMinutes)} · 15m ${formatTps(fifteenMinutes)})`;
+}
+
 // Show/hide frequent data updating indicator
 function setFrequentDataUpdating(isUpdating) {
   // Update stats indicators for frequently updated items
@@ -128,11 +128,10 @@ async function performSearch(query) {
         results = await searchBlock(searchInfo.value);
         break;
       case 'partial_hex':
-        results = await searchPartialHex(searchInfo.value);
-        break;
       default:
-        showNoResults();
-        return;
+        // Partial hashes and addresses, ENS names, token names and symbols
+        results = await searchIndex(searchInfo.value);
+        break;
     }
     
     if (results && results.found) {
@@ -219,10 +218,67 @@ async function searchBlock(blockNumber) {
   }
 }
 
-// Search for partial hex (could be partial hash or address)
-async function searchPartialHex(partial) {
-  // For now, suggest user to enter complete hash/address
-  return { found: false };
+// Search the index for partial hashes, addresses, ENS names and tokens
+async function searchIndex(query) {
+  try {
+    const response = await fetch(`${API_BASE}/search/${encodeURIComponent(query)}`);
+    if (!response.ok) {
+      throw new Error(`API returned ${response.status}`);
+    }
+
+    const data = await response.json();
+    const results = data.results || [];
+    if (results.length === 0) {
+      return { found: false };
+    }
+    // A single match is opened directly
+    if (results.length === 1) {
+      const redirect = resultLink(results[0]);
+      if (redirect) {
+        return { found: true, type: results[0].type, data: results[0].result, redirect };
+      }
+    }
+    return { found: true, type: 'list', data: results };
+  } catch (error) {
+    throw new Error(`Search failed: ${error.message}`);
+  }
+}
+
+// Detail page of a search result
+function resultLink(item) {
+  const result = item.result || {};
+  switch (item.type) {
+    case 'block':
+      return `/block-detail.html?number=${result.number}`;
+    case 'transaction':
+      return `/transaction-detail.html?hash=${result.hash}`;
+    case 'account':
+      return `/account-detail.html?address=${result.address || item.address}`;
+    case 'token':
+      return `/account-detail.html?address=${result.address}`;
+    default:
+      return null;
+  }
+}
+
+// One-line description of a search result
+function resultLabel(item) {
+  const result = item.result || {};
+  switch (item.type) {
+    case 'block':
+      return `Block #${formatNumber(result.number)} <span class="font-mono text-gray-500">${truncateHash(result.hash, 10)}</span>`;
+    case 'transaction':
+      return `<span class="font-mono">${truncateHash(result.hash, 12)}</span> in block #${formatNumber(result.block_number)}`;
+    case 'account': {
+      const address = result.address || item.address;
+      const name = item.ens_name ? `${item.ens_name} ` : '';
+      return `${name}<span class="font-mono">${truncateAddress(address, 8)}</span>`;
+    }
+    case 'token':
+      return `${result.name || 'Unknown token'} (${result.symbol || '?'}) <span class="font-mono text-gray-500">${truncateAddress(result.address, 6)}</span>`;
+    default:
+      return '';
+  }
 }
 
 // Display search results
@@ -264,11 +320,32 @@ function generateResultsHTML(results) {
       return generateAccountHTML(results.data);
     case 'block':
       return generateBlockHTML(results.data);
+    case 'list':
+      return generateListHTML(results.data);
     default:
       return '<p>Unknown result type</p>';
   }
 }
 
+// Generate HTML for a list of typed search results, best match first
+function generateListHTML(items) {
+  const rows = items.map(item => `
+    <li>
+      <a href="${resultLink(item)}" class="flex items-center justify-between py-3 px-2 hover:bg-gray-50">
+        <span class="text-sm text-gray-900">${resultLabel(item)}</span>
+        <span class="inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-gray-100 text-gray-800">${item.type}</span>
+      </a>
+    </li>
+  `).join('');
+
+  return `
+    <div class="bg-white shadow rounded-lg p-6">
+      <h3 class="text-lg font-medium text-gray-900 mb-4">${items.length} Results</h3>
+      <ul class="divide-y divide-gray-200">${rows}</ul>
+    </div>
+  `;
+}
+
 // Generate transaction result HTML
 function generateTransactionHTML(tx) {
   return `
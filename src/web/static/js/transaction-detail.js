@@ -82,7 +82,7 @@ async function loadTransactionDetails(txHash) {
     }
     
     const data = await response.json();
-    displayTransactionDetails(data.transaction, data.logs || []);
+    displayTransactionDetails(data.transaction, data.logs || [], data.decoded_input);
     loadTokenTransfers(txHash);
     
   } catch (error) {
@@ -94,7 +94,7 @@ async function loadTransactionDetails(txHash) {
 }
 
 // Display transaction details
-function displayTransactionDetails(tx, logs) {
+function displayTransactionDetails(tx, logs, decodedInput) {
   if (!tx) return;
   
   // Update page title and breadcrumb
@@ -117,18 +117,19 @@ function displayTransactionDetails(tx, logs) {
   
   const details = [
     { label: "Transaction Hash", value: tx.hash, copyable: true },
-    { label: "Status", value: getStatusText(tx.status) },
+    { label: "Status", value: getStatusText(tx.status, tx.out_of_gas) },
     { label: "Block Number", value: tx.block_number, linkable: true, linkType: "block" },
     { label: "Transaction Index", value: tx.transaction_index || "N/A" },
     { label: "From", value: tx.from_address, copyable: true, linkable: true, linkType: "account" },
     { label: "To", value: tx.to_address || "Contract Creation", copyable: tx.to_address, linkable: tx.to_address, linkType: "account" },
     { label: "Value", value: `${formatEthValue(tx.value)} (${formatNumber(tx.value)} wei)` },
-    { label: "Gas Limit", value: formatNumber(tx.gas_limit) },
-    { label: "Gas Used", value: `${formatNumber(tx.gas_used)} (${tx.gas_limit ? ((tx.gas_used / tx.gas_limit) * 100).toFixed(2) : 0}%)` },
+    { label: "Gas Limit", value: tx.gas_limit ? formatNumber(tx.gas_limit) : "N/A" },
+    { label: "Gas Used", value: tx.gas_efficiency != null ? `${formatNumber(tx.gas_used)} (${tx.gas_efficiency.toFixed(2)}%)` : formatNumber(tx.gas_used) },
     { label: "Gas Price", value: `${formatGasPrice(tx.gas_price)} (${formatNumber(tx.gas_price)} wei)` },
     { label: "Transaction Fee", value: tx.gas_used && tx.gas_price ? formatEthValue((tx.gas_used * tx.gas_price).toString()) : "N/A" },
     { label: "Nonce", value: tx.nonce || "N/A" },
-    { label: "Input Data", value: tx.input_data || "0x", copyable: true, expandable: true }
+    { label: "Method", value: formatDecodedInput(decodedInput, tx.method_name || tx.method_id) },
+    { label: "Input Data", value: tx.input || "0x", copyable: true, expandable: true }
   ];
   
   details.forEach(detail => {
@@ -175,6 +176,20 @@ function displayTransactionDetails(tx, logs) {
   showContent();
 }
 
+// Format a decoded method call as name(param: value, ...)
+function formatDecodedInput(decoded, methodId) {
+  if (!decoded) return methodId || "N/A";
+
+  const params = Object.entries(decoded.params)
+    .map(([name, value]) => `${name}: ${typeof value === "string" ? value : JSON.stringify(value)}`)
+    .join(", ");
+  // Parameters come from calldata, render them as text
+  const span = document.createElement("span");
+  span.className = "font-mono break-all";
+  span.textContent = `${decoded.method}(${params})`;
+  return span.outerHTML;
+}
+
 // Update status banner
 function updateStatusBanner(tx) {
   const banner = document.getElementById("status-banner");
@@ -199,16 +214,18 @@ function updateStatusBanner(tx) {
     title.className = "text-sm font-medium text-red-800";
     title.textContent = "Transaction Failed";
     description.className = "mt-2 text-sm text-red-700";
-    description.textContent = "This transaction failed during execution and was reverted.";
+    description.textContent = tx.out_of_gas
+      ? "This transaction ran out of gas during execution and was reverted."
+      : "This transaction failed during execution and was reverted.";
   }
 }
 
 // Get status text
-function getStatusText(status) {
+function getStatusText(status, outOfGas) {
   const isSuccess = status === "success" || status === true || status === 1;
   return isSuccess ? 
     '<span class="inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-green-100 text-green-800">Success</span>' :
-    '<span class="inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-red-100 text-red-800">Failed</span>';
+    `<span class="inline-flex items-center px-2.5 py-0.5 rounded-full text-xs font-medium bg-red-100 text-red-800">${outOfGas ? "Failed (out of gas)" : "Failed"}</span>`;
 }
 
 // Display transaction logs
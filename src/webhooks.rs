@@ -0,0 +1,266 @@
+use crate::{
+    config::AppConfig,
+    database::{Block, BlockActivity, BlockWebhook, DatabaseService, WebhookDelivery},
+    events::{EventLog, IndexerEventKind, NewIndexerEvent},
+};
+use anyhow::{Context, Result};
+use ethers::types::U256;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time;
+use tracing::{debug, info, warn};
+
+/// Notifications sent per delivery run
+const DELIVERY_BATCH: i64 = 500;
+
+/// Delay before the first retry of a failed delivery, doubled by every further failure
+const RETRY_BASE_SECONDS: i64 = 10;
+
+/// Longest delay between two delivery attempts
+const RETRY_MAX_SECONDS: i64 = 3600;
+
+/// Queue notifications of a newly stored block for every webhook subscribed to it.
+///
+/// Called once the block's transactions, logs and transfers are stored, so the payload
+/// aggregates are complete. Notifications go through the `block_webhook_outbox` table and
+/// survive restarts until delivered.
+pub async fn enqueue_block(db: &DatabaseService, block: &Block) -> Result<u64> {
+    if db.count_block_webhooks_from(block.number).await? == 0 {
+        return Ok(0);
+    }
+
+    let activity = db.get_block_activity(block.number).await?;
+    let value = db
+        .get_transaction_values_by_block(block.number)
+        .await?
+        .iter()
+        .filter_map(|value| U256::from_dec_str(value).ok())
+        .fold(U256::zero(), |total, value| total.saturating_add(value));
+
+    let payload = block_payload(block, &activity, value);
+    db.enqueue_block_webhooks(
+        block.number,
+        &payload.to_string(),
+        chrono::Utc::now().timestamp(),
+    )
+    .await
+}
+
+/// Body POSTed to block webhooks
+fn block_payload(block: &Block, activity: &BlockActivity, value: U256) -> Value {
+    json!({
+        "event": "block",
+        "block": {
+            "number": block.number,
+            "hash": block.hash,
+            "parent_hash": block.parent_hash,
+            "timestamp": block.timestamp,
+            "miner": block.miner,
+            "gas_used": block.gas_used,
+            "gas_limit": block.gas_limit,
+            "base_fee_per_gas": block.base_fee_per_gas,
+            "transaction_count": block.transaction_count,
+            "withdrawal_count": block.withdrawal_count,
+        },
+        "aggregates": {
+            "failed_transactions": activity.failed_transactions,
+            "unique_senders": activity.unique_senders,
+            "contract_creations": activity.contract_creations,
+            "log_count": activity.log_count,
+            "token_transfer_count": activity.token_transfer_count,
+            "value_transferred": value.to_string(),
+            "burnt_fees": block.burnt_fees(),
+        }
+    })
+}
+
+/// Job delivering queued block notifications to their webhooks.
+///
+/// Delivery is at least once: a notification leaves the outbox only after its endpoint
+/// answered with a 2xx status, so receivers should drop repeated `X-Webhook-Delivery` IDs.
+/// Failed deliveries are retried with exponential backoff, and later blocks for the same
+/// webhook wait until the failed one got through, keeping them in order.
+pub struct WebhookService {
+    db: Arc<DatabaseService>,
+    events: EventLog,
+    config: AppConfig,
+    client: Client,
+}
+
+impl WebhookService {
+    /// Create a new webhook delivery service
+    pub fn new(db: Arc<DatabaseService>, events: EventLog, config: AppConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.webhook_timeout_seconds))
+            .build()
+            .context("Failed to create webhook HTTP client")?;
+
+        Ok(Self {
+            db,
+            events,
+            config,
+            client,
+        })
+    }
+
+    /// Start delivering notifications in the background
+    pub async fn start_background_updates(self: Arc<Self>) {
+        let service = Arc::clone(&self);
+        tokio::spawn(async move {
+            info!("Block webhook delivery starting");
+            let mut interval =
+                time::interval(Duration::from_secs(service.config.webhook_interval_seconds));
+
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.run().await {
+                    warn!("Block webhook delivery failed: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Send every notification that is due
+    pub async fn run(&self) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let deliveries = self
+            .db
+            .get_due_webhook_deliveries(now, DELIVERY_BATCH)
+            .await?;
+        if deliveries.is_empty() {
+            return Ok(());
+        }
+
+        let webhooks: HashMap<i64, BlockWebhook> = self
+            .db
+            .get_block_webhooks()
+            .await?
+            .into_iter()
+            .map(|webhook| (webhook.id, webhook))
+            .collect();
+        // Webhooks whose delivery failed in this run, their later blocks wait for the retry
+        let mut failed = HashSet::new();
+        let mut delivered = 0;
+
+        for delivery in deliveries {
+            let Some(webhook) = webhooks.get(&delivery.webhook_id) else {
+                continue;
+            };
+            if failed.contains(&webhook.id) {
+                continue;
+            }
+
+            match self.deliver(webhook, &delivery).await {
+                Ok(()) => {
+                    self.db.delete_webhook_delivery(delivery.id).await?;
+                    delivered += 1;
+                }
+                Err(e) => {
+                    failed.insert(webhook.id);
+                    self.retry_or_drop(webhook, &delivery, &format!("{:#}", e), now)
+                        .await?;
+                }
+            }
+        }
+
+        if delivered > 0 {
+            debug!("Delivered {} block notifications", delivered);
+        }
+        Ok(())
+    }
+
+    /// POST a notification, signing it when the webhook has a secret
+    async fn deliver(&self, webhook: &BlockWebhook, delivery: &WebhookDelivery) -> Result<()> {
+        let mut request = self
+            .client
+            .post(&webhook.url)
+            .header("content-type", "application/json")
+            .header("x-webhook-id", webhook.id.to_string())
+            .header("x-webhook-delivery", delivery.id.to_string());
+        if let Some(secret) = &webhook.secret {
+            request = request.header(
+                "x-webhook-signature",
+                format!("sha256={}", sign(secret, &delivery.payload)),
+            );
+        }
+
+        let response = request
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .context("Request failed")?;
+        anyhow::ensure!(
+            response.status().is_success(),
+            "Endpoint answered {}",
+            response.status()
+        );
+        Ok(())
+    }
+
+    /// Schedule the next attempt of a failed delivery, or drop it once it used up
+    /// WEBHOOK_MAX_ATTEMPTS
+    async fn retry_or_drop(
+        &self,
+        webhook: &BlockWebhook,
+        delivery: &WebhookDelivery,
+        error: &str,
+        now: i64,
+    ) -> Result<()> {
+        let attempts = delivery.attempts + 1;
+        if self.config.webhook_max_attempts > 0 && attempts >= self.config.webhook_max_attempts {
+            warn!(
+                "Dropping notification of block #{} for webhook {} after {} attempts: {}",
+                delivery.block_number, webhook.id, attempts, error
+            );
+            self.db.delete_webhook_delivery(delivery.id).await?;
+            self.events.record(
+                NewIndexerEvent::warning(
+                    IndexerEventKind::WebhookDropped,
+                    format!(
+                        "Dropped block notification for webhook {} after {} attempts",
+                        webhook.id, attempts
+                    ),
+                )
+                .at_block(delivery.block_number)
+                .with_details(json!({
+                    "webhook_id": webhook.id,
+                    "url": webhook.url,
+                    "delivery_id": delivery.id,
+                    "error": error,
+                })),
+            );
+            return Ok(());
+        }
+
+        debug!(
+            "Notification of block #{} for webhook {} failed: {}",
+            delivery.block_number, webhook.id, error
+        );
+        self.db
+            .reschedule_webhook_delivery(delivery.id, now + retry_delay(attempts), error)
+            .await
+    }
+}
+
+/// Seconds to wait after the given number of failed attempts
+fn retry_delay(attempts: i64) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 20) as u32;
+    RETRY_BASE_SECONDS
+        .saturating_mul(2i64.pow(exponent))
+        .min(RETRY_MAX_SECONDS)
+}
+
+/// Hex HMAC-SHA256 of a payload
+fn sign(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
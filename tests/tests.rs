@@ -1,10 +1,106 @@
-use eth_indexer_rs::config::AppConfig;
+use eth_indexer_rs::config::{AppConfig, BlockRange, MarketDataProvider};
 use eth_indexer_rs::{
     App,
-    database::{Block, Transaction},
+    api::ApiError,
+    contracts::{mask_immutables, strip_metadata},
+    database::{
+        block_status, Account, ApprovalChange, Block, BlockData, BlockFeeRecipient, BlockResponse,
+        DatabaseService, DelegationChange, Deposit, FeeRecipientParams, Finality,
+        FunctionSignature, HolderExportRange, Log, ProposerDuty, Token, TokenBalance, TokenSort,
+        TokenTransfer, TokenTransferFilter, Transaction, Validator, Withdrawal,
+    },
+    executor::{RateLimitFeedback, RateLimits, RpcExecutor, RpcOverloaded},
+    indexer::method_name,
+    live::{LiveDispatcher, LiveLimits, LiveLog, LogFilter},
+    log_filter::{expand_directives, parse_directives},
+    market_data::{MarketData, PriceSource, TransferValue},
+    rpc::multicall::selector,
+    storage_stats::storage_report,
+    token_metadata::to_base_units,
+    token_standard::{bytecode_standard, erc165_standard},
 };
+use futures::StreamExt;
+use std::{sync::Arc, time::Duration};
 use tokio;
 
+/// Block with only its required fields set, for tests to fill in what they exercise
+fn test_block(number: i64) -> Block {
+    Block {
+        number,
+        hash: format!("0xblock{}", number),
+        parent_hash: format!("0xblock{}", number - 1),
+        timestamp: 1700000000 + number,
+        gas_used: 0,
+        gas_limit: 30000000,
+        transaction_count: 0,
+        miner: None,
+        difficulty: None,
+        size_bytes: None,
+        base_fee_per_gas: None,
+        extra_data: None,
+        state_root: None,
+        nonce: None,
+        withdrawals_root: None,
+        blob_gas_used: None,
+        excess_blob_gas: None,
+        withdrawal_count: None,
+        slot: None,
+        proposer_index: None,
+        epoch: None,
+        slot_root: None,
+        parent_root: None,
+        block_deposit_count: None,
+        eth1_deposit_count: None,
+        graffiti: None,
+        randao_reveal: None,
+        randao_mix: None,
+        sync_participation: None,
+        attestation_count: None,
+        slashing_count: None,
+    }
+}
+
+/// Block data holding a test block and no other rows
+fn test_block_data(number: i64) -> BlockData {
+    BlockData {
+        block: test_block(number),
+        withdrawals: Vec::new(),
+        deposits: Vec::new(),
+        transactions: Vec::new(),
+        receipts: Vec::new(),
+        logs: Vec::new(),
+        token_transfers: Vec::new(),
+        accounts: Vec::new(),
+        contract_deployments: Vec::new(),
+        contract_codes: Vec::new(),
+        l2_block: None,
+        l2_transactions: Vec::new(),
+        fee_recipient: None,
+        delegation_changes: Vec::new(),
+        approval_changes: Vec::new(),
+    }
+}
+
+/// Successful contract call stored in a test block
+fn test_transaction(hash: &str, block_number: i64) -> Transaction {
+    Transaction {
+        hash: hash.to_string(),
+        block_number,
+        from_address: "0xfrom".to_string(),
+        to_address: Some("0xtoken".to_string()),
+        value: "0".to_string(),
+        gas_used: 50000,
+        gas_price: "1000000000".to_string(),
+        status: 1,
+        transaction_index: 0,
+        method_id: Some("0xa9059cbb".to_string()),
+        method_name: Some("transfer".to_string()),
+        gas_limit: Some(60000),
+        input: None,
+        input_size: None,
+    }
+}
+
 #[tokio::test]
 async fn test_app_initialization_with_env() {
     unsafe {
@@ -65,10 +161,14 @@ async fn test_database_operations() {
         epoch: Some(125000),
         slot_root: Some("0xslot123".to_string()),
         parent_root: Some("0xparent123".to_string()),
-        beacon_deposit_count: Some(500000),
+        block_deposit_count: Some(0),
+        eth1_deposit_count: Some(500000),
         graffiti: Some("test graffiti".to_string()),
         randao_reveal: Some("0xrandao123".to_string()),
         randao_mix: Some("0xmix123".to_string()),
+        sync_participation: Some(100.0),
+        attestation_count: None,
+        slashing_count: None,
     };
 
     let write_result = db.insert_block(&test_block).await;
@@ -103,6 +203,11 @@ async fn test_database_operations() {
         gas_price: "20000000000".to_string(),
         status: 1,
         transaction_index: 0,
+        method_id: Some("0xa9059cbb".to_string()),
+        method_name: Some("transfer".to_string()),
+        gas_limit: Some(21000),
+        input: Some("0xa9059cbb".to_string()),
+        input_size: Some(4),
     };
     let tx_write_result = db.insert_transaction(&test_transaction).await;
     assert!(
@@ -122,6 +227,2307 @@ async fn test_database_operations() {
     assert_eq!(transactions[0].hash, "0xtx123456");
 }
 
+#[tokio::test]
+async fn test_store_block_data_is_idempotent() {
+    let path = "./data/test_block_store.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let transaction = |hash: &str, index: i64| Transaction {
+        hash: hash.to_string(),
+        block_number: 100,
+        from_address: "0xfrom".to_string(),
+        to_address: Some("0xtoken".to_string()),
+        value: "0".to_string(),
+        gas_used: 50000,
+        gas_price: "1000000000".to_string(),
+        status: 1,
+        transaction_index: index,
+        method_id: Some("0xa9059cbb".to_string()),
+        method_name: Some("transfer".to_string()),
+        gas_limit: Some(60000),
+        input: None,
+        input_size: None,
+    };
+    let data = BlockData {
+        block: Block {
+            timestamp: 1700000000,
+            gas_used: 100000,
+            transaction_count: 2,
+            ..test_block(100)
+        },
+        transactions: vec![transaction("0xtx1", 0), transaction("0xtx2", 1)],
+        token_transfers: vec![TokenTransfer {
+            id: None,
+            transaction_hash: "0xtx1".to_string(),
+            block_number: 100,
+            token_address: "0xtoken".to_string(),
+            from_address: "0xfrom".to_string(),
+            to_address: "0xto".to_string(),
+            amount: "1000".to_string(),
+            token_type: Some("ERC20".to_string()),
+            token_id: None,
+            log_index: Some(0),
+        }],
+        accounts: vec![Account {
+            address: "0xfrom".to_string(),
+            balance: "5000".to_string(),
+            transaction_count: 2,
+            first_seen_block: 100,
+            last_seen_block: 100,
+            balance_at_block: Some(100),
+            account_type: Some("eoa".to_string()),
+        }],
+        fee_recipient: Some(BlockFeeRecipient {
+            block_number: 100,
+            fee_recipient: "0xbuilder".to_string(),
+            mismatch: true,
+            proposer_payee: Some("0xproposer".to_string()),
+            proposer_payment: Some("1000000000000000000".to_string()),
+            ..Default::default()
+        }),
+        ..test_block_data(100)
+    };
+
+    // Writing the block again replaces its rows instead of failing or duplicating them
+    for _ in 0..2 {
+        db.store_block_data(&data, 0)
+            .await
+            .expect("Failed to store block data");
+    }
+
+    let transactions = db.get_transactions_by_block(100).await.unwrap();
+    assert_eq!(transactions.len(), 2);
+    let transfers = db.get_token_transfers_by_block(100).await.unwrap();
+    assert_eq!(transfers.len(), 1);
+
+    // A transfer already stored for its log is skipped
+    db.insert_token_transfer(&data.token_transfers[0])
+        .await
+        .expect("Failed to insert token transfer");
+    let transfers = db.get_token_transfers_by_block(100).await.unwrap();
+    assert_eq!(transfers.len(), 1);
+    let contracts = db.get_top_contracts(0, 10).await.unwrap();
+    assert_eq!(contracts.len(), 1);
+    assert_eq!(contracts[0].calls, 2);
+    let account = db.get_account_by_address("0xfrom").await.unwrap().unwrap();
+    assert_eq!(
+        (account.transaction_count, account.balance.as_str()),
+        (2, "5000")
+    );
+    let params = FeeRecipientParams {
+        page: None,
+        per_page: None,
+        fee_recipient: None,
+        proposer_payee: None,
+        proposer_index: None,
+        mismatch: Some(true),
+    };
+    let relationships = db.get_fee_recipient_relationships(&params).await.unwrap();
+    assert_eq!(relationships.len(), 1);
+    assert_eq!(
+        (relationships[0].blocks, relationships[0].total_payment_eth),
+        (1, 1.0)
+    );
+
+    // Reorgs and reindexing delete the block's rows before storing it again
+    db.delete_replaced_block_data(100, "reindex")
+        .await
+        .expect("Failed to delete replaced block data");
+    db.store_block_data(&data, 0)
+        .await
+        .expect("Failed to store block data");
+    let account = db.get_account_by_address("0xfrom").await.unwrap().unwrap();
+    assert_eq!(account.transaction_count, 2);
+
+    // So does the consistency checker when repairing a block
+    db.delete_block_transactions(100)
+        .await
+        .expect("Failed to delete block transactions");
+    db.store_block_data(&data, 0)
+        .await
+        .expect("Failed to store block data");
+    let contracts = db.get_top_contracts(0, 10).await.unwrap();
+    assert_eq!(contracts[0].calls, 2);
+    let account = db.get_account_by_address("0xfrom").await.unwrap().unwrap();
+    assert_eq!(account.transaction_count, 2);
+}
+
+#[tokio::test]
+async fn test_reorged_transactions_keep_their_logs_and_transfers() {
+    let path = "./data/test_reorged_logs.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let block = |number: i64| BlockData {
+        transactions: vec![Transaction {
+            hash: "0xtx1".to_string(),
+            block_number: number,
+            from_address: "0xfrom".to_string(),
+            to_address: Some("0xtoken".to_string()),
+            value: "0".to_string(),
+            gas_used: 50000,
+            gas_price: "1000000000".to_string(),
+            status: 1,
+            transaction_index: 0,
+            method_id: Some("0xa9059cbb".to_string()),
+            method_name: Some("transfer".to_string()),
+            gas_limit: Some(60000),
+            input: None,
+            input_size: None,
+        }],
+        logs: vec![Log {
+            id: None,
+            transaction_hash: "0xtx1".to_string(),
+            block_number: number,
+            address: "0xtoken".to_string(),
+            topic0: Some(
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string(),
+            ),
+            topic1: Some("0xfrom".to_string()),
+            topic2: Some("0xto".to_string()),
+            topic3: None,
+            data: Some("0x03e8".to_string()),
+            log_index: 0,
+        }],
+        token_transfers: vec![TokenTransfer {
+            id: None,
+            transaction_hash: "0xtx1".to_string(),
+            block_number: number,
+            token_address: "0xtoken".to_string(),
+            from_address: "0xfrom".to_string(),
+            to_address: "0xto".to_string(),
+            amount: "1000".to_string(),
+            token_type: Some("ERC20".to_string()),
+            token_id: None,
+            log_index: Some(0),
+        }],
+        ..test_block_data(number)
+    };
+
+    // A reorg moves the transaction from block 10 into the new canonical block 9, which
+    // is stored before the stale block 10 is replaced
+    db.store_block_data(&block(10), 0)
+        .await
+        .expect("Failed to store block data");
+    db.store_block_data(&block(9), 0)
+        .await
+        .expect("Failed to store block data");
+    db.delete_replaced_block_data(10, "reorg")
+        .await
+        .expect("Failed to delete replaced block data");
+
+    let logs = db.get_logs_by_transaction("0xtx1").await.unwrap();
+    assert_eq!(
+        logs.iter().map(|log| log.block_number).collect::<Vec<_>>(),
+        vec![9]
+    );
+    assert_eq!(db.get_token_transfers_by_block(9).await.unwrap().len(), 1);
+    assert!(db
+        .get_token_transfers_by_block(10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_transaction_method_names() {
+    let path = "./data/test_method_names.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    assert_eq!(method_name("0xa9059cbb"), Some("transfer"));
+    assert_eq!(method_name("0x3593564c"), Some("execute"));
+    assert_eq!(method_name("0x12345678"), None);
+
+    // Functions of verified contracts name the selectors the indexer doesn't know
+    db.insert_function_signatures(&[FunctionSignature {
+        selector: "0x12345678".to_string(),
+        signature: "harvest(uint256)".to_string(),
+        abi: "{}".to_string(),
+    }])
+    .await
+    .unwrap();
+
+    let transaction = |hash: &str, index: i64, method_id: Option<&str>| Transaction {
+        hash: hash.to_string(),
+        block_number: 7,
+        from_address: "0xfrom".to_string(),
+        to_address: Some("0xcontract".to_string()),
+        value: "0".to_string(),
+        gas_used: 50000,
+        gas_price: "1000000000".to_string(),
+        status: 1,
+        transaction_index: index,
+        method_id: method_id.map(str::to_string),
+        method_name: method_id.and_then(method_name).map(str::to_string),
+        gas_limit: Some(60000),
+        input: None,
+        input_size: None,
+    };
+    let data = BlockData {
+        block: Block {
+            timestamp: 1700000000,
+            gas_used: 150000,
+            transaction_count: 4,
+            ..test_block(7)
+        },
+        transactions: vec![
+            transaction("0xtransfer", 0, Some("0xa9059cbb")),
+            transaction("0xharvest", 1, Some("0x12345678")),
+            transaction("0xunknown", 2, Some("0xdeadbeef")),
+            transaction("0xplain", 3, None),
+        ],
+        ..test_block_data(7)
+    };
+    db.store_block_data(&data, 0)
+        .await
+        .expect("Failed to store block data");
+
+    let names: Vec<(String, Option<String>)> = db
+        .get_transactions_by_block(7)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|tx| (tx.hash, tx.method_name))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("0xtransfer".to_string(), Some("transfer".to_string())),
+            ("0xharvest".to_string(), Some("harvest".to_string())),
+            ("0xunknown".to_string(), None),
+            ("0xplain".to_string(), None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_token_balance_batch_keeps_newer_balances() {
+    let path = "./data/test_token_balances.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let balance = |account: &str, balance: &str, block_number: i64| TokenBalance {
+        id: None,
+        account_address: account.to_string(),
+        token_address: "0xtoken".to_string(),
+        balance: balance.to_string(),
+        block_number,
+        last_updated_block: block_number,
+        created_at: None,
+        updated_at: None,
+    };
+    db.upsert_token_balances_batch(&[balance("0xa", "10", 200), balance("0xb", "20", 200)])
+        .await
+        .expect("Failed to upsert token balances");
+
+    // A balance read at an older block doesn't replace a newer one
+    db.upsert_token_balances_batch(&[balance("0xa", "5", 100), balance("0xb", "30", 300)])
+        .await
+        .expect("Failed to upsert token balances");
+
+    let a = db
+        .get_token_balance("0xa", "0xtoken")
+        .await
+        .unwrap()
+        .unwrap();
+    let b = db
+        .get_token_balance("0xb", "0xtoken")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((a.balance.as_str(), a.last_updated_block), ("10", 200));
+    assert_eq!((b.balance.as_str(), b.last_updated_block), ("30", 300));
+}
+
+#[tokio::test]
+async fn test_token_holder_export_pages_by_balance_then_address() {
+    let path = "./data/test_holder_export.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let balances: Vec<TokenBalance> = [("0xa", "9"), ("0xb", "10"), ("0xc", "10"), ("0xd", "2")]
+        .into_iter()
+        .map(|(account, balance)| TokenBalance {
+            id: None,
+            account_address: account.to_string(),
+            token_address: "0xtoken".to_string(),
+            balance: balance.to_string(),
+            block_number: 100,
+            last_updated_block: 100,
+            created_at: None,
+            updated_at: None,
+        })
+        .collect();
+    db.upsert_token_balances_batch(&balances)
+        .await
+        .expect("Failed to upsert token balances");
+
+    let export = |range: HolderExportRange| {
+        let db = &db;
+        async move {
+            db.stream_token_holders("0xtoken", &range)
+                .map(|holder| {
+                    let holder = holder.expect("Failed to read holder");
+                    (holder.address, holder.balance)
+                })
+                .collect::<Vec<_>>()
+                .await
+        }
+    };
+    let row = |address: &str, balance: &str| (address.to_string(), balance.to_string());
+
+    // Balances order numerically, ties by address, and stop at min_balance
+    let first_page = export(HolderExportRange {
+        min_balance: "3".to_string(),
+        after: None,
+        limit: Some(2),
+    })
+    .await;
+    assert_eq!(first_page, vec![row("0xb", "10"), row("0xc", "10")]);
+
+    // The next page resumes after the last row received
+    let next_page = export(HolderExportRange {
+        min_balance: "3".to_string(),
+        after: Some(("10".to_string(), "0xc".to_string())),
+        limit: Some(2),
+    })
+    .await;
+    assert_eq!(next_page, vec![row("0xa", "9")]);
+}
+
+#[tokio::test]
+async fn test_token_holder_snapshots_are_built_once_and_evicted() {
+    let path = "./data/test_holder_snapshots.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let holders = |rows: &[(&str, &str)]| -> Vec<(String, String, i64)> {
+        rows.iter()
+            .map(|(address, balance)| (address.to_string(), balance.to_string(), 5))
+            .collect()
+    };
+    let snapshot = db
+        .create_token_holder_snapshot("0xtoken", 10, &holders(&[("0xa", "9"), ("0xb", "10")]), 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_token_holder_snapshot("0xtoken", 10).await.unwrap(),
+        Some(snapshot)
+    );
+    // A snapshot stored meanwhile is reused
+    let again = db
+        .create_token_holder_snapshot("0xtoken", 10, &holders(&[("0xc", "1")]), 2)
+        .await
+        .unwrap();
+    assert_eq!(again, snapshot);
+
+    let page: Vec<(String, String)> = db
+        .stream_token_holder_snapshot(
+            snapshot,
+            &HolderExportRange {
+                min_balance: "1".to_string(),
+                after: None,
+                limit: None,
+            },
+        )
+        .map(|holder| {
+            let holder = holder.expect("Failed to read holder");
+            (holder.address, holder.balance)
+        })
+        .collect()
+        .await;
+    assert_eq!(
+        page,
+        vec![
+            ("0xb".to_string(), "10".to_string()),
+            ("0xa".to_string(), "9".to_string())
+        ]
+    );
+
+    // Only the newest snapshots are kept
+    for block in [11, 12] {
+        db.create_token_holder_snapshot("0xtoken", block, &holders(&[("0xa", "1")]), 2)
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        db.get_token_holder_snapshot("0xtoken", 10).await.unwrap(),
+        None
+    );
+    assert!(db
+        .get_token_holder_snapshot("0xtoken", 12)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+/// Largest uint256, 78 digits
+const MAX_UINT256: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+#[test]
+fn test_token_amounts_convert_to_base_units() {
+    let base_units = |amount: &str, decimals: u8| to_base_units(amount, decimals).ok();
+
+    assert_eq!(base_units("1.5", 6).as_deref(), Some("1500000"));
+    assert_eq!(base_units(" 1000 ", 6).as_deref(), Some("1000000000"));
+    assert_eq!(base_units(".25", 2).as_deref(), Some("25"));
+    assert_eq!(base_units("7.", 1).as_deref(), Some("70"));
+    // Base units carry no leading zeros, like the stored amounts
+    assert_eq!(base_units("0.000001", 6).as_deref(), Some("1"));
+    assert_eq!(base_units("0", 18).as_deref(), Some("0"));
+
+    // Fractions finer than the token's smallest unit are refused
+    assert!(to_base_units("1.0000001", 6)
+        .unwrap_err()
+        .contains("more than 6 decimals"));
+    assert!(to_base_units("1.5", 0).is_err());
+    for invalid in ["", ".", "-1", "1e6", "1,5", "0x10", "1.2.3", "½"] {
+        assert!(to_base_units(invalid, 18).is_err(), "{}", invalid);
+    }
+
+    // Amounts up to the uint256 maximum fit, in whole or in fractional units
+    assert_eq!(base_units(MAX_UINT256, 0).as_deref(), Some(MAX_UINT256));
+    let (whole, fraction) = MAX_UINT256.split_at(MAX_UINT256.len() - 18);
+    assert_eq!(
+        base_units(&format!("{}.{}", whole, fraction), 18).as_deref(),
+        Some(MAX_UINT256)
+    );
+    assert_eq!(base_units("1", 77), Some(format!("1{}", "0".repeat(77))));
+    let above_max = format!("{}6", &MAX_UINT256[..MAX_UINT256.len() - 1]);
+    assert!(to_base_units(&above_max, 0)
+        .unwrap_err()
+        .contains("out of range"));
+    assert!(to_base_units("12", 77).is_err());
+}
+
+#[tokio::test]
+async fn test_token_transfer_amount_filters_compare_numerically() {
+    let path = "./data/test_transfer_amounts.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    // Text comparison would put "9" above "10" and "1500000" below "999999"
+    let amounts = ["9", "10", "999999", "1500000", "1500001", MAX_UINT256];
+    let data = BlockData {
+        transactions: vec![Transaction {
+            hash: "0xtx1".to_string(),
+            block_number: 1,
+            from_address: "0xfrom".to_string(),
+            to_address: Some("0xtoken".to_string()),
+            value: "0".to_string(),
+            gas_used: 50000,
+            gas_price: "1000000000".to_string(),
+            status: 1,
+            transaction_index: 0,
+            method_id: None,
+            method_name: None,
+            gas_limit: Some(60000),
+            input: None,
+            input_size: None,
+        }],
+        token_transfers: amounts
+            .iter()
+            .enumerate()
+            .map(|(index, amount)| TokenTransfer {
+                id: None,
+                transaction_hash: "0xtx1".to_string(),
+                block_number: 1,
+                token_address: "0xtoken".to_string(),
+                from_address: "0xfrom".to_string(),
+                to_address: "0xto".to_string(),
+                amount: amount.to_string(),
+                token_type: Some("ERC20".to_string()),
+                token_id: None,
+                log_index: Some(index as i64),
+            })
+            .collect(),
+        ..test_block_data(1)
+    };
+    db.store_block_data(&data, 0)
+        .await
+        .expect("Failed to store block data");
+
+    let filtered = |min_amount: Option<&str>, max_amount: Option<&str>| {
+        let filter = TokenTransferFilter {
+            token_address: Some("0xtoken".to_string()),
+            min_amount: min_amount.map(str::to_string),
+            max_amount: max_amount.map(str::to_string),
+            ..Default::default()
+        };
+        let db = &db;
+        async move {
+            let (transfers, total) = db
+                .get_filtered_token_transfers(&filter, 100, 0)
+                .await
+                .expect("Failed to filter token transfers");
+            assert_eq!(transfers.len() as i64, total);
+            transfers
+                .into_iter()
+                .map(|transfer| transfer.amount)
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(
+        filtered(Some("10"), Some("1500000")).await,
+        vec!["10", "999999", "1500000"]
+    );
+    assert_eq!(filtered(None, Some("9")).await, vec!["9"]);
+    assert_eq!(
+        filtered(Some("1500001"), None).await,
+        vec!["1500001", MAX_UINT256]
+    );
+    assert_eq!(
+        filtered(Some(MAX_UINT256), Some(MAX_UINT256)).await,
+        vec![MAX_UINT256]
+    );
+}
+
+#[tokio::test]
+async fn test_unique_transfers_migration_removes_reprocessed_copies() {
+    use sqlx::{migrate::Migrator, sqlite::SqliteConnectOptions, SqlitePool};
+    use std::str::FromStr;
+
+    let path = "./data/test_unique_transfers_migration.db";
+    let migrations = "./data/test_unique_transfers_migrations";
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_dir_all(migrations);
+
+    // Bring a database up to the schema before logs and transfers were unique
+    std::fs::create_dir_all(migrations).unwrap();
+    for entry in std::fs::read_dir("./src/database/migrations").unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_name().to_string_lossy().as_ref() < "043" {
+            std::fs::copy(
+                entry.path(),
+                format!("{}/{}", migrations, entry.file_name().to_string_lossy()),
+            )
+            .unwrap();
+        }
+    }
+    let options = SqliteConnectOptions::from_str(path)
+        .unwrap()
+        .create_if_missing(true)
+        .foreign_keys(false);
+    let pool = SqlitePool::connect_with(options).await.unwrap();
+    Migrator::new(std::path::Path::new(migrations))
+        .await
+        .unwrap()
+        .run(&pool)
+        .await
+        .unwrap();
+
+    // 0xtx1 was processed twice, inserting its logs and transfers again. The logs of 0xtx2
+    // were never stored.
+    let transfer_topic = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    for _ in 0..2 {
+        for (log_index, topic0) in [(0, transfer_topic), (1, "0xapproval"), (2, transfer_topic)] {
+            sqlx::query(
+                "INSERT INTO logs (transaction_hash, block_number, address, topic0, topic1, topic2, log_index) VALUES ('0xtx1', 1, '0xtoken', ?, '0xfrom', '0xto', ?)",
+            )
+            .bind(topic0)
+            .bind(log_index)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        for amount in ["100", "200"] {
+            sqlx::query(
+                "INSERT INTO token_transfers (transaction_hash, block_number, token_address, from_address, to_address, amount) VALUES ('0xtx1', 1, '0xtoken', '0xfrom', '0xto', ?)",
+            )
+            .bind(amount)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+    }
+    sqlx::query(
+        "INSERT INTO token_transfers (transaction_hash, block_number, token_address, from_address, to_address, amount) VALUES ('0xtx2', 1, '0xtoken', '0xfrom', '0xto', '300')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to migrate database");
+
+    let logs: Vec<(i64, Option<String>)> = db
+        .get_logs_by_transaction("0xtx1")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|log| (log.log_index, log.topic0))
+        .collect();
+    assert_eq!(
+        logs,
+        vec![
+            (0, Some(transfer_topic.to_string())),
+            (1, Some("0xapproval".to_string())),
+            (2, Some(transfer_topic.to_string())),
+        ]
+    );
+
+    // The first copy of each transfer is matched with its Transfer log, in order
+    let mut transfers: Vec<(String, String, Option<i64>)> = db
+        .get_token_transfers_by_block(1)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|transfer| {
+            (
+                transfer.transaction_hash,
+                transfer.amount,
+                transfer.log_index,
+            )
+        })
+        .collect();
+    transfers.sort();
+    assert_eq!(
+        transfers,
+        vec![
+            ("0xtx1".to_string(), "100".to_string(), Some(0)),
+            ("0xtx1".to_string(), "200".to_string(), Some(2)),
+            ("0xtx2".to_string(), "300".to_string(), None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_delegations_keep_the_newest_event() {
+    let path = "./data/test_delegations.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let change = |block_number: i64, account: &str, delegate: Option<&str>, votes: Option<&str>| {
+        DelegationChange {
+            token_address: "0xtoken".to_string(),
+            account_address: account.to_string(),
+            delegate: delegate.map(str::to_string),
+            votes: votes.map(str::to_string),
+            block_number,
+            log_index: 0,
+        }
+    };
+    let block = |number: i64, delegation_changes: Vec<DelegationChange>| BlockData {
+        delegation_changes,
+        ..test_block_data(number)
+    };
+
+    // The newer block is stored first, as workers may do
+    let newer = block(
+        200,
+        vec![
+            change(200, "0xalice", Some("0xdave"), None),
+            change(200, "0xdave", None, Some("100")),
+        ],
+    );
+    let older = block(
+        100,
+        vec![
+            change(100, "0xalice", Some("0xcarol"), None),
+            change(100, "0xcarol", None, Some("50")),
+            change(100, "0xdave", None, Some("1")),
+        ],
+    );
+    for data in [&newer, &older, &newer] {
+        db.store_block_data(data, 0)
+            .await
+            .expect("Failed to store block data");
+    }
+
+    let (delegates, total) = db.get_token_delegates("0xtoken", 10, 0).await.unwrap();
+    let delegates: Vec<(&str, &str, i64)> = delegates
+        .iter()
+        .map(|delegate| {
+            (
+                delegate.address.as_str(),
+                delegate.votes.as_str(),
+                delegate.delegators,
+            )
+        })
+        .collect();
+    assert_eq!(total, 2);
+    assert_eq!(delegates, vec![("0xdave", "100", 1), ("0xcarol", "50", 0)]);
+}
+
+#[tokio::test]
+async fn test_approvals_keep_the_newest_event() {
+    let path = "./data/test_approvals.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let change = |block_number: i64, spender: &str, kind: &str, value: &str| ApprovalChange {
+        token_address: "0xtoken".to_string(),
+        owner_address: "0xalice".to_string(),
+        spender_address: spender.to_string(),
+        kind: kind.to_string(),
+        value: value.to_string(),
+        transaction_hash: format!("0xtx{}", block_number),
+        block_number,
+        log_index: 0,
+    };
+    let block = |number: i64, approval_changes: Vec<ApprovalChange>| BlockData {
+        approval_changes,
+        ..test_block_data(number)
+    };
+
+    // The newer block is stored first, as workers may do
+    let unlimited = ethers::types::U256::MAX.to_string();
+    let newer = block(
+        200,
+        vec![
+            change(200, "0xrouter", "allowance", "0"),
+            change(200, "0xmarket", "operator", "1"),
+        ],
+    );
+    let older = block(
+        100,
+        vec![
+            change(100, "0xrouter", "allowance", &unlimited),
+            change(100, "0xvault", "allowance", &unlimited),
+            change(100, "0xmarket", "operator", "0"),
+        ],
+    );
+    for data in [&newer, &older, &newer] {
+        db.store_block_data(data, 0)
+            .await
+            .expect("Failed to store block data");
+    }
+
+    let (approvals, total) = db
+        .get_account_approvals("0xalice", None, false, 10, 0)
+        .await
+        .unwrap();
+    let approvals: Vec<(&str, &str, bool)> = approvals
+        .iter()
+        .map(|approval| {
+            (
+                approval.spender_address.as_str(),
+                approval.value.as_str(),
+                approval.unlimited,
+            )
+        })
+        .collect();
+    assert_eq!(total, 2);
+    assert_eq!(
+        approvals,
+        vec![
+            ("0xmarket", "1", false),
+            ("0xvault", unlimited.as_str(), true)
+        ]
+    );
+
+    let (revoked, total) = db
+        .get_account_approvals("0xalice", Some("0xtoken"), true, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(total, 3);
+    assert!(revoked
+        .iter()
+        .any(|approval| approval.spender_address == "0xrouter" && approval.value == "0"));
+}
+
+#[tokio::test]
+async fn test_withdrawals_by_address_and_validator() {
+    let path = "./data/test_withdrawals.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let withdrawal =
+        |block_number: i64, index: i64, validator_index: i64, address: &str| Withdrawal {
+            id: None,
+            block_number,
+            withdrawal_index: index,
+            validator_index,
+            address: address.to_string(),
+            amount: (1_000_000 * (index + 1)).to_string(),
+            created_at: None,
+        };
+    for (number, withdrawals) in [
+        (
+            1,
+            vec![
+                withdrawal(1, 0, 7, "0xstaker"),
+                withdrawal(1, 1, 8, "0xstaker"),
+            ],
+        ),
+        (
+            2,
+            vec![
+                withdrawal(2, 2, 7, "0xstaker"),
+                withdrawal(2, 3, 9, "0xother"),
+            ],
+        ),
+    ] {
+        let data = BlockData {
+            block: Block {
+                withdrawal_count: Some(withdrawals.len() as i64),
+                ..test_block(number)
+            },
+            withdrawals,
+            ..test_block_data(number)
+        };
+        db.store_block_data(&data, 0)
+            .await
+            .expect("Failed to store block data");
+    }
+
+    let by_validator = db.get_withdrawals_by_validator(7, 10, 0).await.unwrap();
+    let indexes: Vec<i64> = by_validator.iter().map(|w| w.withdrawal_index).collect();
+    assert_eq!(indexes, vec![2, 0]);
+    assert_eq!(
+        db.get_withdrawal_totals_by_validator(7).await.unwrap(),
+        (2, 4_000_000)
+    );
+
+    let page = db
+        .get_withdrawals_by_address("0xstaker", 1, 1)
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].withdrawal_index, 1);
+    assert_eq!(
+        db.get_withdrawal_totals_by_address("0xstaker")
+            .await
+            .unwrap(),
+        (3, 6_000_000)
+    );
+
+    let both = db
+        .get_withdrawals(Some("0xstaker"), Some(8), 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(both.len(), 1);
+    assert_eq!(
+        db.get_withdrawal_totals(Some("0xother"), Some(7))
+            .await
+            .unwrap(),
+        (0, 0)
+    );
+}
+
+#[tokio::test]
+async fn test_deposits_by_depositor_and_pubkey() {
+    let path = "./data/test_deposits.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let deposit = |block_number: i64, log_index: i64, depositor: &str, pubkey: &str| Deposit {
+        block_number,
+        log_index,
+        transaction_hash: format!("0xtx{}{}", block_number, log_index),
+        deposit_index: block_number * 10 + log_index,
+        depositor: depositor.to_string(),
+        pubkey: pubkey.to_string(),
+        withdrawal_credentials: format!("0x01{}", "00".repeat(31)),
+        amount: "32000000000".to_string(),
+        signature: "0xsig".to_string(),
+        validator_index: None,
+    };
+    let block = |number: i64, deposits: Vec<Deposit>| BlockData {
+        deposits,
+        ..test_block_data(number)
+    };
+    for data in [
+        block(
+            1,
+            vec![
+                deposit(1, 0, "0xstaker", "0xkey1"),
+                deposit(1, 1, "0xstaker", "0xkey2"),
+            ],
+        ),
+        block(2, vec![deposit(2, 0, "0xpool", "0xkey1")]),
+        // Stored again, the block keeps a single copy of its deposits
+        block(2, vec![deposit(2, 0, "0xpool", "0xkey1")]),
+    ] {
+        db.store_block_data(&data, 0)
+            .await
+            .expect("Failed to store block data");
+    }
+    db.upsert_validators_batch(&[Validator {
+        validator_index: 42,
+        pubkey: "0xkey1".to_string(),
+        withdrawal_credentials: format!("0x01{}", "00".repeat(31)),
+        balance_gwei: 64000000000,
+        effective_balance_gwei: 64000000000,
+        status: "active_ongoing".to_string(),
+        slashed: false,
+        activation_eligibility_epoch: None,
+        activation_epoch: None,
+        exit_epoch: None,
+        withdrawable_epoch: None,
+        updated_at: None,
+    }])
+    .await
+    .unwrap();
+
+    let by_pubkey = db.get_deposits(None, Some("0xkey1"), 10, 0).await.unwrap();
+    let found: Vec<(i64, &str, Option<i64>)> = by_pubkey
+        .iter()
+        .map(|d| (d.block_number, d.depositor.as_str(), d.validator_index))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(2, "0xpool", Some(42)), (1, "0xstaker", Some(42))]
+    );
+    assert_eq!(
+        db.get_deposit_totals(None, Some("0xkey1")).await.unwrap(),
+        (2, 64_000_000_000)
+    );
+
+    let by_depositor = db.get_deposits(Some("0xstaker"), None, 1, 0).await.unwrap();
+    assert_eq!(by_depositor.len(), 1);
+    assert_eq!(by_depositor[0].pubkey, "0xkey2");
+    assert_eq!(by_depositor[0].validator_index, None);
+    assert_eq!(db.get_deposit_totals(None, None).await.unwrap().0, 3);
+    assert_eq!(db.get_deposits_by_block(2).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_proposer_duties_and_sync_participation() {
+    let path = "./data/test_proposer_duties.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let duty = |slot: i64, validator_index: i64| ProposerDuty {
+        slot,
+        epoch: slot / 32,
+        validator_index,
+        pubkey: format!("0xkey{}", validator_index),
+    };
+    assert!(!db.has_proposer_duties(3).await.unwrap());
+    db.insert_proposer_duties(
+        &(96..128)
+            .map(|slot| duty(slot, slot * 10))
+            .collect::<Vec<_>>(),
+    )
+    .await
+    .unwrap();
+    // Fetched again by another worker, the stored duties are kept
+    db.insert_proposer_duties(&[duty(100, 1)]).await.unwrap();
+    assert!(db.has_proposer_duties(3).await.unwrap());
+    assert!(!db.has_proposer_duties(4).await.unwrap());
+
+    let duties = db.get_proposer_duties(99, 101).await.unwrap();
+    let scheduled: Vec<(i64, i64)> = duties
+        .iter()
+        .map(|duty| (duty.slot, duty.validator_index))
+        .collect();
+    assert_eq!(scheduled, vec![(99, 990), (100, 1000), (101, 1010)]);
+
+    let data = BlockData {
+        block: Block {
+            timestamp: 1700000000,
+            slot: Some(101),
+            proposer_index: Some(1010),
+            epoch: Some(3),
+            sync_participation: Some(96.875),
+            ..test_block(50)
+        },
+        ..test_block_data(50)
+    };
+    db.store_block_data(&data, 0)
+        .await
+        .expect("Failed to store block data");
+
+    let block = db.get_block_by_number(50).await.unwrap().unwrap();
+    assert_eq!(block.sync_participation, Some(96.875));
+    assert_eq!(BlockResponse::from(&block).sync_participation, Some(96.875));
+}
+
+#[tokio::test]
+async fn test_epoch_summaries() {
+    let path = "./data/test_epochs.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let block_data = |number: i64, slot: i64, attestations: i64| BlockData {
+        block: Block {
+            timestamp: 1700000000 + slot * 12,
+            slot: Some(slot),
+            proposer_index: Some(slot * 10),
+            epoch: Some(slot / 32),
+            block_deposit_count: Some(1),
+            sync_participation: Some(attestations as f64),
+            attestation_count: Some(attestations),
+            slashing_count: Some(0),
+            ..test_block(number)
+        },
+        ..test_block_data(number)
+    };
+
+    for (number, slot, attestations) in [(10, 96, 90), (11, 98, 100), (12, 99, 110)] {
+        db.store_block_data(&block_data(number, slot, attestations), 0)
+            .await
+            .expect("Failed to store block data");
+    }
+    let epoch = db.get_epoch(3).await.unwrap().unwrap();
+    assert_eq!((epoch.blocks_proposed, epoch.missed_slots), (3, 1));
+    assert_eq!(
+        (epoch.attestations, epoch.deposits, epoch.slashings),
+        (Some(300), Some(3), Some(0))
+    );
+    assert_eq!(epoch.avg_sync_participation, Some(100.0));
+    assert!(db.get_epoch(4).await.unwrap().is_none());
+
+    // The trailing slots of epoch 3 count as missed once a block of epoch 4 is stored
+    db.store_block_data(&block_data(13, 130, 120), 0)
+        .await
+        .expect("Failed to store block data");
+    assert_eq!(db.get_epoch(3).await.unwrap().unwrap().missed_slots, 29);
+    let epoch = db.get_epoch(4).await.unwrap().unwrap();
+    assert_eq!((epoch.blocks_proposed, epoch.missed_slots), (1, 2));
+    assert_eq!(epoch.attestations, Some(120));
+}
+
+#[cfg(feature = "beacon")]
+#[tokio::test]
+async fn test_randao_mix_from_beacon_state() {
+    use axum::{extract::Path, http::StatusCode, routing::get, Json, Router};
+    use eth_indexer_rs::{beacon::BeaconClient, events::EventLog};
+    use serde_json::json;
+
+    let mix = format!("0x{}", "ab".repeat(32));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let router = Router::new().route(
+        "/eth/v1/beacon/states/:slot/randao",
+        get({
+            let mix = mix.clone();
+            move |Path(slot): Path<u64>| async move {
+                if slot == 100 {
+                    let body = json!({ "finalized": true, "data": { "randao": mix } });
+                    (StatusCode::OK, Json(body))
+                } else {
+                    let body = json!({ "code": 404, "message": "State not found" });
+                    (StatusCode::NOT_FOUND, Json(body))
+                }
+            }
+        }),
+    );
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service()),
+    );
+
+    let path = "./data/test_randao.db";
+    let _ = std::fs::remove_file(path);
+    let db = Arc::new(
+        DatabaseService::new(path)
+            .await
+            .expect("Failed to open database"),
+    );
+    let config = AppConfig::load().expect("Failed to load configuration from .env");
+    let beacon = BeaconClient::new(
+        &format!("http://{}", address),
+        &config,
+        db.clone(),
+        EventLog::new(db.clone()),
+    )
+    .unwrap();
+
+    assert_eq!(beacon.get_randao_mix(100).await.unwrap(), Some(mix));
+    // States the node no longer keeps leave the mix unknown
+    assert_eq!(beacon.get_randao_mix(5).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_block_status_follows_finality_checkpoints() {
+    let path = "./data/test_finality.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    // Without checkpoints the status comes from block age
+    let finality = db.get_finality().await.unwrap();
+    assert_eq!(finality, Finality::default());
+    assert_eq!(block_status(68, 100, &finality), "finalized");
+    assert_eq!(block_status(80, 100, &finality), "safe");
+    assert_eq!(block_status(99, 100, &finality), "pending");
+    assert_eq!(block_status(100, 100, &finality), "latest");
+
+    db.upsert_finality_checkpoint("justified", 11, "0xaa", Some(95))
+        .await
+        .unwrap();
+    db.upsert_finality_checkpoint("finalized", 10, "0xbb", Some(60))
+        .await
+        .unwrap();
+    // A moved checkpoint replaces the previous one
+    db.upsert_finality_checkpoint("finalized", 11, "0xcc", Some(90))
+        .await
+        .unwrap();
+
+    let checkpoints = db.get_finality_checkpoints().await.unwrap();
+    assert_eq!(checkpoints.len(), 2);
+    let finalized = checkpoints.iter().find(|c| c.kind == "finalized").unwrap();
+    assert_eq!((finalized.epoch, finalized.root.as_str()), (11, "0xcc"));
+
+    let finality = db.get_finality().await.unwrap();
+    assert_eq!(finality.finalized_block, Some(90));
+    assert_eq!(finality.justified_block, Some(95));
+    assert_eq!(block_status(90, 100, &finality), "finalized");
+    assert_eq!(block_status(91, 100, &finality), "safe");
+    assert_eq!(block_status(96, 100, &finality), "pending");
+    assert_eq!(block_status(100, 100, &finality), "latest");
+}
+
+#[tokio::test]
+async fn test_rewritten_blocks_mark_their_hours_stale() {
+    let path = "./data/test_stale_aggregates.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let block = Block {
+        hash: "0xaa".to_string(),
+        parent_hash: "0x00".to_string(),
+        timestamp: 7_200 + 59,
+        gas_used: 21_000,
+        transaction_count: 1,
+        ..test_block(1)
+    };
+    db.insert_block(&block).await.unwrap();
+    db.insert_block(&Block {
+        number: 2,
+        hash: "0xbb".to_string(),
+        timestamp: 3_600 * 5,
+        ..block.clone()
+    })
+    .await
+    .unwrap();
+
+    let stale = db.get_stale_aggregate_hours(10).await.unwrap();
+    let hours: Vec<i64> = stale.iter().map(|(_, hour)| *hour).collect();
+    assert_eq!(hours, vec![7_200, 18_000]);
+    db.clear_stale_aggregate_hours(stale[1].0).await.unwrap();
+    assert!(db.get_stale_aggregate_hours(10).await.unwrap().is_empty());
+
+    // A reorg moving the block to another hour refreshes both hours
+    db.insert_block(&Block {
+        hash: "0xcc".to_string(),
+        timestamp: 10_800 + 1,
+        ..block.clone()
+    })
+    .await
+    .unwrap();
+    let stale = db.get_stale_aggregate_hours(10).await.unwrap();
+    let hours: Vec<i64> = stale.iter().map(|(_, hour)| *hour).collect();
+    assert_eq!(hours, vec![7_200, 10_800]);
+
+    // An hour written again after it was read stays stale
+    db.insert_block(&Block {
+        number: 3,
+        hash: "0xdd".to_string(),
+        timestamp: 7_200 + 60,
+        ..block
+    })
+    .await
+    .unwrap();
+    db.clear_stale_aggregate_hours(stale[1].0).await.unwrap();
+    let stale = db.get_stale_aggregate_hours(10).await.unwrap();
+    let hours: Vec<i64> = stale.iter().map(|(_, hour)| *hour).collect();
+    assert_eq!(hours, vec![7_200]);
+}
+
+#[test]
+fn test_log_directives_expand_crate_modules() {
+    assert_eq!(
+        expand_directives("info, indexer=debug,rpc::multicall=trace,hyper=warn"),
+        "info,eth_indexer_rs::indexer=debug,eth_indexer_rs::rpc::multicall=trace,hyper=warn"
+    );
+    assert_eq!(
+        expand_directives("database[query]=debug,warn"),
+        "eth_indexer_rs::database[query]=debug,warn"
+    );
+
+    assert!(parse_directives("info,indexer=debug,rpc=warn").is_ok());
+    assert!(parse_directives("indexer=loud").is_err());
+    assert!(parse_directives(" ").is_err());
+}
+
+#[tokio::test]
+async fn test_live_subscribers_are_capped_and_evicted() {
+    let log = |index: i64| Log {
+        id: None,
+        transaction_hash: format!("0x{:064x}", index),
+        block_number: 1,
+        address: "0xc0ffee0000000000000000000000000000000000".to_string(),
+        topic0: None,
+        topic1: None,
+        topic2: None,
+        topic3: None,
+        data: None,
+        log_index: index,
+    };
+    let log_index = |item: Option<LiveLog>| match item {
+        Some(LiveLog::Log(log)) => log.log_index,
+        other => panic!("Expected a log, got {:?}", other),
+    };
+
+    let live = LiveDispatcher::new().with_limits(LiveLimits {
+        max_subscribers: 1,
+        buffer_size: 2,
+        slow_consumer_timeout: Duration::from_secs(3600),
+    });
+    let mut stream = Box::pin(live.subscribe_logs(LogFilter::default()).unwrap());
+    assert!(live.subscribe_logs(LogFilter::default()).is_none());
+
+    // The third log doesn't fit the buffer and is reported once there is room again
+    live.publish_logs(&[log(0), log(1), log(2)]);
+    assert_eq!(log_index(stream.next().await), 0);
+    assert_eq!(log_index(stream.next().await), 1);
+    live.publish_logs(&[log(3)]);
+    assert!(matches!(stream.next().await, Some(LiveLog::Lagged(1))));
+    assert_eq!(log_index(stream.next().await), 3);
+
+    let stats = live.stats();
+    assert_eq!(
+        (
+            stats.subscribers,
+            stats.delivered,
+            stats.dropped,
+            stats.rejected
+        ),
+        (1, 3, 1, 1)
+    );
+
+    // A disconnected subscriber frees its slot
+    drop(stream);
+    assert_eq!(live.stats().subscribers, 0);
+
+    // With a zero timeout the first dropped log evicts the subscriber, after its buffer drains
+    let live = live.with_limits(LiveLimits {
+        max_subscribers: 1,
+        buffer_size: 2,
+        slow_consumer_timeout: Duration::ZERO,
+    });
+    let mut stream = Box::pin(live.subscribe_logs(LogFilter::default()).unwrap());
+    live.publish_logs(&[log(4), log(5), log(6)]);
+    assert_eq!(log_index(stream.next().await), 4);
+    assert_eq!(log_index(stream.next().await), 5);
+    assert!(matches!(stream.next().await, Some(LiveLog::Evicted)));
+    assert!(stream.next().await.is_none());
+    assert_eq!(live.stats().evicted, 1);
+}
+
+#[test]
+fn test_market_data_from_provider_responses() {
+    let coingecko = serde_json::json!({
+        "ethereum": {
+            "usd": 3500.5,
+            "btc": 0.052,
+            "usd_market_cap": 420000000000.0,
+            "usd_24h_change": -1.25
+        }
+    });
+    let market = MarketData::from_coingecko(&coingecko).unwrap();
+    assert_eq!(market.price_usd, 3500.5);
+    assert_eq!(market.price_btc, Some(0.052));
+    assert_eq!(market.market_cap_usd, Some(420000000000.0));
+    assert_eq!(market.change_24h_percentage, Some(-1.25));
+    assert_eq!(market.source, MarketDataProvider::Coingecko);
+
+    let coinbase = serde_json::json!({ "open": "3200.00", "last": "3520.00", "volume": "1000" });
+    let market = MarketData::from_coinbase(&coinbase).unwrap();
+    assert_eq!(market.price_usd, 3520.0);
+    assert!((market.change_24h_percentage.unwrap() - 10.0).abs() < 1e-9);
+    assert_eq!(market.market_cap_usd, None);
+
+    assert!(MarketData::from_coingecko(&serde_json::json!({ "error": "rate limited" })).is_none());
+    assert!(MarketData::from_coinbase(&serde_json::json!({ "message": "NotFound" })).is_none());
+    assert_eq!(
+        MarketDataProvider::parse(" CoinGecko"),
+        Some(MarketDataProvider::Coingecko)
+    );
+    assert_eq!(MarketDataProvider::parse("binance"), None);
+}
+
+#[tokio::test]
+async fn test_transfer_values_from_pegs_and_price_history() {
+    let path = "./data/test_eth_prices.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    // Blocks 1 and 2 in different hours, a price recorded for the hour of block 1 only
+    for (number, timestamp) in [(1, 1700002800 + 60), (2, 1700006400 + 60)] {
+        sqlx::query(
+            "INSERT INTO blocks (number, hash, parent_hash, timestamp, gas_used, gas_limit, transaction_count) VALUES (?, ?, '0x', ?, 0, 0, 0)",
+        )
+        .bind(number)
+        .bind(format!("0xblock{}", number))
+        .bind(timestamp)
+        .execute(&db.pool)
+        .await
+        .expect("Failed to insert block");
+    }
+    db.record_eth_price(1700002800 + 600, 3000.0, "coingecko")
+        .await
+        .expect("Failed to record ETH price");
+    db.record_eth_price(1700002800 + 1200, 3100.0, "coinbase")
+        .await
+        .expect("Failed to record ETH price");
+    let prices = db
+        .get_eth_prices_at_blocks(&[1, 2])
+        .await
+        .expect("Failed to get ETH prices");
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices[&1], 3100.0);
+
+    let token = |symbol: &str, decimals: u8, curated: bool| Token {
+        address: "0xtoken".to_string(),
+        name: None,
+        symbol: Some(symbol.to_string()),
+        decimals: Some(decimals),
+        token_type: "ERC20".to_string(),
+        first_seen_block: 1,
+        last_seen_block: 1,
+        total_transfers: 0,
+        created_at: None,
+        updated_at: None,
+        name_source: None,
+        symbol_source: None,
+        decimals_source: None,
+        logo_uri: None,
+        curated,
+        total_supply: None,
+        total_supply_block: None,
+        holder_count: None,
+        recent_transfers: None,
+        token_type_confidence: None,
+    };
+
+    let usdc = TransferValue::of("2500000", &token("USDC", 6, true), None, Some(3000.0));
+    assert_eq!(usdc.value_usd, Some(2.5));
+    assert_eq!(usdc.price_source, Some(PriceSource::Peg));
+
+    let weth = token("WETH", 18, true);
+    let historical = TransferValue::of("500000000000000000", &weth, Some(3100.0), Some(4000.0));
+    assert_eq!(historical.value_usd, Some(1550.0));
+    assert_eq!(historical.price_source, Some(PriceSource::Historical));
+    let current = TransferValue::of("500000000000000000", &weth, None, Some(4000.0));
+    assert_eq!(current.value_usd, Some(2000.0));
+    assert_eq!(current.price_source, Some(PriceSource::Current));
+    assert_eq!(
+        TransferValue::of("1", &weth, None, None),
+        TransferValue::default()
+    );
+
+    // Tokens named like a stablecoin but not curated are not valued
+    assert_eq!(
+        TransferValue::of("1000000", &token("USDC", 6, false), None, Some(3000.0)),
+        TransferValue::default()
+    );
+    assert_eq!(
+        TransferValue::of("1000000", &token("UNI", 18, true), None, Some(3000.0)),
+        TransferValue::default()
+    );
+}
+
+#[test]
+fn test_image_sizes_snap_to_buckets() {
+    use eth_indexer_rs::database::ImageParams;
+
+    let size = |size: Option<u32>| ImageParams { size }.size();
+    assert_eq!(size(None), 128);
+    assert_eq!(size(Some(1)), 32);
+    assert_eq!(size(Some(64)), 64);
+    assert_eq!(size(Some(65)), 128);
+    assert_eq!(size(Some(300)), 512);
+    assert_eq!(size(Some(100_000)), 512);
+}
+
+#[test]
+fn test_api_key_redacted_from_logged_uris() {
+    use eth_indexer_rs::api::auth::redact_api_key;
+
+    let redact = |uri: &str| redact_api_key(&uri.parse().unwrap());
+    assert_eq!(redact("/api/blocks"), "/api/blocks");
+    assert_eq!(
+        redact("/api/live/stream?api_key=secret&topics=blocks"),
+        "/api/live/stream?api_key=REDACTED&topics=blocks"
+    );
+    assert_eq!(
+        redact("/api/logs?my_api_key=1&api_key="),
+        "/api/logs?my_api_key=1&api_key=REDACTED"
+    );
+}
+
+#[tokio::test]
+async fn test_api_rate_limits_and_quotas() {
+    use axum::{
+        body::{Body, HttpBody},
+        extract::ConnectInfo,
+        http::{Request, StatusCode},
+        middleware,
+        response::Response,
+        routing::get,
+        Router,
+    };
+    use eth_indexer_rs::api::auth::{
+        create_api_key, enforce_api_access, ApiAuth, NewApiKeyOptions,
+    };
+    use std::net::{IpAddr, SocketAddr};
+    use tower::ServiceExt;
+
+    let path = "./data/test_rate_limits.db";
+    let _ = std::fs::remove_file(path);
+    let db = Arc::new(
+        DatabaseService::new(path)
+            .await
+            .expect("Failed to open database"),
+    );
+    let mut config = AppConfig::load().expect("Failed to load configuration from .env");
+    config.api_auth_required = false;
+    config.api_anonymous_requests_per_minute = 2;
+    let router = |config: AppConfig| {
+        Router::new()
+            .route("/api/blocks", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                Arc::new(ApiAuth::new(db.clone(), config)),
+                enforce_api_access,
+            ))
+    };
+    let app = router(config.clone());
+    let request = |app: &Router, ip: IpAddr, key: Option<&str>| {
+        let mut request = Request::builder()
+            .uri("/api/blocks")
+            .extension(ConnectInfo(SocketAddr::new(ip, 443)));
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+    async fn json(response: Response) -> serde_json::Value {
+        let bytes = response.into_body().data().await.unwrap().unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    // Anonymous clients are limited per IP, with the requests left in the headers
+    let ip = IpAddr::from([192, 0, 2, 1]);
+    for remaining in ["1", "0"] {
+        let response = request(&app, ip, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-ratelimit-remaining"], remaining);
+    }
+    let response = request(&app, ip, None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+    let body = json(response).await;
+    assert_eq!(
+        body["error"],
+        "Rate limit of 2 requests per minute exceeded"
+    );
+    assert!((1..=60).contains(&body["retry_after_seconds"].as_u64().unwrap()));
+    let other = IpAddr::from([192, 0, 2, 2]);
+    let response = request(&app, other, None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Keys are limited by their own settings, here a daily quota without a minute limit
+    let key = create_api_key(
+        &db,
+        &NewApiKeyOptions {
+            name: "quota".to_string(),
+            requests_per_minute: 0,
+            daily_quota: Some(3),
+        },
+    )
+    .await
+    .expect("Failed to create API key");
+    for _ in 0..3 {
+        let response = request(&app, ip, Some(&key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = request(&app, ip, Some(&key)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().get("retry-after").is_none());
+    assert_eq!(
+        json(response).await["error"],
+        "Daily quota of 3 requests exhausted"
+    );
+    let response = request(&app, ip, Some("eik_unknown")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Past 10000 tracked clients the one seen longest ago is forgotten
+    config.api_anonymous_requests_per_minute = 1;
+    let app = router(config);
+    let first = IpAddr::from([10, 0, 0, 0]);
+    assert_eq!(
+        request(&app, first, None).await.unwrap().status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        request(&app, first, None).await.unwrap().status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    for client in 1..=10_000u32 {
+        let ip = IpAddr::from((10 << 24 | client).to_be_bytes());
+        assert_eq!(
+            request(&app, ip, None).await.unwrap().status(),
+            StatusCode::OK
+        );
+    }
+    assert_eq!(
+        request(&app, first, None).await.unwrap().status(),
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_api_error_responses() {
+    use axum::{body::HttpBody, http::StatusCode, response::IntoResponse};
+
+    async fn body(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = response.into_body().data().await.unwrap().unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    let (status, json) = body(ApiError::NotFound("Block not found".to_string())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        json,
+        serde_json::json!({ "error": "Block not found", "code": "not_found", "status": 404 })
+    );
+
+    let (status, json) = body(ApiError::BadRequest("Invalid cursor".to_string())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["code"], "bad_request");
+
+    let (status, json) = body(ApiError::Unprocessable("Invalid address".to_string())).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(json["status"], 422);
+
+    // Internal errors answer the context, not the underlying error
+    let error = ApiError::internal("Failed to fetch accounts", "database is locked");
+    let (status, json) = body(error).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(json["error"], "Failed to fetch accounts");
+
+    let response = ApiError::RpcOverloaded.into_response();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()["retry-after"], "1");
+    let (_, json) = body(ApiError::RpcOverloaded).await;
+    assert_eq!(json["retry_after_seconds"], 1);
+
+    let (status, json) = body(ApiError::Unauthorized("Invalid API key".to_string())).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(json["code"], "unauthorized");
+    let (status, _) = body(ApiError::Forbidden("Admin API is disabled".to_string())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Retry delays are rounded up to whole seconds
+    let limited = || ApiError::TooManyRequests {
+        message: "Rate limit of 60 requests per minute exceeded".to_string(),
+        retry_after: Some(std::time::Duration::from_millis(2500)),
+    };
+    let response = limited().into_response();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "3");
+    let (_, json) = body(limited()).await;
+    assert_eq!(json["code"], "too_many_requests");
+    assert_eq!(json["retry_after_seconds"], 3);
+
+    let quota = ApiError::TooManyRequests {
+        message: "Daily quota of 1000 requests exhausted".to_string(),
+        retry_after: None,
+    }
+    .into_response();
+    assert!(quota.headers().get("retry-after").is_none());
+}
+
+#[tokio::test]
+async fn test_shard_rotation_moves_finalized_blocks() {
+    use eth_indexer_rs::{
+        database::TransactionCursor, events::EventLog, sharding::ShardingService,
+    };
+
+    let path = "./data/test_shards.db";
+    for file in [
+        path,
+        "./data/test_shards.shard-0.db",
+        "./data/test_shards.shard-100.db",
+    ] {
+        let _ = std::fs::remove_file(file);
+    }
+    let db = Arc::new(
+        DatabaseService::new(path)
+            .await
+            .expect("Failed to open database")
+            .with_shards(100)
+            .await
+            .expect("Failed to open shards"),
+    );
+    let mut config = AppConfig::load().expect("Failed to load configuration from .env");
+    config.database_shard_confirmations = 10;
+    config.prune_batch_blocks = 5;
+    let sharding = ShardingService::new(db.clone(), EventLog::new(db.clone()), config);
+
+    let block = |number: i64, version: &str| BlockData {
+        block: Block {
+            hash: format!("0xblock{}{}", number, version),
+            transaction_count: 1,
+            ..test_block(number)
+        },
+        transactions: vec![test_transaction(
+            &format!("0xtx{}{}", number, version),
+            number,
+        )],
+        ..test_block_data(number)
+    };
+    for number in [10, 20, 150, 230] {
+        db.store_block_data(&block(number, ""), 0)
+            .await
+            .expect("Failed to store block data");
+    }
+
+    // The head is 30 blocks into shard 200, so shards 0 and 100 leave the main database
+    sharding.run().await.expect("Failed to rotate shards");
+    assert_eq!(db.shard_starts(), vec![0, 100]);
+    assert_eq!(
+        db.get_lowest_block_number_in("blocks").await.unwrap(),
+        Some(230)
+    );
+
+    let block_hash = |number: i64| {
+        let db = db.clone();
+        async move {
+            db.get_block_by_number(number)
+                .await
+                .unwrap()
+                .map(|block| block.hash)
+        }
+    };
+    assert_eq!(block_hash(10).await.as_deref(), Some("0xblock10"));
+    assert_eq!(block_hash(150).await.as_deref(), Some("0xblock150"));
+    let transaction = db.get_transaction_by_hash("0xtx150").await.unwrap();
+    assert_eq!(transaction.map(|tx| tx.block_number), Some(150));
+
+    // Recent lists continue from the main database into the shards
+    let recent_blocks = || {
+        let db = db.clone();
+        async move {
+            db.get_blocks_before(i64::MAX, 10)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|block| block.number)
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(recent_blocks().await, vec![230, 150, 20, 10]);
+    let cursor = TransactionCursor {
+        block_number: i64::MAX,
+        transaction_index: i64::MAX,
+    };
+    let transactions: Vec<String> = db
+        .get_transactions_before(cursor, 3)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|tx| tx.hash)
+        .collect();
+    assert_eq!(transactions, vec!["0xtx230", "0xtx150", "0xtx20"]);
+
+    // A sharded block reindexed into the main database replaces its shard rows when moved
+    db.delete_replaced_block_data(20, "reindex")
+        .await
+        .expect("Failed to delete replaced block data");
+    db.store_block_data(&block(20, "b"), 0)
+        .await
+        .expect("Failed to store block data");
+    sharding.run().await.expect("Failed to rotate shards");
+
+    assert_eq!(
+        db.get_lowest_block_number_in("blocks").await.unwrap(),
+        Some(230)
+    );
+    assert_eq!(block_hash(20).await.as_deref(), Some("0xblock20b"));
+    assert!(db
+        .get_transaction_by_hash("0xtx20")
+        .await
+        .unwrap()
+        .is_none());
+    let transaction = db.get_transaction_by_hash("0xtx20b").await.unwrap();
+    assert_eq!(transaction.map(|tx| tx.block_number), Some(20));
+    assert_eq!(recent_blocks().await, vec![230, 150, 20, 10]);
+}
+
+#[tokio::test]
+async fn test_pruning_keeps_retention_windows_and_archives_rows() {
+    use eth_indexer_rs::{events::EventLog, pruning::PruningService};
+    use sqlx::SqlitePool;
+
+    let path = "./data/test_pruning.db";
+    let archive = "./data/test_pruning_archive.db";
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(archive);
+    let db = Arc::new(
+        DatabaseService::new(path)
+            .await
+            .expect("Failed to open database"),
+    );
+    let mut config = AppConfig::load().expect("Failed to load configuration from .env");
+    config.prune_logs_older_than = 5;
+    config.prune_token_transfers_older_than = 0;
+    config.prune_blocks_older_than = 8;
+    config.prune_batch_blocks = 2;
+    config.prune_archive_database = Some(archive.to_string());
+    config.prune_vacuum = true;
+    let pruning = PruningService::new(db.clone(), EventLog::new(db.clone()), config);
+
+    for number in 1..=10 {
+        let hash = format!("0xtx{}", number);
+        let data = BlockData {
+            block: Block {
+                transaction_count: 1,
+                ..test_block(number)
+            },
+            transactions: vec![test_transaction(&hash, number)],
+            logs: vec![Log {
+                id: None,
+                transaction_hash: hash.clone(),
+                block_number: number,
+                address: "0xtoken".to_string(),
+                topic0: None,
+                topic1: None,
+                topic2: None,
+                topic3: None,
+                data: None,
+                log_index: 0,
+            }],
+            token_transfers: vec![TokenTransfer {
+                id: None,
+                transaction_hash: hash,
+                block_number: number,
+                token_address: "0xtoken".to_string(),
+                from_address: "0xfrom".to_string(),
+                to_address: "0xto".to_string(),
+                amount: "1000".to_string(),
+                token_type: Some("ERC20".to_string()),
+                token_id: None,
+                log_index: Some(0),
+            }],
+            ..test_block_data(number)
+        };
+        db.store_block_data(&data, 0)
+            .await
+            .expect("Failed to store block data");
+    }
+
+    // Blocks 3..=10 and logs 6..=10 are kept; token transfers have no retention window
+    pruning.run().await.expect("Failed to prune");
+    assert_eq!(
+        db.get_lowest_block_number_in("blocks").await.unwrap(),
+        Some(3)
+    );
+    assert!(db.get_block_by_number(2).await.unwrap().is_none());
+    assert!(db.get_transaction_by_hash("0xtx2").await.unwrap().is_none());
+    assert!(db.get_transaction_by_hash("0xtx3").await.unwrap().is_some());
+    assert!(db.get_logs_by_block(5).await.unwrap().is_empty());
+    assert_eq!(db.get_logs_by_block(6).await.unwrap().len(), 1);
+    assert_eq!(db.get_token_transfers_by_block(3).await.unwrap().len(), 1);
+
+    // A second run finds nothing left to prune
+    pruning.run().await.expect("Failed to prune");
+
+    // Pruned rows were copied to the archive once
+    let archived = SqlitePool::connect(archive)
+        .await
+        .expect("Failed to open archive database");
+    let numbers = |query: &'static str| {
+        let archived = archived.clone();
+        async move {
+            sqlx::query_scalar::<_, i64>(query)
+                .fetch_all(&archived)
+                .await
+                .unwrap()
+        }
+    };
+    assert_eq!(
+        numbers("SELECT number FROM blocks ORDER BY number").await,
+        vec![1, 2]
+    );
+    assert_eq!(
+        numbers("SELECT block_number FROM transactions ORDER BY block_number").await,
+        vec![1, 2]
+    );
+    assert_eq!(
+        numbers("SELECT block_number FROM logs ORDER BY block_number").await,
+        vec![1, 2, 3, 4, 5]
+    );
+    assert_eq!(
+        numbers("SELECT block_number FROM token_transfers ORDER BY block_number").await,
+        vec![1, 2]
+    );
+}
+
+#[tokio::test]
+async fn test_storage_report_measures_tables_and_growth() {
+    let path = "./data/test_storage_stats.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let insert_events = |count: usize| {
+        let pool = db.pool.clone();
+        async move {
+            for _ in 0..count {
+                sqlx::query(
+                    "INSERT INTO indexer_events (kind, severity, message) VALUES ('test', 'info', ?)",
+                )
+                .bind("x".repeat(500))
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+    };
+    insert_events(10).await;
+    db.record_storage_snapshot("2026-01-01").await.unwrap();
+    insert_events(50).await;
+    // Sampling twice on a day keeps the latest measure
+    db.record_storage_snapshot("2026-01-11").await.unwrap();
+    db.record_storage_snapshot("2026-01-11").await.unwrap();
+    assert_eq!(
+        db.get_latest_storage_sample_date()
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("2026-01-11")
+    );
+
+    let report = storage_report(&db.get_storage_snapshots("2026-01-01").await.unwrap());
+    let events = report
+        .iter()
+        .find(|table| table.table_name == "indexer_events")
+        .expect("indexer_events not measured");
+    assert_eq!(events.row_count, 60);
+    assert!(events.table_bytes > 60 * 500);
+    assert!(events.index_bytes > 0);
+    assert_eq!(events.total_bytes, events.table_bytes + events.index_bytes);
+    let growth = events.growth.as_ref().expect("No growth over two samples");
+    assert_eq!((growth.since.as_str(), growth.days), ("2026-01-01", 10));
+    assert_eq!(growth.rows_per_day, 5.0);
+    assert!(growth.bytes_per_day > 0.0);
+    assert!(report
+        .windows(2)
+        .all(|pair| pair[0].total_bytes >= pair[1].total_bytes));
+
+    // A window holding only the latest sample has no growth
+    let report = storage_report(&db.get_storage_snapshots("2026-01-05").await.unwrap());
+    assert!(report.iter().all(|table| table.growth.is_none()));
+}
+
+#[tokio::test]
+async fn test_token_stats_count_holders_and_keep_supplies() {
+    let path = "./data/test_token_stats.db";
+    let _ = std::fs::remove_file(path);
+    let db = DatabaseService::new(path)
+        .await
+        .expect("Failed to open database");
+
+    let token = |address: &str, total_transfers: i64| Token {
+        address: address.to_string(),
+        name: None,
+        symbol: None,
+        decimals: Some(18),
+        token_type: "ERC20".to_string(),
+        first_seen_block: 1,
+        last_seen_block: 1,
+        total_transfers,
+        created_at: None,
+        updated_at: None,
+        name_source: None,
+        symbol_source: None,
+        decimals_source: None,
+        logo_uri: None,
+        curated: false,
+        total_supply: None,
+        total_supply_block: None,
+        holder_count: None,
+        recent_transfers: None,
+        token_type_confidence: None,
+    };
+    let balance = |account: &str, token_address: &str, balance: &str| TokenBalance {
+        id: None,
+        account_address: account.to_string(),
+        token_address: token_address.to_string(),
+        balance: balance.to_string(),
+        block_number: 100,
+        last_updated_block: 100,
+        created_at: None,
+        updated_at: None,
+    };
+    db.upsert_token(&token("0xbusy", 10)).await.unwrap();
+    db.upsert_token(&token("0xheld", 1)).await.unwrap();
+    db.upsert_token_balances_batch(&[
+        balance("0xa", "0xbusy", "5"),
+        balance("0xb", "0xbusy", "0"),
+        balance("0xa", "0xheld", "1"),
+        balance("0xb", "0xheld", "2"),
+    ])
+    .await
+    .unwrap();
+
+    // Holder counts and recent transfers are set once, then left alone while unchanged
+    assert_eq!(db.update_token_activity_stats(0).await.unwrap(), 4);
+    assert_eq!(db.update_token_activity_stats(0).await.unwrap(), 0);
+
+    // A failed totalSupply() read keeps the previous supply
+    db.update_token_total_supplies(&[("0xheld".to_string(), Some("3".to_string()))], 100)
+        .await
+        .unwrap();
+    db.update_token_total_supplies(&[("0xheld".to_string(), None)], 200)
+        .await
+        .unwrap();
+
+    let tokens = db.get_tokens(0, 10, TokenSort::Holders).await.unwrap();
+    let tokens: Vec<(&str, Option<i64>, Option<&str>, Option<i64>)> = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.address.as_str(),
+                token.holder_count,
+                token.total_supply.as_deref(),
+                token.total_supply_block,
+            )
+        })
+        .collect();
+    assert_eq!(
+        tokens,
+        vec![
+            ("0xheld", Some(2), Some("3"), Some(200)),
+            ("0xbusy", Some(1), None, None),
+        ]
+    );
+    assert_eq!(
+        db.get_tokens_for_supply_refresh(1).await.unwrap(),
+        vec!["0xbusy".to_string()]
+    );
+}
+
+#[test]
+fn test_token_standard_detection() {
+    // ERC-165 answers for ERC165, 0xffffffff, ERC1155 and ERC721
+    assert_eq!(
+        erc165_standard(&[Some(true), Some(false), Some(false), Some(true)]),
+        Some("ERC721")
+    );
+    assert_eq!(
+        erc165_standard(&[Some(true), Some(false), Some(true), Some(false)]),
+        Some("ERC1155")
+    );
+    // A contract answering true to everything doesn't implement ERC-165
+    assert_eq!(
+        erc165_standard(&[Some(true), Some(true), Some(true), Some(true)]),
+        None
+    );
+    assert_eq!(erc165_standard(&[None, None, None, None]), None);
+
+    // Dispatchers compare the calldata selector with PUSH4 constants
+    let dispatcher = |selectors: &[[u8; 4]]| -> Vec<u8> {
+        selectors
+            .iter()
+            .flat_map(|selector| {
+                [
+                    &[0x80, 0x63][..],
+                    &selector[..],
+                    &[0x14, 0x61, 0x00, 0x10, 0x57][..],
+                ]
+                .concat()
+            })
+            .collect()
+    };
+    let erc20 = dispatcher(&[
+        selector("transfer(address,uint256)"),
+        selector("allowance(address,address)"),
+    ]);
+    let erc721 = dispatcher(&[
+        selector("transfer(address,uint256)"),
+        selector("ownerOf(uint256)"),
+        selector("safeTransferFrom(address,address,uint256)"),
+    ]);
+    assert_eq!(bytecode_standard(&erc20), Some("ERC20"));
+    assert_eq!(bytecode_standard(&erc721), Some("ERC721"));
+    // Selectors not loaded by PUSH4 don't count
+    let push3: Vec<u8> = erc20
+        .iter()
+        .map(|byte| if *byte == 0x63 { 0x62 } else { *byte })
+        .collect();
+    assert_eq!(bytecode_standard(&push3), None);
+    assert_eq!(bytecode_standard(&[]), None);
+}
+
+#[test]
+fn test_verification_ignores_metadata_and_immutables() {
+    let code = |hex: &str| hex::decode(hex).unwrap();
+    let runtime = "6080604052348015600f57600080fd5b50";
+
+    // solc >= 0.6: {"ipfs": <multihash>, "solc": <version>}, then the length 0x0033
+    let ipfs_trailer = |hash: &str| format!("a264697066735822{}64736f6c63430008130033", hash);
+    let first = code(&format!(
+        "{}{}",
+        runtime,
+        ipfs_trailer("1220c0f1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f")
+    ));
+    let second = code(&format!(
+        "{}{}",
+        runtime,
+        ipfs_trailer("12207d6e5f4c3b2a190807f6e5d4c3b2a1908070f6e5d4c3b2a190807f6e5d4c3b2a")
+    ));
+    assert_eq!(strip_metadata(&first), code(runtime));
+    assert_eq!(strip_metadata(&first), strip_metadata(&second));
+
+    // solc 0.5: {"bzzr0": <swarm hash>}, then the length 0x0029
+    let bzzr0 = code(&format!(
+        "{}a165627a7a72305820{}0029",
+        runtime, "9f3b6b4c2e1d0a8f7e6d5c4b3a29180716253443526170819f8e7d6c5b4a3928"
+    ));
+    assert_eq!(strip_metadata(&bzzr0), code(runtime));
+
+    // Without a trailer, or when the length points outside the code, nothing is stripped
+    assert_eq!(strip_metadata(&code(runtime)), code(runtime));
+    assert_eq!(strip_metadata(&code("6080ffff")), code("6080ffff"));
+    assert_eq!(strip_metadata(&[0x33]), [0x33]);
+
+    // An immutable address is a PUSH32 the compiler leaves zeroed, filled in at deployment
+    let compiled = code(&format!(
+        "6080604052347f{}8152{}",
+        "00".repeat(32),
+        "00".repeat(4)
+    ));
+    let deployed = code(&format!(
+        "6080604052347f{}{}8152{}",
+        "00".repeat(12),
+        "a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+        "00".repeat(4)
+    ));
+    let references = serde_json::json!({
+        "7": [{ "start": 7, "length": 32 }],
+        "12": [{ "start": 4096, "length": 32 }],
+    });
+    assert_ne!(deployed, compiled);
+    assert_eq!(mask_immutables(deployed.clone(), &references), compiled);
+    assert_eq!(
+        mask_immutables(deployed.clone(), &serde_json::json!({})),
+        deployed
+    );
+}
+
+#[cfg(feature = "beacon")]
+#[test]
+fn test_slot_clock_places_blocks_by_timestamp() {
+    use eth_indexer_rs::beacon::client::SlotClock;
+
+    let mainnet = SlotClock {
+        genesis_time: 1606824023,
+        seconds_per_slot: 12,
+    };
+    // The merge block
+    assert_eq!(mainnet.slot_at(1663224179), Some(4700013));
+    assert_eq!(mainnet.slot_at(1606824023), Some(0));
+    assert_eq!(mainnet.slot_at(1606824022), None);
+
+    let gnosis = SlotClock {
+        genesis_time: 1638993340,
+        seconds_per_slot: 5,
+    };
+    assert_eq!(gnosis.slot_at(1638993340 + 5 * 42), Some(42));
+}
+
+#[test]
+fn test_headers_only_block_ranges() {
+    let closed = BlockRange::parse("100-199").unwrap();
+    let open = BlockRange::parse(" 500- ").unwrap();
+    assert_eq!(
+        closed,
+        BlockRange {
+            from: 100,
+            to: Some(199)
+        }
+    );
+    assert_eq!(
+        open,
+        BlockRange {
+            from: 500,
+            to: None
+        }
+    );
+    assert!(!closed.contains(99) && closed.contains(100) && closed.contains(199));
+    assert!(!closed.contains(200));
+    assert!(!open.contains(499) && open.contains(500) && open.contains(i64::MAX));
+
+    for invalid in ["", "100", "-100", "200-100", "a-b", "1-2-3"] {
+        assert_eq!(BlockRange::parse(invalid), None, "{:?}", invalid);
+    }
+}
+
+#[test]
+fn test_only_missing_state_errors_downgrade_archive_nodes() {
+    use eth_indexer_rs::rpc::is_missing_state_error;
+
+    let error = |message: &str| {
+        anyhow::anyhow!(message.to_string()).context("Failed to get balance for address")
+    };
+    for missing in [
+        "missing trie node 5d2c3a (path ) state 0x5d2c3a is not available",
+        "historical state not available in path scheme yet",
+        "Error: state is not available",
+    ] {
+        assert!(is_missing_state_error(&error(missing)), "{}", missing);
+    }
+    for transient in [
+        "operation timed out",
+        "429 Too Many Requests",
+        "header not found",
+        "request for pruned block body",
+    ] {
+        assert!(!is_missing_state_error(&error(transient)), "{}", transient);
+    }
+}
+
+#[test]
+fn test_unsupported_method_errors() {
+    use eth_indexer_rs::rpc::is_unsupported_method_error;
+
+    let error = |message: &str| {
+        anyhow::anyhow!(message.to_string()).context("Failed to get block receipts")
+    };
+    for unsupported in [
+        "(code: -32601, message: Method not found, data: None)",
+        "the method eth_getBlockReceipts does not exist/is not available",
+        "Unsupported method: debug_traceBlockByNumber",
+    ] {
+        assert!(
+            is_unsupported_method_error(&error(unsupported)),
+            "{}",
+            unsupported
+        );
+    }
+    for other in [
+        "execution reverted: token does not exist",
+        "block 123 does not exist",
+        "transaction type not supported",
+        "header not found",
+    ] {
+        assert!(!is_unsupported_method_error(&error(other)), "{}", other);
+    }
+}
+
+#[tokio::test]
+async fn test_rpc_executor_refuses_requests_when_queue_is_full() {
+    let (release, released) = tokio::sync::watch::channel(false);
+    let executor = RpcExecutor::new("Test".to_string(), 1, 0, 1, move |value: u64| {
+        let mut released = released.clone();
+        async move {
+            released.wait_for(|released| *released).await?;
+            Ok(value)
+        }
+    });
+
+    // The first request takes the only slot, the second waits in the queue
+    let running = executor.execute(1);
+    let queued = executor.execute(2);
+    tokio::pin!(running, queued);
+    for request in [&mut running, &mut queued] {
+        tokio::select! {
+            _ = request => panic!("Request completed before being released"),
+            _ = tokio::time::sleep(std::time::Duration::from_millis(50)) => {}
+        }
+    }
+    let refused = executor.execute(3).await.unwrap_err();
+    assert!(RpcOverloaded::is(&refused));
+    let stats = executor.queue_stats();
+    assert_eq!((stats.capacity, stats.depth, stats.rejected), (1, 1, 1));
+
+    release.send(true).unwrap();
+    assert_eq!(running.await.unwrap(), 1);
+    assert_eq!(queued.await.unwrap(), 2);
+    assert_eq!(executor.queue_stats().depth, 0);
+}
+
+#[tokio::test]
+async fn test_rpc_executor_lowers_limits_while_rate_limited() {
+    let rate_limits = Arc::new(RateLimitFeedback::new(Duration::from_millis(200)));
+    let transport = rate_limits.clone();
+    let executor = RpcExecutor::with_rate_limit_feedback(
+        "Test".to_string(),
+        4,
+        0,
+        10,
+        rate_limits,
+        move |rate_limited: bool| {
+            // Stands for a transport every provider of which answered 429
+            if rate_limited {
+                transport.report(None);
+            }
+            async move { Ok(rate_limited) }
+        },
+    );
+
+    executor.execute(true).await.unwrap();
+    executor.execute(false).await.unwrap();
+    let limits = executor.limits();
+    assert_eq!((limits.max_concurrent, limits.min_interval_ms), (2, 100));
+    let stats = executor.queue_stats();
+    assert_eq!(stats.rate_limited, 1);
+    assert!(stats.throttled_for_ms.is_some());
+
+    // Limits set by hand end the cooldown
+    executor.set_limits(RateLimits {
+        max_concurrent: 8,
+        min_interval_ms: 0,
+    });
+    assert!(executor.queue_stats().throttled_for_ms.is_none());
+
+    // A further rate limit lowers them again, until a cooldown passes without one
+    executor.execute(true).await.unwrap();
+    executor.execute(false).await.unwrap();
+    assert_eq!(executor.limits().max_concurrent, 4);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    executor.execute(false).await.unwrap();
+    let limits = executor.limits();
+    assert_eq!((limits.max_concurrent, limits.min_interval_ms), (8, 0));
+    assert!(executor.queue_stats().throttled_for_ms.is_none());
+}
+
 #[tokio::test]
 async fn test_api_endpoints() {
     unsafe {
@@ -175,6 +2581,7 @@ async fn test_rpc_connection_and_parsing() {
     }
 }
 
+#[cfg(feature = "beacon")]
 #[tokio::test]
 async fn test_beacon_connection_and_parsing() {
     unsafe {
@@ -193,7 +2600,7 @@ async fn test_beacon_connection_and_parsing() {
 
     let connection_result = beacon.test_connection().await;
     if connection_result.is_ok() {
-        let beacon_data_result = beacon.get_beacon_data_for_block(1000000).await;
+        let beacon_data_result = beacon.get_beacon_data_for_block(1000000, 1455404053).await;
         if beacon_data_result.is_ok() {
             let beacon_data = beacon_data_result.unwrap();
             assert!(beacon_data.is_object(), "Beacon data must be a JSON object");
@@ -255,10 +2662,14 @@ async fn test_full_integration_with_real_data() {
                     epoch: None,
                     slot_root: None,
                     parent_root: None,
-                    beacon_deposit_count: None,
+                    block_deposit_count: None,
+                    eth1_deposit_count: None,
                     graffiti: None,
                     randao_reveal: None,
                     randao_mix: None,
+                    sync_participation: None,
+                    attestation_count: None,
+                    slashing_count: None,
                 };
 
                 let save_result = db.insert_block(&block).await;
@@ -279,3 +2690,266 @@ async fn test_full_integration_with_real_data() {
         }
     }
 }
+
+/// JSON-RPC node serving a synthetic chain whose head moves one block every 100ms
+#[cfg(feature = "chaos")]
+mod chaos_node {
+    use ethers::types::{
+        Block as EthBlock, Log, Transaction, TransactionReceipt, H160, H256, U256, U64,
+    };
+    use serde_json::{json, Value};
+    use std::{net::SocketAddr, time::Instant};
+
+    pub const TRANSACTIONS_PER_BLOCK: u64 = 3;
+
+    fn block_hash(number: u64) -> H256 {
+        H256::from_low_u64_be(0xb10c_0000_0000 + number)
+    }
+
+    fn transaction_hash(number: u64, index: u64) -> H256 {
+        H256::from_low_u64_be(0x7a00_0000_0000 + number * 256 + index)
+    }
+
+    fn block(number: u64) -> EthBlock<Transaction> {
+        EthBlock {
+            hash: Some(block_hash(number)),
+            parent_hash: if number == 0 {
+                H256::zero()
+            } else {
+                block_hash(number - 1)
+            },
+            number: Some(U64::from(number)),
+            timestamp: U256::from(1_700_000_000 + number * 12),
+            gas_limit: U256::from(30_000_000),
+            gas_used: U256::from(21_000 * TRANSACTIONS_PER_BLOCK),
+            author: Some(H160::from_low_u64_be(0xfee)),
+            transactions: (0..TRANSACTIONS_PER_BLOCK)
+                .map(|index| Transaction {
+                    hash: transaction_hash(number, index),
+                    nonce: U256::from(number),
+                    block_hash: Some(block_hash(number)),
+                    block_number: Some(U64::from(number)),
+                    transaction_index: Some(U64::from(index)),
+                    from: H160::from_low_u64_be(0x1000 + index),
+                    to: Some(H160::from_low_u64_be(0x2000 + index)),
+                    value: U256::from(1_000 + index),
+                    gas_price: Some(U256::from(1_000_000_000u64)),
+                    gas: U256::from(21_000),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn receipts(number: u64) -> Vec<TransactionReceipt> {
+        (0..TRANSACTIONS_PER_BLOCK)
+            .map(|index| TransactionReceipt {
+                transaction_hash: transaction_hash(number, index),
+                transaction_index: U64::from(index),
+                block_hash: Some(block_hash(number)),
+                block_number: Some(U64::from(number)),
+                from: H160::from_low_u64_be(0x1000 + index),
+                to: Some(H160::from_low_u64_be(0x2000 + index)),
+                cumulative_gas_used: U256::from(21_000 * (index + 1)),
+                gas_used: Some(U256::from(21_000)),
+                status: Some(U64::one()),
+                logs: vec![Log {
+                    address: H160::from_low_u64_be(0x2000 + index),
+                    topics: vec![H256::from_low_u64_be(0x5eed)],
+                    block_hash: Some(block_hash(number)),
+                    block_number: Some(U64::from(number)),
+                    transaction_hash: Some(transaction_hash(number, index)),
+                    transaction_index: Some(U64::from(index)),
+                    log_index: Some(U256::from(index)),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Result of a request while the head is at `head`
+    fn result(head: u64, method: &str, params: &Value) -> Result<Value, (i64, &'static str)> {
+        let number = || {
+            params[0]
+                .as_str()
+                .and_then(|tag| u64::from_str_radix(tag.trim_start_matches("0x"), 16).ok())
+                .filter(|number| *number <= head)
+        };
+        Ok(match method {
+            "eth_blockNumber" => json!(format!("{:#x}", head)),
+            "eth_chainId" => json!("0x1"),
+            "eth_getBalance" => json!("0x1"),
+            "eth_getCode" => json!("0x"),
+            "eth_getBlockByNumber" => json!(number().map(block)),
+            "eth_getBlockReceipts" => json!(number().map(receipts)),
+            "eth_getTransactionReceipt" => {
+                let hash = params[0].as_str().unwrap_or_default();
+                json!((0..=head)
+                    .flat_map(receipts)
+                    .find(|receipt| format!("{:?}", receipt.transaction_hash) == hash))
+            }
+            _ => return Err((-32601, "method not found")),
+        })
+    }
+
+    /// Start serving the chain, returns the node address
+    pub fn serve() -> SocketAddr {
+        let started = Instant::now();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let router = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(request): axum::Json<Value>| async move {
+                let head = (started.elapsed().as_millis() / 100) as u64;
+                let method = request["method"].as_str().unwrap_or_default();
+                axum::Json(match result(head, method, &request["params"]) {
+                    Ok(result) => {
+                        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+                    }
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "error": { "code": code, "message": message }
+                    }),
+                })
+            }),
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router.into_make_service()),
+        );
+
+        address
+    }
+}
+
+#[cfg(feature = "chaos")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_indexer_recovers_from_injected_faults() {
+    use eth_indexer_rs::{
+        chaos::{FaultInjector, FaultRates},
+        events::EventLog,
+        indexer::IndexerService,
+        live::LiveDispatcher,
+        rpc::RpcClient,
+    };
+    use std::time::Instant;
+
+    const LAST_BLOCK: i64 = 24;
+
+    let path = "./data/test_chaos.db";
+    let _ = std::fs::remove_file(path);
+    let mut config = AppConfig::load().expect("Failed to load configuration from .env");
+    config.eth_rpc_url = format!("http://{}", chaos_node::serve());
+    config.start_block = Some(0);
+    config.worker_pool_size = 4;
+    config.max_concurrent_blocks = 4;
+    config.block_queue_size_multiplier = 2;
+    config.block_fetch_interval_seconds = Some(1);
+    config.consistency_check_interval_seconds = 1;
+    config.eth_rpc_min_interval_ms = 0;
+    config.multicall_address = None;
+    config.tracked_addresses = Vec::new();
+    config.trace_contract_creations = false;
+
+    // Faults are injected once the indexer is connected and stored its first block
+    let faults = Arc::new(FaultInjector::new(FaultRates::default(), 42));
+    let db = Arc::new(
+        DatabaseService::new(path)
+            .await
+            .expect("Failed to open database")
+            .with_fault_injector(faults.clone()),
+    );
+    let events = EventLog::new(db.clone());
+    let rpc = Arc::new(
+        RpcClient::new(&config.eth_rpc_url, config.clone(), events.clone())
+            .expect("Failed to create RPC client")
+            .with_fault_injector(faults.clone()),
+    );
+    let indexer = Arc::new(IndexerService::new(
+        db.clone(),
+        rpc,
+        config,
+        events,
+        LiveDispatcher::new(),
+    ));
+    tokio::spawn({
+        let indexer = indexer.clone();
+        async move { indexer.start_service().await }
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(120);
+    while db.get_block_number_range().await.unwrap().is_none() {
+        assert!(Instant::now() < deadline, "No block was indexed");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    faults.set_rates(FaultRates {
+        rpc_error: 0.1,
+        rpc_delay: 0.2,
+        rpc_delay_duration: Duration::from_millis(50),
+        malformed_receipt: 0.2,
+        db_write_failure: 0.2,
+    });
+
+    // Failed blocks leave gaps and dropped receipts leave blocks short of transactions,
+    // both repaired by the consistency checker
+    let expected = chaos_node::TRANSACTIONS_PER_BLOCK as i64;
+    loop {
+        let counts = db
+            .get_block_transaction_counts(0, LAST_BLOCK)
+            .await
+            .unwrap();
+        if counts.len() == LAST_BLOCK as usize + 1
+            && counts
+                .iter()
+                .all(|(_, declared, stored)| *declared == expected && *stored == expected)
+        {
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "Blocks 0..={} not repaired: {:?}, faults injected: {:?}",
+            LAST_BLOCK,
+            counts,
+            faults.injected()
+        );
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    indexer.stop();
+
+    let injected = faults.injected();
+    assert!(injected.rpc_errors > 0, "{:?}", injected);
+    assert!(injected.rpc_delays > 0, "{:?}", injected);
+    assert!(injected.malformed_receipts > 0, "{:?}", injected);
+    assert!(injected.db_write_failures > 0, "{:?}", injected);
+    assert!(indexer.consistency_report().missing_blocks > 0);
+
+    // Every block kept all of its rows, and no row outlived a failed write of its block
+    let (logs, receipts): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT (SELECT COUNT(*) FROM logs WHERE block_number <= ?),
+               (SELECT COUNT(*) FROM transaction_receipts WHERE block_number <= ?)
+        "#,
+    )
+    .bind(LAST_BLOCK)
+    .bind(LAST_BLOCK)
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(logs, (LAST_BLOCK + 1) * expected);
+    assert_eq!(receipts, (LAST_BLOCK + 1) * expected);
+    let orphans: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM transactions
+        WHERE block_number NOT IN (SELECT number FROM blocks)
+        "#,
+    )
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(orphans, 0);
+}